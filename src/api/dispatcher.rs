@@ -4,27 +4,53 @@ use crate::{
     AppState,
     api::{
         methods::{
+            admin::{broadcast, get_audit_log, get_ws_stats},
             auth::{authenticate_user, logout_user, refresh_user_token},
             canvas::{
                 cancel_publish_canvas, confirm_publish_canvas, create_canvas, delete_canvas,
-                get_canvas, join_canvas, list_canvas, publish_canvas,
+                get_canvas, get_canvas_presence, get_canvas_timelapse, join_canvas, list_canvas,
+                mute_collaborator, publish_canvas, set_bid_rules, set_cooldown_exemption,
+                set_pixel_quota,
             },
             nft::{
-                announce_mint_countdown, cancel_mint, cancel_mint_countdown, confirm_mint, mint,
-                prepare_metadata,
+                announce_mint_countdown, burn, cancel_mint, cancel_mint_countdown, confirm_burn,
+                confirm_edition_mint, confirm_metadata_update, confirm_mint, generate_timelapse,
+                get_lineage, get_market_info, get_mint_status, get_payout_status,
+                get_royalty_payouts, mint, mint_edition, prepare_metadata, preview,
+                update_metadata, verify_collection, verify_consistency,
             },
-            pixel::{cancel_pixel_bid, confirm_pixel_bid, paint_pixel, place_pixel_bid},
+            pixel::{
+                cancel_pixel_bid, confirm_pixel_bid, get_pixel_bid_history, list_owned_pixels,
+                paint_pixel, paint_pixel_batch, place_pixel_bid, prepare_paint, unwatch_pixel,
+                watch_pixel,
+            },
+            solana::{
+                claim_escrow, confirm_escrow_claim, get_wallet_balance, simulate_transaction,
+            },
+            stats::get_leaderboards,
         },
         types::{
-            AnnounceMintParams, AuthOperation, AuthParams, CancelMintCountdownParams,
-            CancelMintParams, CancelPixelBidParams, CancelPublishCanvasParams,
-            ConfirmNftMintParams, ConfirmPixelBidParams, ConfirmPublishCanvasParams,
-            CreateCanvasParams, DeleteCanvasParams, GetCanvasParams, JoinCanvasParams,
-            ListCanvasParams, MintNftParams, PaintPixelParams, PlacePixelBidParams,
-            PrepareMetadataParams, PublishCanvasParams, SessionParams,
+            AnnounceMintParams, AuditLogParams, AuthOperation, AuthParams, BroadcastParams,
+            BurnNftParams, CancelMintCountdownParams, CancelMintParams, CancelPixelBidParams,
+            CancelPublishCanvasParams, CanvasPresenceParams, CanvasTimelapseParams,
+            ClaimEscrowParams, ConfirmBurnParams, ConfirmEditionMintParams,
+            ConfirmEscrowClaimParams, ConfirmMetadataUpdateParams, ConfirmNftMintParams,
+            ConfirmPixelBidParams, ConfirmPublishCanvasParams, CreateCanvasParams,
+            DeleteCanvasParams, GenerateTimelapseParams, GetCanvasParams, JoinCanvasParams,
+            LeaderboardsParams, LineageParams, ListCanvasParams, ListOwnedPixelsParams,
+            MarketInfoParams, MintEditionParams, MintNftParams, MintStatusParams,
+            MuteCollaboratorParams, PaintPixelBatchParams, PaintPixelParams, PayoutStatusParams,
+            PixelBidHistoryParams, PlacePixelBidParams, PrepareMetadataParams, PreparePaintParams,
+            PreviewNftParams, PublishCanvasParams, RoyaltyPayoutsParams, SessionParams,
+            SetBidRulesParams, SetCooldownExemptionParams, SetPixelQuotaParams,
+            SimulateTransactionParams, UnwatchPixelParams, UpdateMetadataParams,
+            VerifyCollectionParams, VerifyConsistencyParams, WalletBalanceParams, WatchPixelParams,
+            WsStatsParams,
         },
     },
     error::AppError,
+    infrastructure::cache::breaker::or_degraded,
+    middleware::audit::{self, AuditRecorder},
 };
 
 macro_rules! dispatch {
@@ -76,6 +102,9 @@ async fn dispatch_canvas(method: &str, params: Value, state: AppState) -> Result
         "canvas.create" => dispatch!(CreateCanvasParams, create_canvas, params, state),
         "canvas.list" => dispatch!(ListCanvasParams, list_canvas, params, state),
         "canvas.get" => dispatch!(GetCanvasParams, get_canvas, params, state),
+        "canvas.presence" => {
+            dispatch!(CanvasPresenceParams, get_canvas_presence, params, state)
+        }
         "canvas.join" => dispatch!(JoinCanvasParams, join_canvas, params, state),
         "canvas.publish" => dispatch!(PublishCanvasParams, publish_canvas, params, state),
         "canvas.confirmPublish" => {
@@ -95,6 +124,22 @@ async fn dispatch_canvas(method: &str, params: Value, state: AppState) -> Result
             )
         }
         "canvas.delete" => dispatch!(DeleteCanvasParams, delete_canvas, params, state),
+        "canvas.timelapse" => {
+            dispatch!(CanvasTimelapseParams, get_canvas_timelapse, params, state)
+        }
+        "canvas.setBidRules" => dispatch!(SetBidRulesParams, set_bid_rules, params, state),
+        "canvas.setCooldownExemption" => {
+            dispatch!(
+                SetCooldownExemptionParams,
+                set_cooldown_exemption,
+                params,
+                state
+            )
+        }
+        "canvas.setPixelQuota" => {
+            dispatch!(SetPixelQuotaParams, set_pixel_quota, params, state)
+        }
+        "canvas.mute" => dispatch!(MuteCollaboratorParams, mute_collaborator, params, state),
         _ => Err(AppError::MethodNotFound(method.to_string())),
     }
 }
@@ -104,7 +149,15 @@ async fn dispatch_pixel(method: &str, params: Value, state: AppState) -> Result<
         "pixel.place" => dispatch!(PlacePixelBidParams, place_pixel_bid, params, state),
         "pixel.confirm" => dispatch!(ConfirmPixelBidParams, confirm_pixel_bid, params, state),
         "pixel.paint" => dispatch!(PaintPixelParams, paint_pixel, params, state),
+        "pixel.paintBatch" => dispatch!(PaintPixelBatchParams, paint_pixel_batch, params, state),
+        "pixel.preparePaint" => dispatch!(PreparePaintParams, prepare_paint, params, state),
         "pixel.cancel" => dispatch!(CancelPixelBidParams, cancel_pixel_bid, params, state),
+        "pixel.mine" => dispatch!(ListOwnedPixelsParams, list_owned_pixels, params, state),
+        "pixel.watch" => dispatch!(WatchPixelParams, watch_pixel, params, state),
+        "pixel.unwatch" => dispatch!(UnwatchPixelParams, unwatch_pixel, params, state),
+        "pixel.bidHistory" => {
+            dispatch!(PixelBidHistoryParams, get_pixel_bid_history, params, state)
+        }
         _ => Err(AppError::MethodNotFound(method.to_string())),
     }
 }
@@ -126,6 +179,79 @@ async fn dispatch_nft(method: &str, params: Value, state: AppState) -> Result<Va
         "nft.prepareMetadata" => {
             dispatch!(PrepareMetadataParams, prepare_metadata, params, state)
         }
+        "nft.lineage" => dispatch!(LineageParams, get_lineage, params, state),
+        "nft.preview" => dispatch!(PreviewNftParams, preview, params, state),
+        "nft.generateTimelapse" => {
+            dispatch!(GenerateTimelapseParams, generate_timelapse, params, state)
+        }
+        "nft.verifyCollection" => {
+            dispatch!(VerifyCollectionParams, verify_collection, params, state)
+        }
+        "nft.mintEdition" => dispatch!(MintEditionParams, mint_edition, params, state),
+        "nft.confirmEdition" => {
+            dispatch!(
+                ConfirmEditionMintParams,
+                confirm_edition_mint,
+                params,
+                state
+            )
+        }
+        "nft.status" => dispatch!(MintStatusParams, get_mint_status, params, state),
+        "nft.marketInfo" => dispatch!(MarketInfoParams, get_market_info, params, state),
+        "nft.verify" => dispatch!(VerifyConsistencyParams, verify_consistency, params, state),
+        "nft.payouts" => dispatch!(RoyaltyPayoutsParams, get_royalty_payouts, params, state),
+        "nft.payoutStatus" => dispatch!(PayoutStatusParams, get_payout_status, params, state),
+        "nft.burn" => dispatch!(BurnNftParams, burn, params, state),
+        "nft.confirmBurn" => dispatch!(ConfirmBurnParams, confirm_burn, params, state),
+        "nft.updateMetadata" => dispatch!(UpdateMetadataParams, update_metadata, params, state),
+        "nft.confirmMetadataUpdate" => {
+            dispatch!(
+                ConfirmMetadataUpdateParams,
+                confirm_metadata_update,
+                params,
+                state
+            )
+        }
+        _ => Err(AppError::MethodNotFound(method.to_string())),
+    }
+}
+
+async fn dispatch_solana(method: &str, params: Value, state: AppState) -> Result<Value, AppError> {
+    match method {
+        "solana.claimEscrow" => dispatch!(ClaimEscrowParams, claim_escrow, params, state),
+        "solana.confirmClaim" => {
+            dispatch!(
+                ConfirmEscrowClaimParams,
+                confirm_escrow_claim,
+                params,
+                state
+            )
+        }
+        "solana.balance" => dispatch!(WalletBalanceParams, get_wallet_balance, params, state),
+        "solana.simulate" => {
+            dispatch!(
+                SimulateTransactionParams,
+                simulate_transaction,
+                params,
+                state
+            )
+        }
+        _ => Err(AppError::MethodNotFound(method.to_string())),
+    }
+}
+
+async fn dispatch_stats(method: &str, params: Value, state: AppState) -> Result<Value, AppError> {
+    match method {
+        "stats.leaderboards" => dispatch!(LeaderboardsParams, get_leaderboards, params, state),
+        _ => Err(AppError::MethodNotFound(method.to_string())),
+    }
+}
+
+async fn dispatch_admin(method: &str, params: Value, state: AppState) -> Result<Value, AppError> {
+    match method {
+        "admin.wsStats" => dispatch!(WsStatsParams, get_ws_stats, params, state),
+        "admin.broadcast" => dispatch!(BroadcastParams, broadcast, params, state),
+        "admin.audit" => dispatch!(AuditLogParams, get_audit_log, params, state),
         _ => Err(AppError::MethodNotFound(method.to_string())),
     }
 }
@@ -138,24 +264,53 @@ pub async fn dispatch_method(
 ) -> Result<Value, AppError> {
     let limiter = match method {
         "auth.login" | "auth.register" | "auth.refresh" => Some(&state.rate_limiters.auth),
-        "pixel.place" | "pixel.paint" => Some(&state.rate_limiters.pixel),
-        "canvas.create" | "canvas.join" | "canvas.delete" => Some(&state.rate_limiters.canvas),
+        "pixel.place" | "pixel.paint" | "pixel.paintBatch" | "pixel.preparePaint" => {
+            Some(&state.rate_limiters.pixel)
+        }
+        "canvas.create" | "canvas.join" | "canvas.delete" | "nft.generateTimelapse" => {
+            Some(&state.rate_limiters.canvas)
+        }
         "canvas.publish"
         | "canvas.confirmPublish"
         | "nft.announceMint"
         | "nft.mint"
         | "nft.confirmMint"
-        | "nft.prepareMetadata" => Some(&state.rate_limiters.solana),
+        | "nft.prepareMetadata"
+        | "nft.verifyCollection"
+        | "nft.marketInfo"
+        | "nft.verify"
+        | "nft.mintEdition"
+        | "nft.confirmEdition"
+        | "nft.updateMetadata"
+        | "nft.confirmMetadataUpdate"
+        | "nft.burn"
+        | "nft.confirmBurn"
+        | "solana.claimEscrow"
+        | "solana.confirmClaim"
+        | "solana.simulate"
+        | "solana.balance" => Some(&state.rate_limiters.solana),
         _ => None,
     };
 
     if let Some(limiter) = limiter {
-        let (allowed, _, _) = limiter.check(client_key).await?;
+        // Requests are let through unthrottled while Redis is unavailable
+        let (allowed, _, _) = or_degraded(limiter.check(client_key).await, (true, 0, 0))?;
         if !allowed {
             return Err(AppError::RateLimitExceeded);
         }
     }
 
+    if !audit::is_audited(method) {
+        return route_method(method, params, state).await;
+    }
+
+    let recorder = AuditRecorder::start(&state, method, &params);
+    let result = route_method(method, params, state.clone()).await;
+    recorder.finish(&state, &result);
+    result
+}
+
+async fn route_method(method: &str, params: Value, state: AppState) -> Result<Value, AppError> {
     if method.starts_with("auth.") {
         return dispatch_auth(method, params, state).await;
     }
@@ -168,5 +323,14 @@ pub async fn dispatch_method(
     if method.starts_with("nft.") {
         return dispatch_nft(method, params, state).await;
     }
+    if method.starts_with("solana.") {
+        return dispatch_solana(method, params, state).await;
+    }
+    if method.starts_with("stats.") {
+        return dispatch_stats(method, params, state).await;
+    }
+    if method.starts_with("admin.") {
+        return dispatch_admin(method, params, state).await;
+    }
     Err(AppError::MethodNotFound(method.to_string()))
 }
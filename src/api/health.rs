@@ -0,0 +1,68 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+
+use crate::AppState;
+
+#[derive(Serialize)]
+struct Readiness {
+    ready: bool,
+    database: DatabaseHealth,
+    /// Informational only: the server keeps answering without Redis, just degraded.
+    redis_available: bool,
+}
+
+#[derive(Serialize)]
+struct DatabaseHealth {
+    ok: bool,
+    latency_ms: Option<u64>,
+    error: Option<String>,
+}
+
+/// The process is up. Says nothing about its dependencies.
+async fn live() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Whether this instance can serve requests, which it cannot while the database is
+/// unreachable.
+async fn ready(State(state): State<AppState>) -> Response {
+    let database = match state.db.health().await {
+        Ok(latency) => DatabaseHealth {
+            ok: true,
+            latency_ms: Some(latency.as_millis() as u64),
+            error: None,
+        },
+        Err(e) => {
+            tracing::warn!("Readiness check failed: {e}");
+            DatabaseHealth {
+                ok: false,
+                latency_ms: None,
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    let status = if database.ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = Readiness {
+        ready: database.ok,
+        database,
+        redis_available: state.cache.redis.is_available(),
+    };
+    (status, Json(body)).into_response()
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/live", get(live))
+        .route("/ready", get(ready))
+}
@@ -0,0 +1,134 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    api::{
+        methods::resolve_limit,
+        types::{
+            AuditLogEntry, AuditLogParams, AuditLogResponse, BroadcastParams, BroadcastResponse,
+            WsStatsParams,
+        },
+    },
+    error::{AppError, Result},
+    infrastructure::db::repositories::{
+        AuditLogRepository, CanvasRepository, audit_log::AuditLogFilter,
+    },
+    services::auth::{TokenType, is_admin},
+    ws::types::{RoomCanvasUpdate, WsStats},
+};
+
+/// Longest system announcement, in characters.
+const MAX_BROADCAST_LENGTH: usize = 500;
+
+pub async fn get_ws_stats(params: WsStatsParams) -> Result<WsStats> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+    require_admin(&app_state, user_id).await?;
+
+    Ok(app_state.ws_rooms.stats().await)
+}
+
+pub async fn broadcast(params: BroadcastParams) -> Result<BroadcastResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+    require_admin(&app_state, user_id).await?;
+
+    let message = params.message.trim();
+    if message.is_empty() {
+        return Err(AppError::InvalidParams("Message is empty".into()));
+    }
+    if message.chars().count() > MAX_BROADCAST_LENGTH {
+        return Err(AppError::InvalidParams(format!(
+            "Message is longer than {MAX_BROADCAST_LENGTH} characters"
+        )));
+    }
+
+    if let Some(canvas_id) = params.canvas_id {
+        CanvasRepository::find_canvas_by_id(app_state.db.get_connection(), canvas_id)
+            .await?
+            .ok_or(AppError::CanvasNotFound)?;
+    }
+
+    let update = RoomCanvasUpdate::System {
+        level: params.level,
+        message: message.to_string(),
+        sent_at: Utc::now(),
+    };
+    let rooms = app_state
+        .ws_rooms
+        .announce(params.canvas_id.as_ref(), update)
+        .await;
+    tracing::info!("Admin {user_id} announced to {rooms} local rooms: {message}");
+
+    Ok(BroadcastResponse { rooms })
+}
+
+pub async fn get_audit_log(params: AuditLogParams) -> Result<AuditLogResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+    require_admin(&app_state, user_id).await?;
+
+    if let (Some(from), Some(to)) = (params.from, params.to)
+        && from >= to
+    {
+        return Err(AppError::InvalidParams("from must be before to".into()));
+    }
+
+    let filter = AuditLogFilter {
+        method: params.method,
+        actor_id: params.actor_id,
+        from: params.from,
+        to: params.to,
+    };
+
+    let page = AuditLogRepository::find_entries(
+        app_state.db.get_connection(),
+        &filter,
+        params.cursor.as_deref(),
+        resolve_limit(params.limit),
+    )
+    .await?;
+
+    Ok(AuditLogResponse {
+        entries: page
+            .items
+            .into_iter()
+            .map(|entry| AuditLogEntry {
+                id: entry.id,
+                method: entry.method,
+                actor_id: entry.actor_id,
+                params_hash: entry.params_hash,
+                result_code: entry.result_code,
+                latency_ms: entry.latency_ms,
+                created_at: entry.created_at,
+            })
+            .collect(),
+        next_cursor: page.next_cursor,
+    })
+}
+
+async fn require_admin(state: &AppState, user_id: Uuid) -> Result<()> {
+    if !is_admin(&state.db, user_id).await? {
+        return Err(AppError::NotAdmin);
+    }
+    Ok(())
+}
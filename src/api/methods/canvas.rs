@@ -1,13 +1,25 @@
 use crate::{
-    api::types::{
-        CancelPublishCanvasParams, CanvasResponse, CanvasWithPixelsResponse,
-        ConfirmPublishCanvasParams, ConfirmPublishCanvasResponse, CreateCanvasParams,
-        DeleteCanvasParams, GetCanvasParams, JoinCanvasParams, JoinCanvasResponse,
-        ListCanvasParams, ListCanvasResponse, OwnedPixelInfo, PublishCanvasParams,
-        PublishCanvasResponse, StateChangeResponse, SuccessResponse,
+    api::{
+        methods::resolve_limit,
+        types::{
+            CancelPublishCanvasParams, CanvasPresenceParams, CanvasPresenceResponse,
+            CanvasResponse, CanvasTimelapseParams, CanvasTimelapseResponse,
+            CanvasWithPixelsResponse, ConfirmPublishCanvasParams, ConfirmPublishCanvasResponse,
+            CreateCanvasParams, DeleteCanvasParams, GetCanvasParams, JoinCanvasParams,
+            JoinCanvasResponse, ListCanvasParams, ListCanvasResponse, MuteCollaboratorParams,
+            MuteCollaboratorResponse, OwnedPixelInfo, PixelCoords, PublishCanvasParams,
+            PublishCanvasResponse, SetBidRulesParams, SetCooldownExemptionParams,
+            SetPixelQuotaParams, StateChangeResponse, SuccessResponse, TimelapseFrameInfo,
+        },
     },
     error::{AppError, Result},
-    services::{auth::TokenType, canvas as canvas_service},
+    services::{
+        auth::TokenType,
+        canvas::{
+            self as canvas_service, DEFAULT_TIMELAPSE_BUCKET_SECS, DEFAULT_TIMELAPSE_MAX_FRAMES,
+            types::{BidRulesUpdate, TimelapseRequest},
+        },
+    },
 };
 
 pub async fn create_canvas(params: CreateCanvasParams) -> Result<CanvasResponse> {
@@ -21,18 +33,17 @@ pub async fn create_canvas(params: CreateCanvasParams) -> Result<CanvasResponse>
         .sub;
 
     let initial_color = params.initial_color.unwrap_or(0);
-    let canvas =
-        canvas_service::create_canvas(&app_state, user_id, &params.name, initial_color).await?;
-
-    Ok(CanvasResponse {
-        id: canvas.id.to_string(),
-        name: canvas.name,
-        invite_code: canvas.invite_code,
-        state: format!("{:?}", canvas.state).to_lowercase(),
-        owner_id: canvas.owner_id.to_string(),
-        canvas_pda: canvas.canvas_pda,
-        mint_address: canvas.mint_address,
-    })
+    let canvas = canvas_service::create_canvas(
+        &app_state,
+        user_id,
+        &params.name,
+        initial_color,
+        params.palette.as_deref(),
+        params.parent_canvas_id,
+    )
+    .await?;
+
+    Ok(CanvasResponse::from(canvas))
 }
 
 pub async fn get_canvas(params: GetCanvasParams) -> Result<CanvasWithPixelsResponse> {
@@ -48,15 +59,8 @@ pub async fn get_canvas(params: GetCanvasParams) -> Result<CanvasWithPixelsRespo
     let result = canvas_service::get_canvas(&app_state, params.canvas_id, user_id).await?;
 
     Ok(CanvasWithPixelsResponse {
-        canvas: CanvasResponse {
-            id: result.canvas.id.to_string(),
-            name: result.canvas.name,
-            invite_code: result.canvas.invite_code,
-            state: format!("{:?}", result.canvas.state).to_lowercase(),
-            owner_id: result.canvas.owner_id.to_string(),
-            canvas_pda: result.canvas.canvas_pda,
-            mint_address: result.canvas.mint_address,
-        },
+        canvas: CanvasResponse::from(result.canvas),
+        palette: result.palette,
         pixel_colors: result.pixel_colors,
         owned_pixels: result
             .owned_pixels
@@ -71,6 +75,24 @@ pub async fn get_canvas(params: GetCanvasParams) -> Result<CanvasWithPixelsRespo
     })
 }
 
+pub async fn get_canvas_presence(params: CanvasPresenceParams) -> Result<CanvasPresenceResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let users = canvas_service::get_presence(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(CanvasPresenceResponse {
+        canvas_id: params.canvas_id,
+        users,
+    })
+}
+
 pub async fn list_canvas(params: ListCanvasParams) -> Result<ListCanvasResponse> {
     let app_state = params.state.ok_or(AppError::InternalServerError(
         "Failed to get app state".to_string(),
@@ -81,35 +103,28 @@ pub async fn list_canvas(params: ListCanvasParams) -> Result<ListCanvasResponse>
         .validate_token(&params.access_token, TokenType::Access)?
         .sub;
 
-    let canvases = canvas_service::list_canvases_by_user(&app_state, user_id).await?;
+    let canvases = canvas_service::list_canvases_by_user(
+        &app_state,
+        user_id,
+        params.owned_cursor.as_deref(),
+        params.collaborating_cursor.as_deref(),
+        resolve_limit(params.limit),
+    )
+    .await?;
 
     Ok(ListCanvasResponse {
         owned: canvases
             .owned
             .into_iter()
-            .map(|c| CanvasResponse {
-                id: c.id.to_string(),
-                name: c.name,
-                invite_code: c.invite_code,
-                state: format!("{:?}", c.state).to_lowercase(),
-                owner_id: c.owner_id.to_string(),
-                canvas_pda: c.canvas_pda,
-                mint_address: c.mint_address,
-            })
+            .map(CanvasResponse::from)
             .collect(),
+        next_owned_cursor: canvases.next_owned_cursor,
         collaborating: canvases
             .collaborating
             .into_iter()
-            .map(|c| CanvasResponse {
-                id: c.id.to_string(),
-                name: c.name,
-                invite_code: c.invite_code,
-                state: format!("{:?}", c.state).to_lowercase(),
-                owner_id: c.owner_id.to_string(),
-                canvas_pda: c.canvas_pda,
-                mint_address: c.mint_address,
-            })
+            .map(CanvasResponse::from)
             .collect(),
+        next_collaborating_cursor: canvases.next_collaborating_cursor,
     })
 }
 
@@ -147,7 +162,10 @@ pub async fn publish_canvas(params: PublishCanvasParams) -> Result<PublishCanvas
     Ok(PublishCanvasResponse {
         success: true,
         state: "publishing".to_string(),
+        program_id: publish_info.program_id,
         pixel_colors_packed: publish_info.pixel_colors_packed,
+        transaction: publish_info.transaction,
+        estimated_cost: publish_info.estimated_cost,
     })
 }
 
@@ -214,3 +232,149 @@ pub async fn delete_canvas(params: DeleteCanvasParams) -> Result<SuccessResponse
 
     Ok(SuccessResponse::ok())
 }
+
+pub async fn set_pixel_quota(params: SetPixelQuotaParams) -> Result<CanvasResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let quota = match (params.pixel_quota, params.window_secs) {
+        (Some(limit), Some(window_secs)) => Some((limit, window_secs)),
+        (None, None) => None,
+        _ => {
+            return Err(AppError::InvalidParams(
+                "pixel_quota and window_secs must be set together".into(),
+            ));
+        }
+    };
+
+    let canvas =
+        canvas_service::set_pixel_quota(&app_state, params.canvas_id, user_id, quota).await?;
+
+    Ok(CanvasResponse::from(canvas))
+}
+
+pub async fn get_canvas_timelapse(
+    params: CanvasTimelapseParams,
+) -> Result<CanvasTimelapseResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let timelapse = canvas_service::get_canvas_timelapse(
+        &app_state,
+        TimelapseRequest {
+            canvas_id: params.canvas_id,
+            user_id,
+            from: params.from,
+            to: params.to,
+            cursor: params.cursor,
+            bucket_secs: params.bucket_secs.unwrap_or(DEFAULT_TIMELAPSE_BUCKET_SECS),
+            max_frames: params.max_frames.unwrap_or(DEFAULT_TIMELAPSE_MAX_FRAMES),
+        },
+    )
+    .await?;
+
+    Ok(CanvasTimelapseResponse {
+        base_colors: timelapse.base_colors,
+        frames: timelapse
+            .frames
+            .into_iter()
+            .map(|frame| TimelapseFrameInfo {
+                timestamp: frame.timestamp,
+                changes: frame
+                    .changes
+                    .into_iter()
+                    .map(|change| PixelCoords {
+                        x: change.x,
+                        y: change.y,
+                        color: change.color,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        total_changes: timelapse.total_changes,
+        next_cursor: timelapse.next_cursor,
+    })
+}
+
+pub async fn set_bid_rules(params: SetBidRulesParams) -> Result<CanvasResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let canvas = canvas_service::set_bid_rules(
+        &app_state,
+        params.canvas_id,
+        user_id,
+        BidRulesUpdate {
+            floor_lamports: params.floor_lamports,
+            step_lamports: params.step_lamports,
+            ceiling_lamports: params.ceiling_lamports,
+        },
+    )
+    .await?;
+
+    Ok(CanvasResponse::from(canvas))
+}
+
+pub async fn set_cooldown_exemption(params: SetCooldownExemptionParams) -> Result<CanvasResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let canvas = canvas_service::set_cooldown_exemption(
+        &app_state,
+        params.canvas_id,
+        user_id,
+        params.owner_exempt,
+    )
+    .await?;
+
+    Ok(CanvasResponse::from(canvas))
+}
+
+pub async fn mute_collaborator(params: MuteCollaboratorParams) -> Result<MuteCollaboratorResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let muted_until = canvas_service::mute_collaborator(
+        &app_state,
+        params.canvas_id,
+        user_id,
+        params.user_id,
+        params.duration_secs,
+    )
+    .await?;
+
+    Ok(MuteCollaboratorResponse {
+        user_id: params.user_id,
+        muted_until,
+    })
+}
@@ -5,10 +5,16 @@ use chrono::Utc;
 
 use crate::error::{AppError, Result};
 
+pub mod admin;
 pub mod auth;
 pub mod canvas;
 pub mod nft;
 pub mod pixel;
+pub mod solana;
+pub mod stats;
+
+const DEFAULT_PAGE_LIMIT: u64 = 50;
+const MAX_PAGE_LIMIT: u64 = 100;
 
 fn validate_wallet_address(wallet: &str) -> Result<()> {
     let decoded = bs58::decode(wallet)
@@ -24,6 +30,11 @@ fn validate_wallet_address(wallet: &str) -> Result<()> {
     Ok(())
 }
 
+/// Clamps a client-supplied page size for cursor pagination.
+fn resolve_limit(limit: Option<u64>) -> u64 {
+    limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+}
+
 fn calculate_remaining_ttl(exp: u64) -> Option<Duration> {
     let now = Utc::now().timestamp() as u64;
     exp.checked_sub(now)
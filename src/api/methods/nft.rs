@@ -1,17 +1,27 @@
 use std::time::Duration;
 
+use sea_orm::ActiveValue::Set;
+
 use crate::{
     api::types::{
-        AnnounceMintParams, AnnounceMintResponse, CancelMintCountdownParams, CancelMintParams,
-        ConfirmNftMintParams, MintNftParams, PrepareMetadataParams, PrepareMetadataResponse,
-        StateChangeResponse, SuccessResponse,
+        AnnounceMintParams, AnnounceMintResponse, BurnNftParams, BurnNftResponse,
+        CancelMintCountdownParams, CancelMintParams, ConfirmBurnParams, ConfirmBurnResponse,
+        ConfirmEditionMintParams, ConfirmEditionMintResponse, ConfirmMetadataUpdateParams,
+        ConfirmMetadataUpdateResponse, ConfirmNftMintParams, GenerateTimelapseParams,
+        GenerateTimelapseResponse, LineageParams, LineageResponse, MarketInfoParams,
+        MarketInfoResponse, MintEditionParams, MintEditionResponse, MintNftParams, MintNftResponse,
+        MintStatusParams, MintStatusResponse, PayoutStatusParams, PayoutStatusResponse,
+        PrepareMetadataParams, PrepareMetadataResponse, PreviewNftParams, PreviewNftResponse,
+        RoyaltyPayoutsParams, RoyaltyPayoutsResponse, StateChangeResponse, SuccessResponse,
+        UpdateMetadataParams, UpdateMetadataResponse, VerifyCollectionParams,
+        VerifyCollectionResponse, VerifyConsistencyParams, VerifyConsistencyResponse,
     },
     error::{AppError, Result},
     infrastructure::{
         cache::keys::CacheKey,
         db::{entities::canvas::CanvasState, repositories::CanvasRepository},
     },
-    services::{auth::TokenType, nft as nft_service},
+    services::{auth::TokenType, market as market_service, nft as nft_service, outbox},
     ws::types::RoomCanvasUpdate,
 };
 
@@ -38,6 +48,15 @@ pub async fn announce_mint_countdown(params: AnnounceMintParams) -> Result<Annou
         return Err(AppError::InvalidCanvasStateTransition);
     }
 
+    if let Some(bps) = params.royalty_bps {
+        nft_service::validate_royalty_bps(&app_state.config.canvas, bps)?;
+    }
+    let symbol = params
+        .symbol
+        .as_deref()
+        .map(nft_service::normalize_nft_symbol)
+        .transpose()?;
+
     let lock_key = CacheKey::canvas_lock(&params.canvas_id);
     let is_locked: Option<bool> = app_state.cache.redis.get(&lock_key).await?;
 
@@ -52,28 +71,39 @@ pub async fn announce_mint_countdown(params: AnnounceMintParams) -> Result<Annou
         .set(&lock_key, &true, Duration::from_secs(60))
         .await?;
 
-    CanvasRepository::update_canvas_state(
+    let events = vec![outbox::room_event(
+        params.canvas_id,
+        RoomCanvasUpdate::MintCountdown {
+            seconds: app_state.config.canvas.mint_countdown_secs,
+        },
+    )?];
+
+    let canvas = CanvasRepository::update_canvas_state(
         &app_state.db,
         params.canvas_id,
         CanvasState::MintPending,
-        |_active| {},
+        events,
+        |active| {
+            if let Some(bps) = params.royalty_bps {
+                active.royalty_bps = Set(Some(bps as i16));
+            }
+            if let Some(symbol) = symbol {
+                active.nft_symbol = Set(Some(symbol));
+            }
+            if let Some(standard) = params.standard {
+                active.nft_standard = Set(standard);
+            }
+        },
     )
     .await?;
 
-    app_state
-        .ws_rooms
-        .broadcast(
-            &params.canvas_id,
-            RoomCanvasUpdate::MintCountdown {
-                seconds: app_state.config.canvas.mint_countdown_secs,
-            },
-        )
-        .await;
-
     Ok(AnnounceMintResponse {
         success: true,
         state: "mint_pending".to_string(),
         countdown_seconds: app_state.config.canvas.mint_countdown_secs,
+        royalty_bps: nft_service::royalty_bps(&canvas),
+        symbol: nft_service::nft_symbol(&canvas),
+        standard: canvas.nft_standard,
     })
 }
 
@@ -105,22 +135,22 @@ pub async fn cancel_mint_countdown(params: CancelMintCountdownParams) -> Result<
     // Clear lock
     let lock_key = CacheKey::canvas_lock(&canvas.id);
     app_state.cache.redis.delete(&lock_key).await?;
+    nft_service::release_mint_slot(&app_state, canvas.id).await;
 
-    // Revert to published state
+    // Revert to published state, broadcasting the cancellation
+    let events = vec![outbox::room_event(
+        canvas.id,
+        RoomCanvasUpdate::MintCountdownCancelled,
+    )?];
     CanvasRepository::update_canvas_state(
         &app_state.db,
         canvas.id,
         CanvasState::Published,
+        events,
         |_active| {},
     )
     .await?;
 
-    // Broadcast cancellation
-    app_state
-        .ws_rooms
-        .broadcast(&canvas.id, RoomCanvasUpdate::MintCountdownCancelled)
-        .await;
-
     Ok(SuccessResponse::ok())
 }
 
@@ -149,7 +179,8 @@ pub async fn prepare_metadata(params: PrepareMetadataParams) -> Result<PrepareMe
         ));
     }
 
-    let result = nft_service::prepare_metadata(&app_state, params.canvas_id).await?;
+    let result =
+        nft_service::prepare_metadata(&app_state, params.canvas_id, params.attributes).await?;
 
     Ok(PrepareMetadataResponse {
         success: true,
@@ -158,10 +189,49 @@ pub async fn prepare_metadata(params: PrepareMetadataParams) -> Result<PrepareMe
         image_gateway_url: result.image_gateway_url,
         metadata_gateway_url: result.metadata_gateway_url,
         creators: result.creators,
+        storage_cost_lamports: result.storage_cost_lamports,
+    })
+}
+
+pub async fn preview(params: PreviewNftParams) -> Result<PreviewNftResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let preview = nft_service::preview_nft(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(PreviewNftResponse {
+        canvas_id: preview.canvas_id,
+        metadata: preview.metadata,
+        image_url: preview.image_url,
+        creators: preview.creators,
+    })
+}
+
+pub async fn get_lineage(params: LineageParams) -> Result<LineageResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?;
+
+    let lineage = nft_service::get_lineage(&app_state, params.canvas_id).await?;
+
+    Ok(LineageResponse {
+        canvas: lineage.canvas,
+        ancestors: lineage.ancestors,
+        descendants: lineage.descendants,
     })
 }
 
-pub async fn mint(params: MintNftParams) -> Result<StateChangeResponse> {
+pub async fn mint(params: MintNftParams) -> Result<MintNftResponse> {
     let app_state = params.state.ok_or(AppError::InternalServerError(
         "Failed to get app state".to_string(),
     ))?;
@@ -171,9 +241,26 @@ pub async fn mint(params: MintNftParams) -> Result<StateChangeResponse> {
         .validate_token(&params.access_token, TokenType::Access)?
         .sub;
 
-    let _ = nft_service::initiate_nft_mint(&app_state, params.canvas_id, user_id).await?;
+    let mint_info = nft_service::initiate_nft_mint(&app_state, params.canvas_id, user_id).await?;
 
-    Ok(StateChangeResponse::new("minting"))
+    Ok(MintNftResponse {
+        success: true,
+        state: "minting".to_string(),
+        program_id: mint_info.program_id,
+        symbol: mint_info.symbol,
+        seller_fee_basis_points: mint_info.seller_fee_basis_points,
+        standard: mint_info.standard,
+        asset_program_id: mint_info.asset_program_id,
+        asset_address: mint_info.asset_address,
+        metadata_pda: mint_info.metadata_pda,
+        master_edition_pda: mint_info.master_edition_pda,
+        collection_mint: mint_info.collection_mint,
+        collection_metadata_pda: mint_info.collection_metadata_pda,
+        collection_master_edition_pda: mint_info.collection_master_edition_pda,
+        parent_mint: mint_info.parent_mint,
+        transaction: mint_info.transaction,
+        estimated_cost: mint_info.estimated_cost,
+    })
 }
 
 pub async fn confirm_mint(params: ConfirmNftMintParams) -> Result<StateChangeResponse> {
@@ -212,3 +299,323 @@ pub async fn cancel_mint(params: CancelMintParams) -> Result<StateChangeResponse
 
     Ok(StateChangeResponse::new("published"))
 }
+
+pub async fn generate_timelapse(
+    params: GenerateTimelapseParams,
+) -> Result<GenerateTimelapseResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let timelapse = nft_service::generate_timelapse(
+        &app_state,
+        params.canvas_id,
+        user_id,
+        params.format,
+        params.attach_to_metadata,
+    )
+    .await?;
+
+    Ok(GenerateTimelapseResponse {
+        success: true,
+        url: timelapse.url,
+        format: timelapse.format,
+        content_hash: timelapse.content_hash,
+        frame_count: timelapse.frame_count,
+        size_bytes: timelapse.size_bytes,
+        attached_to_metadata: timelapse.attached_to_metadata,
+    })
+}
+
+pub async fn verify_collection(params: VerifyCollectionParams) -> Result<VerifyCollectionResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let status = nft_service::verify_collection(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(VerifyCollectionResponse {
+        success: true,
+        collection_mint: status.collection_mint,
+        collection_verified: status.verified,
+    })
+}
+
+pub async fn update_metadata(params: UpdateMetadataParams) -> Result<UpdateMetadataResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let update_info =
+        nft_service::initiate_metadata_update(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(UpdateMetadataResponse {
+        success: true,
+        metadata_uri: update_info.metadata.metadata_uri,
+        image_uri: update_info.metadata.image_uri,
+        image_gateway_url: update_info.metadata.image_gateway_url,
+        metadata_gateway_url: update_info.metadata.metadata_gateway_url,
+        storage_cost_lamports: update_info.metadata.storage_cost_lamports,
+        standard: update_info.standard,
+        asset_program_id: update_info.asset_program_id,
+        asset_address: update_info.asset_address,
+        metadata_pda: update_info.metadata_pda,
+        blockhash: update_info.blockhash,
+        name: update_info.name,
+        symbol: update_info.symbol,
+        seller_fee_basis_points: update_info.seller_fee_basis_points,
+    })
+}
+
+pub async fn confirm_metadata_update(
+    params: ConfirmMetadataUpdateParams,
+) -> Result<ConfirmMetadataUpdateResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let result = nft_service::confirm_metadata_update(
+        &app_state,
+        params.canvas_id,
+        user_id,
+        &params.signature,
+    )
+    .await?;
+
+    Ok(ConfirmMetadataUpdateResponse {
+        success: true,
+        metadata_uri: result.metadata_uri,
+        metadata_updated_at: result.metadata_updated_at,
+    })
+}
+
+pub async fn get_mint_status(params: MintStatusParams) -> Result<MintStatusResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let status = nft_service::get_mint_status(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(MintStatusResponse {
+        state: format!("{:?}", status.state).to_lowercase(),
+        mint_address: status.mint_address,
+        mint_signature: status.mint_signature,
+        mint_submitted_at: status.mint_submitted_at,
+        collection_verified: status.collection_verified,
+    })
+}
+
+pub async fn mint_edition(params: MintEditionParams) -> Result<MintEditionResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let edition_info =
+        nft_service::initiate_edition_mint(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(MintEditionResponse {
+        success: true,
+        edition_number: edition_info.edition_number,
+        max_editions: edition_info.max_editions,
+        program_id: edition_info.program_id,
+        asset_program_id: edition_info.asset_program_id,
+        master_mint: edition_info.master_mint,
+        master_metadata_pda: edition_info.master_metadata_pda,
+        master_edition_pda: edition_info.master_edition_pda,
+        edition_mint: edition_info.edition_mint,
+        edition_metadata_pda: edition_info.edition_metadata_pda,
+        edition_pda: edition_info.edition_pda,
+        edition_marker_pda: edition_info.edition_marker_pda,
+        metadata_uri: edition_info.metadata_uri,
+        blockhash: edition_info.blockhash,
+    })
+}
+
+pub async fn confirm_edition_mint(
+    params: ConfirmEditionMintParams,
+) -> Result<ConfirmEditionMintResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let edition =
+        nft_service::confirm_edition_mint(&app_state, params.canvas_id, user_id, &params.signature)
+            .await?;
+
+    Ok(ConfirmEditionMintResponse {
+        success: true,
+        edition_number: edition.edition_number,
+        mint_address: edition.mint_address,
+        minted_at: edition.minted_at,
+    })
+}
+
+pub async fn get_market_info(params: MarketInfoParams) -> Result<MarketInfoResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?;
+
+    let info = market_service::get_market_info(&app_state, params.canvas_id).await?;
+
+    Ok(MarketInfoResponse {
+        source: info.source,
+        mint_address: info.mint_address,
+        owner: info.owner,
+        listed: info.listed,
+        listed_price_lamports: info.listed_price_lamports,
+        seller: info.seller,
+        last_sale_price_lamports: info.last_sale_price_lamports,
+        last_sale_at: info.last_sale_at,
+        fetched_at: info.fetched_at,
+    })
+}
+
+pub async fn burn(params: BurnNftParams) -> Result<BurnNftResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let burn_info = nft_service::initiate_burn(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(BurnNftResponse {
+        success: true,
+        standard: burn_info.standard,
+        asset_program_id: burn_info.asset_program_id,
+        asset_address: burn_info.asset_address,
+        metadata_pda: burn_info.metadata_pda,
+        master_edition_pda: burn_info.master_edition_pda,
+        collection_mint: burn_info.collection_mint,
+        collection_metadata_pda: burn_info.collection_metadata_pda,
+        blockhash: burn_info.blockhash,
+    })
+}
+
+pub async fn confirm_burn(params: ConfirmBurnParams) -> Result<ConfirmBurnResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let result =
+        nft_service::confirm_burn(&app_state, params.canvas_id, user_id, &params.signature).await?;
+
+    Ok(ConfirmBurnResponse {
+        success: true,
+        state: format!("{:?}", result.state).to_lowercase(),
+        burned_at: result.burned_at,
+    })
+}
+
+pub async fn get_payout_status(params: PayoutStatusParams) -> Result<PayoutStatusResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let status = nft_service::get_payout_status(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(PayoutStatusResponse {
+        canvas_id: status.canvas_id,
+        mint_signature: status.mint_signature,
+        total_lamports: status.total_lamports,
+        claimed_lamports: status.claimed_lamports,
+        recipient_count: status.recipient_count,
+        claimed_count: status.claimed_count,
+        complete: status.claimed_count == status.recipient_count,
+        recipients: status.recipients,
+    })
+}
+
+pub async fn get_royalty_payouts(params: RoyaltyPayoutsParams) -> Result<RoyaltyPayoutsResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let payouts = nft_service::get_royalty_payouts(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(RoyaltyPayoutsResponse {
+        canvas_id: payouts.canvas_id,
+        total_lamports: payouts.total_lamports,
+        creators: payouts.creators,
+    })
+}
+
+pub async fn verify_consistency(
+    params: VerifyConsistencyParams,
+) -> Result<VerifyConsistencyResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let report =
+        nft_service::verify_canvas_consistency(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(VerifyConsistencyResponse {
+        canvas_pda: report.canvas_pda,
+        consistent: report.consistent,
+        pixel_mismatches: report.pixel_mismatches,
+        field_mismatches: report.field_mismatches,
+    })
+}
@@ -1,13 +1,23 @@
 use crate::{
-    api::types::{
-        CancelPixelBidParams, ConfirmPixelBidParams, ConfirmPixelBidResponse, PaintPixelParams,
-        PaintPixelResponse, PixelCoords, PlacePixelBidParams, PlacePixelBidResponse,
-        SuccessResponse,
+    api::{
+        methods::resolve_limit,
+        types::{
+            CancelPixelBidParams, ConfirmPixelBidParams, ConfirmPixelBidResponse,
+            ListOwnedPixelsParams, ListOwnedPixelsResponse, MyPixelInfo, PaintPixelBatchParams,
+            PaintPixelBatchResponse, PaintPixelParams, PaintPixelResponse, PixelBidHistoryParams,
+            PixelBidHistoryResponse, PixelBidInfo, PixelCoords, PlacePixelBidParams,
+            PlacePixelBidResponse, PreparePaintParams, PreparePaintResponse, SuccessResponse,
+            UnwatchPixelParams, WatchPixelParams,
+        },
     },
     error::{AppError, Result},
     services::{
         auth::TokenType,
-        pixel::{self as pixel_service, types::ConfirmPixelRequest},
+        pixel::{
+            self as pixel_service,
+            types::{ConfirmPixelRequest, PaintPixelBatchRequest},
+        },
+        price,
     },
 };
 
@@ -21,7 +31,7 @@ pub async fn place_pixel_bid(params: PlacePixelBidParams) -> Result<PlacePixelBi
         .validate_token(&params.access_token, TokenType::Access)?
         .sub;
 
-    let result = pixel_service::place_pixel(
+    let result = match pixel_service::place_pixel(
         &app_state,
         params.canvas_id,
         user_id,
@@ -30,7 +40,18 @@ pub async fn place_pixel_bid(params: PlacePixelBidParams) -> Result<PlacePixelBi
         params.coords.color,
         params.bid_lamports,
     )
-    .await?;
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(price::price_bid_error(&app_state, e).await),
+    };
+
+    let bid_usd = match params.bid_lamports {
+        Some(bid_lamports) => price::sol_usd(&app_state)
+            .await
+            .map(|rate| price::lamports_to_usd(bid_lamports, rate)),
+        None => None,
+    };
 
     Ok(PlacePixelBidResponse {
         success: true,
@@ -41,6 +62,9 @@ pub async fn place_pixel_bid(params: PlacePixelBidParams) -> Result<PlacePixelBi
         },
         requires_confirmation: result.requires_confirmation,
         previous_owner_wallet: result.previous_owner_wallet,
+        transaction: result.transaction,
+        bid_usd,
+        balance_check: result.balance_check,
     })
 }
 
@@ -54,7 +78,7 @@ pub async fn confirm_pixel_bid(params: ConfirmPixelBidParams) -> Result<ConfirmP
         .validate_token(&params.access_token, TokenType::Access)?
         .sub;
 
-    let pixel_info = pixel_service::confirm_pixel_bid(
+    let pixel_info = match pixel_service::confirm_pixel_bid(
         &app_state,
         ConfirmPixelRequest {
             canvas_id: params.canvas_id,
@@ -66,7 +90,15 @@ pub async fn confirm_pixel_bid(params: ConfirmPixelBidParams) -> Result<ConfirmP
             signature: params.signature,
         },
     )
-    .await?;
+    .await
+    {
+        Ok(pixel_info) => pixel_info,
+        Err(e) => return Err(price::price_bid_error(&app_state, e).await),
+    };
+
+    let price_usd = price::sol_usd(&app_state)
+        .await
+        .map(|rate| price::lamports_to_usd(pixel_info.price_lamports, rate));
 
     Ok(ConfirmPixelBidResponse {
         success: true,
@@ -77,6 +109,7 @@ pub async fn confirm_pixel_bid(params: ConfirmPixelBidParams) -> Result<ConfirmP
         },
         owner_id: pixel_info.owner_id.map(|id| id.to_string()),
         price_lamports: pixel_info.price_lamports,
+        price_usd,
     })
 }
 
@@ -126,3 +159,185 @@ pub async fn paint_pixel(params: PaintPixelParams) -> Result<PaintPixelResponse>
         },
     })
 }
+
+pub async fn paint_pixel_batch(params: PaintPixelBatchParams) -> Result<PaintPixelBatchResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let updated_pixels = pixel_service::paint_pixel_batch(
+        &app_state,
+        PaintPixelBatchRequest {
+            canvas_id: params.canvas_id,
+            user_id,
+            pixels: params
+                .pixels
+                .iter()
+                .map(|coords| (coords.x, coords.y, coords.color))
+                .collect(),
+            signature: params.signature,
+        },
+    )
+    .await?;
+
+    Ok(PaintPixelBatchResponse {
+        success: true,
+        pixels: updated_pixels
+            .into_iter()
+            .map(|pixel| PixelCoords {
+                x: pixel.x,
+                y: pixel.y,
+                color: pixel.color,
+            })
+            .collect(),
+    })
+}
+
+pub async fn prepare_paint(params: PreparePaintParams) -> Result<PreparePaintResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let pixels: Vec<(i16, i16, i16)> = params
+        .pixels
+        .iter()
+        .map(|coords| (coords.x, coords.y, coords.color))
+        .collect();
+    let transaction =
+        pixel_service::prepare_paint(&app_state, params.canvas_id, user_id, &pixels).await?;
+
+    Ok(PreparePaintResponse {
+        success: true,
+        transaction,
+    })
+}
+
+pub async fn list_owned_pixels(params: ListOwnedPixelsParams) -> Result<ListOwnedPixelsResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let limit = resolve_limit(params.limit);
+
+    let page =
+        pixel_service::list_owned_pixels(&app_state, user_id, params.cursor.as_deref(), limit)
+            .await?;
+
+    Ok(ListOwnedPixelsResponse {
+        pixels: page
+            .pixels
+            .into_iter()
+            .map(|p| MyPixelInfo {
+                canvas_id: p.canvas_id.to_string(),
+                canvas_name: p.canvas_name,
+                canvas_state: format!("{:?}", p.canvas_state).to_lowercase(),
+                x: p.x,
+                y: p.y,
+                color: p.color,
+                price_lamports: p.price_lamports,
+                updated_at: p.updated_at,
+            })
+            .collect(),
+        next_cursor: page.next_cursor,
+        total: page.total,
+        total_invested_lamports: page.total_invested_lamports,
+        limit,
+    })
+}
+
+pub async fn watch_pixel(params: WatchPixelParams) -> Result<SuccessResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    pixel_service::watch::watch_pixel(&app_state, params.canvas_id, user_id, params.x, params.y)
+        .await?;
+
+    Ok(SuccessResponse::ok())
+}
+
+pub async fn unwatch_pixel(params: UnwatchPixelParams) -> Result<SuccessResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    pixel_service::watch::unwatch_pixel(&app_state, params.canvas_id, user_id, params.x, params.y)
+        .await?;
+
+    Ok(SuccessResponse::ok())
+}
+
+pub async fn get_pixel_bid_history(
+    params: PixelBidHistoryParams,
+) -> Result<PixelBidHistoryResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let limit = resolve_limit(params.limit);
+
+    let page = pixel_service::list_pixel_bids(
+        &app_state,
+        params.canvas_id,
+        user_id,
+        params.x,
+        params.y,
+        params.cursor.as_deref(),
+        limit,
+    )
+    .await?;
+
+    let rate = price::sol_usd(&app_state).await;
+
+    Ok(PixelBidHistoryResponse {
+        bids: page
+            .bids
+            .into_iter()
+            .map(|bid| {
+                let amount_lamports = bid.amount_lamports.unwrap_or(0);
+                PixelBidInfo {
+                    bidder_id: bid.user_id.to_string(),
+                    bidder_wallet: bid.wallet_address,
+                    bidder_username: bid.username,
+                    amount_lamports,
+                    amount_usd: rate.map(|rate| price::lamports_to_usd(amount_lamports, rate)),
+                    signature: bid.signature,
+                    confirmed_at: bid.created_at,
+                }
+            })
+            .collect(),
+        next_cursor: page.next_cursor,
+        total: page.total,
+        limit,
+    })
+}
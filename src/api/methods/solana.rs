@@ -0,0 +1,111 @@
+use crate::{
+    api::types::{
+        ClaimEscrowParams, ClaimEscrowResponse, ConfirmEscrowClaimParams,
+        ConfirmEscrowClaimResponse, SimulateTransactionParams, SimulateTransactionResponse,
+        WalletBalanceParams, WalletBalanceResponse,
+    },
+    error::{AppError, Result},
+    services::{
+        auth::TokenType,
+        price,
+        solana::{escrow as escrow_service, transactions, wallet as wallet_service},
+    },
+};
+
+pub async fn claim_escrow(params: ClaimEscrowParams) -> Result<ClaimEscrowResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let claim = escrow_service::claim_escrow(&app_state, params.canvas_id, user_id).await?;
+
+    Ok(ClaimEscrowResponse {
+        canvas_id: claim.canvas_id,
+        amount_lamports: claim.amount_lamports,
+        refunds: claim.refunds,
+        blockhash: claim.blockhash,
+        transaction: claim.transaction,
+    })
+}
+
+pub async fn confirm_escrow_claim(
+    params: ConfirmEscrowClaimParams,
+) -> Result<ConfirmEscrowClaimResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let claim = escrow_service::confirm_escrow_claim(
+        &app_state,
+        params.canvas_id,
+        user_id,
+        &params.signature,
+    )
+    .await?;
+
+    Ok(ConfirmEscrowClaimResponse {
+        success: true,
+        canvas_id: claim.canvas_id,
+        amount_lamports: claim.amount_lamports,
+        signature: claim.signature,
+        refunds: claim.refunds,
+    })
+}
+
+pub async fn simulate_transaction(
+    params: SimulateTransactionParams,
+) -> Result<SimulateTransactionResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?;
+
+    let simulation = app_state
+        .solana_client
+        .simulate_transaction(&params.transaction)
+        .await?;
+
+    Ok(SimulateTransactionResponse {
+        success: simulation.success,
+        logs: simulation.logs,
+        units_consumed: simulation.units_consumed,
+        fee_lamports: simulation.fee_lamports,
+        error: simulation.error,
+    })
+}
+
+pub async fn get_wallet_balance(params: WalletBalanceParams) -> Result<WalletBalanceResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let user_id = app_state
+        .jwt_service
+        .validate_token(&params.access_token, TokenType::Access)?
+        .sub;
+
+    let wallet = transactions::user_wallet(&app_state, user_id).await?;
+    let balance_lamports = wallet_service::get_balance(&app_state, &wallet).await?;
+    let balance_usd = price::sol_usd(&app_state)
+        .await
+        .map(|rate| price::lamports_to_usd(balance_lamports as i64, rate));
+
+    Ok(WalletBalanceResponse {
+        wallet: wallet.to_string(),
+        balance_lamports,
+        balance_usd,
+    })
+}
@@ -0,0 +1,52 @@
+use crate::{
+    api::{
+        methods::resolve_limit,
+        types::{
+            CanvasLeaderboardEntry, LeaderboardsParams, LeaderboardsResponse, UserLeaderboardEntry,
+        },
+    },
+    error::{AppError, Result},
+    infrastructure::db::repositories::LeaderboardRepository,
+};
+
+pub async fn get_leaderboards(params: LeaderboardsParams) -> Result<LeaderboardsResponse> {
+    let app_state = params.state.ok_or(AppError::InternalServerError(
+        "Failed to get app state".to_string(),
+    ))?;
+
+    let limit = resolve_limit(params.limit);
+    let db_connection = app_state.db.get_connection();
+
+    let (canvases, users) = tokio::try_join!(
+        LeaderboardRepository::top_canvases(db_connection, limit),
+        LeaderboardRepository::top_users(db_connection, limit),
+    )?;
+
+    Ok(LeaderboardsResponse {
+        canvases: canvases
+            .into_iter()
+            .zip(1..)
+            .map(|(canvas, rank)| CanvasLeaderboardEntry {
+                rank,
+                canvas_id: canvas.canvas_id,
+                name: canvas.name,
+                owner_id: canvas.owner_id,
+                total_escrowed: canvas.total_escrowed,
+                pixel_count: canvas.pixel_count,
+            })
+            .collect(),
+        users: users
+            .into_iter()
+            .zip(1..)
+            .map(|(user, rank)| UserLeaderboardEntry {
+                rank,
+                user_id: user.user_id,
+                wallet_address: user.wallet_address,
+                username: user.username,
+                pixels_owned: user.pixels_owned,
+                total_lamports: user.total_lamports,
+            })
+            .collect(),
+        refresh_interval_secs: app_state.config.canvas.leaderboard_refresh_secs,
+    })
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+
+use axum::{
+    Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    AppState,
+    infrastructure::cache::metrics::{CacheMetrics, FamilySnapshot, LATENCY_BUCKETS},
+    ws::types::{RoomStats, WsStats},
+};
+
+/// Prometheus text exposition of this instance's WebSocket load and cache health.
+async fn metrics(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let Some(expected) = state.config.server.metrics_token.as_deref() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Digests are compared so the check does not leak how much of the token matched.
+    if token.is_none_or(|token| Sha256::digest(token) != Sha256::digest(expected)) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let mut body = render_ws_metrics(&state.ws_rooms.stats().await);
+    write_metric(
+        &mut body,
+        "cache_redis_available",
+        "gauge",
+        "Whether Redis is in use (1) or skipped after failing (0).",
+        u64::from(state.cache.redis.is_available()),
+    );
+    render_cache_metrics(
+        &mut body,
+        &[
+            ("local", state.cache.local.metrics()),
+            ("redis", state.cache.redis.metrics()),
+        ],
+    );
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response()
+}
+
+fn render_ws_metrics(stats: &WsStats) -> String {
+    let mut body = String::new();
+
+    write_metric(
+        &mut body,
+        "ws_rooms",
+        "gauge",
+        "Open canvas rooms.",
+        stats.rooms as u64,
+    );
+    write_metric(
+        &mut body,
+        "ws_user_channels",
+        "gauge",
+        "Users with an open connection.",
+        stats.user_channels as u64,
+    );
+    write_metric(
+        &mut body,
+        "ws_lag_events_total",
+        "counter",
+        "Times a room receiver fell behind.",
+        stats.lag_events,
+    );
+    write_metric(
+        &mut body,
+        "ws_lagged_updates_total",
+        "counter",
+        "Room updates skipped by lagging receivers.",
+        stats.lagged_updates,
+    );
+    write_metric(
+        &mut body,
+        "ws_user_lagged_updates_total",
+        "counter",
+        "User updates skipped by lagging receivers.",
+        stats.user_lagged_updates,
+    );
+    write_metric(
+        &mut body,
+        "ws_slow_evictions_total",
+        "counter",
+        "Connections closed for falling behind.",
+        stats.slow_evictions,
+    );
+
+    write_room_metric(
+        &mut body,
+        "ws_room_connections",
+        "Collaborator connections per room.",
+        stats,
+        |room| room.connections,
+    );
+    write_room_metric(
+        &mut body,
+        "ws_room_spectators",
+        "Spectator connections per room.",
+        stats,
+        |room| room.spectators,
+    );
+    write_room_metric(
+        &mut body,
+        "ws_room_queued_updates",
+        "Updates held in a room's broadcast buffer.",
+        stats,
+        |room| room.queued_updates,
+    );
+
+    body
+}
+
+fn render_cache_metrics(body: &mut String, tiers: &[(&str, &CacheMetrics)]) {
+    let snapshots: Vec<(&str, Vec<FamilySnapshot>)> = tiers
+        .iter()
+        .map(|(tier, metrics)| (*tier, metrics.snapshot()))
+        .collect();
+
+    write_cache_counter(
+        body,
+        "cache_hits_total",
+        "Cache lookups that found an entry.",
+        &snapshots,
+        |family| family.hits,
+    );
+    write_cache_counter(
+        body,
+        "cache_misses_total",
+        "Cache lookups that found nothing.",
+        &snapshots,
+        |family| family.misses,
+    );
+    write_cache_counter(
+        body,
+        "cache_errors_total",
+        "Cache operations that failed.",
+        &snapshots,
+        |family| family.errors,
+    );
+
+    let name = "cache_operation_duration_seconds";
+    write_header(body, name, "histogram", "Time taken by cache operations.");
+    for (tier, families) in &snapshots {
+        for family in families {
+            let labels = format!("tier=\"{tier}\",family=\"{}\"", family.family.as_str());
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(family.buckets) {
+                let _ = writeln!(body, "{name}_bucket{{{labels},le=\"{bound}\"}} {count}");
+            }
+            let _ = writeln!(
+                body,
+                "{name}_bucket{{{labels},le=\"+Inf\"}} {}",
+                family.operations
+            );
+            let _ = writeln!(body, "{name}_sum{{{labels}}} {}", family.latency_secs);
+            let _ = writeln!(body, "{name}_count{{{labels}}} {}", family.operations);
+        }
+    }
+}
+
+fn write_cache_counter(
+    body: &mut String,
+    name: &str,
+    help: &str,
+    snapshots: &[(&str, Vec<FamilySnapshot>)],
+    value: fn(&FamilySnapshot) -> u64,
+) {
+    write_header(body, name, "counter", help);
+    for (tier, families) in snapshots {
+        for family in families {
+            let _ = writeln!(
+                body,
+                "{name}{{tier=\"{tier}\",family=\"{}\"}} {}",
+                family.family.as_str(),
+                value(family)
+            );
+        }
+    }
+}
+
+fn write_header(body: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(body, "# HELP {name} {help}");
+    let _ = writeln!(body, "# TYPE {name} {kind}");
+}
+
+fn write_room_metric(
+    body: &mut String,
+    name: &str,
+    help: &str,
+    stats: &WsStats,
+    value: fn(&RoomStats) -> usize,
+) {
+    write_header(body, name, "gauge", help);
+    for room in &stats.by_room {
+        let _ = writeln!(
+            body,
+            "{name}{{canvas_id=\"{}\"}} {}",
+            room.canvas_id,
+            value(room)
+        );
+    }
+}
+
+fn write_metric(body: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    write_header(body, name, kind, help);
+    let _ = writeln!(body, "{name} {value}");
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(metrics))
+}
@@ -1,7 +1,10 @@
 pub mod dispatcher;
+pub mod health;
 pub mod methods;
+pub mod metrics;
 pub mod nft_metadata;
 pub mod router;
 pub mod types;
+pub mod webhooks;
 
 pub use router::router;
@@ -1,90 +1,365 @@
+use std::time::Duration;
+
 use axum::{
     Router,
-    extract::{Path, State},
-    http::{StatusCode, header},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Response},
     routing::get,
 };
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
     AppState,
-    services::nft::{self as nft_service, image::generate_png_from_colors},
+    error::AppError,
+    infrastructure::{
+        cache::{assets::AssetStore, keys::CacheKey},
+        db::{
+            entities::canvas::{CanvasState, TimelapseFormat},
+            repositories::CanvasRepository,
+        },
+    },
+    services::{
+        canvas::palette::parse_hex_color,
+        nft::{
+            self as nft_service,
+            image::{MAX_IMAGE_SCALE, RenderOptions, scale_for_size},
+        },
+    },
 };
 
-pub async fn get_nft_image(State(state): State<AppState>, Path(canvas_id): Path<Uuid>) -> Response {
-    let (canvas_pda, _) = state.solana_client.derive_canvas_pda_from_uuid(&canvas_id);
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+/// Non-default sizes are looked up in Redis and re-rendered from the chain once this lapses.
+const IMAGE_VARIANT_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
 
-    let account_data = match state.solana_client.get_account_data(&canvas_pda).await {
-        Ok(data) => data,
-        Err(e) => {
-            tracing::error!("Failed to fetch canvas from blockchain: {}", e);
-            return (StatusCode::NOT_FOUND, "Canvas not found on-chain").into_response();
-        }
+/// Whether the request's `If-None-Match` already names this content hash. `*` is not
+/// treated as a match, since every response here is keyed by its content.
+fn etag_matches(headers: &HeaderMap, content_hash: &str) -> bool {
+    let Some(if_none_match) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
     };
 
-    // Parse pixel_colors directly from blockchain account data.
-    // Ensures strictly trustless representation.
-    // CanvasMetadata layout (Total 835 bytes):
-    //   0-7:    Discriminator (8 bytes)
-    //   8-39:   Owner Pubkey (32 bytes)
-    //   40-55:  ID (16 bytes)
-    //   56:     State (1 byte)
-    //   57:     Width (1 byte)
-    //   58:     Height (1 byte)
-    //   59-66:  Total Escrow (8 bytes)
-    //   67-834: Pixel Colors (768 bytes) - 6-bit packed, 4 pixels/3 bytes
-    const PIXEL_COLORS_OFFSET: usize = 67;
-    const PIXEL_COLORS_SIZE: usize = 768;
-
-    if account_data.len() < PIXEL_COLORS_OFFSET + PIXEL_COLORS_SIZE {
+    if_none_match.split(',').any(|tag| {
+        let tag = tag.trim();
+        let tag = tag.strip_prefix("W/").unwrap_or(tag);
+        tag.trim_matches('"') == content_hash
+    })
+}
+
+fn content_response(
+    headers: &HeaderMap,
+    content_hash: &str,
+    content_type: &'static str,
+    cache_control: &'static str,
+    body: Vec<u8>,
+) -> Response {
+    let etag = format!("\"{}\"", content_hash);
+
+    if etag_matches(headers, content_hash) {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Invalid canvas account data",
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, etag),
+                (header::CACHE_CONTROL, cache_control.into()),
+            ],
         )
             .into_response();
     }
 
-    let pixel_colors = &account_data[PIXEL_COLORS_OFFSET..PIXEL_COLORS_OFFSET + PIXEL_COLORS_SIZE];
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, cache_control.into()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// `size` is 64, 512 or 1024 pixels. `scale` is the upscale factor from the 32x32 canvas.
+/// Anything left out falls back to the configured render defaults.
+#[derive(Debug, Deserialize)]
+pub struct ImageQuery {
+    size: Option<u32>,
+    scale: Option<u32>,
+    grid: Option<bool>,
+    background: Option<String>,
+    transparent: Option<bool>,
+}
+
+impl ImageQuery {
+    fn resolve_options(&self, defaults: RenderOptions) -> Option<RenderOptions> {
+        let scale = match (self.size, self.scale) {
+            (None, None) => defaults.scale,
+            (Some(size), None) => scale_for_size(size)?,
+            (None, Some(scale)) => (1..=MAX_IMAGE_SCALE).contains(&scale).then_some(scale)?,
+            (Some(_), Some(_)) => return None,
+        };
+        let background = match self.background.as_deref() {
+            Some(background) => parse_hex_color(background)?,
+            None => defaults.background,
+        };
 
-    let image_data = match generate_png_from_colors(pixel_colors) {
-        Ok(data) => data,
+        Some(RenderOptions {
+            scale,
+            grid_lines: self.grid.unwrap_or(defaults.grid_lines),
+            grid_color: defaults.grid_color,
+            background,
+            transparent_unset: self.transparent.unwrap_or(defaults.transparent_unset),
+        })
+    }
+}
+
+pub async fn get_nft_image(
+    State(state): State<AppState>,
+    Path(canvas_id): Path<Uuid>,
+    Query(query): Query<ImageQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(options) = query.resolve_options(RenderOptions::from_config(&state.config.image))
+    else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Use size 64, 512 or 1024, or a scale from 1 to 32, and a rrggbb background",
+        )
+            .into_response();
+    };
+
+    let canvas =
+        match CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id).await {
+            Ok(canvas) => canvas,
+            Err(e) => {
+                tracing::warn!("Failed to load canvas: {}", e);
+                None
+            }
+        };
+
+    let is_minted = canvas
+        .as_ref()
+        .is_some_and(|canvas| canvas.state == CanvasState::Minted);
+
+    // Minted canvases never change, so each variant is rendered once and served from the asset store.
+    // The default render is recorded on the canvas at mint, other variants in Redis.
+    let recorded_hash = canvas
+        .as_ref()
+        .and_then(|canvas| canvas.image_hash.clone())
+        .filter(|_| options.is_default());
+    let cached_hash = match recorded_hash {
+        Some(image_hash) => Some(image_hash),
+        None if is_minted => state
+            .cache
+            .redis
+            .get::<String>(&CacheKey::image_variant(&canvas_id, &options.cache_tag()))
+            .await
+            .unwrap_or_default(),
+        None => None,
+    };
+
+    if let Some(image_hash) = cached_hash.as_deref() {
+        if etag_matches(&headers, image_hash) {
+            return content_response(
+                &headers,
+                image_hash,
+                "image/png",
+                IMMUTABLE_CACHE_CONTROL,
+                Vec::new(),
+            );
+        }
+
+        match state.cache.assets.get(image_hash).await {
+            Ok(Some(image_data)) => {
+                return content_response(
+                    &headers,
+                    image_hash,
+                    "image/png",
+                    IMMUTABLE_CACHE_CONTROL,
+                    image_data,
+                );
+            }
+            Ok(None) => tracing::warn!("Cached image {} missing, re-rendering", image_hash),
+            Err(e) => tracing::warn!("Failed to read cached image {}: {}", image_hash, e),
+        }
+    }
+
+    let image_data =
+        match nft_service::render_onchain_image(&state, canvas_id, canvas.as_ref(), &options).await
+        {
+            Ok(data) => data,
+            Err(AppError::SolanaRpc(e)) => {
+                tracing::error!("Failed to fetch canvas from blockchain: {}", e);
+                return (StatusCode::NOT_FOUND, "Canvas not found on-chain").into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to generate image: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to generate image",
+                )
+                    .into_response();
+            }
+        };
+
+    if !is_minted {
+        let image_hash = AssetStore::content_hash(&image_data);
+        return content_response(&headers, &image_hash, "image/png", "no-cache", image_data);
+    }
+
+    let image_hash = match state.cache.assets.put(&image_data).await {
+        Ok(image_hash) => {
+            record_image_hash(&state, canvas_id, &options, &image_hash).await;
+            image_hash
+        }
         Err(e) => {
-            tracing::error!("Failed to generate image: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to generate image",
-            )
-                .into_response();
+            tracing::warn!("Failed to cache image: {}", e);
+            AssetStore::content_hash(&image_data)
         }
     };
 
-    (
-        StatusCode::OK,
-        [
-            (header::CONTENT_TYPE, "image/png"),
-            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
-        ],
+    content_response(
+        &headers,
+        &image_hash,
+        "image/png",
+        IMMUTABLE_CACHE_CONTROL,
         image_data,
     )
-        .into_response()
 }
 
-pub async fn get_metadata(State(state): State<AppState>, Path(canvas_id): Path<Uuid>) -> Response {
+/// Recorded in Redis only, so serving an image never writes to the database.
+async fn record_image_hash(
+    state: &AppState,
+    canvas_id: Uuid,
+    options: &RenderOptions,
+    image_hash: &str,
+) {
+    if let Err(e) = state
+        .cache
+        .redis
+        .set(
+            &CacheKey::image_variant(&canvas_id, &options.cache_tag()),
+            &image_hash,
+            IMAGE_VARIANT_TTL,
+        )
+        .await
+    {
+        tracing::warn!("Failed to record image hash: {}", e);
+    }
+}
+
+pub async fn get_metadata(
+    State(state): State<AppState>,
+    Path(canvas_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
     let metadata_result = match nft_service::get_nft_metadata(&state, canvas_id).await {
         Ok(metadata) => metadata,
         Err(_) => return (StatusCode::NOT_FOUND, "Metadata not found").into_response(),
     };
 
-    (
-        StatusCode::OK,
-        [
-            (header::CONTENT_TYPE, "application/json"),
-            (header::CACHE_CONTROL, "public, max-age=86400"),
-        ],
-        serde_json::to_string_pretty(&metadata_result).unwrap_or_default(),
+    let body = serde_json::to_vec_pretty(&metadata_result).unwrap_or_default();
+    let content_hash = AssetStore::content_hash(&body);
+
+    content_response(
+        &headers,
+        &content_hash,
+        "application/json",
+        "public, max-age=86400",
+        body,
     )
-        .into_response()
+}
+
+pub async fn get_edition_metadata(
+    State(state): State<AppState>,
+    Path((canvas_id, edition_number)): Path<(Uuid, i32)>,
+    headers: HeaderMap,
+) -> Response {
+    let metadata_result =
+        match nft_service::get_edition_metadata(&state, canvas_id, edition_number).await {
+            Ok(metadata) => metadata,
+            Err(_) => return (StatusCode::NOT_FOUND, "Metadata not found").into_response(),
+        };
+
+    let body = serde_json::to_vec_pretty(&metadata_result).unwrap_or_default();
+    let content_hash = AssetStore::content_hash(&body);
+
+    content_response(
+        &headers,
+        &content_hash,
+        "application/json",
+        "public, max-age=86400",
+        body,
+    )
+}
+
+pub async fn get_timelapse_gif(
+    State(state): State<AppState>,
+    Path(canvas_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    serve_timelapse(&state, canvas_id, &headers, TimelapseFormat::Gif).await
+}
+
+pub async fn get_timelapse_apng(
+    State(state): State<AppState>,
+    Path(canvas_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    serve_timelapse(&state, canvas_id, &headers, TimelapseFormat::Apng).await
+}
+
+async fn serve_timelapse(
+    state: &AppState,
+    canvas_id: Uuid,
+    headers: &HeaderMap,
+    requested_format: TimelapseFormat,
+) -> Response {
+    let canvas = match CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await
+    {
+        Ok(Some(canvas)) => canvas,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Canvas not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load canvas: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load canvas").into_response();
+        }
+    };
+
+    // Only the most recently generated format is kept.
+    let (Some(timelapse_hash), Some(format)) = (canvas.timelapse_hash, canvas.timelapse_format)
+    else {
+        return (StatusCode::NOT_FOUND, "Timelapse not generated").into_response();
+    };
+    if format != requested_format {
+        return (
+            StatusCode::NOT_FOUND,
+            "Timelapse not generated in this format",
+        )
+            .into_response();
+    }
+
+    match state.cache.assets.get(&timelapse_hash).await {
+        Ok(Some(timelapse_data)) => content_response(
+            headers,
+            &timelapse_hash,
+            format.content_type(),
+            "no-cache",
+            timelapse_data,
+        ),
+        Ok(None) => (StatusCode::NOT_FOUND, "Timelapse not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to read timelapse: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to read timelapse",
+            )
+                .into_response()
+        }
+    }
 }
 
 pub fn router() -> Router<AppState> {
@@ -93,4 +368,10 @@ pub fn router() -> Router<AppState> {
         .route("/{canvas_id}/image.png", get(get_nft_image))
         .route("/{canvas_id}/metadata", get(get_metadata))
         .route("/{canvas_id}/metadata.json", get(get_metadata))
+        .route(
+            "/{canvas_id}/editions/{edition_number}/metadata.json",
+            get(get_edition_metadata),
+        )
+        .route("/{canvas_id}/timelapse.gif", get(get_timelapse_gif))
+        .route("/{canvas_id}/timelapse.png", get(get_timelapse_apng))
 }
@@ -14,8 +14,11 @@ use crate::{
     AppState,
     api::{
         dispatcher::dispatch_method,
+        health,
         methods::extract_cookie,
+        metrics,
         types::{JsonRpcRequest, JsonRpcResponse},
+        webhooks,
     },
     error::{AppError, JsonRpcErrorResponse},
     services::auth::{
@@ -25,7 +28,11 @@ use crate::{
 };
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/", post(rpc_handler))
+    Router::new()
+        .route("/", post(rpc_handler))
+        .nest("/webhooks", webhooks::router())
+        .nest("/metrics", metrics::router())
+        .nest("/health", health::router())
 }
 
 async fn rpc_handler(
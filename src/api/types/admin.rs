@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{AppState, ws::types::SystemLevel};
+
+#[derive(Deserialize)]
+pub struct AdminParams {
+    pub access_token: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+pub type WsStatsParams = AdminParams;
+
+#[derive(Deserialize)]
+pub struct BroadcastParams {
+    pub access_token: String,
+    /// Room to announce to. Every room when absent.
+    pub canvas_id: Option<Uuid>,
+    #[serde(default)]
+    pub level: SystemLevel,
+    pub message: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Serialize)]
+pub struct BroadcastResponse {
+    /// Rooms open on the answering instance that received the announcement.
+    pub rooms: usize,
+}
+
+#[derive(Deserialize)]
+pub struct AuditLogParams {
+    pub access_token: String,
+    pub method: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub limit: Option<u64>,
+
+    /// `next_cursor` from the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub method: String,
+    pub actor_id: Option<Uuid>,
+    pub params_hash: String,
+    /// JSON-RPC error code, or 0 when the call succeeded.
+    pub result_code: i32,
+    pub latency_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditLogEntry>,
+    pub next_cursor: Option<String>,
+}
@@ -1,15 +1,24 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::AppState;
+use crate::{
+    AppState,
+    infrastructure::db::entities::canvas::NftStandard,
+    services::{
+        canvas::types::{CanvasInfo, PresentUser},
+        solana::fees::CostEstimate,
+    },
+};
 
-use super::common::{CanvasActionParams, StateChangeResponse, SuccessResponse};
+use super::common::{CanvasActionParams, PixelCoords, StateChangeResponse, SuccessResponse};
 
 // Type aliases for canvas action params
 pub type GetCanvasParams = CanvasActionParams;
 pub type PublishCanvasParams = CanvasActionParams;
 pub type CancelPublishCanvasParams = CanvasActionParams;
 pub type DeleteCanvasParams = CanvasActionParams;
+pub type CanvasPresenceParams = CanvasActionParams;
 
 #[derive(Deserialize)]
 pub struct CreateCanvasParams {
@@ -19,6 +28,14 @@ pub struct CreateCanvasParams {
     #[serde(default)]
     pub initial_color: Option<i16>,
 
+    /// Custom palette as `#rrggbb` strings. Defaults to the built-in palette.
+    #[serde(default)]
+    pub palette: Option<Vec<String>>,
+
+    /// Minted canvas this one remixes.
+    #[serde(default)]
+    pub parent_canvas_id: Option<Uuid>,
+
     #[serde(skip)]
     pub state: Option<AppState>,
 }
@@ -32,12 +49,124 @@ pub struct CanvasResponse {
     pub owner_id: String,
     pub canvas_pda: Option<String>,
     pub mint_address: Option<String>,
+    pub pixel_quota: Option<i32>,
+    pub pixel_quota_window_secs: Option<i32>,
+    pub bid_floor_lamports: Option<i64>,
+    pub bid_step_lamports: Option<i64>,
+    pub bid_ceiling_lamports: Option<i64>,
+    pub owner_cooldown_exempt: bool,
+    pub royalty_bps: Option<i16>,
+    pub nft_symbol: Option<String>,
+    pub nft_standard: NftStandard,
+    pub collection_mint: Option<String>,
+    pub collection_verified: bool,
+    pub parent_canvas_id: Option<String>,
+}
+
+impl From<CanvasInfo> for CanvasResponse {
+    fn from(canvas: CanvasInfo) -> Self {
+        CanvasResponse {
+            id: canvas.id.to_string(),
+            name: canvas.name,
+            invite_code: canvas.invite_code,
+            state: format!("{:?}", canvas.state).to_lowercase(),
+            owner_id: canvas.owner_id.to_string(),
+            canvas_pda: canvas.canvas_pda,
+            mint_address: canvas.mint_address,
+            pixel_quota: canvas.pixel_quota,
+            pixel_quota_window_secs: canvas.pixel_quota_window_secs,
+            bid_floor_lamports: canvas.bid_floor_lamports,
+            bid_step_lamports: canvas.bid_step_lamports,
+            bid_ceiling_lamports: canvas.bid_ceiling_lamports,
+            owner_cooldown_exempt: canvas.owner_cooldown_exempt,
+            royalty_bps: canvas.royalty_bps,
+            nft_symbol: canvas.nft_symbol,
+            nft_standard: canvas.nft_standard,
+            collection_mint: canvas.collection_mint,
+            collection_verified: canvas.collection_verified,
+            parent_canvas_id: canvas.parent_canvas_id.map(|id| id.to_string()),
+        }
+    }
+}
+
+/// Omitting both `pixel_quota` and `window_secs` clears the quota.
+#[derive(Deserialize)]
+pub struct SetPixelQuotaParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+
+    #[serde(default)]
+    pub pixel_quota: Option<u32>,
+
+    #[serde(default)]
+    pub window_secs: Option<u32>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+/// Omitted rules are cleared.
+#[derive(Deserialize)]
+pub struct SetBidRulesParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+
+    #[serde(default)]
+    pub floor_lamports: Option<u64>,
+
+    #[serde(default)]
+    pub step_lamports: Option<u64>,
+
+    #[serde(default)]
+    pub ceiling_lamports: Option<u64>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Deserialize)]
+pub struct SetCooldownExemptionParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub owner_exempt: bool,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Deserialize)]
+pub struct MuteCollaboratorParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub user_id: Uuid,
+    /// Zero lifts the mute.
+    pub duration_secs: u64,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MuteCollaboratorResponse {
+    pub user_id: Uuid,
+    pub muted_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize)]
 pub struct ListCanvasParams {
     pub access_token: String,
 
+    #[serde(default)]
+    pub limit: Option<u64>,
+
+    /// `next_owned_cursor` from the previous page.
+    #[serde(default)]
+    pub owned_cursor: Option<String>,
+
+    /// `next_collaborating_cursor` from the previous page.
+    #[serde(default)]
+    pub collaborating_cursor: Option<String>,
+
     #[serde(skip)]
     pub state: Option<AppState>,
 }
@@ -45,7 +174,9 @@ pub struct ListCanvasParams {
 #[derive(Debug, Serialize)]
 pub struct ListCanvasResponse {
     pub owned: Vec<CanvasResponse>,
+    pub next_owned_cursor: Option<String>,
     pub collaborating: Vec<CanvasResponse>,
+    pub next_collaborating_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -60,6 +191,9 @@ pub struct OwnedPixelInfo {
 pub struct CanvasWithPixelsResponse {
     pub canvas: CanvasResponse,
 
+    /// Colors as `#rrggbb`, indexed by pixel color.
+    pub palette: Vec<String>,
+
     /// Base64-encoded 1024-byte array where byte at index (y*32 + x) is the color.
     pub pixel_colors: String,
     pub owned_pixels: Vec<OwnedPixelInfo>,
@@ -84,7 +218,10 @@ pub struct JoinCanvasResponse {
 pub struct PublishCanvasResponse {
     pub success: bool,
     pub state: String,
+    pub program_id: String,
     pub pixel_colors_packed: String,
+    pub transaction: String,
+    pub estimated_cost: Option<CostEstimate>,
 }
 
 #[derive(Deserialize)]
@@ -107,3 +244,50 @@ pub struct ConfirmPublishCanvasResponse {
 
 pub type CancelPublishCanvasResponse = StateChangeResponse;
 pub type DeleteCanvasResponse = SuccessResponse;
+
+#[derive(Deserialize)]
+pub struct CanvasTimelapseParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+
+    /// `next_cursor` from the previous page.
+    #[serde(default)]
+    pub cursor: Option<i64>,
+
+    #[serde(default)]
+    pub bucket_secs: Option<u32>,
+
+    #[serde(default)]
+    pub max_frames: Option<u32>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelapseFrameInfo {
+    pub timestamp: DateTime<Utc>,
+    pub changes: Vec<PixelCoords>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanvasTimelapseResponse {
+    /// Base64-encoded color bytes (index y*width + x) before the first frame.
+    pub base_colors: String,
+    pub frames: Vec<TimelapseFrameInfo>,
+    /// Changes in the whole `from`..`to` range, across every page.
+    pub total_changes: u64,
+    pub next_cursor: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanvasPresenceResponse {
+    pub canvas_id: Uuid,
+    pub users: Vec<PresentUser>,
+}
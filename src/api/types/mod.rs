@@ -1,11 +1,17 @@
+mod admin;
 mod auth;
 mod canvas;
 mod common;
 mod nft;
 mod pixel;
+mod solana;
+mod stats;
 
+pub use admin::*;
 pub use auth::*;
 pub use canvas::*;
 pub use common::*;
 pub use nft::*;
 pub use pixel::*;
+pub use solana::*;
+pub use stats::*;
@@ -1,22 +1,77 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{AppState, services::nft::types::CreatorOutput};
+use crate::{
+    AppState,
+    infrastructure::db::entities::canvas::{CustomAttribute, NftStandard, TimelapseFormat},
+    services::{
+        nft::types::{
+            CreatorOutput, CreatorPayout, FieldMismatch, LineageNode, NftMetadata, PayoutRecipient,
+            PixelMismatch,
+        },
+        solana::fees::CostEstimate,
+    },
+};
 
 use super::common::{CanvasActionParams, StateChangeResponse, SuccessResponse};
 
 // Type aliases for NFT action params (all share CanvasActionParams structure)
-pub type AnnounceMintParams = CanvasActionParams;
 pub type CancelMintCountdownParams = CanvasActionParams;
-pub type PrepareMetadataParams = CanvasActionParams;
 pub type MintNftParams = CanvasActionParams;
 pub type CancelMintParams = CanvasActionParams;
+pub type VerifyCollectionParams = CanvasActionParams;
+pub type UpdateMetadataParams = CanvasActionParams;
+pub type MintStatusParams = CanvasActionParams;
+pub type MintEditionParams = CanvasActionParams;
+pub type MarketInfoParams = CanvasActionParams;
+pub type BurnNftParams = CanvasActionParams;
+pub type RoyaltyPayoutsParams = CanvasActionParams;
+pub type VerifyConsistencyParams = CanvasActionParams;
+pub type PreviewNftParams = CanvasActionParams;
+pub type LineageParams = CanvasActionParams;
+pub type PayoutStatusParams = CanvasActionParams;
+
+#[derive(Deserialize)]
+pub struct PrepareMetadataParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+
+    /// Replaces the canvas's custom attributes. Omit to keep the saved ones.
+    #[serde(default)]
+    pub attributes: Option<Vec<CustomAttribute>>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+/// Omitted royalty or symbol keeps the canvas's current value.
+#[derive(Deserialize)]
+pub struct AnnounceMintParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+
+    #[serde(default)]
+    pub royalty_bps: Option<u16>,
+
+    #[serde(default)]
+    pub symbol: Option<String>,
+
+    #[serde(default)]
+    pub standard: Option<NftStandard>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
 
 #[derive(Debug, Serialize)]
 pub struct AnnounceMintResponse {
     pub success: bool,
     pub state: String,
     pub countdown_seconds: u8,
+    pub royalty_bps: u16,
+    pub symbol: String,
+    pub standard: NftStandard,
 }
 
 pub type CancelMintCountdownResponse = SuccessResponse;
@@ -29,9 +84,43 @@ pub struct PrepareMetadataResponse {
     pub image_gateway_url: String,
     pub metadata_gateway_url: String,
     pub creators: Vec<CreatorOutput>,
+    pub storage_cost_lamports: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LineageResponse {
+    pub canvas: LineageNode,
+    pub ancestors: Vec<LineageNode>,
+    pub descendants: Vec<LineageNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreviewNftResponse {
+    pub canvas_id: Uuid,
+    pub metadata: NftMetadata,
+    pub image_url: String,
+    pub creators: Vec<CreatorOutput>,
 }
 
-pub type MintNftResponse = StateChangeResponse;
+#[derive(Debug, Serialize)]
+pub struct MintNftResponse {
+    pub success: bool,
+    pub state: String,
+    pub program_id: String,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+    pub standard: NftStandard,
+    pub asset_program_id: String,
+    pub asset_address: String,
+    pub metadata_pda: Option<String>,
+    pub master_edition_pda: Option<String>,
+    pub collection_mint: Option<String>,
+    pub collection_metadata_pda: Option<String>,
+    pub collection_master_edition_pda: Option<String>,
+    pub parent_mint: Option<String>,
+    pub transaction: String,
+    pub estimated_cost: Option<CostEstimate>,
+}
 
 #[derive(Deserialize)]
 pub struct ConfirmNftMintParams {
@@ -46,3 +135,187 @@ pub struct ConfirmNftMintParams {
 
 pub type ConfirmNftMintResponse = StateChangeResponse;
 pub type CancelMintResponse = StateChangeResponse;
+
+#[derive(Deserialize)]
+pub struct GenerateTimelapseParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+
+    #[serde(default)]
+    pub format: TimelapseFormat,
+
+    /// List the animation as an extra file in the NFT metadata.
+    #[serde(default)]
+    pub attach_to_metadata: bool,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateTimelapseResponse {
+    pub success: bool,
+    pub url: String,
+    pub format: TimelapseFormat,
+    pub content_hash: String,
+    pub frame_count: usize,
+    pub size_bytes: usize,
+    pub attached_to_metadata: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyCollectionResponse {
+    pub success: bool,
+    pub collection_mint: Option<String>,
+    pub collection_verified: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateMetadataResponse {
+    pub success: bool,
+    pub metadata_uri: String,
+    pub image_uri: String,
+    pub image_gateway_url: String,
+    pub metadata_gateway_url: String,
+    pub storage_cost_lamports: Option<u64>,
+    pub standard: NftStandard,
+    pub asset_program_id: String,
+    pub asset_address: String,
+    pub metadata_pda: Option<String>,
+    pub blockhash: String,
+    pub name: String,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmMetadataUpdateParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub signature: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmMetadataUpdateResponse {
+    pub success: bool,
+    pub metadata_uri: String,
+    pub metadata_updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintStatusResponse {
+    pub state: String,
+    pub mint_address: Option<String>,
+    pub mint_signature: Option<String>,
+    pub mint_submitted_at: Option<DateTime<Utc>>,
+    pub collection_verified: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintEditionResponse {
+    pub success: bool,
+    pub edition_number: i32,
+    pub max_editions: i32,
+    pub program_id: String,
+    pub asset_program_id: String,
+    pub master_mint: String,
+    pub master_metadata_pda: String,
+    pub master_edition_pda: String,
+    pub edition_mint: String,
+    pub edition_metadata_pda: String,
+    pub edition_pda: String,
+    pub edition_marker_pda: String,
+    pub metadata_uri: String,
+    pub blockhash: String,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmEditionMintParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub signature: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmEditionMintResponse {
+    pub success: bool,
+    pub edition_number: i32,
+    pub mint_address: Option<String>,
+    pub minted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarketInfoResponse {
+    pub source: String,
+    pub mint_address: String,
+    pub owner: Option<String>,
+    pub listed: bool,
+    pub listed_price_lamports: Option<u64>,
+    pub seller: Option<String>,
+    pub last_sale_price_lamports: Option<u64>,
+    pub last_sale_at: Option<DateTime<Utc>>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BurnNftResponse {
+    pub success: bool,
+    pub standard: NftStandard,
+    pub asset_program_id: String,
+    pub asset_address: String,
+    pub metadata_pda: Option<String>,
+    pub master_edition_pda: Option<String>,
+    pub collection_mint: Option<String>,
+    pub collection_metadata_pda: Option<String>,
+    pub blockhash: String,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmBurnParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub signature: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmBurnResponse {
+    pub success: bool,
+    pub state: String,
+    pub burned_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoyaltyPayoutsResponse {
+    pub canvas_id: Uuid,
+    pub total_lamports: i64,
+    pub creators: Vec<CreatorPayout>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PayoutStatusResponse {
+    pub canvas_id: Uuid,
+    pub mint_signature: String,
+    pub total_lamports: i64,
+    pub claimed_lamports: i64,
+    pub recipient_count: usize,
+    pub claimed_count: usize,
+    pub complete: bool,
+    pub recipients: Vec<PayoutRecipient>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyConsistencyResponse {
+    pub canvas_pda: String,
+    pub consistent: bool,
+    pub pixel_mismatches: Vec<PixelMismatch>,
+    pub field_mismatches: Vec<FieldMismatch>,
+}
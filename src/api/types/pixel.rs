@@ -1,7 +1,8 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::AppState;
+use crate::{AppState, services::solana::wallet::BalanceCheck};
 
 use super::common::{PixelCoords, SuccessResponse};
 
@@ -24,6 +25,10 @@ pub struct PlacePixelBidResponse {
     pub coords: PixelCoords,
     pub requires_confirmation: bool,
     pub previous_owner_wallet: Option<String>,
+    pub transaction: Option<String>,
+    /// USD value of the bid, when a price oracle is configured.
+    pub bid_usd: Option<f64>,
+    pub balance_check: Option<BalanceCheck>,
 }
 
 #[derive(Deserialize)]
@@ -46,6 +51,7 @@ pub struct ConfirmPixelBidResponse {
     pub coords: PixelCoords,
     pub owner_id: Option<String>,
     pub price_lamports: i64,
+    pub price_usd: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -79,3 +85,123 @@ pub struct PaintPixelResponse {
     #[serde(flatten)]
     pub coords: PixelCoords,
 }
+
+#[derive(Deserialize)]
+pub struct PaintPixelBatchParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub pixels: Vec<PixelCoords>,
+    pub signature: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaintPixelBatchResponse {
+    pub success: bool,
+    pub pixels: Vec<PixelCoords>,
+}
+
+#[derive(Deserialize)]
+pub struct PreparePaintParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub pixels: Vec<PixelCoords>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreparePaintResponse {
+    pub success: bool,
+    pub transaction: String,
+}
+
+#[derive(Deserialize)]
+pub struct WatchPixelParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub x: i16,
+    pub y: i16,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+pub type UnwatchPixelParams = WatchPixelParams;
+
+#[derive(Deserialize)]
+pub struct ListOwnedPixelsParams {
+    pub access_token: String,
+
+    #[serde(default)]
+    pub limit: Option<u64>,
+
+    /// `next_cursor` from the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MyPixelInfo {
+    pub canvas_id: String,
+    pub canvas_name: String,
+    pub canvas_state: String,
+    pub x: i16,
+    pub y: i16,
+    pub color: i16,
+    pub price_lamports: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListOwnedPixelsResponse {
+    pub pixels: Vec<MyPixelInfo>,
+    pub next_cursor: Option<String>,
+    pub total: u64,
+    pub total_invested_lamports: i64,
+    pub limit: u64,
+}
+
+#[derive(Deserialize)]
+pub struct PixelBidHistoryParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub x: i16,
+    pub y: i16,
+
+    #[serde(default)]
+    pub limit: Option<u64>,
+
+    /// `next_cursor` from the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PixelBidInfo {
+    pub bidder_id: String,
+    pub bidder_wallet: String,
+    pub bidder_username: Option<String>,
+    pub amount_lamports: i64,
+    /// At the current rate, not the rate when the bid was placed.
+    pub amount_usd: Option<f64>,
+    pub signature: String,
+    pub confirmed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PixelBidHistoryResponse {
+    pub bids: Vec<PixelBidInfo>,
+    pub next_cursor: Option<String>,
+    pub total: u64,
+    pub limit: u64,
+}
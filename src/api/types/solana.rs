@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    services::solana::{escrow::EscrowRefund, simulate::SimulationError},
+};
+
+use super::common::CanvasActionParams;
+
+pub type ClaimEscrowParams = CanvasActionParams;
+
+#[derive(Debug, Serialize)]
+pub struct ClaimEscrowResponse {
+    pub canvas_id: Uuid,
+    pub amount_lamports: i64,
+    pub refunds: Vec<EscrowRefund>,
+    pub blockhash: String,
+    pub transaction: String,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmEscrowClaimParams {
+    pub access_token: String,
+    pub canvas_id: Uuid,
+    pub signature: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmEscrowClaimResponse {
+    pub success: bool,
+    pub canvas_id: Uuid,
+    pub amount_lamports: i64,
+    pub signature: String,
+    pub refunds: Vec<EscrowRefund>,
+}
+
+#[derive(Deserialize)]
+pub struct SimulateTransactionParams {
+    pub access_token: String,
+    /// Base64 wire-format transaction, signed or not.
+    pub transaction: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulateTransactionResponse {
+    pub success: bool,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    pub fee_lamports: Option<u64>,
+    pub error: Option<SimulationError>,
+}
+
+#[derive(Deserialize)]
+pub struct WalletBalanceParams {
+    pub access_token: String,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalletBalanceResponse {
+    pub wallet: String,
+    pub balance_lamports: u64,
+    pub balance_usd: Option<f64>,
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AppState;
+
+#[derive(Deserialize)]
+pub struct LeaderboardsParams {
+    /// Entries per leaderboard.
+    #[serde(default)]
+    pub limit: Option<u64>,
+
+    #[serde(skip)]
+    pub state: Option<AppState>,
+}
+
+#[derive(Serialize)]
+pub struct CanvasLeaderboardEntry {
+    pub rank: u64,
+    pub canvas_id: Uuid,
+    pub name: String,
+    pub owner_id: Uuid,
+    pub total_escrowed: i64,
+    /// Pixels of the canvas someone owns.
+    pub pixel_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct UserLeaderboardEntry {
+    pub rank: u64,
+    pub user_id: Uuid,
+    pub wallet_address: String,
+    pub username: Option<String>,
+    pub pixels_owned: i64,
+    /// Lamports bid on the pixels they own.
+    pub total_lamports: i64,
+}
+
+#[derive(Serialize)]
+pub struct LeaderboardsResponse {
+    /// Canvases by lamports in escrow.
+    pub canvases: Vec<CanvasLeaderboardEntry>,
+    /// Users by pixels owned.
+    pub users: Vec<UserLeaderboardEntry>,
+    /// How often the rankings are recomputed, and so how stale they can be.
+    pub refresh_interval_secs: u64,
+}
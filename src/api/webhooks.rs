@@ -0,0 +1,40 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    routing::post,
+};
+
+use crate::{
+    AppState,
+    services::webhooks::helius::{self, HeliusTransaction},
+};
+
+/// Acknowledged straight away so Helius does not retry while confirmations are
+/// verified, which can take as long as the confirmation timeout.
+async fn helius_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(transactions): Json<Vec<HeliusTransaction>>,
+) -> StatusCode {
+    if state.config.webhooks.helius_auth_header.is_none() {
+        return StatusCode::NOT_FOUND;
+    }
+
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !helius::is_authorized(&state.config.webhooks, authorization) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    tokio::spawn(async move {
+        helius::ingest_transactions(&state, transactions).await;
+    });
+
+    StatusCode::OK
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/helius", post(helius_webhook))
+}
@@ -0,0 +1,85 @@
+use clap::{Parser, Subcommand};
+use sea_orm_migration::MigrationStatus;
+
+use crate::{
+    error::{AppError, Result},
+    infrastructure::db::Database,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Pixel Archives backend",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    /// Apply pending migrations, then exit instead of serving.
+    #[arg(long)]
+    pub migrate_only: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Manage the database schema.
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// Apply pending migrations.
+    Up {
+        /// Apply only this many, oldest first. All of them when absent.
+        #[arg(short, long)]
+        num: Option<u32>,
+    },
+    /// Roll back applied migrations, newest first.
+    Down {
+        #[arg(short, long, default_value_t = 1)]
+        num: u32,
+    },
+    /// List every migration and whether it is applied.
+    Status,
+    /// Drop every table, then apply all migrations. Destroys all data.
+    Fresh {
+        /// Confirms that all data may be lost.
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+pub async fn run_migrate_command(db: &Database, command: MigrateCommand) -> Result<()> {
+    match command {
+        MigrateCommand::Up { num } => {
+            db.run_migrations(num).await?;
+            tracing::info!("Migrations applied");
+        }
+        MigrateCommand::Down { num } => {
+            db.rollback_migrations(num).await?;
+            tracing::info!("Rolled back {num} migrations");
+        }
+        MigrateCommand::Status => {
+            for (name, status) in db.migration_status().await? {
+                let status = match status {
+                    MigrationStatus::Applied => "applied",
+                    MigrationStatus::Pending => "pending",
+                };
+                println!("{status:<8} {name}");
+            }
+        }
+        MigrateCommand::Fresh { yes } => {
+            if !yes {
+                return Err(AppError::InvalidParams(
+                    "migrate fresh drops every table, pass --yes to confirm".into(),
+                ));
+            }
+            db.reset_schema().await?;
+            tracing::info!("Schema recreated from scratch");
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,12 @@
 use std::{env, str::FromStr, time::Duration};
 
-use crate::error::{AppError, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    error::{AppError, Result},
+    infrastructure::cache::metrics::KeyFamily,
+    services::{canvas::palette::parse_hex_color, nft::image::MAX_IMAGE_SCALE},
+};
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -10,6 +16,11 @@ pub struct Config {
     pub jwt: JwtConfig,
     pub canvas: CanvasConfig,
     pub solana: SolanaConfig,
+    pub image: ImageConfig,
+    pub storage: StorageConfig,
+    pub market: MarketConfig,
+    pub price: PriceConfig,
+    pub webhooks: WebhookConfig,
     pub rate_limit: RateLimitConfig,
 }
 
@@ -20,6 +31,43 @@ pub struct ServerConfig {
     pub cors_allowed_origins: Vec<String>,
     pub max_concurrent_requests: usize,
     pub server_public_url: String,
+    /// How often open WebSockets re-check that their user is still a canvas collaborator.
+    pub ws_auth_recheck_secs: u64,
+    /// How long a WebSocket stays open after its access token expires without a refresh.
+    pub ws_auth_grace_secs: u64,
+    /// Shortest gap between cursor positions relayed from one connection.
+    pub ws_cursor_interval_ms: u64,
+    /// Most pixels in one stroke preview.
+    pub ws_stroke_preview_max: usize,
+    /// Longest room chat message, in characters, and the shortest gap between two from
+    /// one connection.
+    pub ws_chat_max_length: usize,
+    pub ws_chat_interval_ms: u64,
+    /// Room updates kept for reconnecting clients to replay, and how long a quiet room's
+    /// buffer is kept. A buffer of zero turns replay off.
+    pub ws_replay_buffer: usize,
+    pub ws_replay_ttl_secs: u64,
+    /// How often the server pings open WebSockets, and how long it waits for a reply before
+    /// dropping the connection as dead.
+    pub ws_ping_interval_secs: u64,
+    pub ws_pong_timeout_secs: u64,
+    /// Token bucket for messages from one connection: sustained messages per second, burst
+    /// size, and how many times it may run dry before the connection is closed.
+    pub ws_message_rate: f64,
+    pub ws_message_burst: u32,
+    pub ws_message_warnings: u32,
+    /// Other canvas rooms one connection can subscribe to.
+    pub ws_max_subscriptions: usize,
+    /// Connections that fall behind their room more than `ws_max_lags` times within
+    /// `ws_lag_window_secs` are evicted instead of resynced. A single send taking longer
+    /// than `ws_send_timeout_ms` evicts the connection straight away.
+    pub ws_max_lags: usize,
+    pub ws_lag_window_secs: u64,
+    pub ws_send_timeout_ms: u64,
+    /// How often rooms are checked for deleted or burned canvases and leaked state.
+    pub ws_room_sweep_secs: u64,
+    /// Bearer token for `/api/metrics`, which is disabled without it.
+    pub metrics_token: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,11 +77,26 @@ pub struct DatabaseConfig {
     pub min_connections: u32,
     pub connect_timeout: Duration,
     pub idle_timeout: Duration,
+    /// Wait before retrying a statement that lost its connection.
+    pub retry_delay: Duration,
+    /// Longest a readiness probe waits for the database to answer.
+    pub health_timeout: Duration,
+    /// Apply pending migrations at startup. When off, the server refuses to start with any
+    /// pending, and they are applied with `migrate up`.
+    pub auto_migrate: bool,
+    /// How often the outbox dispatcher looks for events when nothing wakes it.
+    pub outbox_poll_interval: Duration,
+    /// Outbox events a dispatcher takes at a time.
+    pub outbox_batch_size: u64,
+    /// How long a dispatcher holds the events it took before another may deliver them.
+    pub outbox_lease: Duration,
 }
 
 #[derive(Debug, Clone)]
 pub struct CacheConfig {
     pub url: String,
+    /// Prefix of every Redis key, so environments can share an instance.
+    pub key_namespace: String,
     pub pool_size: usize,
     pub connect_timeout: Duration,
     pub local_canvas_max_capacity: u64,
@@ -44,6 +107,20 @@ pub struct CacheConfig {
     pub local_pixels_mid_ttl: u64,
     pub redis_cache_mid_ttl: u64,
     pub redis_cache_short_ttl: u64,
+    /// Age after which cached canvas pixels are refreshed in the background. They are still
+    /// served until `redis_cache_mid_ttl`.
+    pub pixels_soft_ttl: Duration,
+    /// Most recently active canvases loaded into the caches on startup. 0 skips warming.
+    pub warm_canvas_limit: u64,
+    /// How far back a pixel write makes a canvas count as active for warming.
+    pub warm_activity_hours: u64,
+    pub asset_dir: String,
+    /// Consecutive Redis failures before calls to it are skipped.
+    pub breaker_failure_threshold: u32,
+    /// How long calls skip Redis before one probes whether it is back.
+    pub breaker_open_duration: Duration,
+    /// Key families stored with the compact binary codec instead of JSON.
+    pub binary_codec_families: Vec<KeyFamily>,
 }
 
 #[derive(Debug, Clone)]
@@ -60,18 +137,167 @@ pub struct CanvasConfig {
     pub height: u8,
     pub color_count: u8,
     pub min_bid_lamports: u64,
+    /// Checks the bidder's balance on `pixel.place` and reports a shortfall with the
+    /// transaction, without rejecting the bid.
+    pub bid_balance_check: bool,
     pub cooldown_ms: u64,
     pub max_collaborators: usize,
+    /// Receive-only connections a room takes once its collaborator places are full.
+    pub max_spectators: usize,
     pub lock_ms: u64,
     pub mint_countdown_secs: u8,
+    pub paint_batch_max: usize,
+    pub max_pixel_watches: u64,
+    pub timelapse_max_changes: u64,
+    /// Days of pixel history kept, or 0 to keep all of it.
+    pub history_retention_days: u64,
+    /// How often pixel history past the retention period is pruned.
+    pub history_prune_interval_secs: u64,
+    /// How often the leaderboards behind `stats.leaderboards` are recomputed.
+    pub leaderboard_refresh_secs: u64,
+    pub max_royalty_bps: u16,
+    /// Numbered prints collaborators can mint of a minted canvas.
+    pub max_edition_prints: u32,
+    /// Owner-defined NFT attributes allowed per canvas.
+    pub max_custom_attributes: usize,
+}
+
+/// Defaults for rendering canvas stills. The image route can override each per request.
+#[derive(Debug, Clone)]
+pub struct ImageConfig {
+    pub scale: u32,
+    pub grid_lines: bool,
+    pub grid_color: String,
+    pub background: String,
+    pub transparent_unset: bool,
+}
+
+/// Cluster the RPC endpoint must belong to, checked against its genesis hash at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolanaCluster {
+    Devnet,
+    Testnet,
+    MainnetBeta,
+    /// A local test validator, whose genesis hash differs per ledger and is not checked.
+    Localnet,
+}
+
+impl SolanaCluster {
+    pub fn genesis_hash(self) -> Option<&'static str> {
+        match self {
+            SolanaCluster::Devnet => Some("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG"),
+            SolanaCluster::Testnet => Some("4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY"),
+            SolanaCluster::MainnetBeta => Some("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d"),
+            SolanaCluster::Localnet => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SolanaCluster::Devnet => "devnet",
+            SolanaCluster::Testnet => "testnet",
+            SolanaCluster::MainnetBeta => "mainnet-beta",
+            SolanaCluster::Localnet => "localnet",
+        }
+    }
+}
+
+impl FromStr for SolanaCluster {
+    type Err = ();
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "devnet" => Ok(SolanaCluster::Devnet),
+            "testnet" => Ok(SolanaCluster::Testnet),
+            "mainnet-beta" => Ok(SolanaCluster::MainnetBeta),
+            "localnet" => Ok(SolanaCluster::Localnet),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct SolanaConfig {
+    /// Serves Solana calls from the in-process mock instead of an RPC node.
+    pub mock: bool,
+    pub cluster: SolanaCluster,
     pub rpc_url: String,
-    pub program_id: String,
+    /// PubSub endpoint for signature notifications. `None` confirms by polling only.
+    pub ws_url: Option<String>,
+    pub program_id: Pubkey,
+    /// Earlier deployments still serving canvases published under them.
+    pub legacy_program_ids: Vec<Pubkey>,
     pub commitment: String,
     pub blockhash_ttl: u64,
+    /// RPC calls that hit rate limits, timeouts or unhealthy nodes are retried this many
+    /// times in total, backing off from the base delay up to the max.
+    pub rpc_max_attempts: u32,
+    pub rpc_retry_base_ms: u64,
+    pub rpc_retry_max_ms: u64,
+    /// Verified collection every canvas is minted into. Unset mints standalone NFTs.
+    pub collection_mint: Option<Pubkey>,
+    pub collection_name: String,
+    /// How often submitted mint signatures are polled, and when they are given up on.
+    pub mint_watch_interval_secs: u64,
+    pub mint_signature_timeout_secs: u64,
+    /// Canvases allowed in `Minting` at once across all owners. Each owner gets one.
+    pub max_concurrent_mints: usize,
+    /// How often minted NFTs are scanned for royalty payouts to their creators.
+    pub royalty_index_interval_secs: u64,
+    /// How often program transactions are scanned into the on-chain event index.
+    pub event_index_interval_secs: u64,
+    /// Also streams program transactions as they confirm, from `geyser_url` when set and
+    /// over PubSub otherwise, which needs `ws_url`.
+    pub event_stream: bool,
+    /// Yellowstone gRPC endpoint for the event stream. Needs the `geyser` feature.
+    pub geyser_url: Option<String>,
+    pub geyser_x_token: Option<String>,
+    /// How often stored canvases are reconciled with their on-chain accounts.
+    pub reconcile_interval_secs: u64,
+}
+
+/// `provider` is `magic_eden`, `tensor` or `none`. Tensor needs an API key.
+#[derive(Debug, Clone)]
+pub struct MarketConfig {
+    pub provider: String,
+    pub magic_eden_api_url: String,
+    pub tensor_api_url: String,
+    pub tensor_api_key: Option<String>,
+    pub cache_ttl_secs: u64,
+}
+
+/// `provider` is `pyth`, `coingecko` or `none`. The SOL/USD rate is cached for `cache_ttl_secs`.
+#[derive(Debug, Clone)]
+pub struct PriceConfig {
+    pub provider: String,
+    pub pyth_api_url: String,
+    pub pyth_sol_usd_feed_id: String,
+    pub coingecko_api_url: String,
+    pub cache_ttl_secs: u64,
+}
+
+/// Endpoints notified of canvas and mint lifecycle events. No URLs disables delivery.
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    pub secret: Option<String>,
+    pub max_attempts: u32,
+    pub timeout_secs: u64,
+    /// `Authorization` header Helius sends with enhanced transaction webhooks.
+    /// `/api/webhooks/helius` is disabled without it.
+    pub helius_auth_header: Option<String>,
+}
+
+/// `backend` is `ipfs`, `arweave` or `none`. IPFS pinning is skipped without a Pinata JWT.
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    pub backend: String,
+    pub pinata_jwt: Option<String>,
+    pub pinata_api_url: String,
+    pub ipfs_gateway_url: String,
+    pub irys_private_key: Option<String>,
+    pub irys_node_url: String,
+    pub arweave_gateway_url: String,
 }
 
 #[derive(Debug, Clone)]
@@ -86,6 +312,8 @@ impl Config {
     pub fn from_env() -> Result<Self> {
         dotenvy::dotenv().ok();
 
+        let rpc_url = env_required("SOLANA_RPC_URL")?;
+
         Ok(Self {
             server: ServerConfig {
                 host: env_or_default("HOST", "127.0.0.1"),
@@ -93,6 +321,27 @@ impl Config {
                 cors_allowed_origins: env_list("CORS_ALLOWED_ORIGINS", vec!["".into()]),
                 max_concurrent_requests: env_or_parse("SERVER_MAX_CONCURRENT_REQUESTS", 100)?,
                 server_public_url: env_required("SERVER_PUBLIC_URL")?,
+                ws_auth_recheck_secs: env_or_parse("WS_AUTH_RECHECK_SECS", 60)?,
+                ws_auth_grace_secs: env_or_parse("WS_AUTH_GRACE_SECS", 60)?,
+                ws_cursor_interval_ms: env_or_parse("WS_CURSOR_INTERVAL_MS", 50)?,
+                ws_stroke_preview_max: env_or_parse("WS_STROKE_PREVIEW_MAX", 64)?,
+                ws_chat_max_length: env_or_parse("WS_CHAT_MAX_LENGTH", 280)?,
+                ws_chat_interval_ms: env_or_parse("WS_CHAT_INTERVAL_MS", 1000)?,
+                ws_replay_buffer: env_or_parse("WS_REPLAY_BUFFER", 500)?,
+                ws_replay_ttl_secs: env_or_parse("WS_REPLAY_TTL_SECS", 86400)?,
+                ws_ping_interval_secs: env_or_parse("WS_PING_INTERVAL_SECS", 30)?,
+                ws_pong_timeout_secs: env_or_parse("WS_PONG_TIMEOUT_SECS", 10)?,
+                ws_message_rate: env_or_parse("WS_MESSAGE_RATE", 20.0)?,
+                ws_message_burst: env_or_parse("WS_MESSAGE_BURST", 40)?,
+                ws_message_warnings: env_or_parse("WS_MESSAGE_WARNINGS", 3)?,
+                ws_max_subscriptions: env_or_parse("WS_MAX_SUBSCRIPTIONS", 8)?,
+                ws_max_lags: env_or_parse("WS_MAX_LAGS", 3)?,
+                ws_lag_window_secs: env_or_parse("WS_LAG_WINDOW_SECS", 60)?,
+                ws_send_timeout_ms: env_or_parse("WS_SEND_TIMEOUT_MS", 5000)?,
+                ws_room_sweep_secs: env_or_parse("WS_ROOM_SWEEP_SECS", 60)?,
+                metrics_token: env::var("METRICS_TOKEN")
+                    .ok()
+                    .filter(|token| !token.is_empty()),
             },
             database: DatabaseConfig {
                 url: env_required("DATABASE_URL")?,
@@ -100,9 +349,19 @@ impl Config {
                 min_connections: env_or_parse("DB_MIN_CONNECTIONS", 5)?,
                 connect_timeout: Duration::from_secs(env_or_parse("DB_CONNECT_TIMEOUT_SECS", 10)?),
                 idle_timeout: Duration::from_secs(env_or_parse("DB_IDLE_TIMEOUT_SECS", 300)?),
+                retry_delay: Duration::from_millis(env_or_parse("DB_RETRY_DELAY_MS", 200)?),
+                health_timeout: Duration::from_secs(env_or_parse("DB_HEALTH_TIMEOUT_SECS", 2)?),
+                auto_migrate: env_or_parse("DB_AUTO_MIGRATE", true)?,
+                outbox_poll_interval: Duration::from_millis(env_or_parse(
+                    "OUTBOX_POLL_INTERVAL_MS",
+                    1000,
+                )?),
+                outbox_batch_size: env_or_parse("OUTBOX_BATCH_SIZE", 100)?,
+                outbox_lease: Duration::from_secs(env_or_parse("OUTBOX_LEASE_SECS", 30)?),
             },
             cache: CacheConfig {
                 url: env_required("CACHE_URL")?,
+                key_namespace: env_or("CACHE_KEY_NAMESPACE", ""),
                 pool_size: env_or_parse("CACHE_POOL_SIZE", 10)?,
                 connect_timeout: Duration::from_secs(env_or_parse(
                     "CACHE_CONNECT_TIMEOUT_SECS",
@@ -116,6 +375,26 @@ impl Config {
                 local_pixels_mid_ttl: env_or_parse("CACHE_LOCAL_PIXELS_MID_TTL", 10)?,
                 redis_cache_short_ttl: env_or_parse("CACHE_REDIS_SHORT_TTL", 120)?,
                 redis_cache_mid_ttl: env_or_parse("CACHE_REDIS_MID_TTL", 300)?,
+                pixels_soft_ttl: Duration::from_secs(env_or_parse(
+                    "CACHE_PIXELS_SOFT_TTL_SECS",
+                    60,
+                )?),
+                warm_canvas_limit: env_or_parse("CACHE_WARM_CANVASES", 50)?,
+                warm_activity_hours: env_or_parse("CACHE_WARM_ACTIVITY_HOURS", 24)?,
+                asset_dir: env_or("CACHE_ASSET_DIR", "data/nft-assets"),
+                breaker_failure_threshold: env_or_parse("CACHE_BREAKER_FAILURES", 5)?,
+                breaker_open_duration: Duration::from_secs(env_or_parse(
+                    "CACHE_BREAKER_OPEN_SECS",
+                    30,
+                )?),
+                binary_codec_families: env_list(
+                    "CACHE_BINARY_FAMILIES",
+                    vec!["session".to_string(), "pixels".to_string()],
+                )
+                .iter()
+                .filter(|family| !family.is_empty())
+                .map(|family| family.parse().map_err(AppError::InvalidParams))
+                .collect::<Result<_>>()?,
             },
             jwt: JwtConfig {
                 secret: env_required("JWT_SECRET")?,
@@ -132,16 +411,118 @@ impl Config {
                 height: env_or_parse("CANVAS_HEIGHT", 32)?,
                 color_count: env_or_parse("CANVAS_COLORS", 64)?,
                 min_bid_lamports: env_or_parse("MIN_BID_LAMPORTS", 1_000_000)?, // 0.001 SOL
+                bid_balance_check: env_or_parse("BID_BALANCE_CHECK", true)?,
                 cooldown_ms: env_or_parse("PIXEL_COOLDOWN_MS", 5000)?,
                 max_collaborators: env_or_parse("MAX_COLLABORATORS", 50)?,
+                max_spectators: env_or_parse("MAX_SPECTATORS", 200)?,
                 lock_ms: env_or_parse("PIXEL_LOCK_MS", 60000)?,
                 mint_countdown_secs: env_or_parse("MINT_COUNTDOWN_SECS", 30)?,
+                paint_batch_max: env_or_parse("PAINT_BATCH_MAX", 64)?,
+                max_pixel_watches: env_or_parse("MAX_PIXEL_WATCHES", 100)?,
+                timelapse_max_changes: env_or_parse("TIMELAPSE_MAX_CHANGES", 10000)?,
+                history_retention_days: env_or_parse("PIXEL_HISTORY_RETENTION_DAYS", 0)?,
+                history_prune_interval_secs: env_or_parse(
+                    "PIXEL_HISTORY_PRUNE_INTERVAL_SECS",
+                    3600,
+                )?,
+                leaderboard_refresh_secs: env_or_parse("LEADERBOARD_REFRESH_SECS", 300)?,
+                max_royalty_bps: env_or_parse("MAX_ROYALTY_BPS", 1000)?, // 10%
+                max_edition_prints: env_or_parse("MAX_EDITION_PRINTS", 100)?,
+                max_custom_attributes: env_or_parse("MAX_CUSTOM_ATTRIBUTES", 10)?,
             },
             solana: SolanaConfig {
-                rpc_url: env_required("SOLANA_RPC_URL")?,
-                program_id: env_required("SOLANA_PROGRAM_ID")?,
+                mock: env_or_parse("SOLANA_MOCK", false)?,
+                rpc_url: rpc_url.clone(),
+                ws_url: match env::var("SOLANA_WS_URL") {
+                    Ok(url) if url == "none" => None,
+                    Ok(url) if !url.is_empty() => Some(url),
+                    _ => default_ws_url(&rpc_url),
+                },
+                cluster: env_or_parse("SOLANA_CLUSTER", SolanaCluster::Devnet)?,
+                program_id: parse_pubkey("SOLANA_PROGRAM_ID", &env_required("SOLANA_PROGRAM_ID")?)?,
+                legacy_program_ids: env_list("SOLANA_LEGACY_PROGRAM_IDS", Vec::new())
+                    .into_iter()
+                    .filter(|program_id| !program_id.is_empty())
+                    .map(|program_id| parse_pubkey("SOLANA_LEGACY_PROGRAM_IDS", &program_id))
+                    .collect::<Result<_>>()?,
                 commitment: env_or("SOLANA_COMMITMENT", "confirmed"),
                 blockhash_ttl: env_or_parse("SOLANA_BLOCKHASH_TTL", 15)?,
+                rpc_max_attempts: env_or_parse("SOLANA_RPC_MAX_ATTEMPTS", 4)?,
+                rpc_retry_base_ms: env_or_parse("SOLANA_RPC_RETRY_BASE_MS", 250)?,
+                rpc_retry_max_ms: env_or_parse("SOLANA_RPC_RETRY_MAX_MS", 4000)?,
+                collection_mint: env::var("NFT_COLLECTION_MINT")
+                    .ok()
+                    .filter(|mint| !mint.is_empty())
+                    .map(|mint| parse_pubkey("NFT_COLLECTION_MINT", &mint))
+                    .transpose()?,
+                collection_name: env_or("NFT_COLLECTION_NAME", "Pixel Archives"),
+                mint_watch_interval_secs: env_or_parse("MINT_WATCH_INTERVAL_SECS", 15)?,
+                mint_signature_timeout_secs: env_or_parse("MINT_SIGNATURE_TIMEOUT_SECS", 600)?,
+                max_concurrent_mints: env_or_parse("MAX_CONCURRENT_MINTS", 5)?,
+                royalty_index_interval_secs: env_or_parse("ROYALTY_INDEX_INTERVAL_SECS", 300)?,
+                event_index_interval_secs: env_or_parse("EVENT_INDEX_INTERVAL_SECS", 30)?,
+                event_stream: env_or_parse("SOLANA_EVENT_STREAM", false)?,
+                geyser_url: env::var("SOLANA_GEYSER_URL")
+                    .ok()
+                    .filter(|url| !url.is_empty()),
+                geyser_x_token: env::var("SOLANA_GEYSER_X_TOKEN")
+                    .ok()
+                    .filter(|token| !token.is_empty()),
+                reconcile_interval_secs: env_or_parse("RECONCILE_INTERVAL_SECS", 600)?,
+            },
+            image: ImageConfig {
+                scale: env_or_parse("IMAGE_SCALE", 16)?,
+                grid_lines: env_or_parse("IMAGE_GRID_LINES", false)?,
+                grid_color: env_or("IMAGE_GRID_COLOR", "#000000"),
+                background: env_or("IMAGE_BACKGROUND", "#ffffff"),
+                transparent_unset: env_or_parse("IMAGE_TRANSPARENT_UNSET", false)?,
+            },
+            storage: StorageConfig {
+                backend: env_or("STORAGE_BACKEND", "ipfs"),
+                pinata_jwt: env::var("PINATA_JWT").ok().filter(|jwt| !jwt.is_empty()),
+                pinata_api_url: env_or("PINATA_API_URL", "https://api.pinata.cloud"),
+                ipfs_gateway_url: env_or("IPFS_GATEWAY_URL", "https://gateway.pinata.cloud/ipfs"),
+                irys_private_key: env::var("IRYS_PRIVATE_KEY")
+                    .ok()
+                    .filter(|key| !key.is_empty()),
+                irys_node_url: env_or("IRYS_NODE_URL", "https://node1.irys.xyz"),
+                arweave_gateway_url: env_or("ARWEAVE_GATEWAY_URL", "https://gateway.irys.xyz"),
+            },
+            webhooks: WebhookConfig {
+                urls: env_list("WEBHOOK_URLS", Vec::new())
+                    .into_iter()
+                    .filter(|url| !url.is_empty())
+                    .collect(),
+                secret: env::var("WEBHOOK_SECRET")
+                    .ok()
+                    .filter(|secret| !secret.is_empty()),
+                max_attempts: env_or_parse("WEBHOOK_MAX_ATTEMPTS", 5)?,
+                timeout_secs: env_or_parse("WEBHOOK_TIMEOUT_SECS", 10)?,
+                helius_auth_header: env::var("HELIUS_WEBHOOK_AUTH")
+                    .ok()
+                    .filter(|header| !header.is_empty()),
+            },
+            market: MarketConfig {
+                provider: env_or("MARKET_PROVIDER", "magic_eden"),
+                magic_eden_api_url: env_or(
+                    "MAGIC_EDEN_API_URL",
+                    "https://api-mainnet.magiceden.dev/v2",
+                ),
+                tensor_api_url: env_or("TENSOR_API_URL", "https://api.mainnet.tensordev.io"),
+                tensor_api_key: env::var("TENSOR_API_KEY")
+                    .ok()
+                    .filter(|key| !key.is_empty()),
+                cache_ttl_secs: env_or_parse("MARKET_CACHE_TTL_SECS", 60)?,
+            },
+            price: PriceConfig {
+                provider: env_or("PRICE_PROVIDER", "pyth"),
+                pyth_api_url: env_or("PYTH_API_URL", "https://hermes.pyth.network"),
+                pyth_sol_usd_feed_id: env_or(
+                    "PYTH_SOL_USD_FEED_ID",
+                    "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d",
+                ),
+                coingecko_api_url: env_or("COINGECKO_API_URL", "https://api.coingecko.com/api/v3"),
+                cache_ttl_secs: env_or_parse("PRICE_CACHE_TTL_SECS", 60)?,
             },
             rate_limit: RateLimitConfig {
                 auth_limit: env_or_parse("RATE_LIMIT_AUTH", 10)?,
@@ -159,12 +540,167 @@ impl Config {
             ));
         }
 
+        if self.database.health_timeout.is_zero() {
+            return Err(AppError::InvalidParams(
+                "DB_HEALTH_TIMEOUT_SECS must be positive".into(),
+            ));
+        }
+
+        if self.database.outbox_poll_interval.is_zero()
+            || self.database.outbox_batch_size == 0
+            || self.database.outbox_lease.is_zero()
+        {
+            return Err(AppError::InvalidParams(
+                "OUTBOX_POLL_INTERVAL_MS, OUTBOX_BATCH_SIZE and OUTBOX_LEASE_SECS must be positive"
+                    .into(),
+            ));
+        }
+
         if self.canvas.width == 0 || self.canvas.height == 0 {
             return Err(AppError::InvalidParams(
                 "Canvas dimensions must be positive".into(),
             ));
         }
 
+        if self.cache.breaker_failure_threshold == 0 || self.cache.breaker_open_duration.is_zero() {
+            return Err(AppError::InvalidParams(
+                "CACHE_BREAKER_FAILURES and CACHE_BREAKER_OPEN_SECS must be positive".into(),
+            ));
+        }
+
+        if let Some(family) = self
+            .cache
+            .binary_codec_families
+            .iter()
+            .find(|family| !family.supports_binary())
+        {
+            return Err(AppError::InvalidParams(format!(
+                "CACHE_BINARY_FAMILIES cannot include {}",
+                family.as_str()
+            )));
+        }
+
+        if self.cache.pixels_soft_ttl.as_secs() >= self.cache.redis_cache_mid_ttl {
+            return Err(AppError::InvalidParams(
+                "CACHE_PIXELS_SOFT_TTL_SECS must be less than CACHE_REDIS_MID_TTL".into(),
+            ));
+        }
+
+        if self.canvas.max_royalty_bps > 10_000 {
+            return Err(AppError::InvalidParams(
+                "MAX_ROYALTY_BPS cannot exceed 10000".into(),
+            ));
+        }
+
+        if self
+            .solana
+            .legacy_program_ids
+            .contains(&self.solana.program_id)
+        {
+            return Err(AppError::InvalidParams(
+                "SOLANA_LEGACY_PROGRAM_IDS cannot include SOLANA_PROGRAM_ID".into(),
+            ));
+        }
+
+        if self.solana.mock && self.solana.cluster == SolanaCluster::MainnetBeta {
+            return Err(AppError::InvalidParams(
+                "SOLANA_MOCK cannot be used with SOLANA_CLUSTER=mainnet-beta".into(),
+            ));
+        }
+
+        if self.solana.geyser_url.is_some() && !cfg!(feature = "geyser") {
+            return Err(AppError::InvalidParams(
+                "SOLANA_GEYSER_URL requires building with the geyser feature".into(),
+            ));
+        }
+
+        if self.solana.event_stream
+            && self.solana.ws_url.is_none()
+            && self.solana.geyser_url.is_none()
+            && !self.solana.mock
+        {
+            return Err(AppError::InvalidParams(
+                "SOLANA_EVENT_STREAM requires a SOLANA_WS_URL or SOLANA_GEYSER_URL".into(),
+            ));
+        }
+
+        if self.server.ws_auth_recheck_secs == 0 {
+            return Err(AppError::InvalidParams(
+                "WS_AUTH_RECHECK_SECS must be positive".into(),
+            ));
+        }
+
+        if self.server.ws_ping_interval_secs == 0 || self.server.ws_pong_timeout_secs == 0 {
+            return Err(AppError::InvalidParams(
+                "WS_PING_INTERVAL_SECS and WS_PONG_TIMEOUT_SECS must be positive".into(),
+            ));
+        }
+
+        if self.server.ws_message_rate <= 0.0 || self.server.ws_message_burst == 0 {
+            return Err(AppError::InvalidParams(
+                "WS_MESSAGE_RATE and WS_MESSAGE_BURST must be positive".into(),
+            ));
+        }
+
+        if self.canvas.history_prune_interval_secs == 0 {
+            return Err(AppError::InvalidParams(
+                "PIXEL_HISTORY_PRUNE_INTERVAL_SECS must be positive".into(),
+            ));
+        }
+
+        if self.canvas.leaderboard_refresh_secs == 0 {
+            return Err(AppError::InvalidParams(
+                "LEADERBOARD_REFRESH_SECS must be positive".into(),
+            ));
+        }
+
+        if self.server.ws_room_sweep_secs == 0 {
+            return Err(AppError::InvalidParams(
+                "WS_ROOM_SWEEP_SECS must be positive".into(),
+            ));
+        }
+
+        if self.server.ws_lag_window_secs == 0 || self.server.ws_send_timeout_ms == 0 {
+            return Err(AppError::InvalidParams(
+                "WS_LAG_WINDOW_SECS and WS_SEND_TIMEOUT_MS must be positive".into(),
+            ));
+        }
+
+        if self.server.ws_replay_buffer > 0 && self.server.ws_replay_ttl_secs == 0 {
+            return Err(AppError::InvalidParams(
+                "WS_REPLAY_TTL_SECS must be positive".into(),
+            ));
+        }
+
+        if self.solana.max_concurrent_mints == 0 {
+            return Err(AppError::InvalidParams(
+                "MAX_CONCURRENT_MINTS must be positive".into(),
+            ));
+        }
+
+        if self.image.scale == 0 || self.image.scale > MAX_IMAGE_SCALE {
+            return Err(AppError::InvalidParams(format!(
+                "IMAGE_SCALE must be between 1 and {MAX_IMAGE_SCALE}"
+            )));
+        }
+
+        for (key, color) in [
+            ("IMAGE_GRID_COLOR", &self.image.grid_color),
+            ("IMAGE_BACKGROUND", &self.image.background),
+        ] {
+            if parse_hex_color(color).is_none() {
+                return Err(AppError::InvalidParams(format!(
+                    "{key} must be a #rrggbb color"
+                )));
+            }
+        }
+
+        if !self.webhooks.urls.is_empty() && self.webhooks.secret.is_none() {
+            return Err(AppError::InvalidParams(
+                "WEBHOOK_SECRET is required when WEBHOOK_URLS is set".into(),
+            ));
+        }
+
         if self.canvas.color_count == 0 {
             return Err(AppError::InvalidParams(
                 "Color count must be positive".into(),
@@ -175,6 +711,17 @@ impl Config {
     }
 }
 
+/// RPC providers serve PubSub on the same host, over `ws(s)://`.
+fn default_ws_url(rpc_url: &str) -> Option<String> {
+    if let Some(rest) = rpc_url.strip_prefix("https://") {
+        Some(format!("wss://{rest}"))
+    } else {
+        rpc_url
+            .strip_prefix("http://")
+            .map(|rest| format!("ws://{rest}"))
+    }
+}
+
 fn env_or_default(key: &str, default: &str) -> String {
     env::var(key).unwrap_or_else(|_| default.to_string())
 }
@@ -192,6 +739,11 @@ fn env_required(key: &str) -> Result<String> {
     env::var(key).map_err(|_| AppError::InvalidParams(format!("{key} is required")))
 }
 
+fn parse_pubkey(key: &str, value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value)
+        .map_err(|_| AppError::InvalidParams(format!("Invalid public key in {key}: {value}")))
+}
+
 fn env_list(key: &str, default: Vec<String>) -> Vec<String> {
     env::var(key)
         .map(|val| {
@@ -58,6 +58,10 @@ pub enum AppError {
     #[error("Redis error - {0}")]
     RedisError(#[from] redis::RedisError),
 
+    /// Redis is failing and calls to it are skipped until it recovers.
+    #[error("Cache unavailable")]
+    CacheUnavailable,
+
     #[error("Serialization error - {0}")]
     SerializationError(#[from] serde_json::Error),
 
@@ -76,6 +80,10 @@ pub enum AppError {
     #[error("Canvas name already exists")]
     CanvasNameExists,
 
+    /// Every invite code drawn for a new canvas was already taken.
+    #[error("Invite code conflict")]
+    InviteCodeConflict,
+
     #[error("User already exists")]
     UserExists,
 
@@ -94,20 +102,73 @@ pub enum AppError {
     #[error("Not canvas owner")]
     NotCanvasOwner,
 
+    #[error("Must own a pixel on this canvas")]
+    NotPixelContributor,
+
+    #[error("Admin role required")]
+    NotAdmin,
+
     #[error("Pixel locked")]
     PixelLocked,
 
+    #[error("Transaction signature already used")]
+    SignatureAlreadyUsed,
+
+    #[error("Pixel was modified concurrently")]
+    PixelVersionConflict,
+
     #[error("Solana RPC error - {0}")]
     SolanaRpc(String),
 
+    #[error("Storage error - {0}")]
+    StorageError(String),
+
+    #[error("Marketplace error - {0}")]
+    MarketplaceError(String),
+
+    #[error("Price oracle error - {0}")]
+    PriceOracleError(String),
+
     #[error("Transaction failed - {0}")]
     TransactionFailed(String),
 
     #[error("Cooldown active - {remaining_ms}ms remaining")]
     CooldownActive { remaining_ms: u64 },
 
+    #[error("Pixel quota exceeded - {limit} pixels per {window_secs}s")]
+    PixelQuotaExceeded {
+        limit: u32,
+        window_secs: u64,
+        reset_in_ms: u64,
+    },
+
     #[error("Bid too low - minimum is {min_lamports} lamports")]
-    BidTooLow { min_lamports: u64 },
+    BidTooLow {
+        min_lamports: u64,
+        min_usd: Option<f64>,
+    },
+
+    #[error("Bid too high - maximum is {max_lamports} lamports")]
+    BidTooHigh {
+        max_lamports: u64,
+        max_usd: Option<f64>,
+    },
+
+    #[error("Canvas account already exists on-chain - {canvas_pda}")]
+    CanvasAccountExists { canvas_pda: String },
+
+    #[error("Insufficient balance - {shortfall_lamports} lamports short")]
+    InsufficientBalance {
+        balance_lamports: u64,
+        required_lamports: u64,
+        shortfall_lamports: u64,
+    },
+
+    #[error("Muted on this canvas - {remaining_secs}s remaining")]
+    UserMuted { remaining_secs: u64 },
+
+    #[error("Mint queued - position {position}")]
+    MintQueued { position: u64 },
 
     #[error("TryInitError - {0}")]
     TryInitError(#[from] tracing_subscriber::util::TryInitError),
@@ -125,11 +186,16 @@ impl AppError {
             Self::CanvasNotFound => -32030,
             Self::InvalidCanvasStateTransition => -32031,
             Self::RedisError(_) => -32071,
+            Self::CacheUnavailable => -32076,
             Self::SerializationError(_) => -32072,
+            Self::StorageError(_) => -32073,
+            Self::MarketplaceError(_) => -32074,
+            Self::PriceOracleError(_) => -32075,
             Self::Unauthorized => -32020,
             Self::TokenExpired => -32021,
             Self::InvalidSignature => -32012,
             Self::CanvasNameExists => -32037,
+            Self::InviteCodeConflict => -32039,
             Self::IoError(_) => -32080,
             Self::UserExists => -32010,
             Self::UsernameExists => -32013,
@@ -137,17 +203,42 @@ impl AppError {
             Self::MethodNotFound(_) => -32601,
             Self::NotCanvasCollaborator => -32035,
             Self::NotCanvasOwner => -32034,
+            Self::NotPixelContributor => -32036,
+            Self::NotAdmin => -32022,
             Self::PixelLocked => -32040,
+            Self::PixelVersionConflict => -32044,
             Self::SolanaRpc(_) => -32061,
             Self::TransactionFailed(_) => -32060,
+            Self::SignatureAlreadyUsed => -32062,
+            Self::InsufficientBalance { .. } => -32063,
+            Self::CanvasAccountExists { .. } => -32064,
 
             Self::CooldownActive { .. } => -32042,
+            Self::PixelQuotaExceeded { .. } => -32043,
             Self::BidTooLow { .. } => -32041,
+            Self::BidTooHigh { .. } => -32045,
+            Self::UserMuted { .. } => -32046,
+            Self::MintQueued { .. } => -32038,
             Self::TryInitError(_) => -32080,
             Self::RateLimitExceeded => -32081,
         }
     }
 
+    /// Whether the error means Redis could not be reached, rather than that it rejected a
+    /// command.
+    pub fn is_cache_outage(&self) -> bool {
+        match self {
+            Self::CacheUnavailable => true,
+            Self::RedisError(error) => {
+                error.is_io_error()
+                    || error.is_connection_refusal()
+                    || error.is_connection_dropped()
+                    || error.is_timeout()
+            }
+            _ => false,
+        }
+    }
+
     pub fn user_safe_format(&self) -> JsonRpcError {
         match self {
             Self::InvalidParams(msg) => JsonRpcError {
@@ -164,6 +255,11 @@ impl AppError {
                     data: None,
                 }
             }
+            Self::CacheUnavailable => JsonRpcError {
+                code: self.code(),
+                message: "Service temporarily unavailable. Please try again later.".to_string(),
+                data: None,
+            },
             Self::SerializationError(error) => {
                 tracing::error!(error = %error, "Serialization error");
 
@@ -219,6 +315,35 @@ impl AppError {
                     data: None,
                 }
             }
+            Self::StorageError(error) => {
+                tracing::error!(error = %error, "Storage error");
+
+                JsonRpcError {
+                    code: self.code(),
+                    message: "Failed to upload NFT assets. Please try again.".to_string(),
+                    data: None,
+                }
+            }
+            Self::MarketplaceError(error) => {
+                tracing::error!(error = %error, "Marketplace error");
+
+                JsonRpcError {
+                    code: self.code(),
+                    message: "Marketplace data is unavailable right now. Please try again later."
+                        .to_string(),
+                    data: None,
+                }
+            }
+            Self::PriceOracleError(error) => {
+                tracing::error!(error = %error, "Price oracle error");
+
+                JsonRpcError {
+                    code: self.code(),
+                    message: "Price data is unavailable right now. Please try again later."
+                        .to_string(),
+                    data: None,
+                }
+            }
             Self::TransactionFailed(error) => {
                 tracing::error!(error = %error, "Transaction failed");
 
@@ -270,6 +395,11 @@ impl AppError {
                     .to_string(),
                 data: None,
             },
+            Self::InviteCodeConflict => JsonRpcError {
+                code: self.code(),
+                message: "Could not create the canvas right now. Please try again.".to_string(),
+                data: None,
+            },
             Self::UserNotFound => JsonRpcError {
                 code: self.code(),
                 message: "No account found for this wallet. Please register first.".to_string(),
@@ -288,13 +418,101 @@ impl AppError {
                 ),
                 data: Some(serde_json::json!({ "remaining_ms": remaining_ms })),
             },
-            Self::BidTooLow { min_lamports } => JsonRpcError {
+            Self::PixelQuotaExceeded {
+                limit,
+                window_secs,
+                reset_in_ms,
+            } => JsonRpcError {
+                code: self.code(),
+                message: format!(
+                    "Pixel quota of {} per {}s reached on this canvas.",
+                    limit, window_secs
+                ),
+                data: Some(serde_json::json!({
+                    "limit": limit,
+                    "window_secs": window_secs,
+                    "reset_in_ms": reset_in_ms,
+                })),
+            },
+            Self::BidTooLow {
+                min_lamports,
+                min_usd,
+            } => JsonRpcError {
+                code: self.code(),
+                message: match min_usd {
+                    Some(usd) => format!(
+                        "Bid too low. Minimum is {} SOL (about ${:.2}).",
+                        (*min_lamports as f64) / 1_000_000_000.0,
+                        usd
+                    ),
+                    None => format!(
+                        "Bid too low. Minimum is {} SOL.",
+                        (*min_lamports as f64) / 1_000_000_000.0
+                    ),
+                },
+                data: Some(serde_json::json!({
+                    "min_lamports": min_lamports,
+                    "min_usd": min_usd,
+                })),
+            },
+            Self::BidTooHigh {
+                max_lamports,
+                max_usd,
+            } => JsonRpcError {
+                code: self.code(),
+                message: match max_usd {
+                    Some(usd) => format!(
+                        "Bid too high. Maximum is {} SOL (about ${:.2}).",
+                        (*max_lamports as f64) / 1_000_000_000.0,
+                        usd
+                    ),
+                    None => format!(
+                        "Bid too high. Maximum is {} SOL.",
+                        (*max_lamports as f64) / 1_000_000_000.0
+                    ),
+                },
+                data: Some(serde_json::json!({
+                    "max_lamports": max_lamports,
+                    "max_usd": max_usd,
+                })),
+            },
+            Self::InsufficientBalance {
+                balance_lamports,
+                required_lamports,
+                shortfall_lamports,
+            } => JsonRpcError {
+                code: self.code(),
+                message: format!(
+                    "Not enough SOL. This transaction needs {} SOL and the wallet is {} SOL short.",
+                    (*required_lamports as f64) / 1_000_000_000.0,
+                    (*shortfall_lamports as f64) / 1_000_000_000.0
+                ),
+                data: Some(serde_json::json!({
+                    "balance_lamports": balance_lamports,
+                    "required_lamports": required_lamports,
+                    "shortfall_lamports": shortfall_lamports,
+                })),
+            },
+            Self::CanvasAccountExists { canvas_pda } => JsonRpcError {
+                code: self.code(),
+                message: "This canvas already has an on-chain account.".to_string(),
+                data: Some(serde_json::json!({ "canvas_pda": canvas_pda })),
+            },
+            Self::MintQueued { position } => JsonRpcError {
+                code: self.code(),
+                message: format!(
+                    "Other mints are in progress. Your canvas is number {} in the queue.",
+                    position
+                ),
+                data: Some(serde_json::json!({ "position": position })),
+            },
+            Self::UserMuted { remaining_secs } => JsonRpcError {
                 code: self.code(),
                 message: format!(
-                    "Bid too low. Minimum is {} SOL.",
-                    (*min_lamports as f64) / 1_000_000_000.0
+                    "You are muted on this canvas for another {}s.",
+                    remaining_secs
                 ),
-                data: Some(serde_json::json!({ "min_lamports": min_lamports })),
+                data: Some(serde_json::json!({ "remaining_secs": remaining_secs })),
             },
             Self::RateLimitExceeded => JsonRpcError {
                 code: self.code(),
@@ -325,14 +543,27 @@ impl IntoResponse for AppError {
                 StatusCode::UNAUTHORIZED
             }
             Self::UserNotFound | Self::CanvasNotFound => StatusCode::NOT_FOUND,
-            Self::UserExists | Self::UsernameExists | Self::CanvasNameExists => {
-                StatusCode::CONFLICT
-            }
-            Self::InvalidParams(_) | Self::InvalidCanvasStateTransition => StatusCode::BAD_REQUEST,
-            Self::NotCanvasCollaborator | Self::NotCanvasOwner => StatusCode::FORBIDDEN,
-            Self::CooldownActive { .. } | Self::BidTooLow { .. } | Self::PixelLocked => {
-                StatusCode::TOO_MANY_REQUESTS
-            }
+            Self::UserExists
+            | Self::UsernameExists
+            | Self::CanvasNameExists
+            | Self::InviteCodeConflict
+            | Self::PixelVersionConflict
+            | Self::SignatureAlreadyUsed
+            | Self::CanvasAccountExists { .. } => StatusCode::CONFLICT,
+            Self::InvalidParams(_)
+            | Self::InvalidCanvasStateTransition
+            | Self::InsufficientBalance { .. } => StatusCode::BAD_REQUEST,
+            Self::NotCanvasCollaborator
+            | Self::NotCanvasOwner
+            | Self::NotPixelContributor
+            | Self::NotAdmin
+            | Self::UserMuted { .. } => StatusCode::FORBIDDEN,
+            Self::CooldownActive { .. }
+            | Self::PixelQuotaExceeded { .. }
+            | Self::BidTooLow { .. }
+            | Self::BidTooHigh { .. }
+            | Self::PixelLocked => StatusCode::TOO_MANY_REQUESTS,
+            Self::CacheUnavailable => StatusCode::SERVICE_UNAVAILABLE,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
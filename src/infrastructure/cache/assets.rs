@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::{config::CacheConfig, error::Result};
+
+/// Content-addressed file store for rendered NFT assets.
+pub struct AssetStore {
+    root: PathBuf,
+}
+
+impl AssetStore {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            root: PathBuf::from(&config.asset_dir),
+        }
+    }
+
+    pub fn content_hash(bytes: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(bytes))
+    }
+
+    /// Writes the bytes under their content hash and returns the hash.
+    /// Existing entries are left untouched.
+    pub async fn put(&self, bytes: &[u8]) -> Result<String> {
+        let hash = Self::content_hash(bytes);
+        let path = self.path_for(&hash);
+
+        if fs::try_exists(&path).await? {
+            return Ok(hash);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // Write then rename so readers never see a partial file.
+        let temp_path = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4().simple()));
+        fs::write(&temp_path, bytes).await?;
+        fs::rename(&temp_path, &path).await?;
+
+        Ok(hash)
+    }
+
+    pub async fn get(&self, hash: &str) -> Result<Option<Vec<u8>>> {
+        if !is_content_hash(hash) {
+            return Ok(None);
+        }
+
+        match fs::read(self.path_for(hash)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn path_for(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2]).join(hash)
+    }
+}
+
+fn is_content_hash(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
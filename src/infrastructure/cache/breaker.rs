@@ -0,0 +1,111 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::error::Result;
+
+#[derive(Clone, Copy, Debug)]
+enum BreakerState {
+    Closed {
+        failures: u32,
+    },
+    /// Calls fail fast until `until`, then one is let through to probe Redis.
+    Open {
+        until: Instant,
+    },
+    /// A probe started at `since` is in flight. Other calls keep failing fast until it
+    /// settles, or until it has taken so long it was probably dropped.
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+/// Stops calling Redis after `failure_threshold` consecutive failures, so an outage costs
+/// callers an immediate `CacheUnavailable` instead of a connect timeout each. After
+/// `open_for`, one call probes Redis and closes the breaker again if it succeeds.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_for,
+            state: Mutex::new(BreakerState::Closed { failures: 0 }),
+        }
+    }
+
+    /// Whether a call may go to Redis now.
+    pub fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let probe = match *state {
+            BreakerState::Closed { .. } => return true,
+            BreakerState::Open { until } => now >= until,
+            BreakerState::HalfOpen { since } => now.duration_since(since) >= self.open_for,
+        };
+        if probe {
+            *state = BreakerState::HalfOpen { since: now };
+        }
+        probe
+    }
+
+    /// Whether Redis is believed reachable. Unlike `allow`, never starts a probe.
+    pub fn is_closed(&self) -> bool {
+        matches!(
+            *self.state.lock().unwrap_or_else(|e| e.into_inner()),
+            BreakerState::Closed { .. }
+        )
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*state, BreakerState::HalfOpen { .. }) {
+            tracing::info!("Redis reachable again, resuming cache use");
+        }
+        *state = BreakerState::Closed { failures: 0 };
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let open = match *state {
+            BreakerState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                *state = BreakerState::Closed {
+                    failures: failures + 1,
+                };
+                false
+            }
+            BreakerState::Closed { .. } => {
+                tracing::warn!(
+                    "Redis failed {} times in a row, degrading for {:?}",
+                    self.failure_threshold,
+                    self.open_for
+                );
+                true
+            }
+            BreakerState::HalfOpen { .. } => true,
+            BreakerState::Open { .. } => false,
+        };
+        if open {
+            *state = BreakerState::Open {
+                until: Instant::now() + self.open_for,
+            };
+        }
+    }
+}
+
+/// `fallback` when the call failed only because Redis is unavailable, so callers can skip
+/// caching and throttles that are not worth failing a request over.
+pub fn or_degraded<T>(result: Result<T>, fallback: T) -> Result<T> {
+    match result {
+        Err(e) if e.is_cache_outage() => {
+            tracing::debug!("Skipping Redis while degraded: {e}");
+            Ok(fallback)
+        }
+        result => result,
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::error::{AppError, Result};
+
+/// How values are encoded in Redis. Binary is smaller and cheaper to process, JSON can be
+/// read by scripts and by people inspecting Redis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    Json,
+    Binary,
+}
+
+impl Codec {
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(value)?),
+            Self::Binary => bincode::serialize(value).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to encode cache value: {e}"))
+            }),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(bytes)?),
+            Self::Binary => bincode::deserialize(bytes).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to decode cache value: {e}"))
+            }),
+        }
+    }
+}
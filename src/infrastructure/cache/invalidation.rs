@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use futures::{StreamExt, future::BoxFuture};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::infrastructure::{
+    cache::{breaker::or_degraded, keys::CacheKey, local::LocalCache, redis::RedisCache},
+    db::changes::{ChangeObserver, DataChange},
+};
+
+/// Wait before subscribing again after the subscription drops.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+/// A local cache entry that another instance must drop after a write.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Invalidation {
+    Canvas { canvas_id: Uuid },
+    Pixels { canvas_id: Uuid },
+    PixelWatchers { canvas_id: Uuid },
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct InvalidationMessage {
+    /// Instance that published it, which has already dropped its own entry.
+    pub origin: Uuid,
+    #[serde(flatten)]
+    pub invalidation: Invalidation,
+}
+
+impl LocalCache {
+    pub async fn apply(&self, invalidation: Invalidation) {
+        match invalidation {
+            Invalidation::Canvas { canvas_id } => self.invalidate_canvas(&canvas_id).await,
+            Invalidation::Pixels { canvas_id } => self.invalidate_pixels(&canvas_id).await,
+            Invalidation::PixelWatchers { canvas_id } => {
+                self.invalidate_pixel_watchers(&canvas_id).await
+            }
+        }
+    }
+}
+
+/// Drops local entries on this instance and tells the other instances to drop theirs.
+pub struct CacheInvalidator {
+    pub(super) local: LocalCache,
+    pub(super) redis: RedisCache,
+    /// Tells this instance's invalidations apart from other instances'.
+    pub(super) instance_id: Uuid,
+}
+
+impl CacheInvalidator {
+    pub async fn invalidate(&self, invalidation: Invalidation) {
+        self.local.apply(invalidation).await;
+        self.notify_instances(invalidation).await;
+    }
+
+    /// Tells the other instances to drop a local entry this one already dealt with. Failures
+    /// are logged: their entries then expire on their own TTL.
+    pub async fn notify_instances(&self, invalidation: Invalidation) {
+        let message = InvalidationMessage {
+            origin: self.instance_id,
+            invalidation,
+        };
+        let published = match serde_json::to_string(&message) {
+            Ok(payload) => {
+                self.redis
+                    .publish(&CacheKey::invalidation_channel(), &payload)
+                    .await
+            }
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = or_degraded(published, ()) {
+            tracing::warn!("Failed to publish cache invalidation {invalidation:?}: {e}");
+        }
+    }
+}
+
+impl ChangeObserver for CacheInvalidator {
+    fn changed(&self, change: DataChange) -> BoxFuture<'_, ()> {
+        let invalidation = match change {
+            DataChange::Canvas { canvas_id } => Invalidation::Canvas { canvas_id },
+        };
+        Box::pin(self.invalidate(invalidation))
+    }
+}
+
+/// Drops local entries invalidated by other instances for as long as the runtime runs.
+/// Messages published while the subscription is down are lost, so every local entry is
+/// dropped whenever it is (re)established.
+pub(super) fn spawn_listener(
+    local: LocalCache,
+    redis: RedisCache,
+    instance_id: Uuid,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let channel = CacheKey::invalidation_channel();
+
+        loop {
+            match redis.subscribe(&channel).await {
+                Ok(mut payloads) => {
+                    local.invalidate_all();
+
+                    while let Some(payload) = payloads.next().await {
+                        match serde_json::from_str::<InvalidationMessage>(&payload) {
+                            Ok(message) if message.origin == instance_id => {}
+                            Ok(message) => local.apply(message.invalidation).await,
+                            Err(e) => tracing::warn!("Ignoring malformed cache invalidation: {e}"),
+                        }
+                    }
+                    tracing::warn!("Cache invalidation subscription closed");
+                }
+                Err(e) => tracing::warn!("Failed to subscribe to cache invalidations: {e}"),
+            }
+
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    })
+}
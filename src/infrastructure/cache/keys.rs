@@ -1,29 +1,158 @@
+use std::{fmt::Display, sync::OnceLock};
+
 use uuid::Uuid;
 
+/// Prefix of every key, such as `pa:prod:`, so environments can share a Redis instance.
+static NAMESPACE: OnceLock<String> = OnceLock::new();
+
 pub struct CacheKey;
 
 impl CacheKey {
+    /// Sets the prefix of every key. Only the first call has an effect, so it is set once on
+    /// startup, before any key is built.
+    pub fn set_namespace(namespace: &str) {
+        let _ = NAMESPACE.set(namespace.to_string());
+    }
+
+    pub fn namespace() -> &'static str {
+        NAMESPACE.get().map_or("", String::as_str)
+    }
+
+    /// The key as built without a namespace.
+    pub fn strip_namespace(key: &str) -> &str {
+        key.strip_prefix(Self::namespace()).unwrap_or(key)
+    }
+
+    fn namespaced(key: impl Display) -> String {
+        format!("{}{key}", Self::namespace())
+    }
+
     pub fn canvas_pixels(id: &Uuid) -> String {
-        format!("canvas:{id}:pixels")
+        Self::namespaced(format_args!("canvas:{id}:pixels"))
+    }
+
+    /// Hash of the canvas's owned pixels, cached beside its colors.
+    pub fn canvas_pixel_owners(id: &Uuid) -> String {
+        Self::namespaced(format_args!("canvas:{id}:pixels:owners"))
+    }
+
+    /// Set while the cached canvas pixels are fresh.
+    pub fn canvas_pixels_fresh(id: &Uuid) -> String {
+        Self::namespaced(format_args!("canvas:{id}:pixels:fresh"))
+    }
+
+    /// Held by the one reader refreshing stale canvas pixels.
+    pub fn canvas_pixels_refresh(id: &Uuid) -> String {
+        Self::namespaced(format_args!("lock:refresh:canvas:{id}:pixels"))
     }
 
     pub fn user_session(user_id: &Uuid) -> String {
-        format!("user:session:{user_id}")
+        Self::namespaced(format_args!("user:session:{user_id}"))
+    }
+
+    /// The user's role, read together with their cooldown on placements.
+    pub fn user_role(user_id: &Uuid) -> String {
+        Self::namespaced(format_args!("user:role:{user_id}"))
     }
 
     pub fn token_blacklist(jti: &str) -> String {
-        format!("token:blacklist:{jti}")
+        Self::namespaced(format_args!("token:blacklist:{jti}"))
     }
 
     pub fn canvas_lock(canvas_id: &Uuid) -> String {
-        format!("lock:canvas:{canvas_id}")
+        Self::namespaced(format_args!("lock:canvas:{canvas_id}"))
+    }
+
+    /// Prefix of a rate limiter's window counters.
+    pub fn rate_limit(scope: &str) -> String {
+        Self::namespaced(format_args!("rate:{scope}"))
+    }
+
+    pub fn auth_nonce(wallet: &str, nonce: &str) -> String {
+        Self::namespaced(format_args!("auth:nonce:{wallet}:{nonce}"))
     }
 
     pub fn cooldown(user_id: &Uuid) -> String {
-        format!("cooldown:{user_id}")
+        Self::namespaced(format_args!("cooldown:{user_id}"))
+    }
+
+    pub fn pixel_quota(canvas_id: &Uuid, user_id: &Uuid, window_index: u64) -> String {
+        Self::namespaced(format_args!(
+            "quota:pixel:{canvas_id}:{user_id}:{window_index}"
+        ))
+    }
+
+    pub fn consumed_signature(signature: &str) -> String {
+        Self::namespaced(format_args!("signature:consumed:{signature}"))
+    }
+
+    pub fn pending_confirmation(signature: &str) -> String {
+        Self::namespaced(format_args!("pending:confirmation:{signature}"))
+    }
+
+    pub fn metadata_update(canvas_id: &Uuid) -> String {
+        Self::namespaced(format_args!("pending:metadata:{canvas_id}"))
+    }
+
+    pub fn pending_escrow_claim(canvas_id: &Uuid, user_id: &Uuid) -> String {
+        Self::namespaced(format_args!("pending:escrow_claim:{canvas_id}:{user_id}"))
+    }
+
+    pub fn onchain_event_cursor(program_id: &str) -> String {
+        Self::namespaced(format_args!("onchain:events:cursor:{}", program_id))
+    }
+
+    pub fn canvas_divergence(canvas_id: &Uuid) -> String {
+        Self::namespaced(format_args!("reconcile:divergence:{canvas_id}"))
+    }
+
+    /// Pub/sub channel local cache invalidations are published on.
+    pub fn invalidation_channel() -> String {
+        Self::namespaced("invalidate:local")
+    }
+
+    pub fn canvas_mute(canvas_id: &Uuid, user_id: &Uuid) -> String {
+        Self::namespaced(format_args!("mute:canvas:{canvas_id}:{user_id}"))
+    }
+
+    pub fn canvas_presence(canvas_id: &Uuid) -> String {
+        Self::namespaced(format_args!("presence:canvas:{canvas_id}"))
+    }
+
+    pub fn room_seq(canvas_id: &Uuid) -> String {
+        Self::namespaced(format_args!("room:{canvas_id}:seq"))
+    }
+
+    pub fn room_events(canvas_id: &Uuid) -> String {
+        Self::namespaced(format_args!("room:{canvas_id}:events"))
+    }
+
+    /// Pub/sub channel room updates are relayed to the other instances on.
+    pub fn room_fanout_channel() -> String {
+        Self::namespaced("room:fanout")
+    }
+
+    pub fn mint_queue() -> String {
+        Self::namespaced("mint:queue")
+    }
+
+    pub fn mint_queue_lock() -> String {
+        Self::namespaced("lock:mint:queue")
+    }
+
+    pub fn image_variant(canvas_id: &Uuid, render_tag: &str) -> String {
+        Self::namespaced(format_args!("image:{canvas_id}:{render_tag}"))
+    }
+
+    pub fn market_info(mint_address: &str) -> String {
+        Self::namespaced(format_args!("market:{mint_address}"))
+    }
+
+    pub fn sol_usd_price() -> String {
+        Self::namespaced("price:sol_usd")
     }
 
     pub fn pixel_lock(canvas_id: &Uuid, x: u8, y: u8) -> String {
-        format!("lock:pixel:{canvas_id}:{x}:{y}")
+        Self::namespaced(format_args!("lock:pixel:{canvas_id}:{x}:{y}"))
     }
 }
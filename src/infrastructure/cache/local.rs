@@ -1,10 +1,20 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use moka::future::Cache;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::{config::CacheConfig, infrastructure::db::entities::canvas};
+use crate::{
+    config::CacheConfig,
+    error::Result,
+    infrastructure::{
+        cache::{
+            metrics::{CacheMetrics, KeyFamily},
+            stampede::{JitteredTtl, SingleFlight},
+        },
+        db::entities::canvas,
+    },
+};
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -16,10 +26,17 @@ pub struct CachedPixel {
     pub price_lamports: i64,
 }
 
+/// Users watching each pixel of a canvas, by `(x, y)`.
+pub type PixelWatchers = HashMap<(i16, i16), Vec<Uuid>>;
+
 #[derive(Clone)]
 pub struct LocalCache {
     canvas_cache: Cache<Uuid, Arc<canvas::Model>>,
     pixels_cache: Cache<Uuid, Arc<RwLock<Vec<CachedPixel>>>>,
+    watchers_cache: Cache<Uuid, Arc<PixelWatchers>>,
+    metrics: Arc<CacheMetrics>,
+    canvas_loads: Arc<SingleFlight<Uuid, Arc<canvas::Model>>>,
+    watcher_loads: Arc<SingleFlight<Uuid, Arc<PixelWatchers>>>,
 }
 
 impl LocalCache {
@@ -27,20 +44,64 @@ impl LocalCache {
         Self {
             canvas_cache: Cache::builder()
                 .max_capacity(cache_config.local_canvas_max_capacity)
-                .time_to_live(Duration::from_secs(cache_config.local_canvas_mid_ttl))
+                .expire_after(JitteredTtl(Duration::from_secs(
+                    cache_config.local_canvas_mid_ttl,
+                )))
                 .time_to_idle(Duration::from_secs(cache_config.local_canvas_short_ttl))
                 .build(),
 
             pixels_cache: Cache::builder()
                 .max_capacity(cache_config.local_pixels_max_capacity)
-                .time_to_live(Duration::from_secs(cache_config.local_pixels_mid_ttl))
+                .expire_after(JitteredTtl(Duration::from_secs(
+                    cache_config.local_pixels_mid_ttl,
+                )))
                 .time_to_idle(Duration::from_secs(cache_config.local_pixels_short_ttl))
                 .build(),
+
+            // Watches are as small and as often read as canvases
+            watchers_cache: Cache::builder()
+                .max_capacity(cache_config.local_canvas_max_capacity)
+                .expire_after(JitteredTtl(Duration::from_secs(
+                    cache_config.local_canvas_mid_ttl,
+                )))
+                .time_to_idle(Duration::from_secs(cache_config.local_canvas_short_ttl))
+                .build(),
+
+            metrics: Arc::default(),
+            canvas_loads: Arc::default(),
+            watcher_loads: Arc::default(),
         }
     }
 
+    pub fn metrics(&self) -> &CacheMetrics {
+        &self.metrics
+    }
+
     pub async fn get_canvas(&self, id: &Uuid) -> Option<Arc<canvas::Model>> {
-        self.canvas_cache.get(id).await
+        let op = self.metrics.start(KeyFamily::Canvas);
+        let canvas = self.canvas_cache.get(id).await;
+        op.found(canvas.is_some());
+        canvas
+    }
+
+    /// The cached canvas, or else the one `load` finds, which is then cached. Concurrent
+    /// misses for the same canvas share one load.
+    pub async fn get_or_load_canvas<F, Fut>(&self, id: Uuid, load: F) -> Result<Arc<canvas::Model>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<canvas::Model>>,
+    {
+        if let Some(canvas) = self.get_canvas(&id).await {
+            return Ok(canvas);
+        }
+
+        self.canvas_loads
+            .run(id, || async {
+                let canvas = Arc::new(load().await?);
+                self.canvas_cache.insert(id, canvas.clone()).await;
+                Ok(canvas)
+            })
+            .await
     }
 
     pub async fn set_canvas(&self, canvas: canvas::Model) {
@@ -55,6 +116,45 @@ impl LocalCache {
         self.pixels_cache.invalidate(canvas_id).await;
     }
 
+    /// The cached watchers of the canvas, or else the ones `load` finds, which are then
+    /// cached. Concurrent misses for the same canvas share one load.
+    pub async fn get_or_load_pixel_watchers<F, Fut>(
+        &self,
+        canvas_id: Uuid,
+        load: F,
+    ) -> Result<Arc<PixelWatchers>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<PixelWatchers>>,
+    {
+        let op = self.metrics.start(KeyFamily::Other);
+        let cached = self.watchers_cache.get(&canvas_id).await;
+        op.found(cached.is_some());
+        if let Some(watchers) = cached {
+            return Ok(watchers);
+        }
+
+        self.watcher_loads
+            .run(canvas_id, || async {
+                let watchers = Arc::new(load().await?);
+                self.watchers_cache
+                    .insert(canvas_id, watchers.clone())
+                    .await;
+                Ok(watchers)
+            })
+            .await
+    }
+
+    pub async fn invalidate_pixel_watchers(&self, canvas_id: &Uuid) {
+        self.watchers_cache.invalidate(canvas_id).await;
+    }
+
+    pub fn invalidate_all(&self) {
+        self.canvas_cache.invalidate_all();
+        self.pixels_cache.invalidate_all();
+        self.watchers_cache.invalidate_all();
+    }
+
     pub async fn update_pixel(
         &self,
         canvas_id: &Uuid,
@@ -64,7 +164,11 @@ impl LocalCache {
         owner_id: Option<Uuid>,
         price: i64,
     ) {
-        if let Some(pixels) = self.pixels_cache.get(canvas_id).await {
+        let op = self.metrics.start(KeyFamily::Pixels);
+        let cached = self.pixels_cache.get(canvas_id).await;
+        op.found(cached.is_some());
+
+        if let Some(pixels) = cached {
             let mut pixels = pixels.write().await;
 
             if let Some(pixel) = pixels.iter_mut().find(|p| p.x == x && p.y == y) {
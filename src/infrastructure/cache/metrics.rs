@@ -0,0 +1,198 @@
+use std::{
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::infrastructure::cache::keys::CacheKey;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: [f64; 10] = [
+    0.0001, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.5,
+];
+
+/// What a cache key holds, so entries with different access patterns are measured apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyFamily {
+    Canvas,
+    Pixels,
+    Session,
+    Locks,
+    RateLimits,
+    Other,
+}
+
+impl KeyFamily {
+    pub const ALL: [KeyFamily; 6] = [
+        Self::Canvas,
+        Self::Pixels,
+        Self::Session,
+        Self::Locks,
+        Self::RateLimits,
+        Self::Other,
+    ];
+
+    /// The family of a Redis key, from the prefixes `CacheKey` builds.
+    pub fn of(key: &str) -> Self {
+        let key = CacheKey::strip_namespace(key);
+        if key.starts_with("canvas:") && key.contains(":pixels") {
+            Self::Pixels
+        } else if key.starts_with("user:session:") {
+            Self::Session
+        } else if key.starts_with("lock:") {
+            Self::Locks
+        } else if key.starts_with("rate:") {
+            Self::RateLimits
+        } else {
+            Self::Other
+        }
+    }
+
+    /// The family shared by all the keys, or `Other` when they differ.
+    pub fn common<K: AsRef<str>>(keys: &[K]) -> Self {
+        let mut families = keys.iter().map(|key| Self::of(key.as_ref()));
+        let first = families.next().unwrap_or(Self::Other);
+        if families.all(|family| family == first) {
+            first
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Canvas => "canvas",
+            Self::Pixels => "pixels",
+            Self::Session => "session",
+            Self::Locks => "locks",
+            Self::RateLimits => "rate_limits",
+            Self::Other => "other",
+        }
+    }
+
+    /// Whether the family's values may be stored with the binary codec. Other families hold
+    /// counters that Lua scripts and `INCR` read as text.
+    pub fn supports_binary(self) -> bool {
+        matches!(self, Self::Session | Self::Pixels | Self::Locks)
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Default)]
+struct FamilyMetrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    errors: AtomicU64,
+    /// Operations at or under each bucket's bound, not cumulative.
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    operations: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+impl FromStr for KeyFamily {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|family| family.as_str() == value)
+            .ok_or_else(|| format!("Unknown cache key family: {value}"))
+    }
+}
+
+/// One family's figures at the time they were read. Bucket counts are cumulative, as
+/// Prometheus histograms expect.
+pub struct FamilySnapshot {
+    pub family: KeyFamily,
+    pub hits: u64,
+    pub misses: u64,
+    pub errors: u64,
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    pub operations: u64,
+    pub latency_secs: f64,
+}
+
+/// Hit, miss and error counts and operation latencies of one cache tier, per key family.
+#[derive(Default)]
+pub struct CacheMetrics {
+    families: [FamilyMetrics; KeyFamily::ALL.len()],
+}
+
+impl CacheMetrics {
+    /// Starts timing an operation. Its latency is recorded when the returned guard drops.
+    pub fn start(&self, family: KeyFamily) -> CacheOp<'_> {
+        CacheOp {
+            metrics: &self.families[family.index()],
+            started: Instant::now(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<FamilySnapshot> {
+        KeyFamily::ALL
+            .iter()
+            .map(|&family| {
+                let metrics = &self.families[family.index()];
+                let mut buckets = [0; LATENCY_BUCKETS.len()];
+                let mut cumulative = 0;
+                for (bucket, count) in buckets.iter_mut().zip(&metrics.buckets) {
+                    cumulative += count.load(Ordering::Relaxed);
+                    *bucket = cumulative;
+                }
+
+                FamilySnapshot {
+                    family,
+                    hits: metrics.hits.load(Ordering::Relaxed),
+                    misses: metrics.misses.load(Ordering::Relaxed),
+                    errors: metrics.errors.load(Ordering::Relaxed),
+                    buckets,
+                    operations: metrics.operations.load(Ordering::Relaxed),
+                    latency_secs: metrics.latency_micros.load(Ordering::Relaxed) as f64
+                        / 1_000_000.0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A cache operation being timed.
+pub struct CacheOp<'a> {
+    metrics: &'a FamilyMetrics,
+    started: Instant,
+}
+
+impl CacheOp<'_> {
+    pub fn hit(&self) {
+        self.metrics.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn miss(&self) {
+        self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn error(&self) {
+        self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a lookup as a hit or a miss.
+    pub fn found(&self, found: bool) {
+        if found { self.hit() } else { self.miss() }
+    }
+}
+
+impl Drop for CacheOp<'_> {
+    fn drop(&mut self) {
+        let elapsed: Duration = self.started.elapsed();
+        let secs = elapsed.as_secs_f64();
+
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|&bound| secs <= bound) {
+            self.metrics.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        self.metrics.operations.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .latency_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+}
@@ -1,22 +1,98 @@
+pub mod assets;
+pub mod breaker;
+pub mod codec;
+pub mod invalidation;
 pub mod keys;
 pub mod local;
+pub mod metrics;
+pub mod pixels;
 pub mod redis;
+pub mod stampede;
+
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+use uuid::Uuid;
 
 use crate::config::Config;
 use crate::error::Result;
+use crate::infrastructure::cache::assets::AssetStore;
+use crate::infrastructure::cache::invalidation::{CacheInvalidator, Invalidation};
+use crate::infrastructure::cache::keys::CacheKey;
 use crate::infrastructure::cache::local::LocalCache;
 use crate::infrastructure::cache::redis::RedisCache;
+use crate::infrastructure::db::changes::ChangeObserver;
 
 pub struct Cache {
     pub local: LocalCache,
     pub redis: RedisCache,
+    pub assets: AssetStore,
+    invalidator: Arc<CacheInvalidator>,
 }
 
 impl Cache {
     pub async fn init(config: &Config) -> Result<Self> {
+        CacheKey::set_namespace(&config.cache.key_namespace);
+
+        let local = LocalCache::new(&config.cache);
+        let redis = RedisCache::connect(&config.cache).await?;
+        let invalidator = CacheInvalidator {
+            local: local.clone(),
+            redis: redis.clone(),
+            instance_id: Uuid::new_v4(),
+        };
+
         Ok(Self {
-            local: LocalCache::new(&config.cache),
-            redis: RedisCache::connect(&config.cache).await?,
+            local,
+            redis,
+            assets: AssetStore::new(&config.cache),
+            invalidator: Arc::new(invalidator),
         })
     }
+
+    /// Drops the canvas from the local cache of every instance.
+    pub async fn invalidate_canvas(&self, canvas_id: &Uuid) {
+        self.invalidator
+            .invalidate(Invalidation::Canvas {
+                canvas_id: *canvas_id,
+            })
+            .await;
+    }
+
+    /// Drops the canvas pixels from the local cache of every instance.
+    pub async fn invalidate_pixels(&self, canvas_id: &Uuid) {
+        self.invalidator
+            .invalidate(Invalidation::Pixels {
+                canvas_id: *canvas_id,
+            })
+            .await;
+    }
+
+    /// Drops the canvas's pixel watchers from the local cache of every instance.
+    pub async fn invalidate_pixel_watchers(&self, canvas_id: &Uuid) {
+        self.invalidator
+            .invalidate(Invalidation::PixelWatchers {
+                canvas_id: *canvas_id,
+            })
+            .await;
+    }
+
+    /// Tells the other instances to drop a local entry this one already dealt with.
+    pub async fn notify_instances(&self, invalidation: Invalidation) {
+        self.invalidator.notify_instances(invalidation).await;
+    }
+
+    /// Invalidates cached data as the database reports changes to it.
+    pub fn change_observer(&self) -> Arc<dyn ChangeObserver> {
+        self.invalidator.clone()
+    }
+
+    /// Starts applying the other instances' invalidations to the local cache.
+    pub fn listen_for_invalidations(&self) -> JoinHandle<()> {
+        invalidation::spawn_listener(
+            self.local.clone(),
+            self.redis.clone(),
+            self.invalidator.instance_id,
+        )
+    }
 }
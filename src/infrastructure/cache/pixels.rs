@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use deadpool_redis::redis;
+use serde::{Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::cache::{keys::CacheKey, metrics::KeyFamily, redis::RedisCache},
+};
+
+/// Writes one pixel into a cached canvas, unless the canvas is not cached. The owners hash
+/// is given the colors' remaining TTL, since writing to it may have just created it.
+/// Returns whether the canvas was cached.
+const UPDATE_PIXEL_SCRIPT: &str = r#"
+local ttl = redis.call('PTTL', KEYS[1])
+if ttl < 0 then
+    return 0
+end
+redis.call('SETRANGE', KEYS[1], ARGV[1], ARGV[2])
+if ARGV[4] == '' then
+    redis.call('HDEL', KEYS[2], ARGV[3])
+else
+    redis.call('HSET', KEYS[2], ARGV[3], ARGV[4])
+    redis.call('PEXPIRE', KEYS[2], ttl)
+end
+return 1
+"#;
+
+/// A canvas's pixels as cached in Redis.
+pub struct CachedCanvasPixels<T> {
+    /// One byte per pixel, in row order.
+    pub colors: Vec<u8>,
+    pub owned: Vec<T>,
+    /// Past its soft TTL: still served, but due for a refresh.
+    pub stale: bool,
+}
+
+/// Hash field of an owned pixel.
+fn owner_field(x: i16, y: i16) -> String {
+    format!("{x}:{y}")
+}
+
+/// Canvas pixels are kept as raw color bytes next to a hash of owned pixels, so a placement
+/// updates one byte in place rather than dropping the whole entry.
+impl RedisCache {
+    pub async fn get_canvas_pixels<T: DeserializeOwned>(
+        &self,
+        canvas_id: &Uuid,
+    ) -> Result<Option<CachedCanvasPixels<T>>> {
+        let op = self.metrics().start(KeyFamily::Pixels);
+        let mut redis_connection = self.connection(&op).await?;
+
+        let (colors, owned, fresh): (Option<Vec<u8>>, Vec<Vec<u8>>, bool) = self.observe(
+            &op,
+            redis::pipe()
+                .get(CacheKey::canvas_pixels(canvas_id))
+                .hvals(CacheKey::canvas_pixel_owners(canvas_id))
+                .exists(CacheKey::canvas_pixels_fresh(canvas_id))
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
+        op.found(colors.is_some());
+
+        let Some(colors) = colors else {
+            return Ok(None);
+        };
+        let codec = self.codec(KeyFamily::Pixels);
+        let owned = owned
+            .iter()
+            .map(|value| codec.decode(value))
+            .collect::<Result<_>>()?;
+
+        Ok(Some(CachedCanvasPixels {
+            colors,
+            owned,
+            stale: !fresh,
+        }))
+    }
+
+    /// Replaces the cached pixels of a canvas. They are served until `hard_ttl`, and reported
+    /// stale after `soft_ttl`.
+    pub async fn set_canvas_pixels<T: Serialize>(
+        &self,
+        canvas_id: &Uuid,
+        colors: &[u8],
+        owned: &[(i16, i16, T)],
+        soft_ttl: Duration,
+        hard_ttl: Duration,
+    ) -> Result<()> {
+        let colors_key = CacheKey::canvas_pixels(canvas_id);
+        let owners_key = CacheKey::canvas_pixel_owners(canvas_id);
+
+        let mut pipeline = redis::pipe();
+        pipeline
+            .atomic()
+            .set_ex(&colors_key, colors, hard_ttl.as_secs())
+            .ignore()
+            .del(&owners_key)
+            .ignore()
+            .set_ex(
+                CacheKey::canvas_pixels_fresh(canvas_id),
+                true,
+                soft_ttl.as_secs(),
+            )
+            .ignore();
+        if !owned.is_empty() {
+            let codec = self.codec(KeyFamily::Pixels);
+            let fields = owned
+                .iter()
+                .map(|(x, y, entry)| Ok((owner_field(*x, *y), codec.encode(entry)?)))
+                .collect::<Result<Vec<_>>>()?;
+            pipeline
+                .hset_multiple(&owners_key, &fields)
+                .ignore()
+                .expire(&owners_key, hard_ttl.as_secs() as i64)
+                .ignore();
+        }
+
+        let op = self.metrics().start(KeyFamily::Pixels);
+        let mut redis_connection = self.connection(&op).await?;
+        self.observe(
+            &op,
+            pipeline.query_async::<()>(&mut *redis_connection).await,
+        )
+    }
+
+    /// Writes one pixel into the cached canvas, leaving its TTLs alone. `owned` is the pixel's
+    /// owner entry, or `None` if nobody owns it. Returns false if the canvas was not cached,
+    /// which is left for the next read to load.
+    pub async fn update_canvas_pixel<T: Serialize>(
+        &self,
+        canvas_id: &Uuid,
+        index: usize,
+        (x, y, color): (i16, i16, u8),
+        owned: Option<&T>,
+    ) -> Result<bool> {
+        let owned = match owned {
+            Some(entry) => self.codec(KeyFamily::Pixels).encode(entry)?,
+            None => Vec::new(),
+        };
+
+        let op = self.metrics().start(KeyFamily::Pixels);
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(
+            &op,
+            redis::cmd("EVAL")
+                .arg(UPDATE_PIXEL_SCRIPT)
+                .arg(2)
+                .arg(CacheKey::canvas_pixels(canvas_id))
+                .arg(CacheKey::canvas_pixel_owners(canvas_id))
+                .arg(index)
+                .arg(&[color][..])
+                .arg(owner_field(x, y))
+                .arg(owned)
+                .query_async(&mut *redis_connection)
+                .await,
+        )
+    }
+
+    pub async fn delete_canvas_pixels(&self, canvas_id: &Uuid) -> Result<()> {
+        self.del_many(&[
+            CacheKey::canvas_pixels(canvas_id),
+            CacheKey::canvas_pixel_owners(canvas_id),
+            CacheKey::canvas_pixels_fresh(canvas_id),
+        ])
+        .await
+    }
+}
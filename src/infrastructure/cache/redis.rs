@@ -1,19 +1,91 @@
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use deadpool_redis::{
-    Config as PoolConfig, Pool, Runtime,
-    redis::{self, AsyncCommands},
+    Config as PoolConfig, Connection, Pool, Runtime,
+    redis::{self, AsyncCommands, RedisResult},
 };
+use futures::{Stream, StreamExt};
 use serde::{Serialize, de::DeserializeOwned};
 
 use crate::{
     config::CacheConfig,
     error::{AppError, Result},
+    infrastructure::cache::{
+        breaker::CircuitBreaker,
+        codec::Codec,
+        metrics::{CacheMetrics, CacheOp, KeyFamily},
+    },
 };
 
+/// Numbers an entry with the next counter value and appends it to a capped stream, so the
+/// counter and the stream never disagree.
+const APPEND_SEQUENCED_SCRIPT: &str = r#"
+local seq = redis.call('INCR', KEYS[1])
+redis.call('XADD', KEYS[2], 'MAXLEN', '~', ARGV[1], '*', 'seq', seq, 'entry', ARGV[3])
+redis.call('EXPIRE', KEYS[1], ARGV[2])
+redis.call('EXPIRE', KEYS[2], ARGV[2])
+return seq
+"#;
+
+/// Counts a hit in the current window unless the weighted count of both windows already
+/// reached the limit. The current window's counter expires from its first hit, so later hits
+/// do not push its expiry back. Returns `{allowed, remaining}`.
+const SLIDING_WINDOW_HIT_SCRIPT: &str = r#"
+local current = tonumber(redis.call('GET', KEYS[1]) or '0')
+local previous = tonumber(redis.call('GET', KEYS[2]) or '0')
+local limit = tonumber(ARGV[1])
+local weighted = math.ceil(previous * tonumber(ARGV[2]) + current)
+if weighted >= limit then
+    return {0, 0}
+end
+if redis.call('INCR', KEYS[1]) == 1 then
+    redis.call('EXPIRE', KEYS[1], ARGV[3])
+end
+return {1, math.max(limit - weighted - 1, 0)}
+"#;
+
+/// An `XRANGE` entry: its ID and fields.
+type StreamEntry = (String, HashMap<String, String>);
+
+/// Values read together by `RedisCache::mget`, in the order their keys were given, with the
+/// codec of each key.
+pub struct MultiGet(Vec<(Codec, Option<Vec<u8>>)>);
+
+impl MultiGet {
+    /// The value of the key at `index`, if it was set.
+    pub fn get<T: DeserializeOwned>(&self, index: usize) -> Result<Option<T>> {
+        match self.0.get(index) {
+            Some((codec, Some(value))) => Ok(decode(*codec, value)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.0.get(index).is_some_and(|(_, value)| value.is_some())
+    }
+}
+
+/// A value that does not decode, such as one written before its family's codec changed,
+/// counts as missing and is reloaded by the caller.
+fn decode<T: DeserializeOwned>(codec: Codec, value: &[u8]) -> Option<T> {
+    match codec.decode(value) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!("Ignoring a cached value that does not decode: {e}");
+            None
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct RedisCache {
     pool: Pool,
+    /// Opens the dedicated connections subscriptions need, which cannot come from the pool.
+    client: redis::Client,
+    breaker: Arc<CircuitBreaker>,
+    metrics: Arc<CacheMetrics>,
+    /// Families whose values are stored with the binary codec rather than JSON.
+    binary_families: Arc<[KeyFamily]>,
 }
 
 impl RedisCache {
@@ -37,84 +109,407 @@ impl RedisCache {
             .query_async(&mut *redis_connection)
             .await?;
 
-        Ok(Self { pool })
+        let client = redis::Client::open(cache_config.url.as_str())?;
+        let breaker = CircuitBreaker::new(
+            cache_config.breaker_failure_threshold,
+            cache_config.breaker_open_duration,
+        );
+
+        Ok(Self {
+            pool,
+            client,
+            breaker: Arc::new(breaker),
+            metrics: Arc::default(),
+            binary_families: cache_config.binary_codec_families.as_slice().into(),
+        })
+    }
+
+    /// Whether Redis is believed reachable. Callers with a fallback can skip it when not.
+    pub fn is_available(&self) -> bool {
+        self.breaker.is_closed()
+    }
+
+    /// A pooled connection, unless the breaker is open.
+    pub(super) async fn connection(&self, op: &CacheOp<'_>) -> Result<Connection> {
+        if !self.breaker.allow() {
+            op.error();
+            return Err(AppError::CacheUnavailable);
+        }
+
+        self.pool.get().await.map_err(|e| {
+            tracing::warn!("Failed to get a Redis connection: {e}");
+            self.breaker.record_failure();
+            op.error();
+            AppError::CacheUnavailable
+        })
+    }
+
+    /// Counts the command's outcome towards the breaker. Errors Redis itself returned, such
+    /// as a wrong type, say nothing about its health and leave it alone.
+    pub(super) fn observe<T>(&self, op: &CacheOp<'_>, result: RedisResult<T>) -> Result<T> {
+        let result = result.map_err(AppError::from);
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(e) => {
+                op.error();
+                if e.is_cache_outage() {
+                    self.breaker.record_failure();
+                }
+            }
+        }
+        result
+    }
+
+    pub fn metrics(&self) -> &CacheMetrics {
+        &self.metrics
+    }
+
+    /// How values of the family are encoded.
+    pub fn codec(&self, family: KeyFamily) -> Codec {
+        if self.binary_families.contains(&family) {
+            Codec::Binary
+        } else {
+            Codec::Json
+        }
     }
 
     pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
-        let mut redis_connection = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let value: Option<Vec<u8>> = self.observe(&op, redis_connection.get(key).await)?;
+        op.found(value.is_some());
+        Ok(value.and_then(|value| decode(self.codec(KeyFamily::of(key)), &value)))
+    }
 
-        let value: Option<String> = redis_connection.get(key).await?;
-        match value {
-            Some(val) => Ok(Some(serde_json::from_str(&val)?)),
-            None => Ok(None),
+    /// Reads several keys in one round trip. The values may be of different types.
+    pub async fn mget(&self, keys: &[String]) -> Result<MultiGet> {
+        if keys.is_empty() {
+            return Ok(MultiGet(Vec::new()));
         }
+
+        let op = self.metrics.start(KeyFamily::common(keys));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let values: Vec<Option<Vec<u8>>> = self.observe(
+            &op,
+            redis::cmd("MGET")
+                .arg(keys)
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
+        for value in &values {
+            op.found(value.is_some());
+        }
+        Ok(MultiGet(
+            keys.iter()
+                .map(|key| self.codec(KeyFamily::of(key)))
+                .zip(values)
+                .collect(),
+        ))
     }
 
     pub async fn set<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) -> Result<()> {
-        let mut redis_connection = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
 
-        let serialized = serde_json::to_string(value)?;
-        redis_connection
-            .set_ex::<_, _, ()>(key, serialized, ttl.as_secs())
-            .await?;
+        let serialized = self.codec(KeyFamily::of(key)).encode(value)?;
+        self.observe(
+            &op,
+            redis_connection
+                .set_ex::<_, _, ()>(key, serialized, ttl.as_secs())
+                .await,
+        )?;
+        Ok(())
+    }
+
+    /// Sets several keys, each with its own TTL, in one round trip. Either all of them are
+    /// set or none are.
+    pub async fn mset<T: Serialize>(&self, entries: &[(String, T, Duration)]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let op = self.metrics.start(KeyFamily::common(
+            &entries.iter().map(|(key, _, _)| key).collect::<Vec<_>>(),
+        ));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let mut pipeline = redis::pipe();
+        pipeline.atomic();
+        for (key, value, ttl) in entries {
+            pipeline
+                .set_ex(
+                    key,
+                    self.codec(KeyFamily::of(key)).encode(value)?,
+                    ttl.as_secs(),
+                )
+                .ignore();
+        }
+        self.observe(
+            &op,
+            pipeline.query_async::<()>(&mut *redis_connection).await,
+        )?;
         Ok(())
     }
 
     pub async fn setnx(&self, key: &str, ttl: Duration) -> Result<bool> {
-        let mut redis_connection = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
 
-        let result: Option<String> = redis::cmd("SET")
-            .arg(key)
-            .arg("true")
-            .arg("NX")
-            .arg("EX")
-            .arg(ttl.as_secs())
-            .query_async(&mut *redis_connection)
-            .await?;
+        let result: Option<String> = self.observe(
+            &op,
+            redis::cmd("SET")
+                .arg(key)
+                .arg("true")
+                .arg("NX")
+                .arg("EX")
+                .arg(ttl.as_secs())
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
 
         Ok(result.is_some())
     }
 
     pub async fn setnx_with_value(&self, key: &str, value: &str, ttl: Duration) -> Result<bool> {
-        let mut redis_connection = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
 
-        let serialized_value = serde_json::to_string(value)?;
-        let result: Option<String> = redis::cmd("SET")
-            .arg(key)
-            .arg(serialized_value)
-            .arg("NX")
-            .arg("EX")
-            .arg(ttl.as_secs())
-            .query_async(&mut *redis_connection)
-            .await?;
+        let serialized_value = self.codec(KeyFamily::of(key)).encode(value)?;
+        let result: Option<String> = self.observe(
+            &op,
+            redis::cmd("SET")
+                .arg(key)
+                .arg(serialized_value)
+                .arg("NX")
+                .arg("EX")
+                .arg(ttl.as_secs())
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
 
         Ok(result.is_some())
     }
 
+    /// Adds `by` to the counter at `key`, starting from zero, and returns the new value. The
+    /// key's expiry is left as it was.
+    pub async fn incr(&self, key: &str, by: i64) -> Result<i64> {
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(&op, redis_connection.incr(key, by).await)
+    }
+
+    /// Subtracts `by` from the counter at `key`, starting from zero, and returns the new value.
+    pub async fn decr(&self, key: &str, by: i64) -> Result<i64> {
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(&op, redis_connection.decr(key, by).await)
+    }
+
+    /// Sets the key to expire after `ttl`. `false` when the key does not exist.
+    pub async fn expire(&self, key: &str, ttl: Duration) -> Result<bool> {
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(
+            &op,
+            redis_connection.expire(key, ttl.as_secs() as i64).await,
+        )
+    }
+
+    /// Time left before the key expires. `None` when it does not exist or never expires.
+    pub async fn get_ttl(&self, key: &str) -> Result<Option<Duration>> {
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let ttl_ms: i64 = self.observe(&op, redis_connection.pttl(key).await)?;
+        Ok(u64::try_from(ttl_ms).ok().map(Duration::from_millis))
+    }
+
+    /// Adds or re-scores `member`, and keeps the whole set alive for `ttl`.
+    pub async fn sorted_set_add(
+        &self,
+        key: &str,
+        member: &str,
+        score: i64,
+        ttl: Duration,
+    ) -> Result<()> {
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(
+            &op,
+            redis::pipe()
+                .zadd(key, member, score)
+                .ignore()
+                .expire(key, ttl.as_secs() as i64)
+                .ignore()
+                .query_async::<()>(&mut *redis_connection)
+                .await,
+        )?;
+        Ok(())
+    }
+
+    pub async fn sorted_set_remove(&self, key: &str, member: &str) -> Result<()> {
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(&op, redis_connection.zrem::<_, _, ()>(key, member).await)?;
+        Ok(())
+    }
+
+    /// Drops members scored below `min_score` and returns the rest.
+    pub async fn sorted_set_members_since(&self, key: &str, min_score: i64) -> Result<Vec<String>> {
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let (members,): (Vec<String>,) = self.observe(
+            &op,
+            redis::pipe()
+                .zrembyscore(key, "-inf", format!("({min_score}"))
+                .ignore()
+                .zrange(key, 0, -1)
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
+        Ok(members)
+    }
+
+    /// Appends `entry` to the stream at `stream_key` under the next value of the counter at
+    /// `counter_key`, and returns that value. The stream keeps about the last `max_len`
+    /// entries, and both keys expire `ttl` after the last append.
+    pub async fn stream_append_sequenced(
+        &self,
+        counter_key: &str,
+        stream_key: &str,
+        entry: &str,
+        max_len: usize,
+        ttl: Duration,
+    ) -> Result<u64> {
+        let op = self.metrics.start(KeyFamily::of(stream_key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let seq: u64 = self.observe(
+            &op,
+            redis::cmd("EVAL")
+                .arg(APPEND_SEQUENCED_SCRIPT)
+                .arg(2)
+                .arg(counter_key)
+                .arg(stream_key)
+                .arg(max_len)
+                .arg(ttl.as_secs())
+                .arg(entry)
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
+        Ok(seq)
+    }
+
+    /// Counts a hit against the sliding window made of the counters at `current_key` and
+    /// `previous_key`, with the previous window's hits weighted by `previous_weight`. Hits
+    /// that would reach `limit` are not counted. Returns whether the hit was allowed and how
+    /// many remain, atomically so concurrent hits cannot both take the last one.
+    pub async fn sliding_window_hit(
+        &self,
+        current_key: &str,
+        previous_key: &str,
+        limit: u32,
+        previous_weight: f64,
+        ttl: Duration,
+    ) -> Result<(bool, u32)> {
+        let op = self.metrics.start(KeyFamily::of(current_key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let (allowed, remaining): (bool, u32) = self.observe(
+            &op,
+            redis::cmd("EVAL")
+                .arg(SLIDING_WINDOW_HIT_SCRIPT)
+                .arg(2)
+                .arg(current_key)
+                .arg(previous_key)
+                .arg(limit)
+                .arg(previous_weight)
+                .arg(ttl.as_secs())
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
+        Ok((allowed, remaining))
+    }
+
+    /// The counter's current value and the buffered `(seq, entry)` pairs, oldest first.
+    pub async fn stream_sequenced_entries(
+        &self,
+        counter_key: &str,
+        stream_key: &str,
+    ) -> Result<(Option<u64>, Vec<(u64, String)>)> {
+        let op = self.metrics.start(KeyFamily::of(stream_key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        let (latest, stream): (Option<u64>, Vec<StreamEntry>) = self.observe(
+            &op,
+            redis::pipe()
+                .get(counter_key)
+                .cmd("XRANGE")
+                .arg(stream_key)
+                .arg("-")
+                .arg("+")
+                .query_async(&mut *redis_connection)
+                .await,
+        )?;
+
+        let entries = stream
+            .into_iter()
+            .filter_map(|(_, mut fields)| {
+                let seq = fields.get("seq")?.parse().ok()?;
+                Some((seq, fields.remove("entry")?))
+            })
+            .collect();
+        Ok((latest, entries))
+    }
+
+    pub async fn publish(&self, channel: &str, payload: &str) -> Result<()> {
+        let op = self.metrics.start(KeyFamily::of(channel));
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(
+            &op,
+            redis_connection.publish::<_, _, ()>(channel, payload).await,
+        )?;
+        Ok(())
+    }
+
+    /// Payloads published to `channel` from now on. The stream ends when the connection
+    /// drops.
+    pub async fn subscribe(&self, channel: &str) -> Result<impl Stream<Item = String> + use<>> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(channel).await?;
+
+        Ok(pubsub
+            .into_on_message()
+            .filter_map(|message| async move { message.get_payload::<String>().ok() }))
+    }
+
     pub async fn delete(&self, key: &str) -> Result<()> {
-        let mut redis_connection = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let op = self.metrics.start(KeyFamily::of(key));
+        let mut redis_connection = self.connection(&op).await?;
+
+        self.observe(&op, redis_connection.del::<_, ()>(key).await)?;
+        Ok(())
+    }
+
+    /// Deletes several keys in one round trip.
+    pub async fn del_many(&self, keys: &[String]) -> Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let op = self.metrics.start(KeyFamily::common(keys));
+        let mut redis_connection = self.connection(&op).await?;
 
-        redis_connection.del::<_, ()>(key).await?;
+        self.observe(&op, redis_connection.del::<_, ()>(keys).await)?;
         Ok(())
     }
 }
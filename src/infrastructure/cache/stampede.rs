@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use moka::Expiry;
+use tokio::sync::OnceCell;
+
+use crate::error::Result;
+
+/// Fraction by which cache TTLs are randomly shortened or lengthened, so entries cached
+/// together do not all expire, and reload, together.
+const TTL_JITTER: f64 = 0.1;
+
+/// The TTL moved by up to `TTL_JITTER` either way.
+pub fn jittered(ttl: Duration) -> Duration {
+    ttl.mul_f64(rand::random_range(1.0 - TTL_JITTER..=1.0 + TTL_JITTER))
+}
+
+/// Moka expiry giving every entry a jittered TTL from when it was inserted.
+pub struct JitteredTtl(pub Duration);
+
+impl<K, V> Expiry<K, V> for JitteredTtl {
+    fn expire_after_create(&self, _key: &K, _value: &V, _created_at: Instant) -> Option<Duration> {
+        Some(jittered(self.0))
+    }
+}
+
+/// Collapses concurrent loads of the same key into one. Callers arriving while a load runs
+/// wait for its result instead of starting their own. A failed load is not shared: each
+/// waiter then runs its own, so every caller still gets its own error.
+pub struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, Arc<OnceCell<V>>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> SingleFlight<K, V> {
+    pub async fn run<F, Fut>(&self, key: K, load: F) -> Result<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        let cell = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let result = cell.get_or_try_init(load).await.cloned();
+
+        // Later callers start a new load rather than reuse this result
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+        result
+    }
+}
@@ -0,0 +1,14 @@
+use futures::future::BoxFuture;
+use uuid::Uuid;
+
+/// A committed write that cached copies of the data must not outlive.
+#[derive(Clone, Copy, Debug)]
+pub enum DataChange {
+    Canvas { canvas_id: Uuid },
+}
+
+/// Told about every committed change before the repository call that made it returns, so
+/// callers never read a stale cached copy of what they just wrote.
+pub trait ChangeObserver: Send + Sync {
+    fn changed(&self, change: DataChange) -> BoxFuture<'_, ()>;
+}
@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+
+/// Append-only record of state-changing RPC calls.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "audit_log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    pub method: String,
+
+    #[sea_orm(nullable)]
+    pub actor_id: Option<Uuid>,
+
+    /// Hex SHA-256 of the call's params, tokens left out.
+    pub params_hash: String,
+
+    /// JSON-RPC error code, or 0 when the call succeeded.
+    pub result_code: i32,
+
+    pub latency_ms: i32,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::ActorId",
+        to = "super::user::Column::Id"
+    )]
+    Actor,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,4 +1,4 @@
-use sea_orm::entity::prelude::*;
+use sea_orm::{FromJsonQueryResult, entity::prelude::*};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, DeriveEntityModel)]
@@ -32,6 +32,182 @@ pub struct Model {
 
     #[sea_orm(nullable)]
     pub minted_at: Option<DateTimeUtc>,
+
+    /// Max pixels a collaborator may place per quota window on a Draft canvas.
+    #[sea_orm(nullable)]
+    pub pixel_quota: Option<i32>,
+
+    #[sea_orm(nullable)]
+    pub pixel_quota_window_secs: Option<i32>,
+
+    /// Custom palette as `#rrggbb` entries. `None` uses the built-in palette.
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub palette: Option<CanvasPalette>,
+
+    /// Owner-set bid rules. `None` falls back to the global minimum and a 1 lamport step.
+    #[sea_orm(nullable)]
+    pub bid_floor_lamports: Option<i64>,
+
+    #[sea_orm(nullable)]
+    pub bid_step_lamports: Option<i64>,
+
+    #[sea_orm(nullable)]
+    pub bid_ceiling_lamports: Option<i64>,
+
+    /// Lets the owner place draft pixels without waiting out the cooldown.
+    pub owner_cooldown_exempt: bool,
+
+    /// Content ids of the stored NFT image and metadata: IPFS CIDs or Arweave
+    /// transaction ids, depending on `storage_backend`.
+    #[sea_orm(nullable)]
+    pub image_cid: Option<String>,
+
+    #[sea_orm(nullable)]
+    pub metadata_cid: Option<String>,
+
+    #[sea_orm(nullable)]
+    pub storage_backend: Option<String>,
+
+    /// Provider upload receipts, kept for provenance.
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub storage_receipts: Option<StorageReceipts>,
+
+    /// SHA-256 of the rendered PNG in the asset store, set once the canvas is minted.
+    #[sea_orm(nullable)]
+    pub image_hash: Option<String>,
+
+    /// Latest rendered timelapse animation in the asset store.
+    #[sea_orm(nullable)]
+    pub timelapse_hash: Option<String>,
+
+    #[sea_orm(nullable)]
+    pub timelapse_format: Option<TimelapseFormat>,
+
+    /// Whether the timelapse is listed as an extra file in the NFT metadata.
+    pub timelapse_in_metadata: bool,
+
+    /// Owner-set NFT royalty and symbol. `None` uses the defaults.
+    #[sea_orm(nullable)]
+    pub royalty_bps: Option<i16>,
+
+    #[sea_orm(nullable)]
+    pub nft_symbol: Option<String>,
+
+    /// Which Metaplex standard the canvas is minted as.
+    pub nft_standard: NftStandard,
+
+    /// Collection recorded at mint time, and whether it was verified on-chain.
+    #[sea_orm(nullable)]
+    pub collection_mint: Option<String>,
+
+    pub collection_verified: bool,
+
+    /// Last confirmed post-mint metadata update.
+    #[sea_orm(nullable)]
+    pub metadata_updated_at: Option<DateTimeUtc>,
+
+    /// Mint transaction submitted through `nft.confirmMint`, polled until it settles.
+    #[sea_orm(nullable)]
+    pub mint_signature: Option<String>,
+
+    #[sea_orm(nullable)]
+    pub mint_submitted_at: Option<DateTimeUtc>,
+
+    /// Confirmed burn of the canvas NFT.
+    #[sea_orm(nullable)]
+    pub burn_signature: Option<String>,
+
+    #[sea_orm(nullable)]
+    pub burned_at: Option<DateTimeUtc>,
+
+    /// Newest mint-address signature scanned for royalty payouts.
+    #[sea_orm(nullable)]
+    pub royalty_cursor: Option<String>,
+
+    /// Owner-defined traits listed after the computed ones in the NFT metadata.
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub custom_attributes: Option<CustomAttributes>,
+
+    /// Minted canvas this one was remixed from.
+    #[sea_orm(nullable, indexed)]
+    pub parent_canvas_id: Option<Uuid>,
+
+    /// Program deployment the canvas was published under. `None` before publishing, and
+    /// for canvases published before deployments were recorded, means the current program.
+    #[sea_orm(nullable)]
+    pub program_id: Option<String>,
+
+    /// Set while the canvas is soft-deleted.
+    #[sea_orm(nullable)]
+    pub deleted_at: Option<DateTimeUtc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct CanvasPalette(pub Vec<String>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct CustomAttributes(pub Vec<CustomAttribute>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct StorageReceipts {
+    pub image: serde_json::Value,
+    pub metadata: serde_json::Value,
+    pub cost_lamports: Option<u64>,
+
+    #[serde(default)]
+    pub timelapse: Option<serde_json::Value>,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, EnumIter, DeriveActiveEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(8))")]
+#[serde(rename_all = "lowercase")]
+pub enum TimelapseFormat {
+    #[default]
+    #[sea_orm(string_value = "gif")]
+    Gif,
+
+    #[sea_orm(string_value = "apng")]
+    Apng,
+}
+
+impl TimelapseFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TimelapseFormat::Gif => "image/gif",
+            TimelapseFormat::Apng => "image/apng",
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            TimelapseFormat::Gif => "timelapse.gif",
+            TimelapseFormat::Apng => "timelapse.png",
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, EnumIter, DeriveActiveEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum NftStandard {
+    /// Legacy SPL mint with Token Metadata and master edition accounts.
+    #[default]
+    #[sea_orm(string_value = "token_metadata")]
+    TokenMetadata,
+
+    /// Single Metaplex Core asset account.
+    #[sea_orm(string_value = "core")]
+    Core,
 }
 
 #[derive(Clone, Debug, Default, EnumIter, DeriveActiveEnum, PartialEq, Serialize, Deserialize)]
@@ -55,6 +231,9 @@ pub enum CanvasState {
 
     #[sea_orm(string_value = "minted")]
     Minted,
+
+    #[sea_orm(string_value = "burned")]
+    Burned,
 }
 
 impl CanvasState {
@@ -69,6 +248,7 @@ impl CanvasState {
                 | (Published, MintPending)
                 | (MintPending, Minting)
                 | (Minting, Minted)
+                | (Minted, Burned)
                 // Rollback/failure transitions
                 | (Publishing, Draft)      // Publish failed/cancelled
                 | (MintPending, Published) // Mint cancelled
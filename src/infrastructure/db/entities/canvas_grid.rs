@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+
+/// Colors of every pixel of a canvas, one byte each in row order. Owned pixels also have a
+/// row in `pixels` for their owner and price.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "canvas_grids")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub canvas_id: Uuid,
+
+    pub width: i16,
+
+    pub colors: Vec<u8>,
+
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::canvas::Entity",
+        from = "Column::CanvasId",
+        to = "super::canvas::Column::Id"
+    )]
+    Canvas,
+}
+
+impl Related<super::canvas::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Canvas.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,70 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A verified transaction signature and the operation it authorized.
+/// Kept so a signature can never authorize a second operation.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "consumed_signatures")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub signature: String,
+
+    pub operation: SignatureOperation,
+
+    pub canvas_id: Uuid,
+
+    pub user_id: Uuid,
+
+    #[sea_orm(nullable)]
+    pub x: Option<i16>,
+
+    #[sea_orm(nullable)]
+    pub y: Option<i16>,
+
+    #[sea_orm(nullable)]
+    pub color: Option<i16>,
+
+    #[sea_orm(nullable)]
+    pub amount_lamports: Option<i64>,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveActiveEnum, PartialEq, Eq, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+pub enum SignatureOperation {
+    #[sea_orm(string_value = "pixel_confirm")]
+    PixelConfirm,
+    #[sea_orm(string_value = "escrow_claim")]
+    EscrowClaim,
+    #[sea_orm(string_value = "canvas_publish")]
+    CanvasPublish,
+    #[sea_orm(string_value = "pixel_paint")]
+    PixelPaint,
+    #[sea_orm(string_value = "nft_mint")]
+    NftMint,
+    #[sea_orm(string_value = "edition_mint")]
+    EditionMint,
+    #[sea_orm(string_value = "nft_burn")]
+    NftBurn,
+    #[sea_orm(string_value = "metadata_update")]
+    MetadataUpdate,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,56 @@
+use sea_orm::entity::prelude::*;
+
+/// A numbered print of a minted canvas, reserved by a pixel-owning collaborator.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "edition_prints")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub canvas_id: Uuid,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub edition_number: i32,
+
+    pub user_id: Uuid,
+
+    #[sea_orm(nullable)]
+    pub mint_address: Option<String>,
+
+    #[sea_orm(nullable)]
+    pub signature: Option<String>,
+
+    pub created_at: DateTimeUtc,
+
+    #[sea_orm(nullable)]
+    pub minted_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::canvas::Entity",
+        from = "Column::CanvasId",
+        to = "super::canvas::Column::Id"
+    )]
+    Canvas,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::canvas::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Canvas.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
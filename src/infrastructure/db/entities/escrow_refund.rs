@@ -0,0 +1,72 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Lamports the program escrow owes a user on a canvas, until they withdraw them.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "escrow_refunds")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub user_id: Uuid,
+
+    pub canvas_id: Uuid,
+
+    pub reason: RefundReason,
+
+    pub amount_lamports: i64,
+
+    /// The bid or mint transaction that credited the refund.
+    pub source_signature: String,
+
+    #[sea_orm(nullable)]
+    pub claim_signature: Option<String>,
+
+    #[sea_orm(nullable)]
+    pub claimed_at: Option<DateTimeUtc>,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveActiveEnum, PartialEq, Eq, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum RefundReason {
+    /// The user's bid was outbid, so its price is returned.
+    #[sea_orm(string_value = "outbid")]
+    Outbid,
+    /// The canvas minted, releasing its escrowed bids to the owner.
+    #[sea_orm(string_value = "mint_payout")]
+    MintPayout,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::canvas::Entity",
+        from = "Column::CanvasId",
+        to = "super::canvas::Column::Id"
+    )]
+    Canvas,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::canvas::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Canvas.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
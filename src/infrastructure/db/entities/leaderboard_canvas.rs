@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+
+/// A live canvas ranked by escrow. Read from the `leaderboard_canvases` view, which lags
+/// the canvases it ranks until the next refresh.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "leaderboard_canvases")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub canvas_id: Uuid,
+
+    pub name: String,
+
+    pub owner_id: Uuid,
+
+    pub total_escrowed: i64,
+
+    /// Pixels of the canvas someone owns.
+    pub pixel_count: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
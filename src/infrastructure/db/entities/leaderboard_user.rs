@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+
+/// A user ranked by pixels owned. Read from the `leaderboard_users` view, which lags the
+/// pixels it counts until the next refresh.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "leaderboard_users")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: Uuid,
+
+    pub wallet_address: String,
+
+    #[sea_orm(nullable)]
+    pub username: Option<String>,
+
+    pub pixels_owned: i64,
+
+    /// Lamports bid on the pixels they own.
+    pub total_lamports: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
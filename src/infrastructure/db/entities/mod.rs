@@ -1,9 +1,35 @@
+pub mod audit_log;
 pub mod canvas;
 pub mod canvas_collaborator;
+pub mod canvas_grid;
+pub mod consumed_signature;
+pub mod edition_print;
+pub mod escrow_refund;
+pub mod leaderboard_canvas;
+pub mod leaderboard_user;
+pub mod onchain_event;
+pub mod outbox_event;
 pub mod pixel;
+pub mod pixel_history;
+pub mod pixel_lock;
+pub mod pixel_watch;
+pub mod royalty_payout;
 pub mod user;
 
+pub use audit_log::Entity as AuditLog;
 pub use canvas::Entity as Canvas;
 pub use canvas_collaborator::Entity as CanvasCollaborator;
+pub use canvas_grid::Entity as CanvasGrid;
+pub use consumed_signature::Entity as ConsumedSignature;
+pub use edition_print::Entity as EditionPrint;
+pub use escrow_refund::Entity as EscrowRefund;
+pub use leaderboard_canvas::Entity as LeaderboardCanvas;
+pub use leaderboard_user::Entity as LeaderboardUser;
+pub use onchain_event::Entity as OnchainEvent;
+pub use outbox_event::Entity as OutboxEvent;
 pub use pixel::Entity as Pixel;
+pub use pixel_history::Entity as PixelHistory;
+pub use pixel_lock::Entity as PixelLock;
+pub use pixel_watch::Entity as PixelWatch;
+pub use royalty_payout::Entity as RoyaltyPayout;
 pub use user::Entity as User;
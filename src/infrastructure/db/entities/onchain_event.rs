@@ -0,0 +1,55 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A program instruction found on-chain by the event indexer, whoever submitted it.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "onchain_events")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub signature: String,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub instruction_index: i16,
+
+    pub event_type: OnchainEventType,
+
+    pub canvas_pda: String,
+
+    /// `None` when the canvas PDA matches no stored canvas.
+    #[sea_orm(nullable)]
+    pub canvas_id: Option<Uuid>,
+
+    pub signer: String,
+
+    /// The decoded instruction arguments.
+    #[sea_orm(column_type = "JsonBinary")]
+    pub data: Json,
+
+    pub slot: i64,
+
+    #[sea_orm(nullable)]
+    pub block_time: Option<DateTimeUtc>,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveActiveEnum, PartialEq, Eq, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(24))")]
+#[serde(rename_all = "snake_case")]
+pub enum OnchainEventType {
+    #[sea_orm(string_value = "canvas_created")]
+    CanvasCreated,
+    #[sea_orm(string_value = "bid_placed")]
+    BidPlaced,
+    #[sea_orm(string_value = "pixels_painted")]
+    PixelsPainted,
+    #[sea_orm(string_value = "minted")]
+    Minted,
+    #[sea_orm(string_value = "escrow_withdrawn")]
+    EscrowWithdrawn,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+
+/// An event written in the same transaction as the change it reports, waiting for the
+/// outbox dispatcher to deliver it. Deleted once delivered.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "outbox")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    pub topic: OutboxTopic,
+
+    #[sea_orm(column_type = "JsonBinary")]
+    pub payload: Json,
+
+    /// Deliveries tried so far.
+    pub attempts: i32,
+
+    /// When the event is next due. Pushed back while a dispatcher holds it, and after each
+    /// failed attempt.
+    pub available_at: DateTimeUtc,
+
+    #[sea_orm(nullable)]
+    pub last_error: Option<String>,
+
+    pub created_at: DateTimeUtc,
+}
+
+/// Where an outbox event is delivered.
+#[derive(Clone, Copy, Debug, EnumIter, DeriveActiveEnum, PartialEq, Eq)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum OutboxTopic {
+    /// An update for a canvas's WebSocket room.
+    #[sea_orm(string_value = "room")]
+    Room,
+    /// A written pixel, announced to its canvas's room.
+    #[sea_orm(string_value = "pixel")]
+    Pixel,
+    /// A signed POST to one webhook endpoint.
+    #[sea_orm(string_value = "webhook")]
+    Webhook,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
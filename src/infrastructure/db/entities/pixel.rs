@@ -19,6 +19,9 @@ pub struct Model {
 
     pub price_lamports: i64,
 
+    /// Bumped on every write for compare-and-swap updates.
+    pub version: i32,
+
     pub updated_at: DateTimeUtc,
 }
 
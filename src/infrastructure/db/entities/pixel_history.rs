@@ -0,0 +1,55 @@
+use sea_orm::entity::prelude::*;
+
+/// Append-only log of pixel color changes.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "pixel_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    #[sea_orm(indexed)]
+    pub canvas_id: Uuid,
+
+    pub x: i16,
+    pub y: i16,
+
+    #[sea_orm(nullable)]
+    pub old_color: Option<i16>,
+
+    pub new_color: i16,
+
+    #[sea_orm(nullable)]
+    pub actor_id: Option<Uuid>,
+
+    pub price_lamports: i64,
+
+    #[sea_orm(nullable)]
+    pub signature: Option<String>,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::canvas::Entity",
+        from = "Column::CanvasId",
+        to = "super::canvas::Column::Id"
+    )]
+    Canvas,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::ActorId",
+        to = "super::user::Column::Id"
+    )]
+    Actor,
+}
+
+impl Related<super::canvas::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Canvas.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
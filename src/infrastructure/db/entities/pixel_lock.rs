@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "pixel_locks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub canvas_id: Uuid,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub x: i16,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub y: i16,
+
+    pub user_id: Uuid,
+    pub expires_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::canvas::Entity",
+        from = "Column::CanvasId",
+        to = "super::canvas::Column::Id"
+    )]
+    Canvas,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::canvas::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Canvas.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+
+/// Lamports a creator wallet received in a transaction involving a canvas NFT.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "royalty_payouts")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub signature: String,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub creator_address: String,
+
+    #[sea_orm(indexed)]
+    pub canvas_id: Uuid,
+
+    pub amount_lamports: i64,
+
+    pub paid_at: DateTimeUtc,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::canvas::Entity",
+        from = "Column::CanvasId",
+        to = "super::canvas::Column::Id"
+    )]
+    Canvas,
+}
+
+impl Related<super::canvas::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Canvas.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,4 +1,5 @@
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "users")]
@@ -13,6 +14,25 @@ pub struct Model {
     pub username: Option<String>,
 
     pub created_at: DateTimeUtc,
+
+    pub role: UserRole,
+
+    /// Set while the user is soft-deleted.
+    #[sea_orm(nullable)]
+    pub deleted_at: Option<DateTimeUtc>,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, EnumIter, DeriveActiveEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum UserRole {
+    #[default]
+    #[sea_orm(string_value = "user")]
+    User,
+
+    #[sea_orm(string_value = "admin")]
+    Admin,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -99,26 +99,29 @@ impl MigrationTrait for Migration {
             )
             .await?;
 
-        // Add check constraints
-        manager
-            .get_connection()
-            .execute_unprepared(
-                r#"
-                DO $$
-                BEGIN
-                    IF NOT EXISTS (
-                        SELECT 1 FROM pg_constraint WHERE conname = 'chk_pixels_x'
-                    ) THEN
-                        ALTER TABLE pixels
-                        ADD CONSTRAINT chk_pixels_x CHECK (x >= 0 AND x < 32),
-                        ADD CONSTRAINT chk_pixels_y CHECK (y >= 0 AND y < 32),
-                        ADD CONSTRAINT chk_pixels_color CHECK (color >= 0 AND color < 64);
-                    END IF;
-                END
-                $$;
-                "#,
-            )
-            .await?;
+        // Add check constraints; SQLite cannot add them to an existing table, and the service
+        // validates coordinates and colors before they reach the database anyway
+        if super::is_postgres(manager) {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    r#"
+                    DO $$
+                    BEGIN
+                        IF NOT EXISTS (
+                            SELECT 1 FROM pg_constraint WHERE conname = 'chk_pixels_x'
+                        ) THEN
+                            ALTER TABLE pixels
+                            ADD CONSTRAINT chk_pixels_x CHECK (x >= 0 AND x < 32),
+                            ADD CONSTRAINT chk_pixels_y CHECK (y >= 0 AND y < 32),
+                            ADD CONSTRAINT chk_pixels_color CHECK (color >= 0 AND color < 64);
+                        END IF;
+                    END
+                    $$;
+                    "#,
+                )
+                .await?;
+        }
 
         Ok(())
     }
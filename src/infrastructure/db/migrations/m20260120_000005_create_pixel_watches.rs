@@ -0,0 +1,88 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000001_create_users::Users;
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+pub enum PixelWatches {
+    Table,
+    UserId,
+    CanvasId,
+    X,
+    Y,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PixelWatches::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PixelWatches::UserId).uuid().not_null())
+                    .col(ColumnDef::new(PixelWatches::CanvasId).uuid().not_null())
+                    .col(ColumnDef::new(PixelWatches::X).small_integer().not_null())
+                    .col(ColumnDef::new(PixelWatches::Y).small_integer().not_null())
+                    .col(
+                        ColumnDef::new(PixelWatches::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PixelWatches::UserId)
+                            .col(PixelWatches::CanvasId)
+                            .col(PixelWatches::X)
+                            .col(PixelWatches::Y),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pixel_watches_user")
+                            .from(PixelWatches::Table, PixelWatches::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pixel_watches_canvas")
+                            .from(PixelWatches::Table, PixelWatches::CanvasId)
+                            .to(Canvases::Table, Canvases::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_pixel_watches_pixel")
+                    .table(PixelWatches::Table)
+                    .col(PixelWatches::CanvasId)
+                    .col(PixelWatches::X)
+                    .col(PixelWatches::Y)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PixelWatches::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,40 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    sea_query::{ColumnDef, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasPalette {
+    Palette,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .add_column_if_not_exists(ColumnDef::new(CanvasPalette::Palette).json_binary())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .drop_column(CanvasPalette::Palette)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,105 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000001_create_users::Users;
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+pub enum PixelHistory {
+    Table,
+    Id,
+    CanvasId,
+    X,
+    Y,
+    OldColor,
+    NewColor,
+    ActorId,
+    PriceLamports,
+    Signature,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PixelHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PixelHistory::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PixelHistory::CanvasId).uuid().not_null())
+                    .col(ColumnDef::new(PixelHistory::X).small_integer().not_null())
+                    .col(ColumnDef::new(PixelHistory::Y).small_integer().not_null())
+                    .col(ColumnDef::new(PixelHistory::OldColor).small_integer())
+                    .col(
+                        ColumnDef::new(PixelHistory::NewColor)
+                            .small_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PixelHistory::ActorId).uuid())
+                    .col(
+                        ColumnDef::new(PixelHistory::PriceLamports)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(PixelHistory::Signature).string_len(88))
+                    .col(
+                        ColumnDef::new(PixelHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pixel_history_canvas")
+                            .from(PixelHistory::Table, PixelHistory::CanvasId)
+                            .to(Canvases::Table, Canvases::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pixel_history_actor")
+                            .from(PixelHistory::Table, PixelHistory::ActorId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_pixel_history_canvas_created")
+                    .table(PixelHistory::Table)
+                    .col(PixelHistory::CanvasId)
+                    .col(PixelHistory::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PixelHistory::Table).to_owned())
+            .await
+    }
+}
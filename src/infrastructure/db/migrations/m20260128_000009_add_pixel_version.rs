@@ -0,0 +1,45 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    sea_query::{ColumnDef, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000003_create_pixels::Pixels;
+
+#[derive(DeriveIden)]
+enum PixelVersion {
+    Version,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Pixels::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(PixelVersion::Version)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Pixels::Table)
+                    .drop_column(PixelVersion::Version)
+                    .to_owned(),
+            )
+            .await
+    }
+}
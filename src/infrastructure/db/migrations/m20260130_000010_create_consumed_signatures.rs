@@ -0,0 +1,79 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000001_create_users::Users;
+
+#[derive(DeriveIden)]
+pub enum ConsumedSignatures {
+    Table,
+    Signature,
+    Operation,
+    CanvasId,
+    UserId,
+    X,
+    Y,
+    Color,
+    AmountLamports,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConsumedSignatures::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ConsumedSignatures::Signature)
+                            .string_len(88)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ConsumedSignatures::Operation)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ConsumedSignatures::CanvasId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ConsumedSignatures::UserId).uuid().not_null())
+                    .col(ColumnDef::new(ConsumedSignatures::X).small_integer())
+                    .col(ColumnDef::new(ConsumedSignatures::Y).small_integer())
+                    .col(ColumnDef::new(ConsumedSignatures::Color).small_integer())
+                    .col(ColumnDef::new(ConsumedSignatures::AmountLamports).big_integer())
+                    .col(
+                        ColumnDef::new(ConsumedSignatures::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_consumed_signatures_user")
+                            .from(ConsumedSignatures::Table, ConsumedSignatures::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ConsumedSignatures::Table).to_owned())
+            .await
+    }
+}
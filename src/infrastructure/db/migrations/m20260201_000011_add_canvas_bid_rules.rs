@@ -0,0 +1,52 @@
+use sea_orm::{DbErr, DeriveIden, DeriveMigrationName, sea_query::ColumnDef};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasBidRules {
+    #[sea_orm(iden = "bid_floor_lamports")]
+    Floor,
+    #[sea_orm(iden = "bid_step_lamports")]
+    Step,
+    #[sea_orm(iden = "bid_ceiling_lamports")]
+    Ceiling,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::add_columns(
+            manager,
+            Canvases::Table,
+            [
+                ColumnDef::new(CanvasBidRules::Floor)
+                    .big_integer()
+                    .to_owned(),
+                ColumnDef::new(CanvasBidRules::Step)
+                    .big_integer()
+                    .to_owned(),
+                ColumnDef::new(CanvasBidRules::Ceiling)
+                    .big_integer()
+                    .to_owned(),
+            ],
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::drop_columns(
+            manager,
+            Canvases::Table,
+            [
+                CanvasBidRules::Floor,
+                CanvasBidRules::Step,
+                CanvasBidRules::Ceiling,
+            ],
+        )
+        .await
+    }
+}
@@ -0,0 +1,36 @@
+use sea_orm::{DbErr, DeriveMigrationName, sea_query::Index};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260130_000010_create_consumed_signatures::ConsumedSignatures;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_consumed_signatures_pixel")
+                    .table(ConsumedSignatures::Table)
+                    .col(ConsumedSignatures::CanvasId)
+                    .col(ConsumedSignatures::X)
+                    .col(ConsumedSignatures::Y)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_consumed_signatures_pixel")
+                    .table(ConsumedSignatures::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,69 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    sea_query::{ColumnDef, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::{m20260106_000001_create_users::Users, m20260106_000002_create_canvases::Canvases};
+
+#[derive(DeriveIden)]
+enum CooldownExemptions {
+    Role,
+    OwnerCooldownExempt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(CooldownExemptions::Role)
+                            .string_len(16)
+                            .not_null()
+                            .default("user"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(CooldownExemptions::OwnerCooldownExempt)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .drop_column(CooldownExemptions::OwnerCooldownExempt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(CooldownExemptions::Role)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,42 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    sea_query::{ColumnDef, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasImageHash {
+    ImageHash,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(CanvasImageHash::ImageHash).string_len(64),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .drop_column(CanvasImageHash::ImageHash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,54 @@
+use sea_orm::{DbErr, DeriveIden, DeriveMigrationName, sea_query::ColumnDef};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasTimelapse {
+    #[sea_orm(iden = "timelapse_hash")]
+    Hash,
+    #[sea_orm(iden = "timelapse_format")]
+    Format,
+    #[sea_orm(iden = "timelapse_in_metadata")]
+    InMetadata,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::add_columns(
+            manager,
+            Canvases::Table,
+            [
+                ColumnDef::new(CanvasTimelapse::Hash)
+                    .string_len(64)
+                    .to_owned(),
+                ColumnDef::new(CanvasTimelapse::Format)
+                    .string_len(8)
+                    .to_owned(),
+                ColumnDef::new(CanvasTimelapse::InMetadata)
+                    .boolean()
+                    .not_null()
+                    .default(false)
+                    .to_owned(),
+            ],
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::drop_columns(
+            manager,
+            Canvases::Table,
+            [
+                CanvasTimelapse::Hash,
+                CanvasTimelapse::Format,
+                CanvasTimelapse::InMetadata,
+            ],
+        )
+        .await
+    }
+}
@@ -0,0 +1,41 @@
+use sea_orm::{DbErr, DeriveIden, DeriveMigrationName, sea_query::ColumnDef};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasNftBranding {
+    RoyaltyBps,
+    NftSymbol,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::add_columns(
+            manager,
+            Canvases::Table,
+            [
+                ColumnDef::new(CanvasNftBranding::RoyaltyBps)
+                    .small_integer()
+                    .to_owned(),
+                ColumnDef::new(CanvasNftBranding::NftSymbol)
+                    .string_len(10)
+                    .to_owned(),
+            ],
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::drop_columns(
+            manager,
+            Canvases::Table,
+            [CanvasNftBranding::RoyaltyBps, CanvasNftBranding::NftSymbol],
+        )
+        .await
+    }
+}
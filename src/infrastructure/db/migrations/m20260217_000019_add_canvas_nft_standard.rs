@@ -0,0 +1,45 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    sea_query::{ColumnDef, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasNftStandard {
+    NftStandard,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(CanvasNftStandard::NftStandard)
+                            .string_len(16)
+                            .not_null()
+                            .default("token_metadata"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .drop_column(CanvasNftStandard::NftStandard)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,46 @@
+use sea_orm::{DbErr, DeriveIden, DeriveMigrationName, sea_query::ColumnDef};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasCollection {
+    CollectionMint,
+    CollectionVerified,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::add_columns(
+            manager,
+            Canvases::Table,
+            [
+                ColumnDef::new(CanvasCollection::CollectionMint)
+                    .string_len(44)
+                    .to_owned(),
+                ColumnDef::new(CanvasCollection::CollectionVerified)
+                    .boolean()
+                    .not_null()
+                    .default(false)
+                    .to_owned(),
+            ],
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        super::drop_columns(
+            manager,
+            Canvases::Table,
+            [
+                CanvasCollection::CollectionMint,
+                CanvasCollection::CollectionVerified,
+            ],
+        )
+        .await
+    }
+}
@@ -0,0 +1,95 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000001_create_users::Users;
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+pub enum EditionPrints {
+    Table,
+    CanvasId,
+    EditionNumber,
+    UserId,
+    MintAddress,
+    Signature,
+    CreatedAt,
+    MintedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EditionPrints::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(EditionPrints::CanvasId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(EditionPrints::EditionNumber)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(EditionPrints::UserId).uuid().not_null())
+                    .col(ColumnDef::new(EditionPrints::MintAddress).string_len(44))
+                    .col(ColumnDef::new(EditionPrints::Signature).string_len(88))
+                    .col(
+                        ColumnDef::new(EditionPrints::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(EditionPrints::MintedAt).timestamp_with_time_zone())
+                    .primary_key(
+                        Index::create()
+                            .col(EditionPrints::CanvasId)
+                            .col(EditionPrints::EditionNumber),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_edition_prints_canvas")
+                            .from(EditionPrints::Table, EditionPrints::CanvasId)
+                            .to(Canvases::Table, Canvases::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_edition_prints_user")
+                            .from(EditionPrints::Table, EditionPrints::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One print per collaborator per canvas.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_edition_prints_canvas_user")
+                    .table(EditionPrints::Table)
+                    .col(EditionPrints::CanvasId)
+                    .col(EditionPrints::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EditionPrints::Table).to_owned())
+            .await
+    }
+}
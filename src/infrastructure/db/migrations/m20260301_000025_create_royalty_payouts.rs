@@ -0,0 +1,121 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+pub enum RoyaltyPayouts {
+    Table,
+    Signature,
+    CreatorAddress,
+    CanvasId,
+    AmountLamports,
+    PaidAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum CanvasRoyaltyCursor {
+    RoyaltyCursor,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoyaltyPayouts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RoyaltyPayouts::Signature)
+                            .string_len(88)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RoyaltyPayouts::CreatorAddress)
+                            .string_len(44)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RoyaltyPayouts::CanvasId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RoyaltyPayouts::AmountLamports)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RoyaltyPayouts::PaidAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RoyaltyPayouts::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(RoyaltyPayouts::Signature)
+                            .col(RoyaltyPayouts::CreatorAddress),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_royalty_payouts_canvas")
+                            .from(RoyaltyPayouts::Table, RoyaltyPayouts::CanvasId)
+                            .to(Canvases::Table, Canvases::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_royalty_payouts_canvas_creator")
+                    .table(RoyaltyPayouts::Table)
+                    .col(RoyaltyPayouts::CanvasId)
+                    .col(RoyaltyPayouts::CreatorAddress)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Newest mint signature already indexed, so each pass only reads newer ones.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(CanvasRoyaltyCursor::RoyaltyCursor).string_len(88),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .drop_column(CanvasRoyaltyCursor::RoyaltyCursor)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(RoyaltyPayouts::Table).to_owned())
+            .await
+    }
+}
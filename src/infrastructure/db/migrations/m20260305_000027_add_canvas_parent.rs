@@ -0,0 +1,75 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+enum CanvasParent {
+    ParentCanvasId,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .add_column_if_not_exists(ColumnDef::new(CanvasParent::ParentCanvasId).uuid())
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite cannot add a foreign key to an existing table, so the parent link is only
+        // enforced on Postgres.
+        if super::is_postgres(manager) {
+            manager
+                .create_foreign_key(
+                    ForeignKey::create()
+                        .name("fk_canvases_parent_canvas")
+                        .from(Canvases::Table, CanvasParent::ParentCanvasId)
+                        .to(Canvases::Table, Canvases::Id)
+                        .on_delete(ForeignKeyAction::SetNull)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_canvases_parent_canvas_id")
+                    .table(Canvases::Table)
+                    .col(CanvasParent::ParentCanvasId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_canvases_parent_canvas_id")
+                    .table(Canvases::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Canvases::Table)
+                    .drop_column(CanvasParent::ParentCanvasId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
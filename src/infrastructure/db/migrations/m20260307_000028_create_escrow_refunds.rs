@@ -0,0 +1,119 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000001_create_users::Users;
+use super::m20260106_000002_create_canvases::Canvases;
+
+#[derive(DeriveIden)]
+pub enum EscrowRefunds {
+    Table,
+    Id,
+    UserId,
+    CanvasId,
+    Reason,
+    AmountLamports,
+    SourceSignature,
+    ClaimSignature,
+    ClaimedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EscrowRefunds::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EscrowRefunds::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EscrowRefunds::UserId).uuid().not_null())
+                    .col(ColumnDef::new(EscrowRefunds::CanvasId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(EscrowRefunds::Reason)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EscrowRefunds::AmountLamports)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EscrowRefunds::SourceSignature)
+                            .string_len(88)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(EscrowRefunds::ClaimSignature).string_len(88))
+                    .col(ColumnDef::new(EscrowRefunds::ClaimedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(EscrowRefunds::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_escrow_refunds_user")
+                            .from(EscrowRefunds::Table, EscrowRefunds::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_escrow_refunds_canvas")
+                            .from(EscrowRefunds::Table, EscrowRefunds::CanvasId)
+                            .to(Canvases::Table, Canvases::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A bid or mint credits each user at most once.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_escrow_refunds_source_user")
+                    .table(EscrowRefunds::Table)
+                    .col(EscrowRefunds::SourceSignature)
+                    .col(EscrowRefunds::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_escrow_refunds_user_canvas")
+                    .table(EscrowRefunds::Table)
+                    .col(EscrowRefunds::UserId)
+                    .col(EscrowRefunds::CanvasId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EscrowRefunds::Table).to_owned())
+            .await
+    }
+}
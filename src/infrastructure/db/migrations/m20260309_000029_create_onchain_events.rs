@@ -0,0 +1,110 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+#[derive(DeriveIden)]
+pub enum OnchainEvents {
+    Table,
+    Signature,
+    InstructionIndex,
+    EventType,
+    CanvasPda,
+    CanvasId,
+    Signer,
+    Data,
+    Slot,
+    BlockTime,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OnchainEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OnchainEvents::Signature)
+                            .string_len(88)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OnchainEvents::InstructionIndex)
+                            .small_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OnchainEvents::EventType)
+                            .string_len(24)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OnchainEvents::CanvasPda)
+                            .string_len(44)
+                            .not_null(),
+                    )
+                    // No foreign key: events are kept for canvases this server never stored.
+                    .col(ColumnDef::new(OnchainEvents::CanvasId).uuid())
+                    .col(
+                        ColumnDef::new(OnchainEvents::Signer)
+                            .string_len(44)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OnchainEvents::Data).json_binary().not_null())
+                    .col(ColumnDef::new(OnchainEvents::Slot).big_integer().not_null())
+                    .col(ColumnDef::new(OnchainEvents::BlockTime).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(OnchainEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(OnchainEvents::Signature)
+                            .col(OnchainEvents::InstructionIndex),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_onchain_events_canvas_pda_slot")
+                    .table(OnchainEvents::Table)
+                    .col(OnchainEvents::CanvasPda)
+                    .col(OnchainEvents::Slot)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_onchain_events_slot")
+                    .table(OnchainEvents::Table)
+                    .col(OnchainEvents::Slot)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OnchainEvents::Table).to_owned())
+            .await
+    }
+}
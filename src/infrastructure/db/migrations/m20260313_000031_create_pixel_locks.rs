@@ -0,0 +1,72 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Index, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000001_create_users::Users;
+use super::m20260106_000002_create_canvases::Canvases;
+
+/// Pixel locks taken while Redis is unavailable. Rows outlive their `expires_at` until the
+/// pixel is locked again.
+#[derive(DeriveIden)]
+pub enum PixelLocks {
+    Table,
+    CanvasId,
+    X,
+    Y,
+    UserId,
+    ExpiresAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PixelLocks::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PixelLocks::CanvasId).uuid().not_null())
+                    .col(ColumnDef::new(PixelLocks::X).small_integer().not_null())
+                    .col(ColumnDef::new(PixelLocks::Y).small_integer().not_null())
+                    .col(ColumnDef::new(PixelLocks::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(PixelLocks::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PixelLocks::CanvasId)
+                            .col(PixelLocks::X)
+                            .col(PixelLocks::Y),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pixel_locks_canvas")
+                            .from(PixelLocks::Table, PixelLocks::CanvasId)
+                            .to(Canvases::Table, Canvases::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pixel_locks_user")
+                            .from(PixelLocks::Table, PixelLocks::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PixelLocks::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,133 @@
+use sea_orm::{
+    DbErr, DeriveIden, DeriveMigrationName,
+    prelude::Expr,
+    sea_query::{ColumnDef, ForeignKey, ForeignKeyAction, Table},
+};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+
+/// Canvas colors as one `bytea` per canvas, so `pixels` only keeps rows for owned pixels.
+#[derive(DeriveIden)]
+pub enum CanvasGrids {
+    Table,
+    CanvasId,
+    Width,
+    Colors,
+    UpdatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CanvasGrids::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CanvasGrids::CanvasId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CanvasGrids::Width)
+                            .small_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CanvasGrids::Colors).binary().not_null())
+                    .col(
+                        ColumnDef::new(CanvasGrids::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_canvas_grids_canvas")
+                            .from(CanvasGrids::Table, CanvasGrids::CanvasId)
+                            .to(Canvases::Table, Canvases::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Packs each canvas's rows into its grid, then drops the rows nobody owns. Only
+        // Postgres can hold rows from before this migration, so other backends skip it
+        if super::is_postgres(manager) {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    r#"
+                    INSERT INTO canvas_grids (canvas_id, width, colors, updated_at)
+                    SELECT
+                        bounds.canvas_id,
+                        bounds.width,
+                        string_agg(
+                            set_byte('\x00'::bytea, 0, COALESCE(pixels.color, 0)),
+                            ''::bytea ORDER BY cell.index
+                        ),
+                        bounds.updated_at
+                    FROM (
+                        SELECT
+                            canvas_id,
+                            MAX(x) + 1 AS width,
+                            MAX(y) + 1 AS height,
+                            MAX(updated_at) AS updated_at
+                        FROM pixels
+                        GROUP BY canvas_id
+                    ) bounds
+                    CROSS JOIN LATERAL
+                        generate_series(0, bounds.width::int * bounds.height - 1) AS cell(index)
+                    LEFT JOIN pixels
+                        ON pixels.canvas_id = bounds.canvas_id
+                        AND pixels.x = cell.index % bounds.width
+                        AND pixels.y = cell.index / bounds.width
+                    GROUP BY bounds.canvas_id, bounds.width, bounds.updated_at
+                    ON CONFLICT (canvas_id) DO NOTHING;
+
+                    DELETE FROM pixels WHERE owner_id IS NULL;
+                    "#,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Unpacks the grids back into rows, the reverse of the backfill in `up`
+        if super::is_postgres(manager) {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    r#"
+                    INSERT INTO pixels (canvas_id, x, y, color, owner_id, price_lamports, version, updated_at)
+                    SELECT
+                        canvas_grids.canvas_id,
+                        cell.index % canvas_grids.width,
+                        cell.index / canvas_grids.width,
+                        get_byte(canvas_grids.colors, cell.index),
+                        NULL,
+                        0,
+                        0,
+                        canvas_grids.updated_at
+                    FROM canvas_grids
+                    CROSS JOIN LATERAL
+                        generate_series(0, length(canvas_grids.colors) - 1) AS cell(index)
+                    ON CONFLICT (canvas_id, x, y) DO NOTHING;
+                    "#,
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(CanvasGrids::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,62 @@
+use sea_orm::{DbErr, DeriveMigrationName, sea_query::Index};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000002_create_canvases::Canvases;
+use super::m20260319_000034_create_audit_log::AuditLog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_canvases_owner_created")
+                    .table(Canvases::Table)
+                    .col(Canvases::OwnerId)
+                    .col(Canvases::CreatedAt)
+                    .col(Canvases::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_audit_log_created")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::CreatedAt)
+                    .col(AuditLog::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_audit_log_created")
+                    .table(AuditLog::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_canvases_owner_created")
+                    .table(Canvases::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
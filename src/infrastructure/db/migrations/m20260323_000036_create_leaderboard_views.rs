@@ -0,0 +1,99 @@
+use sea_orm::{ConnectionTrait, DbErr, DeriveMigrationName};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+/// Live canvases by lamports held in escrow, with how many of their pixels are owned.
+const LEADERBOARD_CANVASES: &str = r#"
+    SELECT
+        canvases.id AS canvas_id,
+        canvases.name,
+        canvases.owner_id,
+        canvases.total_escrowed,
+        CAST(COALESCE(owned.pixel_count, 0) AS BIGINT) AS pixel_count
+    FROM canvases
+    LEFT JOIN (
+        SELECT canvas_id, COUNT(*) AS pixel_count
+        FROM pixels
+        WHERE owner_id IS NOT NULL
+        GROUP BY canvas_id
+    ) owned ON owned.canvas_id = canvases.id
+    WHERE canvases.deleted_at IS NULL
+"#;
+
+/// Users by pixels owned across every canvas, with the lamports they bid on them.
+const LEADERBOARD_USERS: &str = r#"
+    SELECT
+        users.id AS user_id,
+        users.wallet_address,
+        users.username,
+        CAST(COUNT(*) AS BIGINT) AS pixels_owned,
+        CAST(COALESCE(SUM(pixels.price_lamports), 0) AS BIGINT) AS total_lamports
+    FROM pixels
+    JOIN users ON users.id = pixels.owner_id
+    WHERE users.deleted_at IS NULL
+    GROUP BY users.id, users.wallet_address, users.username
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Postgres keeps the rankings as materialized views, recomputed by the leaderboard
+        // refresher. Other backends have no materialized views, so plain views stand in
+        if super::is_postgres(manager) {
+            db.execute_unprepared(&format!(
+                "CREATE MATERIALIZED VIEW IF NOT EXISTS leaderboard_canvases AS {LEADERBOARD_CANVASES}"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "CREATE MATERIALIZED VIEW IF NOT EXISTS leaderboard_users AS {LEADERBOARD_USERS}"
+            ))
+            .await?;
+
+            // Unique indexes let the refresher rebuild the views without blocking reads
+            db.execute_unprepared(
+                r#"
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_leaderboard_canvases_id
+                    ON leaderboard_canvases (canvas_id);
+                CREATE INDEX IF NOT EXISTS idx_leaderboard_canvases_escrow
+                    ON leaderboard_canvases (total_escrowed DESC);
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_leaderboard_users_id
+                    ON leaderboard_users (user_id);
+                CREATE INDEX IF NOT EXISTS idx_leaderboard_users_pixels
+                    ON leaderboard_users (pixels_owned DESC);
+                "#,
+            )
+            .await?;
+        } else {
+            db.execute_unprepared(&format!(
+                "CREATE VIEW IF NOT EXISTS leaderboard_canvases AS {LEADERBOARD_CANVASES}"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "CREATE VIEW IF NOT EXISTS leaderboard_users AS {LEADERBOARD_USERS}"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let kind = if super::is_postgres(manager) {
+            "MATERIALIZED VIEW"
+        } else {
+            "VIEW"
+        };
+
+        let db = manager.get_connection();
+        db.execute_unprepared(&format!("DROP {kind} IF EXISTS leaderboard_users"))
+            .await?;
+        db.execute_unprepared(&format!("DROP {kind} IF EXISTS leaderboard_canvases"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use sea_orm::{DbErr, DeriveMigrationName, sea_query::Index};
+use sea_orm_migration::{MigrationTrait, SchemaManager, async_trait::async_trait};
+
+use super::m20260106_000003_create_pixels::Pixels;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_pixels_owner_updated")
+                    .table(Pixels::Table)
+                    .col(Pixels::OwnerId)
+                    .col(Pixels::UpdatedAt)
+                    .col(Pixels::CanvasId)
+                    .col(Pixels::X)
+                    .col(Pixels::Y)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_pixels_owner_updated")
+                    .table(Pixels::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,47 @@
-use sea_orm_migration::{MigrationTrait, MigratorTrait, async_trait::async_trait};
+use sea_orm::{
+    DbBackend, DbErr,
+    sea_query::{ColumnDef, IntoIden, Table},
+};
+use sea_orm_migration::{MigrationTrait, MigratorTrait, SchemaManager, async_trait::async_trait};
 
 mod m20260106_000001_create_users;
 mod m20260106_000002_create_canvases;
 mod m20260106_000003_create_pixels;
 mod m20260106_000004_create_collaborators;
+mod m20260120_000005_create_pixel_watches;
+mod m20260122_000006_add_canvas_pixel_quota;
+mod m20260124_000007_add_canvas_palette;
+mod m20260126_000008_create_pixel_history;
+mod m20260128_000009_add_pixel_version;
+mod m20260130_000010_create_consumed_signatures;
+mod m20260201_000011_add_canvas_bid_rules;
+mod m20260203_000012_index_consumed_signatures_pixel;
+mod m20260205_000013_add_cooldown_exemptions;
+mod m20260207_000014_add_canvas_ipfs_cids;
+mod m20260209_000015_add_canvas_storage_receipts;
+mod m20260211_000016_add_canvas_image_hash;
+mod m20260213_000017_add_canvas_timelapse_asset;
+mod m20260215_000018_add_canvas_nft_branding;
+mod m20260217_000019_add_canvas_nft_standard;
+mod m20260219_000020_add_canvas_collection;
+mod m20260221_000021_add_canvas_metadata_updated_at;
+mod m20260223_000022_add_canvas_mint_signature;
+mod m20260225_000023_create_edition_prints;
+mod m20260227_000024_add_canvas_burn;
+mod m20260301_000025_create_royalty_payouts;
+mod m20260303_000026_add_canvas_custom_attributes;
+mod m20260305_000027_add_canvas_parent;
+mod m20260307_000028_create_escrow_refunds;
+mod m20260309_000029_create_onchain_events;
+mod m20260311_000030_add_canvas_program_id;
+mod m20260313_000031_create_pixel_locks;
+mod m20260315_000032_create_canvas_grids;
+mod m20260317_000033_add_soft_delete;
+mod m20260319_000034_create_audit_log;
+mod m20260321_000035_add_keyset_indexes;
+mod m20260323_000036_create_leaderboard_views;
+mod m20260325_000037_create_outbox;
+mod m20260327_000038_index_owned_pixels;
 
 pub struct Migrator;
 
@@ -15,6 +53,86 @@ impl MigratorTrait for Migrator {
             Box::new(m20260106_000002_create_canvases::Migration),
             Box::new(m20260106_000003_create_pixels::Migration),
             Box::new(m20260106_000004_create_collaborators::Migration),
+            Box::new(m20260120_000005_create_pixel_watches::Migration),
+            Box::new(m20260122_000006_add_canvas_pixel_quota::Migration),
+            Box::new(m20260124_000007_add_canvas_palette::Migration),
+            Box::new(m20260126_000008_create_pixel_history::Migration),
+            Box::new(m20260128_000009_add_pixel_version::Migration),
+            Box::new(m20260130_000010_create_consumed_signatures::Migration),
+            Box::new(m20260201_000011_add_canvas_bid_rules::Migration),
+            Box::new(m20260203_000012_index_consumed_signatures_pixel::Migration),
+            Box::new(m20260205_000013_add_cooldown_exemptions::Migration),
+            Box::new(m20260207_000014_add_canvas_ipfs_cids::Migration),
+            Box::new(m20260209_000015_add_canvas_storage_receipts::Migration),
+            Box::new(m20260211_000016_add_canvas_image_hash::Migration),
+            Box::new(m20260213_000017_add_canvas_timelapse_asset::Migration),
+            Box::new(m20260215_000018_add_canvas_nft_branding::Migration),
+            Box::new(m20260217_000019_add_canvas_nft_standard::Migration),
+            Box::new(m20260219_000020_add_canvas_collection::Migration),
+            Box::new(m20260221_000021_add_canvas_metadata_updated_at::Migration),
+            Box::new(m20260223_000022_add_canvas_mint_signature::Migration),
+            Box::new(m20260225_000023_create_edition_prints::Migration),
+            Box::new(m20260227_000024_add_canvas_burn::Migration),
+            Box::new(m20260301_000025_create_royalty_payouts::Migration),
+            Box::new(m20260303_000026_add_canvas_custom_attributes::Migration),
+            Box::new(m20260305_000027_add_canvas_parent::Migration),
+            Box::new(m20260307_000028_create_escrow_refunds::Migration),
+            Box::new(m20260309_000029_create_onchain_events::Migration),
+            Box::new(m20260311_000030_add_canvas_program_id::Migration),
+            Box::new(m20260313_000031_create_pixel_locks::Migration),
+            Box::new(m20260315_000032_create_canvas_grids::Migration),
+            Box::new(m20260317_000033_add_soft_delete::Migration),
+            Box::new(m20260319_000034_create_audit_log::Migration),
+            Box::new(m20260321_000035_add_keyset_indexes::Migration),
+            Box::new(m20260323_000036_create_leaderboard_views::Migration),
+            Box::new(m20260325_000037_create_outbox::Migration),
+            Box::new(m20260327_000038_index_owned_pixels::Migration),
         ]
     }
 }
+
+/// Whether the migration runs on Postgres. Raw SQL, check constraints and foreign keys added
+/// to existing tables are skipped elsewhere, such as the SQLite databases used for tests.
+fn is_postgres(manager: &SchemaManager) -> bool {
+    manager.get_database_backend() == DbBackend::Postgres
+}
+
+/// Adds columns one `ALTER TABLE` at a time, since SQLite takes a single change per statement.
+async fn add_columns(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    columns: impl IntoIterator<Item = ColumnDef>,
+) -> Result<(), DbErr> {
+    let table = table.into_iden();
+    for column in columns {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(table.clone())
+                    .add_column_if_not_exists(column)
+                    .to_owned(),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Drops columns one `ALTER TABLE` at a time, for the same reason as `add_columns`.
+async fn drop_columns<C: IntoIden>(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    columns: impl IntoIterator<Item = C>,
+) -> Result<(), DbErr> {
+    let table = table.into_iden();
+    for column in columns {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(table.clone())
+                    .drop_column(column)
+                    .to_owned(),
+            )
+            .await?;
+    }
+    Ok(())
+}
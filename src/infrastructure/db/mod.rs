@@ -1,44 +1,151 @@
+pub mod changes;
 pub mod entities;
 pub mod migrations;
 pub mod repositories;
+pub mod resilient;
 
-use sea_orm::{ConnectOptions, DatabaseConnection, DatabaseTransaction, TransactionTrait};
-use sea_orm_migration::MigratorTrait;
+use std::{
+    fmt,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
 
-use crate::{config::DatabaseConfig, error::Result};
+use sea_orm::{ConnectOptions, DatabaseTransaction, DbErr, RuntimeErr, TransactionTrait};
+use sea_orm_migration::{MigrationStatus, MigratorTrait};
+use tokio::sync::Notify;
 
+use crate::{
+    config::DatabaseConfig,
+    error::{AppError, Result},
+};
+
+use changes::{ChangeObserver, DataChange};
 use migrations::Migrator;
+use resilient::ResilientConnection;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Database {
-    connection: DatabaseConnection,
+    connection: ResilientConnection,
+    health_timeout: Duration,
+    /// Shared by every clone, so it can be set after the handles are handed out.
+    observer: Arc<OnceLock<Arc<dyn ChangeObserver>>>,
+    /// Wakes this instance's outbox dispatcher when events are committed.
+    outbox_written: Arc<Notify>,
+}
+
+impl fmt::Debug for Database {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Database")
+            .field("connection", &self.connection)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Database {
     pub async fn init_db(config: &DatabaseConfig) -> Result<Self> {
         let mut options = ConnectOptions::new(&config.url);
 
+        // Every connection to an in-memory SQLite database opens a fresh, empty one, so the
+        // pool must hold on to exactly one
+        let (max_connections, min_connections) = if is_in_memory(&config.url) {
+            (1, 1)
+        } else {
+            (config.max_connections, config.min_connections)
+        };
+
         options
-            .max_connections(config.max_connections)
-            .min_connections(config.min_connections)
+            .max_connections(max_connections)
+            .min_connections(min_connections)
             .connect_timeout(config.connect_timeout)
             .idle_timeout(config.idle_timeout)
             .sqlx_logging(false);
 
         let connection = sea_orm::Database::connect(options).await?;
 
-        Ok(Self { connection })
+        Ok(Self {
+            connection: ResilientConnection::new(connection, config.retry_delay),
+            health_timeout: config.health_timeout,
+            observer: Arc::default(),
+            outbox_written: Arc::default(),
+        })
     }
 
-    pub fn get_connection(&self) -> &DatabaseConnection {
+    pub fn get_connection(&self) -> &ResilientConnection {
         &self.connection
     }
 
+    /// Checks that the database answers, returning how long it took.
+    pub async fn health(&self) -> Result<Duration> {
+        let started = Instant::now();
+        match tokio::time::timeout(self.health_timeout, self.connection.inner().ping()).await {
+            Ok(result) => {
+                result?;
+                Ok(started.elapsed())
+            }
+            Err(_) => Err(AppError::DatabaseError(DbErr::Conn(RuntimeErr::Internal(
+                "Health check timed out".into(),
+            )))),
+        }
+    }
+
+    /// Sets what is told about committed changes. Only the first call has an effect.
+    pub fn observe_changes(&self, observer: Arc<dyn ChangeObserver>) {
+        let _ = self.observer.set(observer);
+    }
+
+    /// Reports a committed change to the observer, if one is set.
+    pub async fn notify(&self, change: DataChange) {
+        if let Some(observer) = self.observer.get() {
+            observer.changed(change).await;
+        }
+    }
+
+    /// Tells the outbox dispatcher that committed events are waiting, so they go out
+    /// without waiting for its next poll.
+    pub fn wake_outbox(&self) {
+        self.outbox_written.notify_one();
+    }
+
+    /// Resolves once `wake_outbox` is called, or at once if it was called since last time.
+    pub async fn outbox_written(&self) {
+        self.outbox_written.notified().await;
+    }
+
     pub async fn begin_transaction(&self) -> Result<DatabaseTransaction> {
-        Ok(self.connection.begin().await?)
+        Ok(self.connection.inner().begin().await?)
+    }
+
+    /// Applies pending migrations, oldest first, at most `steps` of them when set.
+    pub async fn run_migrations(&self, steps: Option<u32>) -> Result<()> {
+        Ok(Migrator::up(self.connection.inner(), steps).await?)
+    }
+
+    /// Rolls back the last `steps` applied migrations.
+    pub async fn rollback_migrations(&self, steps: u32) -> Result<()> {
+        Ok(Migrator::down(self.connection.inner(), Some(steps)).await?)
     }
 
-    pub async fn run_migrations(&self) -> Result<()> {
-        Ok(Migrator::up(&self.connection, None).await?)
+    /// Every migration by name, oldest first.
+    pub async fn migration_status(&self) -> Result<Vec<(String, MigrationStatus)>> {
+        Ok(Migrator::get_migration_with_status(self.connection.inner())
+            .await?
+            .iter()
+            .map(|migration| (migration.name().to_string(), migration.status()))
+            .collect())
     }
+
+    pub async fn pending_migrations(&self) -> Result<usize> {
+        Ok(Migrator::get_pending_migrations(self.connection.inner())
+            .await?
+            .len())
+    }
+
+    /// Drops every table and applies all migrations again.
+    pub async fn reset_schema(&self) -> Result<()> {
+        Ok(Migrator::fresh(self.connection.inner()).await?)
+    }
+}
+
+fn is_in_memory(url: &str) -> bool {
+    url.starts_with("sqlite:") && (url.contains(":memory:") || url.contains("mode=memory"))
 }
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Select,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::{
+        entities::{AuditLog, audit_log},
+        repositories::pagination::{self, Page},
+    },
+};
+
+/// One state-changing call, as recorded by the dispatcher.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub method: String,
+    pub actor_id: Option<Uuid>,
+    pub params_hash: String,
+    pub result_code: i32,
+    pub latency_ms: i32,
+}
+
+/// Narrows an audit query. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub method: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+pub struct AuditLogRepository;
+
+impl AuditLogRepository {
+    pub async fn record<C: ConnectionTrait>(db_connection: &C, entry: AuditEntry) -> Result<()> {
+        AuditLog::insert(audit_log::ActiveModel {
+            id: NotSet,
+            method: Set(entry.method),
+            actor_id: Set(entry.actor_id),
+            params_hash: Set(entry.params_hash),
+            result_code: Set(entry.result_code),
+            latency_ms: Set(entry.latency_ms),
+            created_at: Set(Utc::now()),
+        })
+        .exec(db_connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists matching entries, newest first.
+    pub async fn find_entries<C: ConnectionTrait>(
+        db_connection: &C,
+        filter: &AuditLogFilter,
+        cursor: Option<&str>,
+        limit: u64,
+    ) -> Result<Page<audit_log::Model>> {
+        pagination::fetch_page(
+            db_connection,
+            Self::filtered(filter),
+            audit_log::Column::CreatedAt,
+            &[audit_log::Column::Id],
+            cursor,
+            limit,
+        )
+        .await
+    }
+
+    fn filtered(filter: &AuditLogFilter) -> Select<AuditLog> {
+        let mut query = AuditLog::find();
+        if let Some(method) = &filter.method {
+            query = query.filter(audit_log::Column::Method.eq(method.as_str()));
+        }
+        if let Some(actor_id) = filter.actor_id {
+            query = query.filter(audit_log::Column::ActorId.eq(actor_id));
+        }
+        if let Some(from) = filter.from {
+            query = query.filter(audit_log::Column::CreatedAt.gte(from));
+        }
+        if let Some(to) = filter.to {
+            query = query.filter(audit_log::Column::CreatedAt.lt(to));
+        }
+        query
+    }
+}
@@ -1,7 +1,8 @@
 use chrono::Utc;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, JoinType,
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, SqlErr, TransactionTrait,
+    sea_query::Expr,
 };
 use uuid::Uuid;
 
@@ -9,11 +10,23 @@ use crate::{
     error::{AppError, Result},
     infrastructure::db::{
         Database,
-        entities::{Canvas, CanvasCollaborator, Pixel, canvas, canvas_collaborator, pixel},
-        repositories::generate_invite_code,
+        changes::DataChange,
+        entities::{
+            Canvas, CanvasCollaborator, CanvasGrid, Pixel, PixelHistory, PixelWatch, canvas,
+            canvas_collaborator, canvas_grid, pixel, pixel_history, pixel_watch,
+        },
+        repositories::{
+            generate_invite_code,
+            outbox::{NewOutboxEvent, OutboxRepository},
+            pagination::{self, Page},
+            soft_delete::ExcludeDeleted,
+        },
     },
 };
 
+/// How many invite codes `create_canvas` draws before giving up on collisions.
+const INVITE_CODE_ATTEMPTS: u32 = 5;
+
 pub struct CanvasRepository;
 
 impl CanvasRepository {
@@ -22,25 +35,83 @@ impl CanvasRepository {
         id: Uuid,
     ) -> Result<Option<canvas::Model>> {
         Canvas::find_by_id(id)
+            .exclude_deleted()
             .one(db_connection)
             .await
             .map_err(AppError::DatabaseError)
     }
 
+    /// For admin tooling, which also needs soft-deleted canvases.
+    pub async fn find_canvas_by_id_including_deleted<C: ConnectionTrait>(
+        db_connection: &C,
+        id: Uuid,
+    ) -> Result<Option<canvas::Model>> {
+        Canvas::find_by_id(id)
+            .one(db_connection)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
+    pub async fn find_canvases_by_ids<C: ConnectionTrait>(
+        db_connection: &C,
+        ids: &[Uuid],
+    ) -> Result<Vec<canvas::Model>> {
+        Canvas::find()
+            .filter(canvas::Column::Id.is_in(ids.iter().copied()))
+            .exclude_deleted()
+            .all(db_connection)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
+    pub async fn find_canvases_by_pdas<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_pdas: &[String],
+    ) -> Result<Vec<canvas::Model>> {
+        Canvas::find()
+            .filter(canvas::Column::CanvasPda.is_in(canvas_pdas.iter().cloned()))
+            .exclude_deleted()
+            .all(db_connection)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
     pub async fn find_canvas_by_invite_code<C: ConnectionTrait>(
         db_connection: &C,
         code: &str,
     ) -> Result<Option<canvas::Model>> {
         Canvas::find()
             .filter(canvas::Column::InviteCode.eq(code))
+            .exclude_deleted()
             .one(db_connection)
             .await
             .map_err(AppError::DatabaseError)
     }
 
+    /// Lists the owner's canvases, newest first.
     pub async fn list_canvases_by_owner<C: ConnectionTrait>(
         conn: &C,
         owner_id: Uuid,
+        cursor: Option<&str>,
+        limit: u64,
+    ) -> Result<Page<canvas::Model>> {
+        pagination::fetch_page(
+            conn,
+            Canvas::find()
+                .filter(canvas::Column::OwnerId.eq(owner_id))
+                .exclude_deleted(),
+            canvas::Column::CreatedAt,
+            &[canvas::Column::Id],
+            cursor,
+            limit,
+        )
+        .await
+    }
+
+    /// For admin tooling, which also needs soft-deleted canvases.
+    pub async fn list_canvases_by_owner_including_deleted<C: ConnectionTrait>(
+        conn: &C,
+        owner_id: Uuid,
     ) -> Result<Vec<canvas::Model>> {
         Canvas::find()
             .filter(canvas::Column::OwnerId.eq(owner_id))
@@ -50,22 +121,91 @@ impl CanvasRepository {
             .map_err(AppError::DatabaseError)
     }
 
+    /// Canvases still minting with a submitted mint transaction.
+    pub async fn list_pending_mints<C: ConnectionTrait>(conn: &C) -> Result<Vec<canvas::Model>> {
+        Canvas::find()
+            .filter(canvas::Column::State.eq(canvas::CanvasState::Minting))
+            .filter(canvas::Column::MintSignature.is_not_null())
+            .exclude_deleted()
+            .order_by_asc(canvas::Column::MintSubmittedAt)
+            .all(conn)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
+    pub async fn find_pending_mint_by_signature<C: ConnectionTrait>(
+        conn: &C,
+        signature: &str,
+    ) -> Result<Option<canvas::Model>> {
+        Canvas::find()
+            .filter(canvas::Column::State.eq(canvas::CanvasState::Minting))
+            .filter(canvas::Column::MintSignature.eq(signature))
+            .exclude_deleted()
+            .one(conn)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
+    pub async fn list_canvases_in_states<C: ConnectionTrait>(
+        conn: &C,
+        states: &[canvas::CanvasState],
+    ) -> Result<Vec<canvas::Model>> {
+        Canvas::find()
+            .filter(canvas::Column::State.is_in(states.iter().cloned()))
+            .exclude_deleted()
+            .order_by_asc(canvas::Column::CreatedAt)
+            .all(conn)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
+    pub async fn find_child_canvases<C: ConnectionTrait>(
+        conn: &C,
+        parent_ids: Vec<Uuid>,
+    ) -> Result<Vec<canvas::Model>> {
+        Canvas::find()
+            .filter(canvas::Column::ParentCanvasId.is_in(parent_ids))
+            .exclude_deleted()
+            .order_by_asc(canvas::Column::CreatedAt)
+            .all(conn)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
+    pub async fn list_minted_canvases<C: ConnectionTrait>(conn: &C) -> Result<Vec<canvas::Model>> {
+        Canvas::find()
+            .filter(canvas::Column::State.eq(canvas::CanvasState::Minted))
+            .filter(canvas::Column::MintAddress.is_not_null())
+            .exclude_deleted()
+            .order_by_asc(canvas::Column::MintedAt)
+            .all(conn)
+            .await
+            .map_err(AppError::DatabaseError)
+    }
+
+    /// Lists canvases the user collaborates on but does not own, newest first.
     pub async fn list_canvases_by_collaborator<C: ConnectionTrait>(
         db_connection: &C,
         user_id: Uuid,
-    ) -> Result<Vec<canvas::Model>> {
-        let canvases = Canvas::find()
-            .join(
-                JoinType::InnerJoin,
-                canvas::Relation::CanvasCollaborator.def(),
-            )
-            .filter(canvas_collaborator::Column::UserId.eq(user_id))
-            .filter(canvas::Column::OwnerId.ne(user_id))
-            .order_by_desc(canvas::Column::CreatedAt)
-            .all(db_connection)
-            .await?;
-
-        Ok(canvases)
+        cursor: Option<&str>,
+        limit: u64,
+    ) -> Result<Page<canvas::Model>> {
+        pagination::fetch_page(
+            db_connection,
+            Canvas::find()
+                .join(
+                    JoinType::InnerJoin,
+                    canvas::Relation::CanvasCollaborator.def(),
+                )
+                .filter(canvas_collaborator::Column::UserId.eq(user_id))
+                .filter(canvas::Column::OwnerId.ne(user_id))
+                .exclude_deleted(),
+            canvas::Column::CreatedAt,
+            &[canvas::Column::Id],
+            cursor,
+            limit,
+        )
+        .await
     }
 
     pub async fn exists_by_name_and_owner<C: ConnectionTrait>(
@@ -76,21 +216,27 @@ impl CanvasRepository {
         let count = Canvas::find()
             .filter(canvas::Column::OwnerId.eq(owner_id))
             .filter(canvas::Column::Name.eq(canvas_name))
+            .exclude_deleted()
             .count(db_connection)
             .await?;
 
         Ok(count > 0)
     }
 
-    pub async fn create_canvas<C: ConnectionTrait>(
+    /// Draws a new invite code when the one tried is already taken, and fails with
+    /// `InviteCodeConflict` once `INVITE_CODE_ATTEMPTS` have collided. Each attempt runs in
+    /// a savepoint, so a collision leaves the caller's transaction usable.
+    pub async fn create_canvas<C: ConnectionTrait + TransactionTrait>(
         db_connection: &C,
         owner_id: Uuid,
         name: &str,
+        palette: Option<canvas::CanvasPalette>,
+        parent_canvas_id: Option<Uuid>,
     ) -> Result<canvas::Model> {
         let now = Utc::now();
         let invite_code = generate_invite_code();
 
-        let canvas = canvas::ActiveModel {
+        let mut canvas = canvas::ActiveModel {
             id: Set(Uuid::new_v4()),
             owner_id: Set(owner_id),
             name: Set(name.to_string()),
@@ -102,9 +248,55 @@ impl CanvasRepository {
             created_at: Set(now),
             published_at: Set(None),
             minted_at: Set(None),
+            pixel_quota: Set(None),
+            pixel_quota_window_secs: Set(None),
+            palette: Set(palette),
+            bid_floor_lamports: Set(None),
+            bid_step_lamports: Set(None),
+            bid_ceiling_lamports: Set(None),
+            owner_cooldown_exempt: Set(false),
+            image_cid: Set(None),
+            metadata_cid: Set(None),
+            storage_backend: Set(None),
+            storage_receipts: Set(None),
+            image_hash: Set(None),
+            timelapse_hash: Set(None),
+            timelapse_format: Set(None),
+            timelapse_in_metadata: Set(false),
+            royalty_bps: Set(None),
+            nft_symbol: Set(None),
+            nft_standard: Set(canvas::NftStandard::default()),
+            collection_mint: Set(None),
+            collection_verified: Set(false),
+            metadata_updated_at: Set(None),
+            mint_signature: Set(None),
+            mint_submitted_at: Set(None),
+            burn_signature: Set(None),
+            burned_at: Set(None),
+            royalty_cursor: Set(None),
+            custom_attributes: Set(None),
+            parent_canvas_id: Set(parent_canvas_id),
+            program_id: Set(None),
+            deleted_at: Set(None),
         };
 
-        Ok(canvas.insert(db_connection).await?)
+        for attempt in 1..=INVITE_CODE_ATTEMPTS {
+            let savepoint = db_connection.begin().await?;
+            match canvas.clone().insert(&savepoint).await {
+                Ok(created) => {
+                    savepoint.commit().await?;
+                    return Ok(created);
+                }
+                Err(e) if is_invite_code_collision(&e) => {
+                    savepoint.rollback().await?;
+                    tracing::warn!(attempt, "Invite code collided, drawing another");
+                    canvas.invite_code = Set(generate_invite_code());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Err(AppError::InviteCodeConflict)
     }
 
     pub async fn add_canvas_collaborator<C: ConnectionTrait>(
@@ -137,10 +329,13 @@ impl CanvasRepository {
         Ok(count > 0)
     }
 
+    /// Moves the canvas to `state`, writing `events` to the outbox in the same transaction
+    /// so they go out exactly when the transition commits.
     pub async fn update_canvas_state<F>(
         db: &Database,
         id: Uuid,
         state: canvas::CanvasState,
+        events: Vec<NewOutboxEvent>,
         updater: F,
     ) -> Result<canvas::Model>
     where
@@ -149,6 +344,7 @@ impl CanvasRepository {
         let db_transaction = db.begin_transaction().await?;
 
         let canvas = Canvas::find_by_id(id)
+            .exclude_deleted()
             .lock_exclusive()
             .one(&db_transaction)
             .await?
@@ -165,8 +361,40 @@ impl CanvasRepository {
         updater(&mut active);
 
         let updated_canvas = active.update(&db_transaction).await?;
+        let has_events = !events.is_empty();
+        OutboxRepository::enqueue(&db_transaction, events).await?;
 
         db_transaction.commit().await?;
+        db.notify(DataChange::Canvas { canvas_id: id }).await;
+        if has_events {
+            db.wake_outbox();
+        }
+
+        Ok(updated_canvas)
+    }
+
+    /// Applies owner-configured settings without touching the canvas state.
+    pub async fn update_canvas<F>(db: &Database, id: Uuid, updater: F) -> Result<canvas::Model>
+    where
+        F: FnOnce(&mut canvas::ActiveModel),
+    {
+        let db_transaction = db.begin_transaction().await?;
+
+        let canvas = Canvas::find_by_id(id)
+            .exclude_deleted()
+            .lock_exclusive()
+            .one(&db_transaction)
+            .await?
+            .ok_or(AppError::CanvasNotFound)?;
+
+        let mut active: canvas::ActiveModel = canvas.into();
+
+        updater(&mut active);
+
+        let updated_canvas = active.update(&db_transaction).await?;
+
+        db_transaction.commit().await?;
+        db.notify(DataChange::Canvas { canvas_id: id }).await;
 
         Ok(updated_canvas)
     }
@@ -179,6 +407,7 @@ impl CanvasRepository {
         let db_transaction = db.begin_transaction().await?;
 
         let canvas = Canvas::find_by_id(id)
+            .exclude_deleted()
             .lock_exclusive()
             .one(&db_transaction)
             .await?
@@ -190,11 +419,54 @@ impl CanvasRepository {
         let updated_canvas = active.update(&db_transaction).await?;
 
         db_transaction.commit().await?;
+        db.notify(DataChange::Canvas { canvas_id: id }).await;
 
         Ok(updated_canvas)
     }
 
+    /// Soft-deletes the canvas. Its pixels, history and collaborators are kept, so
+    /// `restore_canvas` can bring it back.
     pub async fn delete_canvas(db: &Database, id: Uuid) -> Result<()> {
+        let deleted = Canvas::update_many()
+            .col_expr(canvas::Column::DeletedAt, Expr::value(Utc::now()))
+            .filter(canvas::Column::Id.eq(id))
+            .exclude_deleted()
+            .exec(db.get_connection())
+            .await?;
+
+        if deleted.rows_affected == 0 {
+            return Err(AppError::CanvasNotFound);
+        }
+
+        db.notify(DataChange::Canvas { canvas_id: id }).await;
+
+        Ok(())
+    }
+
+    /// Undoes `delete_canvas`.
+    pub async fn restore_canvas(db: &Database, id: Uuid) -> Result<canvas::Model> {
+        let db_transaction = db.begin_transaction().await?;
+
+        let canvas = Canvas::find_by_id(id)
+            .filter(canvas::Column::DeletedAt.is_not_null())
+            .lock_exclusive()
+            .one(&db_transaction)
+            .await?
+            .ok_or(AppError::CanvasNotFound)?;
+
+        let mut active: canvas::ActiveModel = canvas.into();
+        active.deleted_at = Set(None);
+
+        let restored_canvas = active.update(&db_transaction).await?;
+
+        db_transaction.commit().await?;
+        db.notify(DataChange::Canvas { canvas_id: id }).await;
+
+        Ok(restored_canvas)
+    }
+
+    /// Removes the canvas and everything on it for good, whether or not it was soft-deleted.
+    pub async fn purge_canvas(db: &Database, id: Uuid) -> Result<()> {
         let db_transaction = db.begin_transaction().await?;
 
         Pixel::delete_many()
@@ -202,6 +474,21 @@ impl CanvasRepository {
             .exec(&db_transaction)
             .await?;
 
+        CanvasGrid::delete_many()
+            .filter(canvas_grid::Column::CanvasId.eq(id))
+            .exec(&db_transaction)
+            .await?;
+
+        PixelWatch::delete_many()
+            .filter(pixel_watch::Column::CanvasId.eq(id))
+            .exec(&db_transaction)
+            .await?;
+
+        PixelHistory::delete_many()
+            .filter(pixel_history::Column::CanvasId.eq(id))
+            .exec(&db_transaction)
+            .await?;
+
         CanvasCollaborator::delete_many()
             .filter(canvas_collaborator::Column::CanvasId.eq(id))
             .exec(&db_transaction)
@@ -210,7 +497,17 @@ impl CanvasRepository {
         Canvas::delete_by_id(id).exec(&db_transaction).await?;
 
         db_transaction.commit().await?;
+        db.notify(DataChange::Canvas { canvas_id: id }).await;
 
         Ok(())
     }
 }
+
+/// Postgres names the constraint `canvases_invite_code_key`, and SQLite reports the
+/// `canvases.invite_code` column.
+fn is_invite_code_collision(error: &DbErr) -> bool {
+    matches!(
+        error.sql_err(),
+        Some(SqlErr::UniqueConstraintViolation(message)) if message.contains("invite_code")
+    )
+}
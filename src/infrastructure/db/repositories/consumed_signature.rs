@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, IntoActiveModel, JoinType,
+    PaginatorTrait, QueryFilter, QuerySelect, RelationTrait, sea_query::OnConflict,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::{
+        entities::{
+            ConsumedSignature,
+            consumed_signature::{self, SignatureOperation},
+            user,
+        },
+        repositories::pagination::{self, Keyed, Page},
+    },
+};
+
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct PixelBidRow {
+    pub signature: String,
+    pub user_id: Uuid,
+    pub wallet_address: String,
+    pub username: Option<String>,
+    pub amount_lamports: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Keyed for PixelBidRow {
+    type Id = String;
+
+    fn key(&self) -> (DateTime<Utc>, String) {
+        (self.created_at, self.signature.clone())
+    }
+}
+
+pub struct ConsumedSignatureRepository;
+
+impl ConsumedSignatureRepository {
+    pub async fn find_by_signature<C: ConnectionTrait>(
+        db_connection: &C,
+        signature: &str,
+    ) -> Result<Option<consumed_signature::Model>> {
+        let record = ConsumedSignature::find_by_id(signature.to_string())
+            .one(db_connection)
+            .await?;
+
+        Ok(record)
+    }
+
+    /// Records a signature as consumed. Returns `false` if it was already recorded.
+    pub async fn try_consume<C: ConnectionTrait>(
+        db_connection: &C,
+        record: consumed_signature::Model,
+    ) -> Result<bool> {
+        let inserted = ConsumedSignature::insert(record.into_active_model())
+            .on_conflict(
+                OnConflict::column(consumed_signature::Column::Signature)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db_connection)
+            .await?;
+
+        Ok(inserted > 0)
+    }
+
+    pub async fn release<C: ConnectionTrait>(db_connection: &C, signature: &str) -> Result<()> {
+        ConsumedSignature::delete_by_id(signature.to_string())
+            .exec(db_connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Lists confirmed bids on a pixel, newest first.
+    pub async fn find_pixel_bids<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        x: i16,
+        y: i16,
+        cursor: Option<&str>,
+        limit: u64,
+    ) -> Result<Page<PixelBidRow>> {
+        pagination::fetch_page(
+            db_connection,
+            Self::pixel_bids_query(canvas_id, x, y)
+                .select_only()
+                .column(consumed_signature::Column::Signature)
+                .column(consumed_signature::Column::UserId)
+                .column(user::Column::WalletAddress)
+                .column(user::Column::Username)
+                .column(consumed_signature::Column::AmountLamports)
+                .column(consumed_signature::Column::CreatedAt)
+                .join(
+                    JoinType::InnerJoin,
+                    consumed_signature::Relation::User.def(),
+                ),
+            consumed_signature::Column::CreatedAt,
+            &[consumed_signature::Column::Signature],
+            cursor,
+            limit,
+        )
+        .await
+    }
+
+    pub async fn count_pixel_bids<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        x: i16,
+        y: i16,
+    ) -> Result<u64> {
+        Ok(Self::pixel_bids_query(canvas_id, x, y)
+            .count(db_connection)
+            .await?)
+    }
+
+    fn pixel_bids_query(canvas_id: Uuid, x: i16, y: i16) -> sea_orm::Select<ConsumedSignature> {
+        ConsumedSignature::find()
+            .filter(consumed_signature::Column::Operation.eq(SignatureOperation::PixelConfirm))
+            .filter(consumed_signature::Column::CanvasId.eq(canvas_id))
+            .filter(consumed_signature::Column::X.eq(x))
+            .filter(consumed_signature::Column::Y.eq(y))
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, Result},
+    infrastructure::db::{
+        Database,
+        entities::{Canvas, EditionPrint, edition_print},
+        repositories::soft_delete::ExcludeDeleted,
+    },
+};
+
+pub struct EditionPrintRepository;
+
+impl EditionPrintRepository {
+    pub async fn find_edition_by_user<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<edition_print::Model>> {
+        Ok(EditionPrint::find()
+            .filter(edition_print::Column::CanvasId.eq(canvas_id))
+            .filter(edition_print::Column::UserId.eq(user_id))
+            .one(db_connection)
+            .await?)
+    }
+
+    pub async fn find_edition<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        edition_number: i32,
+    ) -> Result<Option<edition_print::Model>> {
+        Ok(EditionPrint::find_by_id((canvas_id, edition_number))
+            .one(db_connection)
+            .await?)
+    }
+
+    /// Reserves the next edition number for a user, or returns their existing reservation.
+    /// The canvas row is locked so concurrent reservations get consecutive numbers.
+    pub async fn reserve_edition(
+        db: &Database,
+        canvas_id: Uuid,
+        user_id: Uuid,
+        max_editions: i32,
+    ) -> Result<edition_print::Model> {
+        let db_transaction = db.begin_transaction().await?;
+
+        Canvas::find_by_id(canvas_id)
+            .exclude_deleted()
+            .lock_exclusive()
+            .one(&db_transaction)
+            .await?
+            .ok_or(AppError::CanvasNotFound)?;
+
+        if let Some(existing) =
+            Self::find_edition_by_user(&db_transaction, canvas_id, user_id).await?
+        {
+            db_transaction.rollback().await?;
+            return Ok(existing);
+        }
+
+        let last_edition: Option<i32> = EditionPrint::find()
+            .select_only()
+            .column(edition_print::Column::EditionNumber)
+            .filter(edition_print::Column::CanvasId.eq(canvas_id))
+            .order_by_desc(edition_print::Column::EditionNumber)
+            .into_tuple()
+            .one(&db_transaction)
+            .await?;
+        let edition_number = last_edition.unwrap_or(0) + 1;
+
+        if edition_number > max_editions {
+            db_transaction.rollback().await?;
+            return Err(AppError::InvalidParams(
+                "All edition prints have been reserved".into(),
+            ));
+        }
+
+        let edition = edition_print::ActiveModel {
+            canvas_id: Set(canvas_id),
+            edition_number: Set(edition_number),
+            user_id: Set(user_id),
+            mint_address: Set(None),
+            signature: Set(None),
+            created_at: Set(Utc::now()),
+            minted_at: Set(None),
+        }
+        .insert(&db_transaction)
+        .await?;
+
+        db_transaction.commit().await?;
+
+        Ok(edition)
+    }
+
+    pub async fn mark_edition_minted<C: ConnectionTrait>(
+        db_connection: &C,
+        edition: edition_print::Model,
+        mint_address: &str,
+        signature: &str,
+    ) -> Result<edition_print::Model> {
+        let mut active: edition_print::ActiveModel = edition.into();
+        active.mint_address = Set(Some(mint_address.to_string()));
+        active.signature = Set(Some(signature.to_string()));
+        active.minted_at = Set(Some(Utc::now()));
+
+        Ok(active.update(db_connection).await?)
+    }
+}
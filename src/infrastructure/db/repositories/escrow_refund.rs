@@ -0,0 +1,98 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, sea_query::OnConflict,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::entities::{EscrowRefund, escrow_refund},
+};
+
+pub struct EscrowRefundRepository;
+
+impl EscrowRefundRepository {
+    /// Records refunds, skipping users the same transaction already credited.
+    pub async fn record_refunds<C: ConnectionTrait>(
+        db_connection: &C,
+        refunds: Vec<escrow_refund::Model>,
+    ) -> Result<u64> {
+        if refunds.is_empty() {
+            return Ok(0);
+        }
+
+        let inserted =
+            EscrowRefund::insert_many(refunds.into_iter().map(IntoActiveModel::into_active_model))
+                .on_conflict(
+                    OnConflict::columns([
+                        escrow_refund::Column::SourceSignature,
+                        escrow_refund::Column::UserId,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(db_connection)
+                .await?;
+
+        Ok(inserted)
+    }
+
+    pub async fn find_unclaimed<C: ConnectionTrait>(
+        db_connection: &C,
+        user_id: Uuid,
+        canvas_id: Uuid,
+    ) -> Result<Vec<escrow_refund::Model>> {
+        Ok(EscrowRefund::find()
+            .filter(escrow_refund::Column::UserId.eq(user_id))
+            .filter(escrow_refund::Column::CanvasId.eq(canvas_id))
+            .filter(escrow_refund::Column::ClaimedAt.is_null())
+            .order_by_asc(escrow_refund::Column::CreatedAt)
+            .all(db_connection)
+            .await?)
+    }
+
+    /// Refunds credited by one bid or mint transaction, largest first.
+    pub async fn find_by_source<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        source_signature: &str,
+    ) -> Result<Vec<escrow_refund::Model>> {
+        Ok(EscrowRefund::find()
+            .filter(escrow_refund::Column::CanvasId.eq(canvas_id))
+            .filter(escrow_refund::Column::SourceSignature.eq(source_signature))
+            .order_by_desc(escrow_refund::Column::AmountLamports)
+            .all(db_connection)
+            .await?)
+    }
+
+    pub async fn find_by_claim_signature<C: ConnectionTrait>(
+        db_connection: &C,
+        claim_signature: &str,
+    ) -> Result<Vec<escrow_refund::Model>> {
+        Ok(EscrowRefund::find()
+            .filter(escrow_refund::Column::ClaimSignature.eq(claim_signature))
+            .all(db_connection)
+            .await?)
+    }
+
+    /// Marks the refunds claimed by one withdrawal. Returns how many were still unclaimed.
+    pub async fn mark_claimed<C: ConnectionTrait>(
+        db_connection: &C,
+        refund_ids: &[Uuid],
+        claim_signature: &str,
+    ) -> Result<u64> {
+        let result = EscrowRefund::update_many()
+            .set(escrow_refund::ActiveModel {
+                claim_signature: Set(Some(claim_signature.to_string())),
+                claimed_at: Set(Some(Utc::now())),
+                ..Default::default()
+            })
+            .filter(escrow_refund::Column::Id.is_in(refund_ids.iter().copied()))
+            .filter(escrow_refund::Column::ClaimedAt.is_null())
+            .exec(db_connection)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
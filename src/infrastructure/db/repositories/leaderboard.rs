@@ -0,0 +1,55 @@
+use sea_orm::{ConnectionTrait, DbBackend, EntityTrait, QueryOrder, QuerySelect};
+
+use crate::{
+    error::Result,
+    infrastructure::db::entities::{
+        LeaderboardCanvas, LeaderboardUser, leaderboard_canvas, leaderboard_user,
+    },
+};
+
+pub struct LeaderboardRepository;
+
+impl LeaderboardRepository {
+    /// Recomputes both leaderboards. Reads keep seeing the previous rankings until it
+    /// finishes. Only Postgres materializes them; other backends read live views.
+    pub async fn refresh<C: ConnectionTrait>(db_connection: &C) -> Result<()> {
+        if db_connection.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        db_connection
+            .execute_unprepared("REFRESH MATERIALIZED VIEW CONCURRENTLY leaderboard_canvases")
+            .await?;
+        db_connection
+            .execute_unprepared("REFRESH MATERIALIZED VIEW CONCURRENTLY leaderboard_users")
+            .await?;
+
+        Ok(())
+    }
+
+    /// Canvases holding the most escrow, most first.
+    pub async fn top_canvases<C: ConnectionTrait>(
+        db_connection: &C,
+        limit: u64,
+    ) -> Result<Vec<leaderboard_canvas::Model>> {
+        Ok(LeaderboardCanvas::find()
+            .order_by_desc(leaderboard_canvas::Column::TotalEscrowed)
+            .order_by_asc(leaderboard_canvas::Column::CanvasId)
+            .limit(limit)
+            .all(db_connection)
+            .await?)
+    }
+
+    /// Users owning the most pixels, most first.
+    pub async fn top_users<C: ConnectionTrait>(
+        db_connection: &C,
+        limit: u64,
+    ) -> Result<Vec<leaderboard_user::Model>> {
+        Ok(LeaderboardUser::find()
+            .order_by_desc(leaderboard_user::Column::PixelsOwned)
+            .order_by_asc(leaderboard_user::Column::UserId)
+            .limit(limit)
+            .all(db_connection)
+            .await?)
+    }
+}
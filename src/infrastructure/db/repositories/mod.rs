@@ -1,17 +1,47 @@
+pub mod audit_log;
 pub mod canvas;
+pub mod consumed_signature;
+pub mod edition_print;
+pub mod escrow_refund;
+pub mod leaderboard;
+pub mod onchain_event;
+pub mod outbox;
+pub mod pagination;
 pub mod pixel;
+pub mod pixel_history;
+pub mod pixel_lock;
+pub mod pixel_watch;
+pub mod royalty_payout;
+pub mod soft_delete;
 pub mod user;
 
+pub use audit_log::AuditLogRepository;
 pub use canvas::CanvasRepository;
+pub use consumed_signature::ConsumedSignatureRepository;
+pub use edition_print::EditionPrintRepository;
+pub use escrow_refund::EscrowRefundRepository;
+pub use leaderboard::LeaderboardRepository;
+pub use onchain_event::OnchainEventRepository;
+pub use outbox::{NewOutboxEvent, OutboxRepository};
+pub use pagination::{Keyed, Page};
 pub use pixel::PixelRepository;
+pub use pixel_history::PixelHistoryRepository;
+pub use pixel_lock::PixelLockRepository;
+pub use pixel_watch::PixelWatchRepository;
 use rand::Rng;
+pub use royalty_payout::RoyaltyPayoutRepository;
+pub use soft_delete::{ExcludeDeleted, SoftDelete};
 pub use user::UserRepository;
 
+/// Fills the `invite_code` column, which holds up to 12 characters. That leaves about
+/// 3.4 * 10^18 codes, so collisions are rare enough to retry.
 pub fn generate_invite_code() -> String {
     const CHARSET: &[u8] = b"ABCDEFGHJKLMNOPQRSTUVWXYZ0123456789";
-    (0..8)
+    const LENGTH: usize = 12;
+
+    let mut rng = rand::rng();
+    (0..LENGTH)
         .map(|_| {
-            let mut rng = rand::rng();
             let idx = rng.random_range(0..CHARSET.len());
             CHARSET[idx] as char
         })
@@ -0,0 +1,50 @@
+use sea_orm::{
+    ConnectionTrait, EntityTrait, IntoActiveModel, QueryOrder, QuerySelect, sea_query::OnConflict,
+};
+
+use crate::{
+    error::Result,
+    infrastructure::db::entities::{OnchainEvent, onchain_event},
+};
+
+pub struct OnchainEventRepository;
+
+impl OnchainEventRepository {
+    /// Inserts events, skipping instructions that are already indexed.
+    pub async fn record_events<C: ConnectionTrait>(
+        db_connection: &C,
+        events: Vec<onchain_event::Model>,
+    ) -> Result<u64> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let inserted =
+            OnchainEvent::insert_many(events.into_iter().map(IntoActiveModel::into_active_model))
+                .on_conflict(
+                    OnConflict::columns([
+                        onchain_event::Column::Signature,
+                        onchain_event::Column::InstructionIndex,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(db_connection)
+                .await?;
+
+        Ok(inserted)
+    }
+
+    /// Signature of the newest indexed event, where indexing resumes without a cursor.
+    pub async fn find_latest_signature<C: ConnectionTrait>(
+        db_connection: &C,
+    ) -> Result<Option<String>> {
+        Ok(OnchainEvent::find()
+            .select_only()
+            .column(onchain_event::Column::Signature)
+            .order_by_desc(onchain_event::Column::Slot)
+            .into_tuple::<String>()
+            .one(db_connection)
+            .await?)
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    sea_query::{Expr, LockBehavior, LockType},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::{
+        Database,
+        entities::{OutboxEvent, outbox_event, outbox_event::OutboxTopic, pixel},
+    },
+};
+
+/// An event to write to the outbox, in the transaction of the change it reports.
+#[derive(Debug, Clone)]
+pub struct NewOutboxEvent {
+    pub topic: OutboxTopic,
+    pub payload: serde_json::Value,
+}
+
+/// Payload of a `Pixel` event: the pixel as written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PixelEventPayload {
+    pub canvas_id: Uuid,
+    pub x: i16,
+    pub y: i16,
+    pub color: i16,
+    pub owner_id: Option<Uuid>,
+    pub price_lamports: i64,
+}
+
+impl NewOutboxEvent {
+    pub fn pixel(pixel: &pixel::Model) -> Result<Self> {
+        Ok(Self {
+            topic: OutboxTopic::Pixel,
+            payload: serde_json::to_value(PixelEventPayload {
+                canvas_id: pixel.canvas_id,
+                x: pixel.x,
+                y: pixel.y,
+                color: pixel.color,
+                owner_id: pixel.owner_id,
+                price_lamports: pixel.price_lamports,
+            })?,
+        })
+    }
+}
+
+pub struct OutboxRepository;
+
+impl OutboxRepository {
+    /// Writes events for the dispatcher. Pass the transaction of the change they report,
+    /// so they are kept exactly when it commits.
+    pub async fn enqueue<C: ConnectionTrait>(
+        db_connection: &C,
+        events: Vec<NewOutboxEvent>,
+    ) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        OutboxEvent::insert_many(events.into_iter().map(|event| outbox_event::ActiveModel {
+            id: NotSet,
+            topic: Set(event.topic),
+            payload: Set(event.payload),
+            attempts: Set(0),
+            available_at: Set(now),
+            last_error: Set(None),
+            created_at: Set(now),
+        }))
+        .exec_without_returning(db_connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Takes up to `limit` due events, oldest first, and holds them for `lease`. Events a
+    /// crashed dispatcher held come due again once the lease runs out. Dispatchers on other
+    /// instances skip the rows instead of waiting for them.
+    pub async fn claim_due(
+        db: &Database,
+        limit: u64,
+        lease: Duration,
+    ) -> Result<Vec<outbox_event::Model>> {
+        let db_transaction = db.begin_transaction().await?;
+        let now = Utc::now();
+
+        let events = OutboxEvent::find()
+            .filter(outbox_event::Column::AvailableAt.lte(now))
+            .order_by_asc(outbox_event::Column::Id)
+            .limit(limit)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(&db_transaction)
+            .await?;
+
+        if !events.is_empty() {
+            let lease_until = now + chrono::Duration::milliseconds(lease.as_millis() as i64);
+            OutboxEvent::update_many()
+                .col_expr(outbox_event::Column::AvailableAt, Expr::value(lease_until))
+                .col_expr(
+                    outbox_event::Column::Attempts,
+                    Expr::col(outbox_event::Column::Attempts).add(1),
+                )
+                .filter(outbox_event::Column::Id.is_in(events.iter().map(|event| event.id)))
+                .exec(&db_transaction)
+                .await?;
+        }
+
+        db_transaction.commit().await?;
+
+        Ok(events)
+    }
+
+    /// Removes an event that was delivered or given up on.
+    pub async fn remove<C: ConnectionTrait>(db_connection: &C, id: i64) -> Result<()> {
+        OutboxEvent::delete_by_id(id).exec(db_connection).await?;
+        Ok(())
+    }
+
+    /// Makes a failed event due again at `at`.
+    pub async fn retry_at<C: ConnectionTrait>(
+        db_connection: &C,
+        id: i64,
+        at: DateTime<Utc>,
+        error: &str,
+    ) -> Result<()> {
+        OutboxEvent::update_many()
+            .col_expr(outbox_event::Column::AvailableAt, Expr::value(at))
+            .col_expr(outbox_event::Column::LastError, Expr::value(error))
+            .filter(outbox_event::Column::Id.eq(id))
+            .exec(db_connection)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use std::{fmt::Display, str::FromStr};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder,
+    QuerySelect, Select, Value,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, Result},
+    infrastructure::db::entities::{audit_log, canvas},
+};
+
+/// A row listed newest first by `(created_at, id)`, so a page can resume after it.
+pub trait Keyed {
+    type Id: KeyId;
+
+    fn key(&self) -> (DateTime<Utc>, Self::Id);
+}
+
+/// Breaks ties between rows with the same timestamp. Composite ids give one value per
+/// column, in the order the columns are passed to `fetch_page`.
+pub trait KeyId: Clone + Display + FromStr {
+    fn values(self) -> Vec<Value>;
+}
+
+impl KeyId for Uuid {
+    fn values(self) -> Vec<Value> {
+        vec![self.into()]
+    }
+}
+
+impl KeyId for i64 {
+    fn values(self) -> Vec<Value> {
+        vec![self.into()]
+    }
+}
+
+impl KeyId for String {
+    fn values(self) -> Vec<Value> {
+        vec![self.into()]
+    }
+}
+
+impl Keyed for canvas::Model {
+    type Id = Uuid;
+
+    fn key(&self) -> (DateTime<Utc>, Uuid) {
+        (self.created_at, self.id)
+    }
+}
+
+impl Keyed for audit_log::Model {
+    type Id = i64;
+
+    fn key(&self) -> (DateTime<Utc>, i64) {
+        (self.created_at, self.id)
+    }
+}
+
+/// Key of the last row on a page. Clients get it as an opaque token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor<K> {
+    pub created_at: DateTime<Utc>,
+    pub id: K,
+}
+
+impl<K: Display + FromStr> Cursor<K> {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}|{}",
+            self.created_at.timestamp_micros(),
+            self.id
+        ))
+    }
+
+    pub fn decode(token: &str) -> Result<Self> {
+        let invalid = || AppError::InvalidParams("Invalid cursor".into());
+
+        let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (micros, id) = text.split_once('|').ok_or_else(invalid)?;
+
+        Ok(Self {
+            created_at: micros
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_micros)
+                .ok_or_else(invalid)?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// One page of rows, and the token for the next one if there are more.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// Runs `query` as a keyset page, newest first. Unlike OFFSET, deep pages cost the same as
+/// the first. `query` must not be ordered already, and `created_at` and `id` must be the
+/// columns `M::key` reads.
+pub async fn fetch_page<E, M, Col, C>(
+    db_connection: &C,
+    query: Select<E>,
+    created_at: Col,
+    id: &[Col],
+    cursor: Option<&str>,
+    limit: u64,
+) -> Result<Page<M>>
+where
+    E: EntityTrait,
+    M: FromQueryResult + Keyed,
+    Col: ColumnTrait,
+    C: ConnectionTrait,
+{
+    let mut query = query;
+    if let Some(token) = cursor {
+        let after = Cursor::<M::Id>::decode(token)?;
+        query = query.filter(after_key(created_at, id, after));
+    }
+
+    // One extra row tells whether another page follows
+    let mut query = query.order_by_desc(created_at);
+    for column in id {
+        query = query.order_by_desc(*column);
+    }
+    let mut items = query
+        .limit(limit + 1)
+        .into_model::<M>()
+        .all(db_connection)
+        .await?;
+
+    let next_cursor = if items.len() as u64 > limit {
+        items.truncate(limit as usize);
+        items.last().map(|item| {
+            let (created_at, id) = item.key();
+            Cursor { created_at, id }.encode()
+        })
+    } else {
+        None
+    };
+
+    Ok(Page { items, next_cursor })
+}
+
+/// Rows sorting after `after` in descending key order: a lower timestamp, or the same one and
+/// a lower id, compared column by column.
+fn after_key<Col: ColumnTrait, K: KeyId>(
+    created_at: Col,
+    id: &[Col],
+    after: Cursor<K>,
+) -> Condition {
+    let keys: Vec<(Col, Value)> = std::iter::once((created_at, after.created_at.into()))
+        .chain(id.iter().copied().zip(after.id.values()))
+        .collect();
+
+    keys.iter()
+        .enumerate()
+        .fold(Condition::any(), |condition, (index, (column, value))| {
+            let tied = keys[..index]
+                .iter()
+                .fold(Condition::all(), |tied, (column, value)| {
+                    tied.add(column.eq(value.clone()))
+                });
+            condition.add(tied.add(column.lt(value.clone())))
+        })
+}
@@ -1,7 +1,13 @@
-use chrono::Utc;
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
-    QueryOrder, QuerySelect, prelude::Expr, sea_query::Alias,
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, FromQueryResult, JoinType,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Value,
+    prelude::Expr,
+    sea_query::{Alias, OnConflict},
 };
 use uuid::Uuid;
 
@@ -9,94 +15,499 @@ use crate::{
     error::{AppError, Result},
     infrastructure::db::{
         Database,
-        entities::{Pixel, pixel},
+        entities::{CanvasGrid, Pixel, canvas, canvas_grid, pixel},
+        repositories::{
+            outbox::{NewOutboxEvent, OutboxRepository},
+            pagination::{self, KeyId, Keyed, Page},
+            pixel_history::{PixelChangeSource, PixelHistoryRepository},
+        },
     },
 };
 
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct OwnedPixelRow {
+    pub canvas_id: Uuid,
+    pub canvas_name: String,
+    pub canvas_state: canvas::CanvasState,
+    pub x: i16,
+    pub y: i16,
+    pub color: i16,
+    pub price_lamports: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Keyed for OwnedPixelRow {
+    type Id = PixelKey;
+
+    fn key(&self) -> (DateTime<Utc>, PixelKey) {
+        (
+            self.updated_at,
+            PixelKey {
+                canvas_id: self.canvas_id,
+                x: self.x,
+                y: self.y,
+            },
+        )
+    }
+}
+
+/// Identifies a pixel across canvases, in the column order of the `pixels` primary key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelKey {
+    pub canvas_id: Uuid,
+    pub x: i16,
+    pub y: i16,
+}
+
+impl fmt::Display for PixelKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.canvas_id, self.x, self.y)
+    }
+}
+
+impl FromStr for PixelKey {
+    type Err = AppError;
+
+    fn from_str(key: &str) -> Result<Self> {
+        let invalid = || AppError::InvalidParams("Invalid pixel key".into());
+
+        let mut parts = key.splitn(3, ':');
+        let mut next = || parts.next().ok_or_else(invalid);
+        Ok(Self {
+            canvas_id: next()?.parse().map_err(|_| invalid())?,
+            x: next()?.parse().map_err(|_| invalid())?,
+            y: next()?.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl KeyId for PixelKey {
+    fn values(self) -> Vec<Value> {
+        vec![self.canvas_id.into(), self.x.into(), self.y.into()]
+    }
+}
+
+/// Fields to change on a pixel. `None` leaves a field as is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PixelWrite {
+    pub color: Option<i16>,
+    pub owner_id: Option<Uuid>,
+    pub price_lamports: Option<i64>,
+    pub expected_version: Option<i32>,
+}
+
 pub struct PixelRepository;
 
 impl PixelRepository {
+    /// The pixel at `(x, y)`. Unowned pixels have no row and are read from the canvas grid.
     pub async fn find_pixel<C: ConnectionTrait>(
         db_connection: &C,
         canvas_id: Uuid,
         x: i16,
         y: i16,
     ) -> Result<Option<pixel::Model>> {
-        Ok(Pixel::find()
-            .filter(pixel::Column::CanvasId.eq(canvas_id))
-            .filter(pixel::Column::X.eq(x))
-            .filter(pixel::Column::Y.eq(y))
+        if let Some(pixel) = Pixel::find_by_id((canvas_id, x, y))
             .one(db_connection)
-            .await?)
+            .await?
+        {
+            return Ok(Some(pixel));
+        }
+
+        Ok(CanvasGrid::find_by_id(canvas_id)
+            .one(db_connection)
+            .await?
+            .and_then(|grid| grid_pixel(&grid, x, y)))
     }
 
-    // when we need to fetch all pixels of a canvas
+    /// Every pixel of the canvas, from its grid and the rows of owned pixels. Prefer
+    /// `find_canvas_colors` and `find_owned_pixels_by_canvas` when only one is needed.
     pub async fn find_pixels_by_canvas<C: ConnectionTrait>(
         db_connection: &C,
         canvas_id: Uuid,
+    ) -> Result<Vec<pixel::Model>> {
+        let grid = CanvasGrid::find_by_id(canvas_id).one(db_connection).await?;
+        let owned = Pixel::find()
+            .filter(pixel::Column::CanvasId.eq(canvas_id))
+            .all(db_connection)
+            .await?;
+
+        let Some(grid) = grid else {
+            return Ok(owned);
+        };
+
+        let mut owned: HashMap<(i16, i16), pixel::Model> = owned
+            .into_iter()
+            .map(|pixel| ((pixel.x, pixel.y), pixel))
+            .collect();
+        Ok((0..grid.colors.len())
+            .filter_map(|index| {
+                let (x, y) = grid_position(&grid, index);
+                owned.remove(&(x, y)).or_else(|| grid_pixel(&grid, x, y))
+            })
+            .collect())
+    }
+
+    /// Colors of every pixel of the canvas, one byte each in row order.
+    pub async fn find_canvas_colors<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        width: u8,
+        height: u8,
+    ) -> Result<Vec<u8>> {
+        if let Some(grid) = CanvasGrid::find_by_id(canvas_id).one(db_connection).await? {
+            return Ok(grid.colors);
+        }
+
+        // Canvases without a grid keep every written pixel as a row
+        let mut colors = vec![0u8; (width as usize) * (height as usize)];
+        for pixel in Self::find_pixels_by_canvas(db_connection, canvas_id).await? {
+            let index = (pixel.y as usize) * (width as usize) + (pixel.x as usize);
+            if let Some(color) = colors.get_mut(index) {
+                *color = pixel.color as u8;
+            }
+        }
+        Ok(colors)
+    }
+
+    pub async fn find_owned_pixels_by_canvas<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
     ) -> Result<Vec<pixel::Model>> {
         Ok(Pixel::find()
             .filter(pixel::Column::CanvasId.eq(canvas_id))
+            .filter(pixel::Column::OwnerId.is_not_null())
             .all(db_connection)
             .await?)
     }
 
+    pub async fn count_pixels_owned_on_canvas<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        owner_id: Uuid,
+    ) -> Result<u64> {
+        Ok(Pixel::find()
+            .filter(pixel::Column::CanvasId.eq(canvas_id))
+            .filter(pixel::Column::OwnerId.eq(owner_id))
+            .count(db_connection)
+            .await?)
+    }
+
+    /// Lists pixels owned by a user across all canvases, most recently updated first.
+    pub async fn find_pixels_by_owner<C: ConnectionTrait>(
+        db_connection: &C,
+        owner_id: Uuid,
+        cursor: Option<&str>,
+        limit: u64,
+    ) -> Result<Page<OwnedPixelRow>> {
+        pagination::fetch_page(
+            db_connection,
+            Pixel::find()
+                .select_only()
+                .column(pixel::Column::CanvasId)
+                .column_as(canvas::Column::Name, "canvas_name")
+                .column_as(canvas::Column::State, "canvas_state")
+                .column(pixel::Column::X)
+                .column(pixel::Column::Y)
+                .column(pixel::Column::Color)
+                .column(pixel::Column::PriceLamports)
+                .column(pixel::Column::UpdatedAt)
+                .join(JoinType::InnerJoin, pixel::Relation::Canvas.def())
+                .filter(pixel::Column::OwnerId.eq(owner_id)),
+            pixel::Column::UpdatedAt,
+            &[pixel::Column::CanvasId, pixel::Column::X, pixel::Column::Y],
+            cursor,
+            limit,
+        )
+        .await
+    }
+
+    /// Returns (pixel count, total lamports invested) for a user across all canvases.
+    pub async fn summarize_pixels_by_owner<C: ConnectionTrait>(
+        db_connection: &C,
+        owner_id: Uuid,
+    ) -> Result<(i64, i64)> {
+        let summary = Pixel::find()
+            .select_only()
+            .column_as(Expr::cust("COUNT(*)"), "pixel_count")
+            .column_as(
+                Expr::cust("CAST(COALESCE(SUM(price_lamports), 0) AS BIGINT)"),
+                "total_lamports",
+            )
+            .filter(pixel::Column::OwnerId.eq(owner_id))
+            .into_tuple::<(i64, i64)>()
+            .one(db_connection)
+            .await?;
+
+        Ok(summary.unwrap_or((0, 0)))
+    }
+
+    /// Writes a pixel, bumping its version. Only owned pixels are kept as rows, and the color
+    /// of every pixel goes to the canvas grid. The write is announced to the canvas's room
+    /// through the outbox.
+    ///
+    /// With `expected_version` set, fails with `PixelVersionConflict` if the pixel changed
+    /// since the caller read it, so callers can re-read, re-validate and retry.
     pub async fn upsert_pixel(
         db: &Database,
         canvas_id: Uuid,
         x: i16,
         y: i16,
-        color: Option<i16>,
-        owner_id: Option<Uuid>,
-        price_lamports: Option<i64>,
+        write: PixelWrite,
+        source: &PixelChangeSource,
     ) -> Result<pixel::Model> {
         let db_transaction = db.begin_transaction().await?;
         let now = Utc::now();
-        let existing_pixel = Self::find_pixel(db.get_connection(), canvas_id, x, y).await?;
+        // The grid is locked before any row, by every writer, so they cannot deadlock
+        let grid = CanvasGrid::find_by_id(canvas_id)
+            .lock_exclusive()
+            .one(&db_transaction)
+            .await?;
+        let existing_row = Pixel::find_by_id((canvas_id, x, y))
+            .lock_exclusive()
+            .one(&db_transaction)
+            .await?;
+        let existing_pixel = existing_row
+            .clone()
+            .or_else(|| grid.as_ref().and_then(|grid| grid_pixel(grid, x, y)));
 
-        if let Some(existing_pixel) = existing_pixel {
-            let mut active: pixel::ActiveModel = existing_pixel.into();
+        let current_version = existing_pixel.as_ref().map(|pixel| pixel.version);
+        if write
+            .expected_version
+            .is_some_and(|expected| Some(expected) != current_version)
+        {
+            db_transaction.rollback().await?;
+            return Err(AppError::PixelVersionConflict);
+        }
 
-            if let Some(color) = color {
-                active.color = Set(color);
-            }
-            if let Some(owner_id) = owner_id {
-                active.owner_id = Set(Some(owner_id));
+        let color = write
+            .color
+            .or(existing_pixel.as_ref().map(|pixel| pixel.color))
+            .ok_or_else(|| AppError::InvalidParams("Color required for new pixel".into()))?;
+        let owner_id = write
+            .owner_id
+            .or(existing_pixel.as_ref().and_then(|pixel| pixel.owner_id));
+
+        let mut written_pixel = pixel::Model {
+            canvas_id,
+            x,
+            y,
+            color,
+            owner_id,
+            price_lamports: write
+                .price_lamports
+                .or(existing_pixel.as_ref().map(|pixel| pixel.price_lamports))
+                .unwrap_or(0),
+            version: existing_pixel.as_ref().map_or(0, |pixel| pixel.version + 1),
+            updated_at: now,
+        };
+
+        if owner_id.is_some() || grid.is_none() {
+            let active = pixel::ActiveModel::from(written_pixel.clone()).reset_all();
+            written_pixel = if existing_row.is_some() {
+                active.update(&db_transaction).await?
+            } else {
+                active.insert(&db_transaction).await?
+            };
+        } else {
+            // Nothing tracks the version of a pixel without a row
+            written_pixel.version = 0;
+        }
+
+        if let Some(grid) = &grid {
+            write_grid_colors(&db_transaction, grid, [(x, y, color)], now).await?;
+        }
+
+        PixelHistoryRepository::record_change(
+            &db_transaction,
+            existing_pixel.as_ref(),
+            &written_pixel,
+            source,
+        )
+        .await?;
+        OutboxRepository::enqueue(
+            &db_transaction,
+            vec![NewOutboxEvent::pixel(&written_pixel)?],
+        )
+        .await?;
+
+        db_transaction.commit().await?;
+        db.wake_outbox();
+
+        Ok(written_pixel)
+    }
+
+    /// Recolors several pixels owned by `owner_id` in a single transaction, written like
+    /// `upsert_pixels_bulk`. Fails without applying any change if one of the pixels isn't
+    /// owned by them.
+    pub async fn update_owned_pixel_colors(
+        db: &Database,
+        canvas_id: Uuid,
+        owner_id: Uuid,
+        updates: &[(i16, i16, i16)],
+        signature: &str,
+    ) -> Result<Vec<pixel::Model>> {
+        let source = PixelChangeSource {
+            actor_id: Some(owner_id),
+            signature: Some(signature.to_string()),
+        };
+
+        Self::write_pixels_bulk(db, canvas_id, updates, &source, Some(owner_id)).await
+    }
+
+    /// Sets the colors of several pixels, with a single `INSERT ... ON CONFLICT DO UPDATE`
+    /// for those with a row and one write of the canvas grid. Versions of pixels with a row
+    /// are bumped, and pixels created here are unowned. A pixel given more than once takes
+    /// its last color. Returns the written pixels, in the order given.
+    pub async fn upsert_pixels_bulk(
+        db: &Database,
+        canvas_id: Uuid,
+        colors: &[(i16, i16, i16)],
+        source: &PixelChangeSource,
+    ) -> Result<Vec<pixel::Model>> {
+        Self::write_pixels_bulk(db, canvas_id, colors, source, None).await
+    }
+
+    /// With `owner_id` set, every pixel must already have a row owned by them, checked under
+    /// the row locks so a bid landing meanwhile is not painted over.
+    async fn write_pixels_bulk(
+        db: &Database,
+        canvas_id: Uuid,
+        colors: &[(i16, i16, i16)],
+        source: &PixelChangeSource,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<pixel::Model>> {
+        let order: HashMap<(i16, i16), usize> = colors
+            .iter()
+            .enumerate()
+            .map(|(index, &(x, y, _))| ((x, y), index))
+            .collect();
+        // Postgres rejects a statement that updates the same row twice
+        let colors: HashMap<(i16, i16), i16> = colors
+            .iter()
+            .map(|&(x, y, color)| ((x, y), color))
+            .collect();
+        if colors.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let db_transaction = db.begin_transaction().await?;
+        let now = Utc::now();
+        let grid = CanvasGrid::find_by_id(canvas_id)
+            .lock_exclusive()
+            .one(&db_transaction)
+            .await?;
+
+        let positions = colors.keys().fold(Condition::any(), |condition, &(x, y)| {
+            condition.add(pixel::Column::X.eq(x).and(pixel::Column::Y.eq(y)))
+        });
+        let existing_rows: HashMap<(i16, i16), pixel::Model> = Pixel::find()
+            .filter(pixel::Column::CanvasId.eq(canvas_id))
+            .filter(positions)
+            .lock_exclusive()
+            .all(&db_transaction)
+            .await?
+            .into_iter()
+            .map(|pixel| ((pixel.x, pixel.y), pixel))
+            .collect();
+
+        if let Some(owner_id) = owner_id {
+            for position in colors.keys() {
+                let pixel = existing_rows
+                    .get(position)
+                    .ok_or(AppError::InvalidParams("Pixel not found".into()))?;
+                if pixel.owner_id != Some(owner_id) {
+                    return Err(AppError::Unauthorized);
+                }
             }
-            if let Some(price_lamports) = price_lamports {
-                active.price_lamports = Set(price_lamports);
+        }
+
+        // Without a grid, every pixel is kept as a row
+        let rows: Vec<pixel::ActiveModel> = colors
+            .iter()
+            .filter(|(position, _)| grid.is_none() || existing_rows.contains_key(position))
+            .map(|(&(x, y), &color)| {
+                let existing = existing_rows.get(&(x, y));
+                pixel::ActiveModel {
+                    canvas_id: Set(canvas_id),
+                    x: Set(x),
+                    y: Set(y),
+                    color: Set(color),
+                    owner_id: Set(existing.and_then(|pixel| pixel.owner_id)),
+                    price_lamports: Set(existing.map_or(0, |pixel| pixel.price_lamports)),
+                    version: Set(0),
+                    updated_at: Set(now),
+                }
+            })
+            .collect();
+
+        let mut written_pixels = if rows.is_empty() {
+            Vec::new()
+        } else {
+            Pixel::insert_many(rows)
+                .on_conflict(
+                    OnConflict::columns([
+                        pixel::Column::CanvasId,
+                        pixel::Column::X,
+                        pixel::Column::Y,
+                    ])
+                    .update_columns([pixel::Column::Color, pixel::Column::UpdatedAt])
+                    .value(
+                        pixel::Column::Version,
+                        Expr::col((Pixel, pixel::Column::Version)).add(1),
+                    )
+                    .to_owned(),
+                )
+                .exec_with_returning_many(&db_transaction)
+                .await?
+        };
+
+        let mut existing_pixels = existing_rows;
+        if let Some(grid) = &grid {
+            for (&(x, y), &color) in &colors {
+                if existing_pixels.contains_key(&(x, y)) {
+                    continue;
+                }
+                if let Some(pixel) = grid_pixel(grid, x, y) {
+                    written_pixels.push(pixel::Model {
+                        color,
+                        updated_at: now,
+                        ..pixel.clone()
+                    });
+                    existing_pixels.insert((x, y), pixel);
+                }
             }
-            active.updated_at = Set(now);
 
-            let updated_pixel = active.update(&db_transaction).await?;
+            write_grid_colors(
+                &db_transaction,
+                grid,
+                colors.iter().map(|(&(x, y), &color)| (x, y, color)),
+                now,
+            )
+            .await?;
+        }
 
-            db_transaction.commit().await?;
+        let changes: Vec<_> = written_pixels
+            .iter()
+            .map(|pixel| (existing_pixels.get(&(pixel.x, pixel.y)), pixel))
+            .collect();
+        PixelHistoryRepository::record_changes(&db_transaction, &changes, source).await?;
 
-            Ok(updated_pixel)
-        } else {
-            // validating required fields for new insert
-            let color = color
-                .ok_or_else(|| AppError::InvalidParams("Color required for new pixel".into()))?;
-            let price_lamports = price_lamports.unwrap_or(0);
-
-            let pixel = pixel::ActiveModel {
-                canvas_id: Set(canvas_id),
-                x: Set(x),
-                y: Set(y),
-                color: Set(color),
-                owner_id: Set(owner_id),
-                price_lamports: Set(price_lamports),
-                updated_at: Set(now),
-            };
+        written_pixels.sort_by_key(|pixel| order.get(&(pixel.x, pixel.y)).copied());
 
-            let inserted_pixel = pixel.insert(&db_transaction).await?;
+        let events = written_pixels
+            .iter()
+            .map(NewOutboxEvent::pixel)
+            .collect::<Result<Vec<_>>>()?;
+        OutboxRepository::enqueue(&db_transaction, events).await?;
 
-            db_transaction.commit().await?;
+        db_transaction.commit().await?;
+        db.wake_outbox();
 
-            Ok(inserted_pixel)
-        }
+        Ok(written_pixels)
     }
 
+    /// Creates the canvas grid with every pixel in `initial_color`. Pixels get a row once
+    /// they are owned.
     pub async fn initialize_canvas_pixels<C: ConnectionTrait>(
         db_connection: &C,
         canvas_id: Uuid,
@@ -104,27 +515,17 @@ impl PixelRepository {
         height: u8,
         initial_color: i16,
     ) -> Result<()> {
-        let now = Utc::now();
-        let mut pixels = Vec::with_capacity((width as usize) * (height as usize));
+        let grid = canvas_grid::ActiveModel {
+            canvas_id: Set(canvas_id),
+            width: Set(width as i16),
+            colors: Set(vec![
+                initial_color as u8;
+                (width as usize) * (height as usize)
+            ]),
+            updated_at: Set(Utc::now()),
+        };
 
-        for y in 0..height {
-            for x in 0..width {
-                let pixel = pixel::ActiveModel {
-                    canvas_id: Set(canvas_id),
-                    x: Set(x as i16),
-                    y: Set(y as i16),
-                    color: Set(initial_color),
-                    owner_id: Set(None),
-                    price_lamports: Set(0),
-                    updated_at: Set(now),
-                };
-                pixels.push(pixel);
-            }
-        }
-
-        if !pixels.is_empty() {
-            Pixel::insert_many(pixels).exec(db_connection).await?;
-        }
+        CanvasGrid::insert(grid).exec(db_connection).await?;
 
         Ok(())
     }
@@ -138,7 +539,7 @@ impl PixelRepository {
             .select_only()
             .column(pixel::Column::OwnerId)
             .column_as(
-                Expr::cust("COALESCE(SUM(price_lamports)::BIGINT, 0)"),
+                Expr::cust("CAST(COALESCE(SUM(price_lamports), 0) AS BIGINT)"),
                 "total_lamports",
             )
             .filter(pixel::Column::CanvasId.eq(canvas_id))
@@ -152,4 +553,87 @@ impl PixelRepository {
 
         Ok(results)
     }
+
+    /// Canvases with pixels written since `since`, most recently written first.
+    pub async fn find_recently_active_canvases<C: ConnectionTrait>(
+        db_connection: &C,
+        since: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Uuid>> {
+        Ok(CanvasGrid::find()
+            .select_only()
+            .column(canvas_grid::Column::CanvasId)
+            .filter(canvas_grid::Column::UpdatedAt.gte(since))
+            .order_by_desc(canvas_grid::Column::UpdatedAt)
+            .limit(limit)
+            .into_tuple::<Uuid>()
+            .all(db_connection)
+            .await?)
+    }
+
+    /// Lamports each pixel owner has bid on the canvas, for every owner.
+    pub async fn sum_bids_by_owner<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+    ) -> Result<Vec<(Uuid, i64)>> {
+        Ok(Pixel::find()
+            .select_only()
+            .column(pixel::Column::OwnerId)
+            .column_as(
+                Expr::cust("CAST(COALESCE(SUM(price_lamports), 0) AS BIGINT)"),
+                "total_lamports",
+            )
+            .filter(pixel::Column::CanvasId.eq(canvas_id))
+            .filter(pixel::Column::OwnerId.is_not_null())
+            .group_by(pixel::Column::OwnerId)
+            .into_tuple::<(Uuid, i64)>()
+            .all(db_connection)
+            .await?)
+    }
+}
+
+fn grid_position(grid: &canvas_grid::Model, index: usize) -> (i16, i16) {
+    let width = grid.width.max(1) as usize;
+    ((index % width) as i16, (index / width) as i16)
+}
+
+/// An unowned pixel as the grid holds it. Its version is always 0.
+fn grid_pixel(grid: &canvas_grid::Model, x: i16, y: i16) -> Option<pixel::Model> {
+    if x < 0 || y < 0 || x >= grid.width {
+        return None;
+    }
+    let index = (y as usize) * (grid.width as usize) + (x as usize);
+
+    grid.colors.get(index).map(|&color| pixel::Model {
+        canvas_id: grid.canvas_id,
+        x,
+        y,
+        color: color as i16,
+        owner_id: None,
+        price_lamports: 0,
+        version: 0,
+        updated_at: grid.updated_at,
+    })
+}
+
+/// Writes `colors` into a grid locked by the transaction.
+async fn write_grid_colors<C: ConnectionTrait>(
+    db_connection: &C,
+    grid: &canvas_grid::Model,
+    colors: impl IntoIterator<Item = (i16, i16, i16)>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let mut grid_colors = grid.colors.clone();
+    for (x, y, color) in colors {
+        if grid_pixel(grid, x, y).is_some() {
+            grid_colors[(y as usize) * (grid.width as usize) + (x as usize)] = color as u8;
+        }
+    }
+
+    let mut active: canvas_grid::ActiveModel = grid.clone().into();
+    active.colors = Set(grid_colors);
+    active.updated_at = Set(now);
+    active.update(db_connection).await?;
+
+    Ok(())
 }
@@ -0,0 +1,217 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, QueryTrait, Select,
+    prelude::Expr,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::entities::{PixelHistory, pixel, pixel_history},
+};
+
+/// Who made a pixel change and the transaction that paid for it, if any.
+#[derive(Debug, Clone, Default)]
+pub struct PixelChangeSource {
+    pub actor_id: Option<Uuid>,
+    pub signature: Option<String>,
+}
+
+/// Bounds of a history scan. `after_id` resumes a previous scan.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PixelHistoryRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub after_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct PixelChangeRow {
+    pub id: i64,
+    pub x: i16,
+    pub y: i16,
+    pub new_color: i16,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct PixelColorRow {
+    pub x: i16,
+    pub y: i16,
+    pub old_color: Option<i16>,
+}
+
+pub struct PixelHistoryRepository;
+
+impl PixelHistoryRepository {
+    pub async fn record_change<C: ConnectionTrait>(
+        db_connection: &C,
+        before: Option<&pixel::Model>,
+        after: &pixel::Model,
+        source: &PixelChangeSource,
+    ) -> Result<()> {
+        PixelHistory::insert(Self::entry(before, after, source))
+            .exec(db_connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records several changes from the same source in one statement.
+    pub async fn record_changes<C: ConnectionTrait>(
+        db_connection: &C,
+        changes: &[(Option<&pixel::Model>, &pixel::Model)],
+        source: &PixelChangeSource,
+    ) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        PixelHistory::insert_many(
+            changes
+                .iter()
+                .map(|(before, after)| Self::entry(*before, after, source)),
+        )
+        .exec_without_returning(db_connection)
+        .await?;
+
+        Ok(())
+    }
+
+    fn entry(
+        before: Option<&pixel::Model>,
+        after: &pixel::Model,
+        source: &PixelChangeSource,
+    ) -> pixel_history::ActiveModel {
+        pixel_history::ActiveModel {
+            id: NotSet,
+            canvas_id: Set(after.canvas_id),
+            x: Set(after.x),
+            y: Set(after.y),
+            old_color: Set(before.map(|pixel| pixel.color)),
+            new_color: Set(after.color),
+            actor_id: Set(source.actor_id),
+            price_lamports: Set(after.price_lamports),
+            signature: Set(source.signature.clone()),
+            created_at: Set(after.updated_at),
+        }
+    }
+
+    /// Changes in `range`, oldest first.
+    pub async fn find_changes<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        range: PixelHistoryRange,
+        limit: u64,
+    ) -> Result<Vec<PixelChangeRow>> {
+        let mut query = Self::select_from(canvas_id, range);
+        if let Some(to) = range.to {
+            query = query.filter(pixel_history::Column::CreatedAt.lt(to));
+        }
+
+        let changes = query
+            .select_only()
+            .column(pixel_history::Column::Id)
+            .column(pixel_history::Column::X)
+            .column(pixel_history::Column::Y)
+            .column(pixel_history::Column::NewColor)
+            .column(pixel_history::Column::CreatedAt)
+            .order_by_asc(pixel_history::Column::Id)
+            .limit(limit)
+            .into_model::<PixelChangeRow>()
+            .all(db_connection)
+            .await?;
+
+        Ok(changes)
+    }
+
+    /// Changes between `range.from` and `range.to`, across every page of the scan.
+    pub async fn count_changes<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        range: PixelHistoryRange,
+    ) -> Result<u64> {
+        let range = PixelHistoryRange {
+            after_id: None,
+            ..range
+        };
+        let mut query = Self::select_from(canvas_id, range);
+        if let Some(to) = range.to {
+            query = query.filter(pixel_history::Column::CreatedAt.lt(to));
+        }
+
+        Ok(query.count(db_connection).await?)
+    }
+
+    /// For every pixel changed at or after the start of `range`, the color it had
+    /// before its first change there. Used to rebuild the canvas as of that point.
+    pub async fn find_colors_before<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        range: PixelHistoryRange,
+    ) -> Result<Vec<PixelColorRow>> {
+        // The earliest change of each pixel, without DISTINCT ON, which SQLite lacks
+        let first_changes = Self::select_from(canvas_id, range)
+            .select_only()
+            .column_as(Expr::col(pixel_history::Column::Id).min(), "id")
+            .group_by(pixel_history::Column::X)
+            .group_by(pixel_history::Column::Y)
+            .into_query();
+
+        let colors = PixelHistory::find()
+            .select_only()
+            .column(pixel_history::Column::X)
+            .column(pixel_history::Column::Y)
+            .column(pixel_history::Column::OldColor)
+            .filter(pixel_history::Column::Id.in_subquery(first_changes))
+            .order_by_asc(pixel_history::Column::X)
+            .order_by_asc(pixel_history::Column::Y)
+            .into_model::<PixelColorRow>()
+            .all(db_connection)
+            .await?;
+
+        Ok(colors)
+    }
+
+    /// Deletes up to `limit` of the oldest changes made before `before`. Returns how many
+    /// were deleted.
+    pub async fn delete_changes_before<C: ConnectionTrait>(
+        db_connection: &C,
+        before: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<u64> {
+        let ids: Vec<i64> = PixelHistory::find()
+            .select_only()
+            .column(pixel_history::Column::Id)
+            .filter(pixel_history::Column::CreatedAt.lt(before))
+            .order_by_asc(pixel_history::Column::Id)
+            .limit(limit)
+            .into_tuple()
+            .all(db_connection)
+            .await?;
+
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = PixelHistory::delete_many()
+            .filter(pixel_history::Column::Id.is_in(ids))
+            .exec(db_connection)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    fn select_from(canvas_id: Uuid, range: PixelHistoryRange) -> Select<PixelHistory> {
+        let mut query = PixelHistory::find().filter(pixel_history::Column::CanvasId.eq(canvas_id));
+        if let Some(from) = range.from {
+            query = query.filter(pixel_history::Column::CreatedAt.gte(from));
+        }
+        if let Some(after_id) = range.after_id {
+            query = query.filter(pixel_history::Column::Id.gt(after_id));
+        }
+        query
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, Statement,
+    sea_query::OnConflict,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::{
+        Database,
+        entities::{PixelLock, pixel_lock},
+    },
+};
+
+/// Pixel locks for when Redis, which normally holds them, is unavailable.
+pub struct PixelLockRepository;
+
+impl PixelLockRepository {
+    /// Who holds the pixel's lock, unless it has expired.
+    pub async fn find_holder<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        x: i16,
+        y: i16,
+    ) -> Result<Option<Uuid>> {
+        let lock = PixelLock::find_by_id((canvas_id, x, y))
+            .filter(pixel_lock::Column::ExpiresAt.gt(Utc::now()))
+            .one(db_connection)
+            .await?;
+
+        Ok(lock.map(|lock| lock.user_id))
+    }
+
+    /// Locks the pixel for `user_id` for `ttl`, or extends their lock. `false` when someone
+    /// else holds it. An advisory lock on the pixel, released with the transaction, keeps
+    /// two instances from both taking it.
+    pub async fn try_acquire(
+        db: &Database,
+        canvas_id: Uuid,
+        x: i16,
+        y: i16,
+        user_id: Uuid,
+        ttl: Duration,
+    ) -> Result<bool> {
+        let db_transaction = db.begin_transaction().await?;
+
+        // SQLite already serializes writers, so only Postgres needs the advisory lock
+        if db_transaction.get_database_backend() == DbBackend::Postgres {
+            db_transaction
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+                    [format!("pixel_lock:{canvas_id}:{x}:{y}").into()],
+                ))
+                .await?;
+        }
+
+        if let Some(holder) = Self::find_holder(&db_transaction, canvas_id, x, y).await?
+            && holder != user_id
+        {
+            db_transaction.rollback().await?;
+            return Ok(false);
+        }
+
+        let lock = pixel_lock::ActiveModel {
+            canvas_id: Set(canvas_id),
+            x: Set(x),
+            y: Set(y),
+            user_id: Set(user_id),
+            expires_at: Set(Utc::now() + chrono::Duration::milliseconds(ttl.as_millis() as i64)),
+        };
+
+        PixelLock::insert(lock)
+            .on_conflict(
+                OnConflict::columns([
+                    pixel_lock::Column::CanvasId,
+                    pixel_lock::Column::X,
+                    pixel_lock::Column::Y,
+                ])
+                .update_columns([pixel_lock::Column::UserId, pixel_lock::Column::ExpiresAt])
+                .to_owned(),
+            )
+            .exec_without_returning(&db_transaction)
+            .await?;
+
+        db_transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    pub async fn release<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+        x: i16,
+        y: i16,
+    ) -> Result<()> {
+        PixelLock::delete_by_id((canvas_id, x, y))
+            .exec(db_connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter,
+    QuerySelect, sea_query::OnConflict,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::entities::{PixelWatch, pixel_watch},
+};
+
+pub struct PixelWatchRepository;
+
+impl PixelWatchRepository {
+    pub async fn add_pixel_watch<C: ConnectionTrait>(
+        db_connection: &C,
+        user_id: Uuid,
+        canvas_id: Uuid,
+        x: i16,
+        y: i16,
+    ) -> Result<()> {
+        let watch = pixel_watch::ActiveModel {
+            user_id: Set(user_id),
+            canvas_id: Set(canvas_id),
+            x: Set(x),
+            y: Set(y),
+            created_at: Set(Utc::now()),
+        };
+
+        PixelWatch::insert(watch)
+            .on_conflict(
+                OnConflict::columns([
+                    pixel_watch::Column::UserId,
+                    pixel_watch::Column::CanvasId,
+                    pixel_watch::Column::X,
+                    pixel_watch::Column::Y,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .do_nothing()
+            .exec(db_connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_pixel_watch<C: ConnectionTrait>(
+        db_connection: &C,
+        user_id: Uuid,
+        canvas_id: Uuid,
+        x: i16,
+        y: i16,
+    ) -> Result<()> {
+        PixelWatch::delete_by_id((user_id, canvas_id, x, y))
+            .exec(db_connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn count_watches_by_user<C: ConnectionTrait>(
+        db_connection: &C,
+        user_id: Uuid,
+    ) -> Result<u64> {
+        Ok(PixelWatch::find()
+            .filter(pixel_watch::Column::UserId.eq(user_id))
+            .count(db_connection)
+            .await?)
+    }
+
+    /// Every watch on the canvas, as `(user_id, x, y)`.
+    pub async fn find_canvas_watches<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+    ) -> Result<Vec<(Uuid, i16, i16)>> {
+        Ok(PixelWatch::find()
+            .select_only()
+            .column(pixel_watch::Column::UserId)
+            .column(pixel_watch::Column::X)
+            .column(pixel_watch::Column::Y)
+            .filter(pixel_watch::Column::CanvasId.eq(canvas_id))
+            .into_tuple::<(Uuid, i16, i16)>()
+            .all(db_connection)
+            .await?)
+    }
+}
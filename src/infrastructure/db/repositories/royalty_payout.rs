@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, IntoActiveModel, QueryFilter,
+    QuerySelect,
+    sea_query::{Expr, OnConflict},
+};
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::db::entities::{RoyaltyPayout, royalty_payout},
+};
+
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct CreatorPayoutTotal {
+    pub creator_address: String,
+    pub total_lamports: i64,
+    pub payout_count: i64,
+    pub last_paid_at: DateTime<Utc>,
+}
+
+pub struct RoyaltyPayoutRepository;
+
+impl RoyaltyPayoutRepository {
+    /// Inserts payouts, skipping any already recorded for the same signature and creator.
+    pub async fn record_payouts<C: ConnectionTrait>(
+        db_connection: &C,
+        payouts: Vec<royalty_payout::Model>,
+    ) -> Result<u64> {
+        if payouts.is_empty() {
+            return Ok(0);
+        }
+
+        let inserted =
+            RoyaltyPayout::insert_many(payouts.into_iter().map(IntoActiveModel::into_active_model))
+                .on_conflict(
+                    OnConflict::columns([
+                        royalty_payout::Column::Signature,
+                        royalty_payout::Column::CreatorAddress,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(db_connection)
+                .await?;
+
+        Ok(inserted)
+    }
+
+    pub async fn sum_payouts_by_creator<C: ConnectionTrait>(
+        db_connection: &C,
+        canvas_id: Uuid,
+    ) -> Result<Vec<CreatorPayoutTotal>> {
+        Ok(RoyaltyPayout::find()
+            .select_only()
+            .column(royalty_payout::Column::CreatorAddress)
+            .column_as(
+                Expr::cust("CAST(SUM(amount_lamports) AS BIGINT)"),
+                "total_lamports",
+            )
+            .column_as(royalty_payout::Column::Signature.count(), "payout_count")
+            .column_as(royalty_payout::Column::PaidAt.max(), "last_paid_at")
+            .filter(royalty_payout::Column::CanvasId.eq(canvas_id))
+            .group_by(royalty_payout::Column::CreatorAddress)
+            .into_model::<CreatorPayoutTotal>()
+            .all(db_connection)
+            .await?)
+    }
+}
@@ -0,0 +1,33 @@
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, Select, UpdateMany};
+
+use crate::infrastructure::db::entities::{Canvas, User, canvas, user};
+
+/// Entities deleted by setting `deleted_at`, so the deletion can be undone. Repository
+/// queries leave deleted rows out, except those named `..._including_deleted`.
+pub trait SoftDelete: EntityTrait {
+    const DELETED_AT: Self::Column;
+}
+
+impl SoftDelete for Canvas {
+    const DELETED_AT: canvas::Column = canvas::Column::DeletedAt;
+}
+
+impl SoftDelete for User {
+    const DELETED_AT: user::Column = user::Column::DeletedAt;
+}
+
+pub trait ExcludeDeleted {
+    fn exclude_deleted(self) -> Self;
+}
+
+impl<E: SoftDelete> ExcludeDeleted for Select<E> {
+    fn exclude_deleted(self) -> Self {
+        self.filter(E::DELETED_AT.is_null())
+    }
+}
+
+impl<E: SoftDelete> ExcludeDeleted for UpdateMany<E> {
+    fn exclude_deleted(self) -> Self {
+        self.filter(E::DELETED_AT.is_null())
+    }
+}
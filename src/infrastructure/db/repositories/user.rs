@@ -1,13 +1,15 @@
 use crate::{
-    error::Result,
+    error::{AppError, Result},
     infrastructure::db::{
         Database,
         entities::{User, user},
+        repositories::soft_delete::ExcludeDeleted,
     },
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sea_orm::{
     ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
+    sea_query::Expr,
 };
 use uuid::Uuid;
 
@@ -17,6 +19,18 @@ impl UserRepository {
     pub async fn find_user_by_id<C: ConnectionTrait>(
         db_connection: &C,
         id: Uuid,
+    ) -> Result<Option<user::Model>> {
+        Ok(User::find_by_id(id)
+            .exclude_deleted()
+            .one(db_connection)
+            .await?)
+    }
+
+    /// Also finds soft-deleted users, whose wallets still matter on-chain and to admin
+    /// tooling.
+    pub async fn find_user_by_id_including_deleted<C: ConnectionTrait>(
+        db_connection: &C,
+        id: Uuid,
     ) -> Result<Option<user::Model>> {
         Ok(User::find_by_id(id).one(db_connection).await?)
     }
@@ -27,13 +41,37 @@ impl UserRepository {
     ) -> Result<Option<user::Model>> {
         Ok(User::find()
             .filter(user::Column::WalletAddress.eq(wallet))
+            .exclude_deleted()
             .one(db_connection)
             .await?)
     }
 
+    /// Includes soft-deleted users, who are still paid what their wallets are owed.
+    pub async fn find_users_by_wallets_including_deleted<C: ConnectionTrait>(
+        db_connection: &C,
+        wallets: &[String],
+    ) -> Result<Vec<user::Model>> {
+        Ok(User::find()
+            .filter(user::Column::WalletAddress.is_in(wallets.iter().cloned()))
+            .all(db_connection)
+            .await?)
+    }
+
     pub async fn find_users_by_ids<C: ConnectionTrait>(
         db_connection: &C,
         ids: &[Uuid],
+    ) -> Result<Vec<user::Model>> {
+        Ok(User::find()
+            .filter(user::Column::Id.is_in(ids.iter().cloned()))
+            .exclude_deleted()
+            .all(db_connection)
+            .await?)
+    }
+
+    /// Also finds soft-deleted users, whose wallets still matter on-chain.
+    pub async fn find_users_by_ids_including_deleted<C: ConnectionTrait>(
+        db_connection: &C,
+        ids: &[Uuid],
     ) -> Result<Vec<user::Model>> {
         Ok(User::find()
             .filter(user::Column::Id.is_in(ids.iter().cloned()))
@@ -76,6 +114,8 @@ impl UserRepository {
             wallet_address: Set(wallet.to_string()),
             username: Set(username),
             created_at: Set(now),
+            role: Set(user::UserRole::User),
+            deleted_at: Set(None),
         };
 
         let created_user = user.insert(&db_transaction).await?;
@@ -84,4 +124,38 @@ impl UserRepository {
 
         Ok(created_user)
     }
+
+    /// Soft-deletes the user. Their wallet and username stay taken, so `restore_user` can
+    /// bring them back.
+    pub async fn delete_user<C: ConnectionTrait>(db_connection: &C, id: Uuid) -> Result<()> {
+        let deleted = User::update_many()
+            .col_expr(user::Column::DeletedAt, Expr::value(Utc::now()))
+            .filter(user::Column::Id.eq(id))
+            .exclude_deleted()
+            .exec(db_connection)
+            .await?;
+
+        if deleted.rows_affected == 0 {
+            return Err(AppError::UserNotFound);
+        }
+        Ok(())
+    }
+
+    /// Undoes `delete_user`.
+    pub async fn restore_user<C: ConnectionTrait>(db_connection: &C, id: Uuid) -> Result<()> {
+        let restored = User::update_many()
+            .col_expr(
+                user::Column::DeletedAt,
+                Expr::value(Option::<DateTime<Utc>>::None),
+            )
+            .filter(user::Column::Id.eq(id))
+            .filter(user::Column::DeletedAt.is_not_null())
+            .exec(db_connection)
+            .await?;
+
+        if restored.rows_affected == 0 {
+            return Err(AppError::UserNotFound);
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,113 @@
+use std::{future::Future, time::Duration};
+
+use async_trait::async_trait;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, ExecResult, QueryResult, RuntimeErr,
+    Statement, sqlx,
+};
+
+/// Pool handle that runs a statement once more if it lost its connection, so a Postgres
+/// failover costs a short wait rather than failed requests. Only statements that are safe to
+/// run twice (reads, or writes that never reached the server) are retried.
+/// Transactions are not covered, since a statement cannot be replayed on its own inside one.
+#[derive(Debug, Clone)]
+pub struct ResilientConnection {
+    inner: DatabaseConnection,
+    retry_delay: Duration,
+}
+
+impl ResilientConnection {
+    pub fn new(inner: DatabaseConnection, retry_delay: Duration) -> Self {
+        Self { inner, retry_delay }
+    }
+
+    /// The pool itself, for what needs a plain connection, such as migrations.
+    pub fn inner(&self) -> &DatabaseConnection {
+        &self.inner
+    }
+
+    async fn retry_once<T, F, Fut>(&self, read_only: bool, run: F) -> Result<T, DbErr>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, DbErr>>,
+    {
+        match run().await {
+            Err(error) if should_retry(&error, read_only) => {
+                tracing::warn!("Retrying database statement after a lost connection: {error}");
+                tokio::time::sleep(self.retry_delay).await;
+                run().await
+            }
+            result => result,
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectionTrait for ResilientConnection {
+    fn get_database_backend(&self) -> DbBackend {
+        self.inner.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        let read_only = is_read(&stmt.sql);
+        self.retry_once(read_only, || self.inner.execute(stmt.clone()))
+            .await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        self.retry_once(is_read(sql), || self.inner.execute_unprepared(sql))
+            .await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        let read_only = is_read(&stmt.sql);
+        self.retry_once(read_only, || self.inner.query_one(stmt.clone()))
+            .await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        let read_only = is_read(&stmt.sql);
+        self.retry_once(read_only, || self.inner.query_all(stmt.clone()))
+            .await
+    }
+
+    fn support_returning(&self) -> bool {
+        self.inner.support_returning()
+    }
+}
+
+fn is_read(sql: &str) -> bool {
+    sql.trim_start()
+        .get(..6)
+        .is_some_and(|keyword| keyword.eq_ignore_ascii_case("SELECT"))
+}
+
+fn should_retry(error: &DbErr, read_only: bool) -> bool {
+    match error {
+        // Nothing was sent, so running it again cannot apply it twice
+        DbErr::ConnectionAcquire(_) => true,
+        DbErr::Conn(error) | DbErr::Exec(error) | DbErr::Query(error) => {
+            read_only && is_connection_lost(error)
+        }
+        _ => false,
+    }
+}
+
+/// Whether the error means the connection went away rather than that the statement failed.
+fn is_connection_lost(error: &RuntimeErr) -> bool {
+    let RuntimeErr::SqlxError(error) = error else {
+        return false;
+    };
+
+    match error {
+        sqlx::Error::Io(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => true,
+        // Connection exceptions, and the server shutting down or still starting up
+        sqlx::Error::Database(error) => error.code().is_some_and(|code| {
+            code.starts_with("08") || matches!(code.as_ref(), "57P01" | "57P02" | "57P03")
+        }),
+        _ => false,
+    }
+}
@@ -1,4 +1,5 @@
 pub mod api;
+pub mod cli;
 pub mod config;
 pub mod error;
 pub mod infrastructure;
@@ -21,7 +22,10 @@ use crate::{
     config::Config,
     infrastructure::{cache::Cache, db::Database},
     middleware::rate_limit::RateLimiter,
-    services::{auth::JwtService, solana::SolanaClient},
+    services::{
+        auth::JwtService, market::MarketDataSource, nft::storage::MetadataStorage,
+        price::PriceSource, solana::SolanaService, webhooks::WebhookDispatcher,
+    },
 };
 
 #[derive(Clone)]
@@ -38,7 +42,11 @@ pub struct AppState {
     pub db: Arc<Database>,
     pub cache: Arc<Cache>,
     pub jwt_service: Arc<JwtService>,
-    pub solana_client: Arc<SolanaClient>,
+    pub solana_client: Arc<dyn SolanaService>,
+    pub metadata_storage: Option<Arc<dyn MetadataStorage>>,
+    pub market_data: Option<Arc<dyn MarketDataSource>>,
+    pub price_source: Option<Arc<dyn PriceSource>>,
+    pub webhooks: Arc<WebhookDispatcher>,
     pub ws_rooms: Arc<ws::RoomManager>,
     pub rate_limiters: Arc<RateLimiters>,
 }
@@ -1,19 +1,32 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
+use clap::Parser;
 use pixel_archives::{
     AppState, RateLimiters, build_router,
+    cli::{self, Cli, Command},
     config::Config,
-    error::Result,
+    error::{AppError, Result},
     infrastructure::{cache::Cache, db::Database},
     middleware::rate_limit::create_limiter,
-    services::{auth::JwtService, solana::SolanaClient},
+    services::{
+        auth::JwtService,
+        canvas::{spawn_history_pruner, spawn_leaderboard_refresher, warm_canvas_caches},
+        market,
+        nft::{spawn_mint_watcher, spawn_reconciler, spawn_royalty_indexer, storage},
+        outbox::spawn_outbox_dispatcher,
+        price,
+        solana::{self, spawn_blockhash_refresher, spawn_event_indexer, spawn_event_stream},
+        webhooks::WebhookDispatcher,
+    },
     utils::server::{init_tracing, shutdown_signal},
-    ws::RoomManager,
+    ws::{RoomManager, spawn_room_sweeper},
 };
 use tokio::net::TcpListener;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
     init_tracing()?;
 
     let config = Config::from_env()?;
@@ -23,19 +36,78 @@ async fn main() -> Result<()> {
     let db = Database::init_db(&config.database).await?;
     tracing::info!("Database initialized");
 
-    db.run_migrations().await?;
-    tracing::info!("Migrations completed");
+    if let Some(Command::Migrate(command)) = cli.command {
+        return cli::run_migrate_command(&db, command).await;
+    }
+
+    if cli.migrate_only || config.database.auto_migrate {
+        db.run_migrations(None).await?;
+        tracing::info!("Migrations completed");
+    } else {
+        let pending = db.pending_migrations().await?;
+        if pending > 0 {
+            return Err(AppError::InternalServerError(format!(
+                "{pending} migrations are pending, apply them with `migrate up`"
+            )));
+        }
+    }
+
+    if cli.migrate_only {
+        return Ok(());
+    }
 
     let cache = Cache::init(&config).await?;
     tracing::info!("Cache initialized");
 
+    cache.listen_for_invalidations();
+    tracing::info!("Cache invalidation listener started");
+
+    db.observe_changes(cache.change_observer());
+
     let jwt_service = JwtService::new(&config.jwt);
     tracing::info!("JWT service initialized");
 
-    let solana_client = SolanaClient::initialize(&config.solana);
-    tracing::info!("Solana client initialized");
-
-    let ws_rooms = RoomManager::initialize(config.canvas.max_collaborators);
+    let solana_client = solana::from_config(&config.solana);
+    solana_client.verify_cluster(config.solana.cluster).await?;
+    tracing::info!(
+        "Solana client ({}) initialized on {}",
+        solana_client.name(),
+        config.solana.cluster.as_str()
+    );
+
+    let metadata_storage = storage::from_config(&config.storage)?;
+    match &metadata_storage {
+        Some(storage) => tracing::info!("NFT asset storage initialized: {}", storage.name()),
+        None => tracing::warn!("NFT asset storage disabled, assets will be served by this server"),
+    }
+
+    let market_data = market::from_config(&config.market)?;
+    match &market_data {
+        Some(source) => tracing::info!("Marketplace data source initialized: {}", source.name()),
+        None => tracing::warn!("Marketplace data disabled"),
+    }
+
+    let price_source = price::from_config(&config.price)?;
+    match &price_source {
+        Some(source) => tracing::info!("Price oracle initialized: {}", source.name()),
+        None => tracing::warn!("Price oracle disabled, USD amounts will be omitted"),
+    }
+
+    let webhooks = WebhookDispatcher::new(&config.webhooks);
+    if webhooks.is_enabled() {
+        tracing::info!(
+            "Webhook delivery enabled for {} endpoints",
+            config.webhooks.urls.len()
+        );
+    }
+
+    let ws_rooms = RoomManager::initialize(
+        config.canvas.max_collaborators,
+        config.canvas.max_spectators,
+        cache.redis.clone(),
+        config.server.ws_replay_buffer,
+        Duration::from_secs(config.server.ws_replay_ttl_secs),
+    );
     tracing::info!("WebSocket rooms initialized");
 
     let rate_limit_redis_cache = Arc::new(cache.redis.clone());
@@ -68,11 +140,58 @@ async fn main() -> Result<()> {
         db: Arc::new(db),
         cache: Arc::new(cache),
         jwt_service: Arc::new(jwt_service),
-        solana_client: Arc::new(solana_client),
+        solana_client,
+        metadata_storage,
+        market_data,
+        price_source,
+        webhooks: Arc::new(webhooks),
         ws_rooms: Arc::new(ws_rooms),
         rate_limiters: Arc::new(rate_limiters),
     };
 
+    state.ws_rooms.listen_for_fanout();
+    tracing::info!("WebSocket room fan-out listener started");
+
+    spawn_blockhash_refresher(state.clone());
+    tracing::info!("Blockhash refresher started");
+
+    spawn_mint_watcher(state.clone());
+    tracing::info!("Mint watcher started");
+
+    spawn_royalty_indexer(state.clone());
+    tracing::info!("Royalty indexer started");
+
+    spawn_event_indexer(state.clone());
+    tracing::info!("On-chain event indexer started");
+
+    if config.solana.event_stream {
+        spawn_event_stream(state.clone());
+        tracing::info!("On-chain event stream started");
+    }
+
+    spawn_reconciler(state.clone());
+    tracing::info!("State reconciler started");
+
+    spawn_room_sweeper(state.clone());
+    tracing::info!("WebSocket room sweeper started");
+
+    if config.canvas.history_retention_days > 0 {
+        spawn_history_pruner(state.clone());
+        tracing::info!("Pixel history pruner started");
+    }
+
+    spawn_leaderboard_refresher(state.clone());
+    tracing::info!("Leaderboard refresher started");
+
+    spawn_outbox_dispatcher(state.clone());
+    tracing::info!("Outbox dispatcher started");
+
+    // Before listening, so the first requests after a deploy find warm caches
+    match warm_canvas_caches(&state).await {
+        Ok(warmed) => tracing::info!("Warmed caches for {warmed} active canvases"),
+        Err(e) => tracing::warn!("Failed to warm caches: {e}"),
+    }
+
     let app = build_router(state);
 
     let server_addr = format!("{}:{}", config.server.host, config.server.port);
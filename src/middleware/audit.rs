@@ -0,0 +1,136 @@
+use std::time::Instant;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::AppError,
+    infrastructure::db::repositories::{AuditLogRepository, audit_log::AuditEntry},
+    services::auth::TokenType,
+};
+
+/// Params injected from cookies, which must never reach the log.
+const REDACTED_PARAMS: [&str; 2] = ["access_token", "refresh_token"];
+
+/// Whether calls to the method change state and so belong in the audit log.
+pub fn is_audited(method: &str) -> bool {
+    matches!(
+        method,
+        "auth.register"
+            | "auth.login"
+            | "auth.logout"
+            | "auth.refresh"
+            | "canvas.create"
+            | "canvas.join"
+            | "canvas.publish"
+            | "canvas.confirmPublish"
+            | "canvas.cancelPublish"
+            | "canvas.delete"
+            | "canvas.setBidRules"
+            | "canvas.setCooldownExemption"
+            | "canvas.setPixelQuota"
+            | "canvas.mute"
+            | "pixel.place"
+            | "pixel.confirm"
+            | "pixel.cancel"
+            | "pixel.paint"
+            | "pixel.paintBatch"
+            | "pixel.watch"
+            | "pixel.unwatch"
+            | "nft.announceMint"
+            | "nft.cancelMintCountdown"
+            | "nft.prepareMetadata"
+            | "nft.generateTimelapse"
+            | "nft.mint"
+            | "nft.confirmMint"
+            | "nft.cancelMint"
+            | "nft.mintEdition"
+            | "nft.confirmEdition"
+            | "nft.burn"
+            | "nft.confirmBurn"
+            | "nft.verifyCollection"
+            | "nft.updateMetadata"
+            | "nft.confirmMetadataUpdate"
+            | "solana.claimEscrow"
+            | "solana.confirmClaim"
+            | "admin.broadcast"
+    )
+}
+
+/// Tracks one audited call from dispatch to result.
+pub struct AuditRecorder {
+    method: String,
+    actor_id: Option<Uuid>,
+    params_hash: String,
+    started: Instant,
+}
+
+impl AuditRecorder {
+    pub fn start(state: &AppState, method: &str, params: &Value) -> Self {
+        let actor_id = params
+            .get("access_token")
+            .and_then(Value::as_str)
+            .and_then(|token| {
+                state
+                    .jwt_service
+                    .validate_token(token, TokenType::Access)
+                    .ok()
+            })
+            .map(|claims| claims.sub);
+
+        Self {
+            method: method.to_string(),
+            actor_id,
+            params_hash: hash_params(params),
+            started: Instant::now(),
+        }
+    }
+
+    /// Writes the entry in the background, so a slow insert never holds up the response.
+    pub fn finish(self, state: &AppState, result: &Result<Value, AppError>) {
+        let latency_ms = i32::try_from(self.started.elapsed().as_millis()).unwrap_or(i32::MAX);
+        let result_code = match result {
+            Ok(_) => 0,
+            Err(error) => error.code(),
+        };
+        // A login or registration only learns who the caller is from its own result
+        let actor_id = self.actor_id.or_else(|| {
+            result
+                .as_ref()
+                .ok()
+                .and_then(|value| value.pointer("/user/id"))
+                .and_then(Value::as_str)
+                .and_then(|id| id.parse().ok())
+        });
+
+        let entry = AuditEntry {
+            method: self.method,
+            actor_id,
+            params_hash: self.params_hash,
+            result_code,
+            latency_ms,
+        };
+        let db = state.db.clone();
+        tokio::spawn(async move {
+            let method = entry.method.clone();
+            if let Err(e) = AuditLogRepository::record(db.get_connection(), entry).await {
+                tracing::warn!("Failed to record audit entry for {method}: {e}");
+            }
+        });
+    }
+}
+
+/// Hex SHA-256 of the params without their tokens. Object keys serialize sorted, so equal
+/// params always hash the same.
+fn hash_params(params: &Value) -> String {
+    let mut params = params.clone();
+    if let Value::Object(map) = &mut params {
+        for key in REDACTED_PARAMS {
+            map.remove(key);
+        }
+    }
+    let bytes = serde_json::to_vec(&params).unwrap_or_default();
+    format!("{:x}", Sha256::digest(bytes))
+}
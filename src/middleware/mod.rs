@@ -1,2 +1,3 @@
+pub mod audit;
 pub mod logging;
 pub mod rate_limit;
@@ -2,9 +2,11 @@ use serde::{Deserialize, Serialize};
 
 pub mod cookie;
 pub mod jwt;
+pub mod roles;
 pub mod signature;
 
 pub use jwt::{JwtClaims, JwtService};
+pub use roles::{is_admin, load_user_role};
 pub use signature::{check_and_consume_nonce, parse_auth_message, verify_signature};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::{
+    error::Result,
+    infrastructure::{
+        cache::{keys::CacheKey, redis::RedisCache},
+        db::{Database, entities::user::UserRole, repositories::UserRepository},
+    },
+};
+
+/// Role changes made in the database reach placements once the cached role lapses.
+const ROLE_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Returns whether the user holds the admin role. Unknown users are not admins.
+pub async fn is_admin(db: &Database, user_id: Uuid) -> Result<bool> {
+    let user = UserRepository::find_user_by_id(db.get_connection(), user_id).await?;
+    Ok(user.is_some_and(|user| user.role == UserRole::Admin))
+}
+
+/// Loads the user's role from the database and caches it under `CacheKey::user_role`.
+/// Unknown users get the default role.
+pub async fn load_user_role(db: &Database, redis: &RedisCache, user_id: Uuid) -> Result<UserRole> {
+    let role = UserRepository::find_user_by_id(db.get_connection(), user_id)
+        .await?
+        .map(|user| user.role)
+        .unwrap_or_default();
+
+    if let Err(e) = redis
+        .set(&CacheKey::user_role(&user_id), &role, ROLE_CACHE_TTL)
+        .await
+    {
+        tracing::warn!(user_id = %user_id, "Failed to cache user role: {e}");
+    }
+    Ok(role)
+}
@@ -5,7 +5,7 @@ use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
 use crate::{
     error::{AppError, Result},
-    infrastructure::cache::Cache,
+    infrastructure::cache::{Cache, keys::CacheKey},
     services::MESSAGE_VALIDITY_SECS,
 };
 
@@ -77,7 +77,7 @@ pub fn parse_auth_message(message: &str) -> Result<AuthMessage> {
 }
 
 pub async fn check_and_consume_nonce(cache: &Cache, wallet: &str, nonce: &str) -> Result<()> {
-    let nonce_key = format!("auth:nonce:{}:{}", wallet, nonce);
+    let nonce_key = CacheKey::auth_nonce(wallet, nonce);
 
     let is_new = cache
         .redis
@@ -0,0 +1,15 @@
+/// Inverse of `pack_pixels_to_colors`: one 6-bit color index per pixel, row-major.
+pub fn unpack_colors(pixel_colors: &[u8]) -> Vec<u8> {
+    let mut colors = Vec::with_capacity(pixel_colors.len() / 3 * 4);
+
+    // Unpack: c0 = b0[7:2], c1 = b0[1:0]b1[7:4], c2 = b1[3:0]b2[7:6], c3 = b2[5:0]
+    for group in pixel_colors.chunks_exact(3) {
+        let (b0, b1, b2) = (group[0], group[1], group[2]);
+        colors.push((b0 >> 2) & 0x3F);
+        colors.push(((b0 & 0x03) << 4) | ((b1 >> 4) & 0x0F));
+        colors.push(((b1 & 0x0F) << 2) | ((b2 >> 6) & 0x03));
+        colors.push(b2 & 0x3F);
+    }
+
+    colors
+}
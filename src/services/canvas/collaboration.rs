@@ -1,21 +1,44 @@
 use base64::Engine;
-use std::time::Duration;
+use chrono::Utc;
+use futures::StreamExt;
+use std::{sync::LazyLock, time::Duration};
 use uuid::Uuid;
 
 use crate::{
     AppState,
     error::{AppError, Result},
     infrastructure::{
-        cache::keys::CacheKey,
-        db::repositories::{CanvasRepository, PixelRepository},
+        cache::{
+            breaker::or_degraded,
+            keys::CacheKey,
+            stampede::{SingleFlight, jittered},
+        },
+        db::{
+            entities::canvas,
+            repositories::{CanvasRepository, PixelRepository},
+        },
     },
-    services::canvas::types::{
-        CachedPixelData, CanvasInfo, CanvasWithPixels, JoinCanvasResult, OwnedCanvasPixelInfo,
-        UserCanvases,
+    services::canvas::{
+        palette::{format_hex_color, resolve_palette},
+        presence::present_users,
+        types::{
+            CachedPixelData, CanvasInfo, CanvasSnapshot, CanvasWithPixels, JoinCanvasResult,
+            OwnedCanvasPixelInfo, UserCanvases,
+        },
     },
     ws::types::RoomCanvasUpdate,
 };
 
+/// How long a refresh of stale canvas pixels may take before another reader can start one.
+const PIXEL_REFRESH_LOCK_TTL: Duration = Duration::from_secs(10);
+
+/// Pixel loads from the database in progress, shared by concurrent readers of a canvas.
+static PIXEL_LOADS: LazyLock<SingleFlight<Uuid, CachedPixelData>> =
+    LazyLock::new(SingleFlight::default);
+
+/// Canvases loaded at once while warming the caches.
+const WARM_CONCURRENCY: usize = 8;
+
 pub async fn join_canvas(
     state: &AppState,
     user_id: Uuid,
@@ -59,98 +82,234 @@ pub async fn get_canvas(
     if !CanvasRepository::is_canvas_collaborator(db_connection, canvas_id, user_id).await? {
         return Err(AppError::NotCanvasCollaborator);
     }
-    let canvas = if let Some(cached) = state.cache.local.get_canvas(&canvas_id).await {
-        (*cached).clone()
-    } else {
-        let canvas = CanvasRepository::find_canvas_by_id(db_connection, canvas_id)
-            .await?
-            .ok_or(AppError::CanvasNotFound)?;
 
-        state.cache.local.set_canvas(canvas.clone()).await;
-        canvas
-    };
-
-    // for redis (l2) cache
-    let cache_key = CacheKey::canvas_pixels(&canvas_id);
-
-    let pixel_data =
-        if let Ok(Some(cached)) = state.cache.redis.get::<CachedPixelData>(&cache_key).await {
-            cached.clone()
-        } else {
-            let pixels = PixelRepository::find_pixels_by_canvas(db_connection, canvas_id).await?;
-
-            let width = state.config.canvas.width as usize;
-            let height = state.config.canvas.height as usize;
-
-            let mut color_bytes = vec![0u8; width * height];
-            let mut owned_pixels = Vec::new();
-
-            for pixel in pixels {
-                let index = (pixel.y as usize) * (width) + (pixel.x as usize);
-                if index < color_bytes.len() {
-                    color_bytes[index] = pixel.color as u8;
-                }
-
-                if let Some(owner_id) = pixel.owner_id {
-                    owned_pixels.push(OwnedCanvasPixelInfo {
-                        x: pixel.x,
-                        y: pixel.y,
-                        owner_id: owner_id.to_string(),
-                        price_lamports: pixel.price_lamports,
-                    });
-                }
-            }
+    load_canvas_with_pixels(state, canvas_id).await
+}
 
-            let pixel_colors = base64::engine::general_purpose::STANDARD.encode(&color_bytes);
+/// Current canvas state for a room joining its WebSocket. `seq` is read before anything else,
+/// so updates numbered after it are not yet reflected, or are harmless to apply again.
+pub async fn get_canvas_snapshot(state: &AppState, canvas_id: Uuid) -> Result<CanvasSnapshot> {
+    let seq = state.ws_rooms.latest_seq(&canvas_id).await?;
+    let canvas = load_canvas_with_pixels(state, canvas_id).await?;
+    let presence = present_users(state, canvas_id).await?;
 
-            let cached_pixels = CachedPixelData {
-                pixel_colors,
-                owned_pixels,
-            };
+    Ok(CanvasSnapshot {
+        seq,
+        canvas,
+        presence,
+    })
+}
+
+/// The canvas from the local cache, or the database when it is not cached there.
+pub async fn cached_canvas(state: &AppState, canvas_id: Uuid) -> Result<canvas::Model> {
+    let canvas = state
+        .cache
+        .local
+        .get_or_load_canvas(canvas_id, || async {
+            CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+                .await?
+                .ok_or(AppError::CanvasNotFound)
+        })
+        .await?;
+    Ok((*canvas).clone())
+}
 
-            state
-                .cache
-                .redis
-                .set(
-                    &cache_key,
-                    &cached_pixels,
-                    Duration::from_secs(state.config.cache.redis_cache_mid_ttl),
-                )
-                .await?;
+/// The canvas with its pixels, from the caches where possible.
+async fn load_canvas_with_pixels(state: &AppState, canvas_id: Uuid) -> Result<CanvasWithPixels> {
+    let canvas = cached_canvas(state, canvas_id).await?;
 
-            cached_pixels
-        };
+    let pixel_data = cached_pixel_data(state, canvas_id).await?;
 
     Ok(CanvasWithPixels {
-        canvas: CanvasInfo {
-            id: canvas.id,
-            name: canvas.name,
-            invite_code: canvas.invite_code,
-            state: canvas.state,
-            owner_id: canvas.owner_id,
-            canvas_pda: canvas.canvas_pda,
-            mint_address: canvas.mint_address,
-            total_escrowed: canvas.total_escrowed,
-        },
-        pixel_colors: pixel_data.pixel_colors,
+        palette: resolve_palette(&state.config.canvas, canvas.palette.as_ref())
+            .into_iter()
+            .map(format_hex_color)
+            .collect(),
+        canvas: CanvasInfo::from(canvas),
+        pixel_colors: base64::engine::general_purpose::STANDARD.encode(&pixel_data.colors),
         owned_pixels: pixel_data.owned_pixels,
     })
 }
 
-pub async fn list_canvases_by_user(state: &AppState, user_id: Uuid) -> Result<UserCanvases> {
+/// Canvas pixels from Redis (l2) where possible. A stale entry is still served while one
+/// reader refreshes it in the background, so expiry does not send every reader to the database.
+async fn cached_pixel_data(state: &AppState, canvas_id: Uuid) -> Result<CachedPixelData> {
+    if let Ok(Some(cached)) = state
+        .cache
+        .redis
+        .get_canvas_pixels::<OwnedCanvasPixelInfo>(&canvas_id)
+        .await
+    {
+        if cached.stale {
+            refresh_pixel_data(state, canvas_id).await;
+        }
+        return Ok(CachedPixelData {
+            colors: cached.colors,
+            owned_pixels: cached.owned,
+        });
+    }
+
+    PIXEL_LOADS
+        .run(canvas_id, || async {
+            let pixel_data = load_pixel_data(state, canvas_id).await?;
+            // Served straight from the database while Redis is unavailable
+            or_degraded(store_pixel_data(state, canvas_id, &pixel_data).await, ())?;
+            Ok(pixel_data)
+        })
+        .await
+}
+
+/// Reloads stale canvas pixels in the background, unless another reader already is.
+async fn refresh_pixel_data(state: &AppState, canvas_id: Uuid) {
+    let lock_key = CacheKey::canvas_pixels_refresh(&canvas_id);
+    if !matches!(
+        state
+            .cache
+            .redis
+            .setnx(&lock_key, PIXEL_REFRESH_LOCK_TTL)
+            .await,
+        Ok(true)
+    ) {
+        return;
+    }
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        let refreshed = match load_pixel_data(&state, canvas_id).await {
+            Ok(pixel_data) => store_pixel_data(&state, canvas_id, &pixel_data).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = refreshed {
+            tracing::warn!(%canvas_id, "Failed to refresh cached pixels: {e}");
+        }
+
+        let _ = state.cache.redis.delete(&lock_key).await;
+    });
+}
+
+async fn load_pixel_data(state: &AppState, canvas_id: Uuid) -> Result<CachedPixelData> {
+    let db_connection = state.db.get_connection();
+    let (colors, owned) = tokio::try_join!(
+        PixelRepository::find_canvas_colors(
+            db_connection,
+            canvas_id,
+            state.config.canvas.width,
+            state.config.canvas.height,
+        ),
+        PixelRepository::find_owned_pixels_by_canvas(db_connection, canvas_id),
+    )?;
+
+    let owned_pixels = owned
+        .into_iter()
+        .filter_map(|pixel| {
+            Some(OwnedCanvasPixelInfo {
+                x: pixel.x,
+                y: pixel.y,
+                owner_id: pixel.owner_id?.to_string(),
+                price_lamports: pixel.price_lamports,
+            })
+        })
+        .collect();
+
+    Ok(CachedPixelData {
+        colors,
+        owned_pixels,
+    })
+}
+
+async fn store_pixel_data(
+    state: &AppState,
+    canvas_id: Uuid,
+    pixel_data: &CachedPixelData,
+) -> Result<()> {
+    let owned: Vec<_> = pixel_data
+        .owned_pixels
+        .iter()
+        .map(|pixel| (pixel.x, pixel.y, pixel))
+        .collect();
+
+    state
+        .cache
+        .redis
+        .set_canvas_pixels(
+            &canvas_id,
+            &pixel_data.colors,
+            &owned,
+            jittered(state.config.cache.pixels_soft_ttl),
+            jittered(Duration::from_secs(state.config.cache.redis_cache_mid_ttl)),
+        )
+        .await
+}
+
+/// Loads the most recently active canvases and their pixels into the caches, so the first
+/// readers after a restart do not all go to the database. Returns how many were warmed.
+pub async fn warm_canvas_caches(state: &AppState) -> Result<usize> {
+    let cache_config = &state.config.cache;
+    if cache_config.warm_canvas_limit == 0 {
+        return Ok(0);
+    }
+
+    let db_connection = state.db.get_connection();
+    let since = Utc::now() - chrono::Duration::hours(cache_config.warm_activity_hours as i64);
+    let canvas_ids = PixelRepository::find_recently_active_canvases(
+        db_connection,
+        since,
+        cache_config.warm_canvas_limit,
+    )
+    .await?;
+    let canvases = CanvasRepository::find_canvases_by_ids(db_connection, &canvas_ids).await?;
+
+    let warmed = futures::stream::iter(canvases)
+        .map(|canvas| async move {
+            let canvas_id = canvas.id;
+            state.cache.local.set_canvas(canvas).await;
+
+            let pixel_data = load_pixel_data(state, canvas_id).await?;
+            store_pixel_data(state, canvas_id, &pixel_data).await?;
+            Ok::<_, AppError>(())
+        })
+        .buffer_unordered(WARM_CONCURRENCY)
+        .filter(|warmed| {
+            if let Err(e) = warmed {
+                tracing::warn!("Failed to warm a canvas cache: {e}");
+            }
+            std::future::ready(warmed.is_ok())
+        })
+        .count()
+        .await;
+
+    Ok(warmed)
+}
+
+/// Pages through owned and collaborating canvases independently, each with its own cursor.
+pub async fn list_canvases_by_user(
+    state: &AppState,
+    user_id: Uuid,
+    owned_cursor: Option<&str>,
+    collaborating_cursor: Option<&str>,
+    limit: u64,
+) -> Result<UserCanvases> {
     let db_connection = state.db.get_connection();
 
     let (owned, collaborating) = tokio::join!(
-        CanvasRepository::list_canvases_by_owner(db_connection, user_id),
-        CanvasRepository::list_canvases_by_collaborator(db_connection, user_id)
+        CanvasRepository::list_canvases_by_owner(db_connection, user_id, owned_cursor, limit),
+        CanvasRepository::list_canvases_by_collaborator(
+            db_connection,
+            user_id,
+            collaborating_cursor,
+            limit
+        )
     );
+    let (owned, collaborating) = (owned?, collaborating?);
 
     Ok(UserCanvases {
-        owned: owned?.into_iter().map(CanvasInfo::from).collect(),
-        collaborating: collaborating?
+        owned: owned.items.into_iter().map(CanvasInfo::from).collect(),
+        next_owned_cursor: owned.next_cursor,
+        collaborating: collaborating
+            .items
             .into_iter()
-            .filter(|canvas| canvas.owner_id != user_id)
             .map(CanvasInfo::from)
             .collect(),
+        next_collaborating_cursor: collaborating.next_cursor,
     })
 }
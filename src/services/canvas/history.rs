@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+
+use crate::{
+    AppState, error::Result, infrastructure::db::repositories::PixelHistoryRepository,
+    utils::scheduler::spawn_interval_task,
+};
+
+/// History rows deleted per statement, so pruning a large backlog does not hold long locks.
+const PRUNE_BATCH: u64 = 10_000;
+
+/// Periodically deletes pixel history older than the retention period. Timelapses of
+/// canvases painted before then start from the oldest change kept.
+pub fn spawn_history_pruner(state: AppState) -> JoinHandle<()> {
+    let period = Duration::from_secs(state.config.canvas.history_prune_interval_secs);
+
+    spawn_interval_task("history_prune", period, move || {
+        let state = state.clone();
+        async move { prune_pixel_history(&state).await.map(|_| ()) }
+    })
+}
+
+/// Deletes pixel history past the retention period. Returns how many changes were deleted.
+pub async fn prune_pixel_history(state: &AppState) -> Result<u64> {
+    let retention_days = state.config.canvas.history_retention_days;
+    if retention_days == 0 {
+        return Ok(0);
+    }
+
+    let before = Utc::now() - chrono::Duration::days(retention_days as i64);
+    let mut pruned = 0;
+    loop {
+        let deleted = PixelHistoryRepository::delete_changes_before(
+            state.db.get_connection(),
+            before,
+            PRUNE_BATCH,
+        )
+        .await?;
+        pruned += deleted;
+        if deleted < PRUNE_BATCH {
+            break;
+        }
+    }
+
+    if pruned > 0 {
+        tracing::info!("Pruned {pruned} pixel history changes older than {retention_days} days");
+    }
+    Ok(pruned)
+}
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::{
+    AppState, infrastructure::db::repositories::LeaderboardRepository,
+    utils::scheduler::spawn_interval_task,
+};
+
+/// Periodically recomputes the leaderboards, so `stats.leaderboards` reads precomputed
+/// rankings instead of aggregating pixels on every request.
+pub fn spawn_leaderboard_refresher(state: AppState) -> JoinHandle<()> {
+    let period = Duration::from_secs(state.config.canvas.leaderboard_refresh_secs);
+
+    spawn_interval_task("leaderboard_refresh", period, move || {
+        let state = state.clone();
+        async move { LeaderboardRepository::refresh(state.db.get_connection()).await }
+    })
+}
@@ -1,6 +1,7 @@
 use base64::Engine;
 use chrono::Utc;
 use sea_orm::ActiveValue::Set;
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
 use std::time::Duration;
 use uuid::Uuid;
 
@@ -10,18 +11,32 @@ use crate::{
     infrastructure::{
         cache::keys::CacheKey,
         db::{
-            entities::canvas::CanvasState,
+            entities::{canvas::CanvasState, consumed_signature::SignatureOperation},
             repositories::{CanvasRepository, PixelRepository},
         },
     },
     services::{
         canvas::{
-            pack_pixels_to_colors,
+            cached_canvas, pack_pixels_to_colors,
+            palette::{palette_size, parse_palette},
             types::{CanvasInfo, PublishTransactionInfo},
         },
-        solana,
+        outbox,
+        pixel::validation::validate_pixel_color,
+        solana::{
+            self,
+            accounts::{CanvasMetadata, ProgramAccount},
+            fees::CostEstimate,
+            signatures,
+            transactions::{self, ExpectedInstruction, ExpectedOperation},
+            wallet,
+        },
+        webhooks::{
+            WebhookEvent,
+            helius::{self, PendingConfirmation},
+        },
     },
-    ws::types::RoomCanvasUpdate,
+    ws::{close::CloseReason, types::RoomCanvasUpdate},
 };
 
 pub async fn create_canvas(
@@ -29,6 +44,8 @@ pub async fn create_canvas(
     owner_id: Uuid,
     name: &str,
     initial_color: i16,
+    palette: Option<&[String]>,
+    parent_canvas_id: Option<Uuid>,
 ) -> Result<CanvasInfo> {
     let max_name_length = state.config.canvas.max_name_length;
     let trimmed_name = name.trim();
@@ -39,6 +56,26 @@ pub async fn create_canvas(
         )));
     }
 
+    let palette = palette
+        .map(|colors| parse_palette(&state.config.canvas, colors))
+        .transpose()?;
+    validate_pixel_color(
+        palette_size(&state.config.canvas, palette.as_ref()),
+        initial_color,
+    )?;
+
+    // Remixes can only start from a minted canvas, so provenance always points at a real NFT.
+    if let Some(parent_id) = parent_canvas_id {
+        let parent = CanvasRepository::find_canvas_by_id(state.db.get_connection(), parent_id)
+            .await?
+            .ok_or(AppError::CanvasNotFound)?;
+        if parent.state != CanvasState::Minted {
+            return Err(AppError::InvalidParams(
+                "Only minted canvases can be remixed".into(),
+            ));
+        }
+    }
+
     let db_transaction = state.db.begin_transaction().await?;
 
     if CanvasRepository::exists_by_name_and_owner(&db_transaction, owner_id, name).await? {
@@ -46,7 +83,9 @@ pub async fn create_canvas(
         return Err(AppError::CanvasNameExists);
     }
 
-    let canvas = CanvasRepository::create_canvas(&db_transaction, owner_id, name).await?;
+    let canvas =
+        CanvasRepository::create_canvas(&db_transaction, owner_id, name, palette, parent_canvas_id)
+            .await?;
 
     CanvasRepository::add_canvas_collaborator(&db_transaction, canvas.id, owner_id).await?;
 
@@ -69,7 +108,7 @@ pub async fn initialize_canvas_publish(
     canvas_id: Uuid,
     user_id: Uuid,
 ) -> Result<PublishTransactionInfo> {
-    state.cache.local.invalidate_canvas(&canvas_id).await;
+    state.cache.invalidate_canvas(&canvas_id).await;
 
     let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
         .await?
@@ -79,6 +118,11 @@ pub async fn initialize_canvas_publish(
         return Err(AppError::NotCanvasOwner);
     }
 
+    // Checked here as well as on the transition, so the on-chain checks only run for drafts.
+    if !canvas.state.is_valid_transition(&CanvasState::Publishing) {
+        return Err(AppError::InvalidCanvasStateTransition);
+    }
+
     let lock_key = CacheKey::canvas_lock(&canvas_id);
     let acquired = state
         .cache
@@ -93,27 +137,21 @@ pub async fn initialize_canvas_publish(
         return Err(AppError::PixelLocked);
     }
 
-    let pixels =
-        PixelRepository::find_pixels_by_canvas(state.db.get_connection(), canvas_id).await?;
+    let (width, height) = (state.config.canvas.width, state.config.canvas.height);
+    let pixel_colors =
+        PixelRepository::find_canvas_colors(state.db.get_connection(), canvas_id, width, height)
+            .await?;
 
-    let pixel_colors_packed = pack_pixels_to_colors(
-        &pixels,
-        state.config.canvas.width,
-        state.config.canvas.height,
-    );
+    let pixel_colors_packed = pack_pixels_to_colors(&pixel_colors, width, height);
     let pixel_colors_base64 = base64::engine::general_purpose::STANDARD.encode(pixel_colors_packed);
+    let owner_wallet = transactions::user_wallet(state, user_id).await?;
 
-    CanvasRepository::update_canvas_state(
-        &state.db,
-        canvas_id,
-        CanvasState::Publishing,
-        |_active| {},
-    )
-    .await?;
+    // New canvases always go to the current program; older ones stay where they were published.
+    let program = state.solana_client.current_program();
 
     let canvas_id_bytes = canvas_id.as_bytes();
-    let (canvas_pda, _bump) = state.solana_client.derive_canvas_pda(canvas_id_bytes);
-    let (config_pda, _) = state.solana_client.derive_config_pda();
+    let (canvas_pda, _bump) = program.derive_canvas_pda(canvas_id_bytes);
+    let (config_pda, _) = program.derive_config_pda();
 
     let blockhash = state
         .solana_client
@@ -121,17 +159,102 @@ pub async fn initialize_canvas_publish(
         .await
         .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
 
+    let instruction = transactions::publish_canvas_instruction(
+        &program,
+        &owner_wallet,
+        &canvas_id,
+        &pixel_colors_packed,
+    );
+    let instructions = [instruction];
+    let transaction =
+        transactions::build_unsigned_transaction(&instructions, &owner_wallet, blockhash);
+
+    // A failed estimate only leaves it out, the publish can still go ahead.
+    let estimated_cost = match state
+        .solana_client
+        .estimate_cost(
+            &instructions,
+            &owner_wallet,
+            blockhash,
+            &[CanvasMetadata::SIZE],
+        )
+        .await
+    {
+        Ok(estimate) => Some(estimate),
+        Err(e) => {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to estimate publish cost");
+            None
+        }
+    };
+
+    if let Err(e) =
+        check_publish_preconditions(state, &canvas_pda, &owner_wallet, estimated_cost.as_ref())
+            .await
+    {
+        state.cache.redis.delete(&lock_key).await?;
+        return Err(e);
+    }
+
+    CanvasRepository::update_canvas_state(
+        &state.db,
+        canvas_id,
+        CanvasState::Publishing,
+        Vec::new(),
+        |active| {
+            active.program_id = Set(Some(program.program_id.to_string()));
+        },
+    )
+    .await?;
+
     Ok(PublishTransactionInfo {
         canvas_id,
         canvas_pda: canvas_pda.to_string(),
         config_pda: config_pda.to_string(),
-        program_id: state.solana_client.get_program_id().to_string(),
+        program_id: program.program_id.to_string(),
         blockhash: blockhash.to_string(),
         canvas_id_bytes: *canvas_id_bytes,
         pixel_colors_packed: pixel_colors_base64,
+        transaction,
+        estimated_cost,
     })
 }
 
+/// Stops a publish that would fail on-chain before the owner is asked to sign it: the canvas
+/// account must not exist yet, and the owner must cover its rent plus the fee.
+async fn check_publish_preconditions(
+    state: &AppState,
+    canvas_pda: &Pubkey,
+    owner_wallet: &Pubkey,
+    estimated_cost: Option<&CostEstimate>,
+) -> Result<()> {
+    let exists = state
+        .solana_client
+        .account_exists(canvas_pda)
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+    if exists {
+        return Err(AppError::CanvasAccountExists {
+            canvas_pda: canvas_pda.to_string(),
+        });
+    }
+
+    // Without an estimate, rent falls back to the cluster's default rate.
+    let rent_lamports = match estimated_cost {
+        Some(estimate) => estimate.rent_lamports,
+        None => Rent::default().minimum_balance(CanvasMetadata::SIZE),
+    };
+    let balance = wallet::check_balance(state, owner_wallet, rent_lamports).await?;
+    if !balance.sufficient {
+        return Err(AppError::InsufficientBalance {
+            balance_lamports: balance.balance_lamports,
+            required_lamports: balance.required_lamports,
+            shortfall_lamports: balance.shortfall_lamports,
+        });
+    }
+
+    Ok(())
+}
+
 pub async fn confirm_canvas_publish(
     state: &AppState,
     canvas_id: Uuid,
@@ -139,26 +262,41 @@ pub async fn confirm_canvas_publish(
     signature: &str,
     canvas_pda: &str,
 ) -> Result<CanvasInfo> {
-    let canvas = if let Some(cached) = state.cache.local.get_canvas(&canvas_id).await {
-        (*cached).clone()
-    } else {
-        let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
-            .await?
-            .ok_or(AppError::CanvasNotFound)?;
-        state.cache.local.set_canvas(canvas.clone()).await;
-        canvas
-    };
+    let canvas = cached_canvas(state, canvas_id).await?;
 
     if canvas.owner_id != user_id {
         return Err(AppError::NotCanvasOwner);
     }
 
-    let tx_valid = solana::verify_program_transaction(
-        state.solana_client.get_client(),
+    let record = signatures::operation_record(
         signature,
-        state.solana_client.get_program_id(),
+        SignatureOperation::CanvasPublish,
+        canvas_id,
+        user_id,
+    );
+    signatures::ensure_signature_unused(state, &record).await?;
+
+    helius::track_pending_confirmation(
+        state,
+        signature,
+        &PendingConfirmation::Publish {
+            canvas_id,
+            user_id,
+            canvas_pda: canvas_pda.to_string(),
+        },
     )
-    .await?;
+    .await;
+
+    let expected = ExpectedInstruction {
+        program: solana::canvas_program(state.solana_client.as_ref(), &canvas)?,
+        signer: transactions::user_wallet(state, user_id).await?,
+        canvas_id,
+        operation: ExpectedOperation::Publish,
+    };
+    let tx_valid = state
+        .solana_client
+        .verify_program_instruction(signature, &expected)
+        .await?;
 
     if !tx_valid {
         return Err(AppError::TransactionFailed(
@@ -166,60 +304,81 @@ pub async fn confirm_canvas_publish(
         ));
     }
 
-    let canvas = CanvasRepository::update_canvas_state(
+    signatures::claim_signature(state, &record).await?;
+
+    let mut events = vec![outbox::room_event(
+        canvas_id,
+        RoomCanvasUpdate::Published {
+            pda: canvas_pda.to_string(),
+        },
+    )?];
+    events.extend(outbox::webhook_events(
+        state,
+        &WebhookEvent::CanvasPublished {
+            canvas_id,
+            canvas_pda: canvas_pda.to_string(),
+        },
+    )?);
+
+    let canvas = match CanvasRepository::update_canvas_state(
         &state.db,
         canvas_id,
         CanvasState::Published,
+        events,
         |active| {
             active.published_at = Set(Some(Utc::now()));
             active.canvas_pda = Set(Some(canvas_pda.to_string()));
         },
     )
-    .await?;
+    .await
+    {
+        Ok(canvas) => canvas,
+        Err(AppError::InvalidCanvasStateTransition) => {
+            // The Helius webhook may have published it while this call was verifying.
+            let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+                .await?
+                .ok_or(AppError::CanvasNotFound)?;
+            if canvas.state == CanvasState::Published
+                && canvas.canvas_pda.as_deref() == Some(canvas_pda)
+            {
+                helius::clear_pending_confirmation(state, signature).await;
+                return Ok(CanvasInfo::from(canvas));
+            }
+            return Err(AppError::InvalidCanvasStateTransition);
+        }
+        Err(e) => return Err(e),
+    };
 
     let lock_key = CacheKey::canvas_lock(&canvas_id);
 
     let _ = tokio::join!(
-        state.cache.local.invalidate_canvas(&canvas_id),
         state.cache.redis.delete(&lock_key),
+        helius::clear_pending_confirmation(state, signature),
     );
 
-    state
-        .ws_rooms
-        .broadcast(
-            &canvas_id,
-            RoomCanvasUpdate::Published {
-                pda: canvas_pda.to_string(),
-            },
-        )
-        .await;
-
     Ok(CanvasInfo::from(canvas))
 }
 
 pub async fn cancel_canvas_publish(state: &AppState, canvas_id: Uuid, user_id: Uuid) -> Result<()> {
-    let canvas = if let Some(cached) = state.cache.local.get_canvas(&canvas_id).await {
-        (*cached).clone()
-    } else {
-        let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
-            .await?
-            .ok_or(AppError::CanvasNotFound)?;
-        state.cache.local.set_canvas(canvas.clone()).await;
-        canvas
-    };
+    let canvas = cached_canvas(state, canvas_id).await?;
 
     if canvas.owner_id != user_id {
         return Err(AppError::NotCanvasOwner);
     }
 
-    CanvasRepository::update_canvas_state(&state.db, canvas_id, CanvasState::Draft, |_active| {})
-        .await?;
+    CanvasRepository::update_canvas_state(
+        &state.db,
+        canvas_id,
+        CanvasState::Draft,
+        Vec::new(),
+        |active| {
+            active.program_id = Set(None);
+        },
+    )
+    .await?;
 
     let lock_key = CacheKey::canvas_lock(&canvas_id);
-    let _ = tokio::join!(
-        state.cache.local.invalidate_canvas(&canvas_id),
-        state.cache.redis.delete(&lock_key),
-    );
+    let _ = state.cache.redis.delete(&lock_key).await;
 
     Ok(())
 }
@@ -245,15 +404,24 @@ pub async fn delete_canvas(state: &AppState, canvas_id: Uuid, user_id: Uuid) ->
 
     CanvasRepository::delete_canvas(&state.db, canvas_id).await?;
 
-    let redis_pixel_key = CacheKey::canvas_pixels(&canvas_id);
     let redis_lock_key = CacheKey::canvas_lock(&canvas_id);
 
     let _ = tokio::join!(
-        state.cache.local.invalidate_canvas(&canvas_id),
-        state.cache.local.invalidate_pixels(&canvas_id),
-        state.cache.redis.delete(&redis_pixel_key),
-        state.cache.redis.delete(&redis_lock_key)
+        state.cache.invalidate_pixels(&canvas_id),
+        state.cache.redis.delete_canvas_pixels(&canvas_id),
+        state.cache.redis.delete(&redis_lock_key),
     );
 
+    // Connections to the room are closed as they receive it
+    state
+        .ws_rooms
+        .broadcast(
+            &canvas_id,
+            RoomCanvasUpdate::RoomClosed {
+                reason: CloseReason::CanvasDeleted,
+            },
+        )
+        .await;
+
     Ok(())
 }
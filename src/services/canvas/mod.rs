@@ -1,11 +1,18 @@
-use crate::infrastructure::db::entities::pixel::Model as Pixel;
-
 pub mod types;
 
+pub mod account;
 pub mod collaboration;
+pub mod history;
+pub mod leaderboard;
 pub mod lifecycle;
+pub mod moderation;
+pub mod palette;
+pub mod presence;
+pub mod settings;
+pub mod timelapse;
 
-/// Packs a canvas of pixels into 768 bytes using 6-bit color encoding.
+/// Packs a canvas of pixel colors, one byte each in row order, into 768 bytes using 6-bit
+/// color encoding.
 ///
 /// Solana instruction limit: 1232 bytes. Each 3-byte sequence encodes 4 pixels (4 * 6 bits = 24 bits).
 /// Supports up to 64 colors (6 bits per pixel).
@@ -14,20 +21,20 @@ pub mod lifecycle;
 /// Byte 0: [Pixel 0 (6 bits)] [Pixel 1 (hi 2 bits)]
 /// Byte 1: [Pixel 1 (lo 4 bits)] [Pixel 2 (hi 4 bits)]
 /// Byte 2: [Pixel 2 (lo 2 bits)] [Pixel 3 (6 bits)]
-pub fn pack_pixels_to_colors(pixels: &[Pixel], width: u8, height: u8) -> [u8; 768] {
+pub fn pack_pixels_to_colors(pixel_colors: &[u8], width: u8, height: u8) -> [u8; 768] {
     const DEFAULT_COLOR: u8 = 10; // White
     const GROUPS: usize = 256; // 1024 pixels / 4 pixels per group
 
     let total_pixels = (width as usize) * (height as usize);
 
-    // Flatten pixel array into color indices
-    let mut colors = vec![DEFAULT_COLOR; total_pixels];
-    for pixel in pixels {
-        let index = (pixel.y as usize) * (width as usize) + (pixel.x as usize);
-        if index < total_pixels {
-            colors[index] = pixel.color as u8 & 0x3F; // 6-bit mask
-        }
-    }
+    // Pad to the full canvas
+    let colors: Vec<u8> = (0..total_pixels)
+        .map(|index| {
+            pixel_colors
+                .get(index)
+                .map_or(DEFAULT_COLOR, |color| color & 0x3F) // 6-bit mask
+        })
+        .collect();
 
     let mut packed = [0u8; 768];
 
@@ -49,4 +56,10 @@ pub fn pack_pixels_to_colors(pixels: &[Pixel], width: u8, height: u8) -> [u8; 76
 }
 
 pub use collaboration::*;
+pub use history::*;
+pub use leaderboard::*;
 pub use lifecycle::*;
+pub use moderation::*;
+pub use presence::*;
+pub use settings::*;
+pub use timelapse::*;
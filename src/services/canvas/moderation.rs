@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{cache::keys::CacheKey, db::repositories::CanvasRepository},
+    services::auth::is_admin,
+    ws::types::RoomCanvasUpdate,
+};
+
+/// Longest mute, so a forgotten one still lifts on its own.
+const MAX_MUTE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Mutes a collaborator on the canvas for `duration_secs`, or lifts their mute when it is
+/// zero. Only the owner or an admin can, and the owner cannot be muted. Returns when the
+/// mute ends.
+pub async fn mute_collaborator(
+    state: &AppState,
+    canvas_id: Uuid,
+    moderator_id: Uuid,
+    user_id: Uuid,
+    duration_secs: u64,
+) -> Result<Option<DateTime<Utc>>> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != moderator_id && !is_admin(&state.db, moderator_id).await? {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if user_id == canvas.owner_id {
+        return Err(AppError::InvalidParams(
+            "The canvas owner cannot be muted".into(),
+        ));
+    }
+
+    if duration_secs > MAX_MUTE_SECS {
+        return Err(AppError::InvalidParams(format!(
+            "Mutes last at most {MAX_MUTE_SECS} seconds"
+        )));
+    }
+
+    if !CanvasRepository::is_canvas_collaborator(state.db.get_connection(), canvas_id, user_id)
+        .await?
+    {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    let key = CacheKey::canvas_mute(&canvas_id, &user_id);
+    let until = if duration_secs == 0 {
+        state.cache.redis.delete(&key).await?;
+        None
+    } else {
+        let until = Utc::now() + chrono::Duration::seconds(duration_secs as i64);
+        state
+            .cache
+            .redis
+            .set(&key, &until, Duration::from_secs(duration_secs))
+            .await?;
+        Some(until)
+    };
+
+    // Connections of the muted user pick this up and start dropping their messages
+    state
+        .ws_rooms
+        .broadcast(&canvas_id, RoomCanvasUpdate::UserMuted { user_id, until })
+        .await;
+
+    Ok(until)
+}
+
+/// When the user's mute on the canvas ends, if they are muted.
+pub async fn muted_until(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<DateTime<Utc>>> {
+    let until: Option<DateTime<Utc>> = state
+        .cache
+        .redis
+        .get(&CacheKey::canvas_mute(&canvas_id, &user_id))
+        .await?;
+    Ok(until.filter(|until| *until > Utc::now()))
+}
+
+/// Checks a mute already read from the cache.
+pub fn check_mute(until: Option<DateTime<Utc>>) -> Result<()> {
+    match until.filter(|until| *until > Utc::now()) {
+        Some(until) => Err(AppError::UserMuted {
+            remaining_secs: (until - Utc::now()).num_seconds().max(1) as u64,
+        }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    config::CanvasConfig,
+    error::{AppError, Result},
+    infrastructure::db::entities::canvas::CanvasPalette,
+};
+
+pub type Rgb = (u8, u8, u8);
+
+/// Minimum custom palette size. Packed on-chain colors are 6 bits, capping the maximum at 64.
+const MIN_PALETTE_SIZE: usize = 2;
+
+const FALLBACK_COLOR: Rgb = (0x80, 0x80, 0x80);
+
+/// Built-in palette used by canvases without a custom one.
+pub const DEFAULT_PALETTE: [Rgb; 64] = [
+    // Row 1: Grayscale
+    (0x00, 0x00, 0x00), // #000000
+    (0x1a, 0x1a, 0x1a), // #1a1a1a
+    (0x33, 0x33, 0x33), // #333333
+    (0x4d, 0x4d, 0x4d), // #4d4d4d
+    (0x66, 0x66, 0x66), // #666666
+    (0x80, 0x80, 0x80), // #808080
+    (0x99, 0x99, 0x99), // #999999
+    (0xb3, 0xb3, 0xb3), // #b3b3b3
+    // Row 2: More grayscale + basics
+    (0xcc, 0xcc, 0xcc), // #cccccc
+    (0xe6, 0xe6, 0xe6), // #e6e6e6
+    (0xff, 0xff, 0xff), // #ffffff
+    (0xa9, 0x38, 0x38), // #A93838
+    (0xf5, 0xf5, 0xdc), // #F5F5DC
+    (0x8b, 0x00, 0x00), // #8B0000
+    (0xdc, 0x14, 0x3c), // #DC143C
+    (0xff, 0x63, 0x47), // #FF6347
+    // Row 3: Reds to Oranges
+    (0xff, 0x45, 0x00), // #FF4500
+    (0xff, 0x8c, 0x00), // #FF8C00
+    (0xff, 0xa5, 0x00), // #FFA500
+    (0xff, 0xd7, 0x00), // #FFD700
+    (0xff, 0xff, 0x00), // #FFFF00
+    (0xad, 0xff, 0x2f), // #ADFF2F
+    (0x7f, 0xff, 0x00), // #7FFF00
+    (0x00, 0xff, 0x00), // #00FF00 (Green!)
+    // Row 4: Greens
+    (0x32, 0xcd, 0x32), // #32CD32
+    (0x22, 0x8b, 0x22), // #228B22
+    (0x00, 0x64, 0x00), // #006400
+    (0x00, 0x8b, 0x8b), // #008B8B
+    (0x20, 0xb2, 0xaa), // #20B2AA
+    (0x00, 0xce, 0xd1), // #00CED1
+    (0x00, 0xff, 0xff), // #00FFFF
+    (0x00, 0xbf, 0xff), // #00BFFF
+    // Row 5: Blues
+    (0x1e, 0x90, 0xff), // #1E90FF
+    (0x00, 0x00, 0xff), // #0000FF (Blue!)
+    (0x00, 0x00, 0xcd), // #0000CD
+    (0x00, 0x00, 0x8b), // #00008B
+    (0x19, 0x19, 0x70), // #191970
+    (0x4b, 0x00, 0x82), // #4B0082
+    (0x8b, 0x00, 0x8b), // #8B008B
+    (0x94, 0x00, 0xd3), // #9400D3
+    // Row 6: Purples to Pinks
+    (0x99, 0x32, 0xcc), // #9932CC
+    (0xba, 0x55, 0xd3), // #BA55D3
+    (0xda, 0x70, 0xd6), // #DA70D6
+    (0xff, 0x00, 0xff), // #FF00FF
+    (0xff, 0x69, 0xb4), // #FF69B4
+    (0xff, 0x14, 0x93), // #FF1493
+    (0xc7, 0x15, 0x85), // #C71585
+    (0xdb, 0x70, 0x93), // #DB7093
+    // Row 7: Browns and Earth tones
+    (0x8b, 0x45, 0x13), // #8B4513
+    (0xa0, 0x52, 0x2d), // #A0522D
+    (0xd2, 0x69, 0x1e), // #D2691E
+    (0xcd, 0x85, 0x3f), // #CD853F
+    (0xde, 0xb8, 0x87), // #DEB887
+    (0xf5, 0xde, 0xb3), // #F5DEB3
+    (0xfa, 0xeb, 0xd7), // #FAEBD7
+    (0xff, 0xe4, 0xc4), // #FFE4C4
+    // Row 8: More earth + pastels
+    (0xff, 0xda, 0xb9), // #FFDAB9
+    (0xff, 0xe4, 0xe1), // #FFE4E1
+    (0xff, 0xf0, 0xf5), // #FFF0F5
+    (0xe6, 0xe6, 0xfa), // #E6E6FA
+    (0xd8, 0xbf, 0xd8), // #D8BFD8
+    (0xdd, 0xa0, 0xdd), // #DDA0DD
+    (0xee, 0x82, 0xee), // #EE82EE
+    (0xff, 0xff, 0xe0), // #FFFFE0
+];
+
+/// Number of valid color indices on a canvas.
+pub fn palette_size(config: &CanvasConfig, palette: Option<&CanvasPalette>) -> usize {
+    match palette {
+        Some(palette) => palette.0.len(),
+        None => (config.color_count as usize).min(DEFAULT_PALETTE.len()),
+    }
+}
+
+/// Resolves a canvas palette to RGB, falling back to the built-in palette.
+pub fn resolve_palette(config: &CanvasConfig, palette: Option<&CanvasPalette>) -> Vec<Rgb> {
+    match palette {
+        Some(palette) => palette
+            .0
+            .iter()
+            .map(|hex| parse_hex_color(hex).unwrap_or(FALLBACK_COLOR))
+            .collect(),
+        None => DEFAULT_PALETTE[..palette_size(config, None)].to_vec(),
+    }
+}
+
+/// Validates a client-supplied palette and normalizes entries to lowercase `#rrggbb`.
+pub fn parse_palette(config: &CanvasConfig, colors: &[String]) -> Result<CanvasPalette> {
+    let max_size = (config.color_count as usize).min(DEFAULT_PALETTE.len());
+    if colors.len() < MIN_PALETTE_SIZE || colors.len() > max_size {
+        return Err(AppError::InvalidParams(format!(
+            "Palette must have between {} and {} colors",
+            MIN_PALETTE_SIZE, max_size
+        )));
+    }
+
+    let normalized = colors
+        .iter()
+        .map(|hex| {
+            parse_hex_color(hex)
+                .map(format_hex_color)
+                .ok_or_else(|| AppError::InvalidParams(format!("Invalid palette color: {}", hex)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(CanvasPalette(normalized))
+}
+
+pub fn format_hex_color((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+pub fn parse_hex_color(hex: &str) -> Option<Rgb> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+pub fn color_to_rgb(palette: &[Rgb], index: u8) -> Rgb {
+    palette
+        .get(index as usize)
+        .copied()
+        .unwrap_or(FALLBACK_COLOR)
+}
@@ -0,0 +1,114 @@
+use std::{collections::HashSet, time::Duration};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::repositories::{CanvasRepository, UserRepository},
+    },
+    services::canvas::types::PresentUser,
+};
+
+/// Heartbeats a connection may miss before its user stops counting as present. Covers
+/// instances that went away without removing their users.
+const MISSED_HEARTBEATS: u64 = 3;
+
+fn presence_window_secs(state: &AppState) -> u64 {
+    state.config.server.ws_auth_recheck_secs * MISSED_HEARTBEATS
+}
+
+/// Marks the user as connected to the canvas's room. Called on join and on every heartbeat
+/// of an open connection, so rooms on other instances see them too.
+pub async fn mark_present(state: &AppState, canvas_id: Uuid, user_id: Uuid) {
+    let ttl = Duration::from_secs(presence_window_secs(state));
+    if let Err(e) = state
+        .cache
+        .redis
+        .sorted_set_add(
+            &CacheKey::canvas_presence(&canvas_id),
+            &user_id.to_string(),
+            Utc::now().timestamp(),
+            ttl,
+        )
+        .await
+    {
+        tracing::warn!(canvas_id = %canvas_id, user_id = %user_id, error = ?e, "Failed to record presence");
+    }
+}
+
+/// Removes the user once their last connection on this instance closes. Connections they
+/// still hold on other instances add them back on their next heartbeat.
+pub async fn mark_absent(state: &AppState, canvas_id: Uuid, user_id: Uuid) {
+    if let Err(e) = state
+        .cache
+        .redis
+        .sorted_set_remove(&CacheKey::canvas_presence(&canvas_id), &user_id.to_string())
+        .await
+    {
+        tracing::warn!(canvas_id = %canvas_id, user_id = %user_id, error = ?e, "Failed to clear presence");
+    }
+}
+
+/// Collaborators connected to the canvas on any instance.
+pub async fn get_presence(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<PresentUser>> {
+    let db_connection = state.db.get_connection();
+
+    if !CanvasRepository::is_canvas_collaborator(db_connection, canvas_id, user_id).await? {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    present_users(state, canvas_id).await
+}
+
+pub(crate) async fn present_users(state: &AppState, canvas_id: Uuid) -> Result<Vec<PresentUser>> {
+    let db_connection = state.db.get_connection();
+
+    let mut user_ids: HashSet<Uuid> = state
+        .ws_rooms
+        .present_users(&canvas_id)
+        .await
+        .into_iter()
+        .collect();
+
+    // Without Redis, only this instance's room is known.
+    let min_score = Utc::now().timestamp() - presence_window_secs(state) as i64;
+    match state
+        .cache
+        .redis
+        .sorted_set_members_since(&CacheKey::canvas_presence(&canvas_id), min_score)
+        .await
+    {
+        Ok(members) => {
+            user_ids.extend(
+                members
+                    .iter()
+                    .filter_map(|member| member.parse::<Uuid>().ok()),
+            );
+        }
+        Err(e) => {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to load shared presence");
+        }
+    }
+
+    let user_ids: Vec<Uuid> = user_ids.into_iter().collect();
+    let mut users: Vec<PresentUser> = UserRepository::find_users_by_ids(db_connection, &user_ids)
+        .await?
+        .into_iter()
+        .map(|user| PresentUser {
+            user_id: user.id,
+            username: user.username,
+            wallet_address: user.wallet_address,
+        })
+        .collect();
+    users.sort_by_key(|user| user.user_id);
+
+    Ok(users)
+}
@@ -0,0 +1,154 @@
+use sea_orm::ActiveValue::Set;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{entities::canvas::CanvasState, repositories::CanvasRepository},
+    services::canvas::types::{BidRulesUpdate, CanvasInfo},
+};
+
+const MAX_PIXEL_QUOTA_WINDOW_SECS: u32 = 86_400;
+
+/// Sets or clears the per-collaborator pixel quota on a Draft canvas.
+pub async fn set_pixel_quota(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    quota: Option<(u32, u32)>,
+) -> Result<CanvasInfo> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if canvas.state != CanvasState::Draft {
+        return Err(AppError::InvalidParams(
+            "Pixel quota can only be changed on draft canvases".into(),
+        ));
+    }
+
+    if let Some((limit, window_secs)) = quota {
+        let total_pixels = state.config.canvas.width as u32 * state.config.canvas.height as u32;
+        if limit == 0 || limit > total_pixels {
+            return Err(AppError::InvalidParams(format!(
+                "Pixel quota must be between 1 and {}",
+                total_pixels
+            )));
+        }
+        if window_secs == 0 || window_secs > MAX_PIXEL_QUOTA_WINDOW_SECS {
+            return Err(AppError::InvalidParams(format!(
+                "Quota window must be between 1 and {} seconds",
+                MAX_PIXEL_QUOTA_WINDOW_SECS
+            )));
+        }
+    }
+
+    let updated_canvas = CanvasRepository::update_canvas(&state.db, canvas_id, |canvas| {
+        canvas.pixel_quota = Set(quota.map(|(limit, _)| limit as i32));
+        canvas.pixel_quota_window_secs = Set(quota.map(|(_, window_secs)| window_secs as i32));
+    })
+    .await?;
+
+    Ok(CanvasInfo::from(updated_canvas))
+}
+
+/// Sets the bid floor, step and ceiling for a canvas before it is published.
+/// Each `None` clears that rule.
+pub async fn set_bid_rules(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    rules: BidRulesUpdate,
+) -> Result<CanvasInfo> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    // Bidders rely on the rules staying fixed once bidding opens.
+    if canvas.state != CanvasState::Draft {
+        return Err(AppError::InvalidParams(
+            "Bid rules can only be changed on draft canvases".into(),
+        ));
+    }
+
+    let min_bid = state.config.canvas.min_bid_lamports;
+    if let Some(floor) = rules.floor_lamports
+        && floor < min_bid
+    {
+        return Err(AppError::InvalidParams(format!(
+            "Bid floor must be at least {} lamports",
+            min_bid
+        )));
+    }
+    if rules.step_lamports == Some(0) {
+        return Err(AppError::InvalidParams(
+            "Bid step must be at least 1 lamport".into(),
+        ));
+    }
+    if let Some(ceiling) = rules.ceiling_lamports
+        && ceiling <= rules.floor_lamports.unwrap_or(min_bid)
+    {
+        return Err(AppError::InvalidParams(
+            "Bid ceiling must be above the bid floor".into(),
+        ));
+    }
+
+    let to_db = |value: Option<u64>| -> Result<Option<i64>> {
+        value
+            .map(|lamports| {
+                i64::try_from(lamports)
+                    .map_err(|_| AppError::InvalidParams("Bid amount too large".into()))
+            })
+            .transpose()
+    };
+    let floor = to_db(rules.floor_lamports)?;
+    let step = to_db(rules.step_lamports)?;
+    let ceiling = to_db(rules.ceiling_lamports)?;
+
+    let updated_canvas = CanvasRepository::update_canvas(&state.db, canvas_id, |canvas| {
+        canvas.bid_floor_lamports = Set(floor);
+        canvas.bid_step_lamports = Set(step);
+        canvas.bid_ceiling_lamports = Set(ceiling);
+    })
+    .await?;
+
+    Ok(CanvasInfo::from(updated_canvas))
+}
+
+/// Exempts the owner from the Draft cooldown so they can sketch the base artwork
+/// at full speed. Collaborators remain throttled.
+pub async fn set_cooldown_exemption(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    owner_exempt: bool,
+) -> Result<CanvasInfo> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if canvas.state != CanvasState::Draft {
+        return Err(AppError::InvalidParams(
+            "Cooldown exemption can only be changed on draft canvases".into(),
+        ));
+    }
+
+    let updated_canvas = CanvasRepository::update_canvas(&state.db, canvas_id, |canvas| {
+        canvas.owner_cooldown_exempt = Set(owner_exempt);
+    })
+    .await?;
+
+    Ok(CanvasInfo::from(updated_canvas))
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    config::CanvasConfig,
+    error::{AppError, Result},
+    infrastructure::db::repositories::{
+        CanvasRepository, PixelHistoryRepository, PixelRepository,
+        pixel_history::{PixelChangeRow, PixelHistoryRange},
+    },
+    services::canvas::types::{
+        Timelapse, TimelapseChange, TimelapseFrame, TimelapseReplay, TimelapseRequest,
+    },
+};
+
+pub const DEFAULT_TIMELAPSE_BUCKET_SECS: u32 = 60;
+pub const DEFAULT_TIMELAPSE_MAX_FRAMES: u32 = 300;
+
+const MAX_TIMELAPSE_BUCKET_SECS: u32 = 86_400;
+const MAX_TIMELAPSE_FRAMES: u32 = 1_000;
+
+/// Returns a page of pixel history grouped into time-bucketed frames.
+///
+/// Each frame keeps only the last change per pixel within its bucket. When there are
+/// more buckets than `max_frames`, neighbouring frames are merged the same way.
+pub async fn get_canvas_timelapse(state: &AppState, req: TimelapseRequest) -> Result<Timelapse> {
+    if !CanvasRepository::is_canvas_collaborator(
+        state.db.get_connection(),
+        req.canvas_id,
+        req.user_id,
+    )
+    .await?
+    {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    if req.bucket_secs == 0 || req.bucket_secs > MAX_TIMELAPSE_BUCKET_SECS {
+        return Err(AppError::InvalidParams(format!(
+            "bucket_secs must be between 1 and {}",
+            MAX_TIMELAPSE_BUCKET_SECS
+        )));
+    }
+    if req.max_frames == 0 || req.max_frames > MAX_TIMELAPSE_FRAMES {
+        return Err(AppError::InvalidParams(format!(
+            "max_frames must be between 1 and {}",
+            MAX_TIMELAPSE_FRAMES
+        )));
+    }
+    if let (Some(from), Some(to)) = (req.from, req.to)
+        && from >= to
+    {
+        return Err(AppError::InvalidParams("from must be before to".into()));
+    }
+
+    let range = PixelHistoryRange {
+        from: req.from,
+        to: req.to,
+        after_id: req.cursor,
+    };
+    let replay = replay_timelapse(
+        state.db.get_connection(),
+        &state.config.canvas,
+        req.canvas_id,
+        range,
+        req.bucket_secs,
+        req.max_frames as usize,
+    )
+    .await?;
+
+    Ok(Timelapse {
+        base_colors: base64::engine::general_purpose::STANDARD.encode(&replay.base_colors),
+        frames: replay.frames,
+        total_changes: replay.total_changes,
+        next_cursor: replay.next_cursor,
+    })
+}
+
+/// Rewinds the canvas to the start of `range` and buckets the changes within it.
+/// Reads at most `timelapse_max_changes` changes; `next_cursor` continues from there.
+pub async fn replay_timelapse<C: ConnectionTrait>(
+    db_connection: &C,
+    canvas_config: &CanvasConfig,
+    canvas_id: Uuid,
+    range: PixelHistoryRange,
+    bucket_secs: u32,
+    max_frames: usize,
+) -> Result<TimelapseReplay> {
+    let max_changes = canvas_config.timelapse_max_changes;
+
+    let (changes, total_changes, colors_before, pixels) = tokio::join!(
+        PixelHistoryRepository::find_changes(db_connection, canvas_id, range, max_changes + 1),
+        PixelHistoryRepository::count_changes(db_connection, canvas_id, range),
+        PixelHistoryRepository::find_colors_before(db_connection, canvas_id, range),
+        PixelRepository::find_pixels_by_canvas(db_connection, canvas_id),
+    );
+    let mut changes = changes?;
+
+    let next_cursor = if changes.len() as u64 > max_changes {
+        changes.truncate(max_changes as usize);
+        changes.last().map(|change| change.id)
+    } else {
+        None
+    };
+
+    // Rewind the current canvas to the start of the range.
+    let width = canvas_config.width as usize;
+    let mut base_colors = vec![0u8; width * canvas_config.height as usize];
+    let mut set_color = |x: i16, y: i16, color: i16| {
+        let index = (y as usize) * width + (x as usize);
+        if index < base_colors.len() {
+            base_colors[index] = color as u8;
+        }
+    };
+    for pixel in pixels? {
+        set_color(pixel.x, pixel.y, pixel.color);
+    }
+    for row in colors_before? {
+        if let Some(old_color) = row.old_color {
+            set_color(row.x, row.y, old_color);
+        }
+    }
+
+    let frames = bucket_changes(&changes, bucket_secs);
+    let frames = downsample_frames(frames, max_frames);
+
+    Ok(TimelapseReplay {
+        base_colors,
+        frames,
+        total_changes: total_changes?,
+        next_cursor,
+    })
+}
+
+fn bucket_changes(changes: &[PixelChangeRow], bucket_secs: u32) -> Vec<TimelapseFrame> {
+    let bucket_secs = bucket_secs as i64;
+    let mut frames: Vec<TimelapseFrame> = Vec::new();
+    let mut frame_bucket = None;
+    let mut positions = HashMap::new();
+
+    for change in changes {
+        let bucket = change.created_at.timestamp().div_euclid(bucket_secs);
+
+        if frame_bucket != Some(bucket) {
+            frame_bucket = Some(bucket);
+            positions.clear();
+            frames.push(TimelapseFrame {
+                timestamp: DateTime::<Utc>::from_timestamp(bucket * bucket_secs, 0)
+                    .unwrap_or(change.created_at),
+                changes: Vec::new(),
+            });
+        }
+
+        if let Some(frame) = frames.last_mut() {
+            apply_change(
+                frame,
+                &mut positions,
+                TimelapseChange {
+                    x: change.x,
+                    y: change.y,
+                    color: change.new_color,
+                },
+            );
+        }
+    }
+
+    frames
+}
+
+fn downsample_frames(frames: Vec<TimelapseFrame>, max_frames: usize) -> Vec<TimelapseFrame> {
+    if frames.len() <= max_frames {
+        return frames;
+    }
+
+    let group_size = frames.len().div_ceil(max_frames);
+    let mut merged = Vec::with_capacity(max_frames);
+    let mut positions = HashMap::new();
+
+    for (i, frame) in frames.into_iter().enumerate() {
+        if i % group_size == 0 {
+            positions.clear();
+            merged.push(TimelapseFrame {
+                timestamp: frame.timestamp,
+                changes: Vec::new(),
+            });
+        }
+
+        if let Some(target) = merged.last_mut() {
+            for change in frame.changes {
+                apply_change(target, &mut positions, change);
+            }
+        }
+    }
+
+    merged
+}
+
+/// Adds a change to a frame, replacing any earlier change to the same pixel.
+fn apply_change(
+    frame: &mut TimelapseFrame,
+    positions: &mut HashMap<(i16, i16), usize>,
+    change: TimelapseChange,
+) {
+    match positions.get(&(change.x, change.y)) {
+        Some(&index) => frame.changes[index] = change,
+        None => {
+            positions.insert((change.x, change.y), frame.changes.len());
+            frame.changes.push(change);
+        }
+    }
+}
@@ -1,7 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::infrastructure::db::entities::canvas::{self, CanvasState};
+use crate::{
+    infrastructure::db::entities::canvas::{self, CanvasState, NftStandard},
+    services::solana::fees::CostEstimate,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanvasInfo {
@@ -13,6 +17,18 @@ pub struct CanvasInfo {
     pub canvas_pda: Option<String>,
     pub mint_address: Option<String>,
     pub total_escrowed: i64,
+    pub pixel_quota: Option<i32>,
+    pub pixel_quota_window_secs: Option<i32>,
+    pub bid_floor_lamports: Option<i64>,
+    pub bid_step_lamports: Option<i64>,
+    pub bid_ceiling_lamports: Option<i64>,
+    pub owner_cooldown_exempt: bool,
+    pub royalty_bps: Option<i16>,
+    pub nft_symbol: Option<String>,
+    pub nft_standard: NftStandard,
+    pub collection_mint: Option<String>,
+    pub collection_verified: bool,
+    pub parent_canvas_id: Option<Uuid>,
 }
 
 impl From<canvas::Model> for CanvasInfo {
@@ -26,6 +42,18 @@ impl From<canvas::Model> for CanvasInfo {
             canvas_pda: value.canvas_pda,
             mint_address: value.mint_address,
             total_escrowed: value.total_escrowed,
+            pixel_quota: value.pixel_quota,
+            pixel_quota_window_secs: value.pixel_quota_window_secs,
+            bid_floor_lamports: value.bid_floor_lamports,
+            bid_step_lamports: value.bid_step_lamports,
+            bid_ceiling_lamports: value.bid_ceiling_lamports,
+            owner_cooldown_exempt: value.owner_cooldown_exempt,
+            royalty_bps: value.royalty_bps,
+            nft_symbol: value.nft_symbol,
+            nft_standard: value.nft_standard,
+            collection_mint: value.collection_mint,
+            collection_verified: value.collection_verified,
+            parent_canvas_id: value.parent_canvas_id,
         }
     }
 }
@@ -33,6 +61,7 @@ impl From<canvas::Model> for CanvasInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanvasWithPixels {
     pub canvas: CanvasInfo,
+    pub palette: Vec<String>,
     pub pixel_colors: String,
     pub owned_pixels: Vec<OwnedCanvasPixelInfo>,
 }
@@ -45,15 +74,19 @@ pub struct OwnedCanvasPixelInfo {
     pub price_lamports: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Canvas pixels as cached in Redis.
+#[derive(Debug, Clone)]
 pub struct CachedPixelData {
-    pub pixel_colors: String,
+    /// One byte per pixel, in row order.
+    pub colors: Vec<u8>,
     pub owned_pixels: Vec<OwnedCanvasPixelInfo>,
 }
 
 pub struct UserCanvases {
     pub owned: Vec<CanvasInfo>,
+    pub next_owned_cursor: Option<String>,
     pub collaborating: Vec<CanvasInfo>,
+    pub next_collaborating_cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +100,12 @@ pub struct PublishTransactionInfo {
 
     /// Base64 encoded 768 bytes of 6-bit packed pixel colors from database.
     pub pixel_colors_packed: String,
+
+    /// Base64 unsigned `publish_canvas` transaction for the owner's wallet to sign.
+    pub transaction: String,
+
+    /// Fees plus the canvas account's rent. `None` if the estimate could not be fetched.
+    pub estimated_cost: Option<CostEstimate>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,3 +113,72 @@ pub struct JoinCanvasResult {
     pub canvas_id: Uuid,
     pub already_member: bool,
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct BidRulesUpdate {
+    pub floor_lamports: Option<u64>,
+    pub step_lamports: Option<u64>,
+    pub ceiling_lamports: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelapseRequest {
+    pub canvas_id: Uuid,
+    pub user_id: Uuid,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub cursor: Option<i64>,
+    pub bucket_secs: u32,
+    pub max_frames: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimelapseChange {
+    pub x: i16,
+    pub y: i16,
+    pub color: i16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelapseFrame {
+    pub timestamp: DateTime<Utc>,
+    pub changes: Vec<TimelapseChange>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Timelapse {
+    /// Base64-encoded color bytes of the canvas right before the first frame.
+    pub base_colors: String,
+    pub frames: Vec<TimelapseFrame>,
+    /// Changes in the whole requested range, not only this page.
+    pub total_changes: u64,
+    pub next_cursor: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelapseReplay {
+    /// Color bytes of the canvas right before the first frame, row-major.
+    pub base_colors: Vec<u8>,
+    pub frames: Vec<TimelapseFrame>,
+    /// Changes in the whole requested range, not only this page.
+    pub total_changes: u64,
+    pub next_cursor: Option<i64>,
+}
+
+/// Everything a client needs to draw a room it just joined. Room updates numbered after `seq`
+/// apply on top of it. `seq` is absent when room updates are not numbered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanvasSnapshot {
+    pub seq: Option<u64>,
+    #[serde(flatten)]
+    pub canvas: CanvasWithPixels,
+    pub presence: Vec<PresentUser>,
+}
+
+/// A collaborator with an open WebSocket connection to the canvas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresentUser {
+    pub user_id: Uuid,
+    pub username: Option<String>,
+    pub wallet_address: String,
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    error::{AppError, Result},
+    services::market::{MarketDataSource, MarketInfo, sol_to_lamports},
+};
+
+const ACTIVITY_PAGE_SIZE: u32 = 20;
+
+/// Listing and sale data from the Magic Eden v2 API.
+pub struct MagicEdenSource {
+    http: Client,
+    api_url: String,
+}
+
+impl MagicEdenSource {
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            http: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// `None` when Magic Eden has not indexed the token.
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let response = self
+            .http
+            .get(format!("{}{}", self.api_url, path))
+            .send()
+            .await
+            .map_err(|e| AppError::MarketplaceError(e.to_string()))?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::MarketplaceError(format!(
+                "Magic Eden returned {}: {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| AppError::MarketplaceError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl MarketDataSource for MagicEdenSource {
+    fn name(&self) -> &'static str {
+        "magic_eden"
+    }
+
+    async fn fetch(&self, mint_address: &str) -> Result<MarketInfo> {
+        let mut info = MarketInfo::unindexed(self.name(), mint_address);
+
+        let Some(token) = self
+            .get::<Value>(&format!("/tokens/{mint_address}"))
+            .await?
+        else {
+            return Ok(info);
+        };
+        info.owner = token["owner"].as_str().map(str::to_string);
+
+        let listings = self
+            .get::<Vec<Value>>(&format!("/tokens/{mint_address}/listings"))
+            .await?
+            .unwrap_or_default();
+        if let Some(listing) = listings.first() {
+            info.listed = true;
+            info.listed_price_lamports = listing["price"].as_f64().map(sol_to_lamports);
+            info.seller = listing["seller"].as_str().map(str::to_string);
+        }
+
+        let activities = self
+            .get::<Vec<Value>>(&format!(
+                "/tokens/{mint_address}/activities?offset=0&limit={ACTIVITY_PAGE_SIZE}"
+            ))
+            .await?
+            .unwrap_or_default();
+        // Activities are newest first; `buyNow` is a completed sale.
+        if let Some(sale) = activities
+            .iter()
+            .find(|activity| activity["type"].as_str() == Some("buyNow"))
+        {
+            info.last_sale_price_lamports = sale["price"].as_f64().map(sol_to_lamports);
+            info.last_sale_at = sale["blockTime"]
+                .as_i64()
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0));
+        }
+
+        Ok(info)
+    }
+}
@@ -0,0 +1,125 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    config::MarketConfig,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::{entities::canvas::CanvasState, repositories::CanvasRepository},
+    },
+};
+
+pub mod magic_eden;
+pub mod tensor;
+
+pub use magic_eden::MagicEdenSource;
+pub use tensor::TensorSource;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Secondary-market snapshot of a minted canvas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketInfo {
+    pub source: String,
+    pub mint_address: String,
+    pub owner: Option<String>,
+    pub listed: bool,
+    pub listed_price_lamports: Option<u64>,
+    pub seller: Option<String>,
+    pub last_sale_price_lamports: Option<u64>,
+    pub last_sale_at: Option<DateTime<Utc>>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl MarketInfo {
+    /// Snapshot for a mint the marketplace has not indexed yet.
+    pub fn unindexed(source: &str, mint_address: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            mint_address: mint_address.to_string(),
+            owner: None,
+            listed: false,
+            listed_price_lamports: None,
+            seller: None,
+            last_sale_price_lamports: None,
+            last_sale_at: None,
+            fetched_at: Utc::now(),
+        }
+    }
+}
+
+/// Marketplace API queried for listing and sale data.
+#[async_trait]
+pub trait MarketDataSource: Send + Sync {
+    /// Short marketplace name returned with the data, e.g. `magic_eden`.
+    fn name(&self) -> &'static str;
+
+    async fn fetch(&self, mint_address: &str) -> Result<MarketInfo>;
+}
+
+/// Builds the source selected by `MARKET_PROVIDER`. `None` disables `nft.marketInfo`.
+pub fn from_config(config: &MarketConfig) -> Result<Option<Arc<dyn MarketDataSource>>> {
+    match config.provider.as_str() {
+        "magic_eden" => Ok(Some(Arc::new(MagicEdenSource::new(
+            &config.magic_eden_api_url,
+        )))),
+        "tensor" => {
+            let api_key = config
+                .tensor_api_key
+                .as_ref()
+                .ok_or(AppError::InvalidParams(
+                    "TENSOR_API_KEY is required for the tensor market provider".into(),
+                ))?;
+            Ok(Some(Arc::new(TensorSource::new(
+                &config.tensor_api_url,
+                api_key,
+            ))))
+        }
+        "none" => Ok(None),
+        other => Err(AppError::InvalidParams(format!(
+            "Unknown MARKET_PROVIDER {other}, expected magic_eden, tensor or none"
+        ))),
+    }
+}
+
+pub async fn get_market_info(state: &AppState, canvas_id: Uuid) -> Result<MarketInfo> {
+    let Some(market_data) = state.market_data.as_ref() else {
+        return Err(AppError::InvalidParams(
+            "Marketplace data is not enabled".into(),
+        ));
+    };
+
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.state != CanvasState::Minted {
+        return Err(AppError::InvalidParams("Canvas is not minted".into()));
+    }
+
+    let mint_address = canvas
+        .mint_address
+        .ok_or(AppError::InvalidParams("Canvas has no mint address".into()))?;
+
+    let cache_key = CacheKey::market_info(&mint_address);
+    if let Some(cached) = state.cache.redis.get::<MarketInfo>(&cache_key).await? {
+        return Ok(cached);
+    }
+
+    let info = market_data.fetch(&mint_address).await?;
+
+    let ttl = Duration::from_secs(state.config.market.cache_ttl_secs);
+    state.cache.redis.set(&cache_key, &info, ttl).await?;
+
+    Ok(info)
+}
+
+fn sol_to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL).round() as u64
+}
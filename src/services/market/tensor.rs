@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::{
+    error::{AppError, Result},
+    services::market::{MarketDataSource, MarketInfo},
+};
+
+/// Listing and sale data from the Tensor API.
+pub struct TensorSource {
+    http: Client,
+    api_url: String,
+    api_key: String,
+}
+
+impl TensorSource {
+    pub fn new(api_url: &str, api_key: &str) -> Self {
+        Self {
+            http: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl MarketDataSource for TensorSource {
+    fn name(&self) -> &'static str {
+        "tensor"
+    }
+
+    async fn fetch(&self, mint_address: &str) -> Result<MarketInfo> {
+        let response = self
+            .http
+            .get(format!("{}/api/v1/mint", self.api_url))
+            .query(&[("mints", mint_address)])
+            .header("x-tensor-api-key", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| AppError::MarketplaceError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::MarketplaceError(format!(
+                "Tensor returned {}: {}",
+                status, body
+            )));
+        }
+
+        let mints: Vec<Value> = response
+            .json()
+            .await
+            .map_err(|e| AppError::MarketplaceError(e.to_string()))?;
+
+        let mut info = MarketInfo::unindexed(self.name(), mint_address);
+        let Some(mint) = mints
+            .iter()
+            .find(|mint| mint["mint"].as_str() == Some(mint_address))
+        else {
+            return Ok(info);
+        };
+
+        info.owner = mint["owner"].as_str().map(str::to_string);
+
+        let listing = &mint["listing"];
+        if listing.is_object() {
+            info.listed = true;
+            info.listed_price_lamports = lamports(&listing["price"]);
+            info.seller = listing["seller"].as_str().map(str::to_string);
+        }
+
+        let last_sale = &mint["lastSale"];
+        if last_sale.is_object() {
+            info.last_sale_price_lamports = lamports(&last_sale["price"]);
+            info.last_sale_at = last_sale["txAt"]
+                .as_i64()
+                .and_then(DateTime::<Utc>::from_timestamp_millis);
+        }
+
+        Ok(info)
+    }
+}
+
+/// Tensor reports lamport amounts as decimal strings.
+fn lamports(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(amount) => amount.parse().ok(),
+        other => other.as_u64(),
+    }
+}
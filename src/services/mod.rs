@@ -1,7 +1,11 @@
 pub mod auth;
 pub mod canvas;
+pub mod market;
 pub mod nft;
+pub mod outbox;
 pub mod pixel;
+pub mod price;
 pub mod solana;
+pub mod webhooks;
 
 const MESSAGE_VALIDITY_SECS: u64 = 300;
@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use chrono::Utc;
+use sea_orm::ActiveValue::Set;
+use solana_sdk::pubkey::Pubkey;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::{
+            entities::{
+                canvas::{self, CanvasState, NftStandard},
+                consumed_signature::SignatureOperation,
+            },
+            repositories::CanvasRepository,
+        },
+    },
+    services::{
+        nft::types::{BurnResult, BurnTransactionInfo},
+        outbox,
+        solana::{
+            client::{MPL_CORE_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID},
+            signatures,
+        },
+    },
+    ws::types::RoomCanvasUpdate,
+};
+
+/// Returns the accounts for burning the canvas NFT. The canvas is untouched until
+/// `confirm_burn` sees the burn land on-chain.
+pub async fn initiate_burn(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<BurnTransactionInfo> {
+    let canvas = find_burnable_canvas(state, canvas_id, user_id).await?;
+    let asset_address = asset_address(&canvas)?;
+    let solana_client = &state.solana_client;
+
+    let collection_mint = canvas
+        .collection_mint
+        .as_deref()
+        .and_then(|address| Pubkey::from_str(address).ok());
+
+    let (asset_program_id, metadata_pda, master_edition_pda, collection_metadata_pda) =
+        match canvas.nft_standard {
+            NftStandard::TokenMetadata => (
+                TOKEN_METADATA_PROGRAM_ID,
+                Some(
+                    solana_client
+                        .derive_metadata_pda(&asset_address)
+                        .0
+                        .to_string(),
+                ),
+                Some(
+                    solana_client
+                        .derive_master_edition_pda(&asset_address)
+                        .0
+                        .to_string(),
+                ),
+                collection_mint.map(|mint| solana_client.derive_metadata_pda(&mint).0.to_string()),
+            ),
+            NftStandard::Core => (MPL_CORE_PROGRAM_ID, None, None, None),
+        };
+
+    let blockhash = solana_client
+        .get_recent_blockhash()
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    Ok(BurnTransactionInfo {
+        canvas_id,
+        standard: canvas.nft_standard,
+        asset_program_id: asset_program_id.to_string(),
+        asset_address: asset_address.to_string(),
+        metadata_pda,
+        master_edition_pda,
+        collection_mint: collection_mint.map(|mint| mint.to_string()),
+        collection_metadata_pda,
+        blockhash: blockhash.to_string(),
+    })
+}
+
+/// Verifies the burn transaction, checks the asset accounts are closed and retires the canvas.
+pub async fn confirm_burn(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    signature: &str,
+) -> Result<BurnResult> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if canvas.state == CanvasState::Burned && canvas.burn_signature.as_deref() == Some(signature) {
+        return Ok(BurnResult {
+            canvas_id,
+            state: canvas.state,
+            burned_at: canvas.burned_at,
+        });
+    }
+
+    if canvas.state != CanvasState::Minted {
+        return Err(AppError::InvalidParams("Canvas is not minted".into()));
+    }
+
+    let asset_address = asset_address(&canvas)?;
+    let (asset_program_id, closed_account) = match canvas.nft_standard {
+        NftStandard::TokenMetadata => (
+            TOKEN_METADATA_PROGRAM_ID,
+            state.solana_client.derive_metadata_pda(&asset_address).0,
+        ),
+        NftStandard::Core => (MPL_CORE_PROGRAM_ID, asset_address),
+    };
+
+    let record =
+        signatures::operation_record(signature, SignatureOperation::NftBurn, canvas_id, user_id);
+    signatures::ensure_signature_unused(state, &record).await?;
+
+    let tx_valid = state
+        .solana_client
+        .verify_program_transaction(signature, &asset_program_id.to_string())
+        .await?;
+
+    if !tx_valid {
+        return Err(AppError::TransactionFailed(
+            "Transaction verification failed".into(),
+        ));
+    }
+
+    let still_open = state
+        .solana_client
+        .account_exists(&closed_account)
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+    if still_open {
+        return Err(AppError::TransactionFailed(
+            "Transaction did not burn the canvas NFT".into(),
+        ));
+    }
+
+    signatures::claim_signature(state, &record).await?;
+
+    let events = vec![outbox::room_event(
+        canvas_id,
+        RoomCanvasUpdate::Burned {
+            signature: signature.to_string(),
+        },
+    )?];
+
+    let canvas = CanvasRepository::update_canvas_state(
+        &state.db,
+        canvas_id,
+        CanvasState::Burned,
+        events,
+        |active| {
+            active.burn_signature = Set(Some(signature.to_string()));
+            active.burned_at = Set(Some(Utc::now()));
+            active.total_escrowed = Set(0);
+        },
+    )
+    .await?;
+
+    release_canvas(state, &canvas).await?;
+
+    Ok(BurnResult {
+        canvas_id,
+        state: canvas.state,
+        burned_at: canvas.burned_at,
+    })
+}
+
+/// Drops the locks and cached data that only make sense while the NFT exists.
+async fn release_canvas(state: &AppState, canvas: &canvas::Model) -> Result<()> {
+    let mut keys = vec![
+        CacheKey::canvas_lock(&canvas.id),
+        CacheKey::metadata_update(&canvas.id),
+    ];
+    if let Some(mint_address) = canvas.mint_address.as_deref() {
+        keys.push(CacheKey::market_info(mint_address));
+    }
+
+    state.cache.redis.del_many(&keys).await
+}
+
+async fn find_burnable_canvas(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<canvas::Model> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if canvas.state != CanvasState::Minted {
+        return Err(AppError::InvalidParams("Canvas is not minted".into()));
+    }
+
+    Ok(canvas)
+}
+
+fn asset_address(canvas: &canvas::Model) -> Result<Pubkey> {
+    canvas
+        .mint_address
+        .as_deref()
+        .and_then(|address| Pubkey::from_str(address).ok())
+        .ok_or(AppError::InvalidParams(
+            "Canvas has no valid mint address".into(),
+        ))
+}
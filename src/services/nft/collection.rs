@@ -0,0 +1,196 @@
+use std::str::FromStr;
+
+use sea_orm::ActiveValue::Set;
+use solana_sdk::pubkey::Pubkey;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    config::SolanaConfig,
+    error::{AppError, Result},
+    infrastructure::db::{
+        entities::canvas::{self, CanvasState, NftStandard},
+        repositories::CanvasRepository,
+    },
+    services::nft::types::{CollectionStatus, NftCollection},
+};
+
+/// Off-chain collection block for the metadata JSON. Minted canvases keep the
+/// collection they were minted into, others follow the configured one.
+pub fn metadata_collection(config: &SolanaConfig, canvas: &canvas::Model) -> Option<NftCollection> {
+    let in_collection = match canvas.state {
+        CanvasState::Minted => canvas.collection_mint.is_some(),
+        _ => config.collection_mint.is_some(),
+    };
+
+    in_collection.then(|| NftCollection {
+        name: config.collection_name.clone(),
+        family: config.collection_name.clone(),
+    })
+}
+
+pub async fn verify_collection(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<CollectionStatus> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if canvas.state != CanvasState::Minted {
+        return Err(AppError::InvalidParams("Canvas is not minted".into()));
+    }
+
+    if canvas.collection_mint.is_none() {
+        return Err(AppError::InvalidParams(
+            "Canvas was not minted into a collection".into(),
+        ));
+    }
+
+    let canvas = refresh_collection_status(state, canvas).await?;
+
+    Ok(CollectionStatus {
+        canvas_id,
+        collection_mint: canvas.collection_mint,
+        verified: canvas.collection_verified,
+    })
+}
+
+/// Reads the minted asset on-chain and records whether it belongs to the canvas's collection.
+pub(crate) async fn refresh_collection_status(
+    state: &AppState,
+    canvas: canvas::Model,
+) -> Result<canvas::Model> {
+    let (Some(collection_mint), Some(mint_address)) = (
+        canvas.collection_mint.as_deref(),
+        canvas.mint_address.as_deref(),
+    ) else {
+        return Ok(canvas);
+    };
+
+    let collection_mint = Pubkey::from_str(collection_mint)
+        .map_err(|_| AppError::InternalServerError("Invalid stored collection mint".into()))?;
+    let mint_address = Pubkey::from_str(mint_address)
+        .map_err(|_| AppError::InvalidParams("Invalid mint address".into()))?;
+
+    let account = match canvas.nft_standard {
+        NftStandard::TokenMetadata => state.solana_client.derive_metadata_pda(&mint_address).0,
+        NftStandard::Core => mint_address,
+    };
+    let data = state
+        .solana_client
+        .get_account_data(&account)
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    let onchain_collection = match canvas.nft_standard {
+        NftStandard::TokenMetadata => parse_metadata_collection(&data),
+        NftStandard::Core => parse_core_collection(&data),
+    };
+    let verified = onchain_collection == Some((collection_mint, true));
+
+    if verified == canvas.collection_verified {
+        return Ok(canvas);
+    }
+
+    let canvas = CanvasRepository::update_canvas(&state.db, canvas.id, |active| {
+        active.collection_verified = Set(verified);
+    })
+    .await?;
+
+    Ok(canvas)
+}
+
+struct AccountReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> AccountReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.take(32).map(|bytes| Pubkey::try_from(bytes).unwrap())
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        let len = self.u32()? as usize;
+        self.take(len).map(|_| ())
+    }
+
+    fn skip_option(&mut self, len: usize) -> Option<()> {
+        if self.u8()? == 1 {
+            self.take(len)?;
+        }
+        Some(())
+    }
+}
+
+/// Token Metadata `Metadata` account, borsh layout up to the `collection` field.
+fn parse_metadata_collection(data: &[u8]) -> Option<(Pubkey, bool)> {
+    let mut reader = AccountReader::new(data);
+
+    reader.take(1 + 32 + 32)?; // key, update authority, mint
+    reader.skip_string()?; // name
+    reader.skip_string()?; // symbol
+    reader.skip_string()?; // uri
+    reader.take(2)?; // seller fee basis points
+
+    if reader.u8()? == 1 {
+        let creators = reader.u32()? as usize;
+        reader.take(creators.checked_mul(34)?)?; // address, verified, share
+    }
+
+    reader.take(2)?; // primary sale happened, is mutable
+    reader.skip_option(1)?; // edition nonce
+    reader.skip_option(1)?; // token standard
+
+    if reader.u8()? != 1 {
+        return None;
+    }
+    let verified = reader.u8()? == 1;
+    let key = reader.pubkey()?;
+
+    Some((key, verified))
+}
+
+/// Core `AssetV1` account. Membership is set by the Core program itself, so it is always verified.
+fn parse_core_collection(data: &[u8]) -> Option<(Pubkey, bool)> {
+    const ASSET_V1_KEY: u8 = 1;
+    const UPDATE_AUTHORITY_COLLECTION: u8 = 2;
+
+    let mut reader = AccountReader::new(data);
+
+    if reader.u8()? != ASSET_V1_KEY {
+        return None;
+    }
+    reader.take(32)?; // owner
+
+    if reader.u8()? != UPDATE_AUTHORITY_COLLECTION {
+        return None;
+    }
+
+    Some((reader.pubkey()?, true))
+}
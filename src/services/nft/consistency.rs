@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use solana_sdk::pubkey::Pubkey;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{
+        entities::canvas,
+        repositories::{CanvasRepository, PixelRepository, UserRepository},
+    },
+    services::{
+        canvas::{account::unpack_colors, pack_pixels_to_colors},
+        nft::types::{ConsistencyReport, FieldMismatch, PixelMismatch},
+        solana::{self, accounts::CanvasAccount},
+    },
+};
+
+/// Compares the canvas PDA with the pixels and fields stored in the database.
+pub async fn verify_canvas_consistency(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<ConsistencyReport> {
+    if !CanvasRepository::is_canvas_collaborator(state.db.get_connection(), canvas_id, user_id)
+        .await?
+    {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    let stored_pda = canvas.canvas_pda.as_deref().ok_or(AppError::InvalidParams(
+        "Canvas not published on-chain".into(),
+    ))?;
+    let canvas_pda = Pubkey::from_str(stored_pda)
+        .map_err(|_| AppError::InternalServerError("Invalid stored canvas PDA".into()))?;
+
+    let account_data = state
+        .solana_client
+        .get_account_data(&canvas_pda)
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+    let account = CanvasAccount::decode(&account_data)?;
+
+    let (width, height) = (state.config.canvas.width, state.config.canvas.height);
+    let colors =
+        PixelRepository::find_canvas_colors(state.db.get_connection(), canvas_id, width, height)
+            .await?;
+    let database_colors = unpack_colors(&pack_pixels_to_colors(&colors, width, height));
+
+    let pixel_mismatches = diff_pixels(
+        &unpack_colors(&account.pixel_colors),
+        &database_colors,
+        width,
+        height,
+    );
+    let field_mismatches = diff_fields(state, &canvas, &canvas_pda, &account).await?;
+
+    Ok(ConsistencyReport {
+        canvas_id,
+        canvas_pda: stored_pda.to_string(),
+        consistent: pixel_mismatches.is_empty() && field_mismatches.is_empty(),
+        pixel_mismatches,
+        field_mismatches,
+    })
+}
+
+pub(crate) fn diff_pixels(
+    onchain: &[u8],
+    database: &[u8],
+    width: u8,
+    height: u8,
+) -> Vec<PixelMismatch> {
+    let total_pixels = width as usize * height as usize;
+
+    onchain
+        .iter()
+        .zip(database)
+        .take(total_pixels)
+        .enumerate()
+        .filter(|(_, (onchain, database))| onchain != database)
+        .map(|(index, (onchain, database))| PixelMismatch {
+            x: (index % width as usize) as u8,
+            y: (index / width as usize) as u8,
+            onchain_color: *onchain,
+            database_color: *database,
+        })
+        .collect()
+}
+
+pub(crate) async fn diff_fields(
+    state: &AppState,
+    canvas: &canvas::Model,
+    canvas_pda: &Pubkey,
+    account: &CanvasAccount,
+) -> Result<Vec<FieldMismatch>> {
+    let owner = UserRepository::find_user_by_id_including_deleted(
+        state.db.get_connection(),
+        canvas.owner_id,
+    )
+    .await?
+    .ok_or(AppError::UserNotFound)?;
+    let (expected_pda, _) = solana::canvas_program(state.solana_client.as_ref(), canvas)?
+        .derive_canvas_pda_from_uuid(&canvas.id);
+
+    let fields = [
+        (
+            "canvas_pda",
+            expected_pda.to_string(),
+            canvas_pda.to_string(),
+        ),
+        ("id", account.id.to_string(), canvas.id.to_string()),
+        ("owner", account.owner.to_string(), owner.wallet_address),
+        (
+            "width",
+            account.width.to_string(),
+            state.config.canvas.width.to_string(),
+        ),
+        (
+            "height",
+            account.height.to_string(),
+            state.config.canvas.height.to_string(),
+        ),
+        (
+            "total_escrowed",
+            account.total_escrow.to_string(),
+            canvas.total_escrowed.to_string(),
+        ),
+    ];
+
+    Ok(fields
+        .into_iter()
+        .filter(|(_, onchain, database)| onchain != database)
+        .map(|(field, onchain, database)| FieldMismatch {
+            field: field.to_string(),
+            onchain,
+            database,
+        })
+        .collect())
+}
@@ -0,0 +1,185 @@
+use crate::services::nft::types::CreatorOutput;
+
+/// Share the canvas owner always keeps when others have claimed pixels.
+const OWNER_BASE_SHARE: u8 = 10;
+const TOTAL_SHARES: u8 = 100;
+
+/// A creator candidate and the lamports they invested in the canvas.
+#[derive(Debug, Clone)]
+pub struct CreatorStake {
+    pub address: String,
+    pub amount_lamports: i64,
+}
+
+/// Splits the 100 creator shares between the owner and pixel claimers.
+///
+/// The owner keeps `OWNER_BASE_SHARE` and the rest is split by investment using
+/// the largest-remainder method, so shares always sum to exactly 100. Ties go to
+/// the larger investment, then the lower address, so the result is stable for the
+/// same input. The owner's own investment counts toward their share. Claimers who
+/// round to zero are left out, and the owner is always listed first.
+pub fn allocate_creator_shares(owner_address: &str, stakes: &[CreatorStake]) -> Vec<CreatorOutput> {
+    let stakes: Vec<&CreatorStake> = stakes
+        .iter()
+        .filter(|stake| stake.amount_lamports > 0)
+        .collect();
+    let total_lamports: u128 = stakes
+        .iter()
+        .map(|stake| stake.amount_lamports as u128)
+        .sum();
+
+    if total_lamports == 0 {
+        return vec![CreatorOutput {
+            address: owner_address.to_string(),
+            share: TOTAL_SHARES,
+        }];
+    }
+
+    let distributable = (TOTAL_SHARES - OWNER_BASE_SHARE) as u128;
+
+    // (stake, floor share, remainder numerator)
+    let mut allocations: Vec<(&CreatorStake, u8, u128)> = stakes
+        .iter()
+        .map(|stake| {
+            let scaled = stake.amount_lamports as u128 * distributable;
+            (
+                *stake,
+                (scaled / total_lamports) as u8,
+                scaled % total_lamports,
+            )
+        })
+        .collect();
+
+    let allocated: u8 = allocations.iter().map(|(_, share, _)| share).sum();
+    let leftover = (distributable as u8 - allocated) as usize;
+
+    allocations.sort_by(|a, b| {
+        b.2.cmp(&a.2)
+            .then(b.0.amount_lamports.cmp(&a.0.amount_lamports))
+            .then(a.0.address.cmp(&b.0.address))
+    });
+    for allocation in allocations.iter_mut().take(leftover) {
+        allocation.1 += 1;
+    }
+
+    let mut owner_share = OWNER_BASE_SHARE;
+    let mut creators = Vec::with_capacity(allocations.len() + 1);
+    for (stake, share, _) in allocations {
+        if stake.address == owner_address {
+            owner_share += share;
+        } else if share > 0 {
+            creators.push(CreatorOutput {
+                address: stake.address.clone(),
+                share,
+            });
+        }
+    }
+
+    creators.sort_by(|a, b| b.share.cmp(&a.share).then(a.address.cmp(&b.address)));
+    creators.insert(
+        0,
+        CreatorOutput {
+            address: owner_address.to_string(),
+            share: owner_share,
+        },
+    );
+
+    creators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: &str = "owner";
+
+    fn stake(address: &str, amount_lamports: i64) -> CreatorStake {
+        CreatorStake {
+            address: address.to_string(),
+            amount_lamports,
+        }
+    }
+
+    fn shares(creators: &[CreatorOutput]) -> Vec<(&str, u8)> {
+        creators
+            .iter()
+            .map(|creator| (creator.address.as_str(), creator.share))
+            .collect()
+    }
+
+    #[test]
+    fn shares_sum_to_100() {
+        let creators =
+            allocate_creator_shares(OWNER, &[stake("a", 1), stake("b", 2), stake("c", 4)]);
+
+        assert_eq!(
+            shares(&creators),
+            [(OWNER, 10), ("c", 51), ("b", 26), ("a", 13)]
+        );
+        let total: u32 = creators.iter().map(|creator| creator.share as u32).sum();
+        assert_eq!(total, 100);
+    }
+
+    #[test]
+    fn owner_only_canvas_gets_100() {
+        assert_eq!(shares(&allocate_creator_shares(OWNER, &[])), [(OWNER, 100)]);
+        assert_eq!(
+            shares(&allocate_creator_shares(OWNER, &[stake("a", 0)])),
+            [(OWNER, 100)]
+        );
+    }
+
+    #[test]
+    fn equal_remainders_go_to_the_larger_investment() {
+        // Both remainders are 6/14; one share is left over
+        let creators =
+            allocate_creator_shares(OWNER, &[stake("x", 1), stake("y", 8), stake("z", 5)]);
+
+        assert_eq!(
+            shares(&creators),
+            [(OWNER, 10), ("y", 52), ("z", 32), ("x", 6)]
+        );
+    }
+
+    #[test]
+    fn equal_investments_go_to_the_lower_address() {
+        // 90 / 7 leaves six shares for seven equal claimers
+        let stakes: Vec<CreatorStake> = ["g", "f", "e", "d", "c", "b", "a"]
+            .into_iter()
+            .map(|address| stake(address, 1))
+            .collect();
+        let creators = allocate_creator_shares(OWNER, &stakes);
+
+        assert_eq!(
+            shares(&creators),
+            [
+                (OWNER, 10),
+                ("a", 13),
+                ("b", 13),
+                ("c", 13),
+                ("d", 13),
+                ("e", 13),
+                ("f", 13),
+                ("g", 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn owner_stake_is_merged_into_base_share() {
+        let creators =
+            allocate_creator_shares(OWNER, &[stake("a", 1), stake(OWNER, 1), stake("b", 1)]);
+
+        assert_eq!(shares(&creators), [(OWNER, 40), ("a", 30), ("b", 30)]);
+    }
+
+    #[test]
+    fn zero_share_claimers_are_dropped() {
+        let creators = allocate_creator_shares(
+            OWNER,
+            &[stake("a", 1_000_000), stake("b", 1), stake("c", 0)],
+        );
+
+        assert_eq!(shares(&creators), [(OWNER, 10), ("a", 90)]);
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{
+        entities::{canvas::CanvasState, escrow_refund::RefundReason},
+        repositories::{CanvasRepository, EscrowRefundRepository, PixelRepository, UserRepository},
+    },
+    services::{
+        nft::types::{PayoutRecipient, PayoutStatus},
+        solana::escrow,
+    },
+};
+
+/// Splits the canvas escrow between its pixel owners once the mint confirms. Each owner is
+/// credited what they bid on the pixels they hold, and withdraws it with `solana.claimEscrow`.
+/// Safe to run again for the same mint.
+pub(crate) async fn distribute_mint_payouts(
+    state: &AppState,
+    canvas_id: Uuid,
+    mint_signature: &str,
+) -> Result<()> {
+    let shares = PixelRepository::sum_bids_by_owner(state.db.get_connection(), canvas_id).await?;
+
+    let credited = escrow::credit_refunds(
+        state,
+        canvas_id,
+        RefundReason::MintPayout,
+        mint_signature,
+        &shares,
+    )
+    .await?;
+
+    tracing::info!(canvas_id = %canvas_id, recipients = credited, "Mint payouts distributed");
+
+    Ok(())
+}
+
+pub async fn get_payout_status(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<PayoutStatus> {
+    if !CanvasRepository::is_canvas_collaborator(state.db.get_connection(), canvas_id, user_id)
+        .await?
+    {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    let mint_signature = match (canvas.state, canvas.mint_signature) {
+        (CanvasState::Minted | CanvasState::Burned, Some(signature)) => signature,
+        _ => return Err(AppError::InvalidParams("Canvas is not minted".into())),
+    };
+
+    let payouts = EscrowRefundRepository::find_by_source(
+        state.db.get_connection(),
+        canvas_id,
+        &mint_signature,
+    )
+    .await?;
+
+    let user_ids: Vec<Uuid> = payouts.iter().map(|payout| payout.user_id).collect();
+    let users: HashMap<Uuid, _> =
+        UserRepository::find_users_by_ids_including_deleted(state.db.get_connection(), &user_ids)
+            .await?
+            .into_iter()
+            .map(|user| (user.id, user))
+            .collect();
+
+    let recipients: Vec<PayoutRecipient> = payouts
+        .into_iter()
+        .map(|payout| {
+            let user = users.get(&payout.user_id);
+            PayoutRecipient {
+                user_id: payout.user_id,
+                wallet_address: user.map(|user| user.wallet_address.clone()),
+                username: user.and_then(|user| user.username.clone()),
+                amount_lamports: payout.amount_lamports,
+                claimed: payout.claimed_at.is_some(),
+                claim_signature: payout.claim_signature,
+                claimed_at: payout.claimed_at,
+            }
+        })
+        .collect();
+
+    let claimed: Vec<&PayoutRecipient> = recipients
+        .iter()
+        .filter(|recipient| recipient.claimed)
+        .collect();
+
+    Ok(PayoutStatus {
+        canvas_id,
+        mint_signature,
+        total_lamports: recipients
+            .iter()
+            .map(|recipient| recipient.amount_lamports)
+            .sum(),
+        claimed_lamports: claimed
+            .iter()
+            .map(|recipient| recipient.amount_lamports)
+            .sum(),
+        recipient_count: recipients.len(),
+        claimed_count: claimed.len(),
+        recipients,
+    })
+}
@@ -0,0 +1,230 @@
+use std::str::FromStr;
+
+use solana_sdk::pubkey::Pubkey;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{
+        entities::{
+            canvas::{self, CanvasState, NftStandard},
+            consumed_signature::SignatureOperation,
+        },
+        repositories::{CanvasRepository, EditionPrintRepository, PixelRepository},
+    },
+    services::{
+        nft::{
+            get_nft_metadata,
+            types::{Attribute, EditionMintInfo, EditionPrint, NftMetadata},
+        },
+        solana::{
+            self,
+            client::TOKEN_METADATA_PROGRAM_ID,
+            signatures,
+            transactions::{self, ExpectedInstruction, ExpectedOperation},
+        },
+    },
+};
+
+/// Reserves the caller's numbered print and returns the accounts for printing it from the
+/// canvas's master edition. Only collaborators who own a pixel on the canvas can print.
+pub async fn initiate_edition_mint(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<EditionMintInfo> {
+    let canvas = find_printable_canvas(state, canvas_id).await?;
+    let master_mint = master_mint(&canvas)?;
+
+    let owned_pixels = PixelRepository::count_pixels_owned_on_canvas(
+        state.db.get_connection(),
+        canvas_id,
+        user_id,
+    )
+    .await?;
+    if owned_pixels == 0 {
+        return Err(AppError::NotPixelContributor);
+    }
+
+    let max_editions = i32::try_from(state.config.canvas.max_edition_prints).unwrap_or(i32::MAX);
+    let edition =
+        EditionPrintRepository::reserve_edition(&state.db, canvas_id, user_id, max_editions)
+            .await?;
+
+    if edition.minted_at.is_some() {
+        return Err(AppError::InvalidParams(
+            "Your edition print is already minted".into(),
+        ));
+    }
+
+    let edition_number = edition.edition_number as u64;
+    let solana_client = &state.solana_client;
+    let program = solana::canvas_program(solana_client.as_ref(), &canvas)?;
+    let (edition_mint, _) = program.derive_edition_mint_pda(&canvas_id, edition_number);
+    let (edition_marker, _) = solana_client.derive_edition_marker_pda(&master_mint, edition_number);
+
+    let blockhash = solana_client
+        .get_recent_blockhash()
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    Ok(EditionMintInfo {
+        canvas_id,
+        edition_number: edition.edition_number,
+        max_editions,
+        program_id: program.program_id.to_string(),
+        asset_program_id: TOKEN_METADATA_PROGRAM_ID.to_string(),
+        master_mint: master_mint.to_string(),
+        master_metadata_pda: solana_client
+            .derive_metadata_pda(&master_mint)
+            .0
+            .to_string(),
+        master_edition_pda: solana_client
+            .derive_master_edition_pda(&master_mint)
+            .0
+            .to_string(),
+        edition_mint: edition_mint.to_string(),
+        edition_metadata_pda: solana_client
+            .derive_metadata_pda(&edition_mint)
+            .0
+            .to_string(),
+        edition_pda: solana_client
+            .derive_master_edition_pda(&edition_mint)
+            .0
+            .to_string(),
+        edition_marker_pda: edition_marker.to_string(),
+        metadata_uri: edition_metadata_url(state, canvas_id, edition.edition_number),
+        blockhash: blockhash.to_string(),
+    })
+}
+
+pub async fn confirm_edition_mint(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    signature: &str,
+) -> Result<EditionPrint> {
+    let edition =
+        EditionPrintRepository::find_edition_by_user(state.db.get_connection(), canvas_id, user_id)
+            .await?
+            .ok_or(AppError::InvalidParams(
+                "No edition print reserved for this canvas".into(),
+            ))?;
+
+    if edition.signature.as_deref() == Some(signature) {
+        return Ok(EditionPrint::from(edition));
+    }
+    if edition.minted_at.is_some() {
+        return Err(AppError::InvalidParams(
+            "Your edition print is already minted".into(),
+        ));
+    }
+
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+    let program = solana::canvas_program(state.solana_client.as_ref(), &canvas)?;
+
+    let record = signatures::operation_record(
+        signature,
+        SignatureOperation::EditionMint,
+        canvas_id,
+        user_id,
+    );
+    signatures::ensure_signature_unused(state, &record).await?;
+
+    // The print mint is a program PDA, so its address follows from the edition number.
+    let edition_number = edition.edition_number as u64;
+    let (edition_mint, _) = program.derive_edition_mint_pda(&canvas_id, edition_number);
+
+    let expected = ExpectedInstruction {
+        program,
+        signer: transactions::user_wallet(state, user_id).await?,
+        canvas_id,
+        operation: ExpectedOperation::EditionMint {
+            edition_mint,
+            edition_number,
+        },
+    };
+    let tx_valid = state
+        .solana_client
+        .verify_program_instruction(signature, &expected)
+        .await?;
+
+    if !tx_valid {
+        return Err(AppError::TransactionFailed(
+            "Transaction verification failed".into(),
+        ));
+    }
+
+    signatures::claim_signature(state, &record).await?;
+
+    let edition = EditionPrintRepository::mark_edition_minted(
+        state.db.get_connection(),
+        edition,
+        &edition_mint.to_string(),
+        signature,
+    )
+    .await?;
+
+    Ok(EditionPrint::from(edition))
+}
+
+/// Metadata of the master canvas NFT, renamed and tagged with the edition number.
+pub async fn get_edition_metadata(
+    state: &AppState,
+    canvas_id: Uuid,
+    edition_number: i32,
+) -> Result<NftMetadata> {
+    let edition =
+        EditionPrintRepository::find_edition(state.db.get_connection(), canvas_id, edition_number)
+            .await?
+            .filter(|edition| edition.minted_at.is_some())
+            .ok_or(AppError::InvalidParams("Edition print not found".into()))?;
+
+    let mut metadata = get_nft_metadata(state, canvas_id).await?;
+    metadata.name = format!("{} #{}", metadata.name, edition.edition_number);
+    metadata.attributes.push(Attribute {
+        trait_type: "Edition".into(),
+        value: edition.edition_number.to_string(),
+    });
+
+    Ok(metadata)
+}
+
+fn edition_metadata_url(state: &AppState, canvas_id: Uuid, edition_number: i32) -> String {
+    format!(
+        "{}/nft/{}/editions/{}/metadata.json",
+        state.config.server.server_public_url, canvas_id, edition_number
+    )
+}
+
+async fn find_printable_canvas(state: &AppState, canvas_id: Uuid) -> Result<canvas::Model> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.state != CanvasState::Minted {
+        return Err(AppError::InvalidParams("Canvas is not minted".into()));
+    }
+
+    // Prints come from the Token Metadata master edition.
+    if canvas.nft_standard != NftStandard::TokenMetadata {
+        return Err(AppError::InvalidParams(
+            "Edition prints require a Token Metadata mint".into(),
+        ));
+    }
+
+    Ok(canvas)
+}
+
+fn master_mint(canvas: &canvas::Model) -> Result<Pubkey> {
+    canvas
+        .mint_address
+        .as_deref()
+        .and_then(|address| Pubkey::from_str(address).ok())
+        .ok_or(AppError::InvalidParams(
+            "Canvas has no valid mint address".into(),
+        ))
+}
@@ -3,41 +3,179 @@ use std::io::Cursor;
 use png::{BitDepth, ColorType, Encoder};
 
 use crate::{
+    config::ImageConfig,
     error::{AppError, Result},
     infrastructure::db::entities::pixel::Model as Pixel,
+    services::canvas::{
+        account::unpack_colors,
+        palette::{Rgb, color_to_rgb, format_hex_color, parse_hex_color},
+    },
 };
 
-pub fn generate_png(pixels: &[Pixel]) -> Result<Vec<u8>> {
-    let mut canvas_data = vec![(255u8, 255u8, 255u8); 1024];
+/// Side length of the canvas in pixels.
+const CANVAS_SIDE: u32 = 32;
+const CANVAS_PIXELS: usize = (CANVAS_SIDE * CANVAS_SIDE) as usize;
+/// Default upscale for stills (512x512).
+pub const DEFAULT_IMAGE_SCALE: u32 = 16;
+/// Largest upscale accepted for stills (1024x1024).
+pub const MAX_IMAGE_SCALE: u32 = 32;
+/// Output sizes accepted by the image route's `size` parameter.
+pub const IMAGE_SIZES: [u32; 3] = [64, 512, 1024];
+/// Below this upscale grid lines would cover most of each pixel, so they are skipped.
+const MIN_GRID_SCALE: u32 = 4;
 
+const WHITE: Rgb = (0xff, 0xff, 0xff);
+const BLACK: Rgb = (0x00, 0x00, 0x00);
+
+/// Upscale factor for a requested output size, if it is one of `IMAGE_SIZES`.
+pub fn scale_for_size(size: u32) -> Option<u32> {
+    IMAGE_SIZES.contains(&size).then_some(size / CANVAS_SIDE)
+}
+
+/// How a still is rendered. `Default` is the canonical render recorded on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub scale: u32,
+    pub grid_lines: bool,
+    pub grid_color: Rgb,
+    /// Fill for pixels nobody has painted.
+    pub background: Rgb,
+    /// Leaves unpainted pixels fully transparent instead of filling them with `background`.
+    pub transparent_unset: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            scale: DEFAULT_IMAGE_SCALE,
+            grid_lines: false,
+            grid_color: BLACK,
+            background: WHITE,
+            transparent_unset: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn from_config(config: &ImageConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            scale: config.scale.clamp(1, MAX_IMAGE_SCALE),
+            grid_lines: config.grid_lines,
+            grid_color: parse_hex_color(&config.grid_color).unwrap_or(defaults.grid_color),
+            background: parse_hex_color(&config.background).unwrap_or(defaults.background),
+            transparent_unset: config.transparent_unset,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Stable identifier of these options, for caching rendered variants.
+    pub fn cache_tag(&self) -> String {
+        let grid = if self.grid_lines {
+            format_hex_color(self.grid_color)
+        } else {
+            "none".into()
+        };
+        let background = if self.transparent_unset {
+            "transparent".into()
+        } else {
+            format_hex_color(self.background)
+        };
+        format!("{}:{}:{}", self.scale, grid, background)
+    }
+}
+
+/// Marks which of the 32x32 cells have a pixel row, for telling painted cells from blank ones.
+pub fn painted_cells(pixels: &[Pixel]) -> Vec<bool> {
+    let mut painted = vec![false; CANVAS_PIXELS];
     for pixel in pixels {
-        let index = (pixel.y as usize) * 32 + (pixel.x as usize);
-        if index < 1024 {
-            canvas_data[index] = convert_color_index_to_rgb(pixel.color as u8);
+        let index = (pixel.y as usize) * (CANVAS_SIDE as usize) + (pixel.x as usize);
+        if let Some(cell) = painted.get_mut(index) {
+            *cell = true;
         }
     }
+    painted
+}
+
+/// Renders pixel colors, one byte each in row order.
+pub fn generate_png(
+    pixel_colors: &[u8],
+    palette: &[Rgb],
+    options: &RenderOptions,
+) -> Result<Vec<u8>> {
+    let mut cells = vec![None; CANVAS_PIXELS];
+
+    for (cell, &color) in cells.iter_mut().zip(pixel_colors) {
+        *cell = Some(color_to_rgb(palette, color));
+    }
+
+    encode_png(&cells, options)
+}
+
+/// Renders packed on-chain colors. The account cannot tell blank cells from painted ones,
+/// so `painted` supplies that when known; otherwise every cell is drawn in its color.
+pub fn generate_png_from_colors(
+    pixel_colors: &[u8],
+    palette: &[Rgb],
+    painted: Option<&[bool]>,
+    options: &RenderOptions,
+) -> Result<Vec<u8>> {
+    let mut cells = vec![None; CANVAS_PIXELS];
+
+    for (index, color) in unpack_colors(pixel_colors)
+        .into_iter()
+        .enumerate()
+        .take(CANVAS_PIXELS)
+    {
+        let is_painted = painted.is_none_or(|painted| painted.get(index).copied().unwrap_or(false));
+        if is_painted {
+            cells[index] = Some(color_to_rgb(palette, color));
+        }
+    }
+
+    encode_png(&cells, options)
+}
 
-    // Scales 16x (512x512).
-    let scale = 16u32;
-    let width = 32 * scale;
-    let height = 32 * scale;
+/// Nearest-neighbor upscales the 32x32 canvas and encodes it as PNG. `None` cells are unpainted.
+fn encode_png(cells: &[Option<Rgb>], options: &RenderOptions) -> Result<Vec<u8>> {
+    let scale = options.scale.clamp(1, MAX_IMAGE_SCALE);
+    let width = CANVAS_SIDE * scale;
+    let height = CANVAS_SIDE * scale;
+    let draw_grid = options.grid_lines && scale >= MIN_GRID_SCALE;
+    let (color_type, channels) = if options.transparent_unset {
+        (ColorType::Rgba, 4)
+    } else {
+        (ColorType::Rgb, 3)
+    };
 
-    let mut scaled_data = Vec::with_capacity((width * height) as usize * 3);
+    let mut scaled_data = Vec::with_capacity((width * height) as usize * channels);
     for y in 0..height {
         for x in 0..width {
-            let src_x = (x / scale) as usize;
-            let src_y = (y / scale) as usize;
-            let (r, g, b) = canvas_data[src_y * 32 + src_x];
-            scaled_data.push(r);
-            scaled_data.push(g);
-            scaled_data.push(b);
+            let on_grid = draw_grid
+                && (x % scale == 0 || y % scale == 0 || x == width - 1 || y == height - 1);
+            let cell = cells[((y / scale) * CANVAS_SIDE + x / scale) as usize];
+
+            let (rgb, alpha) = match (on_grid, cell) {
+                (true, _) => (options.grid_color, u8::MAX),
+                (false, Some(color)) => (color, u8::MAX),
+                (false, None) if options.transparent_unset => (WHITE, 0),
+                (false, None) => (options.background, u8::MAX),
+            };
+
+            scaled_data.extend_from_slice(&[rgb.0, rgb.1, rgb.2]);
+            if options.transparent_unset {
+                scaled_data.push(alpha);
+            }
         }
     }
 
     let mut png_data = Vec::new();
     {
         let mut encoder = Encoder::new(Cursor::new(&mut png_data), width, height);
-        encoder.set_color(ColorType::Rgb);
+        encoder.set_color(color_type);
         encoder.set_depth(BitDepth::Eight);
         let mut writer = encoder
             .write_header()
@@ -50,147 +188,126 @@ pub fn generate_png(pixels: &[Pixel]) -> Result<Vec<u8>> {
     Ok(png_data)
 }
 
-pub fn generate_png_from_colors(pixel_colors: &[u8]) -> Result<Vec<u8>> {
-    let mut canvas_data = vec![(255u8, 255u8, 255u8); 1024];
+/// Animation frames are upscaled less than stills to keep file sizes down.
+const ANIMATION_SCALE: u32 = 8;
+const ANIMATION_FRAME_DELAY_MS: u16 = 100;
+/// The finished canvas is held before the animation loops.
+const ANIMATION_FINAL_FRAME_DELAY_MS: u16 = 2000;
 
-    // Unpack 6-bit colors: 4 pixels/3 bytes
-    for group in 0..256 {
-        let base_byte = group * 3;
-        let base_pixel = group * 4;
-
-        if base_byte + 2 < pixel_colors.len() {
-            let b0 = pixel_colors[base_byte];
-            let b1 = pixel_colors[base_byte + 1];
-            let b2 = pixel_colors[base_byte + 2];
+/// Indexed palette for animations. The extra last entry stands in for colors
+/// outside the palette, matching `color_to_rgb`.
+fn animation_palette(palette: &[Rgb]) -> (Vec<u8>, u8) {
+    let fallback_index = palette.len().min(255) as u8;
+    let mut rgb = Vec::with_capacity((palette.len() + 1) * 3);
+    for &(r, g, b) in palette.iter().take(fallback_index as usize) {
+        rgb.extend_from_slice(&[r, g, b]);
+    }
+    let (r, g, b) = color_to_rgb(palette, u8::MAX);
+    rgb.extend_from_slice(&[r, g, b]);
+    (rgb, fallback_index)
+}
 
-            // Unpack: c0 = b0[7:2], c1 = b0[1:0]b1[7:4], c2 = b1[3:0]b2[7:6], c3 = b2[5:0]
-            let c0 = (b0 >> 2) & 0x3F;
-            let c1 = ((b0 & 0x03) << 4) | ((b1 >> 4) & 0x0F);
-            let c2 = ((b1 & 0x0F) << 2) | ((b2 >> 6) & 0x03);
-            let c3 = b2 & 0x3F;
+fn scale_frame(colors: &[u8], width: u32, height: u32, fallback_index: u8) -> Vec<u8> {
+    let scaled_width = width * ANIMATION_SCALE;
+    let scaled_height = height * ANIMATION_SCALE;
 
-            if base_pixel < 1024 {
-                canvas_data[base_pixel] = convert_color_index_to_rgb(c0);
-            }
-            if base_pixel + 1 < 1024 {
-                canvas_data[base_pixel + 1] = convert_color_index_to_rgb(c1);
-            }
-            if base_pixel + 2 < 1024 {
-                canvas_data[base_pixel + 2] = convert_color_index_to_rgb(c2);
-            }
-            if base_pixel + 3 < 1024 {
-                canvas_data[base_pixel + 3] = convert_color_index_to_rgb(c3);
-            }
+    let mut scaled = Vec::with_capacity((scaled_width * scaled_height) as usize);
+    for y in 0..scaled_height {
+        for x in 0..scaled_width {
+            let index = ((y / ANIMATION_SCALE) * width + (x / ANIMATION_SCALE)) as usize;
+            let color = colors.get(index).copied().unwrap_or(fallback_index);
+            scaled.push(color.min(fallback_index));
         }
     }
+    scaled
+}
+
+fn frame_delay_ms(index: usize, frame_count: usize) -> u16 {
+    if index + 1 == frame_count {
+        ANIMATION_FINAL_FRAME_DELAY_MS
+    } else {
+        ANIMATION_FRAME_DELAY_MS
+    }
+}
 
-    // 16x scale for better visibility (512x512 output)
-    let scale = 16u32;
-    let width = 32 * scale;
-    let height = 32 * scale;
+/// Encodes row-major color index frames as a looping GIF, one at a time as `frames`
+/// yields them. `frame_count` is how many it yields.
+pub fn generate_timelapse_gif(
+    frames: impl IntoIterator<Item = Vec<u8>>,
+    frame_count: usize,
+    width: u32,
+    height: u32,
+    palette: &[Rgb],
+) -> Result<Vec<u8>> {
+    let (gif_palette, fallback_index) = animation_palette(palette);
+    let scaled_width = (width * ANIMATION_SCALE) as u16;
+    let scaled_height = (height * ANIMATION_SCALE) as u16;
 
-    let mut scaled_data = Vec::with_capacity((width * height) as usize * 3);
-    for y in 0..height {
-        for x in 0..width {
-            let src_x = (x / scale) as usize;
-            let src_y = (y / scale) as usize;
-            let (r, g, b) = canvas_data[src_y * 32 + src_x];
-            scaled_data.push(r);
-            scaled_data.push(g);
-            scaled_data.push(b);
+    let gif_error =
+        |e: gif::EncodingError| AppError::InternalServerError(format!("GIF error: {}", e));
+
+    let mut gif_data = Vec::new();
+    {
+        let mut encoder =
+            gif::Encoder::new(&mut gif_data, scaled_width, scaled_height, &gif_palette)
+                .map_err(gif_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(gif_error)?;
+
+        for (index, colors) in frames.into_iter().enumerate() {
+            let mut frame = gif::Frame::from_indexed_pixels(
+                scaled_width,
+                scaled_height,
+                scale_frame(&colors, width, height, fallback_index),
+                None,
+            );
+            // GIF delays are in hundredths of a second.
+            frame.delay = frame_delay_ms(index, frame_count) / 10;
+            encoder.write_frame(&frame).map_err(gif_error)?;
         }
     }
 
+    Ok(gif_data)
+}
+
+/// Encodes row-major color index frames as a looping APNG, one at a time as `frames`
+/// yields them. `frame_count` is how many it yields.
+pub fn generate_timelapse_apng(
+    frames: impl IntoIterator<Item = Vec<u8>>,
+    frame_count: usize,
+    width: u32,
+    height: u32,
+    palette: &[Rgb],
+) -> Result<Vec<u8>> {
+    let (png_palette, fallback_index) = animation_palette(palette);
+    let scaled_width = width * ANIMATION_SCALE;
+    let scaled_height = height * ANIMATION_SCALE;
+
+    let png_error =
+        |e: png::EncodingError| AppError::InternalServerError(format!("APNG error: {}", e));
+
     let mut png_data = Vec::new();
     {
-        let mut encoder = png::Encoder::new(Cursor::new(&mut png_data), width, height);
-        encoder.set_color(png::ColorType::Rgb);
-        encoder.set_depth(png::BitDepth::Eight);
-        let mut writer = encoder
-            .write_header()
-            .map_err(|e| AppError::InternalServerError(format!("PNG header error: {}", e)))?;
-        writer
-            .write_image_data(&scaled_data)
-            .map_err(|e| AppError::InternalServerError(format!("PNG write error: {}", e)))?;
+        let mut encoder = Encoder::new(Cursor::new(&mut png_data), scaled_width, scaled_height);
+        encoder.set_color(ColorType::Indexed);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.set_palette(png_palette);
+        encoder
+            .set_animated(frame_count as u32, 0)
+            .map_err(png_error)?;
+
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        for (index, colors) in frames.into_iter().enumerate() {
+            writer
+                .set_frame_delay(frame_delay_ms(index, frame_count), 1000)
+                .map_err(png_error)?;
+            writer
+                .write_image_data(&scale_frame(&colors, width, height, fallback_index))
+                .map_err(png_error)?;
+        }
+        writer.finish().map_err(png_error)?;
     }
 
     Ok(png_data)
 }
-
-fn convert_color_index_to_rgb(index: u8) -> (u8, u8, u8) {
-    match index {
-        // Row 1: Grayscale
-        0 => (0x00, 0x00, 0x00), // #000000
-        1 => (0x1a, 0x1a, 0x1a), // #1a1a1a
-        2 => (0x33, 0x33, 0x33), // #333333
-        3 => (0x4d, 0x4d, 0x4d), // #4d4d4d
-        4 => (0x66, 0x66, 0x66), // #666666
-        5 => (0x80, 0x80, 0x80), // #808080
-        6 => (0x99, 0x99, 0x99), // #999999
-        7 => (0xb3, 0xb3, 0xb3), // #b3b3b3
-        // Row 2: More grayscale + basics
-        8 => (0xcc, 0xcc, 0xcc),  // #cccccc
-        9 => (0xe6, 0xe6, 0xe6),  // #e6e6e6
-        10 => (0xff, 0xff, 0xff), // #ffffff
-        11 => (0xa9, 0x38, 0x38), // #A93838
-        12 => (0xf5, 0xf5, 0xdc), // #F5F5DC
-        13 => (0x8b, 0x00, 0x00), // #8B0000
-        14 => (0xdc, 0x14, 0x3c), // #DC143C
-        15 => (0xff, 0x63, 0x47), // #FF6347
-        // Row 3: Reds to Oranges
-        16 => (0xff, 0x45, 0x00), // #FF4500
-        17 => (0xff, 0x8c, 0x00), // #FF8C00
-        18 => (0xff, 0xa5, 0x00), // #FFA500
-        19 => (0xff, 0xd7, 0x00), // #FFD700
-        20 => (0xff, 0xff, 0x00), // #FFFF00
-        21 => (0xad, 0xff, 0x2f), // #ADFF2F
-        22 => (0x7f, 0xff, 0x00), // #7FFF00
-        23 => (0x00, 0xff, 0x00), // #00FF00 (Green!)
-        // Row 4: Greens
-        24 => (0x32, 0xcd, 0x32), // #32CD32
-        25 => (0x22, 0x8b, 0x22), // #228B22
-        26 => (0x00, 0x64, 0x00), // #006400
-        27 => (0x00, 0x8b, 0x8b), // #008B8B
-        28 => (0x20, 0xb2, 0xaa), // #20B2AA
-        29 => (0x00, 0xce, 0xd1), // #00CED1
-        30 => (0x00, 0xff, 0xff), // #00FFFF
-        31 => (0x00, 0xbf, 0xff), // #00BFFF
-        // Row 5: Blues
-        32 => (0x1e, 0x90, 0xff), // #1E90FF
-        33 => (0x00, 0x00, 0xff), // #0000FF (Blue!)
-        34 => (0x00, 0x00, 0xcd), // #0000CD
-        35 => (0x00, 0x00, 0x8b), // #00008B
-        36 => (0x19, 0x19, 0x70), // #191970
-        37 => (0x4b, 0x00, 0x82), // #4B0082
-        38 => (0x8b, 0x00, 0x8b), // #8B008B
-        39 => (0x94, 0x00, 0xd3), // #9400D3
-        // Row 6: Purples to Pinks
-        40 => (0x99, 0x32, 0xcc), // #9932CC
-        41 => (0xba, 0x55, 0xd3), // #BA55D3
-        42 => (0xda, 0x70, 0xd6), // #DA70D6
-        43 => (0xff, 0x00, 0xff), // #FF00FF
-        44 => (0xff, 0x69, 0xb4), // #FF69B4
-        45 => (0xff, 0x14, 0x93), // #FF1493
-        46 => (0xc7, 0x15, 0x85), // #C71585
-        47 => (0xdb, 0x70, 0x93), // #DB7093
-        // Row 7: Browns and Earth tones
-        48 => (0x8b, 0x45, 0x13), // #8B4513
-        49 => (0xa0, 0x52, 0x2d), // #A0522D
-        50 => (0xd2, 0x69, 0x1e), // #D2691E
-        51 => (0xcd, 0x85, 0x3f), // #CD853F
-        52 => (0xde, 0xb8, 0x87), // #DEB887
-        53 => (0xf5, 0xde, 0xb3), // #F5DEB3
-        54 => (0xfa, 0xeb, 0xd7), // #FAEBD7
-        55 => (0xff, 0xe4, 0xc4), // #FFE4C4
-        // Row 8: More earth + pastels
-        56 => (0xff, 0xda, 0xb9), // #FFDAB9
-        57 => (0xff, 0xe4, 0xe1), // #FFE4E1
-        58 => (0xff, 0xf0, 0xf5), // #FFF0F5
-        59 => (0xe6, 0xe6, 0xfa), // #E6E6FA
-        60 => (0xd8, 0xbf, 0xd8), // #D8BFD8
-        61 => (0xdd, 0xa0, 0xdd), // #DDA0DD
-        62 => (0xee, 0x82, 0xee), // #EE82EE
-        63 => (0xff, 0xff, 0xe0), // #FFFFE0
-        _ => (0x80, 0x80, 0x80),  // Fallback gray
-    }
-}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{entities::canvas, repositories::CanvasRepository},
+    services::nft::types::{CanvasLineage, LineageNode},
+};
+
+/// Remix chains deeper than this are cut off in both directions.
+const MAX_LINEAGE_DEPTH: u32 = 16;
+
+/// Mint address of the canvas this one remixes, for the provenance attribute and the
+/// on-chain pointer returned by `nft.mint`.
+pub(crate) async fn parent_mint_address(
+    state: &AppState,
+    canvas: &canvas::Model,
+) -> Result<Option<String>> {
+    let Some(parent_id) = canvas.parent_canvas_id else {
+        return Ok(None);
+    };
+
+    let parent = CanvasRepository::find_canvas_by_id(state.db.get_connection(), parent_id).await?;
+    Ok(parent.and_then(|parent| parent.mint_address))
+}
+
+/// Ancestors from the nearest parent up to the original canvas, and every remix below it.
+pub async fn get_lineage(state: &AppState, canvas_id: Uuid) -> Result<CanvasLineage> {
+    let conn = state.db.get_connection();
+    let canvas = CanvasRepository::find_canvas_by_id(conn, canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    let mut seen = HashSet::from([canvas.id]);
+
+    let mut ancestors = Vec::new();
+    let mut next_parent = canvas.parent_canvas_id;
+    while let Some(parent_id) = next_parent {
+        let depth = ancestors.len() as u32 + 1;
+        if depth > MAX_LINEAGE_DEPTH || !seen.insert(parent_id) {
+            break;
+        }
+        let Some(parent) = CanvasRepository::find_canvas_by_id(conn, parent_id).await? else {
+            break;
+        };
+        next_parent = parent.parent_canvas_id;
+        ancestors.push(LineageNode::new(parent, depth));
+    }
+
+    let mut descendants = Vec::new();
+    let mut frontier = vec![canvas.id];
+    for depth in 1..=MAX_LINEAGE_DEPTH {
+        if frontier.is_empty() {
+            break;
+        }
+        let children = CanvasRepository::find_child_canvases(conn, frontier).await?;
+        frontier = Vec::new();
+        for child in children {
+            if seen.insert(child.id) {
+                frontier.push(child.id);
+                descendants.push(LineageNode::new(child, depth));
+            }
+        }
+    }
+
+    Ok(CanvasLineage {
+        canvas: LineageNode::new(canvas, 0),
+        ancestors,
+        descendants,
+    })
+}
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::cache::keys::CacheKey,
+    ws::types::RoomCanvasUpdate,
+};
+
+/// A promoted canvas must call `nft.mint` within this window or its slot goes to the next one.
+const SLOT_CLAIM_SECS: i64 = 60;
+/// Queued canvases whose owner never comes back are dropped after this long.
+const QUEUED_MINT_TTL_SECS: i64 = 30 * 60;
+/// Keeps the queue key around between mints.
+const QUEUE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const QUEUE_LOCK_TTL: Duration = Duration::from_secs(5);
+const QUEUE_LOCK_RETRIES: u32 = 50;
+const QUEUE_LOCK_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MintSlot {
+    canvas_id: Uuid,
+    owner_id: Uuid,
+    since: DateTime<Utc>,
+    /// Set once the owner's `nft.mint` call takes the slot.
+    claimed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QueuedMint {
+    canvas_id: Uuid,
+    owner_id: Uuid,
+    queued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MintQueue {
+    slots: Vec<MintSlot>,
+    waiting: Vec<QueuedMint>,
+}
+
+impl MintQueue {
+    fn prune(&mut self, now: DateTime<Utc>, slot_ttl: chrono::Duration) {
+        self.slots.retain(|slot| {
+            let ttl = if slot.claimed {
+                slot_ttl
+            } else {
+                chrono::Duration::seconds(SLOT_CLAIM_SECS)
+            };
+            now - slot.since <= ttl
+        });
+        self.waiting.retain(|queued| {
+            now - queued.queued_at <= chrono::Duration::seconds(QUEUED_MINT_TTL_SECS)
+        });
+    }
+
+    /// Moves waiting canvases into free slots in queue order, at most one per owner.
+    fn promote(&mut self, now: DateTime<Utc>, max_active: usize) -> Vec<Uuid> {
+        let mut promoted = Vec::new();
+        let mut index = 0;
+
+        while index < self.waiting.len() && self.slots.len() < max_active {
+            let owner_id = self.waiting[index].owner_id;
+            if self.slots.iter().any(|slot| slot.owner_id == owner_id) {
+                index += 1;
+                continue;
+            }
+
+            let queued = self.waiting.remove(index);
+            promoted.push(queued.canvas_id);
+            self.slots.push(MintSlot {
+                canvas_id: queued.canvas_id,
+                owner_id: queued.owner_id,
+                since: now,
+                claimed: false,
+            });
+        }
+
+        promoted
+    }
+
+    fn position(&self, canvas_id: Uuid) -> Option<u64> {
+        self.waiting
+            .iter()
+            .position(|queued| queued.canvas_id == canvas_id)
+            .map(|index| index as u64 + 1)
+    }
+}
+
+/// Result of asking for a mint slot.
+pub enum MintSlotStatus {
+    Acquired,
+    Queued { position: u64 },
+}
+
+/// Takes a mint slot for the canvas, or queues it behind earlier mints. Only one canvas per
+/// owner and `MAX_CONCURRENT_MINTS` overall can hold a slot.
+pub async fn acquire_mint_slot(
+    state: &AppState,
+    canvas_id: Uuid,
+    owner_id: Uuid,
+) -> Result<MintSlotStatus> {
+    let (status, promoted, waiting) = with_queue(state, |queue, now| {
+        if !queue.slots.iter().any(|slot| slot.canvas_id == canvas_id)
+            && queue.position(canvas_id).is_none()
+        {
+            queue.waiting.push(QueuedMint {
+                canvas_id,
+                owner_id,
+                queued_at: now,
+            });
+        }
+
+        let promoted = promote_queue(state, queue, now);
+
+        let status = match queue
+            .slots
+            .iter_mut()
+            .find(|slot| slot.canvas_id == canvas_id)
+        {
+            Some(slot) => {
+                slot.claimed = true;
+                slot.since = now;
+                MintSlotStatus::Acquired
+            }
+            None => MintSlotStatus::Queued {
+                position: queue.position(canvas_id).unwrap_or(1),
+            },
+        };
+
+        (status, promoted, waiting_positions(queue))
+    })
+    .await?;
+
+    let promoted = promoted
+        .into_iter()
+        .filter(|promoted_id| *promoted_id != canvas_id)
+        .collect();
+    notify(state, promoted, waiting).await;
+
+    Ok(status)
+}
+
+/// Frees the canvas's slot or queue entry and hands free slots to the next canvases.
+pub async fn release_mint_slot(state: &AppState, canvas_id: Uuid) -> Result<()> {
+    let (promoted, waiting) = with_queue(state, |queue, now| {
+        queue.slots.retain(|slot| slot.canvas_id != canvas_id);
+        queue.waiting.retain(|queued| queued.canvas_id != canvas_id);
+
+        let promoted = promote_queue(state, queue, now);
+        (promoted, waiting_positions(queue))
+    })
+    .await?;
+
+    notify(state, promoted, waiting).await;
+
+    Ok(())
+}
+
+/// Expires stale slots and queue entries. Run periodically so unclaimed slots move on.
+pub async fn advance_mint_queue(state: &AppState) -> Result<()> {
+    let (promoted, waiting) = with_queue(state, |queue, now| {
+        let promoted = promote_queue(state, queue, now);
+        (promoted, waiting_positions(queue))
+    })
+    .await?;
+
+    if !promoted.is_empty() {
+        notify(state, promoted, waiting).await;
+    }
+
+    Ok(())
+}
+
+fn promote_queue(state: &AppState, queue: &mut MintQueue, now: DateTime<Utc>) -> Vec<Uuid> {
+    let slot_ttl =
+        chrono::Duration::seconds(state.config.solana.mint_signature_timeout_secs as i64);
+    queue.prune(now, slot_ttl);
+    queue.promote(now, state.config.solana.max_concurrent_mints)
+}
+
+fn waiting_positions(queue: &MintQueue) -> Vec<(Uuid, u64)> {
+    queue
+        .waiting
+        .iter()
+        .enumerate()
+        .map(|(index, queued)| (queued.canvas_id, index as u64 + 1))
+        .collect()
+}
+
+async fn notify(state: &AppState, promoted: Vec<Uuid>, waiting: Vec<(Uuid, u64)>) {
+    for canvas_id in promoted {
+        state
+            .ws_rooms
+            .broadcast(&canvas_id, RoomCanvasUpdate::MintSlotReady)
+            .await;
+    }
+
+    for (canvas_id, position) in waiting {
+        state
+            .ws_rooms
+            .broadcast(&canvas_id, RoomCanvasUpdate::MintQueued { position })
+            .await;
+    }
+}
+
+/// Loads, updates and stores the queue under a short Redis lock so concurrent
+/// `nft.mint` calls across instances see a consistent queue.
+async fn with_queue<T, F>(state: &AppState, update: F) -> Result<T>
+where
+    F: FnOnce(&mut MintQueue, DateTime<Utc>) -> T,
+{
+    let lock_key = CacheKey::mint_queue_lock();
+    let mut acquired = false;
+    for _ in 0..QUEUE_LOCK_RETRIES {
+        if state.cache.redis.setnx(&lock_key, QUEUE_LOCK_TTL).await? {
+            acquired = true;
+            break;
+        }
+        tokio::time::sleep(QUEUE_LOCK_RETRY_DELAY).await;
+    }
+    if !acquired {
+        return Err(AppError::InternalServerError(
+            "Timed out waiting for the mint queue".into(),
+        ));
+    }
+
+    let result = update_queue(state, update).await;
+    state.cache.redis.delete(&lock_key).await?;
+    result
+}
+
+async fn update_queue<T, F>(state: &AppState, update: F) -> Result<T>
+where
+    F: FnOnce(&mut MintQueue, DateTime<Utc>) -> T,
+{
+    let queue_key = CacheKey::mint_queue();
+    let mut queue: MintQueue = state.cache.redis.get(&queue_key).await?.unwrap_or_default();
+
+    let result = update(&mut queue, Utc::now());
+    state.cache.redis.set(&queue_key, &queue, QUEUE_TTL).await?;
+
+    Ok(result)
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{entities::canvas, repositories::CanvasRepository},
+    services::{
+        nft::{finalize_mint, mint_instruction, mint_queue, revert_mint, types::MintStatus},
+        solana::TransactionStatus,
+    },
+    utils::scheduler::spawn_interval_task,
+};
+
+/// Polls mint transactions submitted through `nft.confirmMint` so canvases do not stay
+/// in `Minting` when the client stops waiting for confirmation.
+pub fn spawn_mint_watcher(state: AppState) -> JoinHandle<()> {
+    let period = Duration::from_secs(state.config.solana.mint_watch_interval_secs);
+
+    spawn_interval_task("mint_watch", period, move || {
+        let state = state.clone();
+        async move { poll_pending_mints(&state).await }
+    })
+}
+
+/// Current mint progress, for clients that reconnect after leaving `nft.confirmMint`.
+pub async fn get_mint_status(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<MintStatus> {
+    if !CanvasRepository::is_canvas_collaborator(state.db.get_connection(), canvas_id, user_id)
+        .await?
+    {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    Ok(MintStatus {
+        canvas_id,
+        state: canvas.state,
+        mint_address: canvas.mint_address,
+        mint_signature: canvas.mint_signature,
+        mint_submitted_at: canvas.mint_submitted_at,
+        collection_verified: canvas.collection_verified,
+    })
+}
+
+pub async fn poll_pending_mints(state: &AppState) -> Result<()> {
+    let pending = CanvasRepository::list_pending_mints(state.db.get_connection()).await?;
+
+    for canvas in pending {
+        let canvas_id = canvas.id;
+        if let Err(e) = settle_pending_mint(state, canvas).await {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to settle pending mint");
+        }
+    }
+
+    // Unclaimed and abandoned mint slots are handed on here.
+    mint_queue::advance_mint_queue(state).await?;
+
+    Ok(())
+}
+
+pub(crate) async fn settle_pending_mint(state: &AppState, canvas: canvas::Model) -> Result<()> {
+    let Some(signature) = canvas.mint_signature.as_deref() else {
+        return Ok(());
+    };
+
+    let status = state
+        .solana_client
+        .check_program_instruction(signature, &mint_instruction(state, &canvas).await?)
+        .await?;
+
+    match status {
+        TransactionStatus::Confirmed => {
+            finalize_mint(state, &canvas, signature).await?;
+            tracing::info!(canvas_id = %canvas.id, "Mint confirmed by watcher");
+        }
+        TransactionStatus::Failed => {
+            revert_mint(state, canvas.id, "Mint transaction failed").await?;
+        }
+        TransactionStatus::Pending => {
+            let timeout =
+                chrono::Duration::seconds(state.config.solana.mint_signature_timeout_secs as i64);
+            let expired = canvas
+                .mint_submitted_at
+                .is_some_and(|submitted_at| Utc::now() - submitted_at > timeout);
+            if expired {
+                revert_mint(state, canvas.id, "Mint transaction expired").await?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,64 +1,240 @@
+pub mod burn;
+pub mod collection;
+pub mod consistency;
+pub mod creators;
+pub mod distribution;
+pub mod editions;
 pub mod image;
+pub mod lineage;
+pub mod mint_queue;
+pub mod mint_watch;
+pub mod preview;
+pub mod reconcile;
+pub mod royalties;
+pub mod storage;
+pub mod timelapse;
 pub mod types;
+pub mod update;
+
+pub use burn::{confirm_burn, initiate_burn};
+pub use collection::verify_collection;
+pub use consistency::verify_canvas_consistency;
+pub use distribution::get_payout_status;
+pub use editions::{confirm_edition_mint, get_edition_metadata, initiate_edition_mint};
+pub use lineage::get_lineage;
+pub use mint_watch::{get_mint_status, spawn_mint_watcher};
+pub use preview::preview_nft;
+pub use reconcile::spawn_reconciler;
+pub use royalties::{get_royalty_payouts, spawn_royalty_indexer};
+pub use timelapse::generate_timelapse;
+pub use update::{confirm_metadata_update, initiate_metadata_update};
 
 use std::collections::HashMap;
 
 use base64::Engine;
+use chrono::Utc;
 use sea_orm::ActiveValue::Set;
 use uuid::Uuid;
 
 use crate::{
     AppState,
+    config::CanvasConfig,
     error::{AppError, Result},
     infrastructure::{
         cache::keys::CacheKey,
         db::{
-            entities::canvas::CanvasState,
+            entities::{
+                canvas::{
+                    self, CanvasState, CustomAttribute, CustomAttributes, NftStandard,
+                    StorageReceipts, TimelapseFormat,
+                },
+                consumed_signature::{self, SignatureOperation},
+            },
             repositories::{CanvasRepository, PixelRepository, UserRepository},
         },
     },
     services::{
-        nft::types::{
-            Attribute, CreatorOutput, ImageFile, MetadataResult, MintResult, MintTransactionInfo,
-            NftMetadata, Properties,
+        canvas::palette::resolve_palette,
+        nft::{
+            creators::CreatorStake,
+            mint_queue::MintSlotStatus,
+            types::{
+                Attribute, CreatorOutput, ImageFile, MetadataResult, MintResult,
+                MintTransactionInfo, NftCollection, NftMetadata, Properties, StoredMetadata,
+            },
         },
-        solana,
+        outbox,
+        solana::{
+            self,
+            accounts::CanvasAccount,
+            fees, signatures,
+            transactions::{self, ExpectedInstruction, ExpectedOperation, MintAccounts, MintArgs},
+        },
+        webhooks::WebhookEvent,
     },
     ws::types::RoomCanvasUpdate,
 };
 
-pub async fn prepare_metadata(state: &AppState, canvas_id: Uuid) -> Result<MetadataResult> {
-    let pixels =
-        PixelRepository::find_pixels_by_canvas(state.db.get_connection(), canvas_id).await?;
-    let image_data = image::generate_png(&pixels)?;
+/// `attributes`, when given, replace the canvas's custom attributes before upload.
+pub async fn prepare_metadata(
+    state: &AppState,
+    canvas_id: Uuid,
+    attributes: Option<Vec<CustomAttribute>>,
+) -> Result<MetadataResult> {
+    if let Some(attributes) = attributes {
+        let attributes = normalize_custom_attributes(&state.config.canvas, attributes)?;
+        CanvasRepository::update_canvas(&state.db, canvas_id, |active| {
+            active.custom_attributes = Set(Some(attributes));
+        })
+        .await?;
+    }
 
-    let image_base64 = base64::engine::general_purpose::STANDARD.encode(&image_data);
-    let image_data_uri = format!("data:image/png;base64,{}", image_base64);
+    let (result, stored) = upload_metadata(state, canvas_id).await?;
 
+    if let Some(stored) = stored {
+        save_stored_metadata(state, canvas_id, stored, |_active| {}).await?;
+    }
+
+    Ok(result)
+}
+
+/// Renders and stores the canvas's NFT assets without recording them on the canvas.
+/// `None` means no storage backend is configured and nothing was uploaded.
+async fn upload_metadata(
+    state: &AppState,
+    canvas_id: Uuid,
+) -> Result<(MetadataResult, Option<StoredMetadata>)> {
     let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
         .await?
         .ok_or(AppError::CanvasNotFound)?;
 
-    let canvas_owner = UserRepository::find_user_by_id(state.db.get_connection(), canvas.owner_id)
-        .await?
-        .ok_or(AppError::UserNotFound)?;
+    let pixel_colors = PixelRepository::find_canvas_colors(
+        state.db.get_connection(),
+        canvas_id,
+        state.config.canvas.width,
+        state.config.canvas.height,
+    )
+    .await?;
+    let palette = resolve_palette(&state.config.canvas, canvas.palette.as_ref());
+    let image_data = image::generate_png(
+        &pixel_colors,
+        &palette,
+        &image::RenderOptions::from_config(&state.config.image),
+    )?;
 
-    let top_pixel_owners =
-        PixelRepository::find_top_pixel_owners(state.db.get_connection(), canvas_id, 4).await?;
+    let creators_output = resolve_creators(state, &canvas).await?;
+
+    let Some(metadata_storage) = state.metadata_storage.as_ref() else {
+        // Without pinning, the image is inlined and the metadata is served by this server.
+        let image_base64 = base64::engine::general_purpose::STANDARD.encode(&image_data);
+        let image_data_uri = format!("data:image/png;base64,{}", image_base64);
+        let metadata_uri = canvas_metadata_uri(state, &canvas);
+
+        let result = MetadataResult {
+            metadata_uri,
+            image_uri: image_data_uri.clone(),
+            image_gateway_url: image_data_uri,
+            metadata_gateway_url: String::new(),
+            creators: creators_output,
+            storage_cost_lamports: None,
+        };
+        return Ok((result, None));
+    };
+
+    let image_cost = metadata_storage
+        .estimate_cost_lamports(image_data.len() as u64)
+        .await?;
+    let image_asset = metadata_storage
+        .store_file(&format!("{}.png", canvas_id), "image/png", image_data)
+        .await?;
+    let image_uri = metadata_storage.uri(&image_asset.id);
+
+    let mut extra_files = Vec::new();
+    let mut timelapse_cost = Some(0);
+    let mut timelapse_receipt = None;
+    if let Some((timelapse_data, format)) = load_attached_timelapse(state, &canvas).await? {
+        timelapse_cost = metadata_storage
+            .estimate_cost_lamports(timelapse_data.len() as u64)
+            .await?;
+        let timelapse_asset = metadata_storage
+            .store_file(
+                &format!("{}-{}", canvas_id, format.file_name()),
+                format.content_type(),
+                timelapse_data,
+            )
+            .await?;
+        extra_files.push(ImageFile {
+            uri: metadata_storage.uri(&timelapse_asset.id),
+            file_type: format.content_type().into(),
+        });
+        timelapse_receipt = Some(timelapse_asset.receipt);
+    }
+
+    let claimed_count =
+        PixelRepository::find_owned_pixels_by_canvas(state.db.get_connection(), canvas_id)
+            .await?
+            .len();
+    let parent_mint = lineage::parent_mint_address(state, &canvas).await?;
+    let metadata = build_nft_metadata(
+        &canvas,
+        claimed_count,
+        &image_uri,
+        creators_output.clone(),
+        extra_files,
+        collection::metadata_collection(&state.config.solana, &canvas),
+        parent_mint.as_deref(),
+    );
+    let metadata_json = serde_json::to_value(&metadata)?;
+    let metadata_cost = metadata_storage
+        .estimate_cost_lamports(serde_json::to_vec(&metadata_json)?.len() as u64)
+        .await?;
+    let metadata_asset = metadata_storage
+        .store_json(&format!("{}.json", canvas_id), &metadata_json)
+        .await?;
+
+    // Unknown if any part has no quoted cost.
+    let storage_cost_lamports = [image_cost, timelapse_cost, metadata_cost]
+        .into_iter()
+        .sum::<Option<u64>>();
 
-    let total_sol_invested: i64 = top_pixel_owners.iter().map(|(_, amount)| amount).sum();
+    let result = MetadataResult {
+        metadata_uri: metadata_storage.uri(&metadata_asset.id),
+        image_uri,
+        image_gateway_url: metadata_storage.gateway_url(&image_asset.id),
+        metadata_gateway_url: metadata_storage.gateway_url(&metadata_asset.id),
+        creators: creators_output,
+        storage_cost_lamports,
+    };
 
-    let mut creators_list = Vec::new();
+    let stored = StoredMetadata {
+        backend: metadata_storage.name().to_string(),
+        image_cid: image_asset.id,
+        metadata_cid: metadata_asset.id,
+        receipts: StorageReceipts {
+            image: image_asset.receipt,
+            metadata: metadata_asset.receipt,
+            cost_lamports: storage_cost_lamports,
+            timelapse: timelapse_receipt,
+        },
+    };
 
-    // Owner gets min 10%.
-    let canvas_owner_base_share: u8 = if top_pixel_owners.is_empty() { 100 } else { 10 };
-    let remaining_share: u8 = 100 - canvas_owner_base_share;
+    Ok((result, Some(stored)))
+}
 
-    // Add owner as first creator
-    creators_list.push(serde_json::json!({
-        "address": canvas_owner.wallet_address,
-        "share": canvas_owner_base_share
-    }));
+/// On-chain creator split of a canvas: the owner plus its top pixel investors.
+pub(crate) async fn resolve_creators(
+    state: &AppState,
+    canvas: &canvas::Model,
+) -> Result<Vec<CreatorOutput>> {
+    let canvas_owner = UserRepository::find_user_by_id_including_deleted(
+        state.db.get_connection(),
+        canvas.owner_id,
+    )
+    .await?
+    .ok_or(AppError::UserNotFound)?;
+
+    let top_pixel_owners =
+        PixelRepository::find_top_pixel_owners(state.db.get_connection(), canvas.id, 4).await?;
 
     // Batch fetch users.
     let other_owner_ids: Vec<Uuid> = top_pixel_owners
@@ -67,71 +243,265 @@ pub async fn prepare_metadata(state: &AppState, canvas_id: Uuid) -> Result<Metad
         .map(|(id, _)| *id)
         .collect();
 
-    let users_map: HashMap<Uuid, _> =
-        UserRepository::find_users_by_ids(state.db.get_connection(), &other_owner_ids)
-            .await?
-            .into_iter()
-            .map(|u| (u.id, u))
-            .collect();
-
-    // Add top pixel claimers (excluding owner)
-    for (owner_id, amount) in &top_pixel_owners {
-        if *owner_id == canvas.owner_id {
-            continue;
-        }
+    let users_map: HashMap<Uuid, _> = UserRepository::find_users_by_ids_including_deleted(
+        state.db.get_connection(),
+        &other_owner_ids,
+    )
+    .await?
+    .into_iter()
+    .map(|u| (u.id, u))
+    .collect();
 
-        if let Some(user) = users_map.get(owner_id) {
-            let share = if total_sol_invested > 0 {
-                ((*amount as f64 / total_sol_invested as f64) * remaining_share as f64).round()
-                    as u8
+    let stakes: Vec<CreatorStake> = top_pixel_owners
+        .iter()
+        .filter_map(|(owner_id, amount)| {
+            let address = if *owner_id == canvas.owner_id {
+                canvas_owner.wallet_address.clone()
             } else {
-                0
+                users_map.get(owner_id)?.wallet_address.clone()
             };
-            if share > 0 {
-                creators_list.push(serde_json::json!({
-                    "address": user.wallet_address,
-                    "share": share
-                }));
-            }
-        }
+            Some(CreatorStake {
+                address,
+                amount_lamports: *amount,
+            })
+        })
+        .collect();
+
+    Ok(creators::allocate_creator_shares(
+        &canvas_owner.wallet_address,
+        &stakes,
+    ))
+}
+
+async fn save_stored_metadata<F>(
+    state: &AppState,
+    canvas_id: Uuid,
+    stored: StoredMetadata,
+    updater: F,
+) -> Result<canvas::Model>
+where
+    F: FnOnce(&mut canvas::ActiveModel),
+{
+    let canvas = CanvasRepository::update_canvas(&state.db, canvas_id, |active| {
+        active.image_cid = Set(Some(stored.image_cid));
+        active.metadata_cid = Set(Some(stored.metadata_cid));
+        active.storage_backend = Set(Some(stored.backend));
+        active.storage_receipts = Set(Some(stored.receipts));
+        updater(active);
+    })
+    .await?;
+
+    Ok(canvas)
+}
+
+/// Reads the canvas's timelapse from the asset store when it is attached to the metadata.
+async fn load_attached_timelapse(
+    state: &AppState,
+    canvas: &canvas::Model,
+) -> Result<Option<(Vec<u8>, TimelapseFormat)>> {
+    let (Some(timelapse_hash), Some(format)) = (&canvas.timelapse_hash, canvas.timelapse_format)
+    else {
+        return Ok(None);
+    };
+    if !canvas.timelapse_in_metadata {
+        return Ok(None);
     }
 
-    // Ensure shares sum to 100.
-    let total_shares: u8 = creators_list
-        .iter()
-        .filter_map(|creator| creator["share"].as_u64().map(|s| s as u8))
-        .sum();
-    if total_shares != 100
-        && let Some(first) = creators_list.first_mut()
+    let timelapse_data =
+        state
+            .cache
+            .assets
+            .get(timelapse_hash)
+            .await?
+            .ok_or(AppError::InvalidParams(
+                "Timelapse is missing, please generate it again".into(),
+            ))?;
+
+    Ok(Some((timelapse_data, format)))
+}
+
+/// Symbol used when the owner did not choose one at mint-announce time.
+pub const DEFAULT_NFT_SYMBOL: &str = "PIXEL";
+/// Royalty used when the owner did not choose one at mint-announce time (5%).
+pub const DEFAULT_ROYALTY_BPS: u16 = 500;
+/// Metaplex limits symbols to 10 bytes.
+const MAX_NFT_SYMBOL_LENGTH: usize = 10;
+
+/// Where the canvas's metadata JSON lives: the stored upload, or this server when nothing was uploaded.
+fn canvas_metadata_uri(state: &AppState, canvas: &canvas::Model) -> String {
+    match (
+        state.metadata_storage.as_ref(),
+        canvas.metadata_cid.as_deref(),
+    ) {
+        (Some(metadata_storage), Some(metadata_cid)) => metadata_storage.uri(metadata_cid),
+        _ => format!(
+            "{}/nft/{}/metadata.json",
+            state.config.server.server_public_url, canvas.id
+        ),
+    }
+}
+
+pub fn nft_symbol(canvas: &canvas::Model) -> String {
+    canvas
+        .nft_symbol
+        .clone()
+        .unwrap_or_else(|| DEFAULT_NFT_SYMBOL.to_string())
+}
+
+pub fn royalty_bps(canvas: &canvas::Model) -> u16 {
+    canvas
+        .royalty_bps
+        .map(|bps| bps as u16)
+        .unwrap_or(DEFAULT_ROYALTY_BPS)
+}
+
+/// Checks the royalty against `MAX_ROYALTY_BPS`.
+pub fn validate_royalty_bps(config: &CanvasConfig, bps: u16) -> Result<()> {
+    if bps > config.max_royalty_bps {
+        return Err(AppError::InvalidParams(format!(
+            "Royalty must be at most {} basis points",
+            config.max_royalty_bps
+        )));
+    }
+    Ok(())
+}
+
+/// Uppercases the symbol and checks it is 1-10 ASCII letters or digits.
+pub fn normalize_nft_symbol(symbol: &str) -> Result<String> {
+    let symbol = symbol.trim().to_ascii_uppercase();
+    if symbol.is_empty()
+        || symbol.len() > MAX_NFT_SYMBOL_LENGTH
+        || !symbol.bytes().all(|b| b.is_ascii_alphanumeric())
     {
-        let first_share = first["share"].as_u64().unwrap_or(0) as i16;
-        let adjustment = 100i16 - total_shares as i16;
-        let new_share = (first_share + adjustment).max(1) as u64;
-        first["share"] = serde_json::json!(new_share);
+        return Err(AppError::InvalidParams(format!(
+            "Symbol must be 1-{} letters or digits",
+            MAX_NFT_SYMBOL_LENGTH
+        )));
     }
+    Ok(symbol)
+}
 
-    let creators_output: Vec<CreatorOutput> = creators_list
-        .iter()
-        .filter_map(|creator| {
-            creator["address"].as_str().map(|addr| CreatorOutput {
-                address: addr.to_string(),
-                share: creator["share"].as_u64().unwrap_or(0) as u8,
-            })
-        })
-        .collect();
+/// Trait types computed by the server, which custom attributes cannot shadow.
+const RESERVED_TRAIT_TYPES: [&str; 5] = [
+    "Width",
+    "Height",
+    "Pixels Claimed",
+    "Edition",
+    "Derived From",
+];
+const MAX_TRAIT_TYPE_LENGTH: usize = 32;
+const MAX_TRAIT_VALUE_LENGTH: usize = 64;
 
-    let metadata_uri = format!(
-        "{}/nft/{}/metadata.json",
-        state.config.server.server_public_url, canvas_id
-    );
+/// Trims each attribute and checks the count, lengths and that trait types are unique.
+pub fn normalize_custom_attributes(
+    config: &CanvasConfig,
+    attributes: Vec<CustomAttribute>,
+) -> Result<CustomAttributes> {
+    if attributes.len() > config.max_custom_attributes {
+        return Err(AppError::InvalidParams(format!(
+            "At most {} custom attributes are allowed",
+            config.max_custom_attributes
+        )));
+    }
 
-    Ok(MetadataResult {
-        metadata_uri,
-        image_uri: image_data_uri.clone(),
-        image_gateway_url: image_data_uri,
-        metadata_gateway_url: String::new(),
-        creators: creators_output,
-    })
+    let mut normalized: Vec<CustomAttribute> = Vec::with_capacity(attributes.len());
+    for attribute in attributes {
+        let trait_type = attribute.trait_type.trim().to_string();
+        let value = attribute.value.trim().to_string();
+
+        if trait_type.is_empty() || trait_type.chars().count() > MAX_TRAIT_TYPE_LENGTH {
+            return Err(AppError::InvalidParams(format!(
+                "Attribute trait_type must be 1-{} characters",
+                MAX_TRAIT_TYPE_LENGTH
+            )));
+        }
+        if value.is_empty() || value.chars().count() > MAX_TRAIT_VALUE_LENGTH {
+            return Err(AppError::InvalidParams(format!(
+                "Attribute value must be 1-{} characters",
+                MAX_TRAIT_VALUE_LENGTH
+            )));
+        }
+
+        let clashes = |existing: &str| existing.eq_ignore_ascii_case(&trait_type);
+        if RESERVED_TRAIT_TYPES
+            .iter()
+            .any(|reserved| clashes(reserved))
+            || normalized
+                .iter()
+                .any(|existing| clashes(&existing.trait_type))
+        {
+            return Err(AppError::InvalidParams(format!(
+                "Duplicate or reserved attribute: {}",
+                trait_type
+            )));
+        }
+
+        normalized.push(CustomAttribute { trait_type, value });
+    }
+
+    Ok(CustomAttributes(normalized))
+}
+
+fn build_nft_metadata(
+    canvas: &canvas::Model,
+    claimed_count: usize,
+    image_uri: &str,
+    creators: Vec<CreatorOutput>,
+    extra_files: Vec<ImageFile>,
+    collection: Option<NftCollection>,
+    parent_mint: Option<&str>,
+) -> NftMetadata {
+    let mut files = vec![ImageFile {
+        uri: image_uri.to_string(),
+        file_type: "image/png".into(),
+    }];
+    files.extend(extra_files);
+
+    // Remixes name the mint they derive from, ahead of the owner's own traits.
+    let provenance = parent_mint.map(|parent_mint| Attribute {
+        trait_type: "Derived From".into(),
+        value: parent_mint.to_string(),
+    });
+    let custom_attributes = canvas
+        .custom_attributes
+        .iter()
+        .flat_map(|attributes| attributes.0.iter())
+        .map(|attribute| Attribute {
+            trait_type: attribute.trait_type.clone(),
+            value: attribute.value.clone(),
+        });
+
+    NftMetadata {
+        name: canvas.name.clone(),
+        symbol: nft_symbol(canvas),
+        description: format!("{}: 32x32 collaborative pixel art canvas.", canvas.name),
+        image: image_uri.to_string(),
+        seller_fee_basis_points: royalty_bps(canvas),
+        attributes: vec![
+            Attribute {
+                trait_type: "Width".into(),
+                value: "32".into(),
+            },
+            Attribute {
+                trait_type: "Height".into(),
+                value: "32".into(),
+            },
+            Attribute {
+                trait_type: "Pixels Claimed".into(),
+                value: claimed_count.to_string(),
+            },
+        ]
+        .into_iter()
+        .chain(provenance)
+        .chain(custom_attributes)
+        .collect(),
+        properties: Properties {
+            files,
+            category: "image".into(),
+            creators,
+        },
+        collection,
+    }
 }
 
 pub async fn initiate_nft_mint(
@@ -152,19 +522,82 @@ pub async fn initiate_nft_mint(
         return Err(AppError::InvalidCanvasStateTransition);
     }
 
-    CanvasRepository::update_canvas_state(&state.db, canvas_id, CanvasState::Minting, |_active| {})
-        .await?;
+    let owner_wallet = transactions::user_wallet(state, user_id).await?;
 
-    state
-        .ws_rooms
-        .broadcast(&canvas_id, RoomCanvasUpdate::MintingStarted)
-        .await;
+    // Mints are serialized so a burst of them does not outlive blockhashes or trip RPC limits.
+    if let MintSlotStatus::Queued { position } =
+        mint_queue::acquire_mint_slot(state, canvas_id, user_id).await?
+    {
+        return Err(AppError::MintQueued { position });
+    }
+
+    let collection_mint = state.solana_client.collection_mint().copied();
+
+    let mut events = vec![outbox::room_event(
+        canvas_id,
+        RoomCanvasUpdate::MintingStarted,
+    )?];
+    events.extend(outbox::webhook_events(
+        state,
+        &WebhookEvent::NftMinting { canvas_id },
+    )?);
+
+    let started = CanvasRepository::update_canvas_state(
+        &state.db,
+        canvas_id,
+        CanvasState::Minting,
+        events,
+        |active| {
+            active.collection_mint = Set(collection_mint.map(|mint| mint.to_string()));
+            active.collection_verified = Set(false);
+        },
+    )
+    .await;
+    if let Err(e) = started {
+        release_mint_slot(state, canvas_id).await;
+        return Err(e);
+    }
+
+    let symbol = nft_symbol(&canvas);
+    let seller_fee_basis_points = royalty_bps(&canvas);
+    let parent_mint = lineage::parent_mint_address(state, &canvas).await?;
+    let metadata_uri = canvas_metadata_uri(state, &canvas);
+    let program = solana::canvas_program(state.solana_client.as_ref(), &canvas)?;
 
     let canvas_pda_string = canvas.canvas_pda.ok_or(AppError::InvalidParams(
         "Canvas not published on-chain".into(),
     ))?;
 
-    let (config_pda, _) = state.solana_client.derive_config_pda();
+    let (config_pda, _) = program.derive_config_pda();
+
+    let (asset_program_id, asset_address, metadata_pda, master_edition_pda) =
+        match canvas.nft_standard {
+            NftStandard::TokenMetadata => {
+                let (mint, _) = program.derive_nft_mint_pda(&canvas_id);
+                let (metadata, _) = state.solana_client.derive_metadata_pda(&mint);
+                let (master_edition, _) = state.solana_client.derive_master_edition_pda(&mint);
+                (
+                    solana::client::TOKEN_METADATA_PROGRAM_ID,
+                    mint,
+                    Some(metadata),
+                    Some(master_edition),
+                )
+            }
+            NftStandard::Core => {
+                let (asset, _) = program.derive_core_asset_pda(&canvas_id);
+                (solana::client::MPL_CORE_PROGRAM_ID, asset, None, None)
+            }
+        };
+
+    // Core collections are plain accounts; Token Metadata collections are NFTs with their own PDAs.
+    let (collection_metadata_pda, collection_master_edition_pda) =
+        match (collection_mint, canvas.nft_standard) {
+            (Some(mint), NftStandard::TokenMetadata) => (
+                Some(state.solana_client.derive_metadata_pda(&mint).0),
+                Some(state.solana_client.derive_master_edition_pda(&mint).0),
+            ),
+            _ => (None, None),
+        };
 
     let blockhash = state
         .solana_client
@@ -172,13 +605,76 @@ pub async fn initiate_nft_mint(
         .await
         .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
 
+    let mut instructions = vec![transactions::mint_nft_instruction(
+        &program,
+        &owner_wallet,
+        &canvas_id,
+        &MintAccounts {
+            asset_program: asset_program_id,
+            asset: asset_address,
+            metadata: metadata_pda,
+            master_edition: master_edition_pda,
+            collection_mint,
+            collection_metadata: collection_metadata_pda,
+            collection_master_edition: collection_master_edition_pda,
+        },
+        &MintArgs {
+            name: &canvas.name,
+            symbol: &symbol,
+            uri: &metadata_uri,
+            seller_fee_basis_points,
+        },
+    )];
+    // Records the remix on-chain alongside the mint.
+    if let Some(parent_mint) = parent_mint.as_deref() {
+        instructions.push(transactions::memo_instruction(&format!(
+            "derived_from:{parent_mint}"
+        )));
+    }
+    let transaction =
+        transactions::build_unsigned_transaction(&instructions, &owner_wallet, blockhash);
+
+    let new_account_sizes = match canvas.nft_standard {
+        NftStandard::TokenMetadata => vec![
+            fees::MINT_ACCOUNT_SIZE,
+            fees::METADATA_ACCOUNT_SIZE,
+            fees::MASTER_EDITION_ACCOUNT_SIZE,
+        ],
+        NftStandard::Core => vec![fees::core_asset_size(&canvas.name, &metadata_uri)],
+    };
+    // A failed estimate only leaves it out, the mint can still go ahead.
+    let estimated_cost = match state
+        .solana_client
+        .estimate_cost(&instructions, &owner_wallet, blockhash, &new_account_sizes)
+        .await
+    {
+        Ok(estimate) => Some(estimate),
+        Err(e) => {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to estimate mint cost");
+            None
+        }
+    };
+
     Ok(MintTransactionInfo {
         canvas_id,
         canvas_pda: canvas_pda_string,
         config_pda: config_pda.to_string(),
-        program_id: state.solana_client.get_program_id().to_string(),
+        program_id: program.program_id.to_string(),
         blockhash: blockhash.to_string(),
         canvas_name: canvas.name,
+        symbol,
+        seller_fee_basis_points,
+        standard: canvas.nft_standard,
+        asset_program_id: asset_program_id.to_string(),
+        asset_address: asset_address.to_string(),
+        metadata_pda: metadata_pda.map(|pda| pda.to_string()),
+        master_edition_pda: master_edition_pda.map(|pda| pda.to_string()),
+        collection_mint: collection_mint.map(|mint| mint.to_string()),
+        collection_metadata_pda: collection_metadata_pda.map(|pda| pda.to_string()),
+        collection_master_edition_pda: collection_master_edition_pda.map(|pda| pda.to_string()),
+        parent_mint,
+        transaction,
+        estimated_cost,
     })
 }
 
@@ -197,82 +693,275 @@ pub async fn confirm_nft_mint(
         return Err(AppError::NotCanvasOwner);
     }
 
-    let tx_valid = solana::verify_program_transaction(
-        state.solana_client.get_client(),
-        signature,
-        state.solana_client.get_program_id(),
-    )
+    // The mint watcher may already have finished this mint.
+    if canvas.state == CanvasState::Minted && canvas.mint_signature.as_deref() == Some(signature) {
+        return Ok(MintResult {
+            canvas_id,
+            mint_address: canvas.mint_address,
+            state: canvas.state,
+        });
+    }
+
+    if canvas.state != CanvasState::Minting {
+        return Err(AppError::InvalidCanvasStateTransition);
+    }
+
+    signatures::ensure_signature_unused(state, &mint_signature_record(&canvas, signature)).await?;
+
+    // Record the signature first so the mint watcher can finish the mint if
+    // verification below times out or the client goes away.
+    let canvas = CanvasRepository::update_canvas(&state.db, canvas_id, |active| {
+        active.mint_signature = Set(Some(signature.to_string()));
+        active.mint_address = Set(Some(mint_address.to_string()));
+        active.mint_submitted_at = Set(Some(Utc::now()));
+    })
     .await?;
 
+    let tx_valid = state
+        .solana_client
+        .verify_program_instruction(signature, &mint_instruction(state, &canvas).await?)
+        .await?;
+
     if !tx_valid {
         return Err(AppError::TransactionFailed(
             "Transaction verification failed".into(),
         ));
     }
 
-    let canvas = CanvasRepository::update_canvas_state(
+    let canvas = finalize_mint(state, &canvas, signature).await?;
+
+    Ok(MintResult {
+        canvas_id,
+        mint_address: canvas.mint_address,
+        state: canvas.state,
+    })
+}
+
+/// The `mint_nft` instruction a mint transaction for the canvas must carry.
+pub(crate) async fn mint_instruction(
+    state: &AppState,
+    canvas: &canvas::Model,
+) -> Result<ExpectedInstruction> {
+    Ok(ExpectedInstruction {
+        program: solana::canvas_program(state.solana_client.as_ref(), canvas)?,
+        signer: transactions::user_wallet(state, canvas.owner_id).await?,
+        canvas_id: canvas.id,
+        operation: ExpectedOperation::Mint,
+    })
+}
+
+fn mint_signature_record(canvas: &canvas::Model, signature: &str) -> consumed_signature::Model {
+    signatures::operation_record(
+        signature,
+        SignatureOperation::NftMint,
+        canvas.id,
+        canvas.owner_id,
+    )
+}
+
+/// Moves a minting canvas with a confirmed mint transaction to `Minted`. Safe to race
+/// between `nft.confirmMint` and the mint watcher: only the first caller's transition
+/// queues the `Minted` events. `canvas` must carry the submitted mint address.
+pub(crate) async fn finalize_mint(
+    state: &AppState,
+    canvas: &canvas::Model,
+    signature: &str,
+) -> Result<canvas::Model> {
+    let canvas_id = canvas.id;
+    signatures::claim_signature(state, &mint_signature_record(canvas, signature)).await?;
+
+    let mut events = Vec::new();
+    if let Some(mint_address) = canvas.mint_address.clone() {
+        events.push(outbox::room_event(
+            canvas_id,
+            RoomCanvasUpdate::Minted {
+                mint_address: mint_address.clone(),
+            },
+        )?);
+        events.extend(outbox::webhook_events(
+            state,
+            &WebhookEvent::NftMinted {
+                canvas_id,
+                mint_address,
+            },
+        )?);
+    }
+
+    let canvas = match CanvasRepository::update_canvas_state(
         &state.db,
         canvas_id,
         CanvasState::Minted,
+        events,
         |active| {
-            active.mint_address = Set(Some(mint_address.to_string()));
+            active.minted_at = Set(Some(Utc::now()));
         },
     )
-    .await?;
+    .await
+    {
+        Ok(canvas) => canvas,
+        Err(AppError::InvalidCanvasStateTransition) => {
+            let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+                .await?
+                .ok_or(AppError::CanvasNotFound)?;
+            if canvas.state == CanvasState::Minted
+                && canvas.mint_signature.as_deref() == Some(signature)
+            {
+                return Ok(canvas);
+            }
+            return Err(AppError::InvalidCanvasStateTransition);
+        }
+        Err(e) => return Err(e),
+    };
 
     let lock_key = CacheKey::canvas_lock(&canvas_id);
     state.cache.redis.delete(&lock_key).await?;
+    release_mint_slot(state, canvas_id).await;
 
-    state
-        .ws_rooms
-        .broadcast(
-            &canvas_id,
-            RoomCanvasUpdate::Minted {
-                mint_address: mint_address.to_string(),
-            },
-        )
-        .await;
+    if let Err(e) = distribution::distribute_mint_payouts(state, canvas_id, signature).await {
+        tracing::error!(canvas_id = %canvas_id, error = ?e, "Failed to distribute mint payouts");
+    }
 
-    Ok(MintResult {
-        canvas_id,
-        mint_address: canvas.mint_address,
-        state: canvas.state,
+    // The image route renders on demand until the hash is recorded.
+    let canvas = match record_minted_image(state, &canvas).await {
+        Ok(canvas) => canvas,
+        Err(e) => {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to record minted image");
+            canvas
+        }
+    };
+
+    // Verification may land in a later transaction; nft.verifyCollection re-checks it.
+    let canvas = match collection::refresh_collection_status(state, canvas.clone()).await {
+        Ok(canvas) => canvas,
+        Err(e) => {
+            tracing::warn!(error = ?e, "Failed to check collection verification");
+            canvas
+        }
+    };
+
+    Ok(canvas)
+}
+
+/// Renders the default still of a minted canvas into the asset store and records its hash
+/// on the canvas, where the image route reads it.
+async fn record_minted_image(state: &AppState, canvas: &canvas::Model) -> Result<canvas::Model> {
+    let image_data = render_onchain_image(
+        state,
+        canvas.id,
+        Some(canvas),
+        &image::RenderOptions::default(),
+    )
+    .await?;
+    let image_hash = state.cache.assets.put(&image_data).await?;
+
+    CanvasRepository::update_canvas(&state.db, canvas.id, |active| {
+        active.image_hash = Set(Some(image_hash));
     })
+    .await
 }
 
-pub async fn cancel_mint(state: &AppState, canvas_id: Uuid, user_id: Uuid) -> Result<()> {
-    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
-        .await?
-        .ok_or(AppError::CanvasNotFound)?;
+/// Renders a still from the canvas's on-chain account. `canvas` supplies the palette and which
+/// cells are painted; unknown canvases render with the built-in palette.
+pub async fn render_onchain_image(
+    state: &AppState,
+    canvas_id: Uuid,
+    canvas: Option<&canvas::Model>,
+    options: &image::RenderOptions,
+) -> Result<Vec<u8>> {
+    let program = canvas
+        .and_then(|canvas| {
+            state
+                .solana_client
+                .program_for(canvas.program_id.as_deref())
+        })
+        .unwrap_or_else(|| state.solana_client.current_program());
+    let (canvas_pda, _) = program.derive_canvas_pda_from_uuid(&canvas_id);
 
-    if canvas.owner_id != user_id {
-        return Err(AppError::NotCanvasOwner);
-    }
+    let account_data = state
+        .solana_client
+        .get_account_data(&canvas_pda)
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    // Pixel colors come straight from the account data, so the image is trustless.
+    let canvas_account = CanvasAccount::decode(&account_data)?;
+
+    let palette = resolve_palette(
+        &state.config.canvas,
+        canvas.and_then(|canvas| canvas.palette.as_ref()),
+    );
+
+    // Which cells are painted is only known off-chain; the colors themselves stay on-chain.
+    let painted = match canvas {
+        Some(canvas) => {
+            match PixelRepository::find_pixels_by_canvas(state.db.get_connection(), canvas.id).await
+            {
+                Ok(pixels) => Some(image::painted_cells(&pixels)),
+                Err(e) => {
+                    tracing::warn!("Failed to load pixels: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    image::generate_png_from_colors(
+        &canvas_account.pixel_colors,
+        &palette,
+        painted.as_deref(),
+        options,
+    )
+}
+
+/// Returns a minting canvas to `Published` and clears the submitted mint.
+pub(crate) async fn revert_mint(state: &AppState, canvas_id: Uuid, reason: &str) -> Result<()> {
+    let events = vec![outbox::room_event(
+        canvas_id,
+        RoomCanvasUpdate::MintingFailed {
+            reason: reason.to_string(),
+        },
+    )?];
 
     CanvasRepository::update_canvas_state(
         &state.db,
         canvas_id,
         CanvasState::Published,
-        |_active| {},
+        events,
+        |active| {
+            active.mint_address = Set(None);
+            active.mint_signature = Set(None);
+            active.mint_submitted_at = Set(None);
+        },
     )
     .await?;
 
     let lock_key = CacheKey::canvas_lock(&canvas_id);
     state.cache.redis.delete(&lock_key).await?;
-
-    state
-        .ws_rooms
-        .broadcast(
-            &canvas_id,
-            RoomCanvasUpdate::MintingFailed {
-                reason: "Cancelled by user".into(),
-            },
-        )
-        .await;
+    release_mint_slot(state, canvas_id).await;
 
     Ok(())
 }
 
+/// Slots also expire on their own, so a failed release is only logged.
+pub(crate) async fn release_mint_slot(state: &AppState, canvas_id: Uuid) {
+    if let Err(e) = mint_queue::release_mint_slot(state, canvas_id).await {
+        tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to release mint slot");
+    }
+}
+
+pub async fn cancel_mint(state: &AppState, canvas_id: Uuid, user_id: Uuid) -> Result<()> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    revert_mint(state, canvas_id, "Cancelled by user").await
+}
+
 pub async fn get_nft_metadata(state: &AppState, canvas_id: Uuid) -> Result<NftMetadata> {
     let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
         .await?
@@ -286,47 +975,56 @@ pub async fn get_nft_metadata(state: &AppState, canvas_id: Uuid) -> Result<NftMe
         .await?
         .ok_or(AppError::UserNotFound)?;
 
-    let pixels =
-        PixelRepository::find_pixels_by_canvas(state.db.get_connection(), canvas_id).await?;
+    let claimed_count =
+        PixelRepository::find_owned_pixels_by_canvas(state.db.get_connection(), canvas_id)
+            .await?
+            .len();
 
-    let claimed_count = pixels
-        .iter()
-        .filter(|pixel| pixel.owner_id.is_some())
-        .count();
+    let parent_mint = lineage::parent_mint_address(state, &canvas).await?;
 
-    let base_url = &state.config.server.server_public_url;
-    let image_url = format!("{}/nft/{}/image.png", base_url, canvas_id);
+    // Stored assets are only addressable through the backend that stored them.
+    let stored_image = canvas.image_cid.as_ref().zip(
+        state
+            .metadata_storage
+            .as_ref()
+            .filter(|storage| canvas.storage_backend.as_deref() == Some(storage.name())),
+    );
+    let image_url = match stored_image {
+        Some((image_cid, metadata_storage)) => metadata_storage.uri(image_cid),
+        None => format!(
+            "{}/nft/{}/image.png",
+            state.config.server.server_public_url, canvas_id
+        ),
+    };
 
-    Ok(NftMetadata {
-        name: canvas.name.clone(),
-        symbol: "PIXEL".into(),
-        description: format!("{}: 32x32 collaborative pixel art canvas.", canvas.name),
-        image: image_url.clone(),
-        seller_fee_basis_points: 500,
-        attributes: vec![
-            Attribute {
-                trait_type: "Width".into(),
-                value: "32".into(),
-            },
-            Attribute {
-                trait_type: "Height".into(),
-                value: "32".into(),
-            },
-            Attribute {
-                trait_type: "Pixels Claimed".into(),
-                value: claimed_count.to_string(),
-            },
-        ],
-        properties: Properties {
-            files: vec![ImageFile {
-                uri: image_url,
-                file_type: "image/png".into(),
-            }],
-            category: "image".into(),
-            creators: vec![CreatorOutput {
-                address: owner.wallet_address,
-                share: 100,
-            }],
-        },
+    Ok(build_nft_metadata(
+        &canvas,
+        claimed_count,
+        &image_url,
+        vec![CreatorOutput {
+            address: owner.wallet_address,
+            share: 100,
+        }],
+        served_timelapse_file(state, &canvas).into_iter().collect(),
+        collection::metadata_collection(&state.config.solana, &canvas),
+        parent_mint.as_deref(),
+    ))
+}
+
+/// The attached timelapse as served by this server, if the owner listed it in the metadata.
+fn served_timelapse_file(state: &AppState, canvas: &canvas::Model) -> Option<ImageFile> {
+    if !canvas.timelapse_in_metadata || canvas.timelapse_hash.is_none() {
+        return None;
+    }
+    let format = canvas.timelapse_format?;
+
+    Some(ImageFile {
+        uri: format!(
+            "{}/nft/{}/{}",
+            state.config.server.server_public_url,
+            canvas.id,
+            format.file_name()
+        ),
+        file_type: format.content_type().into(),
     })
 }
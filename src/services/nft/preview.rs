@@ -0,0 +1,61 @@
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{
+        entities::canvas::CanvasState,
+        repositories::{CanvasRepository, PixelRepository},
+    },
+    services::nft::{
+        build_nft_metadata, collection, lineage, resolve_creators, served_timelapse_file,
+        types::MintPreview,
+    },
+};
+
+/// Builds the metadata `nft.prepareMetadata` would upload, without storing anything or
+/// touching the canvas. The image points at this server's renderer instead of storage.
+pub async fn preview_nft(state: &AppState, canvas_id: Uuid, user_id: Uuid) -> Result<MintPreview> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if canvas.state != CanvasState::Published {
+        return Err(AppError::InvalidParams(
+            "Canvas must be published to preview the mint".into(),
+        ));
+    }
+
+    let claimed_count =
+        PixelRepository::find_owned_pixels_by_canvas(state.db.get_connection(), canvas_id)
+            .await?
+            .len();
+
+    let image_url = format!(
+        "{}/nft/{}/image.png",
+        state.config.server.server_public_url, canvas_id
+    );
+    let creators = resolve_creators(state, &canvas).await?;
+    let parent_mint = lineage::parent_mint_address(state, &canvas).await?;
+
+    let metadata = build_nft_metadata(
+        &canvas,
+        claimed_count,
+        &image_url,
+        creators.clone(),
+        served_timelapse_file(state, &canvas).into_iter().collect(),
+        collection::metadata_collection(&state.config.solana, &canvas),
+        parent_mint.as_deref(),
+    );
+
+    Ok(MintPreview {
+        canvas_id,
+        metadata,
+        image_url,
+        creators,
+    })
+}
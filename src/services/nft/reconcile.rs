@@ -0,0 +1,190 @@
+use std::{str::FromStr, time::Duration};
+
+use serde::de::IgnoredAny;
+use solana_sdk::pubkey::Pubkey;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::{
+            entities::canvas::{self, CanvasState},
+            repositories::{CanvasRepository, PixelRepository},
+        },
+    },
+    services::{
+        canvas::{account::unpack_colors, pack_pixels_to_colors},
+        nft::consistency::{diff_fields, diff_pixels},
+        outbox,
+        solana::{self, accounts::CanvasAccount},
+        webhooks::WebhookEvent,
+    },
+    utils::scheduler::spawn_interval_task,
+};
+
+/// States whose canvas account exists on-chain, or may exist after a missed confirmation.
+const RECONCILED_STATES: [CanvasState; 5] = [
+    CanvasState::Publishing,
+    CanvasState::Published,
+    CanvasState::MintPending,
+    CanvasState::Minting,
+    CanvasState::Minted,
+];
+/// Remembers reported divergences so an unchanged one is not alerted on every run.
+const DIVERGENCE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Periodically compares stored canvases with their on-chain accounts, instead of trusting
+/// client-driven confirmations to keep them in sync.
+pub fn spawn_reconciler(state: AppState) -> JoinHandle<()> {
+    let period = Duration::from_secs(state.config.solana.reconcile_interval_secs);
+
+    spawn_interval_task("reconcile", period, move || {
+        let state = state.clone();
+        async move { reconcile_canvases(&state).await }
+    })
+}
+
+pub async fn reconcile_canvases(state: &AppState) -> Result<()> {
+    let canvases =
+        CanvasRepository::list_canvases_in_states(state.db.get_connection(), &RECONCILED_STATES)
+            .await?;
+
+    for canvas in canvases {
+        let canvas_id = canvas.id;
+        if let Err(e) = reconcile_canvas(state, canvas).await {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to reconcile canvas");
+        }
+    }
+
+    Ok(())
+}
+
+async fn reconcile_canvas(state: &AppState, canvas: canvas::Model) -> Result<()> {
+    let canvas_pda = match canvas.canvas_pda.as_deref() {
+        Some(stored_pda) => Pubkey::from_str(stored_pda)
+            .map_err(|_| AppError::InternalServerError("Invalid stored canvas PDA".into()))?,
+        None => {
+            solana::canvas_program(state.solana_client.as_ref(), &canvas)?
+                .derive_canvas_pda_from_uuid(&canvas.id)
+                .0
+        }
+    };
+
+    let account_data = state
+        .solana_client
+        .find_account_data(&canvas_pda)
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    let (width, height) = (state.config.canvas.width, state.config.canvas.height);
+    let colors =
+        PixelRepository::find_canvas_colors(state.db.get_connection(), canvas.id, width, height)
+            .await?;
+    let database_colors = unpack_colors(&pack_pixels_to_colors(&colors, width, height));
+
+    repair_caches(state, &canvas, &database_colors).await;
+
+    let mut divergences = Vec::new();
+    match account_data.map(|data| CanvasAccount::decode(&data)) {
+        // Not on-chain yet: the publish transaction has not landed.
+        None if canvas.state == CanvasState::Publishing => {}
+        None => divergences.push("canvas account missing on-chain".to_string()),
+        Some(Err(e)) => divergences.push(format!("canvas account unreadable: {}", e)),
+        Some(Ok(account)) => {
+            if canvas.state == CanvasState::Publishing {
+                divergences.push("publish landed on-chain but was never confirmed".to_string());
+            }
+
+            let pixel_mismatches = diff_pixels(
+                &unpack_colors(&account.pixel_colors),
+                &database_colors,
+                width,
+                height,
+            );
+            if !pixel_mismatches.is_empty() {
+                divergences.push(format!("{} pixels differ", pixel_mismatches.len()));
+            }
+
+            for mismatch in diff_fields(state, &canvas, &canvas_pda, &account).await? {
+                divergences.push(format!(
+                    "{} is {} on-chain but {} stored",
+                    mismatch.field, mismatch.onchain, mismatch.database
+                ));
+            }
+        }
+    }
+
+    flag_divergences(state, canvas.id, divergences).await
+}
+
+/// Drops cached canvas data that no longer matches the database, so reads reload it.
+async fn repair_caches(state: &AppState, canvas: &canvas::Model, database_colors: &[u8]) {
+    let cached_canvas = state.cache.local.get_canvas(&canvas.id).await;
+    if cached_canvas.is_some_and(|cached| *cached != *canvas) {
+        state.cache.invalidate_canvas(&canvas.id).await;
+        tracing::info!(canvas_id = %canvas.id, "Repaired stale canvas cache");
+    }
+
+    let Ok(Some(cached)) = state
+        .cache
+        .redis
+        .get_canvas_pixels::<IgnoredAny>(&canvas.id)
+        .await
+    else {
+        return;
+    };
+
+    let total_pixels = state.config.canvas.width as usize * state.config.canvas.height as usize;
+    if database_colors.get(..total_pixels) != cached.colors.get(..total_pixels) {
+        let _ = tokio::join!(
+            state.cache.redis.delete_canvas_pixels(&canvas.id),
+            state.cache.invalidate_pixels(&canvas.id),
+        );
+        tracing::info!(canvas_id = %canvas.id, "Repaired stale pixel cache");
+    }
+}
+
+/// Alerts when a canvas starts diverging or diverges differently, and clears the flag
+/// once it is back in sync.
+async fn flag_divergences(
+    state: &AppState,
+    canvas_id: Uuid,
+    divergences: Vec<String>,
+) -> Result<()> {
+    let flag_key = CacheKey::canvas_divergence(&canvas_id);
+    let flagged: Option<Vec<String>> = state.cache.redis.get(&flag_key).await?;
+
+    if divergences.is_empty() {
+        if flagged.is_some() {
+            state.cache.redis.delete(&flag_key).await?;
+            tracing::info!(canvas_id = %canvas_id, "Canvas back in sync with its on-chain account");
+        }
+        return Ok(());
+    }
+
+    if flagged.as_ref() != Some(&divergences) {
+        tracing::warn!(
+            canvas_id = %canvas_id,
+            divergences = ?divergences,
+            "Canvas diverges from its on-chain account"
+        );
+        let event = WebhookEvent::CanvasDiverged {
+            canvas_id,
+            divergences: divergences.clone(),
+        };
+        if let Err(e) = outbox::emit_webhook(state, &event).await {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to queue divergence webhook");
+        }
+    }
+
+    state
+        .cache
+        .redis
+        .set(&flag_key, &divergences, DIVERGENCE_TTL)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use chrono::Utc;
+use sea_orm::ActiveValue::Set;
+use solana_sdk::pubkey::Pubkey;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{
+        entities::{canvas, royalty_payout},
+        repositories::{CanvasRepository, RoyaltyPayoutRepository, UserRepository},
+    },
+    services::{
+        nft::{
+            resolve_creators,
+            types::{CreatorOutput, CreatorPayout, RoyaltyPayouts},
+        },
+        solana::BalanceChanges,
+    },
+    utils::scheduler::spawn_interval_task,
+};
+
+/// Scans transactions of minted NFTs for lamports paid to their creators.
+pub fn spawn_royalty_indexer(state: AppState) -> JoinHandle<()> {
+    let period = Duration::from_secs(state.config.solana.royalty_index_interval_secs);
+
+    spawn_interval_task("royalty_index", period, move || {
+        let state = state.clone();
+        async move { index_royalties(&state).await }
+    })
+}
+
+/// Cumulative royalty earnings of each creator in the canvas's split.
+pub async fn get_royalty_payouts(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<RoyaltyPayouts> {
+    if !CanvasRepository::is_canvas_collaborator(state.db.get_connection(), canvas_id, user_id)
+        .await?
+    {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.mint_address.is_none() || canvas.minted_at.is_none() {
+        return Err(AppError::InvalidParams("Canvas is not minted".into()));
+    }
+
+    let creators = resolve_creators(state, &canvas).await?;
+    let totals: HashMap<String, _> =
+        RoyaltyPayoutRepository::sum_payouts_by_creator(state.db.get_connection(), canvas_id)
+            .await?
+            .into_iter()
+            .map(|total| (total.creator_address.clone(), total))
+            .collect();
+
+    let addresses: Vec<String> = creators
+        .iter()
+        .map(|creator| creator.address.clone())
+        .collect();
+    let users: HashMap<String, _> = UserRepository::find_users_by_wallets_including_deleted(
+        state.db.get_connection(),
+        &addresses,
+    )
+    .await?
+    .into_iter()
+    .map(|user| (user.wallet_address.clone(), user))
+    .collect();
+
+    let creators: Vec<CreatorPayout> = creators
+        .into_iter()
+        .map(|creator| {
+            let total = totals.get(&creator.address);
+            let user = users.get(&creator.address);
+            CreatorPayout {
+                user_id: user.map(|user| user.id),
+                username: user.and_then(|user| user.username.clone()),
+                share: creator.share,
+                total_lamports: total.map_or(0, |total| total.total_lamports),
+                payout_count: total.map_or(0, |total| total.payout_count),
+                last_paid_at: total.map(|total| total.last_paid_at),
+                address: creator.address,
+            }
+        })
+        .collect();
+
+    Ok(RoyaltyPayouts {
+        canvas_id,
+        total_lamports: creators.iter().map(|creator| creator.total_lamports).sum(),
+        creators,
+    })
+}
+
+pub async fn index_royalties(state: &AppState) -> Result<()> {
+    let minted = CanvasRepository::list_minted_canvases(state.db.get_connection()).await?;
+
+    for canvas in minted {
+        let canvas_id = canvas.id;
+        if let Err(e) = index_canvas_royalties(state, canvas).await {
+            tracing::warn!(canvas_id = %canvas_id, error = ?e, "Failed to index royalty payouts");
+        }
+    }
+
+    Ok(())
+}
+
+async fn index_canvas_royalties(state: &AppState, canvas: canvas::Model) -> Result<()> {
+    let Some(mint_address) = canvas.mint_address.as_deref() else {
+        return Ok(());
+    };
+    let mint = Pubkey::from_str(mint_address)
+        .map_err(|_| AppError::InternalServerError("Invalid stored mint address".into()))?;
+
+    let client = &state.solana_client;
+    let signatures = client
+        .list_signatures_since(&mint, canvas.royalty_cursor.as_deref())
+        .await?;
+    let Some(newest) = signatures.last().cloned() else {
+        return Ok(());
+    };
+
+    let creators = resolve_creators(state, &canvas).await?;
+
+    let mut payouts = Vec::new();
+    for signature in &signatures {
+        if let Some(changes) = client
+            .fetch_balance_changes(signature, mint_address)
+            .await?
+        {
+            payouts.extend(creator_receipts(canvas.id, signature, &creators, &changes));
+        }
+    }
+
+    let recorded =
+        RoyaltyPayoutRepository::record_payouts(state.db.get_connection(), payouts).await?;
+    if recorded > 0 {
+        tracing::info!(canvas_id = %canvas.id, recorded, "Recorded royalty payouts");
+    }
+
+    CanvasRepository::update_canvas(&state.db, canvas.id, |active| {
+        active.royalty_cursor = Set(Some(newest));
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Lamports each creator gained in a transaction. Signers, the seller and the buyer are
+/// skipped, so a creator's own sale or purchase is not counted as royalty income.
+fn creator_receipts(
+    canvas_id: Uuid,
+    signature: &str,
+    creators: &[CreatorOutput],
+    changes: &BalanceChanges,
+) -> Vec<royalty_payout::Model> {
+    let now = Utc::now();
+
+    creators
+        .iter()
+        .filter(|creator| creator.share > 0)
+        .filter(|creator| {
+            !changes.signers.contains(&creator.address)
+                && !changes.token_holders.contains(&creator.address)
+        })
+        .filter_map(|creator| {
+            let amount = *changes.lamport_deltas.get(&creator.address)?;
+            (amount > 0).then(|| royalty_payout::Model {
+                signature: signature.to_string(),
+                creator_address: creator.address.clone(),
+                canvas_id,
+                amount_lamports: amount,
+                paid_at: changes.block_time.unwrap_or(now),
+                created_at: now,
+            })
+        })
+        .collect()
+}
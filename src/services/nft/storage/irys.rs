@@ -0,0 +1,200 @@
+use async_trait::async_trait;
+use ed25519_dalek::{Signer, SigningKey};
+use reqwest::{Client, header};
+use sha2::{Digest, Sha384};
+
+use crate::{
+    error::{AppError, Result},
+    services::nft::storage::{MetadataStorage, StoredAsset},
+};
+
+/// ANS-104 signature type for ed25519 (Solana) keys.
+const ED25519_SIGNATURE_TYPE: u16 = 2;
+
+/// Permanent Arweave storage through an Irys bundler node, paid in SOL.
+pub struct IrysStorage {
+    http: Client,
+    signing_key: SigningKey,
+    node_url: String,
+    gateway_url: String,
+}
+
+impl IrysStorage {
+    /// `private_key` is a base58 Solana keypair funded on the Irys node.
+    pub fn new(private_key: &str, node_url: &str, gateway_url: &str) -> Result<Self> {
+        let invalid_key =
+            || AppError::InvalidParams("IRYS_PRIVATE_KEY is not a valid keypair".into());
+        let keypair_bytes: [u8; 64] = bs58::decode(private_key)
+            .into_vec()
+            .map_err(|_| invalid_key())?
+            .try_into()
+            .map_err(|_| invalid_key())?;
+        let signing_key =
+            SigningKey::from_keypair_bytes(&keypair_bytes).map_err(|_| invalid_key())?;
+
+        Ok(Self {
+            http: Client::new(),
+            signing_key,
+            node_url: node_url.trim_end_matches('/').to_string(),
+            gateway_url: gateway_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    async fn upload(&self, content_type: &str, data: &[u8]) -> Result<StoredAsset> {
+        let data_item = self.build_data_item(&[("Content-Type", content_type)], data);
+
+        let response = self
+            .http
+            .post(format!("{}/tx/solana", self.node_url))
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .body(data_item)
+            .send()
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::StorageError(format!(
+                "Irys returned {}: {}",
+                status, body
+            )));
+        }
+
+        let receipt: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+        let id = receipt["id"]
+            .as_str()
+            .ok_or(AppError::StorageError("Irys receipt missing id".into()))?
+            .to_string();
+
+        Ok(StoredAsset { id, receipt })
+    }
+
+    /// Serializes and signs an ANS-104 data item with no target or anchor.
+    fn build_data_item(&self, tags: &[(&str, &str)], data: &[u8]) -> Vec<u8> {
+        let owner = self.signing_key.verifying_key().to_bytes();
+        let raw_tags = encode_tags(tags);
+
+        let message = deep_hash(&[
+            b"dataitem",
+            b"1",
+            ED25519_SIGNATURE_TYPE.to_string().as_bytes(),
+            &owner,
+            &[],
+            &[],
+            &raw_tags,
+            data,
+        ]);
+        let signature = self.signing_key.sign(&message).to_bytes();
+
+        let mut item = Vec::with_capacity(2 + 64 + 32 + 2 + 16 + raw_tags.len() + data.len());
+        item.extend_from_slice(&ED25519_SIGNATURE_TYPE.to_le_bytes());
+        item.extend_from_slice(&signature);
+        item.extend_from_slice(&owner);
+        item.push(0); // no target
+        item.push(0); // no anchor
+        item.extend_from_slice(&(tags.len() as u64).to_le_bytes());
+        item.extend_from_slice(&(raw_tags.len() as u64).to_le_bytes());
+        item.extend_from_slice(&raw_tags);
+        item.extend_from_slice(data);
+        item
+    }
+}
+
+#[async_trait]
+impl MetadataStorage for IrysStorage {
+    fn name(&self) -> &'static str {
+        "arweave"
+    }
+
+    async fn estimate_cost_lamports(&self, bytes: u64) -> Result<Option<u64>> {
+        let price = self
+            .http
+            .get(format!("{}/price/solana/{}", self.node_url, bytes))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::StorageError(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+
+        price
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| AppError::StorageError(format!("Unexpected Irys price: {}", price)))
+    }
+
+    async fn store_file(
+        &self,
+        _name: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<StoredAsset> {
+        self.upload(content_type, &bytes).await
+    }
+
+    async fn store_json(&self, _name: &str, content: &serde_json::Value) -> Result<StoredAsset> {
+        self.upload("application/json", &serde_json::to_vec(content)?)
+            .await
+    }
+
+    fn uri(&self, id: &str) -> String {
+        format!("{}/{}", self.gateway_url, id)
+    }
+
+    fn gateway_url(&self, id: &str) -> String {
+        format!("{}/{}", self.gateway_url, id)
+    }
+}
+
+/// Arweave deep hash over a list of blobs.
+fn deep_hash(chunks: &[&[u8]]) -> Vec<u8> {
+    let list_tag = format!("list{}", chunks.len());
+    chunks.iter().fold(
+        Sha384::digest(list_tag.as_bytes()).to_vec(),
+        |acc, chunk| {
+            let blob_tag = format!("blob{}", chunk.len());
+            let blob_hash = Sha384::new()
+                .chain_update(Sha384::digest(blob_tag.as_bytes()))
+                .chain_update(Sha384::digest(chunk))
+                .finalize();
+            Sha384::new()
+                .chain_update(acc)
+                .chain_update(blob_hash)
+                .finalize()
+                .to_vec()
+        },
+    )
+}
+
+/// Avro-encodes tags as an array of `{ name: bytes, value: bytes }` records.
+fn encode_tags(tags: &[(&str, &str)]) -> Vec<u8> {
+    if tags.is_empty() {
+        return Vec::new();
+    }
+
+    let mut encoded = Vec::new();
+    write_avro_long(&mut encoded, tags.len() as i64);
+    for (name, value) in tags {
+        write_avro_long(&mut encoded, name.len() as i64);
+        encoded.extend_from_slice(name.as_bytes());
+        write_avro_long(&mut encoded, value.len() as i64);
+        encoded.extend_from_slice(value.as_bytes());
+    }
+    write_avro_long(&mut encoded, 0);
+    encoded
+}
+
+fn write_avro_long(buffer: &mut Vec<u8>, value: i64) {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    while zigzag >= 0x80 {
+        buffer.push((zigzag as u8 & 0x7f) | 0x80);
+        zigzag >>= 7;
+    }
+    buffer.push(zigzag as u8);
+}
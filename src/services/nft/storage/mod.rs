@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::StorageConfig,
+    error::{AppError, Result},
+};
+
+pub mod irys;
+pub mod pinata;
+
+pub use irys::IrysStorage;
+pub use pinata::PinataStorage;
+
+/// An uploaded asset and the provider's receipt for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredAsset {
+    pub id: String,
+    pub receipt: serde_json::Value,
+}
+
+/// Storage for NFT assets. Implemented by IPFS pinning and Arweave uploads.
+#[async_trait]
+pub trait MetadataStorage: Send + Sync {
+    /// Short backend name recorded with the canvas, e.g. `ipfs`.
+    fn name(&self) -> &'static str;
+
+    /// Lamports needed to store `bytes` bytes, if the backend charges per upload.
+    async fn estimate_cost_lamports(&self, _bytes: u64) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    async fn store_file(
+        &self,
+        name: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<StoredAsset>;
+
+    async fn store_json(&self, name: &str, content: &serde_json::Value) -> Result<StoredAsset>;
+
+    /// Canonical URI embedded in the NFT metadata.
+    fn uri(&self, id: &str) -> String;
+
+    /// HTTP URL serving the stored content.
+    fn gateway_url(&self, id: &str) -> String;
+}
+
+/// Builds the backend selected by `STORAGE_BACKEND`. `None` serves assets from this server.
+pub fn from_config(config: &StorageConfig) -> Result<Option<Arc<dyn MetadataStorage>>> {
+    match config.backend.as_str() {
+        "ipfs" => Ok(config.pinata_jwt.as_ref().map(|jwt| {
+            Arc::new(PinataStorage::new(
+                jwt,
+                &config.pinata_api_url,
+                &config.ipfs_gateway_url,
+            )) as Arc<dyn MetadataStorage>
+        })),
+        "arweave" => {
+            let private_key = config
+                .irys_private_key
+                .as_ref()
+                .ok_or(AppError::InvalidParams(
+                    "IRYS_PRIVATE_KEY is required for the arweave storage backend".into(),
+                ))?;
+            Ok(Some(Arc::new(IrysStorage::new(
+                private_key,
+                &config.irys_node_url,
+                &config.arweave_gateway_url,
+            )?)))
+        }
+        "none" => Ok(None),
+        other => Err(AppError::InvalidParams(format!(
+            "Unknown STORAGE_BACKEND {other}, expected ipfs, arweave or none"
+        ))),
+    }
+}
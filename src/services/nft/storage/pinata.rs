@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use reqwest::{Client, header};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, Result},
+    services::nft::storage::{MetadataStorage, StoredAsset},
+};
+
+/// IPFS pinning through Pinata.
+pub struct PinataStorage {
+    http: Client,
+    jwt: String,
+    api_url: String,
+    gateway_url: String,
+}
+
+impl PinataStorage {
+    pub fn new(jwt: &str, api_url: &str, gateway_url: &str) -> Self {
+        Self {
+            http: Client::new(),
+            jwt: jwt.to_string(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            gateway_url: gateway_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn read_asset(response: reqwest::Response) -> Result<StoredAsset> {
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::StorageError(format!(
+                "Pinata returned {}: {}",
+                status, body
+            )));
+        }
+
+        let receipt: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+        let cid = receipt["IpfsHash"]
+            .as_str()
+            .ok_or(AppError::StorageError(
+                "Pinata response missing IpfsHash".into(),
+            ))?
+            .to_string();
+
+        Ok(StoredAsset { id: cid, receipt })
+    }
+}
+
+#[async_trait]
+impl MetadataStorage for PinataStorage {
+    fn name(&self) -> &'static str {
+        "ipfs"
+    }
+
+    async fn store_file(
+        &self,
+        name: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<StoredAsset> {
+        // Built by hand to keep reqwest's multipart feature out of the build.
+        let boundary = format!("pixel-archives-{}", Uuid::new_v4().simple());
+        let metadata = json!({ "name": name }).to_string();
+
+        let mut body = Vec::with_capacity(bytes.len() + 512);
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"pinataMetadata\"\r\n\r\n{metadata}\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{name}\"\r\nContent-Type: {content_type}\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&bytes);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let response = self
+            .http
+            .post(format!("{}/pinning/pinFileToIPFS", self.api_url))
+            .bearer_auth(&self.jwt)
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+
+        Self::read_asset(response).await
+    }
+
+    async fn store_json(&self, name: &str, content: &serde_json::Value) -> Result<StoredAsset> {
+        let response = self
+            .http
+            .post(format!("{}/pinning/pinJSONToIPFS", self.api_url))
+            .bearer_auth(&self.jwt)
+            .json(&json!({
+                "pinataContent": content,
+                "pinataMetadata": { "name": name },
+            }))
+            .send()
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+
+        Self::read_asset(response).await
+    }
+
+    fn uri(&self, cid: &str) -> String {
+        format!("ipfs://{}", cid)
+    }
+
+    fn gateway_url(&self, cid: &str) -> String {
+        format!("{}/{}", self.gateway_url, cid)
+    }
+}
@@ -0,0 +1,129 @@
+use sea_orm::ActiveValue::Set;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{
+        entities::canvas::{CanvasState, TimelapseFormat},
+        repositories::{CanvasRepository, PixelRepository, pixel_history::PixelHistoryRange},
+    },
+    services::{
+        canvas::{palette::resolve_palette, replay_timelapse},
+        nft::{image, types::TimelapseAsset},
+    },
+};
+
+/// Frames replayed from history, excluding the starting and final frames.
+const TIMELAPSE_ANIMATION_FRAMES: usize = 120;
+
+/// Renders the canvas's pixel history as an animation and stores it in the asset store.
+/// Owner only, until the mint is announced. Regenerating replaces the previous animation.
+pub async fn generate_timelapse(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    format: TimelapseFormat,
+    attach_to_metadata: bool,
+) -> Result<TimelapseAsset> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    // The metadata, and whether the animation is listed in it, is fixed once minting starts
+    if !matches!(canvas.state, CanvasState::Draft | CanvasState::Published) {
+        return Err(AppError::InvalidParams(
+            "Timelapse can only be generated before the canvas is minted".into(),
+        ));
+    }
+
+    // One-second buckets, merged evenly down to the frame budget.
+    let replay = replay_timelapse(
+        state.db.get_connection(),
+        &state.config.canvas,
+        canvas_id,
+        PixelHistoryRange::default(),
+        1,
+        TIMELAPSE_ANIMATION_FRAMES,
+    )
+    .await?;
+
+    if replay.frames.is_empty() {
+        return Err(AppError::InvalidParams(
+            "Canvas has no pixel history yet".into(),
+        ));
+    }
+
+    let width = state.config.canvas.width as u32;
+    let height = state.config.canvas.height as u32;
+
+    // History past the replay limit is skipped, so end on the current canvas.
+    let final_colors = match replay.next_cursor {
+        Some(_) => Some(
+            PixelRepository::find_canvas_colors(
+                state.db.get_connection(),
+                canvas_id,
+                state.config.canvas.width,
+                state.config.canvas.height,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    let frame_count = replay.frames.len() + 1 + usize::from(final_colors.is_some());
+
+    // Each frame is built as the encoder takes it, so only one is held at a time.
+    let mut colors = replay.base_colors;
+    let frames = std::iter::once(colors.clone())
+        .chain(replay.frames.into_iter().map(move |frame| {
+            for change in frame.changes {
+                let index = (change.y as usize) * (width as usize) + (change.x as usize);
+                if let Some(color) = colors.get_mut(index) {
+                    *color = change.color as u8;
+                }
+            }
+            colors.clone()
+        }))
+        .chain(final_colors);
+
+    let palette = resolve_palette(&state.config.canvas, canvas.palette.as_ref());
+
+    let animation = tokio::task::spawn_blocking(move || match format {
+        TimelapseFormat::Gif => {
+            image::generate_timelapse_gif(frames, frame_count, width, height, &palette)
+        }
+        TimelapseFormat::Apng => {
+            image::generate_timelapse_apng(frames, frame_count, width, height, &palette)
+        }
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Timelapse render failed: {}", e)))??;
+
+    let content_hash = state.cache.assets.put(&animation).await?;
+
+    let stored_hash = content_hash.clone();
+    CanvasRepository::update_canvas(&state.db, canvas_id, |canvas| {
+        canvas.timelapse_hash = Set(Some(stored_hash));
+        canvas.timelapse_format = Set(Some(format));
+        canvas.timelapse_in_metadata = Set(attach_to_metadata);
+    })
+    .await?;
+
+    Ok(TimelapseAsset {
+        url: format!(
+            "{}/nft/{}/{}",
+            state.config.server.server_public_url,
+            canvas_id,
+            format.file_name()
+        ),
+        format,
+        content_hash,
+        frame_count,
+        size_bytes: animation.len(),
+        attached_to_metadata: attach_to_metadata,
+    })
+}
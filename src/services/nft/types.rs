@@ -1,7 +1,14 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::infrastructure::db::entities::canvas::CanvasState;
+use crate::{
+    infrastructure::db::entities::{
+        canvas::{self, CanvasState, NftStandard, StorageReceipts, TimelapseFormat},
+        edition_print,
+    },
+    services::solana::fees::CostEstimate,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatorOutput {
@@ -16,6 +23,16 @@ pub struct MetadataResult {
     pub image_gateway_url: String,
     pub metadata_gateway_url: String,
     pub creators: Vec<CreatorOutput>,
+    pub storage_cost_lamports: Option<u64>,
+}
+
+/// Uploaded assets not yet recorded on the canvas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMetadata {
+    pub backend: String,
+    pub image_cid: String,
+    pub metadata_cid: String,
+    pub receipts: StorageReceipts,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +43,123 @@ pub struct MintTransactionInfo {
     pub program_id: String,
     pub blockhash: String,
     pub canvas_name: String,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+    pub standard: NftStandard,
+    pub asset_program_id: String,
+    pub asset_address: String,
+    pub metadata_pda: Option<String>,
+    pub master_edition_pda: Option<String>,
+    pub collection_mint: Option<String>,
+    pub collection_metadata_pda: Option<String>,
+    pub collection_master_edition_pda: Option<String>,
+    /// Mint of the canvas this one remixes, to reference from the mint transaction.
+    pub parent_mint: Option<String>,
+    /// Base64 unsigned `mint_nft` transaction for the owner's wallet to sign.
+    pub transaction: String,
+    /// Fees plus rent for the asset accounts. `None` if the estimate could not be fetched.
+    pub estimated_cost: Option<CostEstimate>,
+}
+
+/// Metadata uploaded by `nft.updateMetadata`, waiting for its on-chain update to be confirmed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingMetadataUpdate {
+    pub user_id: Uuid,
+    pub metadata_uri: String,
+    pub stored: Option<StoredMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataUpdateInfo {
+    pub canvas_id: Uuid,
+    pub metadata: MetadataResult,
+    pub standard: NftStandard,
+    pub asset_program_id: String,
+    pub asset_address: String,
+    pub metadata_pda: Option<String>,
+    pub blockhash: String,
+    pub name: String,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataUpdateResult {
+    pub canvas_id: Uuid,
+    pub metadata_uri: String,
+    pub metadata_updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditionMintInfo {
+    pub canvas_id: Uuid,
+    pub edition_number: i32,
+    pub max_editions: i32,
+    pub program_id: String,
+    pub asset_program_id: String,
+    pub master_mint: String,
+    pub master_metadata_pda: String,
+    pub master_edition_pda: String,
+    pub edition_mint: String,
+    pub edition_metadata_pda: String,
+    pub edition_pda: String,
+    pub edition_marker_pda: String,
+    pub metadata_uri: String,
+    pub blockhash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditionPrint {
+    pub canvas_id: Uuid,
+    pub edition_number: i32,
+    pub user_id: Uuid,
+    pub mint_address: Option<String>,
+    pub minted_at: Option<DateTime<Utc>>,
+}
+
+impl From<edition_print::Model> for EditionPrint {
+    fn from(value: edition_print::Model) -> Self {
+        EditionPrint {
+            canvas_id: value.canvas_id,
+            edition_number: value.edition_number,
+            user_id: value.user_id,
+            mint_address: value.mint_address,
+            minted_at: value.minted_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutRecipient {
+    pub user_id: Uuid,
+    pub wallet_address: Option<String>,
+    pub username: Option<String>,
+    pub amount_lamports: i64,
+    pub claimed: bool,
+    pub claim_signature: Option<String>,
+    pub claimed_at: Option<DateTime<Utc>>,
+}
+
+/// Progress of the escrow payouts released by a canvas's mint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutStatus {
+    pub canvas_id: Uuid,
+    pub mint_signature: String,
+    pub total_lamports: i64,
+    pub claimed_lamports: i64,
+    pub recipient_count: usize,
+    pub claimed_count: usize,
+    pub recipients: Vec<PayoutRecipient>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintStatus {
+    pub canvas_id: Uuid,
+    pub state: CanvasState,
+    pub mint_address: Option<String>,
+    pub mint_signature: Option<String>,
+    pub mint_submitted_at: Option<DateTime<Utc>>,
+    pub collection_verified: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +178,22 @@ pub struct NftMetadata {
     pub seller_fee_basis_points: u16,
     pub attributes: Vec<Attribute>,
     pub properties: Properties,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<NftCollection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftCollection {
+    pub name: String,
+    pub family: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionStatus {
+    pub canvas_id: Uuid,
+    pub collection_mint: Option<String>,
+    pub verified: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,3 +216,120 @@ pub struct ImageFile {
     #[serde(rename = "type")]
     pub file_type: String,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelapseAsset {
+    pub url: String,
+    pub format: TimelapseFormat,
+    pub content_hash: String,
+    pub frame_count: usize,
+    pub size_bytes: usize,
+    pub attached_to_metadata: bool,
+}
+
+/// Accounts for burning a minted canvas NFT. Token Metadata burns also close the
+/// metadata and master edition accounts and verify the collection size.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnTransactionInfo {
+    pub canvas_id: Uuid,
+    pub standard: NftStandard,
+    pub asset_program_id: String,
+    pub asset_address: String,
+    pub metadata_pda: Option<String>,
+    pub master_edition_pda: Option<String>,
+    pub collection_mint: Option<String>,
+    pub collection_metadata_pda: Option<String>,
+    pub blockhash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnResult {
+    pub canvas_id: Uuid,
+    pub state: CanvasState,
+    pub burned_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorPayout {
+    pub address: String,
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub share: u8,
+    pub total_lamports: i64,
+    pub payout_count: i64,
+    pub last_paid_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoyaltyPayouts {
+    pub canvas_id: Uuid,
+    pub total_lamports: i64,
+    pub creators: Vec<CreatorPayout>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PixelMismatch {
+    pub x: u8,
+    pub y: u8,
+    pub onchain_color: u8,
+    pub database_color: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldMismatch {
+    pub field: String,
+    pub onchain: String,
+    pub database: String,
+}
+
+/// What minting the canvas right now would produce.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintPreview {
+    pub canvas_id: Uuid,
+    pub metadata: NftMetadata,
+    pub image_url: String,
+    pub creators: Vec<CreatorOutput>,
+}
+
+/// Differences between the canvas PDA and the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    pub canvas_id: Uuid,
+    pub canvas_pda: String,
+    pub consistent: bool,
+    pub pixel_mismatches: Vec<PixelMismatch>,
+    pub field_mismatches: Vec<FieldMismatch>,
+}
+
+/// A canvas in a remix tree. `depth` counts steps away from the queried canvas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageNode {
+    pub canvas_id: Uuid,
+    pub parent_canvas_id: Option<Uuid>,
+    pub name: String,
+    pub state: CanvasState,
+    pub mint_address: Option<String>,
+    pub depth: u32,
+}
+
+impl LineageNode {
+    pub fn new(canvas: canvas::Model, depth: u32) -> Self {
+        Self {
+            canvas_id: canvas.id,
+            parent_canvas_id: canvas.parent_canvas_id,
+            name: canvas.name,
+            state: canvas.state,
+            mint_address: canvas.mint_address,
+            depth,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanvasLineage {
+    pub canvas: LineageNode,
+    /// Nearest parent first.
+    pub ancestors: Vec<LineageNode>,
+    /// Breadth-first, each with its `parent_canvas_id`.
+    pub descendants: Vec<LineageNode>,
+}
@@ -0,0 +1,213 @@
+use std::{str::FromStr, time::Duration};
+
+use chrono::Utc;
+use sea_orm::ActiveValue::Set;
+use solana_sdk::pubkey::Pubkey;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::{
+            entities::{
+                canvas::{self, CanvasState, NftStandard},
+                consumed_signature::SignatureOperation,
+            },
+            repositories::CanvasRepository,
+        },
+    },
+    services::{
+        nft::{
+            nft_symbol, royalty_bps, save_stored_metadata,
+            types::{MetadataUpdateInfo, MetadataUpdateResult, PendingMetadataUpdate},
+            upload_metadata,
+        },
+        solana::{
+            self,
+            client::{MPL_CORE_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID},
+            signatures,
+            transactions::{self, ExpectedInstruction, ExpectedOperation},
+        },
+    },
+};
+
+/// How long uploaded metadata waits for its update transaction.
+const PENDING_METADATA_UPDATE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Re-uploads the metadata of a minted canvas and returns the accounts for the update
+/// transaction. Nothing is recorded on the canvas until `confirm_metadata_update`.
+pub async fn initiate_metadata_update(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<MetadataUpdateInfo> {
+    let canvas = find_minted_canvas(state, canvas_id, user_id).await?;
+
+    let mint_address = canvas
+        .mint_address
+        .as_deref()
+        .and_then(|address| Pubkey::from_str(address).ok())
+        .ok_or(AppError::InvalidParams(
+            "Canvas has no valid mint address".into(),
+        ))?;
+
+    let (metadata, stored) = upload_metadata(state, canvas_id).await?;
+
+    let pending = PendingMetadataUpdate {
+        user_id,
+        metadata_uri: metadata.metadata_uri.clone(),
+        stored,
+    };
+    state
+        .cache
+        .redis
+        .set(
+            &CacheKey::metadata_update(&canvas_id),
+            &pending,
+            PENDING_METADATA_UPDATE_TTL,
+        )
+        .await?;
+
+    let (asset_program_id, metadata_pda) = match canvas.nft_standard {
+        NftStandard::TokenMetadata => (
+            TOKEN_METADATA_PROGRAM_ID,
+            Some(
+                state
+                    .solana_client
+                    .derive_metadata_pda(&mint_address)
+                    .0
+                    .to_string(),
+            ),
+        ),
+        NftStandard::Core => (MPL_CORE_PROGRAM_ID, None),
+    };
+
+    let blockhash = state
+        .solana_client
+        .get_recent_blockhash()
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    Ok(MetadataUpdateInfo {
+        canvas_id,
+        metadata,
+        standard: canvas.nft_standard,
+        asset_program_id: asset_program_id.to_string(),
+        asset_address: mint_address.to_string(),
+        metadata_pda,
+        blockhash: blockhash.to_string(),
+        symbol: nft_symbol(&canvas),
+        seller_fee_basis_points: royalty_bps(&canvas),
+        name: canvas.name,
+    })
+}
+
+/// Verifies the update transaction and records the uploaded metadata on the canvas.
+pub async fn confirm_metadata_update(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    signature: &str,
+) -> Result<MetadataUpdateResult> {
+    let canvas = find_minted_canvas(state, canvas_id, user_id).await?;
+
+    let pending_key = CacheKey::metadata_update(&canvas_id);
+    let pending: PendingMetadataUpdate = state
+        .cache
+        .redis
+        .get(&pending_key)
+        .await?
+        .filter(|pending: &PendingMetadataUpdate| pending.user_id == user_id)
+        .ok_or(AppError::InvalidParams(
+            "No pending metadata update for this canvas".into(),
+        ))?;
+
+    let mint_address = canvas
+        .mint_address
+        .as_deref()
+        .and_then(|address| Pubkey::from_str(address).ok())
+        .ok_or(AppError::InvalidParams(
+            "Canvas has no valid mint address".into(),
+        ))?;
+    let account = match canvas.nft_standard {
+        NftStandard::TokenMetadata => state.solana_client.derive_metadata_pda(&mint_address).0,
+        NftStandard::Core => mint_address,
+    };
+
+    let record = signatures::operation_record(
+        signature,
+        SignatureOperation::MetadataUpdate,
+        canvas_id,
+        user_id,
+    );
+    signatures::ensure_signature_unused(state, &record).await?;
+
+    let expected = ExpectedInstruction {
+        program: solana::canvas_program(state.solana_client.as_ref(), &canvas)?,
+        signer: transactions::user_wallet(state, user_id).await?,
+        canvas_id,
+        operation: ExpectedOperation::MetadataUpdate {
+            standard: canvas.nft_standard,
+            account,
+            uri: pending.metadata_uri.clone(),
+        },
+    };
+    let tx_valid = state
+        .solana_client
+        .verify_program_instruction(signature, &expected)
+        .await?;
+
+    if !tx_valid {
+        return Err(AppError::TransactionFailed(
+            "Transaction verification failed".into(),
+        ));
+    }
+
+    signatures::claim_signature(state, &record).await?;
+
+    let now = Utc::now();
+    match pending.stored {
+        Some(stored) => {
+            save_stored_metadata(state, canvas_id, stored, |active| {
+                active.metadata_updated_at = Set(Some(now));
+            })
+            .await?;
+        }
+        None => {
+            CanvasRepository::update_canvas(&state.db, canvas_id, |active| {
+                active.metadata_updated_at = Set(Some(now));
+            })
+            .await?;
+        }
+    }
+
+    state.cache.redis.delete(&pending_key).await?;
+
+    Ok(MetadataUpdateResult {
+        canvas_id,
+        metadata_uri: pending.metadata_uri,
+        metadata_updated_at: now,
+    })
+}
+
+async fn find_minted_canvas(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<canvas::Model> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    if canvas.owner_id != user_id {
+        return Err(AppError::NotCanvasOwner);
+    }
+
+    if canvas.state != CanvasState::Minted {
+        return Err(AppError::InvalidParams("Canvas is not minted".into()));
+    }
+
+    Ok(canvas)
+}
@@ -0,0 +1,190 @@
+use chrono::Utc;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::Result,
+    infrastructure::db::{
+        entities::{outbox_event, outbox_event::OutboxTopic},
+        repositories::{NewOutboxEvent, OutboxRepository, outbox::PixelEventPayload},
+    },
+    services::webhooks::{self, DeliveryOutcome, PendingDelivery, WebhookEvent},
+    ws::types::{RoomCanvasUpdate, RoomPixelUpdate},
+};
+
+/// Payload of a `Room` event.
+#[derive(Serialize, Deserialize)]
+struct RoomEventPayload {
+    canvas_id: Uuid,
+    update: RoomCanvasUpdate,
+}
+
+/// An update for the canvas's room, to write with the change it reports.
+pub fn room_event(canvas_id: Uuid, update: RoomCanvasUpdate) -> Result<NewOutboxEvent> {
+    Ok(NewOutboxEvent {
+        topic: OutboxTopic::Room,
+        payload: serde_json::to_value(RoomEventPayload { canvas_id, update })?,
+    })
+}
+
+/// Deliveries of `event` to every webhook endpoint, to write with the change it reports.
+pub fn webhook_events(state: &AppState, event: &WebhookEvent) -> Result<Vec<NewOutboxEvent>> {
+    state
+        .webhooks
+        .deliveries(event)?
+        .into_iter()
+        .map(|delivery| {
+            Ok(NewOutboxEvent {
+                topic: OutboxTopic::Webhook,
+                payload: serde_json::to_value(delivery)?,
+            })
+        })
+        .collect()
+}
+
+/// Queues webhook deliveries of an event that no database change goes with.
+pub async fn emit_webhook(state: &AppState, event: &WebhookEvent) -> Result<()> {
+    let events = webhook_events(state, event)?;
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    OutboxRepository::enqueue(state.db.get_connection(), events).await?;
+    state.db.wake_outbox();
+    Ok(())
+}
+
+/// Delivers outbox events until the runtime shuts down. Woken by events committed on this
+/// instance, and polls for those written elsewhere or due for another attempt.
+pub fn spawn_outbox_dispatcher(state: AppState) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let poll_interval = state.config.database.outbox_poll_interval;
+
+        loop {
+            if let Err(e) = dispatch_due_events(&state).await {
+                tracing::warn!(error = ?e, "Failed to dispatch outbox events");
+            }
+
+            tokio::select! {
+                _ = state.db.outbox_written() => {}
+                _ = tokio::time::sleep(poll_interval) => {}
+            }
+        }
+    })
+}
+
+/// Delivers every due event. Room updates go out in the order they were written; webhook
+/// deliveries of a batch are sent concurrently after them. Returns how many events were
+/// taken.
+pub async fn dispatch_due_events(state: &AppState) -> Result<usize> {
+    let batch_size = state.config.database.outbox_batch_size;
+    let lease = state.config.database.outbox_lease;
+    let mut dispatched = 0;
+
+    loop {
+        let events = OutboxRepository::claim_due(&state.db, batch_size, lease).await?;
+        let claimed = events.len();
+
+        let mut deliveries = Vec::new();
+        for event in events {
+            match event.topic {
+                OutboxTopic::Webhook => deliveries.push(event),
+                OutboxTopic::Room | OutboxTopic::Pixel => deliver_room_event(state, event).await?,
+            }
+        }
+
+        let sent = deliveries
+            .into_iter()
+            .map(|event| deliver_webhook(state, event));
+        join_all(sent)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        dispatched += claimed;
+        if (claimed as u64) < batch_size {
+            return Ok(dispatched);
+        }
+    }
+}
+
+async fn deliver_room_event(state: &AppState, event: outbox_event::Model) -> Result<()> {
+    let decoded = match event.topic {
+        OutboxTopic::Pixel => {
+            serde_json::from_value::<PixelEventPayload>(event.payload).map(|pixel| {
+                let update = RoomPixelUpdate {
+                    x: pixel.x as u8,
+                    y: pixel.y as u8,
+                    color: pixel.color as u8,
+                    owner_id: pixel.owner_id,
+                    price_lamports: pixel.owner_id.map(|_| pixel.price_lamports as u64),
+                };
+                (pixel.canvas_id, RoomCanvasUpdate::Pixel(update))
+            })
+        }
+        _ => serde_json::from_value::<RoomEventPayload>(event.payload)
+            .map(|room| (room.canvas_id, room.update)),
+    };
+
+    match decoded {
+        Ok((canvas_id, update)) => state.ws_rooms.broadcast(&canvas_id, update).await,
+        // It would fail the same way every time
+        Err(e) => tracing::error!(id = event.id, error = %e, "Dropping undecodable outbox event"),
+    }
+
+    OutboxRepository::remove(state.db.get_connection(), event.id).await
+}
+
+async fn deliver_webhook(state: &AppState, event: outbox_event::Model) -> Result<()> {
+    let db_connection = state.db.get_connection();
+
+    let delivery: PendingDelivery = match serde_json::from_value(event.payload) {
+        Ok(delivery) => delivery,
+        Err(e) => {
+            tracing::error!(id = event.id, error = %e, "Dropping undecodable outbox event");
+            return OutboxRepository::remove(db_connection, event.id).await;
+        }
+    };
+
+    // Claiming the event counted this attempt
+    let attempt = event.attempts as u32 + 1;
+
+    match state.webhooks.send(&delivery).await {
+        DeliveryOutcome::Delivered => OutboxRepository::remove(db_connection, event.id).await,
+        DeliveryOutcome::Rejected(error) => {
+            tracing::warn!(
+                url = %delivery.url,
+                event = %delivery.event,
+                attempt,
+                error = %error,
+                "Webhook rejected"
+            );
+            OutboxRepository::remove(db_connection, event.id).await
+        }
+        DeliveryOutcome::Retry(error) if attempt >= state.webhooks.max_attempts() => {
+            tracing::error!(
+                url = %delivery.url,
+                event = %delivery.event,
+                delivery_id = %delivery.delivery_id,
+                error = %error,
+                "Webhook delivery abandoned"
+            );
+            OutboxRepository::remove(db_connection, event.id).await
+        }
+        DeliveryOutcome::Retry(error) => {
+            tracing::warn!(
+                url = %delivery.url,
+                event = %delivery.event,
+                attempt,
+                error = %error,
+                "Webhook failed"
+            );
+            let retry_at = Utc::now()
+                + chrono::Duration::milliseconds(webhooks::retry_delay(attempt).as_millis() as i64);
+            OutboxRepository::retry_at(db_connection, event.id, retry_at, &error).await
+        }
+    }
+}
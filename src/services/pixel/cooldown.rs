@@ -1,11 +1,4 @@
-use std::time::Duration;
-
-use uuid::Uuid;
-
-use crate::{
-    error::{AppError, Result},
-    infrastructure::cache::{keys::CacheKey, redis::RedisCache},
-};
+use crate::error::{AppError, Result};
 
 pub fn get_current_time_ms() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -15,15 +8,9 @@ pub fn get_current_time_ms() -> u64 {
         .as_millis() as u64
 }
 
-/// Checks if the user is on cooldown. Returns error with remaining time if active.
-pub async fn check_cooldown_state(
-    redis: &RedisCache,
-    user_id: &Uuid,
-    cooldown_ms: u64,
-) -> Result<()> {
-    let key = CacheKey::cooldown(user_id);
-    if let Some(last_time) = redis.get::<u64>(&key).await? {
-        let now = get_current_time_ms();
+/// Checks a last action time already read from the cache.
+pub fn check_cooldown(last_time: Option<u64>, now: u64, cooldown_ms: u64) -> Result<()> {
+    if let Some(last_time) = last_time {
         let elapsed = now.saturating_sub(last_time);
         if elapsed < cooldown_ms {
             return Err(AppError::CooldownActive {
@@ -33,16 +20,3 @@ pub async fn check_cooldown_state(
     }
     Ok(())
 }
-
-/// Records the current time as the user's last action for cooldown purposes.
-pub async fn record_cooldown_state(
-    redis: &RedisCache,
-    user_id: &Uuid,
-    cooldown_ms: u64,
-) -> Result<()> {
-    let key = CacheKey::cooldown(user_id);
-    let now = get_current_time_ms();
-    redis
-        .set(&key, &now, Duration::from_millis(cooldown_ms))
-        .await
-}
@@ -3,23 +3,47 @@ use std::time::Duration;
 use uuid::Uuid;
 
 use crate::{
+    AppState,
     error::{AppError, Result},
-    infrastructure::cache::{keys::CacheKey, redis::RedisCache},
+    infrastructure::{
+        cache::{breaker::or_degraded, keys::CacheKey},
+        db::repositories::PixelLockRepository,
+    },
 };
 
+/// Who holds the lock on the pixel. Locks live in Redis, and in the database while Redis is
+/// unavailable. Locks taken in the database during an outage are not seen once Redis is
+/// back, which leaves at most one lock TTL where a pixel can be locked twice.
+async fn lock_holder(state: &AppState, canvas_id: &Uuid, x: u8, y: u8) -> Result<Option<String>> {
+    if state.cache.redis.is_available() {
+        let lock_key = CacheKey::pixel_lock(canvas_id, x, y);
+        match state.cache.redis.get::<String>(&lock_key).await {
+            Err(e) if e.is_cache_outage() => {}
+            holder => return holder,
+        }
+    }
+
+    let holder =
+        PixelLockRepository::find_holder(state.db.get_connection(), *canvas_id, x as i16, y as i16)
+            .await?;
+    Ok(holder.map(|holder| holder.to_string()))
+}
+
 /// Checks if the pixel is locked by another user.
 /// Returns Ok(()) if unlocked or locked by the same user.
 pub async fn assert_not_locked_by_other(
-    redis: &RedisCache,
+    state: &AppState,
     canvas_id: &Uuid,
     x: u8,
     y: u8,
     user_id: &Uuid,
 ) -> Result<()> {
-    let lock_key = CacheKey::pixel_lock(canvas_id, x, y);
-    if let Some(lock_holder) = redis.get::<String>(&lock_key).await?
-        && lock_holder != user_id.to_string()
-    {
+    check_lock_holder(lock_holder(state, canvas_id, x, y).await?, user_id)
+}
+
+/// Checks a lock holder already read from the cache.
+pub fn check_lock_holder(holder: Option<String>, user_id: &Uuid) -> Result<()> {
+    if holder.is_some_and(|holder| holder != user_id.to_string()) {
         return Err(AppError::PixelLocked);
     }
     Ok(())
@@ -27,14 +51,13 @@ pub async fn assert_not_locked_by_other(
 
 /// Asserts the user owns the lock. Returns error if no lock or different owner.
 pub async fn assert_lock_owned(
-    redis: &RedisCache,
+    state: &AppState,
     canvas_id: &Uuid,
     x: u8,
     y: u8,
     user_id: &Uuid,
 ) -> Result<()> {
-    let lock_key = CacheKey::pixel_lock(canvas_id, x, y);
-    match redis.get::<String>(&lock_key).await? {
+    match lock_holder(state, canvas_id, x, y).await? {
         Some(holder) if holder == user_id.to_string() => Ok(()),
         Some(_) => Err(AppError::InvalidParams(
             "This pixel is locked by another user".into(),
@@ -47,21 +70,33 @@ pub async fn assert_lock_owned(
 
 /// Attempts to acquire a lock on the pixel. Returns true if acquired.
 pub async fn acquire_pixel_lock(
-    redis: &RedisCache,
+    state: &AppState,
     canvas_id: &Uuid,
     x: u8,
     y: u8,
     user_id: &Uuid,
     ttl: Duration,
 ) -> Result<bool> {
-    let lock_key = CacheKey::pixel_lock(canvas_id, x, y);
-    redis
-        .setnx_with_value(&lock_key, &user_id.to_string(), ttl)
-        .await
+    if state.cache.redis.is_available() {
+        let lock_key = CacheKey::pixel_lock(canvas_id, x, y);
+        match state
+            .cache
+            .redis
+            .setnx_with_value(&lock_key, &user_id.to_string(), ttl)
+            .await
+        {
+            Err(e) if e.is_cache_outage() => {}
+            acquired => return acquired,
+        }
+    }
+
+    PixelLockRepository::try_acquire(&state.db, *canvas_id, x as i16, y as i16, *user_id, ttl).await
 }
 
-/// Releases the lock on the pixel.
-pub async fn release_pixel_lock(redis: &RedisCache, canvas_id: &Uuid, x: u8, y: u8) -> Result<()> {
+/// Releases the lock on the pixel, wherever it was taken.
+pub async fn release_pixel_lock(state: &AppState, canvas_id: &Uuid, x: u8, y: u8) -> Result<()> {
     let lock_key = CacheKey::pixel_lock(canvas_id, x, y);
-    redis.delete(&lock_key).await
+    or_degraded(state.cache.redis.delete(&lock_key).await, ())?;
+
+    PixelLockRepository::release(state.db.get_connection(), *canvas_id, x as i16, y as i16).await
 }
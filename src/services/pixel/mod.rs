@@ -1,41 +1,65 @@
-use std::time::Duration;
+use std::{collections::HashSet, str::FromStr, time::Duration};
+
+use chrono::Utc;
+use solana_sdk::pubkey::Pubkey;
 use uuid::Uuid;
 
 use crate::{
     AppState,
     error::{AppError, Result},
     infrastructure::{
-        cache::keys::CacheKey,
+        cache::{invalidation::Invalidation, keys::CacheKey},
         db::{
-            entities::canvas::{self, CanvasState},
-            repositories::{CanvasRepository, PixelRepository, UserRepository},
+            entities::{
+                canvas::{self, CanvasState},
+                consumed_signature::{self, SignatureOperation},
+                escrow_refund::RefundReason,
+                pixel,
+                user::UserRole,
+            },
+            repositories::{
+                CanvasRepository, ConsumedSignatureRepository, PixelRepository, UserRepository,
+                pixel::PixelWrite, pixel_history::PixelChangeSource,
+            },
         },
     },
     services::{
-        pixel::{cooldown::*, lock::*, types::*, validation::*},
-        solana,
+        auth::load_user_role,
+        canvas::{
+            cached_canvas, moderation::check_mute, palette::palette_size,
+            types::OwnedCanvasPixelInfo,
+        },
+        pixel::{
+            cooldown::*,
+            lock::*,
+            quota::{PixelQuota, check_quota_usage},
+            types::*,
+            validation::*,
+            watch::notify_pixel_watchers,
+        },
+        solana::{
+            self, escrow,
+            signatures::{self, consume_signature, find_consumed_signature, release_signature},
+            transactions::{self, ExpectedInstruction, ExpectedOperation},
+            wallet,
+        },
+        webhooks::helius::{self, PendingConfirmation},
     },
-    ws::types::{RoomCanvasUpdate, RoomPixelUpdate},
+    ws::types::{PixelWatchEvent, RoomCanvasUpdate, RoomPixelUpdate},
 };
 
 pub mod cooldown;
 pub mod lock;
+pub mod quota;
 pub mod types;
 pub mod validation;
+pub mod watch;
 
-/// Fetches canvas from local cache or database.
-async fn get_cached_canvas(state: &AppState, canvas_id: Uuid) -> Result<canvas::Model> {
-    if let Some(cached) = state.cache.local.get_canvas(&canvas_id).await {
-        return Ok((*cached).clone());
-    }
-    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
-        .await?
-        .ok_or(AppError::CanvasNotFound)?;
-    state.cache.local.set_canvas(canvas.clone()).await;
-    Ok(canvas)
-}
+/// Times a pixel write is retried after losing a compare-and-swap race.
+const MAX_PIXEL_WRITE_RETRIES: u32 = 3;
 
-async fn invalidate_pixel_caches(
+/// Writes the pixel into the cached canvas in place, locally and in Redis.
+async fn update_pixel_caches(
     state: &AppState,
     canvas_id: &Uuid,
     x: i16,
@@ -44,17 +68,42 @@ async fn invalidate_pixel_caches(
     owner_id: Option<Uuid>,
     price: i64,
 ) {
-    let cache_key = CacheKey::canvas_pixels(canvas_id);
-    let _ = tokio::join!(
+    let owned = owner_id.map(|owner_id| OwnedCanvasPixelInfo {
+        x,
+        y,
+        owner_id: owner_id.to_string(),
+        price_lamports: price,
+    });
+    let index = y as usize * state.config.canvas.width as usize + x as usize;
+
+    let (_, updated, _) = tokio::join!(
         state
             .cache
             .local
             .update_pixel(canvas_id, x, y, color, owner_id, price),
-        state.cache.redis.delete(&cache_key),
+        state.cache.redis.update_canvas_pixel(
+            canvas_id,
+            index,
+            (x, y, color as u8),
+            owned.as_ref()
+        ),
+        // Other instances drop their copy rather than patch it
+        state.cache.notify_instances(Invalidation::Pixels {
+            canvas_id: *canvas_id
+        }),
     );
+
+    // A copy that missed the write must not be served until it expires
+    if let Err(e) = updated {
+        tracing::warn!(canvas_id = %canvas_id, "Failed to update cached pixel: {e}");
+        let _ = state.cache.redis.delete_canvas_pixels(canvas_id).await;
+    }
 }
 
-async fn broadcast_pixel_update(
+/// Tells watchers of the pixel about a write. Its room hears of it through the outbox event
+/// the write queued.
+#[allow(clippy::too_many_arguments)]
+async fn notify_pixel_update(
     state: &AppState,
     canvas_id: &Uuid,
     x: i16,
@@ -62,6 +111,8 @@ async fn broadcast_pixel_update(
     color: i16,
     owner_id: Option<Uuid>,
     price: Option<u64>,
+    actor_id: Uuid,
+    event: PixelWatchEvent,
 ) {
     let update = RoomPixelUpdate {
         x: x as u8,
@@ -70,10 +121,63 @@ async fn broadcast_pixel_update(
         owner_id,
         price_lamports: price,
     };
-    state
-        .ws_rooms
-        .broadcast(canvas_id, RoomCanvasUpdate::Pixel(update))
-        .await;
+
+    notify_pixel_watchers(state, canvas_id, &update, actor_id, event).await;
+}
+
+/// Lists every pixel a user owns across all canvases, with their total investment.
+pub async fn list_owned_pixels(
+    state: &AppState,
+    user_id: Uuid,
+    cursor: Option<&str>,
+    limit: u64,
+) -> Result<OwnedPixelsPage> {
+    let db_connection = state.db.get_connection();
+
+    let (pixels, summary) = tokio::join!(
+        PixelRepository::find_pixels_by_owner(db_connection, user_id, cursor, limit),
+        PixelRepository::summarize_pixels_by_owner(db_connection, user_id),
+    );
+    let pixels = pixels?;
+    let (total, total_invested_lamports) = summary?;
+
+    Ok(OwnedPixelsPage {
+        pixels: pixels.items,
+        next_cursor: pixels.next_cursor,
+        total: total as u64,
+        total_invested_lamports,
+    })
+}
+
+/// Lists confirmed bids on a pixel, newest first.
+pub async fn list_pixel_bids(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    x: i16,
+    y: i16,
+    cursor: Option<&str>,
+    limit: u64,
+) -> Result<PixelBidsPage> {
+    let db_connection = state.db.get_connection();
+
+    if !CanvasRepository::is_canvas_collaborator(db_connection, canvas_id, user_id).await? {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    validate_pixel_coordinates(&state.config.canvas, x, y)?;
+
+    let (bids, total) = tokio::join!(
+        ConsumedSignatureRepository::find_pixel_bids(db_connection, canvas_id, x, y, cursor, limit),
+        ConsumedSignatureRepository::count_pixel_bids(db_connection, canvas_id, x, y),
+    );
+    let bids = bids?;
+
+    Ok(PixelBidsPage {
+        bids: bids.items,
+        next_cursor: bids.next_cursor,
+        total: total?,
+    })
 }
 
 pub async fn place_pixel(
@@ -92,12 +196,18 @@ pub async fn place_pixel(
     }
 
     validate_pixel_coordinates(&state.config.canvas, x, y)?;
-    validate_pixel_color(&state.config.canvas, color)?;
 
-    let canvas = get_cached_canvas(state, canvas_id).await?;
+    let canvas = cached_canvas(state, canvas_id).await?;
+    validate_pixel_color(
+        palette_size(&state.config.canvas, canvas.palette.as_ref()),
+        color,
+    )?;
 
     match canvas.state {
-        CanvasState::Draft => place_pixel_draft(state, canvas_id, user_id, x, y, color).await,
+        CanvasState::Draft => {
+            let limits = draft_limits(&canvas, user_id);
+            place_pixel_draft(state, canvas_id, user_id, x, y, color, limits).await
+        }
         CanvasState::Published => {
             let bid = bid_lamports.unwrap_or(0);
             place_pixel_bid(state, canvas_id, user_id, x, y, color, bid).await
@@ -111,6 +221,103 @@ pub async fn place_pixel(
     }
 }
 
+/// Resolves which Draft throttles apply to the user on this canvas. The admin exemption is
+/// left to `check_draft_placement`, which reads the role with the cooldown.
+fn draft_limits(canvas: &canvas::Model, user_id: Uuid) -> DraftLimits {
+    let is_owner = canvas.owner_id == user_id;
+
+    // Owners set the quota for their collaborators and are not bound by it
+    let quota = if is_owner {
+        None
+    } else {
+        PixelQuota::from_canvas(canvas.pixel_quota, canvas.pixel_quota_window_secs)
+    };
+
+    DraftLimits {
+        cooldown: !(is_owner && canvas.owner_cooldown_exempt),
+        quota,
+    }
+}
+
+/// Checks a Draft placement against the user's mute, the pixel and canvas locks and the
+/// throttles, all read from Redis in one round trip, along with the role that takes admins off
+/// the cooldown. While Redis is unavailable only the pixel lock is checked, against the
+/// database, rather than failing the placement.
+///
+/// Returns the current quota window's key and the placements already counted in it.
+async fn check_draft_placement(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    x: u8,
+    y: u8,
+    limits: &mut DraftLimits,
+) -> Result<Option<(String, u64)>> {
+    const MUTE: usize = 0;
+    const PIXEL_LOCK: usize = 1;
+    const CANVAS_LOCK: usize = 2;
+    const COOLDOWN: usize = 3;
+    const ROLE: usize = 4;
+    const QUOTA_CURRENT: usize = 5;
+    const QUOTA_PREVIOUS: usize = 6;
+
+    let now = get_current_time_ms();
+    let mut keys = vec![
+        CacheKey::canvas_mute(&canvas_id, &user_id),
+        CacheKey::pixel_lock(&canvas_id, x, y),
+        CacheKey::canvas_lock(&canvas_id),
+        CacheKey::cooldown(&user_id),
+        CacheKey::user_role(&user_id),
+    ];
+    if let Some(quota) = limits.quota {
+        let (current_key, previous_key) = quota.window_keys(&canvas_id, &user_id, now);
+        keys.extend([current_key, previous_key]);
+    }
+
+    let values = match state.cache.redis.mget(&keys).await {
+        Ok(values) => values,
+        Err(e) if e.is_cache_outage() => {
+            assert_not_locked_by_other(state, &canvas_id, x, y, &user_id).await?;
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    check_mute(values.get(MUTE)?)?;
+    check_lock_holder(values.get(PIXEL_LOCK)?, &user_id)?;
+
+    // Held while the canvas is being published
+    if values.contains(CANVAS_LOCK) {
+        return Err(AppError::InvalidParams(
+            "Canvas is being published. Pixel operations are temporarily blocked.".into(),
+        ));
+    }
+
+    if limits.cooldown {
+        let role = match values.get(ROLE)? {
+            Some(role) => role,
+            None => load_user_role(&state.db, &state.cache.redis, user_id).await?,
+        };
+        limits.cooldown = role != UserRole::Admin;
+    }
+    if limits.cooldown {
+        check_cooldown(values.get(COOLDOWN)?, now, state.config.canvas.cooldown_ms)?;
+    }
+
+    let Some(quota) = limits.quota else {
+        return Ok(None);
+    };
+    let quota_used = values.get(QUOTA_CURRENT)?.unwrap_or(0);
+    check_quota_usage(
+        quota,
+        now,
+        quota_used,
+        values.get(QUOTA_PREVIOUS)?.unwrap_or(0),
+    )?;
+
+    Ok(Some((keys.swap_remove(QUOTA_CURRENT), quota_used)))
+}
+
 async fn place_pixel_draft(
     state: &AppState,
     canvas_id: Uuid,
@@ -118,29 +325,65 @@ async fn place_pixel_draft(
     x: i16,
     y: i16,
     color: i16,
+    mut limits: DraftLimits,
 ) -> Result<PlacePixelResult> {
-    assert_not_locked_by_other(&state.cache.redis, &canvas_id, x as u8, y as u8, &user_id).await?;
+    let quota_window =
+        check_draft_placement(state, canvas_id, user_id, x as u8, y as u8, &mut limits).await?;
 
-    check_cooldown_state(
-        &state.cache.redis,
-        &user_id,
-        state.config.canvas.cooldown_ms,
+    let pixel = PixelRepository::upsert_pixel(
+        &state.db,
+        canvas_id,
+        x,
+        y,
+        PixelWrite {
+            color: Some(color),
+            ..Default::default()
+        },
+        &PixelChangeSource {
+            actor_id: Some(user_id),
+            signature: None,
+        },
     )
     .await?;
 
-    let pixel =
-        PixelRepository::upsert_pixel(&state.db, canvas_id, x, y, Some(color), None, None).await?;
+    // Cooldown and quota go out together. The quota counter is written from the count read
+    // during the check, so two placements racing from the same user may count as one.
+    let now = get_current_time_ms();
+    let mut throttles = Vec::with_capacity(2);
+    if limits.cooldown {
+        let cooldown_ms = state.config.canvas.cooldown_ms;
+        throttles.push((
+            CacheKey::cooldown(&user_id),
+            now,
+            Duration::from_millis(cooldown_ms),
+        ));
+    }
+    if let (Some(quota), Some((quota_key, quota_used))) = (limits.quota, quota_window) {
+        // Kept for two windows so the next window can still weigh this one
+        throttles.push((
+            quota_key,
+            quota_used + 1,
+            Duration::from_millis(quota.window_ms * 2),
+        ));
+    }
 
     let _ = tokio::join!(
-        async { invalidate_pixel_caches(state, &canvas_id, x, y, color, None, 0).await },
-        record_cooldown_state(
-            &state.cache.redis,
-            &user_id,
-            state.config.canvas.cooldown_ms
-        ),
+        async { update_pixel_caches(state, &canvas_id, x, y, color, None, 0).await },
+        state.cache.redis.mset(&throttles),
     );
 
-    broadcast_pixel_update(state, &canvas_id, x, y, color, None, None).await;
+    notify_pixel_update(
+        state,
+        &canvas_id,
+        x,
+        y,
+        color,
+        None,
+        None,
+        user_id,
+        PixelWatchEvent::Repainted,
+    )
+    .await;
 
     Ok(PlacePixelResult {
         x: pixel.x,
@@ -149,6 +392,8 @@ async fn place_pixel_draft(
         requires_confirmation: false,
         lock_expires_at: None,
         previous_owner_wallet: None,
+        transaction: None,
+        balance_check: None,
     })
 }
 
@@ -161,34 +406,53 @@ async fn place_pixel_bid(
     color: i16,
     bid_lamports: i64,
 ) -> Result<PlacePixelResult> {
-    validate_min_bid(&state.config.canvas, bid_lamports)?;
+    let canvas = cached_canvas(state, canvas_id).await?;
+    let bid_rules = BidRules::for_canvas(&state.config.canvas, &canvas);
+    validate_min_bid(&bid_rules, bid_lamports)?;
 
     let current_pixel =
         PixelRepository::find_pixel(state.db.get_connection(), canvas_id, x, y).await?;
 
     if let Some(ref pixel) = current_pixel {
-        validate_outbid(pixel.price_lamports, bid_lamports)?;
+        validate_outbid(&bid_rules, pixel.price_lamports, bid_lamports)?;
     }
 
     let previous_owner_wallet =
         if let Some(owner_id) = current_pixel.as_ref().and_then(|p| p.owner_id) {
-            UserRepository::find_user_by_id(state.db.get_connection(), owner_id)
+            UserRepository::find_user_by_id_including_deleted(state.db.get_connection(), owner_id)
                 .await?
                 .map(|user| user.wallet_address)
         } else {
             None
         };
 
-    let lock_ttl = Duration::from_millis(state.config.canvas.lock_ms);
-    let is_acquired = acquire_pixel_lock(
-        &state.cache.redis,
-        &canvas_id,
-        x as u8,
-        y as u8,
-        &user_id,
-        lock_ttl,
+    let bidder = transactions::user_wallet(state, user_id).await?;
+    let transaction = build_bid_transaction(
+        state,
+        &canvas,
+        &bidder,
+        (x, y, color),
+        bid_lamports,
+        previous_owner_wallet.as_deref(),
     )
     .await?;
+
+    // A failed balance lookup only skips the check, the bid can still go ahead.
+    let balance_check = if state.config.canvas.bid_balance_check {
+        match wallet::check_balance(state, &bidder, bid_lamports as u64).await {
+            Ok(check) => Some(check),
+            Err(e) => {
+                tracing::warn!(user_id = %user_id, error = ?e, "Failed to check bidder balance");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let lock_ttl = Duration::from_millis(state.config.canvas.lock_ms);
+    let is_acquired =
+        acquire_pixel_lock(state, &canvas_id, x as u8, y as u8, &user_id, lock_ttl).await?;
     if !is_acquired {
         return Err(AppError::PixelLocked);
     }
@@ -214,13 +478,63 @@ async fn place_pixel_bid(
         requires_confirmation: true,
         lock_expires_at: Some(lock_expires_at),
         previous_owner_wallet,
+        transaction: Some(transaction),
+        balance_check,
     })
 }
 
+async fn build_bid_transaction(
+    state: &AppState,
+    canvas: &canvas::Model,
+    bidder: &Pubkey,
+    (x, y, color): (i16, i16, i16),
+    bid_lamports: i64,
+    previous_owner_wallet: Option<&str>,
+) -> Result<String> {
+    let previous_owner = previous_owner_wallet
+        .map(Pubkey::from_str)
+        .transpose()
+        .map_err(|_| AppError::InternalServerError("Invalid stored wallet address".into()))?;
+
+    let blockhash = state
+        .solana_client
+        .get_recent_blockhash()
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    let program = solana::canvas_program(state.solana_client.as_ref(), canvas)?;
+    let instruction = transactions::place_pixel_bid_instruction(
+        &program,
+        bidder,
+        &canvas.id,
+        (x as u8, y as u8, color as u8),
+        bid_lamports as u64,
+        previous_owner.as_ref(),
+    );
+
+    Ok(transactions::build_unsigned_transaction(
+        &[instruction],
+        bidder,
+        blockhash,
+    ))
+}
+
 pub async fn confirm_pixel_bid(state: &AppState, req: ConfirmPixelRequest) -> Result<PixelInfo> {
-    validate_min_bid(&state.config.canvas, req.bid_lamports)?;
+    // Replays of an applied confirmation return its original result.
+    if let Some(record) = find_consumed_signature(state, &req.signature).await? {
+        return replay_confirmed_bid(&req, &record);
+    }
+
+    let canvas = cached_canvas(state, req.canvas_id).await?;
+    let bid_rules = BidRules::for_canvas(&state.config.canvas, &canvas);
+    validate_min_bid(&bid_rules, req.bid_lamports)?;
+    validate_pixel_color(
+        palette_size(&state.config.canvas, canvas.palette.as_ref()),
+        req.color,
+    )?;
+
     assert_lock_owned(
-        &state.cache.redis,
+        state,
         &req.canvas_id,
         req.x as u8,
         req.y as u8,
@@ -228,18 +542,34 @@ pub async fn confirm_pixel_bid(state: &AppState, req: ConfirmPixelRequest) -> Re
     )
     .await?;
 
-    if let Some(current) =
-        PixelRepository::find_pixel(state.db.get_connection(), req.canvas_id, req.x, req.y).await?
-    {
-        validate_outbid(current.price_lamports, req.bid_lamports)?;
+    let current =
+        PixelRepository::find_pixel(state.db.get_connection(), req.canvas_id, req.x, req.y).await?;
+    if let Some(ref current) = current {
+        validate_outbid(&bid_rules, current.price_lamports, req.bid_lamports)?;
     }
 
-    let is_valid = solana::verify_program_transaction(
-        state.solana_client.get_client(),
+    helius::track_pending_confirmation(
+        state,
         &req.signature,
-        state.solana_client.get_program_id(),
+        &PendingConfirmation::Bid(req.clone()),
     )
-    .await?;
+    .await;
+
+    let expected = ExpectedInstruction {
+        program: solana::canvas_program(state.solana_client.as_ref(), &canvas)?,
+        signer: transactions::user_wallet(state, req.user_id).await?,
+        canvas_id: req.canvas_id,
+        operation: ExpectedOperation::Bid {
+            x: req.x as u8,
+            y: req.y as u8,
+            color: req.color as u8,
+            bid_lamports: req.bid_lamports as u64,
+        },
+    };
+    let is_valid = state
+        .solana_client
+        .verify_program_instruction(&req.signature, &expected)
+        .await?;
 
     if !is_valid {
         return Err(AppError::TransactionFailed(
@@ -247,20 +577,38 @@ pub async fn confirm_pixel_bid(state: &AppState, req: ConfirmPixelRequest) -> Re
         ));
     }
 
-    let pixel = PixelRepository::upsert_pixel(
-        &state.db,
-        req.canvas_id,
-        req.x,
-        req.y,
-        Some(req.color),
-        Some(req.user_id),
-        Some(req.bid_lamports),
-    )
-    .await?;
+    let record = consumed_signature::Model {
+        signature: req.signature.clone(),
+        operation: SignatureOperation::PixelConfirm,
+        canvas_id: req.canvas_id,
+        user_id: req.user_id,
+        x: Some(req.x),
+        y: Some(req.y),
+        color: Some(req.color),
+        amount_lamports: Some(req.bid_lamports),
+        created_at: Utc::now(),
+    };
+    if !consume_signature(state, &record).await? {
+        // A concurrent confirm with the same signature claimed it first.
+        let record = find_consumed_signature(state, &req.signature)
+            .await?
+            .ok_or(AppError::SignatureAlreadyUsed)?;
+        return replay_confirmed_bid(&req, &record);
+    }
+
+    let (pixel, previous) = match write_confirmed_bid(state, &req, &bid_rules, current).await {
+        Ok(written) => written,
+        Err(e) => {
+            if let Err(release_error) = release_signature(state, &req.signature).await {
+                tracing::error!(error = %release_error, "Failed to release signature");
+            }
+            return Err(e);
+        }
+    };
 
     let _ = tokio::join!(
         async {
-            invalidate_pixel_caches(
+            update_pixel_caches(
                 state,
                 &req.canvas_id,
                 req.x,
@@ -271,10 +619,29 @@ pub async fn confirm_pixel_bid(state: &AppState, req: ConfirmPixelRequest) -> Re
             )
             .await
         },
-        release_pixel_lock(&state.cache.redis, &req.canvas_id, req.x as u8, req.y as u8),
+        release_pixel_lock(state, &req.canvas_id, req.x as u8, req.y as u8),
+        helius::clear_pending_confirmation(state, &req.signature),
     );
 
-    broadcast_pixel_update(
+    // The outbid price stays in the canvas escrow until its previous owner withdraws it.
+    if let Some(previous_owner) = previous.as_ref().and_then(|pixel| pixel.owner_id) {
+        let refunded = escrow::credit_refunds(
+            state,
+            req.canvas_id,
+            RefundReason::Outbid,
+            &req.signature,
+            &[(
+                previous_owner,
+                previous.as_ref().map_or(0, |pixel| pixel.price_lamports),
+            )],
+        )
+        .await;
+        if let Err(e) = refunded {
+            tracing::error!(signature = %req.signature, error = ?e, "Failed to record outbid refund");
+        }
+    }
+
+    notify_pixel_update(
         state,
         &req.canvas_id,
         req.x,
@@ -282,6 +649,8 @@ pub async fn confirm_pixel_bid(state: &AppState, req: ConfirmPixelRequest) -> Re
         req.color,
         Some(req.user_id),
         Some(pixel.price_lamports as u64),
+        req.user_id,
+        PixelWatchEvent::Outbid,
     )
     .await;
 
@@ -305,6 +674,77 @@ pub async fn confirm_pixel_bid(state: &AppState, req: ConfirmPixelRequest) -> Re
     })
 }
 
+/// Writes a confirmed bid, re-validating it against any write that beats it to the pixel.
+/// Returns the written pixel and the pixel it replaced.
+async fn write_confirmed_bid(
+    state: &AppState,
+    req: &ConfirmPixelRequest,
+    bid_rules: &BidRules,
+    mut current: Option<pixel::Model>,
+) -> Result<(pixel::Model, Option<pixel::Model>)> {
+    let source = PixelChangeSource {
+        actor_id: Some(req.user_id),
+        signature: Some(req.signature.clone()),
+    };
+    let mut retries = 0;
+
+    loop {
+        let write = PixelWrite {
+            color: Some(req.color),
+            owner_id: Some(req.user_id),
+            price_lamports: Some(req.bid_lamports),
+            expected_version: current.as_ref().map(|pixel| pixel.version),
+        };
+
+        match PixelRepository::upsert_pixel(&state.db, req.canvas_id, req.x, req.y, write, &source)
+            .await
+        {
+            Err(AppError::PixelVersionConflict) if retries < MAX_PIXEL_WRITE_RETRIES => {
+                retries += 1;
+
+                // Another confirm landed first. The bid must still beat it.
+                current = PixelRepository::find_pixel(
+                    state.db.get_connection(),
+                    req.canvas_id,
+                    req.x,
+                    req.y,
+                )
+                .await?;
+                if let Some(ref current) = current {
+                    validate_outbid(bid_rules, current.price_lamports, req.bid_lamports)?;
+                }
+            }
+            result => return result.map(|pixel| (pixel, current)),
+        }
+    }
+}
+
+/// Returns the original result for a signature already used to confirm this exact bid.
+fn replay_confirmed_bid(
+    req: &ConfirmPixelRequest,
+    record: &consumed_signature::Model,
+) -> Result<PixelInfo> {
+    let is_same_bid = record.operation == SignatureOperation::PixelConfirm
+        && record.canvas_id == req.canvas_id
+        && record.user_id == req.user_id
+        && record.x == Some(req.x)
+        && record.y == Some(req.y)
+        && record.color == Some(req.color)
+        && record.amount_lamports == Some(req.bid_lamports);
+
+    if !is_same_bid {
+        return Err(AppError::SignatureAlreadyUsed);
+    }
+
+    Ok(PixelInfo {
+        x: req.x,
+        y: req.y,
+        color: req.color,
+        owner_id: Some(req.user_id),
+        price_lamports: req.bid_lamports,
+    })
+}
+
 pub async fn cancel_pixel_bid(
     state: &AppState,
     canvas_id: Uuid,
@@ -312,8 +752,8 @@ pub async fn cancel_pixel_bid(
     x: i16,
     y: i16,
 ) -> Result<()> {
-    assert_lock_owned(&state.cache.redis, &canvas_id, x as u8, y as u8, &user_id).await?;
-    release_pixel_lock(&state.cache.redis, &canvas_id, x as u8, y as u8).await?;
+    assert_lock_owned(state, &canvas_id, x as u8, y as u8, &user_id).await?;
+    release_pixel_lock(state, &canvas_id, x as u8, y as u8).await?;
 
     state
         .ws_rooms
@@ -338,6 +778,12 @@ pub async fn paint_pixel(
     color: i16,
     signature: &str,
 ) -> Result<PixelInfo> {
+    let canvas = cached_canvas(state, canvas_id).await?;
+    validate_pixel_color(
+        palette_size(&state.config.canvas, canvas.palette.as_ref()),
+        color,
+    )?;
+
     let pixel = PixelRepository::find_pixel(state.db.get_connection(), canvas_id, x, y)
         .await?
         .ok_or(AppError::InvalidParams("Pixel not found".into()))?;
@@ -346,12 +792,31 @@ pub async fn paint_pixel(
         return Err(AppError::Unauthorized);
     }
 
-    let is_valid = solana::verify_program_transaction(
-        state.solana_client.get_client(),
-        signature,
-        state.solana_client.get_program_id(),
-    )
-    .await?;
+    let record = consumed_signature::Model {
+        x: Some(x),
+        y: Some(y),
+        color: Some(color),
+        ..signatures::operation_record(
+            signature,
+            SignatureOperation::PixelPaint,
+            canvas_id,
+            user_id,
+        )
+    };
+    signatures::ensure_signature_unused(state, &record).await?;
+
+    let expected = ExpectedInstruction {
+        program: solana::canvas_program(state.solana_client.as_ref(), &canvas)?,
+        signer: transactions::user_wallet(state, user_id).await?,
+        canvas_id,
+        operation: ExpectedOperation::Paint {
+            pixels: vec![(x as u8, y as u8, color as u8)],
+        },
+    };
+    let is_valid = state
+        .solana_client
+        .verify_program_instruction(signature, &expected)
+        .await?;
 
     if !is_valid {
         return Err(AppError::TransactionFailed(
@@ -359,10 +824,19 @@ pub async fn paint_pixel(
         ));
     }
 
-    let updated =
-        PixelRepository::upsert_pixel(&state.db, canvas_id, x, y, Some(color), None, None).await?;
+    signatures::claim_signature(state, &record).await?;
 
-    invalidate_pixel_caches(
+    let updated = match write_repainted_pixel(state, pixel, user_id, color, signature).await {
+        Ok(updated) => updated,
+        Err(e) => {
+            if let Err(release_error) = release_signature(state, signature).await {
+                tracing::error!(error = %release_error, "Failed to release signature");
+            }
+            return Err(e);
+        }
+    };
+
+    update_pixel_caches(
         state,
         &canvas_id,
         x,
@@ -372,7 +846,7 @@ pub async fn paint_pixel(
         updated.price_lamports,
     )
     .await;
-    broadcast_pixel_update(
+    notify_pixel_update(
         state,
         &canvas_id,
         x,
@@ -380,6 +854,8 @@ pub async fn paint_pixel(
         color,
         updated.owner_id,
         Some(updated.price_lamports as u64),
+        user_id,
+        PixelWatchEvent::Repainted,
     )
     .await;
 
@@ -391,3 +867,224 @@ pub async fn paint_pixel(
         price_lamports: updated.price_lamports,
     })
 }
+
+/// Writes a verified repaint, re-checking ownership if a bid lands on the pixel first.
+async fn write_repainted_pixel(
+    state: &AppState,
+    mut pixel: pixel::Model,
+    user_id: Uuid,
+    color: i16,
+    signature: &str,
+) -> Result<pixel::Model> {
+    let (canvas_id, x, y) = (pixel.canvas_id, pixel.x, pixel.y);
+    let source = PixelChangeSource {
+        actor_id: Some(user_id),
+        signature: Some(signature.to_string()),
+    };
+    let mut retries = 0;
+
+    loop {
+        let write = PixelWrite {
+            color: Some(color),
+            expected_version: Some(pixel.version),
+            ..Default::default()
+        };
+
+        match PixelRepository::upsert_pixel(&state.db, canvas_id, x, y, write, &source).await {
+            Err(AppError::PixelVersionConflict) if retries < MAX_PIXEL_WRITE_RETRIES => {
+                retries += 1;
+
+                // The pixel may have been outbid while the transaction was verified.
+                pixel = PixelRepository::find_pixel(state.db.get_connection(), canvas_id, x, y)
+                    .await?
+                    .ok_or(AppError::InvalidParams("Pixel not found".into()))?;
+                if pixel.owner_id != Some(user_id) {
+                    return Err(AppError::Unauthorized);
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Recolors several owned pixels on a published canvas, backed by a single
+/// on-chain transaction instead of one signature per pixel.
+pub async fn paint_pixel_batch(
+    state: &AppState,
+    req: PaintPixelBatchRequest,
+) -> Result<Vec<PixelInfo>> {
+    // Reject early so the user isn't made to wait on chain verification for a doomed batch.
+    let canvas = check_paint_batch(state, req.canvas_id, req.user_id, &req.pixels).await?;
+
+    let record = signatures::operation_record(
+        &req.signature,
+        SignatureOperation::PixelPaint,
+        req.canvas_id,
+        req.user_id,
+    );
+    signatures::ensure_signature_unused(state, &record).await?;
+
+    let expected = ExpectedInstruction {
+        program: solana::canvas_program(state.solana_client.as_ref(), &canvas)?,
+        signer: transactions::user_wallet(state, req.user_id).await?,
+        canvas_id: req.canvas_id,
+        operation: ExpectedOperation::Paint {
+            pixels: req
+                .pixels
+                .iter()
+                .map(|&(x, y, color)| (x as u8, y as u8, color as u8))
+                .collect(),
+        },
+    };
+    let is_valid = state
+        .solana_client
+        .verify_program_instruction(&req.signature, &expected)
+        .await?;
+
+    if !is_valid {
+        return Err(AppError::TransactionFailed(
+            "Transaction verification failed".into(),
+        ));
+    }
+
+    signatures::claim_signature(state, &record).await?;
+
+    let updated_pixels = match PixelRepository::update_owned_pixel_colors(
+        &state.db,
+        req.canvas_id,
+        req.user_id,
+        &req.pixels,
+        &req.signature,
+    )
+    .await
+    {
+        Ok(updated_pixels) => updated_pixels,
+        Err(e) => {
+            if let Err(release_error) = release_signature(state, &req.signature).await {
+                tracing::error!(error = %release_error, "Failed to release signature");
+            }
+            return Err(e);
+        }
+    };
+
+    for pixel in &updated_pixels {
+        update_pixel_caches(
+            state,
+            &req.canvas_id,
+            pixel.x,
+            pixel.y,
+            pixel.color,
+            pixel.owner_id,
+            pixel.price_lamports,
+        )
+        .await;
+        notify_pixel_update(
+            state,
+            &req.canvas_id,
+            pixel.x,
+            pixel.y,
+            pixel.color,
+            pixel.owner_id,
+            Some(pixel.price_lamports as u64),
+            req.user_id,
+            PixelWatchEvent::Repainted,
+        )
+        .await;
+    }
+
+    Ok(updated_pixels
+        .into_iter()
+        .map(|pixel| PixelInfo {
+            x: pixel.x,
+            y: pixel.y,
+            color: pixel.color,
+            owner_id: pixel.owner_id,
+            price_lamports: pixel.price_lamports,
+        })
+        .collect())
+}
+
+/// Returns an unsigned `paint_pixels` transaction for owned pixels on a published canvas,
+/// to sign and pass to `pixel.paint` or `pixel.paintBatch`.
+pub async fn prepare_paint(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    pixels: &[(i16, i16, i16)],
+) -> Result<String> {
+    let canvas = check_paint_batch(state, canvas_id, user_id, pixels).await?;
+    let program = solana::canvas_program(state.solana_client.as_ref(), &canvas)?;
+
+    let owner = transactions::user_wallet(state, user_id).await?;
+    let blockhash = state
+        .solana_client
+        .get_recent_blockhash()
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    let pixels: Vec<(u8, u8, u8)> = pixels
+        .iter()
+        .map(|&(x, y, color)| (x as u8, y as u8, color as u8))
+        .collect();
+    let instruction = transactions::paint_pixels_instruction(&program, &owner, &canvas_id, &pixels);
+
+    Ok(transactions::build_unsigned_transaction(
+        &[instruction],
+        &owner,
+        blockhash,
+    ))
+}
+
+/// Checks a repaint of owned pixels on a published canvas before anything goes on-chain.
+async fn check_paint_batch(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    pixels: &[(i16, i16, i16)],
+) -> Result<canvas::Model> {
+    if pixels.is_empty() {
+        return Err(AppError::InvalidParams("No pixels to paint".into()));
+    }
+
+    let max_batch = state.config.canvas.paint_batch_max;
+    if pixels.len() > max_batch {
+        return Err(AppError::InvalidParams(format!(
+            "Cannot paint more than {max_batch} pixels at once"
+        )));
+    }
+
+    let canvas = cached_canvas(state, canvas_id).await?;
+    let palette_size = palette_size(&state.config.canvas, canvas.palette.as_ref());
+
+    let mut seen = HashSet::with_capacity(pixels.len());
+    for &(x, y, color) in pixels {
+        validate_pixel_coordinates(&state.config.canvas, x, y)?;
+        validate_pixel_color(palette_size, color)?;
+
+        if !seen.insert((x, y)) {
+            return Err(AppError::InvalidParams(format!(
+                "Duplicate pixel ({x}, {y}) in batch"
+            )));
+        }
+    }
+
+    if canvas.state != CanvasState::Published {
+        return Err(AppError::InvalidParams(
+            "Pixels can only be repainted on published canvases".into(),
+        ));
+    }
+
+    let owned: HashSet<(i16, i16)> =
+        PixelRepository::find_owned_pixels_by_canvas(state.db.get_connection(), canvas_id)
+            .await?
+            .into_iter()
+            .filter(|pixel| pixel.owner_id == Some(user_id))
+            .map(|pixel| (pixel.x, pixel.y))
+            .collect();
+
+    if !seen.is_subset(&owned) {
+        return Err(AppError::Unauthorized);
+    }
+
+    Ok(canvas)
+}
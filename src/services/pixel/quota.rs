@@ -0,0 +1,51 @@
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, Result},
+    infrastructure::cache::keys::CacheKey,
+};
+
+/// Owner-configured pixel quota for a Draft canvas.
+#[derive(Debug, Clone, Copy)]
+pub struct PixelQuota {
+    pub limit: u32,
+    pub window_ms: u64,
+}
+
+impl PixelQuota {
+    pub fn from_canvas(limit: Option<i32>, window_secs: Option<i32>) -> Option<Self> {
+        match (limit, window_secs) {
+            (Some(limit), Some(window_secs)) if limit > 0 && window_secs > 0 => Some(Self {
+                limit: limit as u32,
+                window_ms: window_secs as u64 * 1000,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Keys of the current and previous windows' counters at `now`.
+    pub fn window_keys(&self, canvas_id: &Uuid, user_id: &Uuid, now: u64) -> (String, String) {
+        let window_index = now / self.window_ms;
+        (
+            CacheKey::pixel_quota(canvas_id, user_id, window_index),
+            CacheKey::pixel_quota(canvas_id, user_id, window_index.saturating_sub(1)),
+        )
+    }
+}
+
+/// Checks window counts already read from the cache.
+pub fn check_quota_usage(quota: PixelQuota, now: u64, current: u64, previous: u64) -> Result<()> {
+    let elapsed_in_window = now % quota.window_ms;
+    let remaining_in_window = quota.window_ms - elapsed_in_window;
+    let estimated = current + previous * remaining_in_window / quota.window_ms;
+
+    if estimated >= quota.limit as u64 {
+        return Err(AppError::PixelQuotaExceeded {
+            limit: quota.limit,
+            window_secs: quota.window_ms / 1000,
+            reset_in_ms: remaining_in_window,
+        });
+    }
+
+    Ok(())
+}
@@ -1,6 +1,19 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{
+    infrastructure::db::repositories::{consumed_signature::PixelBidRow, pixel::OwnedPixelRow},
+    services::{pixel::quota::PixelQuota, solana::wallet::BalanceCheck},
+};
+
+/// Throttles applied to a Draft placement for a given user.
+#[derive(Debug, Clone, Copy)]
+pub struct DraftLimits {
+    /// Cleared for admins once their role is read, alongside the cooldown itself.
+    pub cooldown: bool,
+    pub quota: Option<PixelQuota>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlacePixelResult {
     pub x: i16,
@@ -9,6 +22,10 @@ pub struct PlacePixelResult {
     pub requires_confirmation: bool,
     pub lock_expires_at: Option<u64>,
     pub previous_owner_wallet: Option<String>,
+    /// Base64 unsigned `place_pixel_bid` transaction, when confirmation is required.
+    pub transaction: Option<String>,
+    /// Whether the bidder can pay for `transaction`, when `BID_BALANCE_CHECK` is on.
+    pub balance_check: Option<BalanceCheck>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +37,14 @@ pub struct PixelInfo {
     pub price_lamports: i64,
 }
 
+pub struct PaintPixelBatchRequest {
+    pub canvas_id: Uuid,
+    pub user_id: Uuid,
+    pub pixels: Vec<(i16, i16, i16)>,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfirmPixelRequest {
     pub canvas_id: Uuid,
     pub user_id: Uuid,
@@ -43,3 +68,17 @@ pub struct BidPixelInfo {
     pub min_bid: i64,
     pub blockhash: String,
 }
+
+#[derive(Debug, Clone)]
+pub struct OwnedPixelsPage {
+    pub pixels: Vec<OwnedPixelRow>,
+    pub next_cursor: Option<String>,
+    pub total: u64,
+    pub total_invested_lamports: i64,
+}
+
+pub struct PixelBidsPage {
+    pub bids: Vec<PixelBidRow>,
+    pub next_cursor: Option<String>,
+    pub total: u64,
+}
@@ -1,6 +1,7 @@
 use crate::{
     config::CanvasConfig,
     error::{AppError, Result},
+    infrastructure::db::entities::canvas,
 };
 
 pub fn validate_pixel_coordinates(config: &CanvasConfig, x: i16, y: i16) -> Result<()> {
@@ -10,27 +11,62 @@ pub fn validate_pixel_coordinates(config: &CanvasConfig, x: i16, y: i16) -> Resu
     Ok(())
 }
 
-pub fn validate_pixel_color(config: &CanvasConfig, color: i16) -> Result<()> {
-    if color < 0 || color >= config.color_count as i16 {
+/// Validates a color index against the canvas palette size.
+pub fn validate_pixel_color(palette_size: usize, color: i16) -> Result<()> {
+    if color < 0 || color as usize >= palette_size {
         return Err(AppError::InvalidParams("Invalid color".into()));
     }
     Ok(())
 }
 
-pub fn validate_min_bid(config: &CanvasConfig, bid_lamports: i64) -> Result<()> {
-    if (bid_lamports as u64) < config.min_bid_lamports {
+/// Bid limits for a canvas, combining owner settings with the global minimum.
+#[derive(Debug, Clone, Copy)]
+pub struct BidRules {
+    pub floor_lamports: u64,
+    pub step_lamports: u64,
+    pub ceiling_lamports: Option<u64>,
+}
+
+impl BidRules {
+    pub fn for_canvas(config: &CanvasConfig, canvas: &canvas::Model) -> Self {
+        Self {
+            floor_lamports: canvas
+                .bid_floor_lamports
+                .map_or(config.min_bid_lamports, |floor| {
+                    (floor as u64).max(config.min_bid_lamports)
+                }),
+            step_lamports: canvas
+                .bid_step_lamports
+                .map_or(1, |step| step.max(1) as u64),
+            ceiling_lamports: canvas.bid_ceiling_lamports.map(|ceiling| ceiling as u64),
+        }
+    }
+}
+
+pub fn validate_min_bid(rules: &BidRules, bid_lamports: i64) -> Result<()> {
+    if (bid_lamports as u64) < rules.floor_lamports {
         return Err(AppError::BidTooLow {
-            min_lamports: config.min_bid_lamports,
+            min_lamports: rules.floor_lamports,
+            min_usd: None,
+        });
+    }
+    if let Some(ceiling) = rules.ceiling_lamports
+        && bid_lamports as u64 > ceiling
+    {
+        return Err(AppError::BidTooHigh {
+            max_lamports: ceiling,
+            max_usd: None,
         });
     }
     Ok(())
 }
 
-pub fn validate_outbid(current_price: i64, bid_lamports: i64) -> Result<()> {
-    let min_required = current_price + 1;
-    if bid_lamports < min_required {
+pub fn validate_outbid(rules: &BidRules, current_price: i64, bid_lamports: i64) -> Result<()> {
+    let min_required = current_price as u64 + rules.step_lamports;
+    if (bid_lamports as u64) < min_required {
         return Err(AppError::BidTooLow {
-            min_lamports: min_required as u64,
+            min_lamports: min_required,
+            min_usd: None,
         });
     }
     Ok(())
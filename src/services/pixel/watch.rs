@@ -0,0 +1,113 @@
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::local::PixelWatchers,
+        db::repositories::{CanvasRepository, PixelWatchRepository},
+    },
+    services::pixel::validation::validate_pixel_coordinates,
+    ws::types::{PixelWatchEvent, RoomCanvasUpdate, RoomPixelUpdate},
+};
+
+/// Subscribes the user to change notifications for a single pixel.
+pub async fn watch_pixel(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    x: i16,
+    y: i16,
+) -> Result<()> {
+    validate_pixel_coordinates(&state.config.canvas, x, y)?;
+
+    let db_connection = state.db.get_connection();
+
+    if !CanvasRepository::is_canvas_collaborator(db_connection, canvas_id, user_id).await? {
+        return Err(AppError::NotCanvasCollaborator);
+    }
+
+    let max_watches = state.config.canvas.max_pixel_watches;
+    if PixelWatchRepository::count_watches_by_user(db_connection, user_id).await? >= max_watches {
+        return Err(AppError::InvalidParams(format!(
+            "Cannot watch more than {max_watches} pixels"
+        )));
+    }
+
+    PixelWatchRepository::add_pixel_watch(db_connection, user_id, canvas_id, x, y).await?;
+    state.cache.invalidate_pixel_watchers(&canvas_id).await;
+    Ok(())
+}
+
+pub async fn unwatch_pixel(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    x: i16,
+    y: i16,
+) -> Result<()> {
+    PixelWatchRepository::remove_pixel_watch(state.db.get_connection(), user_id, canvas_id, x, y)
+        .await?;
+    state.cache.invalidate_pixel_watchers(&canvas_id).await;
+    Ok(())
+}
+
+/// Notifies connected watchers of a pixel (other than the user who changed it). Watches are
+/// read from the local cache, so a broadcast only reaches the database once per canvas until
+/// the entry expires or a watch changes.
+pub async fn notify_pixel_watchers(
+    state: &AppState,
+    canvas_id: &Uuid,
+    update: &RoomPixelUpdate,
+    actor_id: Uuid,
+    event: PixelWatchEvent,
+) {
+    // Watchers are only reached through their connections to this instance
+    if !state.ws_rooms.has_user_channels().await {
+        return;
+    }
+
+    let watchers = match state
+        .cache
+        .local
+        .get_or_load_pixel_watchers(*canvas_id, || load_pixel_watchers(state, *canvas_id))
+        .await
+    {
+        Ok(watchers) => watchers,
+        Err(e) => {
+            tracing::warn!(error = ?e, "Failed to load pixel watchers");
+            return;
+        }
+    };
+    let Some(pixel_watchers) = watchers.get(&(update.x as i16, update.y as i16)) else {
+        return;
+    };
+
+    for &watcher_id in pixel_watchers.iter().filter(|id| **id != actor_id) {
+        state
+            .ws_rooms
+            .notify_user(
+                &watcher_id,
+                RoomCanvasUpdate::WatchedPixelChanged {
+                    canvas_id: *canvas_id,
+                    x: update.x,
+                    y: update.y,
+                    event,
+                    color: update.color,
+                    owner_id: update.owner_id,
+                    price_lamports: update.price_lamports,
+                },
+            )
+            .await;
+    }
+}
+
+async fn load_pixel_watchers(state: &AppState, canvas_id: Uuid) -> Result<PixelWatchers> {
+    let mut watchers = PixelWatchers::new();
+    for (user_id, x, y) in
+        PixelWatchRepository::find_canvas_watches(state.db.get_connection(), canvas_id).await?
+    {
+        watchers.entry((x, y)).or_default().push(user_id);
+    }
+    Ok(watchers)
+}
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::{
+    error::{AppError, Result},
+    services::price::PriceSource,
+};
+
+/// SOL/USD from the CoinGecko simple price API.
+pub struct CoinGeckoSource {
+    http: Client,
+    api_url: String,
+}
+
+impl CoinGeckoSource {
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            http: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoSource {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn fetch_sol_usd(&self) -> Result<f64> {
+        let response = self
+            .http
+            .get(format!("{}/simple/price", self.api_url))
+            .query(&[("ids", "solana"), ("vs_currencies", "usd")])
+            .send()
+            .await
+            .map_err(|e| AppError::PriceOracleError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::PriceOracleError(format!(
+                "CoinGecko returned {}: {}",
+                status, body
+            )));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::PriceOracleError(e.to_string()))?;
+
+        body["solana"]["usd"]
+            .as_f64()
+            .filter(|rate| *rate > 0.0)
+            .ok_or(AppError::PriceOracleError(
+                "CoinGecko response has no SOL/USD price".into(),
+            ))
+    }
+}
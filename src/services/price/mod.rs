@@ -0,0 +1,98 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    AppState,
+    config::PriceConfig,
+    error::{AppError, Result},
+    infrastructure::cache::keys::CacheKey,
+};
+
+pub mod coingecko;
+pub mod pyth;
+
+pub use coingecko::CoinGeckoSource;
+pub use pyth::PythSource;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Oracle queried for the SOL/USD rate.
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Short oracle name for logs, e.g. `pyth`.
+    fn name(&self) -> &'static str;
+
+    /// USD per SOL.
+    async fn fetch_sol_usd(&self) -> Result<f64>;
+}
+
+/// Builds the oracle selected by `PRICE_PROVIDER`. `None` leaves USD amounts out.
+pub fn from_config(config: &PriceConfig) -> Result<Option<Arc<dyn PriceSource>>> {
+    match config.provider.as_str() {
+        "pyth" => Ok(Some(Arc::new(PythSource::new(
+            &config.pyth_api_url,
+            &config.pyth_sol_usd_feed_id,
+        )))),
+        "coingecko" => Ok(Some(Arc::new(CoinGeckoSource::new(
+            &config.coingecko_api_url,
+        )))),
+        "none" => Ok(None),
+        other => Err(AppError::InvalidParams(format!(
+            "Unknown PRICE_PROVIDER {other}, expected pyth, coingecko or none"
+        ))),
+    }
+}
+
+/// Cached SOL/USD rate. `None` when no oracle is configured or it cannot be reached,
+/// since USD amounts are informational and never block a request.
+pub async fn sol_usd(state: &AppState) -> Option<f64> {
+    let source = state.price_source.as_ref()?;
+
+    let cache_key = CacheKey::sol_usd_price();
+    match state.cache.redis.get::<f64>(&cache_key).await {
+        Ok(Some(rate)) => return Some(rate),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = ?e, "Failed to read cached SOL/USD price"),
+    }
+
+    let rate = match source.fetch_sol_usd().await {
+        Ok(rate) => rate,
+        Err(e) => {
+            tracing::warn!(source = source.name(), error = ?e, "Failed to fetch SOL/USD price");
+            return None;
+        }
+    };
+
+    let ttl = Duration::from_secs(state.config.price.cache_ttl_secs);
+    if let Err(e) = state.cache.redis.set(&cache_key, &rate, ttl).await {
+        tracing::warn!(error = ?e, "Failed to cache SOL/USD price");
+    }
+
+    Some(rate)
+}
+
+/// USD value of `lamports` at `rate`, rounded to a hundredth of a cent.
+pub fn lamports_to_usd(lamports: i64, rate: f64) -> f64 {
+    (lamports as f64 / LAMPORTS_PER_SOL * rate * 10_000.0).round() / 10_000.0
+}
+
+/// Adds the USD equivalent of the limit to bid errors, so clients can show what the
+/// minimum or maximum costs.
+pub async fn price_bid_error(state: &AppState, error: AppError) -> AppError {
+    match error {
+        AppError::BidTooLow { min_lamports, .. } => AppError::BidTooLow {
+            min_lamports,
+            min_usd: sol_usd(state)
+                .await
+                .map(|rate| lamports_to_usd(min_lamports as i64, rate)),
+        },
+        AppError::BidTooHigh { max_lamports, .. } => AppError::BidTooHigh {
+            max_lamports,
+            max_usd: sol_usd(state)
+                .await
+                .map(|rate| lamports_to_usd(max_lamports as i64, rate)),
+        },
+        other => other,
+    }
+}
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::{
+    error::{AppError, Result},
+    services::price::PriceSource,
+};
+
+/// SOL/USD from Pyth's Hermes price service.
+pub struct PythSource {
+    http: Client,
+    api_url: String,
+    feed_id: String,
+}
+
+impl PythSource {
+    pub fn new(api_url: &str, feed_id: &str) -> Self {
+        Self {
+            http: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            feed_id: feed_id.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for PythSource {
+    fn name(&self) -> &'static str {
+        "pyth"
+    }
+
+    async fn fetch_sol_usd(&self) -> Result<f64> {
+        let response = self
+            .http
+            .get(format!("{}/v2/updates/price/latest", self.api_url))
+            .query(&[("ids[]", self.feed_id.as_str()), ("parsed", "true")])
+            .send()
+            .await
+            .map_err(|e| AppError::PriceOracleError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::PriceOracleError(format!(
+                "Pyth returned {}: {}",
+                status, body
+            )));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::PriceOracleError(e.to_string()))?;
+
+        // Prices are integers scaled by 10^expo, e.g. 14352012345 with expo -8.
+        let price = &body["parsed"][0]["price"];
+        let mantissa = price["price"]
+            .as_str()
+            .and_then(|price| price.parse::<i64>().ok());
+        let expo = price["expo"].as_i64();
+
+        match (mantissa, expo) {
+            (Some(mantissa), Some(expo)) if mantissa > 0 => {
+                Ok(mantissa as f64 * 10f64.powi(expo as i32))
+            }
+            _ => Err(AppError::PriceOracleError(
+                "Pyth response has no SOL/USD price".into(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use borsh::BorshDeserialize;
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+
+pub const PIXEL_COLORS_SIZE: usize = 768;
+
+// Program accounts are Anchor-encoded: the first 8 bytes of sha256("account:<Name>"),
+// then the borsh-encoded fields in IDL order. The structs below mirror the program IDL,
+// so a layout change shows up as a decode error instead of bytes read at stale offsets.
+
+fn account_discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("account:{name}").as_bytes());
+    hash[..8].try_into().expect("sha256 is longer than 8 bytes")
+}
+
+/// A program account type from the IDL.
+pub trait ProgramAccount: BorshDeserialize {
+    /// Account name in the IDL, which its discriminator is derived from.
+    const NAME: &'static str;
+    /// Serialized size of the fields, without the discriminator.
+    const DATA_SIZE: usize;
+    /// Space the program allocates for the account.
+    const SIZE: usize = 8 + Self::DATA_SIZE;
+
+    /// Fails if the data is another account type or too short for this one. Trailing bytes
+    /// are ignored, since accounts may be allocated with room to grow.
+    fn decode(data: &[u8]) -> Result<Self> {
+        let mut fields = data
+            .strip_prefix(&account_discriminator(Self::NAME))
+            .ok_or_else(|| AppError::SolanaRpc(format!("Not a {} account", Self::NAME)))?;
+
+        Self::deserialize(&mut fields)
+            .map_err(|e| AppError::SolanaRpc(format!("Invalid {} account: {}", Self::NAME, e)))
+    }
+}
+
+/// `CanvasMetadata` as stored on-chain. Pixel colors are 6-bit packed, 4 pixels per 3 bytes.
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct CanvasMetadata {
+    pub owner: [u8; 32],
+    pub id: [u8; 16],
+    pub state: u8,
+    pub width: u8,
+    pub height: u8,
+    pub total_escrow: u64,
+    pub pixel_colors: [u8; PIXEL_COLORS_SIZE],
+}
+
+impl ProgramAccount for CanvasMetadata {
+    const NAME: &'static str = "CanvasMetadata";
+    const DATA_SIZE: usize = 32 + 16 + 1 + 1 + 1 + 8 + PIXEL_COLORS_SIZE;
+}
+
+/// Decoded on-chain `CanvasMetadata` account.
+#[derive(Debug, Clone)]
+pub struct CanvasAccount {
+    pub owner: Pubkey,
+    pub id: Uuid,
+    pub state: u8,
+    pub width: u8,
+    pub height: u8,
+    pub total_escrow: u64,
+    pub pixel_colors: [u8; PIXEL_COLORS_SIZE],
+}
+
+impl CanvasAccount {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        Ok(CanvasMetadata::decode(data)?.into())
+    }
+}
+
+impl From<CanvasMetadata> for CanvasAccount {
+    fn from(account: CanvasMetadata) -> Self {
+        Self {
+            owner: Pubkey::new_from_array(account.owner),
+            id: Uuid::from_bytes(account.id),
+            state: account.state,
+            width: account.width,
+            height: account.height,
+            total_escrow: account.total_escrow,
+            pixel_colors: account.pixel_colors,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedTransaction, UiMessage, UiTransactionEncoding};
+
+use crate::{
+    error::{AppError, Result},
+    services::solana::SolanaClient,
+};
+
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+/// Lamport movements of a confirmed transaction, keyed by account address.
+#[derive(Debug, Clone)]
+pub struct BalanceChanges {
+    pub block_time: Option<DateTime<Utc>>,
+    pub signers: Vec<String>,
+    pub lamport_deltas: HashMap<String, i64>,
+    /// Owners holding the tracked mint before or after the transaction, i.e. seller and buyer.
+    pub token_holders: Vec<String>,
+}
+
+/// Successful signatures involving `address` that are newer than `until`, oldest first.
+pub async fn list_signatures_since(
+    solana_client: &SolanaClient,
+    address: &Pubkey,
+    until: Option<&str>,
+) -> Result<Vec<String>> {
+    let until = until
+        .map(Signature::from_str)
+        .transpose()
+        .map_err(|_| AppError::InternalServerError("Invalid stored signature cursor".into()))?;
+
+    let client = solana_client.get_client();
+    let mut signatures = Vec::new();
+    let mut before = None;
+
+    loop {
+        let page = solana_client
+            .with_retry(|| {
+                client.get_signatures_for_address_with_config(
+                    address,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(SIGNATURE_PAGE_SIZE),
+                        commitment: None,
+                    },
+                )
+            })
+            .await
+            .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+        let page_len = page.len();
+        before = page
+            .last()
+            .and_then(|status| Signature::from_str(&status.signature).ok());
+        signatures.extend(
+            page.into_iter()
+                .filter(|status| status.err.is_none())
+                .map(|status| status.signature),
+        );
+
+        if page_len < SIGNATURE_PAGE_SIZE || before.is_none() {
+            break;
+        }
+    }
+
+    signatures.reverse();
+    Ok(signatures)
+}
+
+/// Reads the balance changes of a transaction. `None` if it failed or has no status meta.
+pub async fn fetch_balance_changes(
+    solana_client: &SolanaClient,
+    signature: &str,
+    mint: &str,
+) -> Result<Option<BalanceChanges>> {
+    let transaction_signature = Signature::from_str(signature)
+        .map_err(|_| AppError::InvalidParams("Invalid transaction signature".into()))?;
+
+    let client = solana_client.get_client();
+    let response = solana_client
+        .with_retry(|| {
+            client.get_transaction_with_config(
+                &transaction_signature,
+                solana_client::rpc_config::RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(solana_commitment_config::CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+        })
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch transaction: {}", e)))?;
+
+    let Some(meta) = response.transaction.meta else {
+        return Ok(None);
+    };
+    if meta.err.is_some() {
+        return Ok(None);
+    }
+
+    let (mut account_keys, signers) = match response.transaction.transaction {
+        EncodedTransaction::Json(ui_transaction) => match ui_transaction.message {
+            UiMessage::Parsed(parsed) => {
+                let signers = parsed
+                    .account_keys
+                    .iter()
+                    .filter(|key| key.signer)
+                    .map(|key| key.pubkey.clone())
+                    .collect();
+                let keys = parsed.account_keys.into_iter().map(|key| key.pubkey);
+                (keys.collect::<Vec<_>>(), signers)
+            }
+            UiMessage::Raw(raw) => {
+                let signer_count = raw.header.num_required_signatures as usize;
+                let signers = raw
+                    .account_keys
+                    .iter()
+                    .take(signer_count)
+                    .cloned()
+                    .collect();
+                (raw.account_keys, signers)
+            }
+        },
+        _ => {
+            return Err(AppError::SolanaRpc(
+                "Unexpected transaction encoding".into(),
+            ));
+        }
+    };
+
+    // Balances of v0 transactions also cover accounts loaded from lookup tables.
+    if let Some(loaded) = meta.loaded_addresses.as_ref().map(|loaded| loaded.clone()) {
+        account_keys.extend(loaded.writable);
+        account_keys.extend(loaded.readonly);
+    }
+
+    let lamport_deltas = account_keys
+        .iter()
+        .zip(meta.pre_balances.iter().zip(meta.post_balances.iter()))
+        .map(|(key, (pre, post))| (key.clone(), *post as i64 - *pre as i64))
+        .filter(|(_, delta)| *delta != 0)
+        .collect();
+
+    let token_owner = |balance: &solana_transaction_status::UiTransactionTokenBalance| {
+        (balance.mint == mint)
+            .then(|| balance.owner.as_ref().map(|owner| owner.clone()))
+            .flatten()
+    };
+    let mut token_holders: Vec<String> = meta
+        .pre_token_balances
+        .as_ref()
+        .map_or(Vec::new(), |balances| {
+            balances.iter().filter_map(token_owner).collect()
+        });
+    token_holders.extend(
+        meta.post_token_balances
+            .as_ref()
+            .map_or(Vec::new(), |balances| {
+                balances.iter().filter_map(token_owner).collect()
+            }),
+    );
+    token_holders.sort();
+    token_holders.dedup();
+
+    Ok(Some(BalanceChanges {
+        block_time: response
+            .block_time
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0)),
+        signers,
+        lamport_deltas,
+        token_holders,
+    }))
+}
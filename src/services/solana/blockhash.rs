@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::{AppState, error::AppError, utils::scheduler::spawn_interval_task};
+
+/// Keeps the shared blockhash current so publish, bid and mint handlers never wait on the
+/// RPC for one. Refreshes twice per `blockhash_ttl`, so the cached entry does not go stale
+/// between runs.
+pub fn spawn_blockhash_refresher(state: AppState) -> JoinHandle<()> {
+    let period = (state.solana_client.blockhash_ttl() / 2).max(Duration::from_secs(1));
+
+    spawn_interval_task("blockhash_refresh", period, move || {
+        let state = state.clone();
+        async move {
+            state
+                .solana_client
+                .refresh_blockhash()
+                .await
+                .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+            Ok(())
+        }
+    })
+}
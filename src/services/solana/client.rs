@@ -1,27 +1,209 @@
 use std::{
+    future::Future,
     str::FromStr,
     sync::Arc,
     time::{Duration, Instant},
 };
 
-use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+use async_trait::async_trait;
+use solana_client::{
+    client_error::ClientError,
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+};
 use solana_commitment_config::CommitmentConfig;
-use solana_sdk::{hash::Hash, pubkey::Pubkey};
-use tokio::sync::RwLock;
+use solana_sdk::{hash::Hash, instruction::Instruction, pubkey, pubkey::Pubkey};
+use tokio::sync::{Mutex, RwLock, mpsc};
+
+#[cfg(feature = "geyser")]
+use crate::services::solana::geyser::{self, GeyserEndpoint};
+use crate::{
+    config::{SolanaCluster, SolanaConfig},
+    error::Result as AppResult,
+    services::solana::{
+        BalanceChanges, SolanaService, TransactionStatus, balances, cluster,
+        fees::{self, CostEstimate},
+        indexer::{self, ProgramTransaction},
+        retry::RetryPolicy,
+        simulate::{self, SimulationResult},
+        stream,
+        transactions::ExpectedInstruction,
+        verify,
+    },
+};
+
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
+pub const MPL_CORE_PROGRAM_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
 
-use crate::config::SolanaConfig;
+/// Connecting to the PubSub endpoint is not retried for this long after a failure.
+const PUBSUB_RETRY_AFTER: Duration = Duration::from_secs(30);
+const PUBSUB_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A recent blockhash and the last block height at which transactions using it are accepted.
+#[derive(Debug, Clone, Copy)]
+pub struct RecentBlockhash {
+    pub hash: Hash,
+    pub last_valid_block_height: u64,
+}
 
 struct CachedBlockhash {
-    hash: Hash,
+    blockhash: RecentBlockhash,
     fetched_at: Instant,
 }
 
+/// One deployment of the canvas program. Canvas, pixel and mint accounts are PDAs of the
+/// deployment the canvas was published under, so they are derived from it rather than
+/// from whichever program is current.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramDeployment {
+    pub program_id: Pubkey,
+}
+
+impl ProgramDeployment {
+    pub fn derive_canvas_pda(&self, canvas_id: &[u8; 16]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"canvas", canvas_id], &self.program_id)
+    }
+
+    pub fn derive_config_pda(&self) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"config"], &self.program_id)
+    }
+
+    pub fn derive_canvas_pda_from_uuid(&self, canvas_id: &uuid::Uuid) -> (Pubkey, u8) {
+        self.derive_canvas_pda(canvas_id.as_bytes())
+    }
+
+    /// Per-pixel bid account holding the current owner and price.
+    pub fn derive_pixel_pda(&self, canvas_id: &uuid::Uuid, x: u8, y: u8) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"pixel", canvas_id.as_bytes(), &[x], &[y]],
+            &self.program_id,
+        )
+    }
+
+    /// Token Metadata mint, owned by the program so it can sign the mint instruction.
+    pub fn derive_nft_mint_pda(&self, canvas_id: &uuid::Uuid) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"mint", canvas_id.as_bytes()], &self.program_id)
+    }
+
+    /// Mint for a numbered print of the canvas NFT.
+    pub fn derive_edition_mint_pda(&self, canvas_id: &uuid::Uuid, edition: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                b"edition_mint",
+                canvas_id.as_bytes(),
+                &edition.to_le_bytes(),
+            ],
+            &self.program_id,
+        )
+    }
+
+    /// Metaplex Core asset account. Core assets carry their own metadata, so
+    /// no metadata or edition accounts are needed.
+    pub fn derive_core_asset_pda(&self, canvas_id: &uuid::Uuid) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"asset", canvas_id.as_bytes()], &self.program_id)
+    }
+}
+
+/// The current program and the legacy deployments still serving canvases.
+pub struct ProgramRegistry {
+    current: Pubkey,
+    legacy: Vec<Pubkey>,
+}
+
+impl ProgramRegistry {
+    pub fn from_config(config: &SolanaConfig) -> Self {
+        Self {
+            current: config.program_id,
+            legacy: config.legacy_program_ids.clone(),
+        }
+    }
+
+    /// The deployment new canvases are published under.
+    pub fn current_program(&self) -> ProgramDeployment {
+        ProgramDeployment {
+            program_id: self.current,
+        }
+    }
+
+    /// The deployment recorded on a canvas. `None` if that program is no longer configured.
+    pub fn program_for(&self, program_id: Option<&str>) -> Option<ProgramDeployment> {
+        let Some(program_id) = program_id else {
+            return Some(self.current_program());
+        };
+        let program_id = Pubkey::from_str(program_id).ok()?;
+
+        self.deployments()
+            .into_iter()
+            .find(|deployment| deployment.program_id == program_id)
+    }
+
+    /// The current deployment followed by the legacy ones.
+    pub fn deployments(&self) -> Vec<ProgramDeployment> {
+        std::iter::once(self.current)
+            .chain(self.legacy.iter().copied())
+            .map(|program_id| ProgramDeployment { program_id })
+            .collect()
+    }
+}
+
+/// Token Metadata metadata account of a mint.
+pub fn derive_metadata_pda(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+pub fn derive_master_edition_pda(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+            b"edition",
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Token Metadata edition marker, one per 248 editions of a master edition.
+pub fn derive_edition_marker_pda(master_mint: &Pubkey, edition: u64) -> (Pubkey, u8) {
+    let marker_number = (edition / 248).to_string();
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            master_mint.as_ref(),
+            b"edition",
+            marker_number.as_bytes(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+#[derive(Default)]
+struct PubsubConnection {
+    client: Option<Arc<PubsubClient>>,
+    failed_at: Option<Instant>,
+}
+
 pub struct SolanaClient {
     client: RpcClient,
     program_id: Pubkey,
     program_id_str: String,
+    programs: ProgramRegistry,
+    collection_mint: Option<Pubkey>,
     blockhash_cache: Arc<RwLock<Option<CachedBlockhash>>>,
     blockhash_ttl: Duration,
+    retry_policy: RetryPolicy,
+    ws_url: Option<String>,
+    pubsub: Mutex<PubsubConnection>,
+    #[cfg(feature = "geyser")]
+    geyser: Option<GeyserEndpoint>,
 }
 
 impl SolanaClient {
@@ -34,15 +216,22 @@ impl SolanaClient {
         };
 
         let client = RpcClient::new_with_commitment(config.rpc_url.clone(), commitment);
-        let program_id =
-            Pubkey::from_str(&config.program_id).expect("Invalid program ID in config");
-
         Self {
             client,
-            program_id,
-            program_id_str: config.program_id.clone(),
+            program_id: config.program_id,
+            program_id_str: config.program_id.to_string(),
+            programs: ProgramRegistry::from_config(config),
+            collection_mint: config.collection_mint,
             blockhash_cache: Arc::new(RwLock::new(None)),
             blockhash_ttl: Duration::from_secs(config.blockhash_ttl),
+            retry_policy: RetryPolicy::from_config(config),
+            ws_url: config.ws_url.clone(),
+            pubsub: Mutex::new(PubsubConnection::default()),
+            #[cfg(feature = "geyser")]
+            geyser: config.geyser_url.clone().map(|url| GeyserEndpoint {
+                url,
+                x_token: config.geyser_x_token.clone(),
+            }),
         }
     }
 
@@ -50,46 +239,241 @@ impl SolanaClient {
         &self.program_id_str
     }
 
+    pub fn program_pubkey(&self) -> &Pubkey {
+        &self.program_id
+    }
+
     pub fn get_client(&self) -> &RpcClient {
         &self.client
     }
 
-    pub fn derive_canvas_pda(&self, canvas_id: &[u8; 16]) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[b"canvas", canvas_id], &self.program_id)
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
     }
 
-    pub fn derive_config_pda(&self) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[b"config"], &self.program_id)
+    /// Runs an RPC call under the configured retry policy. Every call to the node should
+    /// go through here, e.g. `solana_client.with_retry(|| client.get_slot()).await`.
+    pub async fn with_retry<T, F, Fut>(&self, operation: F) -> Result<T, ClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        self.retry_policy.run(operation).await
     }
 
-    pub fn derive_canvas_pda_from_uuid(&self, canvas_id: &uuid::Uuid) -> (Pubkey, u8) {
-        self.derive_canvas_pda(canvas_id.as_bytes())
+    /// Shared PubSub connection, opened on first use. `None` when no WebSocket URL is
+    /// configured or the endpoint is unreachable, so callers fall back to polling.
+    pub async fn get_pubsub_client(&self) -> Option<Arc<PubsubClient>> {
+        let ws_url = self.ws_url.as_deref()?;
+        let mut pubsub = self.pubsub.lock().await;
+
+        if let Some(client) = pubsub.client.as_ref() {
+            return Some(client.clone());
+        }
+        if pubsub
+            .failed_at
+            .is_some_and(|failed_at| failed_at.elapsed() < PUBSUB_RETRY_AFTER)
+        {
+            return None;
+        }
+
+        match tokio::time::timeout(PUBSUB_CONNECT_TIMEOUT, PubsubClient::new(ws_url)).await {
+            Ok(Ok(client)) => {
+                let client = Arc::new(client);
+                *pubsub = PubsubConnection {
+                    client: Some(client.clone()),
+                    failed_at: None,
+                };
+                Some(client)
+            }
+            Ok(Err(e)) => {
+                tracing::warn!(error = %e, "Failed to connect to Solana PubSub");
+                pubsub.failed_at = Some(Instant::now());
+                None
+            }
+            Err(_) => {
+                tracing::warn!("Timed out connecting to Solana PubSub");
+                pubsub.failed_at = Some(Instant::now());
+                None
+            }
+        }
     }
 
-    pub async fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>, ClientError> {
-        Ok(self.client.get_account(pubkey).await?.data)
+    /// Drops a broken PubSub connection so the next caller reconnects.
+    pub async fn reset_pubsub_client(&self) {
+        let mut pubsub = self.pubsub.lock().await;
+        *pubsub = PubsubConnection {
+            client: None,
+            failed_at: Some(Instant::now()),
+        };
     }
 
-    pub async fn get_recent_blockhash(&self) -> Result<Hash, ClientError> {
+    /// Served from memory while the background refresher keeps it current. Fetched inline
+    /// only when the cached entry is older than `blockhash_ttl`.
+    pub async fn get_recent_blockhash_with_height(&self) -> Result<RecentBlockhash, ClientError> {
         {
             let cache = self.blockhash_cache.read().await;
             if let Some(ref cached) = *cache
                 && cached.fetched_at.elapsed() < self.blockhash_ttl
             {
-                return Ok(cached.hash);
+                return Ok(cached.blockhash);
             }
         }
 
-        let hash = self.client.get_latest_blockhash().await?;
+        self.refresh_blockhash().await
+    }
+}
+
+#[async_trait]
+impl SolanaService for SolanaClient {
+    fn name(&self) -> &'static str {
+        "rpc"
+    }
+
+    fn programs(&self) -> &ProgramRegistry {
+        &self.programs
+    }
+
+    fn collection_mint(&self) -> Option<&Pubkey> {
+        self.collection_mint.as_ref()
+    }
+
+    fn blockhash_ttl(&self) -> Duration {
+        self.blockhash_ttl
+    }
+
+    async fn verify_cluster(&self, cluster: SolanaCluster) -> AppResult<()> {
+        cluster::verify_cluster(self, cluster).await
+    }
+
+    async fn get_recent_blockhash(&self) -> Result<Hash, ClientError> {
+        Ok(self.get_recent_blockhash_with_height().await?.hash)
+    }
+
+    async fn refresh_blockhash(&self) -> Result<RecentBlockhash, ClientError> {
+        let (hash, last_valid_block_height) = self
+            .with_retry(|| {
+                self.client
+                    .get_latest_blockhash_with_commitment(self.client.commitment())
+            })
+            .await?;
+        let blockhash = RecentBlockhash {
+            hash,
+            last_valid_block_height,
+        };
 
+        let mut cache = self.blockhash_cache.write().await;
+        // A concurrent refresh may have stored a newer blockhash already.
+        if let Some(ref cached) = *cache
+            && cached.blockhash.last_valid_block_height > last_valid_block_height
         {
-            let mut cache = self.blockhash_cache.write().await;
-            *cache = Some(CachedBlockhash {
-                hash,
-                fetched_at: Instant::now(),
-            });
+            return Ok(cached.blockhash);
         }
+        *cache = Some(CachedBlockhash {
+            blockhash,
+            fetched_at: Instant::now(),
+        });
+
+        Ok(blockhash)
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>, ClientError> {
+        Ok(self
+            .with_retry(|| self.client.get_account(pubkey))
+            .await?
+            .data)
+    }
+
+    async fn find_account_data(&self, pubkey: &Pubkey) -> Result<Option<Vec<u8>>, ClientError> {
+        let response = self
+            .with_retry(|| {
+                self.client
+                    .get_account_with_commitment(pubkey, self.client.commitment())
+            })
+            .await?;
+        Ok(response.value.map(|account| account.data))
+    }
+
+    async fn account_exists(&self, pubkey: &Pubkey) -> Result<bool, ClientError> {
+        Ok(self.find_account_data(pubkey).await?.is_some())
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, ClientError> {
+        self.with_retry(|| self.client.get_balance(pubkey)).await
+    }
+
+    async fn verify_program_transaction(
+        &self,
+        signature: &str,
+        program_id: &str,
+    ) -> AppResult<bool> {
+        verify::verify_program_transaction(self, signature, program_id).await
+    }
+
+    async fn verify_program_instruction(
+        &self,
+        signature: &str,
+        expected: &ExpectedInstruction,
+    ) -> AppResult<bool> {
+        verify::verify_program_instruction(self, signature, expected).await
+    }
+
+    async fn check_program_instruction(
+        &self,
+        signature: &str,
+        expected: &ExpectedInstruction,
+    ) -> AppResult<TransactionStatus> {
+        verify::check_program_instruction(self, signature, expected).await
+    }
+
+    async fn list_signatures_since(
+        &self,
+        address: &Pubkey,
+        until: Option<&str>,
+    ) -> AppResult<Vec<String>> {
+        balances::list_signatures_since(self, address, until).await
+    }
+
+    async fn fetch_balance_changes(
+        &self,
+        signature: &str,
+        mint: &str,
+    ) -> AppResult<Option<BalanceChanges>> {
+        balances::fetch_balance_changes(self, signature, mint).await
+    }
+
+    async fn fetch_program_transaction(
+        &self,
+        program_id: &Pubkey,
+        signature: &str,
+    ) -> AppResult<Option<ProgramTransaction>> {
+        indexer::fetch_program_transaction(self, program_id, signature).await
+    }
+
+    async fn watch_program_signatures(
+        &self,
+        program_id: &Pubkey,
+        sender: mpsc::Sender<String>,
+    ) -> AppResult<()> {
+        #[cfg(feature = "geyser")]
+        if let Some(endpoint) = &self.geyser {
+            return geyser::watch_program_signatures(endpoint, program_id, sender).await;
+        }
+
+        stream::watch_program_signatures(self, program_id, sender).await
+    }
+
+    async fn simulate_transaction(&self, transaction: &str) -> AppResult<SimulationResult> {
+        simulate::simulate_transaction(self, transaction).await
+    }
 
-        Ok(hash)
+    async fn estimate_cost(
+        &self,
+        instructions: &[Instruction],
+        fee_payer: &Pubkey,
+        blockhash: Hash,
+        new_account_sizes: &[usize],
+    ) -> AppResult<CostEstimate> {
+        fees::estimate_cost(self, instructions, fee_payer, blockhash, new_account_sizes).await
     }
 }
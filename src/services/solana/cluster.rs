@@ -0,0 +1,43 @@
+use crate::{
+    config::SolanaCluster,
+    error::{AppError, Result},
+    services::solana::SolanaClient,
+};
+
+/// Refuses to run against an RPC endpoint on a different cluster than configured, or one
+/// where the program is not deployed, so a devnet config never silently serves mainnet.
+pub async fn verify_cluster(solana_client: &SolanaClient, cluster: SolanaCluster) -> Result<()> {
+    let client = solana_client.get_client();
+
+    if let Some(expected) = cluster.genesis_hash() {
+        let genesis_hash = solana_client
+            .with_retry(|| client.get_genesis_hash())
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch genesis hash: {}", e)))?;
+
+        if genesis_hash.to_string() != expected {
+            return Err(AppError::InvalidParams(format!(
+                "SOLANA_RPC_URL is not on {} (genesis hash {genesis_hash})",
+                cluster.as_str()
+            )));
+        }
+    }
+
+    let program = solana_client
+        .with_retry(|| {
+            client.get_account_with_commitment(solana_client.program_pubkey(), client.commitment())
+        })
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch program account: {}", e)))?
+        .value;
+
+    if !program.is_some_and(|account| account.executable) {
+        return Err(AppError::InvalidParams(format!(
+            "SOLANA_PROGRAM_ID {} is not deployed on {}",
+            solana_client.get_program_id(),
+            cluster.as_str()
+        )));
+    }
+
+    Ok(())
+}
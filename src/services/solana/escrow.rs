@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::{
+            entities::{
+                consumed_signature::{self, SignatureOperation},
+                escrow_refund::{self, RefundReason},
+            },
+            repositories::{CanvasRepository, EscrowRefundRepository},
+        },
+    },
+    services::solana::{
+        self,
+        client::ProgramDeployment,
+        signatures::{consume_signature, find_consumed_signature},
+        transactions::{self, ExpectedInstruction, ExpectedOperation},
+    },
+};
+
+/// Outlives the blockhash of the claim transaction.
+const PENDING_CLAIM_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowRefund {
+    pub id: Uuid,
+    pub reason: RefundReason,
+    pub amount_lamports: i64,
+    pub source_signature: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<escrow_refund::Model> for EscrowRefund {
+    fn from(value: escrow_refund::Model) -> Self {
+        EscrowRefund {
+            id: value.id,
+            reason: value.reason,
+            amount_lamports: value.amount_lamports,
+            source_signature: value.source_signature,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EscrowClaimInfo {
+    pub canvas_id: Uuid,
+    pub amount_lamports: i64,
+    pub refunds: Vec<EscrowRefund>,
+    pub blockhash: String,
+    /// Base64 unsigned `withdraw_escrow` transaction for the claimant's wallet to sign.
+    pub transaction: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct EscrowClaim {
+    pub canvas_id: Uuid,
+    pub amount_lamports: i64,
+    pub signature: String,
+    pub refunds: Vec<EscrowRefund>,
+}
+
+/// The refunds a built claim transaction withdraws, so its confirmation marks exactly those.
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingEscrowClaim {
+    refund_ids: Vec<Uuid>,
+    amount_lamports: i64,
+}
+
+/// Credits users' escrow balances on a canvas. A transaction credits each user at most once.
+pub(crate) async fn credit_refunds(
+    state: &AppState,
+    canvas_id: Uuid,
+    reason: RefundReason,
+    source_signature: &str,
+    credits: &[(Uuid, i64)],
+) -> Result<u64> {
+    let now = Utc::now();
+    let refunds = credits
+        .iter()
+        .filter(|(_, amount_lamports)| *amount_lamports > 0)
+        .map(|&(user_id, amount_lamports)| escrow_refund::Model {
+            id: Uuid::new_v4(),
+            user_id,
+            canvas_id,
+            reason,
+            amount_lamports,
+            source_signature: source_signature.to_string(),
+            claim_signature: None,
+            claimed_at: None,
+            created_at: now,
+        })
+        .collect();
+
+    EscrowRefundRepository::record_refunds(state.db.get_connection(), refunds).await
+}
+
+/// Builds the withdrawal of everything the program escrow owes the user on the canvas.
+pub async fn claim_escrow(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> Result<EscrowClaimInfo> {
+    let refunds =
+        EscrowRefundRepository::find_unclaimed(state.db.get_connection(), user_id, canvas_id)
+            .await?;
+    let amount_lamports: i64 = refunds.iter().map(|refund| refund.amount_lamports).sum();
+
+    if amount_lamports == 0 {
+        return Err(AppError::InvalidParams(
+            "No escrowed funds to claim on this canvas".into(),
+        ));
+    }
+
+    let claimant = transactions::user_wallet(state, user_id).await?;
+    let blockhash = state
+        .solana_client
+        .get_recent_blockhash()
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    let instruction = transactions::withdraw_escrow_instruction(
+        &escrow_program(state, canvas_id).await?,
+        &claimant,
+        &canvas_id,
+        amount_lamports as u64,
+    );
+
+    let pending = PendingEscrowClaim {
+        refund_ids: refunds.iter().map(|refund| refund.id).collect(),
+        amount_lamports,
+    };
+    state
+        .cache
+        .redis
+        .set(
+            &CacheKey::pending_escrow_claim(&canvas_id, &user_id),
+            &pending,
+            PENDING_CLAIM_TTL,
+        )
+        .await?;
+
+    Ok(EscrowClaimInfo {
+        canvas_id,
+        amount_lamports,
+        refunds: refunds.into_iter().map(EscrowRefund::from).collect(),
+        blockhash: blockhash.to_string(),
+        transaction: transactions::build_unsigned_transaction(&[instruction], &claimant, blockhash),
+    })
+}
+
+pub async fn confirm_escrow_claim(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    signature: &str,
+) -> Result<EscrowClaim> {
+    // Replays of an applied claim return its original result.
+    if let Some(record) = find_consumed_signature(state, signature).await? {
+        return replay_escrow_claim(state, canvas_id, user_id, &record).await;
+    }
+
+    let pending_key = CacheKey::pending_escrow_claim(&canvas_id, &user_id);
+    let pending: PendingEscrowClaim =
+        state
+            .cache
+            .redis
+            .get(&pending_key)
+            .await?
+            .ok_or(AppError::InvalidParams(
+                "No pending escrow claim for this canvas".into(),
+            ))?;
+
+    let expected = ExpectedInstruction {
+        program: escrow_program(state, canvas_id).await?,
+        signer: transactions::user_wallet(state, user_id).await?,
+        canvas_id,
+        operation: ExpectedOperation::WithdrawEscrow {
+            amount_lamports: pending.amount_lamports as u64,
+        },
+    };
+    let is_valid = state
+        .solana_client
+        .verify_program_instruction(signature, &expected)
+        .await?;
+
+    if !is_valid {
+        return Err(AppError::TransactionFailed(
+            "Transaction verification failed".into(),
+        ));
+    }
+
+    let record = consumed_signature::Model {
+        signature: signature.to_string(),
+        operation: SignatureOperation::EscrowClaim,
+        canvas_id,
+        user_id,
+        x: None,
+        y: None,
+        color: None,
+        amount_lamports: Some(pending.amount_lamports),
+        created_at: Utc::now(),
+    };
+    if !consume_signature(state, &record).await? {
+        // A concurrent confirm with the same signature claimed it first.
+        let record = find_consumed_signature(state, signature)
+            .await?
+            .ok_or(AppError::SignatureAlreadyUsed)?;
+        return replay_escrow_claim(state, canvas_id, user_id, &record).await;
+    }
+
+    let marked = EscrowRefundRepository::mark_claimed(
+        state.db.get_connection(),
+        &pending.refund_ids,
+        signature,
+    )
+    .await?;
+    if marked != pending.refund_ids.len() as u64 {
+        tracing::warn!(
+            canvas_id = %canvas_id,
+            user_id = %user_id,
+            expected = pending.refund_ids.len(),
+            marked,
+            "Escrow claim covered refunds that were already claimed"
+        );
+    }
+
+    state.cache.redis.delete(&pending_key).await?;
+
+    escrow_claim(state, canvas_id, signature).await
+}
+
+async fn replay_escrow_claim(
+    state: &AppState,
+    canvas_id: Uuid,
+    user_id: Uuid,
+    record: &consumed_signature::Model,
+) -> Result<EscrowClaim> {
+    let is_same_claim = record.operation == SignatureOperation::EscrowClaim
+        && record.canvas_id == canvas_id
+        && record.user_id == user_id;
+
+    if !is_same_claim {
+        return Err(AppError::SignatureAlreadyUsed);
+    }
+
+    escrow_claim(state, canvas_id, &record.signature).await
+}
+
+async fn escrow_claim(state: &AppState, canvas_id: Uuid, signature: &str) -> Result<EscrowClaim> {
+    let refunds =
+        EscrowRefundRepository::find_by_claim_signature(state.db.get_connection(), signature)
+            .await?;
+
+    Ok(EscrowClaim {
+        canvas_id,
+        amount_lamports: refunds.iter().map(|refund| refund.amount_lamports).sum(),
+        signature: signature.to_string(),
+        refunds: refunds.into_iter().map(EscrowRefund::from).collect(),
+    })
+}
+
+/// Escrow is held by the program the canvas was published under.
+async fn escrow_program(state: &AppState, canvas_id: Uuid) -> Result<ProgramDeployment> {
+    let canvas = CanvasRepository::find_canvas_by_id(state.db.get_connection(), canvas_id)
+        .await?
+        .ok_or(AppError::CanvasNotFound)?;
+
+    solana::canvas_program(state.solana_client.as_ref(), &canvas)
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::{hash::Hash, instruction::Instruction, message::Message, pubkey::Pubkey};
+
+use crate::{
+    error::{AppError, Result},
+    services::solana::SolanaClient,
+};
+
+/// Token Metadata mint, metadata and master edition accounts, at their maximum sizes.
+pub const MINT_ACCOUNT_SIZE: usize = 82;
+pub const METADATA_ACCOUNT_SIZE: usize = 679;
+pub const MASTER_EDITION_ACCOUNT_SIZE: usize = 282;
+
+/// Budget of an instruction in a transaction without a compute budget instruction.
+const DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION: u64 = 200_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+/// What a transaction is expected to cost its fee payer, so the wallet prompt is no surprise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// Signature fee charged by the cluster.
+    pub fee_lamports: u64,
+    /// At the median recent rate for the transaction's writable accounts. Only paid if the
+    /// wallet adds a priority fee.
+    pub priority_fee_lamports: u64,
+    /// Rent-exempt deposits for the accounts the transaction creates.
+    pub rent_lamports: u64,
+    pub total_lamports: u64,
+}
+
+/// Size of a Core `AssetV1` account without plugins.
+pub fn core_asset_size(name: &str, uri: &str) -> usize {
+    // key, owner, update authority, name, uri, seq
+    1 + 32 + 33 + 4 + name.len() + 4 + uri.len() + 1
+}
+
+/// Estimates the cost of the transaction built from `instructions`, which creates accounts
+/// of `new_account_sizes` bytes.
+pub async fn estimate_cost(
+    solana_client: &SolanaClient,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    blockhash: Hash,
+    new_account_sizes: &[usize],
+) -> Result<CostEstimate> {
+    let client = solana_client.get_client();
+    let message = Message::new_with_blockhash(instructions, Some(fee_payer), &blockhash);
+
+    let fee_lamports = solana_client
+        .with_retry(|| client.get_fee_for_message(&message))
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to estimate fee: {}", e)))?;
+
+    let mut writable_accounts: Vec<Pubkey> = instructions
+        .iter()
+        .flat_map(|instruction| &instruction.accounts)
+        .filter(|account| account.is_writable)
+        .map(|account| account.pubkey)
+        .collect();
+    writable_accounts.sort();
+    writable_accounts.dedup();
+
+    let mut recent_fees: Vec<u64> = solana_client
+        .with_retry(|| client.get_recent_prioritization_fees(&writable_accounts))
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch priority fees: {}", e)))?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+    recent_fees.sort_unstable();
+    let micro_lamports_per_unit = recent_fees.get(recent_fees.len() / 2).copied().unwrap_or(0);
+    let compute_units = DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION * instructions.len() as u64;
+    let priority_fee_lamports = micro_lamports_per_unit
+        .saturating_mul(compute_units)
+        .div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+
+    let mut rent_lamports: u64 = 0;
+    for &size in new_account_sizes {
+        let rent = solana_client
+            .with_retry(|| client.get_minimum_balance_for_rent_exemption(size))
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch rent: {}", e)))?;
+        rent_lamports = rent_lamports.saturating_add(rent);
+    }
+
+    Ok(CostEstimate {
+        fee_lamports,
+        priority_fee_lamports,
+        rent_lamports,
+        total_lamports: fee_lamports
+            .saturating_add(priority_fee_lamports)
+            .saturating_add(rent_lamports),
+    })
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use futures::{SinkExt, StreamExt};
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::mpsc;
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+use yellowstone_grpc_proto::prelude::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterTransactions, SubscribeRequestPing,
+    subscribe_update::UpdateOneof,
+};
+
+use crate::error::{AppError, Result};
+
+/// Name of the transaction filter, echoed back on every update it matches.
+const FILTER_NAME: &str = "program";
+
+/// A Yellowstone gRPC (Geyser) endpoint, e.g. from Triton or Helius.
+#[derive(Debug, Clone)]
+pub struct GeyserEndpoint {
+    pub url: String,
+    pub x_token: Option<String>,
+}
+
+/// Sends the signature of every successful transaction mentioning the program, as the
+/// validator's Geyser plugin sees it confirm. Returns when the stream or the receiver closes.
+pub async fn watch_program_signatures(
+    endpoint: &GeyserEndpoint,
+    program_id: &Pubkey,
+    sender: mpsc::Sender<String>,
+) -> Result<()> {
+    let mut client = GeyserGrpcClient::build_from_shared(endpoint.url.clone())
+        .and_then(|builder| builder.x_token(endpoint.x_token.clone()))
+        .and_then(|builder| builder.tls_config(ClientTlsConfig::new().with_native_roots()))
+        .map_err(|e| AppError::SolanaRpc(format!("Invalid Geyser endpoint: {e}")))?
+        .connect()
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to connect to Geyser: {e}")))?;
+
+    let request = SubscribeRequest {
+        transactions: HashMap::from([(
+            FILTER_NAME.to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(false),
+                account_include: vec![program_id.to_string()],
+                ..Default::default()
+            },
+        )]),
+        commitment: Some(CommitmentLevel::Confirmed as i32),
+        ..Default::default()
+    };
+    let (mut requests, mut updates) = client
+        .subscribe_with_request(Some(request))
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    while let Some(update) = updates.next().await {
+        let update = update.map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+        match update.update_oneof {
+            Some(UpdateOneof::Transaction(transaction)) => {
+                let Some(info) = transaction.transaction else {
+                    continue;
+                };
+                let signature = bs58::encode(info.signature).into_string();
+                if sender.send(signature).await.is_err() {
+                    break;
+                }
+            }
+            // Some providers drop subscriptions that never answer their pings.
+            Some(UpdateOneof::Ping(_)) => {
+                let pong = SubscribeRequest {
+                    ping: Some(SubscribeRequestPing { id: 1 }),
+                    ..Default::default()
+                };
+                requests
+                    .send(pong)
+                    .await
+                    .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+use tokio::task::JoinHandle;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::{
+            entities::onchain_event::{self, OnchainEventType},
+            repositories::{CanvasRepository, OnchainEventRepository},
+        },
+    },
+    services::solana::{
+        SolanaClient,
+        client::ProgramDeployment,
+        transactions::{DecodedInstruction, ProgramEvent, decode_program_instruction},
+    },
+    utils::scheduler::spawn_interval_task,
+};
+
+/// Transactions indexed per run, so a long backlog is worked through over several runs.
+const INDEX_BATCH_SIZE: usize = 200;
+const CURSOR_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Program instructions of one confirmed transaction, with their position in it.
+pub struct ProgramTransaction {
+    pub slot: u64,
+    pub block_time: Option<DateTime<Utc>>,
+    pub instructions: Vec<(usize, DecodedInstruction)>,
+}
+
+/// Tails the transactions of the current and legacy programs into `onchain_events`, so canvas,
+/// bid and mint history can be recovered and analysed without relying on client confirmations.
+pub fn spawn_event_indexer(state: AppState) -> JoinHandle<()> {
+    let period = Duration::from_secs(state.config.solana.event_index_interval_secs);
+
+    spawn_interval_task("event_index", period, move || {
+        let state = state.clone();
+        async move { index_program_events(&state).await }
+    })
+}
+
+pub async fn index_program_events(state: &AppState) -> Result<()> {
+    let current = state.solana_client.current_program();
+
+    for program in state.solana_client.deployments() {
+        let is_current = program == current;
+        if let Err(e) = index_deployment_events(state, &program, is_current).await {
+            tracing::warn!(program_id = %program.program_id, error = ?e, "Failed to index program events");
+        }
+    }
+
+    Ok(())
+}
+
+async fn index_deployment_events(
+    state: &AppState,
+    program: &ProgramDeployment,
+    is_current: bool,
+) -> Result<()> {
+    let cursor_key = CacheKey::onchain_event_cursor(&program.program_id.to_string());
+    // Redis holds the newest scanned signature per program. Without it, the current program
+    // resumes at the newest event and legacy programs are scanned from the start; rescanned
+    // transactions are skipped on insert.
+    let cursor = match state.cache.redis.get::<String>(&cursor_key).await? {
+        Some(cursor) => Some(cursor),
+        None if is_current => {
+            OnchainEventRepository::find_latest_signature(state.db.get_connection()).await?
+        }
+        None => None,
+    };
+
+    let signatures = state
+        .solana_client
+        .list_signatures_since(&program.program_id, cursor.as_deref())
+        .await?;
+
+    let batch = &signatures[..signatures.len().min(INDEX_BATCH_SIZE)];
+    let Some(newest) = batch.last() else {
+        return Ok(());
+    };
+
+    let mut events = Vec::new();
+    for signature in batch {
+        if let Some(transaction) = state
+            .solana_client
+            .fetch_program_transaction(&program.program_id, signature)
+            .await?
+        {
+            events.extend(to_events(signature, transaction));
+        }
+    }
+
+    let events = resolve_canvas_ids(state, events).await?;
+    let recorded = OnchainEventRepository::record_events(state.db.get_connection(), events).await?;
+    if recorded > 0 {
+        tracing::info!(recorded, "Indexed on-chain program events");
+    }
+
+    state
+        .cache
+        .redis
+        .set(&cursor_key, newest, CURSOR_TTL)
+        .await?;
+
+    Ok(())
+}
+
+/// Decodes the program instructions of a transaction. `None` if it failed on-chain.
+pub async fn fetch_program_transaction(
+    solana_client: &SolanaClient,
+    program_id: &Pubkey,
+    signature: &str,
+) -> Result<Option<ProgramTransaction>> {
+    let transaction_signature = Signature::from_str(signature)
+        .map_err(|_| AppError::InternalServerError("Invalid indexed signature".into()))?;
+
+    let client = solana_client.get_client();
+    let response = solana_client
+        .with_retry(|| {
+            client.get_transaction_with_config(
+                &transaction_signature,
+                solana_client::rpc_config::RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+        })
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch transaction: {}", e)))?;
+
+    if response
+        .transaction
+        .meta
+        .as_ref()
+        .is_some_and(|meta| meta.err.is_some())
+    {
+        return Ok(None);
+    }
+
+    let transaction = response
+        .transaction
+        .transaction
+        .decode()
+        .ok_or(AppError::SolanaRpc(
+            "Unexpected transaction encoding".into(),
+        ))?;
+
+    // Instructions referencing lookup-table accounts are skipped, since only static keys
+    // are resolved here. Transactions built by this server use static keys only.
+    let message = &transaction.message;
+    let account_keys = message.static_account_keys();
+
+    let instructions = message
+        .instructions()
+        .iter()
+        .enumerate()
+        .filter(|(_, instruction)| {
+            account_keys.get(instruction.program_id_index as usize) == Some(program_id)
+        })
+        .filter_map(|(index, instruction)| {
+            let accounts: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .map(|&account| account_keys.get(account as usize).copied())
+                .collect::<Option<_>>()?;
+            decode_program_instruction(&accounts, &instruction.data).map(|decoded| (index, decoded))
+        })
+        .collect();
+
+    Ok(Some(ProgramTransaction {
+        slot: response.slot,
+        block_time: response
+            .block_time
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0)),
+        instructions,
+    }))
+}
+
+pub(crate) fn to_events(
+    signature: &str,
+    transaction: ProgramTransaction,
+) -> Vec<onchain_event::Model> {
+    let now = Utc::now();
+
+    transaction
+        .instructions
+        .into_iter()
+        .map(|(index, decoded)| {
+            let event_type = match decoded.event {
+                ProgramEvent::CanvasCreated { .. } => OnchainEventType::CanvasCreated,
+                ProgramEvent::BidPlaced { .. } => OnchainEventType::BidPlaced,
+                ProgramEvent::PixelsPainted { .. } => OnchainEventType::PixelsPainted,
+                ProgramEvent::Minted { .. } => OnchainEventType::Minted,
+                ProgramEvent::EscrowWithdrawn { .. } => OnchainEventType::EscrowWithdrawn,
+            };
+            let canvas_id = match decoded.event {
+                ProgramEvent::CanvasCreated { canvas_id } => Some(canvas_id),
+                _ => None,
+            };
+
+            onchain_event::Model {
+                signature: signature.to_string(),
+                instruction_index: index as i16,
+                event_type,
+                canvas_pda: decoded.canvas_pda.to_string(),
+                canvas_id,
+                signer: decoded.signer.to_string(),
+                data: serde_json::to_value(&decoded.event).unwrap_or_default(),
+                slot: transaction.slot as i64,
+                block_time: transaction.block_time,
+                created_at: now,
+            }
+        })
+        .collect()
+}
+
+/// Fills in the canvas of events whose instruction only names the canvas PDA.
+pub(crate) async fn resolve_canvas_ids(
+    state: &AppState,
+    mut events: Vec<onchain_event::Model>,
+) -> Result<Vec<onchain_event::Model>> {
+    let mut canvas_pdas: Vec<String> = events
+        .iter()
+        .filter(|event| event.canvas_id.is_none())
+        .map(|event| event.canvas_pda.clone())
+        .collect();
+    canvas_pdas.sort();
+    canvas_pdas.dedup();
+
+    if canvas_pdas.is_empty() {
+        return Ok(events);
+    }
+
+    let canvas_ids: HashMap<String, _> =
+        CanvasRepository::find_canvases_by_pdas(state.db.get_connection(), &canvas_pdas)
+            .await?
+            .into_iter()
+            .filter_map(|canvas| Some((canvas.canvas_pda?, canvas.id)))
+            .collect();
+
+    for event in &mut events {
+        if event.canvas_id.is_none() {
+            event.canvas_id = canvas_ids.get(&event.canvas_pda).copied();
+        }
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,198 @@
+use std::{str::FromStr, time::Duration};
+
+use async_trait::async_trait;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    rent::Rent, signature::Signature,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    config::{SolanaCluster, SolanaConfig},
+    error::{AppError, Result},
+    services::solana::{
+        BalanceChanges, SolanaService, TransactionStatus,
+        client::{ProgramRegistry, RecentBlockhash},
+        fees::CostEstimate,
+        indexer::ProgramTransaction,
+        simulate::SimulationResult,
+        transactions::ExpectedInstruction,
+        wallet::SIGNATURE_FEE_LAMPORTS,
+    },
+};
+
+const MOCK_BLOCKHASH: RecentBlockhash = RecentBlockhash {
+    hash: Hash::new_from_array([1; 32]),
+    last_valid_block_height: u64::MAX,
+};
+/// Balance reported for every wallet.
+const DEFAULT_BALANCE_LAMPORTS: u64 = 10 * LAMPORTS_PER_SOL;
+
+/// Stand-in for a Solana node, selected with `SOLANA_MOCK=true`. Every well-formed signature
+/// confirms, no account exists yet, every wallet holds the same balance, and fees and rent use
+/// the cluster defaults. Nothing is indexed, since the mock has no transaction history.
+pub struct MockSolanaClient {
+    programs: ProgramRegistry,
+    collection_mint: Option<Pubkey>,
+    blockhash_ttl: Duration,
+}
+
+impl MockSolanaClient {
+    pub fn new(config: &SolanaConfig) -> Self {
+        Self {
+            programs: ProgramRegistry::from_config(config),
+            collection_mint: config.collection_mint,
+            blockhash_ttl: Duration::from_secs(config.blockhash_ttl),
+        }
+    }
+}
+
+fn check_signature(signature: &str) -> Result<()> {
+    Signature::from_str(signature)
+        .map(|_| ())
+        .map_err(|_| AppError::InvalidParams("Invalid transaction signature".into()))
+}
+
+#[async_trait]
+impl SolanaService for MockSolanaClient {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn programs(&self) -> &ProgramRegistry {
+        &self.programs
+    }
+
+    fn collection_mint(&self) -> Option<&Pubkey> {
+        self.collection_mint.as_ref()
+    }
+
+    fn blockhash_ttl(&self) -> Duration {
+        self.blockhash_ttl
+    }
+
+    async fn verify_cluster(&self, _cluster: SolanaCluster) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_recent_blockhash(&self) -> std::result::Result<Hash, ClientError> {
+        Ok(MOCK_BLOCKHASH.hash)
+    }
+
+    async fn refresh_blockhash(&self) -> std::result::Result<RecentBlockhash, ClientError> {
+        Ok(MOCK_BLOCKHASH)
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> std::result::Result<Vec<u8>, ClientError> {
+        self.find_account_data(pubkey).await?.ok_or_else(|| {
+            ClientErrorKind::Custom(format!("AccountNotFound: pubkey={}", pubkey)).into()
+        })
+    }
+
+    async fn find_account_data(
+        &self,
+        _pubkey: &Pubkey,
+    ) -> std::result::Result<Option<Vec<u8>>, ClientError> {
+        Ok(None)
+    }
+
+    async fn account_exists(&self, _pubkey: &Pubkey) -> std::result::Result<bool, ClientError> {
+        Ok(false)
+    }
+
+    async fn get_balance(&self, _pubkey: &Pubkey) -> std::result::Result<u64, ClientError> {
+        Ok(DEFAULT_BALANCE_LAMPORTS)
+    }
+
+    async fn verify_program_transaction(&self, signature: &str, program_id: &str) -> Result<bool> {
+        Pubkey::from_str(program_id)
+            .map_err(|_| AppError::InvalidParams("Invalid program ID".into()))?;
+
+        check_signature(signature)?;
+        Ok(true)
+    }
+
+    async fn verify_program_instruction(
+        &self,
+        signature: &str,
+        _expected: &ExpectedInstruction,
+    ) -> Result<bool> {
+        check_signature(signature)?;
+        Ok(true)
+    }
+
+    async fn check_program_instruction(
+        &self,
+        signature: &str,
+        _expected: &ExpectedInstruction,
+    ) -> Result<TransactionStatus> {
+        check_signature(signature)?;
+        Ok(TransactionStatus::Confirmed)
+    }
+
+    async fn list_signatures_since(
+        &self,
+        _address: &Pubkey,
+        _until: Option<&str>,
+    ) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_balance_changes(
+        &self,
+        _signature: &str,
+        _mint: &str,
+    ) -> Result<Option<BalanceChanges>> {
+        Ok(None)
+    }
+
+    async fn fetch_program_transaction(
+        &self,
+        _program_id: &Pubkey,
+        _signature: &str,
+    ) -> Result<Option<ProgramTransaction>> {
+        Ok(None)
+    }
+
+    /// Nothing is ever submitted to the mock, so the stream stays open and silent.
+    async fn watch_program_signatures(
+        &self,
+        _program_id: &Pubkey,
+        sender: mpsc::Sender<String>,
+    ) -> Result<()> {
+        sender.closed().await;
+        Ok(())
+    }
+
+    async fn simulate_transaction(&self, _transaction: &str) -> Result<SimulationResult> {
+        Ok(SimulationResult {
+            success: true,
+            logs: Vec::new(),
+            units_consumed: Some(0),
+            fee_lamports: Some(SIGNATURE_FEE_LAMPORTS),
+            error: None,
+        })
+    }
+
+    async fn estimate_cost(
+        &self,
+        _instructions: &[Instruction],
+        _fee_payer: &Pubkey,
+        _blockhash: Hash,
+        new_account_sizes: &[usize],
+    ) -> Result<CostEstimate> {
+        let rent = Rent::default();
+        let rent_lamports = new_account_sizes
+            .iter()
+            .map(|&size| rent.minimum_balance(size))
+            .sum::<u64>();
+
+        Ok(CostEstimate {
+            fee_lamports: SIGNATURE_FEE_LAMPORTS,
+            priority_fee_lamports: 0,
+            rent_lamports,
+            total_lamports: SIGNATURE_FEE_LAMPORTS + rent_lamports,
+        })
+    }
+}
@@ -1,5 +1,195 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use solana_client::client_error::ClientError;
+use solana_sdk::{hash::Hash, instruction::Instruction, pubkey::Pubkey};
+use tokio::sync::mpsc;
+
+use crate::{
+    config::{SolanaCluster, SolanaConfig},
+    error::{AppError, Result},
+    infrastructure::db::entities::canvas,
+    services::solana::{
+        client::{ProgramRegistry, RecentBlockhash},
+        fees::CostEstimate,
+        indexer::ProgramTransaction,
+        simulate::SimulationResult,
+        transactions::ExpectedInstruction,
+    },
+};
+
+pub mod accounts;
+pub mod balances;
+pub mod blockhash;
 pub mod client;
+pub mod cluster;
+pub mod escrow;
+pub mod fees;
+#[cfg(feature = "geyser")]
+pub mod geyser;
+pub mod indexer;
+pub mod mock;
+pub mod retry;
+pub mod signatures;
+pub mod simulate;
+pub mod stream;
+pub mod transactions;
 pub mod verify;
+pub mod wallet;
+
+pub use balances::BalanceChanges;
+pub use blockhash::spawn_blockhash_refresher;
+pub use client::{ProgramDeployment, SolanaClient};
+pub use indexer::spawn_event_indexer;
+pub use mock::MockSolanaClient;
+pub use stream::spawn_event_stream;
+pub use verify::TransactionStatus;
+
+/// Everything the server needs from Solana. `SolanaClient` talks to an RPC node and
+/// `MockSolanaClient` answers deterministically, so tests and local development can run
+/// without a validator.
+#[async_trait]
+pub trait SolanaService: Send + Sync {
+    /// Short backend name for logs, e.g. `rpc`.
+    fn name(&self) -> &'static str;
+
+    fn programs(&self) -> &ProgramRegistry;
+
+    fn collection_mint(&self) -> Option<&Pubkey>;
+
+    fn blockhash_ttl(&self) -> Duration;
+
+    /// Refuses to run against a node on another cluster or without the program deployed.
+    async fn verify_cluster(&self, cluster: SolanaCluster) -> Result<()>;
+
+    /// Served from a cache kept current by the blockhash refresher.
+    async fn get_recent_blockhash(&self) -> std::result::Result<Hash, ClientError>;
+
+    /// Fetches the latest blockhash into the cache.
+    async fn refresh_blockhash(&self) -> std::result::Result<RecentBlockhash, ClientError>;
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> std::result::Result<Vec<u8>, ClientError>;
+
+    /// Account data, or `None` if the account does not exist.
+    async fn find_account_data(
+        &self,
+        pubkey: &Pubkey,
+    ) -> std::result::Result<Option<Vec<u8>>, ClientError>;
+
+    /// Whether the account is still open, e.g. after a burn that should have closed it.
+    async fn account_exists(&self, pubkey: &Pubkey) -> std::result::Result<bool, ClientError>;
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> std::result::Result<u64, ClientError>;
+
+    /// Waits for the transaction to confirm and checks it invoked `program_id`. `false` if
+    /// it failed on-chain.
+    async fn verify_program_transaction(&self, signature: &str, program_id: &str) -> Result<bool>;
+
+    /// Like `verify_program_transaction`, but the transaction must also carry the expected
+    /// program instruction.
+    async fn verify_program_instruction(
+        &self,
+        signature: &str,
+        expected: &ExpectedInstruction,
+    ) -> Result<bool>;
+
+    /// One status lookup without waiting, for background watchers.
+    async fn check_program_instruction(
+        &self,
+        signature: &str,
+        expected: &ExpectedInstruction,
+    ) -> Result<TransactionStatus>;
+
+    /// Successful signatures involving `address` that are newer than `until`, oldest first.
+    async fn list_signatures_since(
+        &self,
+        address: &Pubkey,
+        until: Option<&str>,
+    ) -> Result<Vec<String>>;
+
+    /// Lamport and token holder changes of a transaction touching `mint`. `None` if it failed.
+    async fn fetch_balance_changes(
+        &self,
+        signature: &str,
+        mint: &str,
+    ) -> Result<Option<BalanceChanges>>;
+
+    /// Decoded `program_id` instructions of a transaction. `None` if it failed on-chain.
+    async fn fetch_program_transaction(
+        &self,
+        program_id: &Pubkey,
+        signature: &str,
+    ) -> Result<Option<ProgramTransaction>>;
+
+    /// Sends the signatures of successful `program_id` transactions as they confirm, until
+    /// the subscription drops or `sender` is closed.
+    async fn watch_program_signatures(
+        &self,
+        program_id: &Pubkey,
+        sender: mpsc::Sender<String>,
+    ) -> Result<()>;
+
+    async fn simulate_transaction(&self, transaction: &str) -> Result<SimulationResult>;
+
+    /// Fees plus rent for the accounts of `new_account_sizes` the transaction creates.
+    async fn estimate_cost(
+        &self,
+        instructions: &[Instruction],
+        fee_payer: &Pubkey,
+        blockhash: Hash,
+        new_account_sizes: &[usize],
+    ) -> Result<CostEstimate>;
+
+    /// The deployment new canvases are published under.
+    fn current_program(&self) -> ProgramDeployment {
+        self.programs().current_program()
+    }
+
+    /// The deployment recorded on a canvas. `None` if that program is no longer configured.
+    fn program_for(&self, program_id: Option<&str>) -> Option<ProgramDeployment> {
+        self.programs().program_for(program_id)
+    }
+
+    /// The current deployment followed by the legacy ones.
+    fn deployments(&self) -> Vec<ProgramDeployment> {
+        self.programs().deployments()
+    }
+
+    fn derive_metadata_pda(&self, mint: &Pubkey) -> (Pubkey, u8) {
+        client::derive_metadata_pda(mint)
+    }
+
+    fn derive_master_edition_pda(&self, mint: &Pubkey) -> (Pubkey, u8) {
+        client::derive_master_edition_pda(mint)
+    }
+
+    fn derive_edition_marker_pda(&self, master_mint: &Pubkey, edition: u64) -> (Pubkey, u8) {
+        client::derive_edition_marker_pda(master_mint, edition)
+    }
+}
+
+/// Builds the RPC client, or the deterministic mock when `SOLANA_MOCK` is set.
+pub fn from_config(config: &SolanaConfig) -> Arc<dyn SolanaService> {
+    if config.mock {
+        Arc::new(MockSolanaClient::new(config))
+    } else {
+        Arc::new(SolanaClient::initialize(config))
+    }
+}
 
-pub use client::SolanaClient;
-pub use verify::verify_program_transaction;
+/// Deployment the canvas was published under, for deriving its accounts and checking its
+/// transactions.
+pub fn canvas_program(
+    solana_client: &dyn SolanaService,
+    canvas: &canvas::Model,
+) -> Result<ProgramDeployment> {
+    solana_client
+        .program_for(canvas.program_id.as_deref())
+        .ok_or_else(|| {
+            AppError::InternalServerError(format!(
+                "Canvas {} was published under program {}, which is not configured",
+                canvas.id,
+                canvas.program_id.as_deref().unwrap_or_default()
+            ))
+        })
+}
@@ -0,0 +1,82 @@
+use std::{future::Future, time::Duration};
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, reqwest::StatusCode},
+    rpc_request::RpcError,
+};
+
+use crate::config::SolanaConfig;
+
+/// JSON-RPC code for a node that is behind or shedding load, e.g. "Node is unhealthy".
+const NODE_UNHEALTHY: i64 = -32005;
+
+/// How failed RPC calls are retried. Delays grow exponentially from `base_delay` up to
+/// `max_delay`, with full jitter so instances that failed together do not retry together.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &SolanaConfig) -> Self {
+        Self {
+            max_attempts: config.rpc_max_attempts.max(1),
+            base_delay: Duration::from_millis(config.rpc_retry_base_ms),
+            max_delay: Duration::from_millis(config.rpc_retry_max_ms),
+        }
+    }
+
+    /// Delay before retry number `attempt`, counting from zero.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let millis = u64::try_from(ceiling.as_millis()).unwrap_or(u64::MAX);
+
+        Duration::from_millis(rand::random_range(0..=millis))
+    }
+
+    /// Runs `operation` until it succeeds, fails with an error that retrying cannot fix,
+    /// or `max_attempts` is reached.
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, ClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt + 1 < self.max_attempts && is_retryable(&e) => {
+                    let delay = self.backoff(attempt);
+                    tracing::debug!(attempt, ?delay, error = %e, "Retrying Solana RPC call");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Rate limits, overloaded nodes, timeouts and dropped connections. Anything the node
+/// answered deliberately, like a missing account or a failed simulation, is final.
+pub fn is_retryable(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status().is_some_and(|status| {
+                    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                })
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == NODE_UNHEALTHY
+        }
+        _ => false,
+    }
+}
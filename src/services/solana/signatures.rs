@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::{
+            entities::consumed_signature::{self, SignatureOperation},
+            repositories::ConsumedSignatureRepository,
+        },
+    },
+};
+
+const CONSUMED_SIGNATURE_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Record of a signature authorizing an operation that is not tied to one pixel or amount.
+pub fn operation_record(
+    signature: &str,
+    operation: SignatureOperation,
+    canvas_id: Uuid,
+    user_id: Uuid,
+) -> consumed_signature::Model {
+    consumed_signature::Model {
+        signature: signature.to_string(),
+        operation,
+        canvas_id,
+        user_id,
+        x: None,
+        y: None,
+        color: None,
+        amount_lamports: None,
+        created_at: Utc::now(),
+    }
+}
+
+/// Looks up what a signature was already used for, checking Redis before the database.
+pub async fn find_consumed_signature(
+    state: &AppState,
+    signature: &str,
+) -> Result<Option<consumed_signature::Model>> {
+    let cache_key = CacheKey::consumed_signature(signature);
+    if let Ok(Some(record)) = state
+        .cache
+        .redis
+        .get::<consumed_signature::Model>(&cache_key)
+        .await
+    {
+        return Ok(Some(record));
+    }
+
+    let record =
+        ConsumedSignatureRepository::find_by_signature(state.db.get_connection(), signature)
+            .await?;
+
+    if let Some(ref record) = record {
+        let _ = state
+            .cache
+            .redis
+            .set(&cache_key, record, CONSUMED_SIGNATURE_CACHE_TTL)
+            .await;
+    }
+
+    Ok(record)
+}
+
+/// Claims a signature for an operation. Returns `false` if another request claimed it first.
+pub async fn consume_signature(
+    state: &AppState,
+    record: &consumed_signature::Model,
+) -> Result<bool> {
+    let is_consumed =
+        ConsumedSignatureRepository::try_consume(state.db.get_connection(), record.clone()).await?;
+
+    if is_consumed {
+        let _ = state
+            .cache
+            .redis
+            .set(
+                &CacheKey::consumed_signature(&record.signature),
+                record,
+                CONSUMED_SIGNATURE_CACHE_TTL,
+            )
+            .await;
+    }
+
+    Ok(is_consumed)
+}
+
+/// Frees a signature whose operation failed after it was claimed, so it can be retried.
+pub async fn release_signature(state: &AppState, signature: &str) -> Result<()> {
+    ConsumedSignatureRepository::release(state.db.get_connection(), signature).await?;
+    state
+        .cache
+        .redis
+        .delete(&CacheKey::consumed_signature(signature))
+        .await
+}
+
+/// Rejects a signature already used for a different operation, before it is verified on-chain.
+pub async fn ensure_signature_unused(
+    state: &AppState,
+    record: &consumed_signature::Model,
+) -> Result<()> {
+    match find_consumed_signature(state, &record.signature).await? {
+        Some(existing) if !is_same_operation(&existing, record) => {
+            Err(AppError::SignatureAlreadyUsed)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Claims a signature for an operation that is safe to confirm twice, such as a publish or a
+/// mint. Retries of the same operation pass; any other use of the signature is rejected.
+pub async fn claim_signature(state: &AppState, record: &consumed_signature::Model) -> Result<()> {
+    if consume_signature(state, record).await? {
+        return Ok(());
+    }
+
+    match find_consumed_signature(state, &record.signature).await? {
+        Some(existing) if is_same_operation(&existing, record) => Ok(()),
+        _ => Err(AppError::SignatureAlreadyUsed),
+    }
+}
+
+fn is_same_operation(
+    existing: &consumed_signature::Model,
+    record: &consumed_signature::Model,
+) -> bool {
+    existing.operation == record.operation
+        && existing.canvas_id == record.canvas_id
+        && existing.user_id == record.user_id
+        && existing.x == record.x
+        && existing.y == record.y
+        && existing.color == record.color
+        && existing.amount_lamports == record.amount_lamports
+}
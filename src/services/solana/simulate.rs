@@ -0,0 +1,140 @@
+use serde::Serialize;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use solana_transaction_status::{
+    EncodedTransaction, TransactionBinaryEncoding, UiTransactionEncoding,
+};
+
+use crate::{
+    error::{AppError, Result},
+    services::solana::SolanaClient,
+};
+
+/// Outcome of running a transaction against the current bank without submitting it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationResult {
+    pub success: bool,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    pub fee_lamports: Option<u64>,
+    pub error: Option<SimulationError>,
+}
+
+/// A failed simulation, with a `kind` clients can match on and a message they can show.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationError {
+    pub kind: &'static str,
+    pub message: String,
+    pub instruction_index: Option<u8>,
+    pub custom_code: Option<u32>,
+}
+
+/// Simulates a base64 wire-format transaction. Signatures are not checked and the
+/// blockhash is replaced, so unsigned transactions from this server simulate as-is.
+pub async fn simulate_transaction(
+    solana_client: &SolanaClient,
+    transaction: &str,
+) -> Result<SimulationResult> {
+    let transaction =
+        EncodedTransaction::Binary(transaction.to_string(), TransactionBinaryEncoding::Base64)
+            .decode()
+            .ok_or(AppError::InvalidParams(
+                "Transaction must be a base64 wire-format transaction".into(),
+            ))?;
+
+    let client = solana_client.get_client();
+    let response = solana_client
+        .with_retry(|| {
+            client.simulate_transaction_with_config(
+                &transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    ..Default::default()
+                },
+            )
+        })
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to simulate transaction: {}", e)))?
+        .value;
+
+    let logs = response.logs.unwrap_or_default();
+    let error = response
+        .err
+        .map(|err| decode_error(TransactionError::from(err), &logs));
+
+    Ok(SimulationResult {
+        success: error.is_none(),
+        logs,
+        units_consumed: response.units_consumed,
+        fee_lamports: response.fee,
+        error,
+    })
+}
+
+fn decode_error(err: TransactionError, logs: &[String]) -> SimulationError {
+    let error = |kind, message: &str| SimulationError {
+        kind,
+        message: message.to_string(),
+        instruction_index: None,
+        custom_code: None,
+    };
+
+    match err {
+        TransactionError::InsufficientFundsForFee | TransactionError::AccountNotFound => error(
+            "insufficient_funds",
+            "The fee payer does not have enough SOL for the transaction fee",
+        ),
+        TransactionError::InsufficientFundsForRent { .. } => error(
+            "insufficient_funds",
+            "An account would be left without enough SOL for rent",
+        ),
+        TransactionError::BlockhashNotFound => error(
+            "blockhash_expired",
+            "The blockhash expired. Request a new transaction",
+        ),
+        TransactionError::AlreadyProcessed => error(
+            "already_processed",
+            "This transaction was already processed",
+        ),
+        TransactionError::InstructionError(index, instruction_error) => {
+            let (kind, message, custom_code) = match instruction_error {
+                // The system program's transfer reports a short balance as custom error 1.
+                _ if logs.iter().any(|log| log.contains("insufficient lamports")) => (
+                    "insufficient_funds",
+                    "The wallet does not have enough SOL for this transaction".to_string(),
+                    None,
+                ),
+                InstructionError::InsufficientFunds => (
+                    "insufficient_funds",
+                    "The wallet does not have enough SOL for this transaction".to_string(),
+                    None,
+                ),
+                InstructionError::Custom(code) => (
+                    "program_error",
+                    anchor_error_message(logs)
+                        .unwrap_or_else(|| format!("Program returned error {code}")),
+                    Some(code),
+                ),
+                other => ("instruction_error", other.to_string(), None),
+            };
+
+            SimulationError {
+                kind,
+                message,
+                instruction_index: Some(index),
+                custom_code,
+            }
+        }
+        other => error("transaction_error", &other.to_string()),
+    }
+}
+
+/// The message of an Anchor error log, e.g. `... Error Message: Bid too low.`
+fn anchor_error_message(logs: &[String]) -> Option<String> {
+    logs.iter().find_map(|log| {
+        let (_, message) = log.split_once("Error Message: ")?;
+        Some(message.trim_end_matches('.').to_string())
+    })
+}
@@ -0,0 +1,173 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::StreamExt;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::repositories::OnchainEventRepository,
+    services::solana::{
+        SolanaClient,
+        client::ProgramDeployment,
+        indexer::{resolve_canvas_ids, to_events},
+        transactions::ProgramEvent,
+    },
+    ws::types::RoomCanvasUpdate,
+};
+
+/// Signatures buffered between the subscription and the indexing loop.
+const STREAM_BUFFER: usize = 256;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Streams the transactions of every deployment into the event index as they confirm, and
+/// broadcasts bids to their canvas rooms. The polling indexer keeps running underneath and
+/// picks up anything the stream misses while reconnecting.
+pub fn spawn_event_stream(state: AppState) -> Vec<JoinHandle<()>> {
+    state
+        .solana_client
+        .deployments()
+        .into_iter()
+        .map(|program| {
+            let state = state.clone();
+            tokio::spawn(async move { stream_program_events(state, program).await })
+        })
+        .collect()
+}
+
+async fn stream_program_events(state: AppState, program: ProgramDeployment) {
+    loop {
+        let (sender, mut signatures) = mpsc::channel(STREAM_BUFFER);
+        let solana_client = state.solana_client.clone();
+        let program_id = program.program_id;
+        let watcher = tokio::spawn(async move {
+            solana_client
+                .watch_program_signatures(&program_id, sender)
+                .await
+        });
+
+        while let Some(signature) = signatures.recv().await {
+            if let Err(e) = ingest_signature(&state, &program, &signature).await {
+                tracing::warn!(signature = %signature, error = ?e, "Failed to ingest streamed transaction");
+            }
+        }
+
+        match watcher.await {
+            Ok(Ok(())) => {
+                tracing::warn!(program_id = %program.program_id, "Program event stream closed, reconnecting")
+            }
+            Ok(Err(e)) => {
+                tracing::warn!(program_id = %program.program_id, error = ?e, "Program event stream failed, reconnecting")
+            }
+            Err(e) => {
+                tracing::error!(program_id = %program.program_id, error = ?e, "Program event stream panicked, reconnecting")
+            }
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn ingest_signature(
+    state: &AppState,
+    program: &ProgramDeployment,
+    signature: &str,
+) -> Result<()> {
+    let Some(transaction) = state
+        .solana_client
+        .fetch_program_transaction(&program.program_id, signature)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let bids: Vec<_> = transaction
+        .instructions
+        .iter()
+        .filter_map(|(_, decoded)| match decoded.event {
+            ProgramEvent::BidPlaced {
+                x,
+                y,
+                color,
+                bid_lamports,
+            } => Some((decoded.canvas_pda.to_string(), x, y, color, bid_lamports)),
+            _ => None,
+        })
+        .collect();
+
+    let events = resolve_canvas_ids(state, to_events(signature, transaction)).await?;
+    let canvas_ids: HashMap<_, _> = events
+        .iter()
+        .filter_map(|event| Some((event.canvas_pda.clone(), event.canvas_id?)))
+        .collect();
+
+    OnchainEventRepository::record_events(state.db.get_connection(), events).await?;
+
+    for (canvas_pda, x, y, color, bid_lamports) in bids {
+        let Some(canvas_id) = canvas_ids.get(&canvas_pda) else {
+            continue;
+        };
+
+        state
+            .ws_rooms
+            .broadcast(
+                canvas_id,
+                RoomCanvasUpdate::BidConfirmed {
+                    x,
+                    y,
+                    color,
+                    bid_lamports,
+                    signature: signature.to_string(),
+                },
+            )
+            .await;
+    }
+
+    Ok(())
+}
+
+/// Sends the signature of every successful transaction mentioning the program, using the
+/// node's `logsSubscribe`. Returns when the subscription or the receiver closes.
+pub async fn watch_program_signatures(
+    solana_client: &SolanaClient,
+    program_id: &Pubkey,
+    sender: mpsc::Sender<String>,
+) -> Result<()> {
+    let pubsub = solana_client
+        .get_pubsub_client()
+        .await
+        .ok_or(AppError::SolanaRpc("PubSub endpoint unavailable".into()))?;
+
+    let filter = RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]);
+    let config = RpcTransactionLogsConfig {
+        commitment: Some(CommitmentConfig::confirmed()),
+    };
+    let (mut notifications, unsubscribe) = match pubsub.logs_subscribe(filter, config).await {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            solana_client.reset_pubsub_client().await;
+            return Err(AppError::SolanaRpc(e.to_string()));
+        }
+    };
+
+    while let Some(notification) = notifications.next().await {
+        if notification.value.err.is_some() {
+            continue;
+        }
+        if sender.send(notification.value.signature).await.is_err() {
+            break;
+        }
+    }
+
+    drop(notifications);
+    unsubscribe().await;
+
+    if !sender.is_closed() {
+        solana_client.reset_pubsub_client().await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,593 @@
+use std::str::FromStr;
+
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+    infrastructure::db::{entities::canvas::NftStandard, repositories::UserRepository},
+    services::solana::client::{MPL_CORE_PROGRAM_ID, ProgramDeployment, TOKEN_METADATA_PROGRAM_ID},
+};
+
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+// Program instructions are Anchor-encoded: the first 8 bytes of
+// sha256("global:<name>"), then the borsh-encoded arguments.
+//   publish_canvas(canvas_id: [u8; 16], pixel_colors: [u8; 768])
+//   place_pixel_bid(x: u8, y: u8, color: u8, bid_lamports: u64)
+//   paint_pixels(pixels: Vec<(u8, u8, u8)>)
+//   mint_nft(name: String, symbol: String, uri: String, seller_fee_basis_points: u16)
+//   withdraw_escrow(amount_lamports: u64)
+//   mint_edition(edition_number: u64)
+// Optional accounts that are absent are passed as the program ID.
+
+/// Token Metadata `UpdateMetadataAccountV2` and MPL Core `UpdateV1` both start with this
+/// one-byte tag instead of an Anchor discriminator.
+const METADATA_UPDATE_TAG: u8 = 15;
+
+fn discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("global:{name}").as_bytes());
+    hash[..8].try_into().expect("sha256 is longer than 8 bytes")
+}
+
+/// Borsh encoder for instruction data.
+struct InstructionData(Vec<u8>);
+
+impl InstructionData {
+    fn new(name: &str) -> Self {
+        Self(discriminator(name).to_vec())
+    }
+
+    fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    fn u8(self, value: u8) -> Self {
+        self.bytes(&[value])
+    }
+
+    fn u16(self, value: u16) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn u32(self, value: u32) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn u64(self, value: u64) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn string(self, value: &str) -> Self {
+        self.u32(value.len() as u32).bytes(value.as_bytes())
+    }
+}
+
+/// Borsh decoder for the arguments after an instruction's discriminator.
+struct InstructionReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> InstructionReader<'a> {
+    /// `None` unless the data starts with the discriminator of `name`.
+    fn new(data: &'a [u8], name: &str) -> Option<Self> {
+        let data = data.strip_prefix(&discriminator(name))?;
+        Some(Self { data })
+    }
+
+    /// `None` unless the data starts with the one-byte instruction tag of a native program.
+    fn tagged(data: &'a [u8], tag: u8) -> Option<Self> {
+        let data = data.strip_prefix(&[tag])?;
+        Some(Self { data })
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2)
+            .map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        self.take(len)
+            .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+    }
+
+    /// A borsh `Option`, whose value `read` decodes when present.
+    fn option<T>(&mut self, read: impl FnOnce(&mut Self) -> Option<T>) -> Option<Option<T>> {
+        match self.u8()? {
+            0 => Some(None),
+            1 => read(self).map(Some),
+            _ => None,
+        }
+    }
+
+    fn pixels(&mut self) -> Option<Vec<(u8, u8, u8)>> {
+        let count = self.u32()?;
+        (0..count)
+            .map(|_| Some((self.u8()?, self.u8()?, self.u8()?)))
+            .collect()
+    }
+}
+
+/// A program instruction decoded from any transaction, for the on-chain event index.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgramEvent {
+    CanvasCreated {
+        canvas_id: Uuid,
+    },
+    BidPlaced {
+        x: u8,
+        y: u8,
+        color: u8,
+        bid_lamports: u64,
+    },
+    PixelsPainted {
+        pixels: Vec<(u8, u8, u8)>,
+    },
+    Minted {
+        name: String,
+        symbol: String,
+        uri: String,
+        seller_fee_basis_points: u16,
+    },
+    EscrowWithdrawn {
+        amount_lamports: u64,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodedInstruction {
+    pub signer: Pubkey,
+    pub canvas_pda: Pubkey,
+    pub event: ProgramEvent,
+}
+
+/// Decodes a program instruction from its accounts and data. `None` for instructions
+/// the index does not track or cannot parse.
+pub fn decode_program_instruction(accounts: &[Pubkey], data: &[u8]) -> Option<DecodedInstruction> {
+    let (event, canvas_index) =
+        if let Some(mut reader) = InstructionReader::new(data, "publish_canvas") {
+            let canvas_id = Uuid::from_slice(reader.take(16)?).ok()?;
+            (ProgramEvent::CanvasCreated { canvas_id }, 2)
+        } else if let Some(mut reader) = InstructionReader::new(data, "place_pixel_bid") {
+            let event = ProgramEvent::BidPlaced {
+                x: reader.u8()?,
+                y: reader.u8()?,
+                color: reader.u8()?,
+                bid_lamports: reader.u64()?,
+            };
+            (event, 2)
+        } else if let Some(mut reader) = InstructionReader::new(data, "paint_pixels") {
+            (
+                ProgramEvent::PixelsPainted {
+                    pixels: reader.pixels()?,
+                },
+                1,
+            )
+        } else if let Some(mut reader) = InstructionReader::new(data, "mint_nft") {
+            let event = ProgramEvent::Minted {
+                name: reader.string()?,
+                symbol: reader.string()?,
+                uri: reader.string()?,
+                seller_fee_basis_points: reader.u16()?,
+            };
+            (event, 2)
+        } else if let Some(mut reader) = InstructionReader::new(data, "withdraw_escrow") {
+            (
+                ProgramEvent::EscrowWithdrawn {
+                    amount_lamports: reader.u64()?,
+                },
+                2,
+            )
+        } else {
+            return None;
+        };
+
+    Some(DecodedInstruction {
+        signer: *accounts.first()?,
+        canvas_pda: *accounts.get(canvas_index)?,
+        event,
+    })
+}
+
+/// What a confirmation expects its transaction to have done.
+#[derive(Debug, Clone)]
+pub enum ExpectedOperation {
+    Publish,
+    Bid {
+        x: u8,
+        y: u8,
+        color: u8,
+        bid_lamports: u64,
+    },
+    /// Any order, as long as exactly these pixels are painted.
+    Paint {
+        pixels: Vec<(u8, u8, u8)>,
+    },
+    Mint,
+    WithdrawEscrow {
+        amount_lamports: u64,
+    },
+    /// Printing edition `edition_number` into its program-derived print mint.
+    EditionMint {
+        edition_mint: Pubkey,
+        edition_number: u64,
+    },
+    /// Pointing the minted asset at `uri`. `account` is the Token Metadata account, or the
+    /// Core asset.
+    MetadataUpdate {
+        standard: NftStandard,
+        account: Pubkey,
+        uri: String,
+    },
+}
+
+/// A program instruction a confirmation must find in its transaction, signed by `signer`.
+#[derive(Debug, Clone)]
+pub struct ExpectedInstruction {
+    /// Deployment the canvas was published under, which must have run the instruction
+    /// unless it belongs to the asset program (see `program_id`).
+    pub program: ProgramDeployment,
+    pub signer: Pubkey,
+    pub canvas_id: Uuid,
+    pub operation: ExpectedOperation,
+}
+
+impl ExpectedInstruction {
+    /// Program that must run the instruction.
+    pub fn program_id(&self) -> Pubkey {
+        match &self.operation {
+            ExpectedOperation::MetadataUpdate {
+                standard: NftStandard::TokenMetadata,
+                ..
+            } => TOKEN_METADATA_PROGRAM_ID,
+            ExpectedOperation::MetadataUpdate {
+                standard: NftStandard::Core,
+                ..
+            } => MPL_CORE_PROGRAM_ID,
+            _ => self.program.program_id,
+        }
+    }
+
+    /// Whether a program instruction with these accounts and data performs the operation.
+    pub fn matches(&self, accounts: &[Pubkey], data: &[u8]) -> bool {
+        let (canvas_pda, _) = self.program.derive_canvas_pda_from_uuid(&self.canvas_id);
+
+        match &self.operation {
+            ExpectedOperation::Publish => {
+                let Some(mut reader) = InstructionReader::new(data, "publish_canvas") else {
+                    return false;
+                };
+                accounts.first() == Some(&self.signer)
+                    && accounts.get(2) == Some(&canvas_pda)
+                    && reader.take(16) == Some(self.canvas_id.as_bytes().as_slice())
+            }
+            ExpectedOperation::Bid {
+                x,
+                y,
+                color,
+                bid_lamports,
+            } => {
+                let Some(mut reader) = InstructionReader::new(data, "place_pixel_bid") else {
+                    return false;
+                };
+                accounts.first() == Some(&self.signer)
+                    && accounts.get(2) == Some(&canvas_pda)
+                    && reader.u8() == Some(*x)
+                    && reader.u8() == Some(*y)
+                    && reader.u8() == Some(*color)
+                    && reader.u64() == Some(*bid_lamports)
+            }
+            ExpectedOperation::Paint { pixels } => {
+                let Some(mut reader) = InstructionReader::new(data, "paint_pixels") else {
+                    return false;
+                };
+                let (Some(mut painted), mut expected) = (reader.pixels(), pixels.clone()) else {
+                    return false;
+                };
+                painted.sort_unstable();
+                expected.sort_unstable();
+
+                accounts.first() == Some(&self.signer)
+                    && accounts.get(1) == Some(&canvas_pda)
+                    && painted == expected
+            }
+            ExpectedOperation::Mint => {
+                InstructionReader::new(data, "mint_nft").is_some()
+                    && accounts.first() == Some(&self.signer)
+                    && accounts.get(2) == Some(&canvas_pda)
+            }
+            ExpectedOperation::WithdrawEscrow { amount_lamports } => {
+                let Some(mut reader) = InstructionReader::new(data, "withdraw_escrow") else {
+                    return false;
+                };
+                accounts.first() == Some(&self.signer)
+                    && accounts.get(2) == Some(&canvas_pda)
+                    && reader.u64() == Some(*amount_lamports)
+            }
+            ExpectedOperation::EditionMint {
+                edition_mint,
+                edition_number,
+            } => {
+                let Some(mut reader) = InstructionReader::new(data, "mint_edition") else {
+                    return false;
+                };
+                accounts.first() == Some(&self.signer)
+                    && accounts.get(2) == Some(&canvas_pda)
+                    && accounts.get(3) == Some(edition_mint)
+                    && reader.u64() == Some(*edition_number)
+            }
+            ExpectedOperation::MetadataUpdate {
+                standard,
+                account,
+                uri,
+            } => {
+                let Some(mut reader) = InstructionReader::tagged(data, METADATA_UPDATE_TAG) else {
+                    return false;
+                };
+                let new_uri = match standard {
+                    // Option<DataV2>, which starts with the name, symbol and uri
+                    NftStandard::TokenMetadata => reader.option(|data| {
+                        data.string()?;
+                        data.string()?;
+                        data.string()
+                    }),
+                    // new_name: Option<String>, then new_uri: Option<String>
+                    NftStandard::Core => reader
+                        .option(InstructionReader::string)
+                        .and_then(|_| reader.option(InstructionReader::string)),
+                };
+
+                // The update authority signs, at a position that depends on the program
+                accounts.first() == Some(account)
+                    && accounts.contains(&self.signer)
+                    && new_uri.flatten().as_deref() == Some(uri.as_str())
+            }
+        }
+    }
+}
+
+/// The user's wallet, which signs and pays for the transactions built for them.
+pub async fn user_wallet(state: &AppState, user_id: Uuid) -> Result<Pubkey> {
+    let user = UserRepository::find_user_by_id(state.db.get_connection(), user_id)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+
+    Pubkey::from_str(&user.wallet_address)
+        .map_err(|_| AppError::InternalServerError("Invalid stored wallet address".into()))
+}
+
+pub struct MintAccounts {
+    pub asset_program: Pubkey,
+    pub asset: Pubkey,
+    pub metadata: Option<Pubkey>,
+    pub master_edition: Option<Pubkey>,
+    pub collection_mint: Option<Pubkey>,
+    pub collection_metadata: Option<Pubkey>,
+    pub collection_master_edition: Option<Pubkey>,
+}
+
+pub struct MintArgs<'a> {
+    pub name: &'a str,
+    pub symbol: &'a str,
+    pub uri: &'a str,
+    pub seller_fee_basis_points: u16,
+}
+
+pub fn publish_canvas_instruction(
+    program: &ProgramDeployment,
+    owner: &Pubkey,
+    canvas_id: &Uuid,
+    pixel_colors: &[u8],
+) -> Instruction {
+    let (config_pda, _) = program.derive_config_pda();
+    let (canvas_pda, _) = program.derive_canvas_pda_from_uuid(canvas_id);
+
+    Instruction {
+        program_id: program.program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(canvas_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: InstructionData::new("publish_canvas")
+            .bytes(canvas_id.as_bytes())
+            .bytes(pixel_colors)
+            .0,
+    }
+}
+
+/// The bid is held in the canvas escrow. `previous_owner`, when the pixel has one, is credited
+/// the outbid price there and withdraws it with `withdraw_escrow_instruction`.
+pub fn place_pixel_bid_instruction(
+    program: &ProgramDeployment,
+    bidder: &Pubkey,
+    canvas_id: &Uuid,
+    (x, y, color): (u8, u8, u8),
+    bid_lamports: u64,
+    previous_owner: Option<&Pubkey>,
+) -> Instruction {
+    let program_id = program.program_id;
+    let (config_pda, _) = program.derive_config_pda();
+    let (canvas_pda, _) = program.derive_canvas_pda_from_uuid(canvas_id);
+    let (pixel_pda, _) = program.derive_pixel_pda(canvas_id, x, y);
+    let previous_owner = match previous_owner {
+        Some(previous_owner) => AccountMeta::new(*previous_owner, false),
+        None => AccountMeta::new_readonly(program_id, false),
+    };
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(*bidder, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(canvas_pda, false),
+            AccountMeta::new(pixel_pda, false),
+            previous_owner,
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: InstructionData::new("place_pixel_bid")
+            .u8(x)
+            .u8(y)
+            .u8(color)
+            .u64(bid_lamports)
+            .0,
+    }
+}
+
+/// The pixel accounts follow the canvas, in the order of `pixels`.
+pub fn paint_pixels_instruction(
+    program: &ProgramDeployment,
+    owner: &Pubkey,
+    canvas_id: &Uuid,
+    pixels: &[(u8, u8, u8)],
+) -> Instruction {
+    let (canvas_pda, _) = program.derive_canvas_pda_from_uuid(canvas_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(canvas_pda, false),
+    ];
+    let mut data = InstructionData::new("paint_pixels").u32(pixels.len() as u32);
+    for &(x, y, color) in pixels {
+        accounts.push(AccountMeta::new(
+            program.derive_pixel_pda(canvas_id, x, y).0,
+            false,
+        ));
+        data = data.u8(x).u8(y).u8(color);
+    }
+
+    Instruction {
+        program_id: program.program_id,
+        accounts,
+        data: data.0,
+    }
+}
+
+pub fn mint_nft_instruction(
+    program: &ProgramDeployment,
+    owner: &Pubkey,
+    canvas_id: &Uuid,
+    accounts: &MintAccounts,
+    args: &MintArgs,
+) -> Instruction {
+    let program_id = program.program_id;
+    let (config_pda, _) = program.derive_config_pda();
+    let (canvas_pda, _) = program.derive_canvas_pda_from_uuid(canvas_id);
+    let optional = |account: Option<Pubkey>, is_writable: bool| match account {
+        Some(account) if is_writable => AccountMeta::new(account, false),
+        Some(account) => AccountMeta::new_readonly(account, false),
+        None => AccountMeta::new_readonly(program_id, false),
+    };
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(canvas_pda, false),
+            AccountMeta::new(accounts.asset, false),
+            optional(accounts.metadata, true),
+            optional(accounts.master_edition, true),
+            optional(accounts.collection_mint, false),
+            optional(accounts.collection_metadata, true),
+            optional(accounts.collection_master_edition, false),
+            AccountMeta::new_readonly(accounts.asset_program, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: InstructionData::new("mint_nft")
+            .string(args.name)
+            .string(args.symbol)
+            .string(args.uri)
+            .u16(args.seller_fee_basis_points)
+            .0,
+    }
+}
+
+/// Pays the claimant's escrowed refunds and mint payouts on the canvas out of its escrow.
+pub fn withdraw_escrow_instruction(
+    program: &ProgramDeployment,
+    claimant: &Pubkey,
+    canvas_id: &Uuid,
+    amount_lamports: u64,
+) -> Instruction {
+    let (config_pda, _) = program.derive_config_pda();
+    let (canvas_pda, _) = program.derive_canvas_pda_from_uuid(canvas_id);
+
+    Instruction {
+        program_id: program.program_id,
+        accounts: vec![
+            AccountMeta::new(*claimant, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(canvas_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: InstructionData::new("withdraw_escrow")
+            .u64(amount_lamports)
+            .0,
+    }
+}
+
+pub fn memo_instruction(memo: &str) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: Vec::new(),
+        data: memo.as_bytes().to_vec(),
+    }
+}
+
+/// Base64 wire-format transaction with empty signatures, for the fee payer's wallet to sign.
+pub fn build_unsigned_transaction(
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    blockhash: Hash,
+) -> String {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(fee_payer));
+    transaction.message.recent_blockhash = blockhash;
+
+    // Fewer than 128 signers, so the compact-u16 length is a single byte.
+    let signer_count = transaction.message.header.num_required_signatures;
+    let mut wire = Vec::with_capacity(1 + 64 * signer_count as usize);
+    wire.push(signer_count);
+    wire.resize(1 + 64 * signer_count as usize, 0);
+    wire.extend(transaction.message_data());
+
+    base64::engine::general_purpose::STANDARD.encode(wire)
+}
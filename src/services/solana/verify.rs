@@ -1,13 +1,37 @@
 use std::{str::FromStr, time::Duration};
 
-use solana_client::nonblocking::rpc_client::RpcClient;
+use futures::StreamExt;
+use solana_client::{
+    client_error::ClientError, rpc_config::RpcSignatureSubscribeConfig,
+    rpc_response::RpcSignatureResult,
+};
+use solana_commitment_config::CommitmentConfig;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
 use solana_transaction_status::UiTransactionEncoding;
+use tokio::time::Instant;
 
-use crate::error::{AppError, Result};
+use crate::{
+    error::{AppError, Result},
+    services::solana::{SolanaClient, transactions::ExpectedInstruction},
+};
 
+/// Longest wait for a submitted transaction to confirm.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(30);
+/// Floor for the backoff between status polls, since full jitter can pick a zero delay.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How waiting on a signature ended.
+enum Confirmation {
+    Confirmed,
+    Failed,
+    /// Still unconfirmed at the timeout, with the last status lookup error if any.
+    TimedOut(Option<String>),
+}
+
+/// Waits for the transaction to confirm and checks it invoked the program. Confirmation
+/// comes from a `signatureSubscribe` notification, or from polling when PubSub is unavailable.
 pub async fn verify_program_transaction(
-    client: &RpcClient,
+    solana_client: &SolanaClient,
     signature: &str,
     program_id: &str,
 ) -> Result<bool> {
@@ -17,57 +41,225 @@ pub async fn verify_program_transaction(
     let program_pubkey = Pubkey::from_str(program_id)
         .map_err(|_| AppError::InvalidParams("Invalid program ID".into()))?;
 
-    let mut last_status_err = None;
+    if !await_confirmation(solana_client, &transaction_signature).await? {
+        return Ok(false);
+    }
+
+    ensure_program_involved(solana_client, &transaction_signature, &program_pubkey).await?;
+
+    Ok(true)
+}
+
+/// Like `verify_program_transaction`, but the transaction must also carry the expected
+/// program instruction, decoded and checked against the pending operation.
+pub async fn verify_program_instruction(
+    solana_client: &SolanaClient,
+    signature: &str,
+    expected: &ExpectedInstruction,
+) -> Result<bool> {
+    let transaction_signature = Signature::from_str(signature)
+        .map_err(|_| AppError::InvalidParams("Invalid transaction signature".into()))?;
+
+    if !await_confirmation(solana_client, &transaction_signature).await? {
+        return Ok(false);
+    }
+
+    ensure_instruction_matches(solana_client, &transaction_signature, expected).await?;
 
-    // Retry for up to 30 seconds
-    for _ in 0..30 {
-        match client
-            .get_signature_statuses(&[transaction_signature])
-            .await
-        {
-            Ok(response) => {
-                if let Some(Some(status)) = response.value.first() {
-                    if status.err.is_some() {
-                        return Ok(false);
-                    }
-
-                    if matches!(
-                        status.confirmation_status,
-                        Some(solana_transaction_status::TransactionConfirmationStatus::Processed)
-                            | Some(
-                                solana_transaction_status::TransactionConfirmationStatus::Confirmed
-                            )
-                            | Some(
-                                solana_transaction_status::TransactionConfirmationStatus::Finalized
-                            )
-                    ) {
-                        break;
-                    }
+    Ok(true)
+}
+
+/// `false` if the transaction failed on-chain.
+async fn await_confirmation(
+    solana_client: &SolanaClient,
+    transaction_signature: &Signature,
+) -> Result<bool> {
+    let confirmation = match subscribe_confirmation(solana_client, transaction_signature).await {
+        Some(confirmation) => confirmation,
+        None => poll_confirmation(solana_client, transaction_signature).await,
+    };
+
+    match confirmation {
+        Confirmation::Failed => Ok(false),
+        Confirmation::TimedOut(Some(last_status_err)) => Err(AppError::SolanaRpc(format!(
+            "Transaction not confirmed after {}s. Last error: {}",
+            CONFIRMATION_TIMEOUT.as_secs(),
+            last_status_err
+        ))),
+        Confirmation::Confirmed | Confirmation::TimedOut(None) => Ok(true),
+    }
+}
+
+/// `None` means the subscription could not be used and the caller should poll instead.
+async fn subscribe_confirmation(
+    solana_client: &SolanaClient,
+    transaction_signature: &Signature,
+) -> Option<Confirmation> {
+    let pubsub = solana_client.get_pubsub_client().await?;
+
+    let config = RpcSignatureSubscribeConfig {
+        commitment: Some(CommitmentConfig::confirmed()),
+        enable_received_notification: Some(false),
+    };
+    let (mut notifications, unsubscribe) = match pubsub
+        .signature_subscribe(transaction_signature, Some(config))
+        .await
+    {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            tracing::warn!(error = %e, "Signature subscription failed, polling instead");
+            solana_client.reset_pubsub_client().await;
+            return None;
+        }
+    };
+
+    // The transaction may have confirmed before the subscription was registered.
+    let confirmation = match fetch_confirmation(solana_client, transaction_signature).await {
+        Ok(Some(confirmation)) => Some(confirmation),
+        _ => match tokio::time::timeout(CONFIRMATION_TIMEOUT, notifications.next()).await {
+            Ok(Some(notification)) => match notification.value {
+                RpcSignatureResult::ProcessedSignature(result) if result.err.is_some() => {
+                    Some(Confirmation::Failed)
                 }
+                _ => Some(Confirmation::Confirmed),
+            },
+            Ok(None) => {
+                tracing::warn!("Signature subscription closed, polling instead");
+                None
             }
+            Err(_) => Some(Confirmation::TimedOut(None)),
+        },
+    };
+
+    drop(notifications);
+    unsubscribe().await;
+
+    if confirmation.is_none() {
+        solana_client.reset_pubsub_client().await;
+    }
+    confirmation
+}
+
+/// Polls with the RPC retry policy's backoff until `CONFIRMATION_TIMEOUT`.
+async fn poll_confirmation(
+    solana_client: &SolanaClient,
+    transaction_signature: &Signature,
+) -> Confirmation {
+    let deadline = Instant::now() + CONFIRMATION_TIMEOUT;
+    let mut last_status_err = None;
+    let mut attempt = 0;
+
+    loop {
+        match fetch_confirmation(solana_client, transaction_signature).await {
+            Ok(Some(confirmation)) => return confirmation,
+            Ok(None) => {}
             Err(e) => last_status_err = Some(e.to_string()),
         }
 
-        tokio::time::sleep(Duration::from_secs(1)).await;
+        let delay = solana_client
+            .retry_policy()
+            .backoff(attempt)
+            .max(MIN_POLL_INTERVAL);
+        if Instant::now() + delay >= deadline {
+            return Confirmation::TimedOut(last_status_err);
+        }
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
     }
+}
 
-    if last_status_err.is_some() {
-        return Err(AppError::SolanaRpc(format!(
-            "Transaction not confirmed after 30s. Last error: {:?}",
-            last_status_err
-        )));
+/// `None` while the transaction is unknown to the cluster or not yet processed.
+async fn fetch_confirmation(
+    solana_client: &SolanaClient,
+    transaction_signature: &Signature,
+) -> std::result::Result<Option<Confirmation>, ClientError> {
+    let client = solana_client.get_client();
+    let signatures = [*transaction_signature];
+    let response = solana_client
+        .with_retry(|| client.get_signature_statuses(&signatures))
+        .await?;
+
+    let Some(Some(status)) = response.value.first() else {
+        return Ok(None);
+    };
+
+    if status.err.is_some() {
+        return Ok(Some(Confirmation::Failed));
     }
 
-    // If not confirmed after 30 retries
-    let transaction_response = client
-        .get_transaction_with_config(
-            &transaction_signature,
-            solana_client::rpc_config::RpcTransactionConfig {
-                encoding: Some(UiTransactionEncoding::Json),
-                commitment: Some(solana_commitment_config::CommitmentConfig::confirmed()),
-                max_supported_transaction_version: Some(0),
-            },
-        )
+    Ok(status
+        .confirmation_status
+        .is_some()
+        .then_some(Confirmation::Confirmed))
+}
+
+/// Outcome of a single signature status lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// Not seen by the cluster yet, or not confirmed.
+    Pending,
+    Failed,
+    /// Confirmed and carrying the expected instruction.
+    Confirmed,
+}
+
+/// One-shot version of `verify_program_instruction` for background polling.
+pub async fn check_program_instruction(
+    solana_client: &SolanaClient,
+    signature: &str,
+    expected: &ExpectedInstruction,
+) -> Result<TransactionStatus> {
+    let transaction_signature = Signature::from_str(signature)
+        .map_err(|_| AppError::InvalidParams("Invalid transaction signature".into()))?;
+
+    let client = solana_client.get_client();
+    let signatures = [transaction_signature];
+    let response = solana_client
+        .with_retry(|| client.get_signature_statuses(&signatures))
+        .await
+        .map_err(|e| AppError::SolanaRpc(e.to_string()))?;
+
+    let Some(Some(status)) = response.value.first() else {
+        return Ok(TransactionStatus::Pending);
+    };
+
+    if status.err.is_some() {
+        return Ok(TransactionStatus::Failed);
+    }
+
+    if !matches!(
+        status.confirmation_status,
+        Some(solana_transaction_status::TransactionConfirmationStatus::Confirmed)
+            | Some(solana_transaction_status::TransactionConfirmationStatus::Finalized)
+    ) {
+        return Ok(TransactionStatus::Pending);
+    }
+
+    match ensure_instruction_matches(solana_client, &transaction_signature, expected).await {
+        Ok(()) => Ok(TransactionStatus::Confirmed),
+        Err(AppError::InvalidParams(_)) => Ok(TransactionStatus::Failed),
+        Err(e) => Err(e),
+    }
+}
+
+async fn ensure_program_involved(
+    solana_client: &SolanaClient,
+    transaction_signature: &Signature,
+    program_pubkey: &Pubkey,
+) -> Result<()> {
+    let client = solana_client.get_client();
+    let transaction_response = solana_client
+        .with_retry(|| {
+            client.get_transaction_with_config(
+                transaction_signature,
+                solana_client::rpc_config::RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(solana_commitment_config::CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+        })
         .await
         .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch transaction: {}", e)))?;
 
@@ -95,11 +287,72 @@ pub async fn verify_program_transaction(
             .collect(),
     };
 
-    if !account_keys.contains(&program_pubkey) {
+    if !account_keys.contains(program_pubkey) {
         return Err(AppError::InvalidParams(
             "Transaction does not involve our program".into(),
         ));
     }
 
-    Ok(true)
+    Ok(())
+}
+
+async fn ensure_instruction_matches(
+    solana_client: &SolanaClient,
+    transaction_signature: &Signature,
+    expected: &ExpectedInstruction,
+) -> Result<()> {
+    let client = solana_client.get_client();
+    let transaction_response = solana_client
+        .with_retry(|| {
+            client.get_transaction_with_config(
+                transaction_signature,
+                solana_client::rpc_config::RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+        })
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch transaction: {}", e)))?;
+
+    let transaction = transaction_response
+        .transaction
+        .transaction
+        .decode()
+        .ok_or(AppError::SolanaRpc(
+            "Unexpected transaction encoding".into(),
+        ))?;
+
+    // Accounts from address lookup tables are not resolved, so the checked
+    // accounts must be static keys, as they are in transactions built here.
+    let message = &transaction.message;
+    let account_keys = message.static_account_keys();
+    let signer_count = message.header().num_required_signatures as usize;
+
+    let is_signed = account_keys
+        .iter()
+        .take(signer_count)
+        .any(|key| *key == expected.signer);
+
+    let program_id = &expected.program_id();
+    let has_instruction = message.instructions().iter().any(|instruction| {
+        if account_keys.get(instruction.program_id_index as usize) != Some(program_id) {
+            return false;
+        }
+        let accounts: Option<Vec<Pubkey>> = instruction
+            .accounts
+            .iter()
+            .map(|&index| account_keys.get(index as usize).copied())
+            .collect();
+        accounts.is_some_and(|accounts| expected.matches(&accounts, &instruction.data))
+    });
+
+    if !is_signed || !has_instruction {
+        return Err(AppError::InvalidParams(
+            "Transaction does not match the pending operation".into(),
+        ));
+    }
+
+    Ok(())
 }
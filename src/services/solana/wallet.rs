@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    AppState,
+    error::{AppError, Result},
+};
+
+/// Fee of a transaction with a single signature, as built for bids.
+pub const SIGNATURE_FEE_LAMPORTS: u64 = 5000;
+
+/// Whether a wallet can pay for a transaction, so clients can stop before asking for a
+/// signature that would fail on-chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceCheck {
+    pub balance_lamports: u64,
+    /// Amount plus the transaction fee.
+    pub required_lamports: u64,
+    pub sufficient: bool,
+    pub shortfall_lamports: u64,
+}
+
+pub async fn get_balance(state: &AppState, wallet: &Pubkey) -> Result<u64> {
+    state
+        .solana_client
+        .get_balance(wallet)
+        .await
+        .map_err(|e| AppError::SolanaRpc(format!("Failed to fetch balance: {}", e)))
+}
+
+/// Compares the wallet's balance with `amount_lamports` plus one signature fee.
+pub async fn check_balance(
+    state: &AppState,
+    wallet: &Pubkey,
+    amount_lamports: u64,
+) -> Result<BalanceCheck> {
+    let balance_lamports = get_balance(state, wallet).await?;
+    let required_lamports = amount_lamports.saturating_add(SIGNATURE_FEE_LAMPORTS);
+
+    Ok(BalanceCheck {
+        balance_lamports,
+        required_lamports,
+        sufficient: balance_lamports >= required_lamports,
+        shortfall_lamports: required_lamports.saturating_sub(balance_lamports),
+    })
+}
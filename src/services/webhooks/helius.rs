@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    config::WebhookConfig,
+    error::Result,
+    infrastructure::{cache::keys::CacheKey, db::repositories::CanvasRepository},
+    services::{canvas, nft::mint_watch, pixel},
+};
+
+/// Long enough for Helius to deliver a confirmed transaction, including its retries.
+const PENDING_CONFIRMATION_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// One entry of a Helius enhanced transaction webhook. Only the fields needed to
+/// match a pending confirmation are read.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeliusTransaction {
+    pub signature: String,
+    #[serde(default)]
+    pub transaction_error: Option<Value>,
+}
+
+/// A confirmation the client started, kept until its transaction is seen confirmed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum PendingConfirmation {
+    Bid(pixel::types::ConfirmPixelRequest),
+    Publish {
+        canvas_id: Uuid,
+        user_id: Uuid,
+        canvas_pda: String,
+    },
+}
+
+/// Checks the `Authorization` header against the auth header configured on the Helius webhook.
+pub fn is_authorized(config: &WebhookConfig, authorization: Option<&str>) -> bool {
+    let (Some(expected), Some(authorization)) =
+        (config.helius_auth_header.as_deref(), authorization)
+    else {
+        return false;
+    };
+
+    // Digests are compared so the check does not leak how much of the header matched.
+    Sha256::digest(expected.as_bytes()) == Sha256::digest(authorization.as_bytes())
+}
+
+/// Records the confirmation under its signature so a webhook can complete it if the
+/// client goes away. A failed write is only logged, as the client may still finish it.
+pub async fn track_pending_confirmation(
+    state: &AppState,
+    signature: &str,
+    pending: &PendingConfirmation,
+) {
+    let key = CacheKey::pending_confirmation(signature);
+    if let Err(e) = state
+        .cache
+        .redis
+        .set(&key, pending, PENDING_CONFIRMATION_TTL)
+        .await
+    {
+        tracing::warn!(signature, error = ?e, "Failed to track pending confirmation");
+    }
+}
+
+pub async fn clear_pending_confirmation(state: &AppState, signature: &str) {
+    let key = CacheKey::pending_confirmation(signature);
+    if let Err(e) = state.cache.redis.delete(&key).await {
+        tracing::warn!(signature, error = ?e, "Failed to clear pending confirmation");
+    }
+}
+
+/// Completes the bids, publishes and mints waiting on these transactions.
+pub async fn ingest_transactions(state: &AppState, transactions: Vec<HeliusTransaction>) {
+    for transaction in transactions {
+        if let Err(e) = complete_pending(state, &transaction).await {
+            tracing::warn!(
+                signature = %transaction.signature,
+                error = ?e,
+                "Failed to complete confirmation from webhook"
+            );
+        }
+    }
+}
+
+async fn complete_pending(state: &AppState, transaction: &HeliusTransaction) -> Result<()> {
+    let signature = transaction.signature.as_str();
+
+    // Mints already record their signature, and the mint watcher settles them the same way.
+    if let Some(canvas) =
+        CanvasRepository::find_pending_mint_by_signature(state.db.get_connection(), signature)
+            .await?
+    {
+        return mint_watch::settle_pending_mint(state, canvas).await;
+    }
+
+    let key = CacheKey::pending_confirmation(signature);
+    let Some(pending) = state.cache.redis.get::<PendingConfirmation>(&key).await? else {
+        return Ok(());
+    };
+
+    // A failed transaction can never confirm, so the client's own call fails too.
+    if transaction.transaction_error.is_some() {
+        clear_pending_confirmation(state, signature).await;
+        return Ok(());
+    }
+
+    let result = match pending {
+        PendingConfirmation::Bid(request) => {
+            pixel::confirm_pixel_bid(state, request).await.map(|_| ())
+        }
+        PendingConfirmation::Publish {
+            canvas_id,
+            user_id,
+            canvas_pda,
+        } => canvas::confirm_canvas_publish(state, canvas_id, user_id, signature, &canvas_pda)
+            .await
+            .map(|_| ()),
+    };
+
+    clear_pending_confirmation(state, signature).await;
+    result
+}
@@ -0,0 +1,195 @@
+pub mod helius;
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{config::WebhookConfig, error::Result};
+
+const SIGNATURE_HEADER: &str = "X-Pixel-Archives-Signature";
+const TIMESTAMP_HEADER: &str = "X-Pixel-Archives-Timestamp";
+const EVENT_HEADER: &str = "X-Pixel-Archives-Event";
+const DELIVERY_HEADER: &str = "X-Pixel-Archives-Delivery";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Lifecycle events delivered to webhook endpoints.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data")]
+pub enum WebhookEvent {
+    #[serde(rename = "canvas.published")]
+    CanvasPublished { canvas_id: Uuid, canvas_pda: String },
+
+    #[serde(rename = "nft.minting")]
+    NftMinting { canvas_id: Uuid },
+
+    #[serde(rename = "nft.minted")]
+    NftMinted {
+        canvas_id: Uuid,
+        mint_address: String,
+    },
+
+    #[serde(rename = "canvas.diverged")]
+    CanvasDiverged {
+        canvas_id: Uuid,
+        divergences: Vec<String>,
+    },
+}
+
+impl WebhookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::CanvasPublished { .. } => "canvas.published",
+            WebhookEvent::NftMinting { .. } => "nft.minting",
+            WebhookEvent::NftMinted { .. } => "nft.minted",
+            WebhookEvent::CanvasDiverged { .. } => "canvas.diverged",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookDelivery<'a> {
+    id: Uuid,
+    created_at: DateTime<Utc>,
+
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+}
+
+/// An event bound for one endpoint, as the outbox keeps it until it is delivered. The body
+/// and delivery id stay the same across attempts, so receivers can drop repeats.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    pub url: String,
+    pub event: String,
+    pub delivery_id: Uuid,
+    pub body: serde_json::Value,
+}
+
+/// How one attempt at a delivery went.
+#[derive(Debug)]
+pub enum DeliveryOutcome {
+    Delivered,
+    /// Worth trying again: the endpoint was unreachable, failed or asked to slow down.
+    Retry(String),
+    /// The endpoint refused the delivery, so trying again would not help.
+    Rejected(String),
+}
+
+/// Signs and posts events to every configured URL. Deliveries go through the outbox,
+/// which retries failed ones with exponential backoff.
+pub struct WebhookDispatcher {
+    http: Client,
+    urls: Vec<String>,
+    secret: String,
+    max_attempts: u32,
+}
+
+impl WebhookDispatcher {
+    pub fn new(config: &WebhookConfig) -> Self {
+        Self {
+            http: Client::builder()
+                .timeout(Duration::from_secs(config.timeout_secs))
+                .build()
+                .unwrap_or_default(),
+            urls: config.urls.clone(),
+            secret: config.secret.clone().unwrap_or_default(),
+            max_attempts: config.max_attempts.max(1),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.urls.is_empty()
+    }
+
+    /// Attempts after which a delivery is abandoned.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// One delivery of `event` per configured URL. None when webhooks are disabled.
+    pub fn deliveries(&self, event: &WebhookEvent) -> Result<Vec<PendingDelivery>> {
+        if !self.is_enabled() {
+            return Ok(Vec::new());
+        }
+
+        let delivery_id = Uuid::new_v4();
+        let body = serde_json::to_value(WebhookDelivery {
+            id: delivery_id,
+            created_at: Utc::now(),
+            event,
+        })?;
+
+        Ok(self
+            .urls
+            .iter()
+            .map(|url| PendingDelivery {
+                url: url.clone(),
+                event: event.name().to_string(),
+                delivery_id,
+                body: body.clone(),
+            })
+            .collect())
+    }
+
+    /// Posts the delivery once.
+    pub async fn send(&self, delivery: &PendingDelivery) -> DeliveryOutcome {
+        let body = match serde_json::to_vec(&delivery.body) {
+            Ok(body) => body,
+            Err(e) => return DeliveryOutcome::Rejected(e.to_string()),
+        };
+
+        // Signed per attempt so receivers can reject stale timestamps.
+        let timestamp = Utc::now().timestamp().to_string();
+        let signature = sign(&self.secret, &timestamp, &body);
+
+        let result = self
+            .http
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event)
+            .header(DELIVERY_HEADER, delivery.delivery_id.to_string())
+            .header(TIMESTAMP_HEADER, &timestamp)
+            .header(SIGNATURE_HEADER, format!("sha256={signature}"))
+            .body(body)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => DeliveryOutcome::Delivered,
+            Ok(response) => {
+                let status = response.status();
+                let error = format!("Endpoint responded {status}");
+                if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                    DeliveryOutcome::Retry(error)
+                } else {
+                    DeliveryOutcome::Rejected(error)
+                }
+            }
+            Err(e) => DeliveryOutcome::Retry(e.to_string()),
+        }
+    }
+}
+
+/// Wait before the next attempt at a delivery that has failed `attempts` times.
+pub fn retry_delay(attempts: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// Hex HMAC-SHA256 of `{timestamp}.{body}`.
+fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
@@ -1,2 +1,3 @@
+pub mod scheduler;
 pub mod security;
 pub mod server;
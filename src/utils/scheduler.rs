@@ -0,0 +1,26 @@
+use std::{future::Future, time::Duration};
+
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+use crate::error::Result;
+
+/// Runs `task` every `period` until the runtime shuts down. Failures are logged and the
+/// next tick runs as usual; a slow run delays the next tick instead of stacking up.
+pub fn spawn_interval_task<F, Fut>(name: &'static str, period: Duration, task: F) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = task().await {
+                tracing::warn!(task = name, error = ?e, "Background task failed");
+            }
+        }
+    })
+}
@@ -0,0 +1,80 @@
+use axum::extract::ws::{CloseFrame, Message};
+use serde::{Deserialize, Serialize};
+
+/// Why the server closed a WebSocket. Each has its own code in the application range, and
+/// the close reason is JSON telling clients whether reconnecting can help.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    /// Both the collaborator and spectator places are taken.
+    RoomFull,
+    /// The access token lapsed without a `Refresh`. Reconnecting with a new one works.
+    TokenExpired,
+    NotCollaborator,
+    CanvasDeleted,
+    /// The canvas was burned, so its room has nothing left to carry.
+    CanvasArchived,
+    TooManyMessages,
+    /// Fell behind the room's updates too often, or stopped reading them.
+    SlowConsumer,
+    /// No answer to a ping in time.
+    Unresponsive,
+}
+
+/// The close reason sent as JSON. Close reasons are limited to 123 bytes, so messages stay
+/// short.
+#[derive(Serialize)]
+struct CloseBody {
+    reason: CloseReason,
+    retryable: bool,
+    message: &'static str,
+}
+
+impl CloseReason {
+    pub fn code(self) -> u16 {
+        match self {
+            Self::RoomFull => 4000,
+            Self::TokenExpired => 4001,
+            Self::NotCollaborator => 4003,
+            Self::CanvasDeleted => 4004,
+            Self::CanvasArchived => 4005,
+            Self::TooManyMessages => 4008,
+            Self::SlowConsumer => 4009,
+            Self::Unresponsive => 4010,
+        }
+    }
+
+    /// Whether the same client may reconnect, possibly after a delay or a new token.
+    pub fn retryable(self) -> bool {
+        !matches!(
+            self,
+            Self::NotCollaborator | Self::CanvasDeleted | Self::CanvasArchived
+        )
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Self::RoomFull => "Room full",
+            Self::TokenExpired => "Access token expired",
+            Self::NotCollaborator => "No longer a collaborator on this canvas",
+            Self::CanvasDeleted => "Canvas was deleted",
+            Self::CanvasArchived => "Canvas was burned",
+            Self::TooManyMessages => "Too many messages",
+            Self::SlowConsumer => "Could not keep up with updates",
+            Self::Unresponsive => "No answer to ping",
+        }
+    }
+
+    pub fn frame(self) -> Message {
+        let body = CloseBody {
+            reason: self,
+            retryable: self.retryable(),
+            message: self.message(),
+        };
+        let reason = serde_json::to_string(&body).unwrap_or_else(|_| self.message().to_string());
+        Message::Close(Some(CloseFrame {
+            code: self.code(),
+            reason: reason.into(),
+        }))
+    }
+}
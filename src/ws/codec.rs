@@ -0,0 +1,172 @@
+use axum::extract::ws::{Message, WebSocket};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::ws::types::DEFAULT_PROTOCOL_VERSION;
+
+/// Subprotocol a client requests to receive MessagePack binary frames instead of JSON text.
+pub const MSGPACK_PROTOCOL: &str = "msgpack";
+
+/// How server messages are framed on a connection. Client messages are always JSON text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WsEncoding {
+    Json,
+    MessagePack,
+}
+
+impl WsEncoding {
+    /// The encoding picked during the upgrade handshake.
+    pub fn negotiated(socket: &WebSocket) -> Self {
+        match socket
+            .protocol()
+            .and_then(|protocol| protocol.to_str().ok())
+        {
+            Some(MSGPACK_PROTOCOL) => Self::MessagePack,
+            _ => Self::Json,
+        }
+    }
+
+    fn frame(self, value: &Value) -> serde_json::Result<Message> {
+        match self {
+            Self::Json => Ok(Message::Text(serde_json::to_string(value)?.into())),
+            Self::MessagePack => {
+                // Same shape as the JSON messages, so clients decode them into the same types.
+                let mut buffer = Vec::new();
+                write_value(&mut buffer, value);
+                Ok(Message::Binary(buffer.into()))
+            }
+        }
+    }
+}
+
+/// Shapes and frames server messages for one connection.
+#[derive(Debug, Clone, Copy)]
+pub struct WsCodec {
+    version: u8,
+    encoding: WsEncoding,
+}
+
+impl WsCodec {
+    pub fn negotiated(socket: &WebSocket, version: u8) -> Self {
+        Self {
+            version,
+            encoding: WsEncoding::negotiated(socket),
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn encode<T: Serialize>(self, value: &T) -> serde_json::Result<Message> {
+        let mut value = serde_json::to_value(value)?;
+        if self.version > DEFAULT_PROTOCOL_VERSION {
+            into_envelope(&mut value);
+        }
+        self.encoding.frame(&value)
+    }
+}
+
+/// Moves a version 1 message's `data` to `payload`, which is `null` for messages without one.
+fn into_envelope(value: &mut Value) {
+    if let Value::Object(fields) = value {
+        let payload = fields.remove("data").unwrap_or(Value::Null);
+        fields.insert("payload".to_string(), payload);
+    }
+}
+
+fn write_value(buffer: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => buffer.push(0xc0),
+        Value::Bool(false) => buffer.push(0xc2),
+        Value::Bool(true) => buffer.push(0xc3),
+        Value::Number(number) => write_number(buffer, number),
+        Value::String(string) => write_string(buffer, string),
+        Value::Array(items) => {
+            write_length(buffer, items.len(), 0x90, 0xdc, 0xdd);
+            for item in items {
+                write_value(buffer, item);
+            }
+        }
+        Value::Object(fields) => write_map(buffer, fields),
+    }
+}
+
+fn write_number(buffer: &mut Vec<u8>, number: &Number) {
+    if let Some(n) = number.as_u64() {
+        match n {
+            0..=0x7f => buffer.push(n as u8),
+            0x80..=0xff => buffer.extend_from_slice(&[0xcc, n as u8]),
+            0x100..=0xffff => {
+                buffer.push(0xcd);
+                buffer.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                buffer.push(0xce);
+                buffer.extend_from_slice(&(n as u32).to_be_bytes());
+            }
+            _ => {
+                buffer.push(0xcf);
+                buffer.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+    } else if let Some(n) = number.as_i64() {
+        // Only negative values get here.
+        match n {
+            -32..=-1 => buffer.push(n as u8),
+            -0x80..=-33 => buffer.extend_from_slice(&[0xd0, n as u8]),
+            -0x8000..=-0x81 => {
+                buffer.push(0xd1);
+                buffer.extend_from_slice(&(n as i16).to_be_bytes());
+            }
+            -0x8000_0000..=-0x8001 => {
+                buffer.push(0xd2);
+                buffer.extend_from_slice(&(n as i32).to_be_bytes());
+            }
+            _ => {
+                buffer.push(0xd3);
+                buffer.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+    } else {
+        buffer.push(0xcb);
+        buffer.extend_from_slice(&number.as_f64().unwrap_or_default().to_be_bytes());
+    }
+}
+
+fn write_string(buffer: &mut Vec<u8>, string: &str) {
+    let len = string.len();
+    if len < 32 {
+        buffer.push(0xa0 | len as u8);
+    } else if len <= 0xff {
+        buffer.extend_from_slice(&[0xd9, len as u8]);
+    } else if len <= 0xffff {
+        buffer.push(0xda);
+        buffer.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        buffer.push(0xdb);
+        buffer.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    buffer.extend_from_slice(string.as_bytes());
+}
+
+fn write_map(buffer: &mut Vec<u8>, fields: &Map<String, Value>) {
+    write_length(buffer, fields.len(), 0x80, 0xde, 0xdf);
+    for (key, value) in fields {
+        write_string(buffer, key);
+        write_value(buffer, value);
+    }
+}
+
+/// Array and map headers: a fix type for up to 15 entries, then 16 and 32-bit lengths.
+fn write_length(buffer: &mut Vec<u8>, len: usize, fix: u8, marker16: u8, marker32: u8) {
+    if len < 16 {
+        buffer.push(fix | len as u8);
+    } else if len <= 0xffff {
+        buffer.push(marker16);
+        buffer.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        buffer.push(marker32);
+        buffer.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
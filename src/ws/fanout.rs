@@ -0,0 +1,54 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    infrastructure::cache::keys::CacheKey,
+    ws::{RoomManager, types::RoomCanvasUpdate},
+};
+
+/// Wait before subscribing again after the subscription drops.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+/// A room update relayed to the other instances, which hold the rest of the room's
+/// connections.
+#[derive(Serialize, Deserialize)]
+pub(super) struct FanoutMessage {
+    /// Instance that published it, which has already delivered it to its own rooms.
+    pub origin: Uuid,
+    /// `None` for an announcement to every room.
+    pub canvas_id: Option<Uuid>,
+    /// Already numbered by the origin, so it is not buffered again.
+    pub seq: Option<u64>,
+    pub update: RoomCanvasUpdate,
+}
+
+/// Delivers updates published by other instances to the rooms open on this one, for as
+/// long as the runtime runs. Updates published while the subscription is down are missed;
+/// clients notice the gap in `seq` and resume from the replay buffer.
+pub(super) fn spawn_listener(rooms: Arc<RoomManager>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let channel = CacheKey::room_fanout_channel();
+
+        loop {
+            match rooms.redis().subscribe(&channel).await {
+                Ok(mut payloads) => {
+                    while let Some(payload) = payloads.next().await {
+                        match serde_json::from_str::<FanoutMessage>(&payload) {
+                            Ok(message) if message.origin == rooms.instance_id() => {}
+                            Ok(message) => rooms.deliver_relayed(message).await,
+                            Err(e) => tracing::warn!("Ignoring malformed room update: {e}"),
+                        }
+                    }
+                    tracing::warn!("Room fan-out subscription closed");
+                }
+                Err(e) => tracing::warn!("Failed to subscribe to room fan-out: {e}"),
+            }
+
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    })
+}
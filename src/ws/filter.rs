@@ -0,0 +1,123 @@
+use serde::Deserialize;
+
+use crate::ws::types::RoomCanvasUpdate;
+
+/// Kinds of room update a connection can choose to receive.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventCategory {
+    /// Placed, locked and unlocked pixels, and bids seen on-chain.
+    Pixels,
+    /// Publishing, minting and burning of the canvas.
+    Lifecycle,
+    /// Users joining, leaving and being muted, and the connection count.
+    Presence,
+    Cursors,
+    StrokePreviews,
+    Chat,
+}
+
+/// Rectangle of the canvas, from `(x, y)` inclusive.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct Region {
+    pub x: u8,
+    pub y: u8,
+    pub width: u8,
+    pub height: u8,
+}
+
+impl Region {
+    fn contains(&self, x: u8, y: u8) -> bool {
+        x >= self.x
+            && y >= self.y
+            && u16::from(x) < u16::from(self.x) + u16::from(self.width)
+            && u16::from(y) < u16::from(self.y) + u16::from(self.height)
+    }
+}
+
+/// Which of a room's updates a connection is sent. Updates outside every category, such as
+/// snapshots and resync notices, are always sent.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct EventFilter {
+    /// Categories to receive. All of them when absent.
+    pub events: Option<Vec<EventCategory>>,
+    /// Only positional updates inside this rectangle.
+    pub region: Option<Region>,
+}
+
+impl EventFilter {
+    /// Why the filter cannot be used on a canvas of the given size, if it cannot.
+    pub fn validate(&self, width: u8, height: u8) -> Result<(), String> {
+        if let Some(region) = self.region {
+            if region.width == 0 || region.height == 0 {
+                return Err("Region must not be empty".to_string());
+            }
+            if region.x >= width || region.y >= height {
+                return Err("Region starts outside the canvas".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn allows(&self, update: &RoomCanvasUpdate) -> bool {
+        let Some(category) = category(update) else {
+            return true;
+        };
+        if let Some(events) = &self.events
+            && !events.contains(&category)
+        {
+            return false;
+        }
+
+        match self.region {
+            Some(region) => in_region(update, region),
+            None => true,
+        }
+    }
+}
+
+fn category(update: &RoomCanvasUpdate) -> Option<EventCategory> {
+    use RoomCanvasUpdate::*;
+
+    match update {
+        Pixel(_) | PixelLocked { .. } | PixelUnlocked { .. } | BidConfirmed { .. } => {
+            Some(EventCategory::Pixels)
+        }
+        PublishingStarted
+        | Published { .. }
+        | PublishingFailed { .. }
+        | MintQueued { .. }
+        | MintSlotReady
+        | MintingStarted
+        | Minted { .. }
+        | MintingFailed { .. }
+        | Burned { .. }
+        | MintCountdown { .. }
+        | MintCountdownCancelled
+        | Finalized => Some(EventCategory::Lifecycle),
+        UserJoined { .. } | UserLeft { .. } | UserMuted { .. } | ConnectionCount { .. } => {
+            Some(EventCategory::Presence)
+        }
+        Cursor { .. } => Some(EventCategory::Cursors),
+        StrokePreview { .. } => Some(EventCategory::StrokePreviews),
+        Chat { .. } => Some(EventCategory::Chat),
+        _ => None,
+    }
+}
+
+/// Whether a positional update touches the region. Other updates always pass.
+fn in_region(update: &RoomCanvasUpdate, region: Region) -> bool {
+    use RoomCanvasUpdate::*;
+
+    match update {
+        Pixel(pixel) => region.contains(pixel.x, pixel.y),
+        PixelLocked { x, y, .. }
+        | PixelUnlocked { x, y }
+        | BidConfirmed { x, y, .. }
+        | Cursor { x, y, .. } => region.contains(*x, *y),
+        StrokePreview { pixels, .. } => {
+            pixels.iter().any(|pixel| region.contains(pixel.x, pixel.y))
+        }
+        _ => true,
+    }
+}
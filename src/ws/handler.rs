@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::{collections::VecDeque, net::SocketAddr, time::Duration};
 
 use axum::{
     extract::{
@@ -8,16 +8,37 @@ use axum::{
     response::Response,
 };
 use axum_extra::TypedHeader;
-use futures::{SinkExt, StreamExt};
+use chrono::{DateTime, Utc};
+use futures::{Sink, SinkExt, StreamExt};
 use headers::Cookie;
-use tokio::sync::broadcast::{Receiver, error};
+use tokio::{
+    sync::broadcast::{Receiver, error},
+    time::{Instant, interval_at, sleep_until, timeout},
+};
 use uuid::Uuid;
 
 use crate::{
     AppState,
+    api::types::UserResponse,
     error::AppError,
-    services::auth::TokenType,
-    ws::types::{ClientMessage, RoomCanvasUpdate, WsQuery},
+    infrastructure::{
+        cache::keys::CacheKey,
+        db::repositories::{CanvasRepository, UserRepository},
+    },
+    services::{
+        auth::TokenType,
+        canvas::{get_canvas_snapshot, muted_until, presence},
+    },
+    ws::{
+        close::CloseReason,
+        codec::{MSGPACK_PROTOCOL, WsCodec},
+        room::Room,
+        subscriptions::RoomSubscriptions,
+        types::{
+            ClientMessage, PreviewPixel, RoomCanvasUpdate, RoomEvent, SUPPORTED_PROTOCOL_VERSIONS,
+            WsQuery,
+        },
+    },
 };
 
 pub async fn ws_handler(
@@ -33,16 +54,42 @@ pub async fn ws_handler(
         .map(|s| s.to_string())
         .ok_or(AppError::Unauthorized)?;
 
-    let user_id = state
+    let claims = state
         .jwt_service
         .validate_token(&token, TokenType::Access)
-        .map_err(|_| AppError::Unauthorized)?
-        .sub;
+        .map_err(|_| AppError::Unauthorized)?;
+    let auth = ConnectionAuth {
+        user_id: claims.sub,
+        expires_at: claims.exp,
+    };
+    let user_id = auth.user_id;
+
+    if let Some(version) = query.protocol
+        && !SUPPORTED_PROTOCOL_VERSIONS.contains(&version)
+    {
+        return Err(AppError::InvalidParams(format!(
+            "Unsupported protocol version {version}"
+        )));
+    }
+
+    // Rooms carry drafts and bids, so only collaborators may listen in.
+    if !CanvasRepository::is_canvas_collaborator(
+        state.db.get_connection(),
+        query.canvas_id,
+        user_id,
+    )
+    .await?
+    {
+        return Err(AppError::NotCanvasCollaborator);
+    }
 
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, query, user_id)))
+    Ok(ws
+        .protocols([MSGPACK_PROTOCOL])
+        .on_upgrade(move |socket| handle_socket(socket, state, query, auth)))
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState, query: WsQuery, user_id: Uuid) {
+async fn handle_socket(socket: WebSocket, state: AppState, query: WsQuery, auth: ConnectionAuth) {
+    let user_id = auth.user_id;
     let canvas_id = query.canvas_id;
     tracing::info!("WebSocket connection for canvas {canvas_id} from user {user_id}");
 
@@ -51,34 +98,799 @@ async fn handle_socket(socket: WebSocket, state: AppState, query: WsQuery, user_
     let receiver = match room.subscribe() {
         Some(value) => value,
         None => {
-            tracing::warn!("Room full for canvas {canvas_id}");
+            match room.spectate() {
+                Some(receiver) => {
+                    tracing::info!(
+                        "Room full for canvas {canvas_id}, user {user_id} is spectating"
+                    );
+                    handle_spectator(&state, &room, auth, socket, receiver, &query).await;
+                    room.stop_spectating();
+                    state.ws_rooms.remove_room_if_empty(&canvas_id).await;
+                }
+                None => {
+                    tracing::warn!("Room full for canvas {canvas_id}");
+                    let mut socket = socket;
+                    let _ = socket.send(CloseReason::RoomFull.frame()).await;
+                    state.ws_rooms.remove_room_if_empty(&canvas_id).await;
+                }
+            }
             return;
         }
     };
 
-    room.broadcast(RoomCanvasUpdate::UserJoined { user_id });
-    handle_connection(socket, receiver).await;
+    let user_receiver = state.ws_rooms.subscribe_user(user_id).await;
+
+    // Joins and leaves are broadcast per user, not per connection.
+    if room.join(user_id) {
+        state
+            .ws_rooms
+            .broadcast(&canvas_id, RoomCanvasUpdate::UserJoined { user_id })
+            .await;
+    }
+    presence::mark_present(&state, canvas_id, user_id).await;
+
+    handle_connection(&state, &room, auth, socket, receiver, user_receiver, &query).await;
 
     room.unsubscribe();
-    room.broadcast(RoomCanvasUpdate::UserLeft { user_id });
+    if room.leave(user_id) {
+        state
+            .ws_rooms
+            .broadcast(&canvas_id, RoomCanvasUpdate::UserLeft { user_id })
+            .await;
+        presence::mark_absent(&state, canvas_id, user_id).await;
+    }
     state.ws_rooms.remove_room_if_empty(&canvas_id).await;
+    state.ws_rooms.remove_user_channel_if_unused(&user_id).await;
 
     tracing::info!("WebSocket disconnected for canvas {canvas_id}");
 }
 
-async fn handle_connection(socket: WebSocket, mut ws_receiver: Receiver<RoomCanvasUpdate>) {
+/// Tells clients that asked for a protocol version which one they got. Older clients never
+/// ask. `false` if the socket is gone.
+async fn send_hello(socket: &mut WebSocket, codec: WsCodec, query: &WsQuery) -> bool {
+    if query.protocol.is_none() {
+        return true;
+    }
+
+    let hello = RoomCanvasUpdate::Hello {
+        protocol_version: codec.version(),
+        supported_versions: SUPPORTED_PROTOCOL_VERSIONS.to_vec(),
+    };
+    match codec.encode(&hello) {
+        Ok(message) => socket.send(message).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+/// Sends the current canvas, presence and latest `seq`. Returns that `seq`, so live updates
+/// already reflected in the snapshot can be skipped.
+async fn send_snapshot<S>(
+    state: &AppState,
+    canvas_id: Uuid,
+    socket: &mut S,
+    codec: WsCodec,
+) -> Option<u64>
+where
+    S: Sink<Message> + Unpin,
+{
+    let snapshot = match get_canvas_snapshot(state, canvas_id).await {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            tracing::warn!("Failed to load snapshot of canvas {canvas_id}: {e}");
+            return None;
+        }
+    };
+
+    let seq = snapshot.seq;
+    let update = RoomEvent::unsequenced(canvas_id, RoomCanvasUpdate::Snapshot(Box::new(snapshot)));
+    match codec.encode(&update) {
+        Ok(message) => {
+            let _ = socket.send(message).await;
+            seq
+        }
+        Err(e) => {
+            tracing::error!("Failed to serialize snapshot of canvas {canvas_id}: {e}");
+            None
+        }
+    }
+}
+
+/// Starts a connection that fell behind over from a fresh snapshot, since the updates it
+/// skipped are gone. Returns the snapshot's `seq`.
+async fn resync<S>(state: &AppState, canvas_id: Uuid, sender: &mut S, codec: WsCodec) -> Option<u64>
+where
+    S: Sink<Message> + Unpin,
+{
+    let resync = RoomEvent::unsequenced(canvas_id, RoomCanvasUpdate::ResyncRequired);
+    if let Ok(message) = codec.encode(&resync) {
+        let _ = sender.send(message).await;
+    }
+    send_snapshot(state, canvas_id, sender, codec).await
+}
+
+/// Sends a room or user update, giving up after `WS_SEND_TIMEOUT_MS` so a client that
+/// stopped reading cannot stall its connection. `false` if the connection should close.
+async fn deliver<S>(state: &AppState, sender: &mut S, message: Message) -> bool
+where
+    S: Sink<Message> + Unpin,
+{
+    let limit = Duration::from_millis(state.config.server.ws_send_timeout_ms);
+    match timeout(limit, sender.send(message)).await {
+        Ok(sent) => sent.is_ok(),
+        Err(_) => {
+            tracing::info!("Evicting WebSocket whose send stalled for {limit:?}");
+            state.ws_rooms.record_slow_eviction();
+            false
+        }
+    }
+}
+
+/// Sends the updates numbered after `resume_from`, or `ResyncRequired` when they are no
+/// longer all buffered. Returns the `seq` the client is caught up to.
+async fn replay_missed(
+    state: &AppState,
+    canvas_id: Uuid,
+    resume_from: u64,
+    socket: &mut WebSocket,
+    codec: WsCodec,
+) -> Option<u64> {
+    let missed = match state.ws_rooms.replay_since(&canvas_id, resume_from).await {
+        Ok(missed) => missed,
+        Err(e) => {
+            tracing::warn!("Failed to load missed updates for canvas {canvas_id}: {e}");
+            None
+        }
+    };
+
+    let Some(missed) = missed else {
+        let resync = RoomEvent::unsequenced(canvas_id, RoomCanvasUpdate::ResyncRequired);
+        if let Ok(message) = codec.encode(&resync) {
+            let _ = socket.send(message).await;
+        }
+        return None;
+    };
+
+    let mut replayed_through = resume_from;
+    for event in missed {
+        let Ok(message) = codec.encode(&event) else {
+            continue;
+        };
+        if socket.send(message).await.is_err() {
+            break;
+        }
+        replayed_through = event.seq.unwrap_or(replayed_through);
+    }
+
+    Some(replayed_through)
+}
+
+/// `false` only when the user is known to have left the canvas. Lookup failures keep the
+/// connection open until the next check.
+pub(super) async fn still_collaborator(state: &AppState, canvas_id: Uuid, user_id: Uuid) -> bool {
+    match CanvasRepository::is_canvas_collaborator(state.db.get_connection(), canvas_id, user_id)
+        .await
+    {
+        Ok(is_collaborator) => is_collaborator,
+        Err(e) => {
+            tracing::warn!("Failed to recheck collaborator {user_id} on canvas {canvas_id}: {e}");
+            true
+        }
+    }
+}
+
+/// When the user's mute on the canvas ends. Lookup failures leave them unmuted until the next
+/// check.
+async fn load_mute(state: &AppState, canvas_id: Uuid, user_id: Uuid) -> Option<DateTime<Utc>> {
+    match muted_until(state, canvas_id, user_id).await {
+        Ok(until) => until,
+        Err(e) => {
+            tracing::warn!("Failed to read mute of user {user_id} on canvas {canvas_id}: {e}");
+            None
+        }
+    }
+}
+
+fn is_muted(until: Option<DateTime<Utc>>) -> bool {
+    until.is_some_and(|until| until > Utc::now())
+}
+
+/// Display name for chat, from the session cached at login, falling back to the database.
+async fn session_username(state: &AppState, user_id: Uuid) -> Option<String> {
+    let session_key = CacheKey::user_session(&user_id);
+    match state.cache.redis.get::<UserResponse>(&session_key).await {
+        Ok(Some(session)) => return session.username,
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to read session of user {user_id}: {e}"),
+    }
+
+    match UserRepository::find_user_by_id(state.db.get_connection(), user_id).await {
+        Ok(user) => user.and_then(|user| user.username),
+        Err(e) => {
+            tracing::warn!("Failed to load user {user_id}: {e}");
+            None
+        }
+    }
+}
+
+/// Trimmed chat text, or why it was dropped.
+fn validate_chat(text: &str, max_length: usize) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Message is empty".to_string());
+    }
+    if text.chars().count() > max_length {
+        return Err(format!("Message is longer than {max_length} characters"));
+    }
+    if text.chars().any(char::is_control) {
+        return Err("Message contains control characters".to_string());
+    }
+
+    Ok(text.to_string())
+}
+
+/// Whether a stroke preview is worth relaying: non-empty, no larger than `max_pixels`, and
+/// entirely on the canvas.
+fn valid_stroke_preview(pixels: &[PreviewPixel], max_pixels: usize, width: u8, height: u8) -> bool {
+    !pixels.is_empty()
+        && pixels.len() <= max_pixels
+        && pixels
+            .iter()
+            .all(|pixel| pixel.x < width && pixel.y < height)
+}
+
+/// The user behind a connection, and when the access token it was authenticated with
+/// expires.
+struct ConnectionAuth {
+    user_id: Uuid,
+    expires_at: u64,
+}
+
+impl ConnectionAuth {
+    /// When the connection is closed unless refreshed before then.
+    fn deadline(&self, grace: Duration) -> Instant {
+        let remaining = self
+            .expires_at
+            .saturating_sub(Utc::now().timestamp() as u64);
+        Instant::now() + Duration::from_secs(remaining) + grace
+    }
+
+    /// Moves the expiry to that of `token`, which must be a live access token of the same
+    /// user. Returns the new expiry, or why the token was refused.
+    async fn refresh(&mut self, state: &AppState, token: &str) -> Result<DateTime<Utc>, String> {
+        let claims = state
+            .jwt_service
+            .validate_token(token, TokenType::Access)
+            .map_err(|e| e.to_string())?;
+        if claims.sub != self.user_id {
+            return Err("Token belongs to another user".to_string());
+        }
+
+        match state
+            .cache
+            .redis
+            .get::<bool>(&CacheKey::token_blacklist(&claims.jti))
+            .await
+        {
+            Ok(Some(true)) => return Err("Token has been revoked".to_string()),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to check token of user {}: {e}", self.user_id),
+        }
+
+        self.expires_at = self.expires_at.max(claims.exp);
+        DateTime::from_timestamp(self.expires_at as i64, 0)
+            .ok_or_else(|| "Token has an invalid expiry".to_string())
+    }
+}
+
+async fn refresh_reply(
+    state: &AppState,
+    auth: &mut ConnectionAuth,
+    token: &str,
+) -> RoomCanvasUpdate {
+    match auth.refresh(state, token).await {
+        Ok(expires_at) => RoomCanvasUpdate::AuthRefreshed { expires_at },
+        Err(reason) => RoomCanvasUpdate::AuthRejected { reason },
+    }
+}
+
+/// Relays a connection's cursor at most once per interval. Positions arriving in between
+/// replace each other, and the latest is sent when the interval is up.
+struct CursorThrottle {
+    interval: Duration,
+    last_sent: Option<Instant>,
+    pending: Option<(u8, u8)>,
+}
+
+impl CursorThrottle {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: None,
+            pending: None,
+        }
+    }
+
+    /// The position to relay now, if the interval since the last one has passed.
+    fn offer(&mut self, position: (u8, u8)) -> Option<(u8, u8)> {
+        match self.last_sent {
+            Some(last_sent) if last_sent.elapsed() < self.interval => {
+                self.pending = Some(position);
+                None
+            }
+            _ => {
+                self.last_sent = Some(Instant::now());
+                self.pending = None;
+                Some(position)
+            }
+        }
+    }
+
+    fn next_flush(&self) -> Option<Instant> {
+        self.pending?;
+        Some(self.last_sent? + self.interval)
+    }
+
+    fn flush(&mut self) -> Option<(u8, u8)> {
+        let position = self.pending.take()?;
+        self.last_sent = Some(Instant::now());
+        Some(position)
+    }
+}
+
+enum Admission {
+    Allowed,
+    /// The first message dropped since the bucket ran dry.
+    Warned,
+    Dropped,
+}
+
+/// Token bucket over the messages a connection sends. Running dry costs a warning, and
+/// refilling completely forgives the earlier ones.
+struct MessageLimiter {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last_refill: Instant,
+    throttled: bool,
+    warnings: u32,
+}
+
+impl MessageLimiter {
+    fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: f64::from(burst),
+            tokens: f64::from(burst),
+            last_refill: Instant::now(),
+            throttled: false,
+            warnings: 0,
+        }
+    }
+
+    fn admit(&mut self) -> Admission {
+        let now = Instant::now();
+        let refill = now.duration_since(self.last_refill).as_secs_f64() * self.rate;
+        self.tokens = (self.tokens + refill).min(self.burst);
+        self.last_refill = now;
+        if self.tokens >= self.burst {
+            self.warnings = 0;
+        }
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            self.throttled = false;
+            Admission::Allowed
+        } else if self.throttled {
+            Admission::Dropped
+        } else {
+            self.throttled = true;
+            self.warnings += 1;
+            Admission::Warned
+        }
+    }
+}
+
+/// Times a connection fell behind its room within a sliding window. Each lag is answered
+/// with a resync, until there have been too many and the connection is evicted instead.
+struct LagTracker {
+    window: Duration,
+    max_lags: usize,
+    lags: VecDeque<Instant>,
+}
+
+impl LagTracker {
+    fn new(window: Duration, max_lags: usize) -> Self {
+        Self {
+            window,
+            max_lags,
+            lags: VecDeque::new(),
+        }
+    }
+
+    /// Records a lag. `true` once the connection has lagged too often to keep.
+    fn record(&mut self) -> bool {
+        let now = Instant::now();
+        while self
+            .lags
+            .front()
+            .is_some_and(|lag| now.duration_since(*lag) > self.window)
+        {
+            self.lags.pop_front();
+        }
+        self.lags.push_back(now);
+        self.lags.len() > self.max_lags
+    }
+}
+
+/// Receive-only connection to a full room. Room updates are sent, and client messages other
+/// than pings are ignored, so spectators take no part in presence, cursors or chat.
+async fn handle_spectator(
+    state: &AppState,
+    room: &Room,
+    mut auth: ConnectionAuth,
+    mut socket: WebSocket,
+    mut ws_receiver: Receiver<RoomEvent>,
+    query: &WsQuery,
+) {
+    let user_id = auth.user_id;
+    let canvas_id = room.canvas_id();
+    let codec = WsCodec::negotiated(&socket, query.protocol_version());
+
+    if !send_hello(&mut socket, codec, query).await {
+        return;
+    }
+    let spectating = RoomCanvasUpdate::Spectating {
+        max_collaborators: state.config.canvas.max_collaborators,
+    };
+    if let Ok(message) = codec.encode(&spectating)
+        && socket.send(message).await.is_err()
+    {
+        return;
+    }
+    let mut snapshot_seq = send_snapshot(state, canvas_id, &mut socket, codec).await;
+
+    let (mut sender, mut receiver) = socket.split();
+
+    let recheck_period = Duration::from_secs(state.config.server.ws_auth_recheck_secs);
+    let mut recheck = interval_at(Instant::now() + recheck_period, recheck_period);
+
+    let ping_period = Duration::from_secs(state.config.server.ws_ping_interval_secs);
+    let pong_timeout = Duration::from_secs(state.config.server.ws_pong_timeout_secs);
+    let mut ping = interval_at(Instant::now() + ping_period, ping_period);
+    let mut pong_deadline: Option<Instant> = None;
+
+    let auth_grace = Duration::from_secs(state.config.server.ws_auth_grace_secs);
+
+    let mut lags = LagTracker::new(
+        Duration::from_secs(state.config.server.ws_lag_window_secs),
+        state.config.server.ws_max_lags,
+    );
+
+    loop {
+        tokio::select! {
+            _ = sleep_until(auth.deadline(auth_grace)) => {
+                tracing::info!("Closing spectator {user_id} on canvas {canvas_id} with an expired token");
+                let _ = sender.send(CloseReason::TokenExpired.frame()).await;
+                break;
+            }
+
+            _ = recheck.tick() => {
+                if !still_collaborator(state, canvas_id, user_id).await {
+                    let _ = sender.send(CloseReason::NotCollaborator.frame()).await;
+                    break;
+                }
+            }
+
+            _ = ping.tick() => {
+                if sender.send(Message::Ping(Default::default())).await.is_err() {
+                    break;
+                }
+                pong_deadline.get_or_insert(Instant::now() + pong_timeout);
+            }
+
+            _ = sleep_until(pong_deadline.unwrap_or_else(Instant::now)), if pong_deadline.is_some() => {
+                tracing::info!("Closing unresponsive spectator {user_id} on canvas {canvas_id}");
+                let _ = sender.send(CloseReason::Unresponsive.frame()).await;
+                break;
+            }
+
+            msg = receiver.next() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        pong_deadline = None;
+                        let reply = match ClientMessage::decode(&text) {
+                            Ok(ClientMessage::Ping) => Some(Message::Text("pong".into())),
+                            Ok(ClientMessage::Refresh { token }) => {
+                                codec.encode(&refresh_reply(state, &mut auth, &token).await).ok()
+                            }
+                            _ => None,
+                        };
+                        if let Some(reply) = reply
+                            && sender.send(reply).await.is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => pong_deadline = None,
+                }
+            }
+
+            update = ws_receiver.recv() => {
+                match update {
+                    Ok(RoomEvent { update: RoomCanvasUpdate::RoomClosed { reason }, .. }) => {
+                        tracing::info!("Closing spectator {user_id} on canvas {canvas_id}: {reason:?}");
+                        let _ = sender.send(reason.frame()).await;
+                        break;
+                    }
+                    Ok(RoomEvent { seq: Some(seq), .. })
+                        if snapshot_seq.is_some_and(|snapshot| seq <= snapshot) => {}
+                    Ok(event) => {
+                        if let Ok(message) = codec.encode(&event)
+                            && !deliver(state, &mut sender, message).await
+                        {
+                            break;
+                        }
+                    }
+                    Err(error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Spectator lagged {n} messages");
+                        room.record_lag(n);
+                        if lags.record() {
+                            tracing::info!("Evicting slow spectator {user_id} on canvas {canvas_id}");
+                            state.ws_rooms.record_slow_eviction();
+                            let _ = sender.send(CloseReason::SlowConsumer.frame()).await;
+                            break;
+                        }
+                        snapshot_seq = resync(state, canvas_id, &mut sender, codec).await;
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+    }
+}
+
+async fn handle_connection(
+    state: &AppState,
+    room: &Room,
+    mut auth: ConnectionAuth,
+    mut socket: WebSocket,
+    mut ws_receiver: Receiver<RoomEvent>,
+    mut user_receiver: Receiver<RoomCanvasUpdate>,
+    query: &WsQuery,
+) {
+    let user_id = auth.user_id;
+    let canvas_id = room.canvas_id();
+    let codec = WsCodec::negotiated(&socket, query.protocol_version());
+
+    if !send_hello(&mut socket, codec, query).await {
+        return;
+    }
+
+    // The room is already subscribed, so updates numbered while the buffer is read arrive
+    // live as well
+    let replayed_through = match query.resume_from {
+        Some(resume_from) => replay_missed(state, canvas_id, resume_from, &mut socket, codec).await,
+        None => None,
+    };
+    // Clients that did not resume, or could not, start from the current state instead
+    let mut replayed_through = match replayed_through {
+        Some(seq) => Some(seq),
+        None => send_snapshot(state, canvas_id, &mut socket, codec).await,
+    };
+
     let (mut sender, mut receiver) = socket.split();
 
+    let recheck_period = Duration::from_secs(state.config.server.ws_auth_recheck_secs);
+    let mut recheck = interval_at(Instant::now() + recheck_period, recheck_period);
+
+    let ping_period = Duration::from_secs(state.config.server.ws_ping_interval_secs);
+    let pong_timeout = Duration::from_secs(state.config.server.ws_pong_timeout_secs);
+    let mut ping = interval_at(Instant::now() + ping_period, ping_period);
+    // Set while a ping is unanswered. Any frame from the client counts as an answer.
+    let mut pong_deadline: Option<Instant> = None;
+
+    let mut limiter = MessageLimiter::new(
+        state.config.server.ws_message_rate,
+        state.config.server.ws_message_burst,
+    );
+    let max_warnings = state.config.server.ws_message_warnings;
+
+    let (mut subscriptions, mut subscribed_events) =
+        RoomSubscriptions::new(canvas_id, state.config.server.ws_max_subscriptions);
+
+    let (width, height) = (state.config.canvas.width, state.config.canvas.height);
+    let mut cursor = CursorThrottle::new(Duration::from_millis(
+        state.config.server.ws_cursor_interval_ms,
+    ));
+    let relay_cursor = |(x, y): (u8, u8)| {
+        room.broadcast(RoomEvent::unsequenced(
+            canvas_id,
+            RoomCanvasUpdate::Cursor { user_id, x, y },
+        ));
+    };
+
+    let username = session_username(state, user_id).await;
+    // Cursors, stroke previews and chat from a muted user are dropped
+    let mut muted = load_mute(state, canvas_id, user_id).await;
+    let chat_interval = Duration::from_millis(state.config.server.ws_chat_interval_ms);
+    let mut last_chat: Option<Instant> = None;
+
+    let auth_grace = Duration::from_secs(state.config.server.ws_auth_grace_secs);
+
+    let mut lags = LagTracker::new(
+        Duration::from_secs(state.config.server.ws_lag_window_secs),
+        state.config.server.ws_max_lags,
+    );
+
     loop {
         tokio::select! {
+            // Disconnect clients that let their token expire without sending a new one
+            _ = sleep_until(auth.deadline(auth_grace)) => {
+                tracing::info!("Closing WebSocket of user {user_id} on canvas {canvas_id} with an expired token");
+                let _ = sender.send(CloseReason::TokenExpired.frame()).await;
+                break;
+            }
+
+            // Disconnect users removed from the canvas since they joined, and keep the
+            // shared presence of the rest fresh
+            _ = recheck.tick() => {
+                if !still_collaborator(state, canvas_id, user_id).await {
+                    tracing::info!("Closing WebSocket of removed collaborator {user_id} on canvas {canvas_id}");
+                    let _ = sender.send(CloseReason::NotCollaborator.frame()).await;
+                    break;
+                }
+                presence::mark_present(state, canvas_id, user_id).await;
+                muted = load_mute(state, canvas_id, user_id).await;
+
+                for revoked in subscriptions.recheck(state, user_id).await {
+                    let unsubscribed = RoomCanvasUpdate::Unsubscribed { canvas_id: revoked };
+                    if let Ok(message) = codec.encode(&unsubscribed) {
+                        let _ = sender.send(message).await;
+                    }
+                }
+            }
+
+            // Ping the client, so half-open connections are noticed
+            _ = ping.tick() => {
+                if sender.send(Message::Ping(Default::default())).await.is_err() {
+                    break;
+                }
+                pong_deadline.get_or_insert(Instant::now() + pong_timeout);
+            }
+
+            _ = sleep_until(pong_deadline.unwrap_or_else(Instant::now)), if pong_deadline.is_some() => {
+                tracing::info!("Closing unresponsive WebSocket of user {user_id} on canvas {canvas_id}");
+                let _ = sender.send(CloseReason::Unresponsive.frame()).await;
+                break;
+            }
+
             // Handle incoming messages
             msg = receiver.next() => {
+                if matches!(msg, Some(Ok(_))) {
+                    pong_deadline = None;
+                }
+
+                if matches!(msg, Some(Ok(Message::Text(_) | Message::Binary(_) | Message::Ping(_)))) {
+                    match limiter.admit() {
+                        Admission::Allowed => {}
+                        Admission::Dropped => continue,
+                        Admission::Warned if limiter.warnings > max_warnings => {
+                            tracing::info!("Closing flooding WebSocket of user {user_id} on canvas {canvas_id}");
+                            let _ = sender.send(CloseReason::TooManyMessages.frame()).await;
+                            break;
+                        }
+                        Admission::Warned => {
+                            let warning = RoomCanvasUpdate::RateLimited {
+                                warnings_left: max_warnings - limiter.warnings,
+                            };
+                            if let Ok(message) = codec.encode(&warning)
+                                && sender.send(message).await.is_err() {
+                                    break;
+                                }
+                            continue;
+                        }
+                    }
+                }
+
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        if let Ok(ClientMessage::Ping) = serde_json::from_str::<ClientMessage>(&text)
-                            && sender.send(Message::Text("pong".into())).await.is_err() {
+                        match ClientMessage::decode(&text) {
+                            Ok(ClientMessage::Ping)
+                                if sender.send(Message::Text("pong".into())).await.is_err() =>
+                            {
                                 break;
                             }
+                            Ok(ClientMessage::Cursor { x, y })
+                                if x < width && y < height && !is_muted(muted) =>
+                            {
+                                if let Some(position) = cursor.offer((x, y)) {
+                                    relay_cursor(position);
+                                }
+                            }
+                            Ok(ClientMessage::StrokePreview { pixels })
+                                if !is_muted(muted)
+                                    && valid_stroke_preview(&pixels, state.config.server.ws_stroke_preview_max, width, height) =>
+                            {
+                                room.broadcast(RoomEvent::unsequenced(
+                                    canvas_id,
+                                    RoomCanvasUpdate::StrokePreview { user_id, pixels },
+                                ));
+                            }
+                            Ok(ClientMessage::Chat { text }) => {
+                                let checked = if is_muted(muted) {
+                                    Err("You are muted in this room".to_string())
+                                } else if last_chat.is_some_and(|last| last.elapsed() < chat_interval) {
+                                    Err("You are sending messages too quickly".to_string())
+                                } else {
+                                    validate_chat(&text, state.config.server.ws_chat_max_length)
+                                };
+
+                                match checked {
+                                    Ok(text) => {
+                                        last_chat = Some(Instant::now());
+                                        room.broadcast(RoomEvent::unsequenced(canvas_id, RoomCanvasUpdate::Chat {
+                                            user_id,
+                                            username: username.clone(),
+                                            text,
+                                            sent_at: Utc::now(),
+                                        }));
+                                    }
+                                    Err(reason) => {
+                                        let rejected = RoomCanvasUpdate::ChatRejected { reason };
+                                        if let Ok(message) = codec.encode(&rejected)
+                                            && sender.send(message).await.is_err() {
+                                                break;
+                                            }
+                                    }
+                                }
+                            }
+                            Ok(ClientMessage::Subscribe { canvas_id: other, filter }) => {
+                                let subscribed = match filter.validate(width, height) {
+                                    Ok(()) => subscriptions.subscribe(state, other, user_id, filter).await,
+                                    Err(reason) => Err(reason),
+                                };
+                                let reply = match subscribed {
+                                    Ok(()) => RoomCanvasUpdate::Subscribed { canvas_id: other },
+                                    Err(reason) => RoomCanvasUpdate::SubscribeRejected { canvas_id: other, reason },
+                                };
+                                if let Ok(message) = codec.encode(&reply)
+                                    && sender.send(message).await.is_err() {
+                                        break;
+                                    }
+                            }
+                            Ok(ClientMessage::Filter { canvas_id: target, filter }) => {
+                                let target = target.unwrap_or(canvas_id);
+                                let reply = match filter
+                                    .validate(width, height)
+                                    .and_then(|()| subscriptions.set_filter(target, filter))
+                                {
+                                    Ok(()) => RoomCanvasUpdate::FilterApplied { canvas_id: target },
+                                    Err(reason) => RoomCanvasUpdate::FilterRejected { canvas_id: target, reason },
+                                };
+                                if let Ok(message) = codec.encode(&reply)
+                                    && sender.send(message).await.is_err() {
+                                        break;
+                                    }
+                            }
+                            Ok(ClientMessage::Refresh { token }) => {
+                                let reply = refresh_reply(state, &mut auth, &token).await;
+                                if let Ok(message) = codec.encode(&reply)
+                                    && sender.send(message).await.is_err() {
+                                        break;
+                                    }
+                            }
+                            Ok(ClientMessage::Unsubscribe { canvas_id: other })
+                                if subscriptions.unsubscribe(state, other).await =>
+                            {
+                                let reply = RoomCanvasUpdate::Unsubscribed { canvas_id: other };
+                                if let Ok(message) = codec.encode(&reply)
+                                    && sender.send(message).await.is_err() {
+                                        break;
+                                    }
+                            }
+                            _ => {}
+                        }
                     }
                     Some(Ok(Message::Close(_))) | None => break,
                     Some(Err(e)) => {
@@ -89,13 +901,38 @@ async fn handle_connection(socket: WebSocket, mut ws_receiver: Receiver<RoomCanv
                 }
             }
 
+            // Send the cursor position held back by the throttle
+            _ = sleep_until(cursor.next_flush().unwrap_or_else(Instant::now)), if cursor.next_flush().is_some() => {
+                if let Some(position) = cursor.flush()
+                    && !is_muted(muted)
+                {
+                    relay_cursor(position);
+                }
+            }
+
             // Handle broadcasts
             update = ws_receiver.recv() => {
+                if let Ok(RoomEvent { update: RoomCanvasUpdate::UserMuted { user_id: target, until }, .. }) = &update
+                    && *target == user_id
+                {
+                    muted = *until;
+                }
+
                 match update {
-                    Ok(update) => {
-                        match serde_json::to_string(&update) {
-                            Ok(json) => {
-                                if sender.send(Message::Text(json.into())).await.is_err() {
+                    Ok(RoomEvent { update: RoomCanvasUpdate::RoomClosed { reason }, .. }) => {
+                        tracing::info!("Closing WebSocket of user {user_id} on canvas {canvas_id}: {reason:?}");
+                        let _ = sender.send(reason.frame()).await;
+                        break;
+                    }
+                    Ok(event) if event.update.relayed_from() == Some(user_id) => {}
+                    Ok(event) if !subscriptions.allows(canvas_id, &event.update) => {}
+                    // Already sent while replaying
+                    Ok(RoomEvent { seq: Some(seq), .. })
+                        if replayed_through.is_some_and(|replayed| seq <= replayed) => {}
+                    Ok(event) => {
+                        match codec.encode(&event) {
+                            Ok(message) => {
+                                if !deliver(state, &mut sender, message).await {
                                     break;
                                 }
                             }
@@ -104,12 +941,85 @@ async fn handle_connection(socket: WebSocket, mut ws_receiver: Receiver<RoomCanv
                             }
                         }
                     }
+                    // The skipped updates are gone, so start the client over rather than
+                    // let it carry on with holes in the canvas
                     Err(error::RecvError::Lagged(n)) => {
                         tracing::warn!("Lagged {n} messages");
+                        room.record_lag(n);
+                        if lags.record() {
+                            tracing::info!("Evicting slow WebSocket of user {user_id} on canvas {canvas_id}");
+                            state.ws_rooms.record_slow_eviction();
+                            let _ = sender.send(CloseReason::SlowConsumer.frame()).await;
+                            break;
+                        }
+                        replayed_through = resync(state, canvas_id, &mut sender, codec).await;
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            // Handle broadcasts from the other rooms subscribed to
+            Some(event) = subscribed_events.recv() => {
+                if matches!(event.update, RoomCanvasUpdate::RoomClosed { .. }) {
+                    subscriptions.unsubscribe(state, event.canvas_id).await;
+                    let unsubscribed = RoomCanvasUpdate::Unsubscribed { canvas_id: event.canvas_id };
+                    if let Ok(message) = codec.encode(&unsubscribed)
+                        && sender.send(message).await.is_err() {
+                            break;
+                        }
+                    continue;
+                }
+                if matches!(event.update, RoomCanvasUpdate::ResyncRequired) {
+                    if lags.record() {
+                        tracing::info!("Evicting slow WebSocket of user {user_id} on canvas {}", event.canvas_id);
+                        state.ws_rooms.record_slow_eviction();
+                        let _ = sender.send(CloseReason::SlowConsumer.frame()).await;
+                        break;
+                    }
+                    resync(state, event.canvas_id, &mut sender, codec).await;
+                    continue;
+                }
+                if event.update.relayed_from() == Some(user_id)
+                    || !subscriptions.allows(event.canvas_id, &event.update)
+                {
+                    continue;
+                }
+                match codec.encode(&event) {
+                    Ok(message) => {
+                        if !deliver(state, &mut sender, message).await {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to serialize update: {e}");
+                    }
+                }
+            }
+
+            // Handle updates addressed to this user
+            update = user_receiver.recv() => {
+                match update {
+                    Ok(update) => {
+                        match codec.encode(&update) {
+                            Ok(message) => {
+                                if !deliver(state, &mut sender, message).await {
+                                    break;
+                                }
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to serialize user update: {e}");
+                            }
+                        }
+                    }
+                    Err(error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Lagged {n} user messages");
+                        state.ws_rooms.record_user_lag(n);
                     }
                     Err(_) => break,
                 }
             }
         }
     }
+
+    subscriptions.close(state).await;
 }
@@ -1,6 +1,13 @@
+pub mod close;
+pub mod codec;
+mod fanout;
+pub mod filter;
 pub mod handler;
 pub mod room;
 pub mod router;
+pub mod subscriptions;
+pub mod sweep;
 pub mod types;
 
 pub use router::{RoomManager, router};
+pub use sweep::spawn_room_sweeper;
@@ -1,33 +1,73 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
 
-use tokio::sync::broadcast::{self, Receiver, Sender};
+use tokio::{
+    sync::{
+        Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard,
+        broadcast::{self, Receiver, Sender},
+    },
+    time::{Duration, Instant},
+};
 use uuid::Uuid;
 
-use crate::ws::types::RoomCanvasUpdate;
+use crate::ws::types::{RoomEvent, RoomStats};
 
 pub struct Room {
-    sender: Sender<RoomCanvasUpdate>,
+    canvas_id: Uuid,
+    opened_at: Instant,
+    sender: Sender<RoomEvent>,
+    /// Held while an update is numbered and sent, so connections get them in `seq` order.
+    sequencer: AsyncMutex<()>,
     connection_count: AtomicUsize,
     max_connections: usize,
+    /// Receive-only connections taken once the room is full. Not counted as connections.
+    spectator_count: AtomicUsize,
+    max_spectators: usize,
+    /// Open connections per user, so a second tab does not count as a second join.
+    presence: Mutex<HashMap<Uuid, usize>>,
+    /// Times a receiver fell behind the broadcast buffer, and the updates it skipped.
+    lag_events: AtomicU64,
+    lagged_updates: AtomicU64,
 }
 
 impl Room {
-    pub fn new(_canvas_id: Uuid, max_connections: usize) -> Self {
+    pub fn new(canvas_id: Uuid, max_connections: usize, max_spectators: usize) -> Self {
         const BROADCAST_BUFFER_SIZE: usize = 256;
 
         let (sender, _) = broadcast::channel(BROADCAST_BUFFER_SIZE);
         Self {
+            canvas_id,
+            opened_at: Instant::now(),
             sender,
+            sequencer: AsyncMutex::new(()),
             connection_count: AtomicUsize::new(0),
             max_connections,
+            spectator_count: AtomicUsize::new(0),
+            max_spectators,
+            presence: Mutex::new(HashMap::new()),
+            lag_events: AtomicU64::new(0),
+            lagged_updates: AtomicU64::new(0),
         }
     }
 
+    pub fn canvas_id(&self) -> Uuid {
+        self.canvas_id
+    }
+
+    pub fn age(&self) -> Duration {
+        self.opened_at.elapsed()
+    }
+
     pub async fn get_connection_count(&self) -> usize {
         self.connection_count.load(Ordering::SeqCst)
     }
 
-    pub fn subscribe(&self) -> Option<Receiver<RoomCanvasUpdate>> {
+    pub fn subscribe(&self) -> Option<Receiver<RoomEvent>> {
         loop {
             let count = self.connection_count.load(Ordering::SeqCst);
             if count >= self.max_connections {
@@ -51,7 +91,76 @@ impl Room {
         self.connection_count.fetch_sub(1, Ordering::SeqCst);
     }
 
-    pub fn broadcast(&self, update: RoomCanvasUpdate) {
-        let _ = self.sender.send(update);
+    /// Receives the room's updates without taking a connection place, up to the spectator
+    /// limit.
+    pub fn spectate(&self) -> Option<Receiver<RoomEvent>> {
+        self.spectator_count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < self.max_spectators).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| self.sender.subscribe())
+    }
+
+    pub fn stop_spectating(&self) {
+        self.spectator_count.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connection_count.load(Ordering::SeqCst) == 0
+            && self.spectator_count.load(Ordering::SeqCst) == 0
+    }
+
+    /// Records a connection of the user. `true` if it is their first one in this room.
+    pub fn join(&self, user_id: Uuid) -> bool {
+        let mut presence = self.presence.lock().unwrap_or_else(|e| e.into_inner());
+        let connections = presence.entry(user_id).or_insert(0);
+        *connections += 1;
+        *connections == 1
+    }
+
+    /// Records a closed connection of the user. `true` if it was their last one in this room.
+    pub fn leave(&self, user_id: Uuid) -> bool {
+        let mut presence = self.presence.lock().unwrap_or_else(|e| e.into_inner());
+        match presence.get_mut(&user_id) {
+            Some(connections) if *connections > 1 => {
+                *connections -= 1;
+                false
+            }
+            Some(_) => {
+                presence.remove(&user_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn present_users(&self) -> Vec<Uuid> {
+        let presence = self.presence.lock().unwrap_or_else(|e| e.into_inner());
+        presence.keys().copied().collect()
+    }
+
+    pub async fn lock_sequence(&self) -> AsyncMutexGuard<'_, ()> {
+        self.sequencer.lock().await
+    }
+
+    pub fn record_lag(&self, skipped: u64) {
+        self.lag_events.fetch_add(1, Ordering::Relaxed);
+        self.lagged_updates.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> RoomStats {
+        RoomStats {
+            canvas_id: self.canvas_id,
+            connections: self.connection_count.load(Ordering::SeqCst),
+            spectators: self.spectator_count.load(Ordering::SeqCst),
+            queued_updates: self.sender.len(),
+            lag_events: self.lag_events.load(Ordering::Relaxed),
+            lagged_updates: self.lagged_updates.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn broadcast(&self, event: RoomEvent) {
+        let _ = self.sender.send(event);
     }
 }
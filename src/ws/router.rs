@@ -1,12 +1,34 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
 use axum::{Router, routing::get};
-use tokio::sync::RwLock;
+use tokio::{
+    sync::{
+        RwLock,
+        broadcast::{self, Receiver, Sender},
+    },
+    task::JoinHandle,
+};
 use uuid::Uuid;
 
 use crate::{
     AppState,
-    ws::{handler::ws_handler, room::Room, types::RoomCanvasUpdate},
+    error::Result,
+    infrastructure::cache::{breaker::or_degraded, keys::CacheKey, redis::RedisCache},
+    ws::{
+        close::CloseReason,
+        fanout::{self, FanoutMessage},
+        handler::ws_handler,
+        room::Room,
+        types::{RoomCanvasUpdate, RoomEvent, WsStats},
+    },
 };
 
 pub fn router() -> Router<AppState> {
@@ -15,22 +37,284 @@ pub fn router() -> Router<AppState> {
 
 pub struct RoomManager {
     rooms: RwLock<HashMap<Uuid, Arc<Room>>>,
+    user_channels: RwLock<HashMap<Uuid, Sender<RoomCanvasUpdate>>>,
     max_connections_per_room: usize,
+    max_spectators_per_room: usize,
+    redis: RedisCache,
+    /// Tells updates this instance relayed apart from other instances'.
+    instance_id: Uuid,
+    /// Replayable updates kept per room. Zero turns numbering and replay off.
+    replay_buffer: usize,
+    replay_ttl: Duration,
+    /// Lag counted by rooms that have closed, so the totals keep counting up.
+    closed_lag_events: AtomicU64,
+    closed_lagged_updates: AtomicU64,
+    user_lagged_updates: AtomicU64,
+    slow_evictions: AtomicU64,
 }
 
 impl RoomManager {
-    pub fn initialize(max_connections: usize) -> Self {
+    pub fn initialize(
+        max_connections: usize,
+        max_spectators: usize,
+        redis: RedisCache,
+        replay_buffer: usize,
+        replay_ttl: Duration,
+    ) -> Self {
         Self {
             rooms: RwLock::new(HashMap::new()),
+            user_channels: RwLock::new(HashMap::new()),
             max_connections_per_room: max_connections,
+            max_spectators_per_room: max_spectators,
+            redis,
+            instance_id: Uuid::new_v4(),
+            replay_buffer,
+            replay_ttl,
+            closed_lag_events: AtomicU64::new(0),
+            closed_lagged_updates: AtomicU64::new(0),
+            user_lagged_updates: AtomicU64::new(0),
+            slow_evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Subscribes to updates addressed to a single user, regardless of room.
+    pub async fn subscribe_user(&self, user_id: Uuid) -> Receiver<RoomCanvasUpdate> {
+        const USER_BUFFER_SIZE: usize = 64;
+
+        let mut channels = self.user_channels.write().await;
+        channels
+            .entry(user_id)
+            .or_insert_with(|| broadcast::channel(USER_BUFFER_SIZE).0)
+            .subscribe()
+    }
+
+    /// Whether any user is connected to this instance.
+    pub async fn has_user_channels(&self) -> bool {
+        !self.user_channels.read().await.is_empty()
+    }
+
+    /// Drops the user's channel once their last connection has gone away.
+    pub async fn remove_user_channel_if_unused(&self, user_id: &Uuid) {
+        let mut channels = self.user_channels.write().await;
+        if let Some(sender) = channels.get(user_id)
+            && sender.receiver_count() == 0
+        {
+            channels.remove(user_id);
         }
     }
 
+    pub async fn notify_user(&self, user_id: &Uuid, update: RoomCanvasUpdate) {
+        let channels = self.user_channels.read().await;
+        if let Some(sender) = channels.get(user_id) {
+            let _ = sender.send(update);
+        }
+    }
+
+    /// Sends the update to the canvas's room on every instance. Replayable updates are
+    /// numbered and buffered even when nobody is connected here, since clients may resume on
+    /// any instance.
     pub async fn broadcast(&self, canvas_id: &Uuid, update: RoomCanvasUpdate) {
+        let room = self.rooms.read().await.get(canvas_id).cloned();
+
+        if !update.is_replayable() {
+            if let Some(room) = room {
+                room.broadcast(RoomEvent::unsequenced(*canvas_id, update.clone()));
+            }
+            self.relay(Some(*canvas_id), None, update).await;
+            return;
+        }
+
+        // Held while relaying too, so other instances see the room's updates in order
+        let _sequence = match &room {
+            Some(room) => Some(room.lock_sequence().await),
+            None => None,
+        };
+        let seq = self.record(canvas_id, &update).await;
+        if let Some(room) = &room {
+            room.broadcast(RoomEvent {
+                canvas_id: *canvas_id,
+                seq,
+                update: update.clone(),
+            });
+        }
+        self.relay(Some(*canvas_id), seq, update).await;
+    }
+
+    /// Sends an unnumbered update to one canvas's room, or to every room, on every instance.
+    /// Returns how many rooms it reached on this instance.
+    pub async fn announce(&self, canvas_id: Option<&Uuid>, update: RoomCanvasUpdate) -> usize {
+        let reached = self.deliver_local(canvas_id, None, &update).await;
+        self.relay(canvas_id.copied(), None, update).await;
+        reached
+    }
+
+    /// Delivers an update relayed by another instance to the rooms open here.
+    pub(super) async fn deliver_relayed(&self, message: FanoutMessage) {
+        self.deliver_local(message.canvas_id.as_ref(), message.seq, &message.update)
+            .await;
+    }
+
+    /// Sends the update to one canvas's room, or to every room, open on this instance.
+    async fn deliver_local(
+        &self,
+        canvas_id: Option<&Uuid>,
+        seq: Option<u64>,
+        update: &RoomCanvasUpdate,
+    ) -> usize {
         let rooms = self.rooms.read().await;
-        if let Some(room) = rooms.get(canvas_id) {
-            room.broadcast(update);
+        let targets: Vec<&Arc<Room>> = match canvas_id {
+            Some(canvas_id) => rooms.get(canvas_id).into_iter().collect(),
+            None => rooms.values().collect(),
+        };
+
+        for room in &targets {
+            room.broadcast(RoomEvent {
+                canvas_id: room.canvas_id(),
+                seq,
+                update: update.clone(),
+            });
         }
+        targets.len()
+    }
+
+    /// Publishes an update this instance delivered, for the other instances' rooms. Failures
+    /// are logged: connections elsewhere then catch up from the replay buffer, or miss it.
+    async fn relay(&self, canvas_id: Option<Uuid>, seq: Option<u64>, update: RoomCanvasUpdate) {
+        let message = FanoutMessage {
+            origin: self.instance_id,
+            canvas_id,
+            seq,
+            update,
+        };
+        let published = match serde_json::to_string(&message) {
+            Ok(payload) => {
+                self.redis
+                    .publish(&CacheKey::room_fanout_channel(), &payload)
+                    .await
+            }
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = or_degraded(published, ()) {
+            tracing::warn!("Failed to relay room update: {e}");
+        }
+    }
+
+    /// Delivers updates other instances relay to the rooms open on this one.
+    pub fn listen_for_fanout(self: &Arc<Self>) -> JoinHandle<()> {
+        fanout::spawn_listener(Arc::clone(self))
+    }
+
+    pub(super) fn redis(&self) -> &RedisCache {
+        &self.redis
+    }
+
+    pub(super) fn instance_id(&self) -> Uuid {
+        self.instance_id
+    }
+
+    /// Buffers the update and returns its `seq`. Without Redis it goes out unnumbered, and
+    /// clients resuming past it are asked to resync.
+    async fn record(&self, canvas_id: &Uuid, update: &RoomCanvasUpdate) -> Option<u64> {
+        if self.replay_buffer == 0 {
+            return None;
+        }
+
+        let entry = match serde_json::to_string(update) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::error!("Failed to serialize room update for canvas {canvas_id}: {e}");
+                return None;
+            }
+        };
+
+        match self
+            .redis
+            .stream_append_sequenced(
+                &CacheKey::room_seq(canvas_id),
+                &CacheKey::room_events(canvas_id),
+                &entry,
+                self.replay_buffer,
+                self.replay_ttl,
+            )
+            .await
+        {
+            Ok(seq) => Some(seq),
+            Err(e) => {
+                tracing::warn!("Failed to buffer room update for canvas {canvas_id}: {e}");
+                None
+            }
+        }
+    }
+
+    /// `seq` of the room's latest replayable update, if updates are numbered.
+    pub async fn latest_seq(&self, canvas_id: &Uuid) -> Result<Option<u64>> {
+        if self.replay_buffer == 0 {
+            return Ok(None);
+        }
+
+        match self.redis.get(&CacheKey::room_seq(canvas_id)).await {
+            Ok(latest) => Ok(Some(latest.unwrap_or(0))),
+            // Updates go out unnumbered until Redis is back
+            Err(e) if e.is_cache_outage() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Buffered updates numbered after `resume_from`, oldest first. `None` when some of them
+    /// are no longer buffered, or the room's numbering restarted since.
+    pub async fn replay_since(
+        &self,
+        canvas_id: &Uuid,
+        resume_from: u64,
+    ) -> Result<Option<Vec<RoomEvent>>> {
+        if self.replay_buffer == 0 {
+            return Ok(None);
+        }
+
+        let (latest, entries) = self
+            .redis
+            .stream_sequenced_entries(
+                &CacheKey::room_seq(canvas_id),
+                &CacheKey::room_events(canvas_id),
+            )
+            .await?;
+        let latest = latest.unwrap_or(0);
+        if resume_from > latest {
+            return Ok(None);
+        }
+
+        let missed: Vec<(u64, String)> = entries
+            .into_iter()
+            .filter(|(seq, _)| *seq > resume_from)
+            .collect();
+        let complete = match missed.first() {
+            Some((seq, _)) => *seq == resume_from + 1,
+            None => resume_from == latest,
+        };
+        if !complete {
+            return Ok(None);
+        }
+
+        let mut events = Vec::with_capacity(missed.len());
+        for (seq, entry) in missed {
+            let update = serde_json::from_str(&entry)?;
+            events.push(RoomEvent {
+                canvas_id: *canvas_id,
+                seq: Some(seq),
+                update,
+            });
+        }
+
+        Ok(Some(events))
+    }
+
+    /// Users connected to the canvas's room on this instance.
+    pub async fn present_users(&self, canvas_id: &Uuid) -> Vec<Uuid> {
+        let rooms = self.rooms.read().await;
+        rooms
+            .get(canvas_id)
+            .map(|room| room.present_users())
+            .unwrap_or_default()
     }
 
     pub async fn get_or_create_room(&self, canvas_id: Uuid) -> Arc<Room> {
@@ -44,16 +328,106 @@ impl RoomManager {
         let mut rooms = self.rooms.write().await;
         rooms
             .entry(canvas_id)
-            .or_insert_with(|| Arc::new(Room::new(canvas_id, self.max_connections_per_room)))
+            .or_insert_with(|| {
+                tracing::debug!("Opened room for canvas {canvas_id}");
+                Arc::new(Room::new(
+                    canvas_id,
+                    self.max_connections_per_room,
+                    self.max_spectators_per_room,
+                ))
+            })
             .clone()
     }
 
     pub async fn remove_room_if_empty(&self, canvas_id: &Uuid) {
         let mut rooms = self.rooms.write().await;
         if let Some(room) = rooms.get(canvas_id)
-            && room.get_connection_count().await == 0
+            && room.is_empty()
         {
+            self.retire(room);
             rooms.remove(canvas_id);
         }
     }
+
+    /// Drops rooms left open with nobody in them, which `remove_room_if_empty` should have
+    /// caught. Returns how many there were.
+    pub async fn remove_empty_rooms(&self) -> usize {
+        let mut rooms = self.rooms.write().await;
+        let before = rooms.len();
+        rooms.retain(|canvas_id, room| {
+            if !room.is_empty() {
+                return true;
+            }
+            tracing::warn!("Removing leaked room for canvas {canvas_id}");
+            self.retire(room);
+            false
+        });
+        before - rooms.len()
+    }
+
+    /// Keeps the lag of a room being removed in the totals, and logs its lifetime.
+    fn retire(&self, room: &Room) {
+        let stats = room.stats();
+        self.closed_lag_events
+            .fetch_add(stats.lag_events, Ordering::Relaxed);
+        self.closed_lagged_updates
+            .fetch_add(stats.lagged_updates, Ordering::Relaxed);
+        tracing::debug!(
+            "Closed room for canvas {} after {}s with {} lag events",
+            stats.canvas_id,
+            room.age().as_secs(),
+            stats.lag_events
+        );
+    }
+
+    /// Canvases with a room open on this instance.
+    pub async fn open_rooms(&self) -> Vec<Uuid> {
+        self.rooms.read().await.keys().copied().collect()
+    }
+
+    /// Tells every connection to the room to close with the reason. The room goes away
+    /// once they have.
+    pub async fn close_room(&self, canvas_id: &Uuid, reason: CloseReason) {
+        let room = self.rooms.read().await.get(canvas_id).cloned();
+        if let Some(room) = room {
+            room.broadcast(RoomEvent::unsequenced(
+                *canvas_id,
+                RoomCanvasUpdate::RoomClosed { reason },
+            ));
+        }
+    }
+
+    pub fn record_user_lag(&self, skipped: u64) {
+        self.user_lagged_updates
+            .fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    pub fn record_slow_eviction(&self) {
+        self.slow_evictions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub async fn stats(&self) -> WsStats {
+        let mut by_room: Vec<_> = self
+            .rooms
+            .read()
+            .await
+            .values()
+            .map(|room| room.stats())
+            .collect();
+        by_room.sort_by_key(|room| Reverse(room.connections));
+
+        WsStats {
+            rooms: by_room.len(),
+            connections: by_room.iter().map(|room| room.connections).sum(),
+            spectators: by_room.iter().map(|room| room.spectators).sum(),
+            user_channels: self.user_channels.read().await.len(),
+            lag_events: self.closed_lag_events.load(Ordering::Relaxed)
+                + by_room.iter().map(|room| room.lag_events).sum::<u64>(),
+            lagged_updates: self.closed_lagged_updates.load(Ordering::Relaxed)
+                + by_room.iter().map(|room| room.lagged_updates).sum::<u64>(),
+            user_lagged_updates: self.user_lagged_updates.load(Ordering::Relaxed),
+            slow_evictions: self.slow_evictions.load(Ordering::Relaxed),
+            by_room,
+        }
+    }
 }
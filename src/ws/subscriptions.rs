@@ -0,0 +1,190 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::{
+    sync::{
+        broadcast::error::RecvError,
+        mpsc::{self, Receiver, Sender},
+    },
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    infrastructure::db::repositories::CanvasRepository,
+    ws::{
+        filter::EventFilter,
+        handler::still_collaborator,
+        room::Room,
+        types::{RoomCanvasUpdate, RoomEvent},
+    },
+};
+
+/// Updates from extra rooms buffered before the connection sends them.
+const SUBSCRIPTION_BUFFER: usize = 256;
+
+struct RoomSubscription {
+    room: Arc<Room>,
+    forwarder: JoinHandle<()>,
+    filter: EventFilter,
+}
+
+/// Rooms a connection listens to besides the canvas it connected for. Their updates are
+/// merged into one channel, and each carries its `canvas_id`. Also holds the filter each
+/// room's updates pass through, including the connection's own.
+pub struct RoomSubscriptions {
+    primary_canvas_id: Uuid,
+    primary_filter: EventFilter,
+    max_subscriptions: usize,
+    rooms: HashMap<Uuid, RoomSubscription>,
+    sender: Sender<RoomEvent>,
+}
+
+impl RoomSubscriptions {
+    pub fn new(primary_canvas_id: Uuid, max_subscriptions: usize) -> (Self, Receiver<RoomEvent>) {
+        let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let subscriptions = Self {
+            primary_canvas_id,
+            primary_filter: EventFilter::default(),
+            max_subscriptions,
+            rooms: HashMap::new(),
+            sender,
+        };
+        (subscriptions, receiver)
+    }
+
+    /// Joins the canvas's room, or says why not.
+    pub async fn subscribe(
+        &mut self,
+        state: &AppState,
+        canvas_id: Uuid,
+        user_id: Uuid,
+        filter: EventFilter,
+    ) -> Result<(), String> {
+        if canvas_id == self.primary_canvas_id || self.rooms.contains_key(&canvas_id) {
+            return Err("Already subscribed".to_string());
+        }
+        if self.rooms.len() >= self.max_subscriptions {
+            return Err(format!(
+                "Cannot subscribe to more than {} other canvases",
+                self.max_subscriptions
+            ));
+        }
+
+        match CanvasRepository::is_canvas_collaborator(
+            state.db.get_connection(),
+            canvas_id,
+            user_id,
+        )
+        .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err("Not a collaborator on this canvas".to_string()),
+            Err(e) => {
+                tracing::warn!("Failed to check collaborator {user_id} on canvas {canvas_id}: {e}");
+                return Err("Could not check access to this canvas".to_string());
+            }
+        }
+
+        let room = state.ws_rooms.get_or_create_room(canvas_id).await;
+        let Some(mut receiver) = room.subscribe() else {
+            state.ws_rooms.remove_room_if_empty(&canvas_id).await;
+            return Err("Room is full".to_string());
+        };
+
+        let sender = self.sender.clone();
+        let lag_room = room.clone();
+        let forwarder = tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if sender.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!("Lagged {n} messages from canvas {canvas_id}");
+                        lag_room.record_lag(n);
+                        // The connection answers with a fresh snapshot of the canvas
+                        let resync =
+                            RoomEvent::unsequenced(canvas_id, RoomCanvasUpdate::ResyncRequired);
+                        if sender.send(resync).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        self.rooms.insert(
+            canvas_id,
+            RoomSubscription {
+                room,
+                forwarder,
+                filter,
+            },
+        );
+        Ok(())
+    }
+
+    /// Replaces the filter on the connection's canvas or one subscribed to.
+    pub fn set_filter(&mut self, canvas_id: Uuid, filter: EventFilter) -> Result<(), String> {
+        if canvas_id == self.primary_canvas_id {
+            self.primary_filter = filter;
+            return Ok(());
+        }
+        match self.rooms.get_mut(&canvas_id) {
+            Some(subscription) => {
+                subscription.filter = filter;
+                Ok(())
+            }
+            None => Err("Not subscribed".to_string()),
+        }
+    }
+
+    /// Whether the connection wants an update from one of its rooms.
+    pub fn allows(&self, canvas_id: Uuid, update: &RoomCanvasUpdate) -> bool {
+        if canvas_id == self.primary_canvas_id {
+            return self.primary_filter.allows(update);
+        }
+        self.rooms
+            .get(&canvas_id)
+            .is_none_or(|subscription| subscription.filter.allows(update))
+    }
+
+    /// `false` if the connection was not subscribed to the canvas.
+    pub async fn unsubscribe(&mut self, state: &AppState, canvas_id: Uuid) -> bool {
+        match self.rooms.remove(&canvas_id) {
+            Some(subscription) => {
+                leave(state, canvas_id, subscription).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drops the rooms of canvases the user no longer collaborates on, and returns them.
+    pub async fn recheck(&mut self, state: &AppState, user_id: Uuid) -> Vec<Uuid> {
+        let mut revoked = Vec::new();
+        for canvas_id in self.rooms.keys().copied().collect::<Vec<_>>() {
+            if !still_collaborator(state, canvas_id, user_id).await {
+                self.unsubscribe(state, canvas_id).await;
+                revoked.push(canvas_id);
+            }
+        }
+        revoked
+    }
+
+    pub async fn close(self, state: &AppState) {
+        for (canvas_id, subscription) in self.rooms {
+            leave(state, canvas_id, subscription).await;
+        }
+    }
+}
+
+async fn leave(state: &AppState, canvas_id: Uuid, subscription: RoomSubscription) {
+    subscription.forwarder.abort();
+    subscription.room.unsubscribe();
+    state.ws_rooms.remove_room_if_empty(&canvas_id).await;
+}
@@ -0,0 +1,51 @@
+use std::{collections::HashMap, time::Duration};
+
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    error::Result,
+    infrastructure::db::{entities::canvas::CanvasState, repositories::CanvasRepository},
+    utils::scheduler::spawn_interval_task,
+    ws::close::CloseReason,
+};
+
+/// Periodically closes rooms whose canvas is gone or burned, in case the change happened
+/// on another instance, and drops rooms leaked with nobody in them.
+pub fn spawn_room_sweeper(state: AppState) -> JoinHandle<()> {
+    let period = Duration::from_secs(state.config.server.ws_room_sweep_secs);
+
+    spawn_interval_task("room_sweep", period, move || {
+        let state = state.clone();
+        async move { sweep_rooms(&state).await }
+    })
+}
+
+pub async fn sweep_rooms(state: &AppState) -> Result<()> {
+    state.ws_rooms.remove_empty_rooms().await;
+
+    let canvas_ids = state.ws_rooms.open_rooms().await;
+    if canvas_ids.is_empty() {
+        return Ok(());
+    }
+
+    let states: HashMap<Uuid, CanvasState> =
+        CanvasRepository::find_canvases_by_ids(state.db.get_connection(), &canvas_ids)
+            .await?
+            .into_iter()
+            .map(|canvas| (canvas.id, canvas.state))
+            .collect();
+
+    for canvas_id in canvas_ids {
+        let reason = match states.get(&canvas_id) {
+            None => CloseReason::CanvasDeleted,
+            Some(CanvasState::Burned) => CloseReason::CanvasArchived,
+            Some(_) => continue,
+        };
+        tracing::info!("Closing room for canvas {canvas_id}: {reason:?}");
+        state.ws_rooms.close_room(&canvas_id, reason).await;
+    }
+
+    Ok(())
+}
@@ -1,24 +1,273 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
+use crate::{
+    services::canvas::types::CanvasSnapshot,
+    ws::{close::CloseReason, filter::EventFilter},
+};
+
+/// Message schema versions the server speaks.
+///
+/// 1. `{ "type", "data" }`, plus `canvas_id` and `seq` on room updates. Used when the client
+///    does not ask for a version.
+/// 2. The envelope `{ "type", "seq", "canvas_id", "payload" }`, with `payload` always present.
+pub const SUPPORTED_PROTOCOL_VERSIONS: [u8; 2] = [1, 2];
+pub const DEFAULT_PROTOCOL_VERSION: u8 = 1;
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(tag = "type", content = "data")]
 pub enum RoomCanvasUpdate {
     Pixel(RoomPixelUpdate),
-    PixelLocked { x: u8, y: u8, user_id: Uuid },
-    PixelUnlocked { x: u8, y: u8 },
+    PixelLocked {
+        x: u8,
+        y: u8,
+        user_id: Uuid,
+    },
+    PixelUnlocked {
+        x: u8,
+        y: u8,
+    },
     PublishingStarted,
-    Published { pda: String },
-    PublishingFailed { reason: String },
+    Published {
+        pda: String,
+    },
+    PublishingFailed {
+        reason: String,
+    },
+    /// Seen on-chain by the event stream, usually before the bidder confirms it.
+    BidConfirmed {
+        x: u8,
+        y: u8,
+        color: u8,
+        bid_lamports: u64,
+        signature: String,
+    },
+    MintQueued {
+        position: u64,
+    },
+    MintSlotReady,
     MintingStarted,
-    Minted { mint_address: String },
-    MintingFailed { reason: String },
-    MintCountdown { seconds: u8 },
+    Minted {
+        mint_address: String,
+    },
+    MintingFailed {
+        reason: String,
+    },
+    Burned {
+        signature: String,
+    },
+    MintCountdown {
+        seconds: u8,
+    },
     MintCountdownCancelled,
-    UserJoined { user_id: Uuid },
-    UserLeft { user_id: Uuid },
-    ConnectionCount { count: usize },
+    UserJoined {
+        user_id: Uuid,
+    },
+    UserLeft {
+        user_id: Uuid,
+    },
+    /// A moderator muted the user until `until`, or lifted their mute when it is absent.
+    UserMuted {
+        user_id: Uuid,
+        until: Option<DateTime<Utc>>,
+    },
+    /// Where a collaborator's pointer is on the canvas. Not sent back to its own connection.
+    Cursor {
+        user_id: Uuid,
+        x: u8,
+        y: u8,
+    },
+    /// Pixels a collaborator is drawing, ahead of the request that places them. Not stored,
+    /// and not sent back to its own connection.
+    StrokePreview {
+        user_id: Uuid,
+        pixels: Vec<PreviewPixel>,
+    },
+    /// Room chat. Not stored, so only connected users ever see it.
+    Chat {
+        user_id: Uuid,
+        username: Option<String>,
+        text: String,
+        sent_at: DateTime<Utc>,
+    },
+    /// Sent only to the connection whose chat message was dropped.
+    ChatRejected {
+        reason: String,
+    },
+    /// Sent to a connection when it joins, unless it resumed without missing too much.
+    Snapshot(Box<CanvasSnapshot>),
+    /// Sent to a connection that found the room full and was let in receive-only.
+    Spectating {
+        max_collaborators: usize,
+    },
+    /// First message on connections that asked for a protocol version.
+    Hello {
+        protocol_version: u8,
+        supported_versions: Vec<u8>,
+    },
+    /// Sent only to a connection whose messages are being dropped for arriving too fast.
+    /// Once `warnings_left` runs out, the next time closes the connection.
+    RateLimited {
+        warnings_left: u32,
+    },
+    /// Answers to `Subscribe` and `Unsubscribe`, sent only to the requesting connection.
+    /// `Unsubscribed` is also sent when a subscribed canvas is deleted or access to it is lost.
+    Subscribed {
+        canvas_id: Uuid,
+    },
+    SubscribeRejected {
+        canvas_id: Uuid,
+        reason: String,
+    },
+    Unsubscribed {
+        canvas_id: Uuid,
+    },
+    /// Answers to `Filter`, sent only to the requesting connection.
+    FilterApplied {
+        canvas_id: Uuid,
+    },
+    FilterRejected {
+        canvas_id: Uuid,
+        reason: String,
+    },
+    /// Answers to `Refresh`, sent only to the requesting connection. Without a successful
+    /// refresh, the connection is closed once its token has been expired for the grace period.
+    AuthRefreshed {
+        expires_at: DateTime<Utc>,
+    },
+    AuthRejected {
+        reason: String,
+    },
+    /// Sent only to a reconnecting connection whose missed updates are no longer buffered.
+    /// A `Snapshot` follows.
+    ResyncRequired,
+    /// Announcement from an admin to one room or all of them, such as planned maintenance.
+    System {
+        level: SystemLevel,
+        message: String,
+        sent_at: DateTime<Utc>,
+    },
+    /// Broadcast when the room is shut down, such as when its canvas is deleted.
+    /// Connections close with the reason instead of forwarding it.
+    RoomClosed {
+        reason: CloseReason,
+    },
+    ConnectionCount {
+        count: usize,
+    },
     Finalized,
+    WatchedPixelChanged {
+        canvas_id: Uuid,
+        x: u8,
+        y: u8,
+        event: PixelWatchEvent,
+        color: u8,
+        owner_id: Option<Uuid>,
+        price_lamports: Option<u64>,
+    },
+}
+
+impl RoomCanvasUpdate {
+    /// Whether the update is numbered and buffered for reconnecting clients. Cursors and
+    /// chat are only for whoever is connected at the time.
+    pub fn is_replayable(&self) -> bool {
+        !matches!(
+            self,
+            Self::Cursor { .. }
+                | Self::StrokePreview { .. }
+                | Self::Chat { .. }
+                | Self::ChatRejected { .. }
+                | Self::RateLimited { .. }
+                | Self::Hello { .. }
+                | Self::Spectating { .. }
+                | Self::Snapshot(_)
+                | Self::Subscribed { .. }
+                | Self::SubscribeRejected { .. }
+                | Self::Unsubscribed { .. }
+                | Self::FilterApplied { .. }
+                | Self::FilterRejected { .. }
+                | Self::AuthRefreshed { .. }
+                | Self::AuthRejected { .. }
+                | Self::ResyncRequired
+                | Self::RoomClosed { .. }
+                | Self::System { .. }
+        )
+    }
+
+    /// The user whose connection relayed the update, for updates not echoed back to it.
+    pub fn relayed_from(&self) -> Option<Uuid> {
+        match self {
+            Self::Cursor { user_id, .. } | Self::StrokePreview { user_id, .. } => Some(*user_id),
+            _ => None,
+        }
+    }
+}
+
+/// A room update as sent to connections. `seq` increases by one with every replayable update
+/// in the room, so clients can reconnect with the last one they saw as `resume_from`.
+#[derive(Serialize, Clone)]
+pub struct RoomEvent {
+    /// Room the update came from, since a connection can subscribe to several.
+    pub canvas_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    #[serde(flatten)]
+    pub update: RoomCanvasUpdate,
+}
+
+impl RoomEvent {
+    pub fn unsequenced(canvas_id: Uuid, update: RoomCanvasUpdate) -> Self {
+        Self {
+            canvas_id,
+            seq: None,
+            update,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomStats {
+    pub canvas_id: Uuid,
+    pub connections: usize,
+    pub spectators: usize,
+    /// Updates still held in the broadcast buffer for the slowest receiver.
+    pub queued_updates: usize,
+    /// Times a connection fell behind the buffer, and the updates it skipped.
+    pub lag_events: u64,
+    pub lagged_updates: u64,
+}
+
+/// WebSocket load on this instance. Lag totals count since startup, including rooms that
+/// have since closed.
+#[derive(Debug, Serialize)]
+pub struct WsStats {
+    pub rooms: usize,
+    pub connections: usize,
+    pub spectators: usize,
+    pub user_channels: usize,
+    pub lag_events: u64,
+    pub lagged_updates: u64,
+    pub user_lagged_updates: u64,
+    /// Connections closed for not keeping up with their updates.
+    pub slow_evictions: u64,
+    pub by_room: Vec<RoomStats>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemLevel {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PixelWatchEvent {
+    Outbid,
+    Repainted,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -30,16 +279,77 @@ pub struct RoomPixelUpdate {
     pub price_lamports: Option<u64>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PreviewPixel {
+    pub x: u8,
+    pub y: u8,
+    pub color: u8,
+}
+
 #[derive(Deserialize)]
 pub struct WsQuery {
     pub canvas_id: Uuid,
     pub token: Option<String>,
+    /// `seq` of the last update seen before a reconnect. Updates after it are replayed.
+    pub resume_from: Option<u64>,
+    /// Message schema the client speaks. See [`SUPPORTED_PROTOCOL_VERSIONS`].
+    pub protocol: Option<u8>,
+}
+
+impl WsQuery {
+    pub fn protocol_version(&self) -> u8 {
+        self.protocol.unwrap_or(DEFAULT_PROTOCOL_VERSION)
+    }
 }
 
 #[derive(Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientMessage {
     Ping,
-    Subscribe { x: u8, y: u8 },
-    Unsubscribe { x: u8, y: u8 },
+    /// Also receive updates from another canvas room on this connection, optionally only
+    /// those passing `filter`.
+    Subscribe {
+        canvas_id: Uuid,
+        #[serde(default)]
+        filter: EventFilter,
+    },
+    Unsubscribe {
+        canvas_id: Uuid,
+    },
+    /// Replaces the filter on a room of this connection, its own canvas when `canvas_id` is
+    /// absent.
+    Filter {
+        canvas_id: Option<Uuid>,
+        #[serde(default)]
+        filter: EventFilter,
+    },
+    Cursor {
+        x: u8,
+        y: u8,
+    },
+    /// Provisional pixels of a stroke in progress, relayed to the room but never placed.
+    StrokePreview {
+        pixels: Vec<PreviewPixel>,
+    },
+    Chat {
+        text: String,
+    },
+    /// Replaces the access token the connection was opened with, before it expires.
+    Refresh {
+        token: String,
+    },
+}
+
+impl ClientMessage {
+    /// Reads both the flat version 1 messages and version 2 envelopes, whose fields are
+    /// under `payload`.
+    pub fn decode(text: &str) -> serde_json::Result<Self> {
+        let mut message: Value = serde_json::from_str(text)?;
+        if let Value::Object(fields) = &mut message
+            && let Some(Value::Object(payload)) = fields.remove("payload")
+        {
+            fields.extend(payload);
+        }
+        serde_json::from_value(message)
+    }
 }
@@ -0,0 +1,473 @@
+//! Runs the migrations and repositories against an in-memory SQLite database.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use pixel_archives::{
+    config::{CanvasConfig, DatabaseConfig},
+    error::AppError,
+    infrastructure::db::{
+        Database,
+        entities::{canvas, outbox_event::OutboxTopic},
+        migrations::Migrator,
+        repositories::{
+            CanvasRepository, OutboxRepository, PixelHistoryRepository, PixelRepository,
+            UserRepository,
+            pixel::PixelWrite,
+            pixel_history::{PixelChangeSource, PixelHistoryRange},
+        },
+    },
+    services::canvas::replay_timelapse,
+};
+use sea_orm_migration::MigratorTrait;
+use uuid::Uuid;
+
+const CANVAS_SIZE: u8 = 32;
+const INITIAL_COLOR: i16 = 10;
+
+async fn memory_database() -> Database {
+    let config = DatabaseConfig {
+        url: "sqlite::memory:".to_string(),
+        max_connections: 1,
+        min_connections: 1,
+        connect_timeout: Duration::from_secs(5),
+        idle_timeout: Duration::from_secs(60),
+        retry_delay: Duration::from_millis(10),
+        health_timeout: Duration::from_secs(1),
+        auto_migrate: true,
+        outbox_poll_interval: Duration::from_secs(1),
+        outbox_batch_size: 100,
+        outbox_lease: Duration::from_secs(30),
+    };
+    let db = Database::init_db(&config).await.expect("connect to SQLite");
+    Migrator::up(db.get_connection().inner(), None)
+        .await
+        .expect("apply migrations");
+    db
+}
+
+fn canvas_config() -> CanvasConfig {
+    CanvasConfig {
+        max_name_length: 64,
+        width: CANVAS_SIZE,
+        height: CANVAS_SIZE,
+        color_count: 32,
+        min_bid_lamports: 1_000,
+        bid_balance_check: false,
+        cooldown_ms: 0,
+        max_collaborators: 10,
+        max_spectators: 10,
+        lock_ms: 30_000,
+        mint_countdown_secs: 10,
+        paint_batch_max: 32,
+        max_pixel_watches: 10,
+        timelapse_max_changes: 1_000,
+        history_retention_days: 0,
+        history_prune_interval_secs: 3_600,
+        leaderboard_refresh_secs: 300,
+        max_royalty_bps: 1_000,
+        max_edition_prints: 10,
+        max_custom_attributes: 10,
+    }
+}
+
+async fn paint(db: &Database, canvas_id: Uuid, x: i16, y: i16, color: i16) {
+    let write = PixelWrite {
+        color: Some(color),
+        ..PixelWrite::default()
+    };
+    PixelRepository::upsert_pixel(db, canvas_id, x, y, write, &PixelChangeSource::default())
+        .await
+        .unwrap();
+}
+
+async fn create_canvas(db: &Database, owner_id: Uuid, name: &str) -> canvas::Model {
+    let db_transaction = db.begin_transaction().await.unwrap();
+    let canvas = CanvasRepository::create_canvas(&db_transaction, owner_id, name, None, None)
+        .await
+        .unwrap();
+    PixelRepository::initialize_canvas_pixels(
+        &db_transaction,
+        canvas.id,
+        CANVAS_SIZE,
+        CANVAS_SIZE,
+        INITIAL_COLOR,
+    )
+    .await
+    .unwrap();
+    db_transaction.commit().await.unwrap();
+    canvas
+}
+
+#[tokio::test]
+async fn migrations_apply() {
+    let db = memory_database().await;
+
+    let pending = Migrator::get_pending_migrations(db.get_connection().inner())
+        .await
+        .unwrap();
+    assert!(pending.is_empty());
+}
+
+#[tokio::test]
+async fn canvas_and_pixel_round_trip() {
+    let db = memory_database().await;
+    let owner = UserRepository::create_user(&db, "OwnerWallet111111111111111111111", None)
+        .await
+        .unwrap();
+
+    let canvas = create_canvas(&db, owner.id, "Round trip").await;
+
+    let found = CanvasRepository::find_canvas_by_id(db.get_connection(), canvas.id)
+        .await
+        .unwrap()
+        .expect("canvas was stored");
+    assert_eq!(found.invite_code, canvas.invite_code);
+    assert_eq!(found.invite_code.len(), 12);
+
+    let untouched = PixelRepository::find_pixel(db.get_connection(), canvas.id, 1, 2)
+        .await
+        .unwrap()
+        .expect("unowned pixels are read from the grid");
+    assert_eq!(untouched.color, INITIAL_COLOR);
+    assert_eq!(untouched.owner_id, None);
+
+    let source = PixelChangeSource {
+        actor_id: Some(owner.id),
+        signature: None,
+    };
+    let write = PixelWrite {
+        color: Some(5),
+        owner_id: Some(owner.id),
+        price_lamports: Some(1_000),
+        expected_version: None,
+    };
+    let written = PixelRepository::upsert_pixel(&db, canvas.id, 1, 2, write, &source)
+        .await
+        .unwrap();
+    assert_eq!(written.color, 5);
+    assert_eq!(written.owner_id, Some(owner.id));
+    assert_eq!(written.price_lamports, 1_000);
+
+    let stale = PixelWrite {
+        color: Some(6),
+        expected_version: Some(written.version - 1),
+        ..PixelWrite::default()
+    };
+    assert!(matches!(
+        PixelRepository::upsert_pixel(&db, canvas.id, 1, 2, stale, &source).await,
+        Err(AppError::PixelVersionConflict)
+    ));
+
+    let stored = PixelRepository::find_pixel(db.get_connection(), canvas.id, 1, 2)
+        .await
+        .unwrap()
+        .expect("owned pixel was stored");
+    assert_eq!(stored.color, 5);
+    assert_eq!(stored.version, written.version);
+
+    let history = PixelHistoryRepository::find_changes(
+        db.get_connection(),
+        canvas.id,
+        PixelHistoryRange::default(),
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(
+        (history[0].x, history[0].y, history[0].new_color),
+        (1, 2, 5)
+    );
+
+    let events = OutboxRepository::claim_due(&db, 10, Duration::from_secs(30))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].topic, OutboxTopic::Pixel);
+}
+
+#[tokio::test]
+async fn owned_pixels_page_by_cursor() {
+    let db = memory_database().await;
+    let owner = UserRepository::create_user(&db, "OwnerWallet111111111111111111111", None)
+        .await
+        .unwrap();
+    let canvas = create_canvas(&db, owner.id, "Owned").await;
+
+    let source = PixelChangeSource {
+        actor_id: Some(owner.id),
+        signature: None,
+    };
+    for x in 0..5 {
+        let write = PixelWrite {
+            color: Some(3),
+            owner_id: Some(owner.id),
+            price_lamports: Some(1_000),
+            expected_version: None,
+        };
+        PixelRepository::upsert_pixel(&db, canvas.id, x, 0, write, &source)
+            .await
+            .unwrap();
+    }
+
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = PixelRepository::find_pixels_by_owner(
+            db.get_connection(),
+            owner.id,
+            cursor.as_deref(),
+            2,
+        )
+        .await
+        .unwrap();
+        assert!(page.items.len() <= 2);
+        seen.extend(page.items.iter().map(|pixel| pixel.x));
+
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    seen.sort_unstable();
+    assert_eq!(seen, vec![0, 1, 2, 3, 4]);
+
+    assert!(matches!(
+        PixelRepository::find_pixels_by_owner(db.get_connection(), owner.id, Some("bogus"), 2)
+            .await,
+        Err(AppError::InvalidParams(_))
+    ));
+}
+
+#[tokio::test]
+async fn batch_paint_writes_in_bulk() {
+    let db = memory_database().await;
+    let owner = UserRepository::create_user(&db, "OwnerWallet111111111111111111111", None)
+        .await
+        .unwrap();
+    let other = UserRepository::create_user(&db, "OtherWallet111111111111111111111", None)
+        .await
+        .unwrap();
+    let canvas = create_canvas(&db, owner.id, "Batch").await;
+
+    let bid = PixelChangeSource {
+        actor_id: Some(owner.id),
+        signature: None,
+    };
+    let mut owned = Vec::new();
+    for (x, owner_id) in [(0, owner.id), (1, owner.id), (2, other.id)] {
+        let write = PixelWrite {
+            color: Some(1),
+            owner_id: Some(owner_id),
+            price_lamports: Some(1_000),
+            expected_version: None,
+        };
+        owned.push(
+            PixelRepository::upsert_pixel(&db, canvas.id, x, 0, write, &bid)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let painted = PixelRepository::update_owned_pixel_colors(
+        &db,
+        canvas.id,
+        owner.id,
+        &[(1, 0, 7), (0, 0, 8)],
+        "PaintSignature",
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        painted
+            .iter()
+            .map(|pixel| (pixel.x, pixel.color, pixel.version, pixel.owner_id))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, 7, owned[1].version + 1, Some(owner.id)),
+            (0, 8, owned[0].version + 1, Some(owner.id)),
+        ]
+    );
+
+    let colors = PixelRepository::find_canvas_colors(
+        db.get_connection(),
+        canvas.id,
+        CANVAS_SIZE,
+        CANVAS_SIZE,
+    )
+    .await
+    .unwrap();
+    assert_eq!(&colors[..3], &[8, 7, 1]);
+
+    let history = PixelHistoryRepository::find_changes(
+        db.get_connection(),
+        canvas.id,
+        PixelHistoryRange::default(),
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(history.len(), 5);
+
+    // Someone else's pixel fails the whole batch
+    assert!(matches!(
+        PixelRepository::update_owned_pixel_colors(
+            &db,
+            canvas.id,
+            owner.id,
+            &[(0, 0, 9), (2, 0, 9)],
+            "OtherSignature",
+        )
+        .await,
+        Err(AppError::Unauthorized)
+    ));
+    let untouched = PixelRepository::find_pixel(db.get_connection(), canvas.id, 0, 0)
+        .await
+        .unwrap()
+        .expect("owned pixel is stored");
+    assert_eq!(
+        (untouched.color, untouched.version),
+        (8, painted[1].version)
+    );
+
+    // Unowned pixels only go to the grid
+    let written = PixelRepository::upsert_pixels_bulk(&db, canvas.id, &[(5, 5, 4)], &bid)
+        .await
+        .unwrap();
+    assert_eq!((written[0].color, written[0].owner_id), (4, None));
+    let unowned = PixelRepository::find_pixel(db.get_connection(), canvas.id, 5, 5)
+        .await
+        .unwrap()
+        .expect("unowned pixels are read from the grid");
+    assert_eq!((unowned.color, unowned.owner_id), (4, None));
+}
+
+#[tokio::test]
+async fn colors_before_take_the_earliest_change() {
+    let db = memory_database().await;
+    let owner = UserRepository::create_user(&db, "OwnerWallet111111111111111111111", None)
+        .await
+        .unwrap();
+    let canvas = create_canvas(&db, owner.id, "History").await;
+
+    paint(&db, canvas.id, 3, 3, 1).await;
+    paint(&db, canvas.id, 3, 3, 2).await;
+    paint(&db, canvas.id, 4, 3, 5).await;
+
+    let colors = PixelHistoryRepository::find_colors_before(
+        db.get_connection(),
+        canvas.id,
+        PixelHistoryRange::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        colors
+            .iter()
+            .map(|row| (row.x, row.y, row.old_color))
+            .collect::<Vec<_>>(),
+        vec![(3, 3, Some(INITIAL_COLOR)), (4, 3, Some(INITIAL_COLOR))]
+    );
+}
+
+#[tokio::test]
+async fn timelapse_rewinds_to_the_cutoff() {
+    let db = memory_database().await;
+    let owner = UserRepository::create_user(&db, "OwnerWallet111111111111111111111", None)
+        .await
+        .unwrap();
+    let canvas = create_canvas(&db, owner.id, "Timelapse").await;
+    let index = 3 * CANVAS_SIZE as usize + 3;
+
+    paint(&db, canvas.id, 3, 3, 1).await;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let cutoff = Utc::now();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    paint(&db, canvas.id, 3, 3, 2).await;
+    paint(&db, canvas.id, 3, 3, 4).await;
+
+    let range = PixelHistoryRange {
+        from: Some(cutoff),
+        ..PixelHistoryRange::default()
+    };
+    let replay = replay_timelapse(
+        db.get_connection(),
+        &canvas_config(),
+        canvas.id,
+        range,
+        3_600,
+        10,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(replay.base_colors[index], 1);
+    assert_eq!(replay.total_changes, 2);
+    let last = replay
+        .frames
+        .iter()
+        .flat_map(|frame| &frame.changes)
+        .rev()
+        .find(|change| (change.x, change.y) == (3, 3))
+        .expect("the cutoff's changes are replayed");
+    assert_eq!(last.color, 4);
+
+    let full = replay_timelapse(
+        db.get_connection(),
+        &canvas_config(),
+        canvas.id,
+        PixelHistoryRange::default(),
+        3_600,
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(full.base_colors[index], INITIAL_COLOR as u8);
+}
+
+#[tokio::test]
+async fn timelapse_counts_changes_past_the_page() {
+    let db = memory_database().await;
+    let owner = UserRepository::create_user(&db, "OwnerWallet111111111111111111111", None)
+        .await
+        .unwrap();
+    let canvas = create_canvas(&db, owner.id, "Paged").await;
+    for x in 0..3 {
+        paint(&db, canvas.id, x, 0, 1).await;
+    }
+
+    let config = CanvasConfig {
+        timelapse_max_changes: 2,
+        ..canvas_config()
+    };
+    let replay = replay_timelapse(
+        db.get_connection(),
+        &config,
+        canvas.id,
+        PixelHistoryRange::default(),
+        3_600,
+        10,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(replay.total_changes, 3);
+    let next_page = PixelHistoryRange {
+        after_id: replay.next_cursor,
+        ..PixelHistoryRange::default()
+    };
+    let rest = replay_timelapse(
+        db.get_connection(),
+        &config,
+        canvas.id,
+        next_page,
+        3_600,
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(rest.total_changes, 3);
+    assert!(rest.next_cursor.is_none());
+}